@@ -7,20 +7,27 @@ use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::io::{self, Write};
 use std::path::Path;
+use std::process::Command;
 
 use inkwell::context::Context;
-use inkwell::values::{BasicValue, FunctionValue, StructValue};
+use inkwell::memory_buffer::MemoryBuffer;
+use inkwell::passes::PassBuilderOptions;
+use inkwell::targets::{
+    CodeModel, FileType, InitializationConfig, RelocMode, Target, TargetMachine,
+};
+use inkwell::values::{BasicValue, FunctionValue, IntValue, PointerValue, StructValue};
+use inkwell::{IntPredicate, OptimizationLevel};
 
 use cons::codegen::Codegen;
 use cons::jit::JitError;
 use cons::jit::analysis::find_free_variables;
-use cons::runtime::{TAG_BOOL, TAG_NIL};
+use cons::runner::MAIN;
+use cons::runtime::TAG_INT;
+use cons::{Environment, eval, expand_all_macros, register_stdlib};
 
 use consair::interner::InternedSymbol;
 use consair::language::{AtomType, StringType, SymbolType, Value};
-use consair::lexer::Lexer;
 use consair::numeric::NumericType;
-use consair::parser::Parser;
 
 use super::runtime_ir::generate_runtime_ir;
 
@@ -47,6 +54,10 @@ pub enum AotError {
     IoError(io::Error),
     /// JIT compilation error (for reusing compile_value)
     JitError(JitError),
+    /// The native target could not be set up or could not emit code
+    TargetError(String),
+    /// The system linker failed to produce an executable
+    LinkError(String),
 }
 
 impl std::fmt::Display for AotError {
@@ -56,6 +67,8 @@ impl std::fmt::Display for AotError {
             AotError::CodegenError(msg) => write!(f, "Codegen error: {}", msg),
             AotError::IoError(err) => write!(f, "IO error: {}", err),
             AotError::JitError(err) => write!(f, "JIT error: {:?}", err),
+            AotError::TargetError(msg) => write!(f, "Target error: {}", msg),
+            AotError::LinkError(msg) => write!(f, "Link error: {}", msg),
         }
     }
 }
@@ -81,6 +94,11 @@ impl From<JitError> for AotError {
 pub struct AotCompiler {
     /// Whether to include debug comments in the output
     pub debug: bool,
+    /// Whether the program exits with its final value, truncated to 32
+    /// bits, when that value is an integer. Otherwise it exits with 0.
+    pub exit_code: bool,
+    /// Optimization level for object files and executables
+    pub opt_level: OptimizationLevel,
 }
 
 impl Default for AotCompiler {
@@ -92,7 +110,11 @@ impl Default for AotCompiler {
 impl AotCompiler {
     /// Create a new AOT compiler.
     pub fn new() -> Self {
-        AotCompiler { debug: false }
+        AotCompiler {
+            debug: false,
+            exit_code: false,
+            opt_level: OptimizationLevel::Default,
+        }
     }
 
     /// Compile a Lisp source file to LLVM IR.
@@ -114,10 +136,92 @@ impl AotCompiler {
         Ok(())
     }
 
+    /// Compile a Lisp source file to a native object file for the host.
+    pub fn compile_to_object(&self, input: &Path, output: &Path) -> Result<(), AotError> {
+        let source = fs::read_to_string(input)?;
+        let ir = self.compile_source(&source)?;
+
+        let context = Context::create();
+        let buffer = MemoryBuffer::create_from_memory_range_copy(ir.as_bytes(), "consair_aot");
+        let module = context
+            .create_module_from_ir(buffer)
+            .map_err(|e| AotError::CodegenError(e.to_string()))?;
+
+        let machine = self.host_target_machine()?;
+        module.set_triple(&machine.get_triple());
+        module.set_data_layout(&machine.get_target_data().get_data_layout());
+
+        let passes = match self.opt_level {
+            OptimizationLevel::None => "default<O0>",
+            OptimizationLevel::Less => "default<O1>",
+            OptimizationLevel::Default => "default<O2>",
+            OptimizationLevel::Aggressive => "default<O3>",
+        };
+        module
+            .run_passes(passes, &machine, PassBuilderOptions::create())
+            .map_err(|e| AotError::TargetError(e.to_string()))?;
+
+        machine
+            .write_to_file(&module, FileType::Object, output)
+            .map_err(|e| AotError::TargetError(e.to_string()))
+    }
+
+    /// Compile a Lisp source file to a native executable.
+    ///
+    /// The runtime is part of the generated code, so the object file is
+    /// linked against libc alone, with `$CC` or else `cc`.
+    pub fn compile_to_executable(&self, input: &Path, output: &Path) -> Result<(), AotError> {
+        let object = output.with_extension("o");
+        self.compile_to_object(input, &object)?;
+
+        let linker = std::env::var("CC").unwrap_or_else(|_| "cc".to_string());
+        let result = Command::new(&linker)
+            .arg(&object)
+            .arg("-o")
+            .arg(output)
+            .output();
+        fs::remove_file(&object)?;
+
+        let result =
+            result.map_err(|e| AotError::LinkError(format!("cannot run {linker}: {e}")))?;
+        if !result.status.success() {
+            return Err(AotError::LinkError(format!(
+                "{linker} failed: {}",
+                String::from_utf8_lossy(&result.stderr).trim()
+            )));
+        }
+        Ok(())
+    }
+
+    /// A target machine for the host, at this compiler's optimization level.
+    fn host_target_machine(&self) -> Result<TargetMachine, AotError> {
+        Target::initialize_native(&InitializationConfig::default())
+            .map_err(AotError::TargetError)?;
+
+        let triple = TargetMachine::get_default_triple();
+        let target =
+            Target::from_triple(&triple).map_err(|e| AotError::TargetError(e.to_string()))?;
+        target
+            .create_target_machine(
+                &triple,
+                &TargetMachine::get_host_cpu_name().to_string(),
+                &TargetMachine::get_host_cpu_features().to_string(),
+                self.opt_level,
+                RelocMode::PIC,
+                CodeModel::Default,
+            )
+            .ok_or_else(|| {
+                AotError::TargetError(format!(
+                    "cannot create a target machine for {}",
+                    triple.as_str().to_string_lossy()
+                ))
+            })
+    }
+
     /// Compile source code to LLVM IR.
     pub fn compile_source(&self, source: &str) -> Result<String, AotError> {
-        // Parse all expressions from the source
-        let exprs = self.parse_all(source)?;
+        // Parse all expressions from the source and expand their macros
+        let exprs = self.expand_macros(self.parse_all(source)?)?;
 
         // Generate IR for each expression
         let context = Context::create();
@@ -154,16 +258,31 @@ impl AotCompiler {
             self.compile_toplevel_label(&codegen, *name, lambda_expr, &compiled_fns)?;
         }
 
-        // Third pass: compile all expressions with shared compiled_fns
+        // Third pass: compile the remaining expressions, in order, with shared compiled_fns
         let mut expr_fns = Vec::new();
         for (i, expr) in exprs.iter().enumerate() {
+            if extract_toplevel_label(expr).is_some() {
+                continue;
+            }
             let fn_name = format!("__consair_expr_{}", i);
             let func = self.compile_expr_to_function(&codegen, &fn_name, expr, &compiled_fns)?;
             expr_fns.push(func);
         }
 
-        // Generate main function that calls all expressions and prints the last result
-        self.generate_main(&codegen, &expr_fns)?;
+        // A top-level `main` is the program's entry point, as in `cons`
+        let lisp_main = match compiled_fns.get(&InternedSymbol::new(MAIN)) {
+            Some(main) if main.count_params() == 1 => Some(*main),
+            Some(_) => {
+                return Err(AotError::CodegenError(format!(
+                    "{MAIN} must take one argument, the list of command-line arguments"
+                )));
+            }
+            None => None,
+        };
+
+        // Generate main function that calls all expressions and then the
+        // Lisp main, or prints the last result if there is none
+        self.generate_main(&codegen, &expr_fns, lisp_main)?;
 
         // Get the generated IR (without runtime definitions - they're external)
         let user_ir = codegen.emit_ir();
@@ -209,6 +328,28 @@ impl AotCompiler {
         Ok(combined_ir)
     }
 
+    /// Expand macros in `exprs` using a scratch interpreter environment that
+    /// holds the standard library's macros and those the source defines.
+    ///
+    /// `defmacro` forms are evaluated there and left out of the result, so
+    /// each macro is available to the forms after it.
+    fn expand_macros(&self, exprs: Vec<Value>) -> Result<Vec<Value>, AotError> {
+        let mut env = Environment::new();
+        register_stdlib(&mut env);
+
+        let mut expanded = Vec::new();
+        for expr in exprs {
+            if is_defmacro(&expr) {
+                eval(expr, &mut env).map_err(|e| AotError::CodegenError(e.to_string()))?;
+            } else {
+                let expr = expand_all_macros(expr, &mut env, 0)
+                    .map_err(|e| AotError::CodegenError(e.to_string()))?;
+                expanded.push(expr);
+            }
+        }
+        Ok(expanded)
+    }
+
     /// Compile a single expression to a function.
     fn compile_expr_to_function<'ctx>(
         &self,
@@ -438,6 +579,8 @@ impl AotCompiler {
             let name = sym.resolve();
             match name.as_str() {
                 "quote" => return self.compile_quote(codegen, cdr),
+                // Sub-forms are never compiled, so they may name anything
+                "comment" => return Ok(codegen.compile_nil()),
                 "label" => {
                     // Check if this is a top-level label that was pre-compiled
                     // If so, the function already exists in compiled_fns; just return nil
@@ -462,6 +605,16 @@ impl AotCompiler {
                         tail_position,
                     );
                 }
+                "do" => {
+                    return self.compile_do(
+                        codegen,
+                        cdr,
+                        env,
+                        lambdas,
+                        compiled_fns,
+                        tail_position,
+                    );
+                }
                 "cond" => {
                     return self.compile_cond(
                         codegen,
@@ -850,6 +1003,28 @@ impl AotCompiler {
         }
     }
 
+    /// Compile a do form, which evaluates its forms in order and returns
+    /// the last. Macros such as `when` expand to it.
+    fn compile_do<'ctx>(
+        &self,
+        codegen: &Codegen<'ctx>,
+        args: &Value,
+        env: &AotEnv<'ctx>,
+        lambdas: &LambdaStore,
+        compiled_fns: &CompiledFns<'ctx>,
+        tail_position: bool,
+    ) -> Result<StructValue<'ctx>, AotError> {
+        let forms = self.collect_args(args)?;
+        let Some((last, rest)) = forms.split_last() else {
+            return Ok(codegen.compile_nil());
+        };
+
+        for form in rest {
+            self.compile_value(codegen, form, env, lambdas, compiled_fns, false)?;
+        }
+        self.compile_value(codegen, last, env, lambdas, compiled_fns, tail_position)
+    }
+
     /// Compile an if form.
     #[allow(clippy::too_many_arguments)]
     fn compile_if<'ctx>(
@@ -868,58 +1043,9 @@ impl AotCompiler {
         // Compile condition (not in tail position)
         let cond_val = self.compile_value(codegen, cond_expr, env, lambdas, compiled_fns, false)?;
 
-        // Extract tag and data to check truthiness
-        let tag = codegen
-            .builder
-            .build_extract_value(cond_val, 0, "tag")
-            .unwrap()
-            .into_int_value();
-        let data = codegen
-            .builder
-            .build_extract_value(cond_val, 1, "data")
-            .unwrap()
-            .into_int_value();
-
-        // Check if nil (tag == 0)
-        let is_nil = codegen
-            .builder
-            .build_int_compare(
-                inkwell::IntPredicate::EQ,
-                tag,
-                codegen.i8_type().const_int(TAG_NIL as u64, false),
-                "is_nil",
-            )
-            .unwrap();
-
-        // Check if false (tag == 1 && data == 0)
-        let is_bool = codegen
-            .builder
-            .build_int_compare(
-                inkwell::IntPredicate::EQ,
-                tag,
-                codegen.i8_type().const_int(TAG_BOOL as u64, false),
-                "is_bool",
-            )
-            .unwrap();
-        let is_false_data = codegen
-            .builder
-            .build_int_compare(
-                inkwell::IntPredicate::EQ,
-                data,
-                codegen.i64_type().const_int(0, false),
-                "is_false_data",
-            )
-            .unwrap();
-        let is_false = codegen
-            .builder
-            .build_and(is_bool, is_false_data, "is_false")
-            .unwrap();
-
-        // Falsy = nil or false
         let is_falsy = codegen
-            .builder
-            .build_or(is_nil, is_false, "is_falsy")
-            .unwrap();
+            .build_is_falsy(cond_val)
+            .map_err(AotError::CodegenError)?;
 
         // Get current function
         let func = codegen
@@ -1066,61 +1192,9 @@ impl AotCompiler {
             let test_val =
                 self.compile_value(codegen, test_expr, env, lambdas, compiled_fns, false)?;
 
-            // Check if test is truthy (not nil and not false)
-            let tag = codegen
-                .builder
-                .build_extract_value(test_val, 0, "tag")
-                .unwrap()
-                .into_int_value();
-
-            let data = codegen
-                .builder
-                .build_extract_value(test_val, 1, "data")
-                .unwrap()
-                .into_int_value();
-
-            // Check if tag == TAG_NIL (0)
-            let is_nil = codegen
-                .builder
-                .build_int_compare(
-                    inkwell::IntPredicate::EQ,
-                    tag,
-                    codegen.i8_type().const_int(TAG_NIL as u64, false),
-                    "is_nil",
-                )
-                .unwrap();
-
-            // Check if tag == TAG_BOOL (1) and data == 0 (false)
-            let is_bool = codegen
-                .builder
-                .build_int_compare(
-                    inkwell::IntPredicate::EQ,
-                    tag,
-                    codegen.i8_type().const_int(TAG_BOOL as u64, false),
-                    "is_bool",
-                )
-                .unwrap();
-
-            let is_false_data = codegen
-                .builder
-                .build_int_compare(
-                    inkwell::IntPredicate::EQ,
-                    data,
-                    codegen.i64_type().const_int(0, false),
-                    "is_false_data",
-                )
-                .unwrap();
-
-            let is_false = codegen
-                .builder
-                .build_and(is_bool, is_false_data, "is_false")
-                .unwrap();
-
-            // Falsy if nil OR (bool AND data==0)
             let is_falsy = codegen
-                .builder
-                .build_or(is_nil, is_false, "is_falsy")
-                .unwrap();
+                .build_is_falsy(test_val)
+                .map_err(AotError::CodegenError)?;
 
             // Create blocks for then and else
             let then_block = codegen
@@ -2187,14 +2261,20 @@ impl AotCompiler {
     }
 
     /// Generate the main function.
+    ///
+    /// With a Lisp `main`, it is called after the top-level expressions with
+    /// the program's arguments (without the program name) as a list of
+    /// strings, and nothing is printed, matching `cons <file> args...`.
+    /// Otherwise the last expression's value is printed.
     fn generate_main<'ctx>(
         &self,
         codegen: &Codegen<'ctx>,
         expr_fns: &[FunctionValue<'ctx>],
+        lisp_main: Option<FunctionValue<'ctx>>,
     ) -> Result<(), AotError> {
-        // Create main: () -> i32
+        // Create main: (i32 argc, ptr argv) -> i32
         let i32_type = codegen.i32_type();
-        let main_type = i32_type.fn_type(&[], false);
+        let main_type = i32_type.fn_type(&[i32_type.into(), codegen.ptr_type().into()], false);
         let main_fn = codegen.add_function("main", main_type);
 
         let entry = codegen.context.append_basic_block(main_fn, "entry");
@@ -2212,26 +2292,41 @@ impl AotCompiler {
             last_result = result;
         }
 
-        // Print the last result if we have one
+        // The value the exit status comes from
+        let mut status_value = last_result;
+
+        if let Some(lisp_main) = lisp_main {
+            let argc = main_fn.get_nth_param(0).unwrap().into_int_value();
+            let argv = main_fn.get_nth_param(1).unwrap().into_pointer_value();
+            let args = self.build_argv_list(codegen, main_fn, argc, argv)?;
+            status_value = codegen
+                .builder
+                .build_call(lisp_main, &[args.into()], "main_result")
+                .unwrap()
+                .try_as_basic_value()
+                .left();
+            last_result = None;
+        }
+
+        // Print the last result if we have one, as the interpreter displays it
         if let Some(result) = last_result {
-            // Call print_value
-            let print_value = codegen
+            let rt_display = codegen
                 .module
-                .get_function("print_value")
+                .get_function("rt_display")
                 .unwrap_or_else(|| {
-                    // Declare print_value if not already declared
+                    // Declare rt_display if not already declared
                     let void_type = codegen.context.void_type();
-                    let print_type = void_type.fn_type(&[codegen.value_type.into()], false);
+                    let display_type = void_type.fn_type(&[codegen.value_type.into()], false);
                     codegen.module.add_function(
-                        "print_value",
-                        print_type,
+                        "rt_display",
+                        display_type,
                         Some(inkwell::module::Linkage::External),
                     )
                 });
 
             codegen
                 .builder
-                .build_call(print_value, &[result.into()], "")
+                .build_call(rt_display, &[result.into()], "")
                 .unwrap();
 
             // Print newline
@@ -2257,13 +2352,157 @@ impl AotCompiler {
                 .unwrap();
         }
 
-        // Return 0
+        let status = match status_value {
+            Some(value) if self.exit_code => {
+                self.build_exit_status(codegen, value.into_struct_value())
+            }
+            _ => i32_type.const_int(0, false),
+        };
+        codegen.builder.build_return(Some(&status)).unwrap();
+
+        Ok(())
+    }
+
+    /// The exit status for `value`: an integer truncated to 32 bits, or 0
+    /// for anything else.
+    fn build_exit_status<'ctx>(
+        &self,
+        codegen: &Codegen<'ctx>,
+        value: StructValue<'ctx>,
+    ) -> IntValue<'ctx> {
+        let i32_type = codegen.i32_type();
+        let tag = codegen
+            .builder
+            .build_extract_value(value, 0, "status_tag")
+            .unwrap()
+            .into_int_value();
+        let data = codegen
+            .builder
+            .build_extract_value(value, 1, "status_data")
+            .unwrap()
+            .into_int_value();
+        let is_int = codegen
+            .builder
+            .build_int_compare(
+                IntPredicate::EQ,
+                tag,
+                codegen.i8_type().const_int(TAG_INT as u64, false),
+                "status_is_int",
+            )
+            .unwrap();
+        let truncated = codegen
+            .builder
+            .build_int_truncate(data, i32_type, "status_int")
+            .unwrap();
         codegen
             .builder
-            .build_return(Some(&i32_type.const_int(0, false)))
+            .build_select(is_int, truncated, i32_type.const_zero(), "exit_status")
+            .unwrap()
+            .into_int_value()
+    }
+
+    /// Build a list of strings from `argv[1..argc]`, back to front.
+    fn build_argv_list<'ctx>(
+        &self,
+        codegen: &Codegen<'ctx>,
+        function: FunctionValue<'ctx>,
+        argc: IntValue<'ctx>,
+        argv: PointerValue<'ctx>,
+    ) -> Result<StructValue<'ctx>, AotError> {
+        let i32_type = codegen.i32_type();
+        let ptr_type = codegen.ptr_type();
+        let one = i32_type.const_int(1, false);
+
+        let strlen = codegen.module.get_function("strlen").unwrap_or_else(|| {
+            let strlen_type = codegen.i64_type().fn_type(&[ptr_type.into()], false);
+            codegen.module.add_function(
+                "strlen",
+                strlen_type,
+                Some(inkwell::module::Linkage::External),
+            )
+        });
+
+        let entry_bb = codegen.builder.get_insert_block().unwrap();
+        let loop_bb = codegen.context.append_basic_block(function, "argv_loop");
+        let body_bb = codegen.context.append_basic_block(function, "argv_body");
+        let done_bb = codegen.context.append_basic_block(function, "argv_done");
+
+        let nil = codegen.compile_nil();
+        let last_index = codegen
+            .builder
+            .build_int_sub(argc, one, "last_arg")
             .unwrap();
+        codegen.builder.build_unconditional_branch(loop_bb).unwrap();
 
-        Ok(())
+        // Loop while index >= 1, consing argv[index] onto the list
+        codegen.builder.position_at_end(loop_bb);
+        let index = codegen.builder.build_phi(i32_type, "index").unwrap();
+        let list = codegen
+            .builder
+            .build_phi(codegen.value_type, "args")
+            .unwrap();
+        let index_val = index.as_basic_value().into_int_value();
+        let more = codegen
+            .builder
+            .build_int_compare(IntPredicate::SGE, index_val, one, "more_args")
+            .unwrap();
+        codegen
+            .builder
+            .build_conditional_branch(more, body_bb, done_bb)
+            .unwrap();
+
+        codegen.builder.position_at_end(body_bb);
+        let slot = unsafe {
+            codegen
+                .builder
+                .build_gep(ptr_type, argv, &[index_val], "arg_slot")
+        }
+        .map_err(|e| AotError::CodegenError(e.to_string()))?;
+        let arg = codegen
+            .builder
+            .build_load(ptr_type, slot, "arg")
+            .map_err(|e| AotError::CodegenError(e.to_string()))?
+            .into_pointer_value();
+        let len = codegen
+            .builder
+            .build_call(strlen, &[arg.into()], "arg_len")
+            .unwrap()
+            .try_as_basic_value()
+            .left()
+            .ok_or_else(|| AotError::CodegenError("strlen didn't return value".into()))?;
+        let string = codegen
+            .builder
+            .build_call(codegen.rt_make_string, &[arg.into(), len.into()], "arg_str")
+            .unwrap()
+            .try_as_basic_value()
+            .left()
+            .ok_or_else(|| AotError::CodegenError("rt_make_string didn't return value".into()))?;
+        let cell = codegen
+            .builder
+            .build_call(
+                codegen.rt_cons,
+                &[string.into(), list.as_basic_value().into()],
+                "args_cons",
+            )
+            .unwrap()
+            .try_as_basic_value()
+            .left()
+            .ok_or_else(|| AotError::CodegenError("rt_cons didn't return value".into()))?
+            .into_struct_value();
+        let next_index = codegen
+            .builder
+            .build_int_sub(index_val, one, "next_index")
+            .unwrap();
+        codegen.builder.build_unconditional_branch(loop_bb).unwrap();
+
+        index.add_incoming(&[(&last_index, entry_bb), (&next_index, body_bb)]);
+        list.add_incoming(&[
+            (&nil.as_basic_value_enum(), entry_bb),
+            (&cell.as_basic_value_enum(), body_bb),
+        ]);
+
+        codegen.builder.position_at_end(done_bb);
+        Ok(list.as_basic_value().into_struct_value())
     }
 
     // Helper functions
@@ -2309,23 +2548,7 @@ impl AotCompiler {
 
     /// Parse all expressions from source code.
     fn parse_all(&self, source: &str) -> Result<Vec<Value>, AotError> {
-        let mut lexer = Lexer::new(source);
-        let mut parser = Parser::new(&mut lexer);
-        let mut exprs = Vec::new();
-
-        loop {
-            // Try to parse an expression
-            match parser.parse_expression() {
-                Ok(expr) => exprs.push(expr),
-                Err(e) => {
-                    // Check if we're at end of input
-                    if e.contains("Unexpected end of input") || e.contains("end of input") {
-                        break;
-                    }
-                    return Err(AotError::ParseError(e));
-                }
-            }
-        }
+        let exprs = consair::parse_all(source).map_err(|e| AotError::ParseError(e.to_string()))?;
 
         if exprs.is_empty() {
             return Err(AotError::ParseError("No expressions to compile".into()));
@@ -2353,6 +2576,17 @@ fn is_label(value: &Value) -> bool {
     )
 }
 
+/// Check if an expression is a macro definition: (defmacro name ...)
+fn is_defmacro(expr: &Value) -> bool {
+    matches!(
+        expr,
+        Value::Cons(cell) if matches!(
+            &cell.car,
+            Value::Atom(AtomType::Symbol(SymbolType::Symbol(sym))) if sym.resolve() == "defmacro"
+        )
+    )
+}
+
 /// Check if an expression is a top-level label definition: (label name (lambda ...))
 /// Returns Some((name, lambda_expr)) if it is, None otherwise.
 fn extract_toplevel_label(expr: &Value) -> Option<(InternedSymbol, Value)> {
@@ -2484,6 +2718,32 @@ mod tests {
         assert!(ir.contains("@rt_div"));
     }
 
+    /// Run `(/ 1 0)` with `lli`: like the interpreter, it is an error rather
+    /// than infinity. Skipped when `lli` is not installed.
+    #[test]
+    fn test_integer_division_by_zero_fails() {
+        if Command::new("lli").arg("--version").output().is_err() {
+            eprintln!("lli not found, skipping");
+            return;
+        }
+
+        for (source, fails) in [("(/ 1 0)", true), ("(/ 7 2)", false), ("(/ 1.0 0)", false)] {
+            let ir = AotCompiler::new().compile_source(source).unwrap();
+            let path = std::env::temp_dir().join(format!("cadr_div_{}.ll", std::process::id()));
+            fs::write(&path, ir).unwrap();
+            let output = Command::new("lli").arg(&path).output().unwrap();
+            fs::remove_file(&path).unwrap();
+
+            assert_eq!(output.status.success(), !fails, "{source}: {output:?}");
+            if fails {
+                assert_eq!(
+                    String::from_utf8(output.stderr).unwrap(),
+                    "Error: Division by zero\n"
+                );
+            }
+        }
+    }
+
     #[test]
     fn test_compile_comparison() {
         let compiler = AotCompiler::new();
@@ -2521,6 +2781,17 @@ mod tests {
         assert!(ir.contains("i64 42"));
     }
 
+    #[test]
+    fn test_compile_comment() {
+        let compiler = AotCompiler::new();
+        let ir = compiler
+            .compile_source("(do (comment (undefined-fn 1 2) nowhere) 42)")
+            .unwrap();
+
+        assert!(ir.contains("i64 42"));
+        assert!(!ir.contains("undefined-fn"));
+    }
+
     #[test]
     fn test_compile_quoted_list() {
         let compiler = AotCompiler::new();
@@ -2712,4 +2983,149 @@ mod tests {
 
         assert!(ir.contains("@rt_vector_length"));
     }
+
+    #[test]
+    fn test_compile_main_receives_argv() {
+        let compiler = AotCompiler::new();
+        let ir = compiler
+            .compile_source("(label main (lambda (args) (length args)))")
+            .unwrap();
+
+        assert!(ir.contains("define i32 @main(i32"));
+        assert!(ir.contains("@strlen"));
+        assert!(ir.contains("argv_loop"));
+        assert!(ir.contains("%main_result = call"));
+    }
+
+    #[test]
+    fn test_compile_displays_final_value() {
+        let compiler = AotCompiler::new();
+        let ir = compiler.compile_source("(list 1 2.5)").unwrap();
+
+        assert!(ir.contains("define void @rt_display"));
+        assert!(ir.contains("call void @rt_display"));
+        assert!(!ir.contains("exit_status"));
+    }
+
+    #[test]
+    fn test_compile_exit_code() {
+        let mut compiler = AotCompiler::new();
+        compiler.exit_code = true;
+        let ir = compiler.compile_source("(+ 40 2)").unwrap();
+
+        assert!(ir.contains("%status_is_int = icmp eq i8"));
+        assert!(ir.contains("ret i32 %exit_status"));
+    }
+
+    #[test]
+    fn test_compile_to_object() {
+        let dir = std::env::temp_dir();
+        let input = dir.join(format!("cadr_object_{}.lisp", std::process::id()));
+        let output = input.with_extension("o");
+        fs::write(&input, "(label sq (lambda (x) (* x x)))\n(sq 7)").unwrap();
+
+        let mut compiler = AotCompiler::new();
+        compiler.opt_level = OptimizationLevel::Aggressive;
+        let result = compiler.compile_to_object(&input, &output);
+        let size = fs::metadata(&output).map(|m| m.len());
+        fs::remove_file(&input).unwrap();
+        let _ = fs::remove_file(&output);
+
+        result.unwrap();
+        assert!(size.unwrap() > 0);
+    }
+
+    #[test]
+    fn test_emit_error_is_reported() {
+        let dir = std::env::temp_dir();
+        let input = dir.join(format!("cadr_emit_{}.lisp", std::process::id()));
+        fs::write(&input, "42").unwrap();
+        let err = AotCompiler::new()
+            .compile_to_executable(&input, &dir.join("no-such-dir/program"))
+            .unwrap_err();
+        fs::remove_file(&input).unwrap();
+
+        assert!(
+            matches!(err, AotError::TargetError(_) | AotError::LinkError(_)),
+            "{err}"
+        );
+    }
+
+    /// Run compiled IR with `lli` and compare its output with the
+    /// interpreter's. Skipped when `lli` is not installed.
+    #[test]
+    fn test_compiled_output_matches_interpreter() {
+        if Command::new("lli").arg("--version").output().is_err() {
+            eprintln!("lli not found, skipping");
+            return;
+        }
+
+        let source = "(label fact (lambda (n) (cond ((= n 0) 1) (t (* n (fact (- n 1)))))))\n\
+                      (list (fact 10) 2.5 0.1 (vector 1 \"a\\\"b\") (= 1 1) (cons 1 2))";
+        let ir = AotCompiler::new().compile_source(source).unwrap();
+        let path = std::env::temp_dir().join(format!("cadr_display_{}.ll", std::process::id()));
+        fs::write(&path, ir).unwrap();
+        let output = Command::new("lli").arg(&path).output().unwrap();
+        fs::remove_file(&path).unwrap();
+        assert!(output.status.success(), "{output:?}");
+
+        let expected = cons::run(source).unwrap();
+        assert_eq!(
+            String::from_utf8(output.stdout).unwrap(),
+            format!("{expected}\n")
+        );
+    }
+
+    /// Parse generated IR back into a module and run the LLVM verifier.
+    fn verify_ir(ir: &str) {
+        let context = Context::create();
+        let buffer = MemoryBuffer::create_from_memory_range_copy(ir.as_bytes(), "test");
+        let module = context.create_module_from_ir(buffer).unwrap();
+        module.verify().unwrap();
+    }
+
+    #[test]
+    fn test_compile_forward_reference() {
+        let compiler = AotCompiler::new();
+        let ir = compiler
+            .compile_source(
+                "(label main-logic (lambda (n) (helper (+ n 1))))\n\
+                 (label helper (lambda (x) (* x 2)))\n\
+                 (main-logic 20)",
+            )
+            .unwrap();
+
+        assert!(ir.contains("define %RuntimeValue @__consair_labeled_main-logic_"));
+        assert!(ir.contains("define %RuntimeValue @__consair_labeled_helper_"));
+        // Only the call is an expression; the definitions are functions
+        assert!(ir.contains("@__consair_expr_2"));
+        assert!(!ir.contains("@__consair_expr_0"));
+        verify_ir(&ir);
+    }
+
+    #[test]
+    fn test_compile_expands_macros() {
+        let compiler = AotCompiler::new();
+        let ir = compiler
+            .compile_source(
+                "(defmacro twice (x) (list '+ x x))\n\
+                 (label f (lambda (n) (when (> n 0) (twice n))))\n\
+                 (f 21)",
+            )
+            .unwrap();
+
+        assert!(ir.contains("define %RuntimeValue @__consair_labeled_f_"));
+        assert!(ir.contains("@rt_add"));
+        verify_ir(&ir);
+    }
+
+    #[test]
+    fn test_compile_main_must_take_one_argument() {
+        let compiler = AotCompiler::new();
+        let err = compiler
+            .compile_source("(label main (lambda () 1))")
+            .unwrap_err();
+
+        assert!(err.to_string().contains("main must take one argument"));
+    }
 }
@@ -27,6 +27,9 @@ pub fn generate_runtime_ir() -> String {
     // Print function for main (print_value, print_list)
     ir.push_str(&generate_print_result());
 
+    // Display function for the program's final value (rt_display)
+    ir.push_str(&generate_rt_display());
+
     // I/O functions (depend on print_value, so must come after)
     ir.push_str(&generate_io_functions());
 
@@ -35,7 +38,7 @@ pub fn generate_runtime_ir() -> String {
 
 fn generate_type_definitions() -> String {
     format!(
-        r#"
+        r###"
 ; Type definitions
 %RuntimeValue = type {{ i8, i64 }}
 %RuntimeConsCell = type {{ %RuntimeValue, %RuntimeValue, i32 }}
@@ -66,7 +69,21 @@ fn generate_type_definitions() -> String {
 @fmt_dot = private constant [4 x i8] c" . \00"
 @fmt_newline = private constant [2 x i8] c"\0A\00"
 @fmt_string = private constant [5 x i8] c"%.*s\00"
-"#
+
+; Format strings for displaying values as the interpreter prints them
+@fmt_t = private constant [2 x i8] c"t\00"
+@fmt_nan = private constant [6 x i8] c"##NaN\00"
+@fmt_inf = private constant [6 x i8] c"##Inf\00"
+@fmt_neg_inf = private constant [7 x i8] c"##-Inf\00"
+@fmt_float_precision = private constant [5 x i8] c"%.*g\00"
+@fmt_cstring = private constant [3 x i8] c"%s\00"
+@fmt_vector_open = private constant [3 x i8] c"<<\00"
+@fmt_vector_close = private constant [3 x i8] c">>\00"
+@fmt_lambda = private constant [9 x i8] c"<lambda>\00"
+
+; Runtime errors, written to stderr before exiting with status 1
+@msg_div_by_zero = private constant [25 x i8] c"Error: Division by zero\0A\00"
+"###
     )
 }
 
@@ -77,6 +94,11 @@ declare ptr @malloc(i64)
 declare void @free(ptr)
 declare i32 @printf(ptr, ...)
 declare ptr @memcpy(ptr, ptr, i64)
+declare i32 @snprintf(ptr, i64, ptr, ...)
+declare double @strtod(ptr, ptr)
+declare i32 @putchar(i32)
+declare i64 @write(i32, ptr, i64)
+declare void @exit(i32)
 "#
     .to_string()
 }
@@ -391,6 +413,21 @@ entry:
   %a_data = extractvalue %RuntimeValue %a, 1
   %b_data = extractvalue %RuntimeValue %b, 1
 
+  ; An integer divided by integer zero is an error, as in the interpreter;
+  ; with a float operand it is float division
+  %a_is_int = icmp eq i8 %a_tag, {TAG_INT}
+  %b_is_int = icmp eq i8 %b_tag, {TAG_INT}
+  %both_int = and i1 %a_is_int, %b_is_int
+  %b_is_zero = icmp eq i64 %b_data, 0
+  %by_zero = and i1 %both_int, %b_is_zero
+  br i1 %by_zero, label %div_by_zero, label %divide
+
+div_by_zero:
+  call i64 @write(i32 2, ptr @msg_div_by_zero, i64 24)
+  call void @exit(i32 1)
+  unreachable
+
+divide:
   ; Always do float division for simplicity
   %a_is_float = icmp eq i8 %a_tag, {TAG_FLOAT}
   %a_float_bits = select i1 %a_is_float, i64 %a_data, i64 0
@@ -1431,6 +1468,282 @@ done:
     )
 }
 
+/// `rt_display` prints a value the way the interpreter's `Display` does:
+/// booleans as `t` and `nil`, strings quoted and escaped, vectors as
+/// `<<...>>`. Floats print in the shortest form that reads back as the same
+/// value, but very large and very small ones use exponent notation, which
+/// the interpreter does not.
+fn generate_rt_display() -> String {
+    format!(
+        r#"
+; rt_display: Print a RuntimeValue as the interpreter displays it
+define void @rt_display(%RuntimeValue %val) {{
+entry:
+  %tag = extractvalue %RuntimeValue %val, 0
+  %data = extractvalue %RuntimeValue %val, 1
+
+  switch i8 %tag, label %done [
+    i8 {TAG_NIL}, label %display_nil
+    i8 {TAG_BOOL}, label %display_bool
+    i8 {TAG_INT}, label %display_int
+    i8 {TAG_FLOAT}, label %display_float
+    i8 {TAG_CONS}, label %display_cons
+    i8 {TAG_STRING}, label %display_string
+    i8 {TAG_VECTOR}, label %display_vector
+    i8 {TAG_CLOSURE}, label %display_closure
+  ]
+
+display_nil:
+  %nil_fmt = getelementptr [4 x i8], ptr @fmt_nil, i32 0, i32 0
+  call i32 (ptr, ...) @printf(ptr %nil_fmt)
+  br label %done
+
+display_bool:
+  %is_true = icmp ne i64 %data, 0
+  br i1 %is_true, label %display_t, label %display_nil
+
+display_t:
+  %t_fmt = getelementptr [2 x i8], ptr @fmt_t, i32 0, i32 0
+  call i32 (ptr, ...) @printf(ptr %t_fmt)
+  br label %done
+
+display_int:
+  %int_fmt = getelementptr [5 x i8], ptr @fmt_int, i32 0, i32 0
+  call i32 (ptr, ...) @printf(ptr %int_fmt, i64 %data)
+  br label %done
+
+display_float:
+  %float_val = bitcast i64 %data to double
+  call void @display_float(double %float_val)
+  br label %done
+
+display_cons:
+  %open_fmt = getelementptr [2 x i8], ptr @fmt_cons_open, i32 0, i32 0
+  call i32 (ptr, ...) @printf(ptr %open_fmt)
+  call void @display_list(%RuntimeValue %val)
+  %close_fmt = getelementptr [2 x i8], ptr @fmt_cons_close, i32 0, i32 0
+  call i32 (ptr, ...) @printf(ptr %close_fmt)
+  br label %done
+
+display_string:
+  %str_ptr = inttoptr i64 %data to ptr
+  %str_data_slot = getelementptr %RuntimeString, ptr %str_ptr, i32 0, i32 0
+  %str_data = load ptr, ptr %str_data_slot
+  %str_len_slot = getelementptr %RuntimeString, ptr %str_ptr, i32 0, i32 1
+  %str_len = load i64, ptr %str_len_slot
+  call void @display_string(ptr %str_data, i64 %str_len)
+  br label %done
+
+display_vector:
+  %vec_ptr = inttoptr i64 %data to ptr
+  %elements_slot = getelementptr %RuntimeVector, ptr %vec_ptr, i32 0, i32 0
+  %elements = load ptr, ptr %elements_slot
+  %vec_len_slot = getelementptr %RuntimeVector, ptr %vec_ptr, i32 0, i32 1
+  %vec_len = load i64, ptr %vec_len_slot
+  call void @display_vector(ptr %elements, i64 %vec_len)
+  br label %done
+
+display_closure:
+  %lambda_fmt = getelementptr [9 x i8], ptr @fmt_lambda, i32 0, i32 0
+  call i32 (ptr, ...) @printf(ptr %lambda_fmt)
+  br label %done
+
+done:
+  ret void
+}}
+
+; display_float: Print the shortest precision that reads back as the same value
+define void @display_float(double %x) {{
+entry:
+  %buf = alloca [32 x i8]
+  %is_nan = fcmp uno double %x, %x
+  br i1 %is_nan, label %nan, label %check_inf
+
+nan:
+  %nan_fmt = getelementptr [6 x i8], ptr @fmt_nan, i32 0, i32 0
+  call i32 (ptr, ...) @printf(ptr %nan_fmt)
+  br label %done
+
+check_inf:
+  %is_inf = fcmp oeq double %x, 0x7FF0000000000000
+  br i1 %is_inf, label %inf, label %check_neg_inf
+
+inf:
+  %inf_fmt = getelementptr [6 x i8], ptr @fmt_inf, i32 0, i32 0
+  call i32 (ptr, ...) @printf(ptr %inf_fmt)
+  br label %done
+
+check_neg_inf:
+  %is_neg_inf = fcmp oeq double %x, 0xFFF0000000000000
+  br i1 %is_neg_inf, label %neg_inf, label %try_precision
+
+neg_inf:
+  %neg_inf_fmt = getelementptr [7 x i8], ptr @fmt_neg_inf, i32 0, i32 0
+  call i32 (ptr, ...) @printf(ptr %neg_inf_fmt)
+  br label %done
+
+try_precision:
+  %precision = phi i32 [ 1, %check_neg_inf ], [ %next_precision, %next ]
+  %precision_fmt = getelementptr [5 x i8], ptr @fmt_float_precision, i32 0, i32 0
+  call i32 (ptr, i64, ptr, ...) @snprintf(ptr %buf, i64 32, ptr %precision_fmt, i32 %precision, double %x)
+  %read_back = call double @strtod(ptr %buf, ptr null)
+  %exact = fcmp oeq double %read_back, %x
+  %at_max = icmp sge i32 %precision, 17
+  %stop = or i1 %exact, %at_max
+  br i1 %stop, label %print, label %next
+
+next:
+  %next_precision = add i32 %precision, 1
+  br label %try_precision
+
+print:
+  %cstring_fmt = getelementptr [3 x i8], ptr @fmt_cstring, i32 0, i32 0
+  call i32 (ptr, ...) @printf(ptr %cstring_fmt, ptr %buf)
+  br label %done
+
+done:
+  ret void
+}}
+
+; display_string: Print a string in double quotes, escaping as the reader expects
+define void @display_string(ptr %data, i64 %len) {{
+entry:
+  call i32 @putchar(i32 34)
+  br label %loop
+
+loop:
+  %i = phi i64 [ 0, %entry ], [ %next_i, %next ]
+  %more = icmp ult i64 %i, %len
+  br i1 %more, label %body, label %close
+
+body:
+  %byte_ptr = getelementptr i8, ptr %data, i64 %i
+  %byte = load i8, ptr %byte_ptr
+  %c = zext i8 %byte to i32
+  switch i32 %c, label %plain [
+    i32 10, label %escape_n
+    i32 9, label %escape_t
+    i32 13, label %escape_r
+    i32 92, label %escape_self
+    i32 34, label %escape_self
+  ]
+
+escape_n:
+  call i32 @putchar(i32 92)
+  call i32 @putchar(i32 110)
+  br label %next
+
+escape_t:
+  call i32 @putchar(i32 92)
+  call i32 @putchar(i32 116)
+  br label %next
+
+escape_r:
+  call i32 @putchar(i32 92)
+  call i32 @putchar(i32 114)
+  br label %next
+
+escape_self:
+  call i32 @putchar(i32 92)
+  call i32 @putchar(i32 %c)
+  br label %next
+
+plain:
+  call i32 @putchar(i32 %c)
+  br label %next
+
+next:
+  %next_i = add i64 %i, 1
+  br label %loop
+
+close:
+  call i32 @putchar(i32 34)
+  ret void
+}}
+
+; display_list: Display elements of a list (without parens)
+define void @display_list(%RuntimeValue %val) {{
+entry:
+  br label %loop
+
+loop:
+  %current = phi %RuntimeValue [ %val, %entry ], [ %cdr, %get_car ]
+  %first = phi i1 [ true, %entry ], [ false, %get_car ]
+
+  %tag = extractvalue %RuntimeValue %current, 0
+  %is_cons = icmp eq i8 %tag, {TAG_CONS}
+  br i1 %is_cons, label %process_cons, label %check_nil
+
+process_cons:
+  br i1 %first, label %get_car, label %print_sep
+
+print_sep:
+  %space_fmt = getelementptr [2 x i8], ptr @fmt_space, i32 0, i32 0
+  call i32 (ptr, ...) @printf(ptr %space_fmt)
+  br label %get_car
+
+get_car:
+  %ptr_int = extractvalue %RuntimeValue %current, 1
+  %cell_ptr = inttoptr i64 %ptr_int to ptr
+  %car_ptr = getelementptr %RuntimeConsCell, ptr %cell_ptr, i32 0, i32 0
+  %car = load %RuntimeValue, ptr %car_ptr
+  call void @rt_display(%RuntimeValue %car)
+  %cdr_ptr = getelementptr %RuntimeConsCell, ptr %cell_ptr, i32 0, i32 1
+  %cdr = load %RuntimeValue, ptr %cdr_ptr
+  br label %loop
+
+check_nil:
+  %is_nil = icmp eq i8 %tag, {TAG_NIL}
+  br i1 %is_nil, label %done, label %print_dot
+
+print_dot:
+  ; Improper list - print " . value"
+  %dot_fmt = getelementptr [4 x i8], ptr @fmt_dot, i32 0, i32 0
+  call i32 (ptr, ...) @printf(ptr %dot_fmt)
+  call void @rt_display(%RuntimeValue %current)
+  br label %done
+
+done:
+  ret void
+}}
+
+; display_vector: Display vector elements as <<a b c>>
+define void @display_vector(ptr %elements, i64 %len) {{
+entry:
+  %open_fmt = getelementptr [3 x i8], ptr @fmt_vector_open, i32 0, i32 0
+  call i32 (ptr, ...) @printf(ptr %open_fmt)
+  br label %loop
+
+loop:
+  %i = phi i64 [ 0, %entry ], [ %next_i, %display_element ]
+  %more = icmp ult i64 %i, %len
+  br i1 %more, label %check_first, label %close
+
+check_first:
+  %is_first = icmp eq i64 %i, 0
+  br i1 %is_first, label %display_element, label %print_sep
+
+print_sep:
+  %space_fmt = getelementptr [2 x i8], ptr @fmt_space, i32 0, i32 0
+  call i32 (ptr, ...) @printf(ptr %space_fmt)
+  br label %display_element
+
+display_element:
+  %element_ptr = getelementptr %RuntimeValue, ptr %elements, i64 %i
+  %element = load %RuntimeValue, ptr %element_ptr
+  call void @rt_display(%RuntimeValue %element)
+  %next_i = add i64 %i, 1
+  br label %loop
+
+close:
+  %close_fmt = getelementptr [3 x i8], ptr @fmt_vector_close, i32 0, i32 0
+  call i32 (ptr, ...) @printf(ptr %close_fmt)
+  ret void
+}}
+"#
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1453,6 +1766,7 @@ mod tests {
         assert!(ir.contains("define %RuntimeValue @rt_lt"));
         assert!(ir.contains("define %RuntimeValue @rt_is_nil"));
         assert!(ir.contains("define void @print_value"));
+        assert!(ir.contains("define void @rt_display"));
     }
 
     #[test]
@@ -1,6 +1,7 @@
 //! cadr - AOT compiler for Consair Lisp
 //!
-//! Compiles Consair Lisp source files to LLVM IR.
+//! Compiles Consair Lisp source files to LLVM IR, object files or
+//! executables.
 //!
 //! # Usage
 //!
@@ -13,13 +14,28 @@
 //!
 //! # Then compile to native with clang
 //! clang -O3 output.ll -o output
+//!
+//! # Or compile straight to an executable
+//! cadr input.lisp --emit exe -O3 -o output
 //! ```
+//!
+//! The program prints its final value as `cons` would. With `--exit-code`,
+//! an integer final value becomes the exit status instead of 0.
 
 use std::env;
 use std::path::Path;
 use std::process;
 
 use cadr::aot::AotCompiler;
+use inkwell::OptimizationLevel;
+
+/// What `cadr` writes.
+#[derive(Clone, Copy, PartialEq)]
+enum Emit {
+    Ir,
+    Object,
+    Executable,
+}
 
 fn print_usage() {
     eprintln!("cadr - AOT compiler for Consair Lisp");
@@ -30,15 +46,28 @@ fn print_usage() {
     eprintln!("  cadr --help                    Show this help");
     eprintln!("  cadr --version                 Show version");
     eprintln!();
+    eprintln!("Options:");
+    eprintln!("  --emit ir|obj|exe  What to write (default: ir); obj and exe need -o");
+    eprintln!("  -O0, -O1, -O2, -O3 Optimization level for obj and exe (default: -O2)");
+    eprintln!("  --exit-code        Exit with the final value if it is an integer");
+    eprintln!();
     eprintln!("Examples:");
     eprintln!("  cadr factorial.lisp -o factorial.ll");
     eprintln!("  clang -O3 factorial.ll -o factorial");
+    eprintln!("  cadr factorial.lisp --emit exe -O3 -o factorial");
 }
 
 fn print_version() {
     eprintln!("cadr {}", env!("CARGO_PKG_VERSION"));
 }
 
+fn usage_error(message: &str) -> ! {
+    eprintln!("Error: {}", message);
+    eprintln!();
+    print_usage();
+    process::exit(1);
+}
+
 fn main() {
     let args: Vec<String> = env::args().collect();
 
@@ -61,16 +90,35 @@ fn main() {
     }
 
     let input = &args[1];
+    let mut output = None;
+    let mut emit = Emit::Ir;
+    let mut compiler = AotCompiler::new();
 
-    // Check for -o flag
-    let output = if args.len() >= 4 && args[2] == "-o" {
-        Some(args[3].as_str())
-    } else {
-        None
-    };
+    let mut rest = args[2..].iter();
+    while let Some(arg) = rest.next() {
+        match arg.as_str() {
+            "-o" => match rest.next() {
+                Some(path) => output = Some(path.as_str()),
+                None => usage_error("-o requires a path"),
+            },
+            "--emit" => {
+                emit = match rest.next().map(String::as_str) {
+                    Some("ir") => Emit::Ir,
+                    Some("obj") => Emit::Object,
+                    Some("exe") => Emit::Executable,
+                    _ => usage_error("--emit must be ir, obj or exe"),
+                }
+            }
+            "-O0" => compiler.opt_level = OptimizationLevel::None,
+            "-O1" => compiler.opt_level = OptimizationLevel::Less,
+            "-O2" => compiler.opt_level = OptimizationLevel::Default,
+            "-O3" => compiler.opt_level = OptimizationLevel::Aggressive,
+            "--exit-code" => compiler.exit_code = true,
+            other => usage_error(&format!("unknown option {}", other)),
+        }
+    }
 
     // Compile
-    let compiler = AotCompiler::new();
     let input_path = Path::new(input);
 
     if !input_path.exists() {
@@ -78,7 +126,14 @@ fn main() {
         process::exit(1);
     }
 
-    match compiler.compile_file(input_path, output.map(Path::new)) {
+    let result = match (emit, output) {
+        (Emit::Ir, output) => compiler.compile_file(input_path, output.map(Path::new)),
+        (Emit::Object, Some(out)) => compiler.compile_to_object(input_path, Path::new(out)),
+        (Emit::Executable, Some(out)) => compiler.compile_to_executable(input_path, Path::new(out)),
+        (_, None) => usage_error("--emit obj and --emit exe need an output path (-o)"),
+    };
+
+    match result {
         Ok(()) => {
             if let Some(out) = output {
                 eprintln!("Compiled {} to {}", input, out);
@@ -1,9 +1,13 @@
-use codspeed_criterion_compat::{Criterion, black_box, criterion_group, criterion_main};
+use codspeed_criterion_compat::{
+    BatchSize, BenchmarkId, Criterion, black_box, criterion_group, criterion_main,
+};
 use cons::jit::JitEngine;
 use cons::{eval, register_stdlib};
+use consair::abstractions::conj;
 use consair::interner::InternedSymbol;
-use consair::language::AtomType;
+use consair::language::{AtomType, PersistentVector, VectorValue};
 use consair::{Environment, NumericType, Value, cons, parse};
+use std::sync::Arc;
 use std::time::Duration;
 
 // ============================================================================
@@ -516,6 +520,96 @@ fn bench_list_traverse(c: &mut Criterion) {
     });
 }
 
+fn bench_list_drop(c: &mut Criterion) {
+    // Dropping is iterative, so time should grow linearly with length
+    let mut group = c.benchmark_group("list drop");
+    for len in [10_000, 100_000, 1_000_000] {
+        group.bench_with_input(BenchmarkId::from_parameter(len), &len, |b, &len| {
+            b.iter_batched(
+                || {
+                    let mut list = Value::Nil;
+                    for i in (0..len).rev() {
+                        list = cons(Value::Atom(AtomType::Number(NumericType::Int(i))), list);
+                    }
+                    list
+                },
+                drop,
+                BatchSize::LargeInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+/// List-heavy builtins: the cost is the cons cells they allocate and drop,
+/// since the integers in them are stored inline.
+///
+/// These measure the `cons-freelist` feature. Run them once without it to
+/// save a baseline, then with it to compare:
+///
+/// ```text
+/// cargo bench -p cons --bench benchmarks -- --save-baseline plain 'list (reverse|append)'
+/// cargo bench -p cons --bench benchmarks --features cons-freelist -- --baseline plain 'list (reverse|append)'
+/// ```
+fn bench_list_reverse_append(c: &mut Criterion) {
+    let mut env = Environment::new();
+    register_stdlib(&mut env);
+    let mut list = Value::Nil;
+    for i in (0..100_000).rev() {
+        list = cons(Value::Atom(AtomType::Number(NumericType::Int(i))), list);
+    }
+    env.define("big".to_string(), list);
+
+    let reverse = parse("(reverse big)").unwrap();
+    c.bench_function("list reverse (100000 elements)", |b| {
+        b.iter(|| black_box(eval(reverse.clone(), &mut env).unwrap()))
+    });
+
+    let append = parse("(append big big)").unwrap();
+    c.bench_function("list append (2 x 100000 elements)", |b| {
+        b.iter(|| black_box(eval(append.clone(), &mut env).unwrap()))
+    });
+
+    // Builds and drops two lists per iteration, so with the freelist each
+    // one reuses the cells of the last
+    let reverse_range = parse("(reverse (range 100000))").unwrap();
+    c.bench_function("list reverse of a fresh range (100000 elements)", |b| {
+        b.iter(|| black_box(eval(reverse_range.clone(), &mut env).unwrap()))
+    });
+}
+
+fn bench_vector_conj(c: &mut Criterion) {
+    // Building a vector one conj at a time: persistent vectors share
+    // structure, fast vectors copy their elements on every conj
+    let mut group = c.benchmark_group("vector conj build");
+    let build = |empty: Value, len: i64| {
+        (0..len).fold(empty, |vec, i| {
+            conj(&vec, Value::Atom(AtomType::Number(NumericType::Int(i)))).unwrap()
+        })
+    };
+    for len in [1_000, 10_000, 100_000] {
+        group.bench_with_input(BenchmarkId::new("persistent", len), &len, |b, &len| {
+            b.iter(|| {
+                let empty = Value::PersistentVector(Arc::new(PersistentVector {
+                    elements: Default::default(),
+                }));
+                black_box(build(empty, len))
+            })
+        });
+    }
+    for len in [1_000, 10_000] {
+        group.bench_with_input(BenchmarkId::new("fast", len), &len, |b, &len| {
+            b.iter(|| {
+                let empty = Value::Vector(Arc::new(VectorValue {
+                    elements: Vec::new(),
+                }));
+                black_box(build(empty, len))
+            })
+        });
+    }
+    group.finish();
+}
+
 // ============================================================================
 // Symbol Interning Benchmarks
 // ============================================================================
@@ -804,7 +898,10 @@ criterion_group! {
         bench_list_car,
         bench_list_cdr,
         bench_list_build_large,
-        bench_list_traverse
+        bench_list_traverse,
+        bench_list_drop,
+        bench_list_reverse_append,
+        bench_vector_conj
 }
 
 criterion_group! {
@@ -1071,6 +1168,17 @@ fn bench_precompiled_comparison(c: &mut Criterion) {
     });
 }
 
+fn bench_precompiled_quoted_list(c: &mut Criterion) {
+    let engine = JitEngine::new().unwrap();
+    let elements: Vec<String> = (0..100).map(|i| i.to_string()).collect();
+    let expr = parse(&format!("(car (cdr '({})))", elements.join(" "))).unwrap();
+    let compiled = engine.compile(&expr).unwrap();
+
+    c.bench_function("precompiled quoted list (100 elements)", |b| {
+        b.iter(|| black_box(compiled.execute()))
+    });
+}
+
 criterion_group! {
     name = precompiled_jit_benches;
     config = Criterion::default()
@@ -1082,7 +1190,8 @@ criterion_group! {
         bench_precompiled_cons_car_cdr,
         bench_precompiled_vector_operations,
         bench_precompiled_cond_expression,
-        bench_precompiled_comparison
+        bench_precompiled_comparison,
+        bench_precompiled_quoted_list
 }
 
 // ============================================================================
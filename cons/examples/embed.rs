@@ -0,0 +1,37 @@
+//! Embedding Consair in a Rust application
+//!
+//! Registers a native closure that counts how often Lisp code calls it,
+//! using a standard library without file or shell access.
+//!
+//! Run with `cargo run -p cons --example embed`.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicI64, Ordering};
+
+use cons::native::{check_arity_exact, make_int};
+use cons::{Environment, StdlibConfig, register_stdlib_with, run_with};
+
+fn main() {
+    let mut env = Environment::new();
+    register_stdlib_with(&mut env, &StdlibConfig::sandboxed());
+
+    // State owned by the application and shared with Lisp code
+    let visits = Arc::new(AtomicI64::new(0));
+    let counter = visits.clone();
+    env.register_native("visit!", move |args, _env| {
+        check_arity_exact("visit!", args, 0)?;
+        Ok(make_int(counter.fetch_add(1, Ordering::SeqCst) + 1))
+    });
+
+    let source = "(visit!) (visit!) (list 'visits (visit!))";
+    match run_with(&mut env, source) {
+        Ok(value) => println!("{value}"),
+        Err(e) => eprintln!("error: {e}"),
+    }
+    println!("Rust saw {} visits", visits.load(Ordering::SeqCst));
+
+    // The sandboxed standard library has no shell
+    if let Err(e) = run_with(&mut env, "(shell \"ls\")") {
+        println!("shell is unavailable: {e}");
+    }
+}
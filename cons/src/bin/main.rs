@@ -1,66 +1,99 @@
-use cons::{eval, jit::JitEngine, register_stdlib, runtime::RuntimeValue};
-use consair::{Environment, parse};
+use cons::cli::{Cli, Mode};
+use cons::doc::{extract_docs, render_markdown, stdlib_docs};
+use cons::jit::JitEngine;
+use cons::repl::{JitMode, ReplSession, complete, default_init_file, is_complete_expression};
+use cons::runner::{
+    RunReport, call_main, decode_source, define_script_args, enter_file, form_error, read_source,
+    run_file_jit_report_with, run_source_jit_report_with, snippet,
+};
+use cons::{StdlibConfig, eval, register_stdlib_with};
+use consair::Environment;
+use consair::lexer::Lexer;
+use consair::parser::Parser;
+use rustyline::completion::Completer;
 use rustyline::error::ReadlineError;
-use rustyline::{Config, Editor};
+use rustyline::highlight::{Highlighter, MatchingBracketHighlighter};
+use rustyline::hint::Hinter;
+use rustyline::validate::Validator;
+use rustyline::{Config, Context, Editor, Helper};
+use std::borrow::Cow;
 use std::env;
-use std::fs;
-use std::path::PathBuf;
+use std::io::{self, IsTerminal, Read};
+use std::path::{Path, PathBuf};
 use std::process;
 
-/// Check if an expression has balanced parentheses and is complete
-fn is_complete_expression(input: &str) -> bool {
-    let mut depth = 0;
-    let mut in_string = false;
-    let mut escape_next = false;
-
-    for ch in input.chars() {
-        if in_string {
-            if escape_next {
-                escape_next = false;
-                continue;
-            }
-            if ch == '\\' {
-                escape_next = true;
-                continue;
-            }
-            if ch == '"' {
-                in_string = false;
-            }
-            continue;
-        }
+/// Line-editing support for the REPL: completion of commands and names, and
+/// highlighting of the bracket matching the one at the cursor
+#[derive(Default)]
+struct ReplHelper {
+    /// Names to complete, refreshed from the session before each prompt
+    names: Vec<String>,
+    brackets: MatchingBracketHighlighter,
+}
 
-        match ch {
-            '"' => in_string = true,
-            '(' => depth += 1,
-            ')' => depth -= 1,
-            _ => {}
-        }
+impl Completer for ReplHelper {
+    type Candidate = String;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<String>)> {
+        Ok(complete(line, pos, &self.names))
+    }
+}
+
+impl Highlighter for ReplHelper {
+    fn highlight<'l>(&self, line: &'l str, pos: usize) -> Cow<'l, str> {
+        self.brackets.highlight(line, pos)
+    }
+
+    fn highlight_char(&self, line: &str, pos: usize, forced: bool) -> bool {
+        self.brackets.highlight_char(line, pos, forced)
     }
+}
 
-    depth == 0 && !in_string
+impl Hinter for ReplHelper {
+    type Hint = String;
 }
 
-/// Print help information
+impl Validator for ReplHelper {}
+
+impl Helper for ReplHelper {}
+
 fn print_help(jit_available: bool) {
     println!("Consair REPL - Interactive Lisp Interpreter");
     println!();
     println!("Special Commands:");
     println!("  :help, :h        Show this help message");
     println!("  :quit, :q        Exit the REPL");
-    println!("  :env             Show current environment bindings");
+    println!("  :env             Show the bindings you have defined");
+    println!("  :env all         Show every binding, including the standard library");
     if jit_available {
         println!("  :jit             Toggle JIT compilation mode");
     }
+    println!("  :jit-status      Show JIT engine status and any initialization error");
+    println!("  :jit-stats       Show JIT result cache hits, misses and entries");
+    println!("  :jit-clear       Empty the JIT result cache");
+    println!("  :expand <expr>   Show <expr> with every macro expanded");
+    println!("  :doc <name>      Describe what <name> is bound to, with its docstring");
+    println!("  :pretty on|off   Print results across lines, or on one line");
+    println!("  :time on|off     Report the engine and time taken for each form");
+    println!("  :save <file>     Save your definitions to a file");
+    println!("  :load-session <file>  Evaluate definitions saved with :save");
     println!();
     println!("Keyboard Shortcuts:");
     println!("  Ctrl-C           Clear current input");
     println!("  Ctrl-D           Exit REPL");
     println!("  Up/Down          Navigate command history");
     println!("  Ctrl-R           Reverse history search");
+    println!("  Tab              Complete a command, special form or bound name");
     println!();
     println!("Multi-line Input:");
     println!("  If you have unclosed parentheses, press Enter to continue");
     println!("  on the next line. The prompt will change to '......> '");
+    println!("  Several expressions on one line are evaluated in turn.");
     println!();
     println!("Examples:");
     println!("  (+ 1 2 3)");
@@ -68,32 +101,15 @@ fn print_help(jit_available: bool) {
     println!("  (square 5)");
 }
 
-/// Show environment bindings (simplified - just shows that env exists)
-fn print_env_info(env: &Environment) {
-    println!("Environment is active with standard library loaded.");
-    println!("Use (quote env-name) to inspect specific bindings.");
-    // Note: Full environment introspection would require adding methods to Environment
-    // For now, we just acknowledge it exists
-    let _ = env; // Suppress unused warning
-}
+fn repl_with_jit(jit_mode: JitMode, init_files: &[PathBuf], stdlib: &StdlibConfig) {
+    let mut session = ReplSession::with_stdlib(jit_mode, JitEngine::new, stdlib);
 
-/// Convert RuntimeValue to string for display
-fn runtime_value_to_string(val: RuntimeValue) -> String {
-    // Convert RuntimeValue back to Value for display
-    match val.to_value() {
-        Ok(v) => format!("{v}"),
-        Err(e) => format!("<JIT error: {e}>"),
+    // --jit was explicitly requested: don't silently run interpreted
+    if let Some(e) = session.startup_error() {
+        eprintln!("Error: {e}");
+        process::exit(1);
     }
-}
-
-fn repl_with_jit(start_with_jit: bool) {
-    let mut env = Environment::new();
-    register_stdlib(&mut env);
-
-    // JIT mode state
-    let mut jit_enabled = start_with_jit;
-    let jit_engine = JitEngine::new().ok();
-    let jit_available = jit_engine.is_some();
+    let jit_available = session.jit_available();
 
     // Configure rustyline
     let config = Config::builder()
@@ -101,7 +117,8 @@ fn repl_with_jit(start_with_jit: bool) {
         .history_ignore_space(true)
         .build();
 
-    let mut rl = Editor::<(), _>::with_config(config).unwrap();
+    let mut rl = Editor::with_config(config).unwrap();
+    rl.set_helper(Some(ReplHelper::default()));
 
     // Set up history file
     let history_file = dirs::home_dir()
@@ -118,17 +135,30 @@ fn repl_with_jit(start_with_jit: bool) {
     if jit_available {
         println!(
             "JIT compilation available (mode: {})",
-            if jit_enabled { "enabled" } else { "disabled" }
+            if session.jit_enabled() {
+                "enabled"
+            } else {
+                "disabled"
+            }
         );
+    } else if let Some(e) = session.jit_init_error() {
+        println!("JIT not available: {e}");
     }
     println!("Type :help for help, :quit to exit");
     println!();
 
+    // Init files are loaded before the first prompt; their errors don't stop the REPL
+    for path in init_files {
+        for e in session.load_init_file(path) {
+            eprintln!("⚠ {e}");
+        }
+    }
+
     let mut accumulated_input = String::new();
 
     loop {
         // Build prompt based on mode
-        let base_prompt = if jit_enabled {
+        let base_prompt = if session.jit_enabled() {
             "consair[jit]> "
         } else {
             "consair> "
@@ -140,6 +170,11 @@ fn repl_with_jit(start_with_jit: bool) {
             "......> "
         };
 
+        // Complete whatever the last input defined
+        if let Some(helper) = rl.helper_mut() {
+            helper.names = session.completion_names();
+        }
+
         match rl.readline(prompt) {
             Ok(line) => {
                 // Add to accumulated input
@@ -167,20 +202,95 @@ fn repl_with_jit(start_with_jit: bool) {
                         ":quit" | ":q" => {
                             break;
                         }
-                        ":env" => {
-                            print_env_info(&env);
+                        ":env" | ":env all" => {
+                            let listing = session.env_listing(trimmed == ":env all");
+                            if listing.is_empty() {
+                                println!(
+                                    "No bindings defined yet (:env all lists the standard library)"
+                                );
+                            }
+                            for line in listing {
+                                println!("{line}");
+                            }
                             accumulated_input.clear();
                             continue;
                         }
                         ":jit" => {
-                            if jit_available {
-                                jit_enabled = !jit_enabled;
-                                println!(
+                            match session.toggle_jit() {
+                                Ok(enabled) => println!(
                                     "JIT mode {}",
-                                    if jit_enabled { "enabled" } else { "disabled" }
-                                );
-                            } else {
-                                println!("JIT not available (engine failed to initialize)");
+                                    if enabled { "enabled" } else { "disabled" }
+                                ),
+                                Err(e) => println!("{e}"),
+                            }
+                            accumulated_input.clear();
+                            continue;
+                        }
+                        ":jit-status" => {
+                            println!("{}", session.jit_status());
+                            accumulated_input.clear();
+                            continue;
+                        }
+                        ":jit-stats" => {
+                            println!("{}", session.jit_stats());
+                            accumulated_input.clear();
+                            continue;
+                        }
+                        ":jit-clear" => {
+                            println!("{}", session.jit_clear());
+                            accumulated_input.clear();
+                            continue;
+                        }
+                        ":pretty" | ":pretty on" | ":pretty off" => {
+                            if trimmed != ":pretty" {
+                                session.set_pretty(trimmed == ":pretty on");
+                            }
+                            println!(
+                                "Pretty printing {}",
+                                if session.pretty() { "on" } else { "off" }
+                            );
+                            accumulated_input.clear();
+                            continue;
+                        }
+                        ":time" | ":time on" | ":time off" => {
+                            if trimmed != ":time" {
+                                session.set_timing(trimmed == ":time on");
+                            }
+                            println!("Timing {}", if session.timing() { "on" } else { "off" });
+                            accumulated_input.clear();
+                            continue;
+                        }
+                        _ if trimmed.starts_with(":save ") => {
+                            let path = Path::new(trimmed[":save ".len()..].trim());
+                            match session.save_session(path) {
+                                Ok(warnings) => {
+                                    for warning in warnings {
+                                        eprintln!("⚠ {warning}");
+                                    }
+                                    println!("Session saved to {}", path.display());
+                                }
+                                Err(e) => eprintln!("⚠ Error: {e}"),
+                            }
+                            accumulated_input.clear();
+                            continue;
+                        }
+                        _ if trimmed.starts_with(":load-session ") => {
+                            let path = Path::new(trimmed[":load-session ".len()..].trim());
+                            for e in session.load_session(path) {
+                                eprintln!("⚠ {e}");
+                            }
+                            accumulated_input.clear();
+                            continue;
+                        }
+                        _ if trimmed.starts_with(":doc ") => {
+                            println!("{}", session.describe(trimmed[":doc ".len()..].trim()));
+                            accumulated_input.clear();
+                            continue;
+                        }
+                        _ if trimmed.starts_with(":expand ") => {
+                            match session.expand(&trimmed[":expand ".len()..]) {
+                                Ok(s) => println!("{s}"),
+                                Err(e) => eprintln!("⚠ Error: {e}"),
                             }
                             accumulated_input.clear();
                             continue;
@@ -200,33 +310,20 @@ fn repl_with_jit(start_with_jit: bool) {
                     continue;
                 }
 
-                // Try to parse and evaluate
-                match parse(&accumulated_input) {
-                    Ok(expr) => {
-                        // Evaluate with JIT or interpreter
-                        let result = if jit_enabled {
-                            if let Some(ref engine) = jit_engine {
-                                match engine.eval_with_env(&expr, &mut env) {
-                                    Ok(rv) => Ok(runtime_value_to_string(rv)),
-                                    Err(e) => {
-                                        // Fall back to interpreter on JIT error
-                                        eprintln!("⚠ JIT fallback: {e}");
-                                        eval(expr, &mut env).map(|v| format!("{v}"))
-                                    }
-                                }
-                            } else {
-                                eval(expr, &mut env).map(|v| format!("{v}"))
-                            }
-                        } else {
-                            eval(expr, &mut env).map(|v| format!("{v}"))
-                        };
-
-                        match result {
-                            Ok(s) => println!("{s}"),
-                            Err(e) => eprintln!("⚠ Error: {e}"),
-                        }
+                // Evaluate every form, printing each result in turn
+                let result = session.eval_input(&accumulated_input);
+                for printed in &result.results {
+                    println!("{printed}");
+                }
+                if let Some(e) = result.error {
+                    eprintln!("⚠ {e}");
+                    if result.forms > 1 {
+                        eprintln!(
+                            "  ({} of {} forms evaluated)",
+                            result.results.len(),
+                            result.forms
+                        );
                     }
-                    Err(e) => eprintln!("⚠ Parse error: {e}"),
                 }
 
                 accumulated_input.clear();
@@ -259,410 +356,235 @@ fn repl_with_jit(start_with_jit: bool) {
     }
 }
 
-fn run_file(filename: &str) -> Result<(), String> {
-    let contents = fs::read_to_string(filename)
-        .map_err(|e| format!("Failed to read file '{filename}': {e}"))?;
+/// Source to run, with the name its errors give it
+struct Program<'a> {
+    name: &'a str,
+    source: &'a str,
+    /// The file it was read from, which `load` and `require` resolve against
+    file: Option<&'a Path>,
+}
 
+/// Run a program with the interpreter
+///
+/// Scripts print explicitly, so the last value is only echoed with `print_last`.
+/// Errors name the line and column where the failing form starts. With
+/// `main_args`, a `main` defined by the program is then called with them.
+fn run_program(
+    program: &Program,
+    print_last: bool,
+    main_args: Option<&[String]>,
+    stdlib: &StdlibConfig,
+) -> Result<(), String> {
+    let name = program.name;
     let mut env = Environment::new();
-    register_stdlib(&mut env);
-    let mut last_result = None;
-
-    // Split the file into expressions and evaluate each one
-    // Simple approach: parse and evaluate complete s-expressions
-    let trimmed = contents.trim();
-    if trimmed.is_empty() {
-        return Ok(());
+    register_stdlib_with(&mut env, stdlib);
+    if let Some(file) = program.file {
+        enter_file(&mut env, file);
     }
+    define_script_args(&mut env, main_args.unwrap_or_default());
+    let mut last_result = None;
 
-    // Try to parse the entire content as a sequence of expressions
-    let mut remaining = trimmed;
-    while !remaining.trim().is_empty() {
-        // Find the next complete s-expression
-        let expr_result = parse_next_expr(remaining)?;
-        let (expr, rest) = expr_result;
-
-        match eval(expr, &mut env) {
+    let mut lexer = Lexer::new(program.source);
+    let mut parser = Parser::new(&mut lexer);
+    let mut index = 0;
+    while !parser.is_at_end() {
+        let form = parser
+            .parse_spanned_expression()
+            .map_err(|e| format!("{name}: {e}"))?;
+        index += 1;
+        let snippet = snippet(&form.value);
+
+        match eval(form.value, &mut env) {
             Ok(result) => last_result = Some(result),
-            Err(e) => return Err(format!("Evaluation error: {e}")),
+            Err(e) => {
+                return Err(format!(
+                    "{name}, {}: {}",
+                    form.span,
+                    form_error(index, &snippet, &e)
+                ));
+            }
         }
+    }
 
-        remaining = rest;
+    if let Some(args) = main_args
+        && let Some(result) = call_main(&mut env, args).map_err(|e| format!("{name}: main: {e}"))?
+    {
+        last_result = Some(result);
     }
 
     // Print the last result
-    if let Some(result) = last_result {
+    if print_last && let Some(result) = last_result {
         println!("{result}");
     }
 
     Ok(())
 }
 
-// Helper function to parse the next expression from a string
-fn parse_next_expr(input: &str) -> Result<(consair::Value, &str), String> {
-    // Skip leading whitespace and comments to find the next expression start
-    let trimmed = skip_whitespace_and_comments(input);
-    if trimmed.is_empty() {
-        return Err("No expression found".to_string());
-    }
-
-    // Find the end of the expression
-    let mut depth = 0;
-    let mut vec_depth = 0;
-    let mut in_string = false;
-    let mut in_raw_string = false;
-    let mut raw_hash_count = 0;
-    let mut escape_next = false;
-    let mut end_pos = 0;
-
-    let chars_vec: Vec<char> = trimmed.chars().collect();
-    let mut i = 0;
-
-    // Handle atoms (non-list/non-vector expressions that don't start with special chars)
-    if !trimmed.starts_with('(')
-        && !trimmed.starts_with('\'')
-        && !trimmed.starts_with('<')
-        && !trimmed.starts_with('"')
-    {
-        // Find the end of the atom (whitespace or delimiter)
-        while i < chars_vec.len() {
-            let ch = chars_vec[i];
-            if ch.is_whitespace() || ch == '(' || ch == ')' || ch == '<' || ch == '>' || ch == ';' {
-                end_pos = i;
-                break;
-            }
-            i += 1;
-        }
-        if end_pos == 0 {
-            end_pos = trimmed.len();
-        }
-    } else {
-        // Handle complex expressions (lists, vectors, strings, quoted expressions)
-        while i < chars_vec.len() {
-            let ch = chars_vec[i];
-
-            // Handle escape sequences in strings
-            if in_string && !in_raw_string {
-                if escape_next {
-                    escape_next = false;
-                    i += 1;
-                    continue;
-                }
-                if ch == '\\' {
-                    escape_next = true;
-                    i += 1;
-                    continue;
-                }
-                if ch == '"' {
-                    in_string = false;
-                    i += 1;
-                    continue;
-                }
-                i += 1;
-                continue;
-            }
-
-            // Handle raw strings
-            if in_raw_string {
-                if ch == '"' && i + raw_hash_count < chars_vec.len() {
-                    // Check if followed by correct number of #
-                    let mut hash_match = true;
-                    for j in 1..=raw_hash_count {
-                        if i + j >= chars_vec.len() || chars_vec[i + j] != '#' {
-                            hash_match = false;
-                            break;
-                        }
-                    }
-                    if hash_match {
-                        in_raw_string = false;
-                        i += raw_hash_count + 1;
-                        // If we're at top level, we're done
-                        if depth == 0 && vec_depth == 0 {
-                            end_pos = i;
-                            break;
-                        }
-                        continue;
-                    }
-                }
-                i += 1;
-                continue;
-            }
-
-            // Handle regular parsing
-            match ch {
-                // Raw string detection: r" or r#"
-                'r' if !in_string && i + 1 < chars_vec.len() => {
-                    let mut j = i + 1;
-                    let mut hashes = 0;
-                    while j < chars_vec.len() && chars_vec[j] == '#' {
-                        hashes += 1;
-                        j += 1;
-                    }
-                    if j < chars_vec.len() && chars_vec[j] == '"' {
-                        in_raw_string = true;
-                        raw_hash_count = hashes;
-                        i = j + 1;
-                        continue;
-                    }
-                    i += 1;
-                }
-                '"' if !in_string => {
-                    in_string = true;
-                    i += 1;
-                }
-                '(' if !in_string => {
-                    depth += 1;
-                    i += 1;
-                }
-                ')' if !in_string => {
-                    depth -= 1;
-                    if depth == 0 && vec_depth == 0 {
-                        end_pos = i + 1;
-                        break;
-                    }
-                    if depth < 0 {
-                        return Err("Unmatched closing parenthesis".to_string());
-                    }
-                    i += 1;
-                }
-                '<' if !in_string && i + 1 < chars_vec.len() && chars_vec[i + 1] == '<' => {
-                    vec_depth += 1;
-                    i += 2;
-                }
-                '>' if !in_string && i + 1 < chars_vec.len() && chars_vec[i + 1] == '>' => {
-                    vec_depth -= 1;
-                    if vec_depth == 0 && depth == 0 {
-                        end_pos = i + 2;
-                        break;
-                    }
-                    if vec_depth < 0 {
-                        return Err("Unmatched closing vector delimiter".to_string());
-                    }
-                    i += 2;
-                }
-                '\'' if !in_string && depth == 0 && vec_depth == 0 => {
-                    // Quote at top level - the quoted expression is the complete expression
-                    // Need to find the end of the quoted expression
-                    i += 1;
-                    let mut quote_depth = 0;
-                    let mut quote_vec_depth = 0;
-                    let mut quote_in_string = false;
-                    let mut quote_escape = false;
-
-                    while i < chars_vec.len() {
-                        let qch = chars_vec[i];
-                        if quote_in_string {
-                            if quote_escape {
-                                quote_escape = false;
-                            } else if qch == '\\' {
-                                quote_escape = true;
-                            } else if qch == '"' {
-                                quote_in_string = false;
-                            }
-                            i += 1;
-                            continue;
-                        }
-
-                        match qch {
-                            '"' => quote_in_string = true,
-                            '(' => quote_depth += 1,
-                            ')' => {
-                                quote_depth -= 1;
-                                if quote_depth == 0 && quote_vec_depth == 0 {
-                                    end_pos = i + 1;
-                                    break;
-                                }
-                            }
-                            '<' if i + 1 < chars_vec.len() && chars_vec[i + 1] == '<' => {
-                                quote_vec_depth += 1;
-                                i += 1;
-                            }
-                            '>' if i + 1 < chars_vec.len() && chars_vec[i + 1] == '>' => {
-                                quote_vec_depth -= 1;
-                                if quote_vec_depth == 0 && quote_depth == 0 {
-                                    end_pos = i + 2;
-                                    break;
-                                }
-                                i += 1;
-                            }
-                            c if c.is_whitespace() && quote_depth == 0 && quote_vec_depth == 0 => {
-                                end_pos = i;
-                                break;
-                            }
-                            _ => {}
-                        }
-                        i += 1;
-                    }
-                    if end_pos == 0 {
-                        end_pos = chars_vec.len();
-                    }
-                    break;
-                }
-                _ => {
-                    i += 1;
-                }
-            }
-        }
-    }
-
-    if end_pos == 0 {
-        if depth > 0 {
-            return Err("Unclosed opening parenthesis".to_string());
-        } else if vec_depth > 0 {
-            return Err("Unclosed vector delimiter".to_string());
-        } else if in_string {
-            return Err("Unclosed string literal".to_string());
-        }
-        return Err("Incomplete expression".to_string());
-    }
-
-    let expr_str = &trimmed[..end_pos];
-    let rest = &trimmed[end_pos..];
-
-    parse(expr_str).map(|expr| (expr, rest))
-}
-
-// Helper function to skip whitespace and comments between expressions
-// Note: Comments WITHIN expressions are now handled natively by the lexer
-// This function is only needed to skip comments BETWEEN top-level expressions
-fn skip_whitespace_and_comments(input: &str) -> &str {
-    let mut remaining = input;
-    loop {
-        // Skip whitespace
-        remaining = remaining.trim_start();
-        if remaining.is_empty() {
-            return remaining;
-        }
-
-        // Skip comments (from ; to end of line)
-        if remaining.starts_with(';') {
-            if let Some(newline_pos) = remaining.find('\n') {
-                remaining = &remaining[newline_pos + 1..];
-            } else {
-                // Comment to end of file
-                return "";
-            }
-        } else {
-            // No more comments or whitespace
-            break;
-        }
-    }
-    remaining
-}
-
 fn print_usage() {
     eprintln!("Usage:");
-    eprintln!("  cons              Start interactive REPL");
-    eprintln!("  cons <file.lisp>  Run a Lisp file");
-    eprintln!("  cons --help       Show this help message");
-    eprintln!("  cons --jit        Start REPL with JIT compilation enabled");
-    eprintln!("  cons --jit <file> Run a Lisp file with JIT compilation");
+    eprintln!("  cons                 Start interactive REPL");
+    eprintln!("  cons <file.lisp> [args...]");
+    eprintln!("                       Run a Lisp file, then call its main with args, if defined");
+    eprintln!("  cons -e <expr>...    Evaluate expressions and print the last value");
+    eprintln!("  ... | cons           Run the program piped to stdin, like a file");
+    eprintln!("  cons --help          Show this help message");
+    eprintln!("  cons --jit           Start REPL with JIT compilation enabled");
+    eprintln!("  cons --jit <file>    Run a Lisp file with JIT compilation");
+    eprintln!("  cons --no-jit        Start REPL without constructing the JIT engine");
+    eprintln!("  cons --init <file>   Start REPL after loading <file> (after ~/.consairrc)");
+    eprintln!("  cons --no-init       Start REPL without loading ~/.consairrc or $CONSAIR_INIT");
+    eprintln!("  cons --no-jit <file> Run a Lisp file with the interpreter only");
+    eprintln!("  cons --jit -v <file> Run with JIT, printing per-form timing and fallbacks");
+    eprintln!("  cons --no-main <file> Run a Lisp file without calling its main");
+    eprintln!("  cons --sandbox [file] Run without file access or shell commands");
+    eprintln!("  cons --print-last <file>");
+    eprintln!("                       Run a file and print the value of its last expression");
+    eprintln!("  cons doc <file.lisp> Print Markdown API docs for a file, without running it");
+    eprintln!("  cons doc --stdlib    Print Markdown API docs for the standard library");
 }
 
-/// Check if an expression is a definition (label, defmacro) that must use interpreter
-fn is_definition_expr(expr: &consair::Value) -> bool {
-    use consair::language::SymbolType;
-    use consair::{AtomType, Value};
-
-    if let Value::Cons(cell) = expr
-        && let Value::Atom(AtomType::Symbol(SymbolType::Symbol(sym))) = &cell.car
-    {
-        let name = sym.resolve();
-        return name == "label" || name == "defmacro";
-    }
-    false
+/// Run a program that is not a file with JIT compilation enabled
+fn run_program_jit(
+    program: &Program,
+    verbose: bool,
+    print_last: bool,
+    main_args: Option<&[String]>,
+    stdlib: &StdlibConfig,
+) -> Result<(), String> {
+    let report = run_source_jit_report_with(program.source, main_args, stdlib)
+        .map_err(|e| format!("{}: {e}", program.name))?;
+    print_report(&report, verbose, print_last);
+    Ok(())
 }
 
-/// Run a file with JIT compilation enabled
-fn run_file_jit(filename: &str) -> Result<(), String> {
-    let contents = fs::read_to_string(filename)
-        .map_err(|e| format!("Failed to read file '{filename}': {e}"))?;
-
-    let mut env = Environment::new();
-    register_stdlib(&mut env);
-
-    let jit_engine = JitEngine::new().map_err(|e| format!("Failed to initialize JIT: {e}"))?;
-
-    let mut last_result = None;
-
-    let trimmed = contents.trim();
-    if trimmed.is_empty() {
-        return Ok(());
-    }
-
-    let mut remaining = trimmed;
-    while !remaining.trim().is_empty() {
-        let expr_result = parse_next_expr(remaining)?;
-        let (expr, rest) = expr_result;
-
-        // Definitions must use interpreter to store bindings
-        if is_definition_expr(&expr) {
-            match eval(expr, &mut env) {
-                Ok(result) => last_result = Some(format!("{result}")),
-                Err(e) => return Err(format!("Evaluation error: {e}")),
-            }
-        } else {
-            // Try JIT first, fall back to interpreter
-            match jit_engine.eval_with_env(&expr, &mut env) {
-                Ok(rv) => last_result = Some(runtime_value_to_string(rv)),
-                Err(_) => {
-                    // Fall back to interpreter for unsupported expressions
-                    match eval(expr, &mut env) {
-                        Ok(result) => last_result = Some(format!("{result}")),
-                        Err(e) => return Err(format!("Evaluation error: {e}")),
-                    }
-                }
-            }
+/// Print what a JIT run produced
+///
+/// With `verbose`, per-form telemetry and a summary are printed to stderr.
+fn print_report(report: &RunReport, verbose: bool, print_last: bool) {
+    if verbose {
+        for form in &report.forms {
+            eprintln!("{form}");
         }
-
-        remaining = rest;
+        eprintln!("{}", report.summary());
     }
 
-    if let Some(result) = last_result {
+    if print_last && let Some(result) = report.last_result() {
         println!("{result}");
     }
+}
+
+/// Read all of stdin as source text
+fn read_stdin() -> Result<String, String> {
+    let mut bytes = Vec::new();
+    io::stdin()
+        .read_to_end(&mut bytes)
+        .map_err(|e| format!("Failed to read stdin: {e}"))?;
+    decode_source(&bytes).map_err(|e| format!("<stdin>: {e}"))
+}
 
+/// Print Markdown documentation for a file or, with `--stdlib`, the standard library
+fn print_docs(args: &[String]) -> Result<(), String> {
+    let markdown = match args {
+        [flag] if flag == "--stdlib" => render_markdown("Standard Library", &stdlib_docs()),
+        [file] if !file.starts_with("--") => {
+            let contents = read_source(file)?;
+            render_markdown(file, &extract_docs(&contents)?)
+        }
+        _ => return Err("Usage: cons doc <file.lisp> | cons doc --stdlib".to_string()),
+    };
+    print!("{markdown}");
     Ok(())
 }
 
 fn main() {
-    let args: Vec<String> = env::args().collect();
+    let args: Vec<String> = env::args().skip(1).collect();
 
-    match args.len() {
-        1 => {
-            // No arguments: start REPL
-            repl_with_jit(false);
+    if args.first().is_some_and(|arg| arg == "doc") {
+        if let Err(e) = print_docs(&args[1..]) {
+            eprintln!("{e}");
+            process::exit(1);
         }
-        2 => {
-            let arg = &args[1];
-            if arg == "--help" || arg == "-h" {
+        return;
+    }
+
+    let cli = match Cli::parse(&args) {
+        Ok(cli) => cli,
+        Err(e) => {
+            eprintln!("Error: {e}");
+            if e.starts_with("Unknown option") {
                 print_usage();
-            } else if arg == "--jit" {
-                repl_with_jit(true);
-            } else {
-                // Run file
-                if let Err(e) = run_file(arg) {
-                    eprintln!("{e}");
-                    process::exit(1);
-                }
             }
+            process::exit(1);
         }
-        3 => {
-            // --jit <file>
-            if args[1] == "--jit" {
-                if let Err(e) = run_file_jit(&args[2]) {
-                    eprintln!("{e}");
-                    process::exit(1);
-                }
-            } else {
-                eprintln!("Error: Invalid arguments");
-                print_usage();
-                process::exit(1);
+    };
+    if cli.help {
+        print_usage();
+        return;
+    }
+    let mode = cli.mode(io::stdin().is_terminal()).unwrap_or_else(|e| {
+        eprintln!("Error: {e}");
+        process::exit(1);
+    });
+    let stdlib = cli.stdlib();
+
+    let result = match mode {
+        Mode::Repl => {
+            let mut init_files = Vec::new();
+            if !cli.no_init {
+                init_files.extend(default_init_file(
+                    env::var_os("CONSAIR_INIT").map(PathBuf::from),
+                    dirs::home_dir(),
+                ));
             }
+            init_files.extend(cli.init_file.clone());
+            repl_with_jit(cli.jit_mode(), &init_files, &stdlib);
+            Ok(())
         }
-        _ => {
-            eprintln!("Error: Too many arguments");
-            print_usage();
-            process::exit(1);
+        Mode::File(file) if cli.jit => run_file_jit_report_with(&file, cli.main_args(), &stdlib)
+            .map(|report| print_report(&report, cli.verbose, cli.print_last)),
+        Mode::File(file) => read_source(&file).and_then(|contents| {
+            let program = Program {
+                name: &file,
+                source: &contents,
+                file: Some(Path::new(&file)),
+            };
+            run_program(&program, cli.print_last, cli.main_args(), &stdlib)
+        }),
+        Mode::Stdin => read_stdin().and_then(|contents| {
+            let program = Program {
+                name: "<stdin>",
+                source: &contents,
+                file: None,
+            };
+            if cli.jit {
+                run_program_jit(
+                    &program,
+                    cli.verbose,
+                    cli.print_last,
+                    cli.main_args(),
+                    &stdlib,
+                )
+            } else {
+                run_program(&program, cli.print_last, cli.main_args(), &stdlib)
+            }
+        }),
+        // The value of the last expression is the point of --eval
+        Mode::Eval(source) => {
+            let program = Program {
+                name: "<eval>",
+                source: &source,
+                file: None,
+            };
+            if cli.jit {
+                run_program_jit(&program, cli.verbose, true, None, &stdlib)
+            } else {
+                run_program(&program, true, None, &stdlib)
+            }
         }
+    };
+
+    if let Err(e) = result {
+        eprintln!("{e}");
+        process::exit(1);
     }
 }
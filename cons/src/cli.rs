@@ -0,0 +1,148 @@
+//! Command-line arguments of the `cons` binary
+//!
+//! `Cli::parse` reads the options and checks those that conflict whatever
+//! runs; `Cli::mode` then decides what to run, which depends on whether
+//! stdin is a terminal, and checks the options that only make sense for
+//! some modes. Both are kept out of the binary so they can be tested.
+
+use std::path::PathBuf;
+
+use crate::repl::JitMode;
+use crate::stdlib::StdlibConfig;
+
+/// The options given to `cons`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Cli {
+    /// `--help`: print usage and do nothing else
+    pub help: bool,
+    pub jit: bool,
+    pub no_jit: bool,
+    pub verbose: bool,
+    pub print_last: bool,
+    pub no_init: bool,
+    pub no_main: bool,
+    pub sandbox: bool,
+    /// File given with `--init`
+    pub init_file: Option<PathBuf>,
+    /// Expressions given with `-e`/`--eval`, in order
+    pub eval: Vec<String>,
+    /// The file to run
+    pub file: Option<String>,
+    /// Arguments after the file, passed to its `main`
+    pub script_args: Vec<String>,
+}
+
+/// What `cons` runs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Mode {
+    /// The interactive REPL
+    Repl,
+    /// A source file
+    File(String),
+    /// The source piped to stdin, run like a file
+    Stdin,
+    /// The `--eval` expressions, joined into one source
+    Eval(String),
+}
+
+impl Cli {
+    /// Parse the arguments after the program name.
+    ///
+    /// Options come before the file; everything after it is for the script.
+    pub fn parse(args: &[String]) -> Result<Cli, String> {
+        let mut cli = Cli::default();
+        let mut args = args.iter();
+        while cli.file.is_none()
+            && let Some(arg) = args.next()
+        {
+            match arg.as_str() {
+                "--help" | "-h" => {
+                    cli.help = true;
+                    return Ok(cli);
+                }
+                "--jit" => cli.jit = true,
+                "--no-jit" => cli.no_jit = true,
+                "--verbose" | "-v" => cli.verbose = true,
+                "--print-last" => cli.print_last = true,
+                "--no-init" => cli.no_init = true,
+                "--no-main" => cli.no_main = true,
+                "--sandbox" => cli.sandbox = true,
+                "--init" => match args.next() {
+                    Some(path) => cli.init_file = Some(PathBuf::from(path)),
+                    None => return Err("--init requires a file".to_string()),
+                },
+                "--eval" | "-e" => match args.next() {
+                    Some(expr) => cli.eval.push(expr.clone()),
+                    None => return Err(format!("{arg} requires an expression")),
+                },
+                _ if arg.starts_with('-') => {
+                    return Err(format!("Unknown option '{arg}'"));
+                }
+                _ => cli.file = Some(arg.clone()),
+            }
+        }
+        cli.script_args = args.cloned().collect();
+
+        if cli.jit && cli.no_jit {
+            return Err("--jit and --no-jit cannot be used together".to_string());
+        }
+        if !cli.eval.is_empty() && cli.file.is_some() {
+            return Err("--eval cannot be used with a file".to_string());
+        }
+        Ok(cli)
+    }
+
+    /// What to run, given whether stdin is a terminal.
+    ///
+    /// Expressions from `--eval` come first, then a file. With neither, a
+    /// terminal gets the REPL and anything else is read as a program.
+    pub fn mode(&self, stdin_is_terminal: bool) -> Result<Mode, String> {
+        let mode = if !self.eval.is_empty() {
+            Mode::Eval(self.eval.join("\n"))
+        } else if let Some(file) = &self.file {
+            Mode::File(file.clone())
+        } else if stdin_is_terminal {
+            Mode::Repl
+        } else {
+            Mode::Stdin
+        };
+
+        if self.verbose && !(self.jit && mode != Mode::Repl) {
+            return Err(
+                "--verbose is only supported when running a program with --jit".to_string(),
+            );
+        }
+        if (self.no_init || self.init_file.is_some()) && mode != Mode::Repl {
+            return Err("--init and --no-init only apply to the REPL".to_string());
+        }
+        if self.no_main && matches!(mode, Mode::Repl | Mode::Eval(_)) {
+            return Err("--no-main only applies when running a file".to_string());
+        }
+        Ok(mode)
+    }
+
+    /// The arguments for a program's `main`, unless `--no-main` was given.
+    pub fn main_args(&self) -> Option<&[String]> {
+        (!self.no_main).then_some(self.script_args.as_slice())
+    }
+
+    /// The standard library to register: all of it, or the sandboxed part.
+    pub fn stdlib(&self) -> StdlibConfig {
+        if self.sandbox {
+            StdlibConfig::sandboxed()
+        } else {
+            StdlibConfig::new()
+        }
+    }
+
+    /// How the REPL treats the JIT engine.
+    pub fn jit_mode(&self) -> JitMode {
+        if self.jit {
+            JitMode::Enabled
+        } else if self.no_jit {
+            JitMode::Disabled
+        } else {
+            JitMode::Available
+        }
+    }
+}
@@ -33,6 +33,14 @@ pub struct Codegen<'ctx> {
     pub rt_mul: FunctionValue<'ctx>,
     pub rt_div: FunctionValue<'ctx>,
     pub rt_neg: FunctionValue<'ctx>,
+    pub rt_mod: FunctionValue<'ctx>,
+    pub rt_abs: FunctionValue<'ctx>,
+    pub rt_bit_and: FunctionValue<'ctx>,
+    pub rt_bit_or: FunctionValue<'ctx>,
+    pub rt_bit_xor: FunctionValue<'ctx>,
+    pub rt_bit_not: FunctionValue<'ctx>,
+    pub rt_bit_shift_left: FunctionValue<'ctx>,
+    pub rt_bit_shift_right: FunctionValue<'ctx>,
     pub rt_num_eq: FunctionValue<'ctx>,
     pub rt_lt: FunctionValue<'ctx>,
     pub rt_gt: FunctionValue<'ctx>,
@@ -97,6 +105,14 @@ impl<'ctx> Codegen<'ctx> {
             rt_mul: unsafe { std::mem::zeroed() },
             rt_div: unsafe { std::mem::zeroed() },
             rt_neg: unsafe { std::mem::zeroed() },
+            rt_mod: unsafe { std::mem::zeroed() },
+            rt_abs: unsafe { std::mem::zeroed() },
+            rt_bit_and: unsafe { std::mem::zeroed() },
+            rt_bit_or: unsafe { std::mem::zeroed() },
+            rt_bit_xor: unsafe { std::mem::zeroed() },
+            rt_bit_not: unsafe { std::mem::zeroed() },
+            rt_bit_shift_left: unsafe { std::mem::zeroed() },
+            rt_bit_shift_right: unsafe { std::mem::zeroed() },
             rt_num_eq: unsafe { std::mem::zeroed() },
             rt_lt: unsafe { std::mem::zeroed() },
             rt_gt: unsafe { std::mem::zeroed() },
@@ -143,6 +159,14 @@ impl<'ctx> Codegen<'ctx> {
         codegen.rt_mul = codegen.declare_binary_fn("rt_mul");
         codegen.rt_div = codegen.declare_binary_fn("rt_div");
         codegen.rt_neg = codegen.declare_unary_fn("rt_neg");
+        codegen.rt_mod = codegen.declare_binary_fn("rt_mod");
+        codegen.rt_abs = codegen.declare_unary_fn("rt_abs");
+        codegen.rt_bit_and = codegen.declare_binary_fn("rt_bit_and");
+        codegen.rt_bit_or = codegen.declare_binary_fn("rt_bit_or");
+        codegen.rt_bit_xor = codegen.declare_binary_fn("rt_bit_xor");
+        codegen.rt_bit_not = codegen.declare_unary_fn("rt_bit_not");
+        codegen.rt_bit_shift_left = codegen.declare_binary_fn("rt_bit_shift_left");
+        codegen.rt_bit_shift_right = codegen.declare_binary_fn("rt_bit_shift_right");
         codegen.rt_num_eq = codegen.declare_binary_fn("rt_num_eq");
         codegen.rt_lt = codegen.declare_binary_fn("rt_lt");
         codegen.rt_gt = codegen.declare_binary_fn("rt_gt");
@@ -476,6 +500,61 @@ impl<'ctx> Codegen<'ctx> {
             .unwrap()
             .into_struct_value()
     }
+
+    // ========================================================================
+    // Truthiness
+    // ========================================================================
+
+    /// Emit a test for whether a value is falsy.
+    ///
+    /// Matches `Value::is_truthy`: only nil and false are falsy, so 0, empty
+    /// strings and empty collections are truthy.
+    pub fn build_is_falsy(
+        &self,
+        value: inkwell::values::StructValue<'ctx>,
+    ) -> Result<inkwell::values::IntValue<'ctx>, String> {
+        let tag = self
+            .builder
+            .build_extract_value(value, 0, "tag")
+            .map_err(|e| e.to_string())?
+            .into_int_value();
+        let data = self
+            .builder
+            .build_extract_value(value, 1, "data")
+            .map_err(|e| e.to_string())?
+            .into_int_value();
+
+        let tag_is = |tag_value: u8, name: &str| {
+            self.builder
+                .build_int_compare(
+                    inkwell::IntPredicate::EQ,
+                    tag,
+                    self.i8_type().const_int(tag_value as u64, false),
+                    name,
+                )
+                .map_err(|e| e.to_string())
+        };
+        let is_nil = tag_is(crate::runtime::TAG_NIL, "is_nil")?;
+        let is_bool = tag_is(crate::runtime::TAG_BOOL, "is_bool")?;
+        let is_zero = self
+            .builder
+            .build_int_compare(
+                inkwell::IntPredicate::EQ,
+                data,
+                self.i64_type().const_int(0, false),
+                "is_false_data",
+            )
+            .map_err(|e| e.to_string())?;
+
+        // Falsy if nil OR (bool AND data==0)
+        let is_false = self
+            .builder
+            .build_and(is_bool, is_zero, "is_false")
+            .map_err(|e| e.to_string())?;
+        self.builder
+            .build_or(is_nil, is_false, "is_falsy")
+            .map_err(|e| e.to_string())
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,271 @@
+//! API documentation generator
+//!
+//! `cons doc <file.lisp>` lists the functions and macros a file defines, and
+//! `cons doc --stdlib` lists the native standard library and the prelude
+//! macros. Files are read without being evaluated, so documenting a script
+//! never runs it.
+//!
+//! A definition is a top-level `(label name (lambda params ...))` or
+//! `(defmacro name params ...)`. Its documentation is the block of `;`
+//! comment lines directly above it, or failing that its docstring. Names
+//! starting with `%` are treated as private and left out.
+
+use std::fmt::Write;
+
+use consair::language::{AtomType, SymbolType, Value};
+use consair::parse_all_with_spans;
+
+use crate::interpreter::docstring;
+use crate::prelude::PRELUDE;
+use crate::stdlib::{STDLIB, native_entry};
+
+/// What kind of value a documented name is bound to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DocKind {
+    /// A lambda bound with label
+    Function,
+    /// A macro defined with defmacro
+    Macro,
+    /// A native standard library function
+    Native,
+}
+
+impl DocKind {
+    fn label(self) -> &'static str {
+        match self {
+            DocKind::Function => "function",
+            DocKind::Macro => "macro",
+            DocKind::Native => "native function",
+        }
+    }
+}
+
+/// Documentation for one definition.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DocEntry {
+    pub name: String,
+    pub kind: DocKind,
+    /// Parameters as written, without the surrounding parentheses
+    pub params: String,
+    /// Comment text above the definition or its docstring, if any
+    pub doc: Option<String>,
+    /// 1-based source line of the definition; 0 for natives
+    pub line: usize,
+}
+
+impl DocEntry {
+    /// How a call looks, e.g. `(connect host &key port)`.
+    pub fn signature(&self) -> String {
+        if self.params.is_empty() {
+            format!("({})", self.name)
+        } else {
+            format!("({} {})", self.name, self.params)
+        }
+    }
+}
+
+/// Collect the public definitions in `source` without evaluating it.
+pub fn extract_docs(source: &str) -> Result<Vec<DocEntry>, String> {
+    let mut entries = Vec::new();
+    for form in parse_all_with_spans(source)? {
+        if let Some((name, kind, params, docstring)) = definition(&form.value)
+            && !name.starts_with('%')
+        {
+            entries.push(DocEntry {
+                name,
+                kind,
+                params,
+                doc: leading_comment(&source[..form.span.offset]).or(docstring),
+                line: form.span.line,
+            });
+        }
+    }
+    Ok(entries)
+}
+
+/// Documentation entries for every public native in the standard library,
+/// followed by the prelude macros.
+pub fn stdlib_docs() -> Vec<DocEntry> {
+    let prelude = extract_docs(PRELUDE)
+        .expect("prelude parses")
+        .into_iter()
+        .map(|entry| DocEntry { line: 0, ..entry });
+    STDLIB
+        .iter()
+        .filter(|entry| !entry.name.starts_with('%'))
+        .map(|entry| DocEntry {
+            name: entry.name.to_string(),
+            kind: DocKind::Native,
+            params: entry.params.to_string(),
+            doc: Some(entry.doc.to_string()),
+            line: 0,
+        })
+        .chain(prelude)
+        .collect()
+}
+
+/// What `describe` and the REPL's `:doc` report for `name`, bound to
+/// `value`: what kind of value it is and, for a function or macro, how a
+/// call looks and its documentation.
+pub fn describe(name: &str, value: Option<&Value>) -> String {
+    let Some(value) = value else {
+        return format!("{name}: unbound");
+    };
+    let (kind, params, doc) = match value {
+        Value::Lambda(lambda) => (
+            DocKind::Function,
+            Some(lambda.params.to_string()),
+            lambda.doc.clone(),
+        ),
+        Value::Macro(mc) => (DocKind::Macro, Some(mc.params.to_string()), mc.doc.clone()),
+        Value::NativeFn(f) => match native_entry(*f) {
+            Some(entry) => (
+                DocKind::Native,
+                Some(format!("({})", entry.params)),
+                Some(entry.doc.to_string()),
+            ),
+            None => (DocKind::Native, None, None),
+        },
+        Value::NativeClosure(_) => (DocKind::Native, None, None),
+        _ => return format!("{name}: {}", value_kind(value)),
+    };
+
+    let mut out = format!("{name}: {}", kind.label());
+    if let Some(params) = params {
+        let entry = DocEntry {
+            name: name.to_string(),
+            kind,
+            params: params[1..params.len() - 1].to_string(),
+            doc: None,
+            line: 0,
+        };
+        let _ = write!(out, "\n{}", entry.signature());
+    }
+    let _ = write!(out, "\n{}", doc.as_deref().unwrap_or("no documentation"));
+    out
+}
+
+/// The kind of a value that is not a function or macro.
+fn value_kind(value: &Value) -> &'static str {
+    match value {
+        Value::Nil => "nil",
+        Value::Atom(AtomType::Bool(_)) => "boolean",
+        Value::Atom(AtomType::Number(_)) => "number",
+        Value::Atom(AtomType::String(_)) => "string",
+        Value::Atom(AtomType::Char(_)) => "character",
+        Value::Atom(AtomType::Symbol(SymbolType::Symbol(sym))) => {
+            if sym.resolve().starts_with(':') {
+                "keyword"
+            } else {
+                "symbol"
+            }
+        }
+        Value::Cons(_) => "list",
+        Value::Vector(_) | Value::PersistentVector(_) => "vector",
+        Value::Map(_) | Value::PersistentMap(_) => "map",
+        Value::Set(_) | Value::PersistentSet(_) => "set",
+        Value::Reduced(_) => "reduced value",
+        Value::Ref(_) => "ref",
+        Value::Lambda(_) | Value::Macro(_) | Value::NativeFn(_) | Value::NativeClosure(_) => {
+            "function"
+        }
+    }
+}
+
+/// Render entries as Markdown, one section per definition.
+pub fn render_markdown(title: &str, entries: &[DocEntry]) -> String {
+    let mut out = format!("# {title}\n");
+    for entry in entries {
+        let _ = write!(out, "\n## `{}`\n\n", entry.name);
+        let _ = write!(out, "```lisp\n{}\n```\n\n", entry.signature());
+        if let Some(ref doc) = entry.doc {
+            let _ = write!(out, "{doc}\n\n");
+        }
+        if entry.line > 0 {
+            let _ = writeln!(out, "*{}, line {}*", entry.kind.label(), entry.line);
+        } else {
+            let _ = writeln!(out, "*{}*", entry.kind.label());
+        }
+    }
+    out
+}
+
+/// Recognize `(label name (lambda params ...))` and `(defmacro name params ...)`,
+/// with the docstring of the lambda or macro.
+fn definition(expr: &Value) -> Option<(String, DocKind, String, Option<String>)> {
+    let items = list_items(expr)?;
+    let head = symbol_name(items.first()?)?;
+    let name = symbol_name(items.get(1)?)?;
+    match head.as_str() {
+        "label" => {
+            let lambda = list_items(items.get(2)?)?;
+            if symbol_name(lambda.first()?)? != "lambda" {
+                return None;
+            }
+            Some((
+                name,
+                DocKind::Function,
+                params_text(lambda.get(1)?),
+                lambda.get(2..).and_then(docstring),
+            ))
+        }
+        "defmacro" => Some((
+            name,
+            DocKind::Macro,
+            params_text(items.get(2)?),
+            items.get(3..).and_then(docstring),
+        )),
+        _ => None,
+    }
+}
+
+fn list_items(expr: &Value) -> Option<Vec<Value>> {
+    let mut items = Vec::new();
+    let mut current = expr;
+    while let Value::Cons(cell) = current {
+        items.push(cell.car.clone());
+        current = &cell.cdr;
+    }
+    (!items.is_empty()).then_some(items)
+}
+
+fn symbol_name(value: &Value) -> Option<String> {
+    match value {
+        Value::Atom(AtomType::Symbol(SymbolType::Symbol(sym))) => Some(sym.resolve()),
+        _ => None,
+    }
+}
+
+/// Parameter list without its outer parentheses.
+fn params_text(params: &Value) -> String {
+    match params {
+        Value::Nil => String::new(),
+        Value::Cons(_) => {
+            let text = params.to_string();
+            text[1..text.len() - 1].to_string()
+        }
+        other => other.to_string(),
+    }
+}
+
+/// The `;` comment lines immediately above the end of `before`.
+fn leading_comment(before: &str) -> Option<String> {
+    let mut lines: Vec<&str> = before.lines().collect();
+    // `before` ends at the start of the definition's line
+    if !before.ends_with('\n') {
+        lines.pop();
+    }
+    let mut doc = Vec::new();
+    for line in lines.iter().rev() {
+        let trimmed = line.trim();
+        if !trimmed.starts_with(';') {
+            break;
+        }
+        doc.push(trimmed.trim_start_matches(';').trim());
+    }
+    if doc.is_empty() {
+        return None;
+    }
+    doc.reverse();
+    Some(doc.join("\n"))
+}
@@ -0,0 +1,58 @@
+//! One-call embedding
+//!
+//! Evaluate a string of Lisp source and get the result back, without setting
+//! up an environment by hand. `run` starts from a fresh environment with the
+//! standard library every time; `run_with` reuses one, so definitions carry
+//! over between calls. Source may contain any number of top-level forms and
+//! the value of the last one is returned.
+
+use consair::lexer::Lexer;
+use consair::parser::Parser;
+use consair::{EvalError, Value};
+
+use crate::interpreter::{Environment, eval};
+use crate::native::FromValue;
+use crate::stdlib::register_stdlib;
+
+/// Evaluate `src` in a fresh environment with the standard library.
+///
+/// ```
+/// let value = cons::run("(label sq (lambda (x) (* x x))) (sq 7)").unwrap();
+/// assert_eq!(value.to_string(), "49");
+/// ```
+pub fn run(src: &str) -> Result<Value, EvalError> {
+    let mut env = Environment::new();
+    register_stdlib(&mut env);
+    run_with(&mut env, src)
+}
+
+/// Evaluate `src` in `env`, keeping any definitions it makes.
+///
+/// ```
+/// use cons::{Environment, register_stdlib, run_with};
+///
+/// let mut env = Environment::new();
+/// register_stdlib(&mut env);
+/// run_with(&mut env, "(label base 40)").unwrap();
+/// assert_eq!(run_with(&mut env, "(+ base 2)").unwrap().to_string(), "42");
+/// ```
+pub fn run_with(env: &mut Environment, src: &str) -> Result<Value, EvalError> {
+    let mut lexer = Lexer::new(src);
+    let mut parser = Parser::new(&mut lexer);
+    let mut result = Value::Nil;
+    while !parser.is_at_end() {
+        result = eval(parser.parse_expression()?, env)?;
+    }
+    Ok(result)
+}
+
+/// Evaluate `src` like `run` and convert the result to `T`.
+///
+/// ```
+/// assert_eq!(cons::run_as::<i64>("(+ 1 2 3)").unwrap(), 6);
+/// assert_eq!(cons::run_as::<Vec<i64>>("(map (lambda (x) (* x 2)) '(1 2))").unwrap(), vec![2, 4]);
+/// assert!(cons::run_as::<String>("42").is_err());
+/// ```
+pub fn run_as<T: FromValue>(src: &str) -> Result<T, EvalError> {
+    T::from_value(&run(src)?)
+}
@@ -1,8 +1,20 @@
+use std::cell::{Cell, RefCell};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use consair::interner::InternedSymbol;
-use consair::language::{AtomType, LambdaCell, MacroCell, SymbolType, Value, car, cdr, cons};
-use consair::numeric::NumericType;
+use consair::language::{
+    AtomType, LambdaCell, MacroCell, NativeClosureFn, ParamSpec, StringType, SymbolType, Value,
+    car, cdr, cons, print_limits, set_print_limits,
+};
+use consair::numeric::{NumericType, print_float_digits, set_print_float_digits};
+use consair::{Arity, EvalError};
+
+use crate::native::{list_to_vec, make_string, make_symbol, vec_to_alist, vec_to_list};
+use crate::runner::snippet;
+use crate::stdlib::fresh_symbol;
 
 // Re-export Environment from core
 pub use consair::Environment;
@@ -11,22 +23,49 @@ pub use consair::Environment;
 // Evaluator with Tail Call Optimization
 // ============================================================================
 
-/// Maximum recursion depth for non-tail calls
-const MAX_DEPTH: usize = 10000;
+/// Stack left when `eval_loop` moves to a new stack segment. It must hold
+/// the deepest chain of frames between two `eval_loop` calls in an
+/// unoptimized build.
+const STACK_RED_ZONE: usize = 256 * 1024;
+
+/// Size of each stack segment allocated for deep recursion
+const STACK_SEGMENT: usize = 4 * 1024 * 1024;
 
-pub fn eval(expr: Value, env: &mut Environment) -> Result<Value, String> {
-    eval_loop(expr, env, 0)
+thread_local! {
+    /// Depth of the innermost native function call, so that natives which
+    /// call back into `eval` or `apply` continue the count instead of
+    /// restarting it
+    static NATIVE_DEPTH: Cell<usize> = const { Cell::new(0) };
 }
 
-fn eval_loop(mut expr: Value, env: &mut Environment, depth: usize) -> Result<Value, String> {
-    // Track depth for non-tail recursive calls
-    if depth >= MAX_DEPTH {
-        return Err(format!(
-            "Maximum recursion depth ({MAX_DEPTH}) exceeded. \
-             This usually indicates very deep non-tail recursion."
-        ));
+fn recursion_error(limit: usize) -> EvalError {
+    format!("maximum recursion depth {limit} exceeded").into()
+}
+
+/// Run `f` with enough stack for one more level of evaluation, growing the
+/// stack on the heap if needed. Depth is bounded by the recursion limit
+/// instead of the thread's stack size.
+fn with_stack<R>(f: impl FnOnce() -> R) -> R {
+    stacker::maybe_grow(STACK_RED_ZONE, STACK_SEGMENT, f)
+}
+
+pub fn eval(expr: Value, env: &mut Environment) -> Result<Value, EvalError> {
+    eval_loop(expr, env, NATIVE_DEPTH.get())
+}
+
+/// Evaluate `expr` one level deeper than its caller.
+///
+/// Non-tail calls nest; once they are `env.recursion_limit()` deep,
+/// evaluation fails instead of overflowing the stack.
+fn eval_loop(expr: Value, env: &mut Environment, depth: usize) -> Result<Value, EvalError> {
+    let limit = env.recursion_limit();
+    if depth >= limit {
+        return Err(recursion_error(limit));
     }
+    with_stack(|| eval_frame(expr, env, depth))
+}
 
+fn eval_frame(mut expr: Value, env: &mut Environment, depth: usize) -> Result<Value, EvalError> {
     // Start with the passed-in environment
     // For tail calls within the same scope, we keep this
     // For tail calls to lambdas, we'll replace it
@@ -38,14 +77,20 @@ fn eval_loop(mut expr: Value, env: &mut Environment, depth: usize) -> Result<Val
             Value::Atom(AtomType::Number(_))
             | Value::Atom(AtomType::Bool(_))
             | Value::Atom(AtomType::String(_))
+            | Value::Atom(AtomType::Char(_))
             | Value::Nil => return Ok(expr),
 
             // Symbol lookup
             Value::Atom(AtomType::Symbol(SymbolType::Symbol(ref name))) => {
                 return name.with_str(|s| {
+                    // Keywords (:name) evaluate to themselves
+                    if s.starts_with(':') {
+                        return Ok(expr.clone());
+                    }
                     current_env
                         .lookup(s)
-                        .ok_or_else(|| format!("Unbound symbol: {name}"))
+                        .or_else(|| dynamic_var(s))
+                        .ok_or(EvalError::UnboundSymbol(*name))
                 });
             }
 
@@ -59,7 +104,9 @@ fn eval_loop(mut expr: Value, env: &mut Environment, depth: usize) -> Result<Val
             | Value::PersistentMap(_)
             | Value::PersistentSet(_)
             | Value::Reduced(_)
-            | Value::NativeFn(_) => {
+            | Value::NativeFn(_)
+            | Value::NativeClosure(_)
+            | Value::Ref(_) => {
                 return Ok(expr);
             }
 
@@ -75,124 +122,210 @@ fn eval_loop(mut expr: Value, env: &mut Environment, depth: usize) -> Result<Val
                     // Special forms
                     if let Value::Atom(AtomType::Symbol(SymbolType::Symbol(name))) = operator {
                         let sym_str = name.resolve();
+                        if let Some(step) =
+                            eval_control_form(&sym_str, &cell.cdr, &mut current_env, depth)
+                        {
+                            match step? {
+                                Step::Done(value) => return Ok(value),
+                                Step::Tail(tail_expr, tail_env) => {
+                                    // TAIL POSITION: the sub-expression replaces the form
+                                    expr = tail_expr;
+                                    current_env = tail_env;
+                                    continue 'outer;
+                                }
+                            }
+                        }
                         match sym_str.as_str() {
                             "quote" => {
                                 let arg = car(&cell.cdr)?;
                                 return Ok(arg);
                             }
+                            "comment" => {
+                                // Sub-forms are parsed but never evaluated
+                                return Ok(Value::Nil);
+                            }
+                            "binding" => {
+                                let bindings = car(&cell.cdr)?;
+                                let body = car(&cdr(&cell.cdr)?)?;
+                                return eval_binding(&bindings, body, &mut current_env, depth);
+                            }
+                            "with-redefs" => {
+                                let bindings = car(&cell.cdr)?;
+                                let body = cdr(&cell.cdr)?;
+                                return eval_with_redefs(&bindings, &body, &mut current_env, depth);
+                            }
+                            "with-sandbox" => {
+                                let body = cell.cdr.clone();
+                                return eval_with_sandbox(&body, &mut current_env, depth);
+                            }
+                            "with-precision" => {
+                                // (with-precision n body) is (binding ((*print-float-digits* n)) body)
+                                let digits = car(&cell.cdr)?;
+                                let body = car(&cdr(&cell.cdr)?)?;
+                                let var = Value::Atom(AtomType::Symbol(SymbolType::Symbol(
+                                    InternedSymbol::new("*print-float-digits*"),
+                                )));
+                                let bindings =
+                                    cons(cons(var, cons(digits, Value::Nil)), Value::Nil);
+                                return eval_binding(&bindings, body, &mut current_env, depth);
+                            }
+                            "define-constant" => {
+                                check_unfrozen("define-constant", &current_env)?;
+                                let name = match car(&cell.cdr)? {
+                                    Value::Atom(AtomType::Symbol(SymbolType::Symbol(n))) => {
+                                        n.resolve()
+                                    }
+                                    _ => {
+                                        return Err("define-constant: first argument must be a \
+                                                    symbol"
+                                            .to_string()
+                                            .into());
+                                    }
+                                };
+                                let value_expr = car(&cdr(&cell.cdr)?)?;
+                                let value = eval_loop(value_expr, &mut current_env, depth + 1)?;
+                                let global = current_env.global();
+                                let existing = if global.is_constant(&name) {
+                                    global.lookup(&name)
+                                } else {
+                                    None
+                                };
+                                check_constant(&name, existing.as_ref(), &value)?;
+                                if existing.is_none() {
+                                    global.define_constant(name, value.clone());
+                                }
+                                return Ok(value);
+                            }
                             "quasiquote" => {
                                 let arg = car(&cell.cdr)?;
-                                return eval_quasiquote(arg, &mut current_env, depth, 0);
+                                return eval_quasiquote(
+                                    arg,
+                                    &mut current_env,
+                                    depth,
+                                    0,
+                                    &mut HashMap::new(),
+                                );
                             }
                             "defmacro" => {
+                                check_unfrozen("defmacro", env)?;
                                 let name_expr = car(&cell.cdr)?;
                                 let rest = cdr(&cell.cdr)?;
                                 let params_expr = car(&rest)?;
-                                let body = car(&cdr(&rest)?)?;
+                                let forms = list_to_vec(&cdr(&rest)?)?;
+                                let body = lambda_body(&forms);
 
                                 // Extract macro name
                                 let name = match name_expr {
                                     Value::Atom(AtomType::Symbol(SymbolType::Symbol(n))) => n,
                                     _ => {
                                         return Err(
-                                            "defmacro: first argument must be a symbol".to_string()
+                                            "defmacro: first argument must be a symbol".into()
                                         );
                                     }
                                 };
 
-                                // Extract parameter names
-                                let mut params = Vec::new();
-                                let mut current_param = params_expr;
-                                while let Value::Cons(ref param_cell) = current_param {
-                                    if let Value::Atom(AtomType::Symbol(SymbolType::Symbol(
-                                        param_name,
-                                    ))) = &param_cell.car
-                                    {
-                                        params.push(*param_name);
-                                    } else {
-                                        return Err(
-                                            "defmacro parameters must be symbols".to_string()
-                                        );
-                                    }
-                                    current_param = param_cell.cdr.clone();
-                                }
+                                let params = ParamSpec::parse(&params_expr, "defmacro")?;
 
                                 // Create macro
                                 let macro_val = Value::Macro(Arc::new(MacroCell {
                                     params,
                                     body,
                                     env: current_env.clone(),
+                                    doc: docstring(&forms),
                                 }));
 
                                 // Define in environment
                                 env.define(name.resolve(), macro_val.clone());
                                 return Ok(macro_val);
                             }
-                            "cond" => {
-                                // TAIL POSITION: cond result expressions are in tail position
-                                let mut clauses = cell.cdr.clone();
-                                while let Value::Cons(ref clause_cell) = clauses {
-                                    let clause = clause_cell.car.clone();
-                                    let condition = car(&clause)?;
-                                    let result_expr = car(&cdr(&clause)?)?;
-
-                                    // Evaluate condition (NOT tail position)
-                                    let cond_val =
-                                        eval_loop(condition, &mut current_env, depth + 1)?;
-                                    let is_true = !matches!(
-                                        cond_val,
-                                        Value::Nil | Value::Atom(AtomType::Bool(false))
-                                    );
-
-                                    if is_true {
-                                        // TAIL CALL: update expr and continue loop
-                                        expr = result_expr;
-                                        continue 'outer;
-                                    }
-
-                                    clauses = clause_cell.cdr.clone();
-                                }
-                                return Ok(Value::Nil);
-                            }
                             "lambda" => {
                                 let params_expr = car(&cell.cdr)?;
-                                let body = car(&cdr(&cell.cdr)?)?;
+                                let forms = list_to_vec(&cdr(&cell.cdr)?)?;
+                                let body = lambda_body(&forms);
 
-                                // Extract parameter names
-                                let mut params = Vec::new();
-                                let mut current = params_expr;
-                                while let Value::Cons(ref param_cell) = current {
-                                    if let Value::Atom(AtomType::Symbol(SymbolType::Symbol(name))) =
-                                        &param_cell.car
-                                    {
-                                        params.push(*name);
-                                    } else {
-                                        return Err("lambda parameters must be symbols".to_string());
-                                    }
-                                    current = param_cell.cdr.clone();
-                                }
+                                let params = ParamSpec::parse(&params_expr, "lambda")?;
 
                                 return Ok(Value::Lambda(Arc::new(LambdaCell {
                                     params,
                                     body,
                                     env: current_env.clone(),
+                                    doc: docstring(&forms),
                                 })));
                             }
                             "label" => {
+                                check_unfrozen("label", env)?;
                                 let name_expr = car(&cell.cdr)?;
                                 let fn_expr = car(&cdr(&cell.cdr)?)?;
 
                                 if let Value::Atom(AtomType::Symbol(SymbolType::Symbol(name))) =
                                     name_expr
                                 {
+                                    if env.is_constant(&name.resolve()) {
+                                        return Err(format!(
+                                            "label: cannot redefine constant {name}"
+                                        )
+                                        .into());
+                                    }
                                     let fn_val = eval_loop(fn_expr, &mut current_env, depth + 1)?;
                                     env.define(name.resolve(), fn_val.clone());
                                     return Ok(fn_val);
                                 } else {
+                                    return Err("label: first argument must be a symbol".into());
+                                }
+                            }
+                            "def" => {
+                                check_unfrozen("def", &current_env)?;
+                                let name = match car(&cell.cdr)? {
+                                    Value::Atom(AtomType::Symbol(SymbolType::Symbol(n))) => {
+                                        n.resolve()
+                                    }
+                                    _ => {
+                                        return Err("def: first argument must be a symbol".into());
+                                    }
+                                };
+                                let global = current_env.global();
+                                if global.is_constant(&name) {
                                     return Err(
-                                        "label: first argument must be a symbol".to_string()
+                                        format!("def: cannot redefine constant {name}").into()
                                     );
                                 }
+                                let value_expr = car(&cdr(&cell.cdr)?)?;
+                                let value = eval_loop(value_expr, &mut current_env, depth + 1)?;
+                                global.define(name, value.clone());
+                                return Ok(value);
+                            }
+                            "set!" => {
+                                check_unfrozen("set!", &current_env)?;
+                                let name = match car(&cell.cdr)? {
+                                    Value::Atom(AtomType::Symbol(SymbolType::Symbol(n))) => n,
+                                    _ => {
+                                        return Err("set!: first argument must be a symbol".into());
+                                    }
+                                };
+                                let key = name.resolve();
+                                if current_env.is_constant(&key) {
+                                    return Err(
+                                        format!("set!: cannot change constant {key}").into()
+                                    );
+                                }
+                                let value_expr = car(&cdr(&cell.cdr)?)?;
+                                let value = eval_loop(value_expr, &mut current_env, depth + 1)?;
+                                return match current_env.set(&key, value.clone()) {
+                                    Some(_) => Ok(value),
+                                    None => Err(EvalError::UnboundSymbol(name)),
+                                };
+                            }
+                            "try" => {
+                                return eval_try(&cell.cdr, &mut current_env, depth);
+                            }
+                            "dotimes" => {
+                                return eval_dotimes(&cell.cdr, &mut current_env, depth);
+                            }
+                            "while" => {
+                                return eval_while(&cell.cdr, &mut current_env, depth);
+                            }
+                            "time" => {
+                                return eval_time(&cell.cdr, &mut current_env, depth);
                             }
                             // Vector operations (NOT tail position)
                             "vector-length" => {
@@ -202,7 +335,7 @@ fn eval_loop(mut expr: Value, env: &mut Environment, depth: usize) -> Result<Val
                                     Value::Vector(vec) => Ok(Value::Atom(AtomType::Number(
                                         NumericType::Int(vec.elements.len() as i64),
                                     ))),
-                                    _ => Err("vector-length: expected vector".to_string()),
+                                    _ => Err("vector-length: expected vector".into()),
                                 };
                             }
                             "vector-ref" => {
@@ -223,18 +356,16 @@ fn eval_loop(mut expr: Value, env: &mut Environment, depth: usize) -> Result<Val
                                             Err(format!(
                                                 "vector-ref: index {idx} out of bounds (length {})",
                                                 vec.elements.len()
-                                            ))
+                                            )
+                                            .into())
                                         } else {
                                             Ok(vec.elements[idx as usize].clone())
                                         }
                                     }
                                     (Value::Vector(_), _) => {
-                                        Err("vector-ref: index must be an integer".to_string())
-                                    }
-                                    _ => {
-                                        Err("vector-ref: first argument must be a vector"
-                                            .to_string())
+                                        Err("vector-ref: index must be an integer".into())
                                     }
+                                    _ => Err("vector-ref: first argument must be a vector".into()),
                                 };
                             }
                             _ => {}
@@ -256,25 +387,37 @@ fn eval_loop(mut expr: Value, env: &mut Environment, depth: usize) -> Result<Val
                     // Apply function
                     match func {
                         Value::Lambda(ref lambda) => {
-                            if args.len() != lambda.params.len() {
-                                return Err(format!(
-                                    "lambda: expected {} arguments, got {}",
-                                    lambda.params.len(),
-                                    args.len()
-                                ));
-                            }
-
                             // TAIL CALL OPTIMIZATION:
                             // Instead of recursing, update environment and expression
-                            current_env = lambda.env.extend(&lambda.params, &args);
+                            current_env = if lambda.params.is_fixed()
+                                && args.len() == lambda.params.required.len()
+                            {
+                                lambda.env.extend(&lambda.params.required, &args)
+                            } else {
+                                let name = match operator {
+                                    Value::Atom(AtomType::Symbol(SymbolType::Symbol(s))) => {
+                                        s.resolve()
+                                    }
+                                    _ => "lambda".to_string(),
+                                };
+                                bind_params(&name, &lambda.params, &args, &lambda.env, depth)?
+                            };
                             expr = lambda.body.clone();
                             // Continue the loop - this is tail call optimization!
                         }
                         Value::NativeFn(native_fn) => {
                             // Native functions can't be tail-optimized
-                            return native_fn(&args, &mut current_env);
+                            return call_native(&native_fn, &args, &mut current_env, depth);
+                        }
+                        Value::NativeClosure(closure) => {
+                            return call_native(
+                                &move |args, env| closure.call(args, env),
+                                &args,
+                                &mut current_env,
+                                depth,
+                            );
                         }
-                        _ => return Err(format!("Cannot apply non-function: {func}")),
+                        _ => return Err(format!("Cannot apply non-function: {func}").into()),
                     }
                 } else {
                     // After macro expansion, result is not a list - just return it
@@ -285,17 +428,739 @@ fn eval_loop(mut expr: Value, env: &mut Environment, depth: usize) -> Result<Val
     }
 }
 
+// ============================================================================
+// Tail Positions
+// ============================================================================
+
+/// Which sub-expressions of a control-flow special form are in tail position.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TailPosition {
+    /// The arguments at these indices (0-based, after the operator)
+    Args(&'static [usize]),
+    /// The last body form of whichever clause is chosen
+    ClauseResult,
+    /// The last argument
+    LastArg,
+}
+
+/// Every control-flow special form and where its tail positions are.
+///
+/// Forms listed here are dispatched by `eval_control_form` and evaluate their
+/// tail sub-expressions by returning `Step::Tail` rather than calling
+/// `eval_loop`, so deep recursion through them runs in constant stack. A new
+/// control-flow form must be added here and to the tail-safety tests in
+/// `tests/tail_position_tests.rs`.
+pub const TAIL_POSITIONS: &[(&str, TailPosition)] = &[
+    ("cond", TailPosition::ClauseResult),
+    ("if", TailPosition::Args(&[1, 2])),
+    ("let", TailPosition::LastArg),
+    ("do", TailPosition::LastArg),
+];
+
+/// Special forms other than the control-flow ones in `TAIL_POSITIONS`.
+pub const SPECIAL_FORMS: &[&str] = &[
+    "quote",
+    "comment",
+    "binding",
+    "with-redefs",
+    "with-sandbox",
+    "with-precision",
+    "define-constant",
+    "quasiquote",
+    "defmacro",
+    "lambda",
+    "label",
+    "def",
+    "set!",
+    "dotimes",
+    "while",
+    "time",
+    "try",
+    "vector-length",
+    "vector-ref",
+];
+
+/// True if `name` is evaluated as a special form rather than looked up.
+pub fn is_special_form(name: &str) -> bool {
+    SPECIAL_FORMS.contains(&name) || TAIL_POSITIONS.iter().any(|(form, _)| *form == name)
+}
+
+/// What evaluating a control-flow special form leaves to do.
+enum Step {
+    /// The form's value
+    Done(Value),
+    /// Evaluate this expression in this environment as the form's value,
+    /// in tail position
+    Tail(Value, Environment),
+}
+
+/// Evaluate the control-flow special form `name`, or None if it is not one.
+fn eval_control_form(
+    name: &str,
+    args: &Value,
+    env: &mut Environment,
+    depth: usize,
+) -> Option<Result<Step, EvalError>> {
+    match name {
+        "cond" => Some(eval_cond(args, env, depth)),
+        "if" => Some(eval_if(args, env, depth)),
+        "let" => Some(eval_let(args, env, depth)),
+        "do" => Some(eval_do(args, env, depth)),
+        _ => None,
+    }
+}
+
+/// The body of a lambda with these body forms: the form itself if there is
+/// one, or a `do` of them all. A docstring is not part of the body.
+pub fn lambda_body(forms: &[Value]) -> Value {
+    let forms = if docstring(forms).is_some() {
+        &forms[1..]
+    } else {
+        forms
+    };
+    match forms {
+        [] => Value::Nil,
+        [form] => form.clone(),
+        _ => cons(make_symbol("do"), vec_to_list(forms.to_vec())),
+    }
+}
+
+/// The docstring of a lambda or macro with these body forms: a string
+/// followed by at least one more form. A lone string is the body.
+pub fn docstring(forms: &[Value]) -> Option<String> {
+    match forms {
+        [
+            Value::Atom(AtomType::String(StringType::Basic(text))),
+            _,
+            ..,
+        ] => Some(text.clone()),
+        _ => None,
+    }
+}
+
+/// `(cond (test body ...) ...)`: the body of the first clause whose test is
+/// true runs like a `do`, so its last form is in tail position. A clause with
+/// no body returns the test's value, and a final `else` clause always runs.
+fn eval_cond(clauses: &Value, env: &mut Environment, depth: usize) -> Result<Step, EvalError> {
+    let mut clauses = clauses.clone();
+    while let Value::Cons(clause_cell) = clauses {
+        let clause = &clause_cell.car;
+        let test = car(clause)?;
+        let body = cdr(clause)?;
+        let value = if is_else(&test) {
+            if !matches!(clause_cell.cdr, Value::Nil) {
+                return Err("cond: else must be the last clause".into());
+            }
+            Value::Atom(AtomType::Bool(true))
+        } else {
+            eval_loop(test, env, depth + 1)?
+        };
+        if value.is_truthy() {
+            // A clause with only a test returns the test's value
+            if matches!(body, Value::Nil) {
+                return Ok(Step::Done(value));
+            }
+            return eval_do(&body, env, depth);
+        }
+        clauses = clause_cell.cdr.clone();
+    }
+    Ok(Step::Done(Value::Nil))
+}
+
+/// True if `test` is the `else` that marks the default clause of a `cond`.
+pub fn is_else(test: &Value) -> bool {
+    matches!(test, Value::Atom(AtomType::Symbol(SymbolType::Symbol(name))) if name.resolve() == "else")
+}
+
+/// `(if test then else)`: the chosen branch is in tail position. A false
+/// test with no else branch is nil.
+fn eval_if(args: &Value, env: &mut Environment, depth: usize) -> Result<Step, EvalError> {
+    let (test, then, otherwise) = match list_to_vec(args)?.as_slice() {
+        [test, then] => (test.clone(), then.clone(), None),
+        [test, then, otherwise] => (test.clone(), then.clone(), Some(otherwise.clone())),
+        _ => return Err("if: expected (if test then [else])".into()),
+    };
+    if eval_loop(test, env, depth + 1)?.is_truthy() {
+        Ok(Step::Tail(then, env.clone()))
+    } else if let Some(otherwise) = otherwise {
+        Ok(Step::Tail(otherwise, env.clone()))
+    } else {
+        Ok(Step::Done(Value::Nil))
+    }
+}
+
+/// `(do form ...)`: forms are evaluated in order and the last one, in tail
+/// position, is the value. An empty `do` is nil.
+fn eval_do(forms: &Value, env: &mut Environment, depth: usize) -> Result<Step, EvalError> {
+    let mut forms = forms.clone();
+    while let Value::Cons(cell) = forms {
+        if matches!(cell.cdr, Value::Nil) {
+            return Ok(Step::Tail(cell.car.clone(), env.clone()));
+        }
+        eval_loop(cell.car.clone(), env, depth + 1)?;
+        forms = cell.cdr.clone();
+    }
+    Ok(Step::Done(Value::Nil))
+}
+
+/// `(let ((name value) ...) body ...)`: the body runs like a `do`, so its
+/// last form is in tail position. An empty body is nil.
+///
+/// Bindings are evaluated in order, each seeing the ones before it (let*
+/// semantics).
+fn eval_let(args: &Value, env: &mut Environment, depth: usize) -> Result<Step, EvalError> {
+    let bindings = parse_let_bindings(&car(args)?)?;
+    let body = cdr(args)?;
+    let mut let_env = env.extend(&[], &[]);
+    for (name, value_expr) in bindings {
+        let value = eval_loop(value_expr, &mut let_env, depth + 1)?;
+        let_env.define(name.resolve(), value);
+    }
+    eval_do(&body, &mut let_env, depth)
+}
+
+// ============================================================================
+// Dynamic Variables
+// ============================================================================
+
+/// Current value of a dynamic variable, or None if `name` is not one.
+///
+/// Dynamic variables are backed by thread-local state outside the
+/// environment, so code that cannot see the environment (such as `Display`)
+/// can read them.
+fn dynamic_var(name: &str) -> Option<Value> {
+    if name == "*macroexpand-trace*" {
+        return Some(Value::Atom(AtomType::Bool(MACROEXPAND_TRACE.get())));
+    }
+    if name == "*macro-debug*" {
+        return Some(Value::Atom(AtomType::Bool(MACRO_DEBUG.get())));
+    }
+    let limits = print_limits();
+    let limit = match name {
+        "*print-float-digits*" => print_float_digits(),
+        "*print-length*" => limits.length,
+        "*print-depth*" => limits.depth,
+        "*print-total*" => limits.total,
+        _ => return None,
+    };
+    Some(limit.map_or(Value::Nil, |n| {
+        Value::Atom(AtomType::Number(NumericType::Int(n as i64)))
+    }))
+}
+
+/// Set a dynamic variable, returning its previous value.
+fn set_dynamic_var(name: &str, value: &Value) -> Result<Value, String> {
+    let previous =
+        dynamic_var(name).ok_or_else(|| format!("binding: {name} is not a dynamic variable"))?;
+    if name == "*macroexpand-trace*" {
+        MACROEXPAND_TRACE.set(value.is_truthy());
+        return Ok(previous);
+    }
+    if name == "*macro-debug*" {
+        MACRO_DEBUG.set(value.is_truthy());
+        return Ok(previous);
+    }
+    // Every dynamic variable is a non-negative integer, or nil for no limit
+    let limit = match value {
+        Value::Nil => None,
+        Value::Atom(AtomType::Number(NumericType::Int(n))) if *n >= 0 => Some(*n as usize),
+        _ => {
+            return Err(format!(
+                "{name} must be a non-negative integer or nil, got {value}"
+            ));
+        }
+    };
+    let mut limits = print_limits();
+    match name {
+        "*print-float-digits*" => {
+            set_print_float_digits(limit);
+        }
+        "*print-length*" => limits.length = limit,
+        "*print-depth*" => limits.depth = limit,
+        "*print-total*" => limits.total = limit,
+        _ => unreachable!("dynamic_var and set_dynamic_var list the same variables"),
+    }
+    set_print_limits(limits);
+    Ok(previous)
+}
+
+/// Evaluate `(binding ((var expr) ...) body)`.
+///
+/// The dynamic variables take the new values for the dynamic extent of the
+/// body and are restored afterwards, even if the body fails.
+fn eval_binding(
+    bindings: &Value,
+    body: Value,
+    env: &mut Environment,
+    depth: usize,
+) -> Result<Value, EvalError> {
+    let mut new_values = Vec::new();
+    let mut current = bindings.clone();
+    while let Value::Cons(cell) = current {
+        let name = match car(&cell.car)? {
+            Value::Atom(AtomType::Symbol(SymbolType::Symbol(name))) => name.resolve(),
+            other => return Err(format!("binding: expected a variable name, got {other}").into()),
+        };
+        let value = eval_loop(car(&cdr(&cell.car)?)?, env, depth + 1)?;
+        new_values.push((name, value));
+        current = cell.cdr.clone();
+    }
+
+    let mut saved = Vec::new();
+    let mut result = Ok(Value::Nil);
+    for (name, value) in &new_values {
+        match set_dynamic_var(name, value) {
+            Ok(previous) => saved.push((name, previous)),
+            Err(e) => {
+                result = Err(e.into());
+                break;
+            }
+        }
+    }
+    if result.is_ok() {
+        result = eval_loop(body, env, depth + 1);
+    }
+    for (name, previous) in saved.into_iter().rev() {
+        set_dynamic_var(name, &previous)?;
+    }
+    result
+}
+
+/// Evaluate `(with-redefs ((name expr) ...) body...)`.
+///
+/// Each name must already be bound. It takes the new value for the dynamic
+/// extent of the body, seen by every function that looks it up, including
+/// ones defined earlier, and the old value is restored afterwards even if the
+/// body fails.
+fn eval_with_redefs(
+    bindings: &Value,
+    body: &Value,
+    env: &mut Environment,
+    depth: usize,
+) -> Result<Value, EvalError> {
+    check_unfrozen("with-redefs", env)?;
+    let mut new_values = Vec::new();
+    let mut current = bindings.clone();
+    while let Value::Cons(cell) = current {
+        let name = match car(&cell.car)? {
+            Value::Atom(AtomType::Symbol(SymbolType::Symbol(name))) => name,
+            other => return Err(format!("with-redefs: expected a name, got {other}").into()),
+        };
+        let resolved = name.resolve();
+        if is_special_form(&resolved) {
+            return Err(format!("with-redefs: cannot redefine special form {name}").into());
+        }
+        if env.is_constant(&resolved) {
+            return Err(format!("with-redefs: cannot redefine constant {name}").into());
+        }
+        if env.lookup(&resolved).is_none() {
+            return Err(EvalError::UnboundSymbol(name));
+        }
+        let value = eval_loop(car(&cdr(&cell.car)?)?, env, depth + 1)?;
+        new_values.push((resolved, value));
+        current = cell.cdr.clone();
+    }
+
+    let saved: Vec<_> = new_values
+        .into_iter()
+        .filter_map(|(name, value)| env.set(&name, value).map(|previous| (name, previous)))
+        .collect();
+    let result = eval_body(body, env, depth);
+    for (name, previous) in saved.into_iter().rev() {
+        env.set(&name, previous);
+    }
+    result
+}
+
+/// Evaluate `(with-sandbox body...)`.
+///
+/// The body runs in a child scope, so its definitions are dropped
+/// afterwards. Every enclosing scope up to the global one is snapshotted and
+/// restored too, since `def` defines globally and `set!` changes the nearest
+/// binding wherever it is. Restoration happens even if the body fails. Only
+/// bindings are rolled back, not effects such as output or files written.
+fn eval_with_sandbox(
+    body: &Value,
+    env: &mut Environment,
+    depth: usize,
+) -> Result<Value, EvalError> {
+    let scopes = env.scopes();
+    let snapshots: Vec<_> = scopes.iter().map(Environment::snapshot).collect();
+    let mut sandbox = env.extend(&[], &[]);
+    let result = eval_body(body, &mut sandbox, depth);
+    for (scope, snapshot) in scopes.iter().zip(snapshots) {
+        scope.restore(snapshot);
+    }
+    result
+}
+
+/// Evaluate each form of `body` in turn, returning the value of the last
+/// (nil if there are none) or the first error.
+fn eval_body(body: &Value, env: &mut Environment, depth: usize) -> Result<Value, EvalError> {
+    let mut result = Value::Nil;
+    let mut forms = body.clone();
+    while let Value::Cons(cell) = forms {
+        result = eval_loop(cell.car.clone(), env, depth + 1)?;
+        forms = cell.cdr.clone();
+    }
+    Ok(result)
+}
+
+// ============================================================================
+// Loops
+// ============================================================================
+
+/// Evaluate `(dotimes (var count) body...)`, returning nil.
+///
+/// `count` is evaluated once. The body runs with `var` bound to 0, 1, ...
+/// up to `count - 1`, in a single child scope made for the whole loop, so
+/// definitions made by one iteration are seen by the next.
+fn eval_dotimes(args: &Value, env: &mut Environment, depth: usize) -> Result<Value, EvalError> {
+    let spec = car(args)?;
+    let body = cdr(args)?;
+    let var = match car(&spec)? {
+        Value::Atom(AtomType::Symbol(SymbolType::Symbol(name))) => name.resolve(),
+        _ => return Err("dotimes: loop variable must be a symbol".into()),
+    };
+    let count = match eval_loop(car(&cdr(&spec)?)?, env, depth + 1)? {
+        Value::Atom(AtomType::Number(NumericType::Int(n))) => n,
+        other => {
+            return Err(EvalError::TypeError {
+                callee: Some("dotimes".to_string()),
+                expected: "integer count".to_string(),
+                found: other,
+            });
+        }
+    };
+
+    let mut loop_env = env.extend(&[], &[]);
+    for i in 0..count.max(0) {
+        loop_env.define(
+            var.clone(),
+            Value::Atom(AtomType::Number(NumericType::Int(i))),
+        );
+        eval_body(&body, &mut loop_env, depth)?;
+    }
+    Ok(Value::Nil)
+}
+
+/// Evaluate `(while test body...)`, returning nil.
+///
+/// The body runs in the enclosing scope each time `test` is truthy, so it
+/// can change loop state with `set!`.
+fn eval_while(args: &Value, env: &mut Environment, depth: usize) -> Result<Value, EvalError> {
+    let test = car(args)?;
+    let body = cdr(args)?;
+    while eval_loop(test.clone(), env, depth + 1)?.is_truthy() {
+        eval_body(&body, env, depth)?;
+    }
+    Ok(Value::Nil)
+}
+
+/// Evaluate `(time expr)`: the value of `expr`, after writing how long it
+/// took to stderr so the program's own output is left alone.
+fn eval_time(args: &Value, env: &mut Environment, depth: usize) -> Result<Value, EvalError> {
+    let [expr] = list_to_vec(args)?
+        .try_into()
+        .map_err(|_| "time: expected (time expr)")?;
+    let start = Instant::now();
+    let value = eval_loop(expr, env, depth + 1)?;
+    eprintln!("Elapsed time: {}", format_elapsed(start.elapsed()));
+    Ok(value)
+}
+
+/// A duration in milliseconds to microsecond precision, e.g. `1.250 ms`.
+pub fn format_elapsed(elapsed: Duration) -> String {
+    format!("{:.3} ms", elapsed.as_secs_f64() * 1000.0)
+}
+
+// ============================================================================
+// Error Handling
+// ============================================================================
+
+/// The value a `catch` clause binds for `error`: an alist with the error's
+/// `message` and its `code`.
+pub fn error_value(error: &EvalError) -> Value {
+    vec_to_alist(vec![
+        (make_symbol("message"), make_string(error.message())),
+        (make_symbol("code"), make_string(error.code())),
+    ])
+}
+
+/// Evaluate `(try body... (catch name handler...))`.
+///
+/// The body forms are evaluated in order. If one fails, the rest are
+/// skipped and the handler runs in a child scope with `name` bound to the
+/// error's `error_value`; its last value is the result. Effects of the body
+/// before the failure are kept, and errors from the handler propagate.
+fn eval_try(args: &Value, env: &mut Environment, depth: usize) -> Result<Value, EvalError> {
+    let mut forms = list_to_vec(args)?;
+    let (name, handler) = match forms.pop() {
+        Some(Value::Cons(clause))
+            if matches!(&clause.car, Value::Atom(AtomType::Symbol(SymbolType::Symbol(s)))
+                if s.resolve() == "catch") =>
+        {
+            match car(&clause.cdr)? {
+                Value::Atom(AtomType::Symbol(SymbolType::Symbol(name))) => {
+                    (name, cdr(&clause.cdr)?)
+                }
+                _ => return Err("try: catch variable must be a symbol".into()),
+            }
+        }
+        _ => return Err("try: expected (catch name handler...) as the last form".into()),
+    };
+
+    let mut result = Value::Nil;
+    for form in forms {
+        match eval_loop(form, env, depth + 1) {
+            Ok(value) => result = value,
+            Err(error) => {
+                let mut handler_env = env.extend(&[name], &[error_value(&error)]);
+                return eval_body(&handler, &mut handler_env, depth);
+            }
+        }
+    }
+    Ok(result)
+}
+
+// ============================================================================
+// Let Bindings
+// ============================================================================
+
+/// Split a let binding list `((name value) ...)` into names and value expressions.
+pub fn parse_let_bindings(bindings: &Value) -> Result<Vec<(InternedSymbol, Value)>, String> {
+    let mut result = Vec::new();
+    let mut current = bindings;
+    while let Value::Cons(cell) = current {
+        let malformed = || format!("let: expected (name value), got {}", cell.car);
+        let Value::Cons(binding) = &cell.car else {
+            return Err(malformed());
+        };
+        let Value::Cons(rest) = &binding.cdr else {
+            return Err(malformed());
+        };
+        if !matches!(rest.cdr, Value::Nil) {
+            return Err(malformed());
+        }
+        let Value::Atom(AtomType::Symbol(SymbolType::Symbol(name))) = &binding.car else {
+            return Err(format!(
+                "let: binding name must be a symbol, got {}",
+                binding.car
+            ));
+        };
+        result.push((*name, rest.car.clone()));
+        current = &cell.cdr;
+    }
+    if !matches!(current, Value::Nil) {
+        return Err(format!("let: bindings must be a list, got {bindings}"));
+    }
+    Ok(result)
+}
+
+// ============================================================================
+// Constants
+// ============================================================================
+
+/// True for values define-constant accepts: integers, floats, t and nil.
+/// These are the values the JIT can inline as immediates.
+pub fn is_constant_literal(value: &Value) -> bool {
+    matches!(
+        value,
+        Value::Nil
+            | Value::Atom(AtomType::Bool(_))
+            | Value::Atom(AtomType::Number(
+                NumericType::Int(_) | NumericType::Float(_)
+            ))
+    )
+}
+
+/// Validate a define-constant of `name` to `value`, given the constant's
+/// current value if it already exists. Redefining a constant is only allowed
+/// with an equal value, so reloading a file is harmless but the interpreter
+/// and JIT can never disagree about what a constant means.
+pub fn check_constant(name: &str, existing: Option<&Value>, value: &Value) -> Result<(), String> {
+    if !is_constant_literal(value) {
+        return Err(format!(
+            "define-constant: {name} must be an integer, float, t or nil, got {value}"
+        ));
+    }
+    match existing {
+        Some(old) if old != value => Err(format!(
+            "define-constant: {name} is already defined as {old}"
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Fail if `env` is frozen, since `form` would change one of its bindings.
+fn check_unfrozen(form: &str, env: &Environment) -> Result<(), EvalError> {
+    if env.is_frozen() {
+        return Err(format!(
+            "{form}: cannot change bindings while the environment is frozen, as it is \
+             inside par-map; use a ref for shared state"
+        )
+        .into());
+    }
+    Ok(())
+}
+
+// ============================================================================
+// Function Application
+// ============================================================================
+
+/// Apply a lambda or native function to already-evaluated arguments.
+///
+/// This is the entry point for native functions that call back into Lisp
+/// code (higher-order functions such as `map` or `filter`).
+pub fn apply(func: &Value, args: &[Value], env: &mut Environment) -> Result<Value, EvalError> {
+    match func {
+        Value::Lambda(lambda) => {
+            let depth = NATIVE_DEPTH.get();
+            let mut body_env = bind_params("lambda", &lambda.params, args, &lambda.env, depth)?;
+            eval_loop(lambda.body.clone(), &mut body_env, depth)
+        }
+        Value::NativeFn(native_fn) => native_fn(args, env),
+        Value::NativeClosure(closure) => closure.call(args, env),
+        _ => Err(format!("Cannot apply non-function: {func}").into()),
+    }
+}
+
+/// Call a native function from an `eval_loop` at `depth`.
+fn call_native(
+    native_fn: &NativeClosureFn,
+    args: &[Value],
+    env: &mut Environment,
+    depth: usize,
+) -> Result<Value, EvalError> {
+    let outer = NATIVE_DEPTH.replace(depth + 1);
+    let result = native_fn(args, env);
+    NATIVE_DEPTH.set(outer);
+    result
+}
+
+/// Bind call arguments to a lambda or macro parameter list.
+///
+/// Returns `env` extended with one binding per parameter. Defaults for
+/// omitted `&opt` and `&key` parameters are evaluated in the new environment
+/// as it is built, so they can refer to earlier parameters. When a function
+/// has `&key` parameters, optionals are only filled by arguments that are not
+/// keywords. `fn_name` is used in error messages.
+pub fn bind_params(
+    fn_name: &str,
+    params: &ParamSpec,
+    args: &[Value],
+    env: &Environment,
+    depth: usize,
+) -> Result<Environment, EvalError> {
+    let required = params.required.len();
+    let max_positional = required + params.optional.len();
+    let arity_error = |expected: Arity| EvalError::ArityMismatch {
+        callee: fn_name.to_string(),
+        expected,
+        got: args.len(),
+    };
+
+    if args.len() < required {
+        return Err(arity_error(if params.is_fixed() {
+            Arity::Exact(required)
+        } else {
+            Arity::AtLeast(required)
+        }));
+    }
+
+    let body_env = env.extend(&params.required, &args[..required]);
+    let eval_default =
+        |default: &Value| eval_loop(default.clone(), &mut body_env.clone(), depth + 1);
+    let mut remaining = &args[required..];
+
+    for param in &params.optional {
+        let value = match remaining.split_first() {
+            Some((arg, tail)) if params.keys.is_empty() || keyword_name(arg).is_none() => {
+                remaining = tail;
+                arg.clone()
+            }
+            _ => eval_default(&param.default)?,
+        };
+        body_env.define(param.name.resolve(), value);
+    }
+
+    if let Some(rest) = &params.rest {
+        body_env.define(rest.resolve(), vec_to_list(remaining.to_vec()));
+    }
+
+    if params.keys.is_empty() {
+        if params.rest.is_none() && !remaining.is_empty() {
+            return Err(arity_error(if params.is_fixed() {
+                Arity::Exact(max_positional)
+            } else {
+                Arity::AtMost(max_positional)
+            }));
+        }
+        return Ok(body_env);
+    }
+
+    let mut supplied: Vec<Option<Value>> = vec![None; params.keys.len()];
+    while let Some((key, tail)) = remaining.split_first() {
+        let name = keyword_name(key)
+            .ok_or_else(|| format!("{fn_name}: expected keyword argument, got {key}"))?;
+        let index = params
+            .keys
+            .iter()
+            .position(|p| p.name.resolve() == name)
+            .ok_or_else(|| {
+                let accepted: Vec<String> =
+                    params.keys.iter().map(|p| format!(":{}", p.name)).collect();
+                format!(
+                    "{fn_name}: unknown keyword argument :{name} (accepts {})",
+                    accepted.join(", ")
+                )
+            })?;
+        let Some((value, tail)) = tail.split_first() else {
+            return Err(format!("{fn_name}: missing value for keyword argument :{name}").into());
+        };
+        if supplied[index].replace(value.clone()).is_some() {
+            return Err(format!("{fn_name}: keyword argument :{name} given more than once").into());
+        }
+        remaining = tail;
+    }
+
+    for (param, value) in params.keys.iter().zip(supplied) {
+        let value = match value {
+            Some(value) => value,
+            None => eval_default(&param.default)?,
+        };
+        body_env.define(param.name.resolve(), value);
+    }
+
+    Ok(body_env)
+}
+
+/// The name of a keyword argument without its colon, e.g. `port` for `:port`.
+fn keyword_name(value: &Value) -> Option<String> {
+    match value {
+        Value::Atom(AtomType::Symbol(SymbolType::Symbol(sym))) => {
+            sym.resolve().strip_prefix(':').map(str::to_string)
+        }
+        _ => None,
+    }
+}
+
 // ============================================================================
 // Macro Support - Quasiquote Evaluation
 // ============================================================================
 
 /// Evaluate quasiquote - construct templates with unquote/unquote-splicing
+///
+/// A symbol ending in `#`, such as `result#`, is replaced by a fresh gensym.
+/// `gensyms` holds the replacements made so far, so every `result#` in one
+/// template becomes the same symbol.
 fn eval_quasiquote(
     expr: Value,
     env: &mut Environment,
     depth: usize,
     level: usize,
-) -> Result<Value, String> {
+    gensyms: &mut HashMap<InternedSymbol, InternedSymbol>,
+) -> Result<Value, EvalError> {
     match expr {
         // Check for unquote at this level
         Value::Cons(ref cell) => {
@@ -307,12 +1172,12 @@ fn eval_quasiquote(
                         return eval_loop(arg, env, depth + 1);
                     }
                     "unquote-splicing" if level == 0 => {
-                        return Err("unquote-splicing not in list context".to_string());
+                        return Err("unquote-splicing not in list context".into());
                     }
                     "quasiquote" => {
                         // Nested quasiquote - increase level
                         let arg = car(&cell.cdr)?;
-                        let result = eval_quasiquote(arg, env, depth, level + 1)?;
+                        let result = eval_quasiquote(arg, env, depth, level + 1, gensyms)?;
                         return Ok(cons(
                             Value::Atom(AtomType::Symbol(SymbolType::Symbol(InternedSymbol::new(
                                 "quasiquote",
@@ -323,7 +1188,7 @@ fn eval_quasiquote(
                     "unquote" => {
                         // Nested unquote - decrease level
                         let arg = car(&cell.cdr)?;
-                        let result = eval_quasiquote(arg, env, depth, level - 1)?;
+                        let result = eval_quasiquote(arg, env, depth, level - 1, gensyms)?;
                         return Ok(cons(
                             Value::Atom(AtomType::Symbol(SymbolType::Symbol(InternedSymbol::new(
                                 "unquote",
@@ -340,6 +1205,12 @@ fn eval_quasiquote(
             let mut current = expr;
 
             while let Value::Cons(ref element_cell) = current {
+                // `(a . ,b)` reads as `(a unquote b)`: the rest is the tail
+                if let Value::Atom(AtomType::Symbol(SymbolType::Symbol(name))) = &element_cell.car
+                    && name.resolve() == "unquote"
+                {
+                    break;
+                }
                 // Check if this element is unquote-splicing
                 if let Value::Cons(ref inner) = element_cell.car
                     && let Value::Atom(AtomType::Symbol(SymbolType::Symbol(name))) = &inner.car
@@ -350,35 +1221,55 @@ fn eval_quasiquote(
                     let splice_expr = car(&inner.cdr)?;
                     let splice_result = eval_loop(splice_expr, env, depth + 1)?;
 
-                    // Splice the list into result
-                    let mut splice_current = splice_result;
+                    // Splice the list into result; anything else is an error
+                    // rather than being dropped
+                    let mut splice_current = splice_result.clone();
                     while let Value::Cons(ref splice_cell) = splice_current {
                         result_elements.push(splice_cell.car.clone());
                         splice_current = splice_cell.cdr.clone();
                     }
+                    if !matches!(splice_current, Value::Nil) {
+                        return Err(EvalError::TypeError {
+                            callee: Some("unquote-splicing".to_string()),
+                            expected: "list".to_string(),
+                            found: splice_result,
+                        });
+                    }
 
                     current = element_cell.cdr.clone();
                     continue;
                 }
 
                 // Not unquote-splicing, process normally
-                let processed = eval_quasiquote(element_cell.car.clone(), env, depth, level)?;
+                let processed =
+                    eval_quasiquote(element_cell.car.clone(), env, depth, level, gensyms)?;
                 result_elements.push(processed);
                 current = element_cell.cdr.clone();
             }
 
-            // Handle improper list (dotted pair)
-            if !matches!(current, Value::Nil) {
-                return Err("quasiquote: improper list not fully supported".to_string());
-            }
+            // The tail of a dotted list is quasiquoted like an element
+            let tail = match current {
+                Value::Nil => Value::Nil,
+                tail => eval_quasiquote(tail, env, depth, level, gensyms)?,
+            };
 
             // Build result list
             let result = result_elements
                 .into_iter()
                 .rev()
-                .fold(Value::Nil, |acc, elem| cons(elem, acc));
+                .fold(tail, |acc, elem| cons(elem, acc));
             Ok(result)
         }
+        Value::Atom(AtomType::Symbol(SymbolType::Symbol(name))) => {
+            let text = name.resolve();
+            match text.strip_suffix('#') {
+                Some(prefix) if !prefix.is_empty() => {
+                    let fresh = *gensyms.entry(name).or_insert_with(|| fresh_symbol(prefix));
+                    Ok(Value::Atom(AtomType::Symbol(SymbolType::Symbol(fresh))))
+                }
+                _ => Ok(expr),
+            }
+        }
         // Atoms and other values quote themselves
         _ => Ok(expr),
     }
@@ -388,12 +1279,76 @@ fn eval_quasiquote(
 // Macro Expansion
 // ============================================================================
 
-/// Check if a value is a macro call and expand it
-fn expand_macro_once(
+/// One step of macro expansion: a call to the macro `name` and what it
+/// expanded to.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Expansion {
+    pub name: InternedSymbol,
+    pub input: Value,
+    pub output: Value,
+}
+
+impl fmt::Display for Expansion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {} => {}", self.name, self.input, self.output)
+    }
+}
+
+thread_local! {
+    /// Whether `*macroexpand-trace*` is set
+    static MACROEXPAND_TRACE: Cell<bool> = const { Cell::new(false) };
+    /// Whether `*macro-debug*` is set
+    static MACRO_DEBUG: Cell<bool> = const { Cell::new(false) };
+    /// Steps collected by `record_expansions`, while it is running
+    static EXPANSIONS: RefCell<Option<Vec<Expansion>>> = const { RefCell::new(None) };
+}
+
+/// Run `f`, returning its result and every macro expansion it performed, in
+/// order.
+pub fn record_expansions<T>(f: impl FnOnce() -> T) -> (T, Vec<Expansion>) {
+    let outer = EXPANSIONS.with(|steps| steps.replace(Some(Vec::new())));
+    let result = f();
+    let steps = EXPANSIONS
+        .with(|steps| steps.replace(outer))
+        .unwrap_or_default();
+    // An enclosing recording sees these steps too
+    EXPANSIONS.with(|outer| {
+        if let Some(outer) = outer.borrow_mut().as_mut() {
+            outer.extend(steps.iter().cloned());
+        }
+    });
+    (result, steps)
+}
+
+/// Report an expansion to `*macroexpand-trace*` and `record_expansions`.
+fn note_expansion(name: InternedSymbol, input: &Value, output: &Value) {
+    let tracing = MACROEXPAND_TRACE.get();
+    let recording = EXPANSIONS.with(|steps| steps.borrow().is_some());
+    if !tracing && !recording {
+        return;
+    }
+    let step = Expansion {
+        name,
+        input: input.clone(),
+        output: output.clone(),
+    };
+    if tracing {
+        eprintln!("{step}");
+    }
+    EXPANSIONS.with(|steps| {
+        if let Some(steps) = steps.borrow_mut().as_mut() {
+            steps.push(step);
+        }
+    });
+}
+
+/// Expand `expr` once if it is a macro call, returning the result and
+/// whether it was one. Every macro expansion goes through here.
+pub(crate) fn expand_macro_once(
     expr: Value,
     env: &mut Environment,
     depth: usize,
-) -> Result<(Value, bool), String> {
+) -> Result<(Value, bool), EvalError> {
     if let Value::Cons(cell) = &expr
         && let Value::Atom(AtomType::Symbol(SymbolType::Symbol(name))) = &cell.car
         && let Some(Value::Macro(macro_cell)) = env.lookup(&name.resolve())
@@ -406,33 +1361,138 @@ fn expand_macro_once(
             current = arg_cell.cdr.clone();
         }
 
-        // Check argument count
-        if args.len() != macro_cell.params.len() {
-            return Err(format!(
-                "macro: expected {} arguments, got {}",
-                macro_cell.params.len(),
-                args.len()
-            ));
-        }
-
         // Create environment for macro expansion
-        let mut macro_env = macro_cell.env.extend(&macro_cell.params, &args);
+        let mut macro_env = bind_params(
+            &name.resolve(),
+            &macro_cell.params,
+            &args,
+            &macro_cell.env,
+            depth,
+        )?;
 
         // Evaluate macro body to get expanded code
         let expanded = eval_loop(macro_cell.body.clone(), &mut macro_env, depth + 1)?;
+        note_expansion(*name, &expr, &expanded);
+        if MACRO_DEBUG.get() {
+            for symbol in captured_symbols(&macro_cell, &args, &expanded) {
+                eprintln!(
+                    "warning: macro {name} binds {symbol}, which its arguments also use; \
+                     name it {symbol}# to bind a fresh symbol"
+                );
+            }
+        }
         return Ok((expanded, true));
     }
 
     Ok((expr, false))
 }
 
+/// Symbols an expansion binds that could capture a use in the macro's
+/// arguments.
+///
+/// A symbol is reported when the expansion binds it with `let`, `lambda` or
+/// `dotimes`, the macro's body names it other than as a parameter, and it
+/// also appears in `args`. Bindings of names taken from the arguments, and of
+/// auto-gensyms, are not reported.
+pub fn captured_symbols(
+    macro_cell: &MacroCell,
+    args: &[Value],
+    expansion: &Value,
+) -> Vec<InternedSymbol> {
+    let params: HashSet<InternedSymbol> = macro_cell.params.names().collect();
+    let mut in_body = HashSet::new();
+    collect_symbols(&macro_cell.body, &mut in_body);
+    let mut in_args = HashSet::new();
+    for arg in args {
+        collect_symbols(arg, &mut in_args);
+    }
+
+    let mut bound = Vec::new();
+    collect_bindings(expansion, &mut bound);
+    let mut captured = Vec::new();
+    for symbol in bound {
+        if !params.contains(&symbol)
+            && in_body.contains(&symbol)
+            && in_args.contains(&symbol)
+            && !captured.contains(&symbol)
+        {
+            captured.push(symbol);
+        }
+    }
+    captured
+}
+
+/// Every symbol in `value`.
+fn collect_symbols(value: &Value, symbols: &mut HashSet<InternedSymbol>) {
+    match value {
+        Value::Atom(AtomType::Symbol(SymbolType::Symbol(sym))) => {
+            symbols.insert(*sym);
+        }
+        Value::Cons(cell) => {
+            collect_symbols(&cell.car, symbols);
+            collect_symbols(&cell.cdr, symbols);
+        }
+        _ => {}
+    }
+}
+
+/// The names bound by `let`, `lambda` and `dotimes` forms in `value`, outside
+/// quoted data.
+fn collect_bindings(value: &Value, bound: &mut Vec<InternedSymbol>) {
+    let Value::Cons(cell) = value else {
+        return;
+    };
+    let head = match &cell.car {
+        Value::Atom(AtomType::Symbol(SymbolType::Symbol(sym))) => sym.resolve(),
+        _ => String::new(),
+    };
+    let first = car(&cell.cdr).unwrap_or(Value::Nil);
+    match head.as_str() {
+        "quote" => return,
+        "let" => {
+            if let Ok(bindings) = parse_let_bindings(&first) {
+                bound.extend(bindings.into_iter().map(|(name, _)| name));
+            }
+        }
+        "lambda" => {
+            if let Ok(params) = ParamSpec::parse(&first, "lambda") {
+                bound.extend(params.names());
+            }
+        }
+        "dotimes" => {
+            if let Ok(Value::Atom(AtomType::Symbol(SymbolType::Symbol(var)))) = car(&first) {
+                bound.push(var);
+            }
+        }
+        _ => {}
+    }
+    let mut current = value.clone();
+    while let Value::Cons(cell) = current {
+        collect_bindings(&cell.car, bound);
+        current = cell.cdr.clone();
+    }
+}
+
 /// Recursively expand all macros in an expression.
 /// This is public so that the JIT can expand macros before compilation.
-pub fn expand_macros(expr: Value, env: &mut Environment, depth: usize) -> Result<Value, String> {
+///
+/// A macro that keeps expanding to another macro call is stopped after
+/// `env.recursion_limit()` steps, the same limit as for nested calls.
+pub fn expand_macros(expr: Value, env: &mut Environment, depth: usize) -> Result<Value, EvalError> {
     let (mut result, mut expanded) = expand_macro_once(expr, env, depth)?;
 
     // Keep expanding until no more macros
+    let limit = env.recursion_limit();
+    let mut steps = 1;
     while expanded {
+        if steps >= limit {
+            return Err(format!(
+                "Macro expansion did not finish after {limit} steps; last expanded to {}",
+                snippet(&result)
+            )
+            .into());
+        }
+        steps += 1;
         let (new_result, new_expanded) = expand_macro_once(result, env, depth)?;
         result = new_result;
         expanded = new_expanded;
@@ -444,32 +1504,49 @@ pub fn expand_macros(expr: Value, env: &mut Environment, depth: usize) -> Result
 /// Recursively expand all macros in an expression and its sub-expressions.
 /// This walks the entire expression tree to expand macros at all levels.
 /// Use this for JIT compilation where we need all macros expanded before compilation.
+///
+/// Each level of nesting counts toward the recursion limit, like nested
+/// calls in `eval`.
 pub fn expand_all_macros(
     expr: Value,
     env: &mut Environment,
     depth: usize,
-) -> Result<Value, String> {
+) -> Result<Value, EvalError> {
+    let limit = env.recursion_limit();
+    if depth >= limit {
+        return Err(recursion_error(limit));
+    }
+
     // First expand any macros at the top level
     let expanded = expand_macros(expr, env, depth)?;
 
     // Then recursively expand in sub-expressions
-    match expanded {
-        Value::Cons(cell) => {
-            // Check if this is a quote - don't expand inside quotes
-            if let Value::Atom(AtomType::Symbol(SymbolType::Symbol(sym))) = &cell.car
-                && sym.resolve() == "quote"
-            {
-                return Ok(Value::Cons(cell));
-            }
+    let Value::Cons(ref cell) = expanded else {
+        // For non-cons values, just return as-is
+        return Ok(expanded);
+    };
 
-            // Expand the car and cdr recursively
-            let expanded_car = expand_all_macros(cell.car.clone(), env, depth)?;
-            let expanded_cdr = expand_all_macros(cell.cdr.clone(), env, depth)?;
+    // Don't expand inside quotes or comments
+    if let Value::Atom(AtomType::Symbol(SymbolType::Symbol(sym))) = &cell.car
+        && matches!(sym.resolve().as_str(), "quote" | "comment")
+    {
+        return Ok(expanded);
+    }
 
-            // Create a new cons cell with the expanded values
-            Ok(cons(expanded_car, expanded_cdr))
-        }
-        // For non-cons values, just return as-is
-        _ => Ok(expanded),
+    // Expand each element one level deeper, walking the list itself
+    // iteratively so long lists don't count as deep ones
+    let mut elements = Vec::new();
+    let mut current = expanded;
+    while let Value::Cons(cell) = current {
+        elements.push(with_stack(|| {
+            expand_all_macros(cell.car.clone(), env, depth + 1)
+        })?);
+        current = cell.cdr.clone();
     }
+    let tail = expand_all_macros(current, env, depth + 1)?;
+
+    Ok(elements
+        .into_iter()
+        .rev()
+        .fold(tail, |rest, element| cons(element, rest)))
 }
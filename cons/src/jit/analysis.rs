@@ -8,6 +8,8 @@ use std::collections::HashSet;
 use consair::interner::InternedSymbol;
 use consair::language::{AtomType, SymbolType, Value};
 
+use crate::interpreter::{is_else, lambda_body};
+
 /// Find all free variables in an expression.
 /// A free variable is one that is used but not defined in the local scope.
 pub fn find_free_variables(
@@ -40,15 +42,15 @@ fn find_free_vars_helper(
             if let Value::Atom(AtomType::Symbol(SymbolType::Symbol(sym))) = &cell.car {
                 let name = sym.resolve();
                 match name.as_str() {
-                    "quote" => {
-                        // Don't look for free variables in quoted expressions
+                    "quote" | "comment" => {
+                        // Don't look for free variables in quoted or commented expressions
                     }
                     "lambda" => {
                         // Lambda binds its parameters
                         let args = collect_list(&cell.cdr);
                         if args.len() >= 2 {
                             let params = &args[0];
-                            let body = &args[1];
+                            let body = &lambda_body(&args[1..]);
                             let param_list = collect_list(params);
                             let mut new_bound = bound.clone();
                             for p in param_list {
@@ -59,6 +61,27 @@ fn find_free_vars_helper(
                             find_free_vars_helper(body, &new_bound, free);
                         }
                     }
+                    "let" => {
+                        // Each binding is visible to later values and to the body
+                        let args = collect_list(&cell.cdr);
+                        if let Some((bindings, body)) = args.split_first() {
+                            let mut new_bound = bound.clone();
+                            for binding in collect_list(bindings) {
+                                let parts = collect_list(&binding);
+                                if let Some(value) = parts.get(1) {
+                                    find_free_vars_helper(value, &new_bound, free);
+                                }
+                                if let Some(Value::Atom(AtomType::Symbol(SymbolType::Symbol(s)))) =
+                                    parts.first()
+                                {
+                                    new_bound.insert(*s);
+                                }
+                            }
+                            for form in body {
+                                find_free_vars_helper(form, &new_bound, free);
+                            }
+                        }
+                    }
                     "label" => {
                         // Label binds the name for recursive calls
                         let args = collect_list(&cell.cdr);
@@ -73,12 +96,14 @@ fn find_free_vars_helper(
                         }
                     }
                     "cond" => {
-                        // Check all condition clauses
+                        // Check all condition clauses; an else test is not a
+                        // variable
                         let clauses = collect_list(&cell.cdr);
                         for clause in clauses {
                             let parts = collect_list(&clause);
-                            for part in parts {
-                                find_free_vars_helper(&part, bound, free);
+                            let skip = usize::from(parts.first().is_some_and(is_else));
+                            for part in &parts[skip..] {
+                                find_free_vars_helper(part, bound, free);
                             }
                         }
                     }
@@ -132,7 +157,12 @@ fn find_free_vars_helper(
                 find_free_vars_helper(elem, bound, free);
             }
         }
-        Value::Lambda(_) | Value::Macro(_) | Value::Reduced(_) | Value::NativeFn(_) => {}
+        Value::Lambda(_)
+        | Value::Macro(_)
+        | Value::Reduced(_)
+        | Value::NativeFn(_)
+        | Value::NativeClosure(_)
+        | Value::Ref(_) => {}
     }
 }
 
@@ -141,9 +171,12 @@ pub fn is_builtin(name: &str) -> bool {
     matches!(
         name,
         "quote"
+            | "comment"
             | "lambda"
+            | "let"
             | "label"
             | "cond"
+            | "do"
             | "cons"
             | "car"
             | "cdr"
@@ -151,6 +184,14 @@ pub fn is_builtin(name: &str) -> bool {
             | "-"
             | "*"
             | "/"
+            | "mod"
+            | "abs"
+            | "bit-and"
+            | "bit-or"
+            | "bit-xor"
+            | "bit-not"
+            | "bit-shift-left"
+            | "bit-shift-right"
             | "="
             | "<"
             | ">"
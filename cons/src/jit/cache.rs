@@ -1,16 +1,26 @@
 //! JIT caching logic for avoiding recompilation of pure expressions.
 
-use std::collections::hash_map::DefaultHasher;
-use std::hash::{Hash, Hasher};
+use std::collections::hash_map::RandomState;
+use std::collections::{BTreeMap, HashMap};
+use std::hash::BuildHasher;
+use std::sync::OnceLock;
 
-use consair::language::{AtomType, SymbolType, Value};
+use consair::language::{AtomType, PrintLimits, SymbolType, Value, set_print_limits};
 
-/// Compute a hash of an expression for cache lookup.
-pub fn hash_expression(expr: &Value) -> u64 {
-    let mut hasher = DefaultHasher::new();
-    // Use the Display representation for hashing
-    format!("{}", expr).hash(&mut hasher);
-    hasher.finish()
+/// The expression printed in full, whatever the thread's print limits, so
+/// that expressions with the same canonical form compile to the same code.
+pub fn canonical_form(expr: &Value) -> String {
+    let previous = set_print_limits(PrintLimits::UNLIMITED);
+    let form = expr.to_string();
+    set_print_limits(previous);
+    form
+}
+
+/// SipHash of a canonical form, with keys chosen randomly once per process
+/// so that colliding expressions cannot be picked in advance.
+pub fn hash_form(form: &str) -> u64 {
+    static KEYS: OnceLock<RandomState> = OnceLock::new();
+    KEYS.get_or_init(RandomState::new).hash_one(form)
 }
 
 /// Check if an expression is pure (no side effects, no free variables).
@@ -21,6 +31,7 @@ pub fn is_pure_expression(expr: &Value) -> bool {
         Value::Atom(AtomType::Number(_)) => true,
         Value::Atom(AtomType::String(_)) => true,
         Value::Atom(AtomType::Bool(_)) => true,
+        Value::Atom(AtomType::Char(_)) => true,
         // Symbols are not pure - they reference variables
         Value::Atom(AtomType::Symbol(SymbolType::Symbol(_))) => false,
         Value::Cons(cell) => {
@@ -28,8 +39,8 @@ pub fn is_pure_expression(expr: &Value) -> bool {
             if let Value::Atom(AtomType::Symbol(SymbolType::Symbol(sym))) = &cell.car {
                 let name = sym.resolve();
 
-                // Quote is always pure - it returns its argument unevaluated
-                if name.as_str() == "quote" {
+                // Quote and comment are always pure - neither evaluates its arguments
+                if name.as_str() == "quote" || name.as_str() == "comment" {
                     return true;
                 }
 
@@ -39,6 +50,14 @@ pub fn is_pure_expression(expr: &Value) -> bool {
                     "-",
                     "*",
                     "/",
+                    "mod",
+                    "abs",
+                    "bit-and",
+                    "bit-or",
+                    "bit-xor",
+                    "bit-not",
+                    "bit-shift-left",
+                    "bit-shift-right",
                     "=",
                     "<",
                     ">",
@@ -54,6 +73,7 @@ pub fn is_pure_expression(expr: &Value) -> bool {
                     "car",
                     "cdr",
                     "cond",
+                    "do",
                     "vector",
                     "vector-length",
                     "vector-ref",
@@ -80,7 +100,8 @@ pub fn is_pure_expression(expr: &Value) -> bool {
         }
         Value::Lambda(_) => false,
         Value::Macro(_) => false,
-        Value::NativeFn(_) => false,
+        // A ref's value can change between evaluations
+        Value::NativeFn(_) | Value::NativeClosure(_) | Value::Ref(_) => false,
         Value::Vector(v) => v.elements.iter().all(is_pure_expression),
         Value::PersistentVector(v) => v.elements.iter().all(is_pure_expression),
         Value::Map(m) => m
@@ -104,6 +125,8 @@ pub struct CacheConfig {
     pub enabled: bool,
     /// Maximum number of entries in the cache
     pub max_entries: usize,
+    /// What to do with a new result once the cache is full
+    pub eviction: EvictionPolicy,
 }
 
 impl Default for CacheConfig {
@@ -111,10 +134,21 @@ impl Default for CacheConfig {
         CacheConfig {
             enabled: true,
             max_entries: 1000,
+            eviction: EvictionPolicy::default(),
         }
     }
 }
 
+/// How the result cache makes room once it holds `max_entries` results.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum EvictionPolicy {
+    /// Keep the results already cached and do not cache new ones
+    None,
+    /// Evict the least recently used result
+    #[default]
+    Lru,
+}
+
 /// Statistics about JIT cache usage.
 #[derive(Clone, Debug, Default)]
 pub struct CacheStats {
@@ -124,4 +158,96 @@ pub struct CacheStats {
     pub misses: usize,
     /// Number of compilations avoided
     pub compilations_avoided: usize,
+    /// Number of results currently cached
+    pub entries: usize,
+    /// Number of results evicted to make room for newer ones
+    pub evictions: usize,
+}
+
+/// Cached results of pure expressions, with the statistics about them.
+///
+/// The engine keeps this behind an `Arc<Mutex<_>>` so that the Lisp
+/// functions installed by `register_jit_introspection` can share it.
+#[derive(Debug, Default)]
+pub(crate) struct ResultCache {
+    /// Expression hash -> cached result
+    results: HashMap<u64, CachedResult>,
+    /// Expression hashes by when they were last used, least recent first
+    recency: BTreeMap<u64, u64>,
+    /// Incremented on every insert or hit
+    clock: u64,
+    pub(crate) stats: CacheStats,
+}
+
+#[derive(Debug)]
+struct CachedResult {
+    /// Canonical form of the expression, compared on lookup so that a hash
+    /// collision is a miss rather than another expression's result
+    form: String,
+    tag: u8,
+    data: u64,
+    /// Clock value when the result was last inserted or hit
+    last_used: u64,
+}
+
+impl ResultCache {
+    /// The cached `(tag, data)` for the expression with canonical `form`
+    /// and its `hash`, marking it as recently used.
+    pub(crate) fn get(&mut self, hash: u64, form: &str) -> Option<(u8, u64)> {
+        self.clock += 1;
+        let entry = self
+            .results
+            .get_mut(&hash)
+            .filter(|entry| entry.form == form)?;
+        self.recency.remove(&entry.last_used);
+        self.recency.insert(self.clock, hash);
+        entry.last_used = self.clock;
+        Some((entry.tag, entry.data))
+    }
+
+    /// Cache a result, making room as `config.eviction` says if the cache
+    /// is full. A result whose hash collides with a cached one replaces it.
+    pub(crate) fn insert(
+        &mut self,
+        hash: u64,
+        form: String,
+        (tag, data): (u8, u64),
+        config: &CacheConfig,
+    ) {
+        if !self.results.contains_key(&hash) && self.results.len() >= config.max_entries {
+            if config.eviction == EvictionPolicy::None {
+                return;
+            }
+            let Some((_, oldest)) = self.recency.pop_first() else {
+                return;
+            };
+            self.results.remove(&oldest);
+            self.stats.evictions += 1;
+        }
+        self.clock += 1;
+        let entry = CachedResult {
+            form,
+            tag,
+            data,
+            last_used: self.clock,
+        };
+        if let Some(previous) = self.results.insert(hash, entry) {
+            self.recency.remove(&previous.last_used);
+        }
+        self.recency.insert(self.clock, hash);
+    }
+
+    /// Remove every cached result. The statistics are kept.
+    pub(crate) fn clear(&mut self) {
+        self.results.clear();
+        self.recency.clear();
+    }
+
+    /// The statistics, with the current number of entries.
+    pub(crate) fn stats(&self) -> CacheStats {
+        CacheStats {
+            entries: self.results.len(),
+            ..self.stats.clone()
+        }
+    }
 }
@@ -1,12 +1,41 @@
 //! Pre-compiled expression handling.
+//!
+//! ## Thread safety
+//!
+//! A compiled expression is an `extern "C"` function that takes no arguments
+//! and returns a `RuntimeValue`. The generated code only calls the `rt_*`
+//! runtime helpers, which allocate with reference counts that are atomic and
+//! keep only thread-local state such as the allocation epoch. Calling the function from several threads
+//! at once is therefore safe, provided that:
+//!
+//! - the LLVM `ExecutionEngine` that owns the machine code outlives every
+//!   call, and
+//! - the expression does not read interpreter state such as variables, which
+//!   is guaranteed for expressions that pass `is_pure_expression`.
+//!
+//! The execution engine is tied to an LLVM context, which may only be used
+//! by one thread at a time. A [`CompiledExpr`] borrows the `JitEngine`'s
+//! context, so it stays on the engine's thread. A [`SharedCompiledExpr`] is
+//! compiled into a context of its own that nothing else uses, so it is
+//! `Send + Sync`: it can be moved to another thread, kept in an `Arc` and
+//! executed from several threads at once, and dropped anywhere.
 
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+use inkwell::context::Context;
 use inkwell::execution_engine::ExecutionEngine;
 
-use crate::runtime::RuntimeValue;
+use crate::runtime::{RuntimeClosure, RuntimeValue, rt_decref};
 
 /// Type alias for a compiled expression function.
 pub type ExprFn = unsafe extern "C" fn() -> RuntimeValue;
 
+/// Type alias for a compiled closure function, called with the closure's
+/// captured values, a pointer to the arguments and the argument count.
+pub type ClosureFn =
+    unsafe extern "C" fn(*const RuntimeValue, *const RuntimeValue, u32) -> RuntimeValue;
+
 /// A pre-compiled expression that can be executed multiple times efficiently.
 ///
 /// This struct holds the compiled LLVM code and execution engine, allowing
@@ -30,15 +59,186 @@ pub struct CompiledExpr<'ctx> {
     pub(crate) execution_engine: ExecutionEngine<'ctx>,
     /// The raw function pointer to the compiled code
     pub(crate) func_ptr: ExprFn,
+    /// Whether the source expression passed `is_pure_expression`
+    pub(crate) pure: bool,
+    /// Execution counters reported by `stats()`
+    pub(crate) counters: ExecCounters,
 }
 
 impl<'ctx> CompiledExpr<'ctx> {
     /// Execute the pre-compiled expression.
     ///
     /// This is very fast as no compilation occurs - it just calls the
-    /// already-compiled native code.
+    /// already-compiled native code. If the code raised a runtime error, such
+    /// as integer overflow, the result is the error value.
     #[inline]
     pub fn execute(&self) -> RuntimeValue {
-        unsafe { (self.func_ptr)() }
+        let start = Instant::now();
+        let result = crate::runtime::run_compiled(|| unsafe { (self.func_ptr)() });
+        self.counters.record(start.elapsed());
+        result
+    }
+
+    /// Number of executions and total time spent executing so far.
+    pub fn stats(&self) -> ExecStats {
+        self.counters.snapshot()
+    }
+
+    /// True if the expression has no side effects and no free variables.
+    pub fn is_pure(&self) -> bool {
+        self.pure
+    }
+}
+
+/// A pure compiled expression that owns its LLVM context, so it can be sent
+/// to other threads and executed from several at once.
+///
+/// Created by [`JitEngine::compile_shared`](super::JitEngine::compile_shared).
+/// Execution counters are shared by all threads.
+pub struct SharedCompiledExpr {
+    // Fields drop in order: the code goes before the context it was built in
+    /// Compiled in `context`, which outlives it despite the `'static`
+    pub(crate) inner: CompiledExpr<'static>,
+    /// Quoted lists the code refers to, released on drop
+    pub(crate) quoted: Vec<RuntimeValue>,
+    /// Used by nothing but `inner`
+    #[allow(dead_code)]
+    pub(crate) context: Box<Context>,
+}
+
+// SAFETY: every LLVM object the handle holds belongs to `context`, and no
+// other object refers to them: `compile_shared` drops the module and function
+// handles that shared the execution engine's reference count before
+// returning. Moving the handle therefore moves all users of the context
+// together, so it is still used by one thread at a time. Shared access only
+// reads the function pointer and updates atomic counters; the function is
+// pure (checked in `compile_shared`) and the runtime helpers it calls, like
+// the reference counts of the quoted lists, are thread-safe.
+unsafe impl Send for SharedCompiledExpr {}
+unsafe impl Sync for SharedCompiledExpr {}
+
+impl Drop for SharedCompiledExpr {
+    fn drop(&mut self) {
+        for list in self.quoted.drain(..) {
+            rt_decref(list);
+        }
+    }
+}
+
+impl SharedCompiledExpr {
+    /// Execute the compiled expression. Safe to call concurrently.
+    #[inline]
+    pub fn execute(&self) -> RuntimeValue {
+        self.inner.execute()
+    }
+
+    /// Number of executions and total time spent executing, across all threads.
+    pub fn stats(&self) -> ExecStats {
+        self.inner.stats()
+    }
+}
+
+/// A compiled lambda that can be called many times with different arguments.
+///
+/// Created by [`JitEngine::compile_function`](super::JitEngine::compile_function).
+/// Each call runs in its own allocation epoch, like [`CompiledExpr::execute`].
+///
+/// # Example
+/// ```ignore
+/// let engine = JitEngine::new()?;
+/// let square = engine.compile_function(&parse("(lambda (n) (* n n))").unwrap())?;
+///
+/// for n in 1..=10 {
+///     let result = square.call(&[RuntimeValue::from_int(n)])?;
+///     assert_eq!(result.to_int(), Some(n * n));
+/// }
+/// ```
+pub struct CompiledFn<'ctx> {
+    /// The compiled expression that built the closure; it owns the code
+    pub(crate) expr: CompiledExpr<'ctx>,
+    /// The closure value, released when this is dropped
+    pub(crate) closure: RuntimeValue,
+    /// Number of parameters the lambda takes
+    pub(crate) arity: usize,
+    /// Execution counters reported by `stats()`
+    pub(crate) counters: ExecCounters,
+}
+
+impl CompiledFn<'_> {
+    /// Call the function with `args`.
+    ///
+    /// Fails without calling it if the number of arguments does not match
+    /// the lambda's parameters, and fails if the call raises a runtime error.
+    pub fn call(&self, args: &[RuntimeValue]) -> Result<RuntimeValue, String> {
+        if args.len() != self.arity {
+            return Err(format!(
+                "lambda expects {} arguments, got {}",
+                self.arity,
+                args.len()
+            ));
+        }
+        let closure = unsafe { &*(self.closure.data as *const RuntimeClosure) };
+        let func = unsafe { std::mem::transmute::<*const (), ClosureFn>(closure.fn_ptr) };
+        let start = Instant::now();
+        let result = crate::runtime::run_compiled(|| unsafe {
+            func(closure.env, args.as_ptr(), args.len() as u32)
+        });
+        self.counters.record(start.elapsed());
+        match result.to_error() {
+            Some(error) => Err(error.to_string()),
+            None => Ok(result),
+        }
+    }
+
+    /// Number of arguments the function takes.
+    pub fn arity(&self) -> usize {
+        self.arity
+    }
+
+    /// Number of calls and total time spent in the function so far.
+    pub fn stats(&self) -> ExecStats {
+        self.counters.snapshot()
+    }
+
+    /// True if the lambda has no side effects and no free variables.
+    pub fn is_pure(&self) -> bool {
+        self.expr.is_pure()
+    }
+}
+
+impl Drop for CompiledFn<'_> {
+    fn drop(&mut self) {
+        rt_decref(self.closure);
+    }
+}
+
+/// Execution counters for a compiled expression, returned by `stats()`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ExecStats {
+    /// Number of times the expression has been executed
+    pub exec_count: u64,
+    /// Total wall-clock time spent inside the compiled function
+    pub total_exec_time: Duration,
+}
+
+/// Atomic execution counters, updated on every `execute()`.
+#[derive(Debug, Default)]
+pub(crate) struct ExecCounters {
+    count: AtomicU64,
+    nanos: AtomicU64,
+}
+
+impl ExecCounters {
+    fn record(&self, elapsed: Duration) {
+        self.count.fetch_add(1, Ordering::Relaxed);
+        self.nanos
+            .fetch_add(elapsed.as_nanos() as u64, Ordering::Relaxed);
+    }
+
+    fn snapshot(&self) -> ExecStats {
+        ExecStats {
+            exec_count: self.count.load(Ordering::Relaxed),
+            total_exec_time: Duration::from_nanos(self.nanos.load(Ordering::Relaxed)),
+        }
     }
 }
@@ -2,24 +2,31 @@
 
 use std::collections::{HashMap, HashSet};
 use std::sync::atomic::AtomicUsize;
+use std::sync::{Arc, Mutex};
 
 use inkwell::OptimizationLevel;
 use inkwell::context::Context;
 use inkwell::execution_engine::ExecutionEngine;
+use inkwell::targets::{InitializationConfig, Target};
 use inkwell::values::FunctionValue;
 
 use crate::codegen::Codegen;
-use crate::interpreter::expand_all_macros;
+use crate::interpreter::{
+    check_constant, expand_all_macros, is_else, lambda_body, parse_let_bindings,
+};
 use crate::runtime::RuntimeValue;
 
 use consair::Environment;
 use consair::interner::InternedSymbol;
-use consair::language::{AtomType, SymbolType, Value};
+use consair::language::{AtomType, ParamSpec, SymbolType, Value};
 use consair::numeric::NumericType;
 
 use super::analysis::find_free_variables;
-use super::cache::{CacheConfig, CacheStats, hash_expression, is_pure_expression};
-use super::compiled::{CompiledExpr, ExprFn};
+use super::cache::{
+    CacheConfig, CacheStats, ResultCache, canonical_form, hash_form, is_pure_expression,
+};
+use super::compiled::{CompiledExpr, CompiledFn, ExprFn, SharedCompiledExpr};
+use super::error::JitError;
 
 /// JIT compilation environment - maps symbols to their compiled values.
 pub(crate) type JitEnv<'ctx> = HashMap<InternedSymbol, inkwell::values::StructValue<'ctx>>;
@@ -33,16 +40,43 @@ pub(crate) type CompiledFns<'ctx> = HashMap<InternedSymbol, FunctionValue<'ctx>>
 /// Counter for generating unique function names
 static EXPR_COUNTER: AtomicUsize = AtomicUsize::new(0);
 
+/// A function defined at the top level with `label` or `def`.
+#[derive(Clone)]
+struct DefinedFn {
+    params: Vec<InternedSymbol>,
+    body: Value,
+    /// Distinguishes this definition from earlier ones of the same name
+    generation: usize,
+    /// The interpreter's value it was compiled from, if it came from one
+    source: Option<Value>,
+}
+
+/// Whether `value` is the symbol `name`.
+fn is_symbol(value: &Value, name: &str) -> bool {
+    matches!(value, Value::Atom(AtomType::Symbol(SymbolType::Symbol(s))) if s.resolve() == name)
+}
+
+/// Whether a lambda takes only required parameters, the kind the JIT compiles.
+fn is_simple(params: &ParamSpec) -> bool {
+    params.optional.is_empty() && params.rest.is_none() && params.keys.is_empty()
+}
+
 /// JIT execution engine for compiling and running Consair expressions.
 pub struct JitEngine {
     /// LLVM context - must be kept alive as long as execution engine exists
     context: Context,
     /// Cache configuration
     cache_config: CacheConfig,
-    /// Cache for pure expression results: hash -> (result_tag, result_data)
-    result_cache: std::cell::RefCell<HashMap<u64, (u8, u64)>>,
-    /// Cache statistics
-    stats: std::cell::RefCell<CacheStats>,
+    /// Cache for pure expression results and its statistics, shared with
+    /// the functions `register_jit_introspection` installs
+    cache: Arc<Mutex<ResultCache>>,
+    /// Values of define-constant names, inlined wherever the symbol appears
+    constants: std::cell::RefCell<HashMap<InternedSymbol, Value>>,
+    /// Quoted lists built at compile time and shared by every execution.
+    /// The engine holds one reference to each until it is dropped.
+    quoted: std::cell::RefCell<Vec<RuntimeValue>>,
+    /// Top-level functions, compiled into each module that calls them
+    functions: std::cell::RefCell<HashMap<InternedSymbol, DefinedFn>>,
 }
 
 impl JitEngine {
@@ -52,49 +86,166 @@ impl JitEngine {
     }
 
     /// Create a new JIT engine with custom configuration.
+    ///
+    /// Fails if LLVM's native target cannot be initialized or an execution
+    /// engine cannot be created for it, so callers can report why the JIT is
+    /// unavailable instead of failing on the first eval.
     pub fn with_config(cache_config: CacheConfig) -> Result<Self, String> {
+        Target::initialize_native(&InitializationConfig::default())
+            .map_err(|e| format!("native target initialization failed: {e}"))?;
+
+        let context = Context::create();
+        {
+            // Probe execution engine creation with an empty module
+            let module = context.create_module("__consair_probe");
+            module
+                .create_jit_execution_engine(OptimizationLevel::None)
+                .map_err(|e| format!("execution engine creation failed: {e}"))?;
+        }
+
         Ok(JitEngine {
-            context: Context::create(),
+            context,
             cache_config,
-            result_cache: std::cell::RefCell::new(HashMap::new()),
-            stats: std::cell::RefCell::new(CacheStats::default()),
+            cache: Arc::default(),
+            constants: std::cell::RefCell::new(HashMap::new()),
+            quoted: std::cell::RefCell::new(Vec::new()),
+            functions: std::cell::RefCell::new(HashMap::new()),
         })
     }
 
     /// Get cache statistics.
     pub fn cache_stats(&self) -> CacheStats {
-        self.stats.borrow().clone()
+        self.cache.lock().unwrap().stats()
     }
 
     /// Clear the result cache.
     pub fn clear_cache(&self) {
-        self.result_cache.borrow_mut().clear();
+        self.cache.lock().unwrap().clear();
+    }
+
+    /// The result cache, for Lisp functions that inspect or clear it.
+    pub(crate) fn shared_cache(&self) -> Arc<Mutex<ResultCache>> {
+        Arc::clone(&self.cache)
+    }
+
+    /// Record a constant so compiled code can inline it.
+    ///
+    /// Callers that evaluate `define-constant` with the interpreter use this
+    /// to keep both engines in agreement. The same rules apply as in the
+    /// interpreter: the value must be an integer, float, t or nil, and an
+    /// existing constant may only be redefined with an equal value.
+    pub fn define_constant(&self, name: InternedSymbol, value: Value) -> Result<(), String> {
+        let mut constants = self.constants.borrow_mut();
+        check_constant(&name.resolve(), constants.get(&name), &value)?;
+        constants.entry(name).or_insert(value);
+        Ok(())
+    }
+
+    /// The value of a constant, if `name` has been defined as one.
+    pub fn constant(&self, name: InternedSymbol) -> Option<Value> {
+        self.constants.borrow().get(&name).cloned()
+    }
+
+    /// Record the value a top-level `label` or `def` gave `name`.
+    ///
+    /// Callers that evaluate definitions with the interpreter use this, as
+    /// with `define_constant`. A lambda with only required parameters, made
+    /// in the global environment, is compiled into each later expression that
+    /// calls `name`; any other value forgets an earlier definition, so calls
+    /// fall back to the interpreter. A closure over local bindings is never
+    /// compiled, since its body would see globals of the same names instead.
+    pub fn define_function(&self, name: InternedSymbol, value: &Value) {
+        match value {
+            Value::Lambda(lambda) if is_simple(&lambda.params) && lambda.env.depth() == 0 => self
+                .add_function(
+                    name,
+                    lambda.params.required.clone(),
+                    lambda.body.clone(),
+                    Some(value.clone()),
+                ),
+            _ => {
+                if self.functions.borrow_mut().remove(&name).is_some() {
+                    self.clear_cache();
+                }
+            }
+        }
+    }
+
+    /// Bring the top-level functions up to date with `env`, where the
+    /// interpreter may have changed them since they were defined.
+    ///
+    /// `set!` and `with-redefs` are evaluated by the interpreter, and may run
+    /// inside any function it calls, so callers that share definitions with
+    /// `define_function` call this before each expression they compile. A
+    /// function whose name `env` now binds to another value is redefined from
+    /// that value, or forgotten if it cannot be compiled.
+    pub fn refresh_functions(&self, env: &Environment) {
+        let stale: Vec<(InternedSymbol, Value)> = self
+            .functions
+            .borrow()
+            .iter()
+            .filter_map(|(name, defined)| {
+                let current = env.lookup(&name.resolve())?;
+                (defined.source.as_ref() != Some(&current)).then_some((*name, current))
+            })
+            .collect();
+        for (name, value) in stale {
+            self.define_function(name, &value);
+        }
+    }
+
+    /// Whether calls to `name` compile to a top-level function.
+    pub fn has_function(&self, name: InternedSymbol) -> bool {
+        self.functions.borrow().contains_key(&name)
+    }
+
+    /// Replace the definition of `name`. Cached results may have called the
+    /// old one, so they are dropped.
+    fn add_function(
+        &self,
+        name: InternedSymbol,
+        params: Vec<InternedSymbol>,
+        body: Value,
+        source: Option<Value>,
+    ) {
+        let generation = EXPR_COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        self.functions.borrow_mut().insert(
+            name,
+            DefinedFn {
+                params,
+                body,
+                generation,
+                source,
+            },
+        );
+        self.clear_cache();
     }
 
     /// Compile and execute a single expression.
     pub fn eval(&self, expr: &Value) -> Result<RuntimeValue, String> {
         // Check cache for pure expressions
         if self.cache_config.enabled && is_pure_expression(expr) {
-            let hash = hash_expression(expr);
-
-            // Try cache lookup
-            if let Some(&(tag, data)) = self.result_cache.borrow().get(&hash) {
-                let mut stats = self.stats.borrow_mut();
-                stats.hits += 1;
-                stats.compilations_avoided += 1;
-                return Ok(RuntimeValue { tag, data });
+            let form = canonical_form(expr);
+            let hash = hash_form(&form);
+
+            // Try cache lookup; the form is compared, so a collision misses
+            {
+                let mut cache = self.cache.lock().unwrap();
+                if let Some((tag, data)) = cache.get(hash, &form) {
+                    cache.stats.hits += 1;
+                    cache.stats.compilations_avoided += 1;
+                    return Ok(RuntimeValue { tag, data });
+                }
             }
 
             // Cache miss - compile and cache result
             let result = self.compile_and_execute(expr)?;
 
-            // Store in cache if not at capacity
-            let mut cache = self.result_cache.borrow_mut();
-            if cache.len() < self.cache_config.max_entries {
-                cache.insert(hash, (result.tag, result.data));
-            }
+            // Store in cache, evicting an older result if it is full
+            let mut cache = self.cache.lock().unwrap();
+            cache.insert(hash, form, (result.tag, result.data), &self.cache_config);
 
-            self.stats.borrow_mut().misses += 1;
+            cache.stats.misses += 1;
             return Ok(result);
         }
 
@@ -115,11 +266,7 @@ impl JitEngine {
         let env = JitEnv::new();
         let lambdas = LambdaStore::new();
         let compiled_fns = CompiledFns::new();
-        let _compiled =
-            self.compile_expr(&codegen, expr, &fn_name, &env, &lambdas, &compiled_fns)?;
-
-        // Verify the module
-        codegen.verify()?;
+        self.compile_module(&codegen, expr, &fn_name, &env, &lambdas, &compiled_fns)?;
 
         // Create execution engine
         let execution_engine = codegen
@@ -137,10 +284,15 @@ impl JitEngine {
                 .map_err(|e| e.to_string())?
         };
 
-        // Execute the function
-        let result = unsafe { func.call() };
-
-        Ok(result)
+        // Execute the function, freeing whatever it allocated that the
+        // result does not reach. A runtime error such as integer overflow
+        // fails the evaluation, so callers fall back to the interpreter and
+        // the result is not cached
+        let result = crate::runtime::run_compiled(|| unsafe { func.call() });
+        match result.to_error() {
+            Some(error) => Err(format!("{error} in compiled code")),
+            None => Ok(result),
+        }
     }
 
     /// Compile and execute an expression with macro expansion.
@@ -155,6 +307,7 @@ impl JitEngine {
     ) -> Result<RuntimeValue, String> {
         // Expand all macros recursively using the interpreter's environment
         let expanded = expand_all_macros(expr.clone(), env, 0)?;
+        self.refresh_functions(env);
 
         // Compile and execute the expanded expression
         self.eval(&expanded)
@@ -179,22 +332,60 @@ impl JitEngine {
     /// }
     /// ```
     pub fn compile(&self, expr: &Value) -> Result<CompiledExpr<'_>, String> {
+        self.compile_in(&self.context, expr)
+    }
+
+    /// Compile a pure expression into a handle that owns its LLVM context,
+    /// so it can be moved to other threads and executed from several at once.
+    ///
+    /// Fails for expressions that read variables or have side effects.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let engine = JitEngine::new()?;
+    /// let shared = Arc::new(engine.compile_shared(&parse("(* 6 7)").unwrap())?);
+    /// let worker = Arc::clone(&shared);
+    /// std::thread::spawn(move || assert_eq!(worker.execute().to_int(), Some(42)));
+    /// ```
+    pub fn compile_shared(&self, expr: &Value) -> Result<SharedCompiledExpr, String> {
+        if !is_pure_expression(expr) {
+            return Err(
+                "only pure expressions (no variables or side effects) can be shared".to_string(),
+            );
+        }
+        let context = Box::new(Context::create());
+        // SAFETY: the context is boxed, so it stays put when the handle
+        // moves, and the handle drops the compiled code before the context
+        let context_ref: &'static Context = unsafe { &*(context.as_ref() as *const Context) };
+        let quoted_before = self.quoted.borrow().len();
+        let inner = self.compile_in(context_ref, expr)?;
+        // The quoted lists belong to the handle, which may outlive the engine
+        let quoted = self.quoted.borrow_mut().split_off(quoted_before);
+        Ok(SharedCompiledExpr {
+            inner,
+            quoted,
+            context,
+        })
+    }
+
+    /// Compile an expression into a function in a new module of `context`.
+    fn compile_in<'ctx>(
+        &self,
+        context: &'ctx Context,
+        expr: &Value,
+    ) -> Result<CompiledExpr<'ctx>, String> {
         // Generate unique function name
         let counter = EXPR_COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
         let fn_name = format!("__consair_compiled_{}", counter);
 
         // Create code generator
-        let codegen = Codegen::new(&self.context, &fn_name);
+        let codegen = Codegen::new(context, &fn_name);
 
         // Compile the expression into a function
         let env = JitEnv::new();
         let lambdas = LambdaStore::new();
         let compiled_fns = CompiledFns::new();
-        let _compiled =
-            self.compile_expr(&codegen, expr, &fn_name, &env, &lambdas, &compiled_fns)?;
-
-        // Verify the module
-        codegen.verify()?;
+        self.compile_module(&codegen, expr, &fn_name, &env, &lambdas, &compiled_fns)?;
 
         // Create execution engine
         let execution_engine = codegen
@@ -218,9 +409,101 @@ impl JitEngine {
         Ok(CompiledExpr {
             execution_engine,
             func_ptr,
+            pure: is_pure_expression(expr),
+            counters: Default::default(),
         })
     }
 
+    /// Compile a lambda once so it can be called from Rust with different
+    /// arguments.
+    ///
+    /// The lambda is compiled like any other expression and evaluated once to
+    /// build its closure. `CompiledFn::call` then calls the closure's code
+    /// directly through the uniform closure calling convention.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let engine = JitEngine::new()?;
+    /// let add = engine.compile_function(&parse("(lambda (a b) (+ a b))").unwrap())?;
+    /// let args = [RuntimeValue::from_int(2), RuntimeValue::from_int(3)];
+    /// assert_eq!(add.call(&args)?.to_int(), Some(5));
+    /// ```
+    pub fn compile_function(&self, lambda_expr: &Value) -> Result<CompiledFn<'_>, String> {
+        let arity = match lambda_expr {
+            Value::Cons(cell) if is_symbol(&cell.car, "lambda") => {
+                let parts = self.collect_args(&cell.cdr)?;
+                if parts.len() < 2 {
+                    return Err("lambda requires parameters and body".to_string());
+                }
+                self.collect_param_symbols(&parts[0])?.len()
+            }
+            _ => return Err("compile_function expects a lambda expression".to_string()),
+        };
+        let expr = self.compile(lambda_expr)?;
+        let closure = expr.execute();
+        if !closure.is_closure() {
+            return Err("lambda did not compile to a closure".to_string());
+        }
+        Ok(CompiledFn {
+            expr,
+            closure,
+            arity,
+            counters: Default::default(),
+        })
+    }
+
+    /// Compile an expression into a verified module.
+    ///
+    /// The labels the expression defines at its top level are declared
+    /// first, so functions defined together can call each other. If
+    /// compilation fails, the top-level functions are put back as they were.
+    fn compile_module<'ctx>(
+        &self,
+        codegen: &Codegen<'ctx>,
+        expr: &Value,
+        fn_name: &str,
+        env: &JitEnv<'ctx>,
+        lambdas: &LambdaStore,
+        compiled_fns: &CompiledFns<'ctx>,
+    ) -> Result<(), String> {
+        let previous = self.functions.borrow().clone();
+        self.declare_labels(expr);
+        let result = self
+            .compile_expr(codegen, expr, fn_name, env, lambdas, compiled_fns)
+            .and_then(|_| codegen.verify());
+        if result.is_err() {
+            *self.functions.borrow_mut() = previous;
+            self.clear_cache();
+        }
+        result
+    }
+
+    /// Record the functions defined by `(label name (lambda ...))` forms at
+    /// the top of `expr`: the expression itself, or the forms of a `do`.
+    fn declare_labels(&self, expr: &Value) {
+        let forms = match expr {
+            Value::Cons(cell) if is_symbol(&cell.car, "do") => {
+                self.collect_args(&cell.cdr).unwrap_or_default()
+            }
+            _ => vec![expr.clone()],
+        };
+        for form in &forms {
+            let Value::Cons(cell) = form else { continue };
+            if !is_symbol(&cell.car, "label") {
+                continue;
+            }
+            if let Ok(args) = self.collect_args(&cell.cdr)
+                && let [
+                    Value::Atom(AtomType::Symbol(SymbolType::Symbol(name))),
+                    lambda,
+                ] = args.as_slice()
+                && let Ok((params, body)) = self.label_lambda(lambda)
+            {
+                self.declare_function(*name, params, body);
+            }
+        }
+    }
+
     /// Compile an expression into LLVM IR.
     fn compile_expr<'ctx>(
         &self,
@@ -288,6 +571,18 @@ impl JitEngine {
                     return Ok(*val);
                 }
 
+                // Constants are inlined as immediates
+                if let Some(constant) = self.constant(*interned) {
+                    return self.compile_value(
+                        codegen,
+                        &constant,
+                        env,
+                        lambdas,
+                        compiled_fns,
+                        tail_position,
+                    );
+                }
+
                 // Special symbols that evaluate to themselves
                 let sym_str = interned.resolve();
                 if sym_str == "t" {
@@ -316,6 +611,10 @@ impl JitEngine {
                 Err("JIT string literals not yet supported".to_string())
             }
 
+            Value::Atom(AtomType::Char(_)) => {
+                Err("JIT character literals not yet supported".to_string())
+            }
+
             Value::Cons(cell) => {
                 // Try to compile as a function call
                 self.compile_call(
@@ -353,7 +652,11 @@ impl JitEngine {
 
             Value::Reduced(_) => Err("JIT reduced values not yet supported".to_string()),
 
-            Value::NativeFn(_) => Err("Native functions cannot be JIT compiled".to_string()),
+            Value::NativeFn(_) | Value::NativeClosure(_) => {
+                Err("Native functions cannot be JIT compiled".to_string())
+            }
+
+            Value::Ref(_) => Err(JitError::unsupported_type("JIT does not support refs").into()),
         }
     }
 
@@ -377,14 +680,28 @@ impl JitEngine {
             match sym_str.as_str() {
                 // Special forms
                 "quote" => self.compile_quote(codegen, args),
+                "comment" => Ok(codegen.compile_nil()),
                 "cond" => {
                     self.compile_cond(codegen, args, env, lambdas, compiled_fns, tail_position)
                 }
                 "if" => self.compile_if(codegen, args, env, lambdas, compiled_fns, tail_position),
+                "and" | "or" => self.compile_and_or(
+                    codegen,
+                    args,
+                    sym_str == "and",
+                    env,
+                    lambdas,
+                    compiled_fns,
+                    tail_position,
+                ),
+                "let" => self.compile_let(codegen, args, env, lambdas, compiled_fns, tail_position),
+                "do" => self.compile_do(codegen, args, env, lambdas, compiled_fns, tail_position),
                 "lambda" => self.compile_closure(codegen, args, env, lambdas, compiled_fns),
                 "label" => self.compile_label(codegen, args, env, lambdas, compiled_fns),
+                "define-constant" => self.compile_define_constant(codegen, args),
                 // List operations
-                "cons" => self.compile_binary_op(
+                "cons" => self.compile_exact_binary_op(
+                    "cons",
                     codegen,
                     args,
                     codegen.rt_cons,
@@ -424,8 +741,72 @@ impl JitEngine {
                     lambdas,
                     compiled_fns,
                 ),
+                "mod" => self.compile_exact_binary_op(
+                    "mod",
+                    codegen,
+                    args,
+                    codegen.rt_mod,
+                    env,
+                    lambdas,
+                    compiled_fns,
+                ),
+                "abs" => {
+                    self.compile_unary_op(codegen, args, codegen.rt_abs, env, lambdas, compiled_fns)
+                }
+                // Bitwise operators
+                "bit-and" => self.compile_binary_op(
+                    codegen,
+                    args,
+                    codegen.rt_bit_and,
+                    env,
+                    lambdas,
+                    compiled_fns,
+                ),
+                "bit-or" => self.compile_binary_op(
+                    codegen,
+                    args,
+                    codegen.rt_bit_or,
+                    env,
+                    lambdas,
+                    compiled_fns,
+                ),
+                "bit-xor" => self.compile_binary_op(
+                    codegen,
+                    args,
+                    codegen.rt_bit_xor,
+                    env,
+                    lambdas,
+                    compiled_fns,
+                ),
+                "bit-shift-left" => self.compile_exact_binary_op(
+                    "bit-shift-left",
+                    codegen,
+                    args,
+                    codegen.rt_bit_shift_left,
+                    env,
+                    lambdas,
+                    compiled_fns,
+                ),
+                "bit-shift-right" => self.compile_exact_binary_op(
+                    "bit-shift-right",
+                    codegen,
+                    args,
+                    codegen.rt_bit_shift_right,
+                    env,
+                    lambdas,
+                    compiled_fns,
+                ),
+                "bit-not" => self.compile_unary_op(
+                    codegen,
+                    args,
+                    codegen.rt_bit_not,
+                    env,
+                    lambdas,
+                    compiled_fns,
+                ),
                 // Comparison operators
-                "=" => self.compile_binary_op(
+                "=" => self.compile_exact_binary_op(
+                    "=",
                     codegen,
                     args,
                     codegen.rt_num_eq,
@@ -433,13 +814,26 @@ impl JitEngine {
                     lambdas,
                     compiled_fns,
                 ),
-                "<" => {
-                    self.compile_binary_op(codegen, args, codegen.rt_lt, env, lambdas, compiled_fns)
-                }
-                ">" => {
-                    self.compile_binary_op(codegen, args, codegen.rt_gt, env, lambdas, compiled_fns)
-                }
-                "<=" => self.compile_binary_op(
+                "<" => self.compile_exact_binary_op(
+                    "<",
+                    codegen,
+                    args,
+                    codegen.rt_lt,
+                    env,
+                    lambdas,
+                    compiled_fns,
+                ),
+                ">" => self.compile_exact_binary_op(
+                    ">",
+                    codegen,
+                    args,
+                    codegen.rt_gt,
+                    env,
+                    lambdas,
+                    compiled_fns,
+                ),
+                "<=" => self.compile_exact_binary_op(
+                    "<=",
                     codegen,
                     args,
                     codegen.rt_lte,
@@ -447,7 +841,8 @@ impl JitEngine {
                     lambdas,
                     compiled_fns,
                 ),
-                ">=" => self.compile_binary_op(
+                ">=" => self.compile_exact_binary_op(
+                    ">=",
                     codegen,
                     args,
                     codegen.rt_gte,
@@ -456,9 +851,15 @@ impl JitEngine {
                     compiled_fns,
                 ),
                 // Equality and type predicates
-                "eq" => {
-                    self.compile_binary_op(codegen, args, codegen.rt_eq, env, lambdas, compiled_fns)
-                }
+                "eq" => self.compile_exact_binary_op(
+                    "eq",
+                    codegen,
+                    args,
+                    codegen.rt_eq,
+                    env,
+                    lambdas,
+                    compiled_fns,
+                ),
                 "atom" => self.compile_unary_op(
                     codegen,
                     args,
@@ -504,7 +905,8 @@ impl JitEngine {
                     lambdas,
                     compiled_fns,
                 ),
-                "append" => self.compile_binary_op(
+                "append" => self.compile_exact_binary_op(
+                    "append",
                     codegen,
                     args,
                     codegen.rt_append,
@@ -520,7 +922,8 @@ impl JitEngine {
                     lambdas,
                     compiled_fns,
                 ),
-                "nth" => self.compile_binary_op(
+                "nth" => self.compile_exact_binary_op(
+                    "nth",
                     codegen,
                     args,
                     codegen.rt_nth,
@@ -574,6 +977,27 @@ impl JitEngine {
                             compiled_fns,
                         );
                     }
+                    // A function defined at the top level, unless a local shadows it
+                    if !env.contains_key(sym)
+                        && let Some(function) = self.defined_function(codegen, *sym)?
+                    {
+                        let expected = function.count_params() as usize;
+                        let given = self.collect_args(args)?.len();
+                        if given != expected {
+                            return Err(format!(
+                                "{sym_str} expects {expected} arguments, got {given}"
+                            ));
+                        }
+                        return self.compile_recursive_call(
+                            codegen,
+                            function,
+                            args,
+                            env,
+                            lambdas,
+                            compiled_fns,
+                            tail_position,
+                        );
+                    }
                     Err(format!("JIT does not yet support operator: {}", sym_str))
                 }
             }
@@ -693,19 +1117,9 @@ impl JitEngine {
                         return Err("lambda requires parameters and body".to_string());
                     }
                     let params = &lambda_parts[0];
-                    let body = lambda_parts[1].clone();
-
-                    let param_names = self.collect_args(params)?;
-                    let param_symbols: Vec<InternedSymbol> = param_names
-                        .iter()
-                        .map(|p| {
-                            if let Value::Atom(AtomType::Symbol(SymbolType::Symbol(sym))) = p {
-                                Ok(*sym)
-                            } else {
-                                Err("lambda parameters must be symbols".to_string())
-                            }
-                        })
-                        .collect::<Result<Vec<_>, _>>()?;
+                    let body = lambda_body(&lambda_parts[1..]);
+
+                    let param_symbols = self.collect_param_symbols(params)?;
                     (param_symbols, body)
                 } else {
                     return Err("label second argument must be a lambda".to_string());
@@ -795,34 +1209,236 @@ impl JitEngine {
             .ok_or_else(|| "Label call did not return a value".to_string())?
             .into_struct_value();
 
-        Ok(call_result)
+        Ok(call_result)
+    }
+
+    /// Compile a label expression: (label name lambda-expr)
+    ///
+    /// The lambda becomes a top-level function, compiled here and into every
+    /// later expression that calls it. Labels of other values are left to
+    /// the interpreter.
+    fn compile_label<'ctx>(
+        &self,
+        codegen: &Codegen<'ctx>,
+        args: &Value,
+        _env: &JitEnv<'ctx>,
+        _lambdas: &LambdaStore,
+        _compiled_fns: &CompiledFns<'ctx>,
+    ) -> Result<inkwell::values::StructValue<'ctx>, String> {
+        let arg_values = self.collect_args(args)?;
+        if arg_values.len() != 2 {
+            return Err("label requires exactly 2 arguments: name and lambda".to_string());
+        }
+
+        // Get the name
+        let name = match &arg_values[0] {
+            Value::Atom(AtomType::Symbol(SymbolType::Symbol(sym))) => *sym,
+            _ => return Err("label name must be a symbol".to_string()),
+        };
+        if self.constant(name).is_some() {
+            return Err(format!("label: cannot redefine constant {name}"));
+        }
+
+        let (params, body) = self.label_lambda(&arg_values[1])?;
+        self.declare_function(name, params, body);
+
+        // Compile now so an unsupported body falls back, and the definition
+        // is forgotten, before anything calls it
+        self.defined_function(codegen, name)?;
+
+        // Lambdas are not values in compiled code, so label returns nil
+        Ok(codegen.compile_nil())
+    }
+
+    /// The parameters and body of the `(lambda ...)` form a label defines.
+    fn label_lambda(&self, value: &Value) -> Result<(Vec<InternedSymbol>, Value), String> {
+        match value {
+            Value::Cons(cell) if is_symbol(&cell.car, "lambda") => {
+                let parts = self.collect_args(&cell.cdr)?;
+                let Some((params, body)) = parts.split_first() else {
+                    return Err("lambda requires parameters and body".to_string());
+                };
+                let spec = ParamSpec::parse(params, "lambda")?;
+                if !is_simple(&spec) {
+                    return Err("JIT label supports only required parameters".to_string());
+                }
+                Ok((spec.required, lambda_body(body)))
+            }
+            _ => Err("JIT label requires a lambda".to_string()),
+        }
+    }
+
+    /// Record a definition unless `name` already has this one, which keeps
+    /// the function declared for the expression that contains the label.
+    fn declare_function(&self, name: InternedSymbol, params: Vec<InternedSymbol>, body: Value) {
+        let unchanged = self
+            .functions
+            .borrow()
+            .get(&name)
+            .is_some_and(|f| f.params == params && f.body == body);
+        if !unchanged {
+            self.add_function(name, params, body, None);
+        }
+    }
+
+    /// The function compiled for top-level definition `name` in this module,
+    /// compiling it on first use. Its body sees only its parameters and other
+    /// top-level definitions.
+    fn defined_function<'ctx>(
+        &self,
+        codegen: &Codegen<'ctx>,
+        name: InternedSymbol,
+    ) -> Result<Option<FunctionValue<'ctx>>, String> {
+        let Some(defined) = self.functions.borrow().get(&name).cloned() else {
+            return Ok(None);
+        };
+        let fn_name = format!("__consair_fn_{}_{}", name.resolve(), defined.generation);
+        if let Some(function) = codegen.module.get_function(&fn_name) {
+            return Ok(Some(function));
+        }
+
+        let param_types: Vec<inkwell::types::BasicMetadataTypeEnum> = (0..defined.params.len())
+            .map(|_| codegen.value_type.into())
+            .collect();
+        let fn_type = codegen.value_type.fn_type(&param_types, false);
+
+        // Declared before the body is compiled, so recursive calls find it
+        let saved_block = codegen.builder.get_insert_block();
+        let function = codegen.module.add_function(&fn_name, fn_type, None);
+        let entry = self.context.append_basic_block(function, "entry");
+        codegen.builder.position_at_end(entry);
+
+        let mut fn_env = JitEnv::new();
+        for (i, sym) in defined.params.iter().enumerate() {
+            let param = function
+                .get_nth_param(i as u32)
+                .ok_or_else(|| "Failed to get function parameter".to_string())?
+                .into_struct_value();
+            fn_env.insert(*sym, param);
+        }
+        let result = self
+            .compile_value(
+                codegen,
+                &defined.body,
+                &fn_env,
+                &LambdaStore::new(),
+                &CompiledFns::new(),
+                true,
+            )
+            .and_then(|result| {
+                codegen
+                    .builder
+                    .build_return(Some(&result))
+                    .map_err(|e| e.to_string())
+            });
+
+        if let Some(block) = saved_block {
+            codegen.builder.position_at_end(block);
+        }
+        result?;
+        Ok(Some(function))
+    }
+
+    /// Compile a let expression: (let ((name value) ...) body ...)
+    ///
+    /// Bindings extend the compile-time environment one at a time, so each
+    /// value sees the bindings before it (let* semantics). The body is
+    /// compiled like a do, so its last form inherits the let's tail position,
+    /// keeping tail calls in the body optimized.
+    fn compile_let<'ctx>(
+        &self,
+        codegen: &Codegen<'ctx>,
+        args: &Value,
+        env: &JitEnv<'ctx>,
+        lambdas: &LambdaStore,
+        compiled_fns: &CompiledFns<'ctx>,
+        tail_position: bool,
+    ) -> Result<inkwell::values::StructValue<'ctx>, String> {
+        let Value::Cons(cell) = args else {
+            return Err("let requires a binding list".to_string());
+        };
+
+        let mut let_env = env.clone();
+        for (name, value_expr) in parse_let_bindings(&cell.car)? {
+            let value =
+                self.compile_value(codegen, &value_expr, &let_env, lambdas, compiled_fns, false)?;
+            let_env.insert(name, value);
+        }
+
+        self.compile_do(
+            codegen,
+            &cell.cdr,
+            &let_env,
+            lambdas,
+            compiled_fns,
+            tail_position,
+        )
+    }
+
+    /// Compile a do expression: (do form ...)
+    ///
+    /// Each form is compiled in order and only the last one's value is kept.
+    /// The last form inherits the do's tail position; an empty do is nil.
+    fn compile_do<'ctx>(
+        &self,
+        codegen: &Codegen<'ctx>,
+        args: &Value,
+        env: &JitEnv<'ctx>,
+        lambdas: &LambdaStore,
+        compiled_fns: &CompiledFns<'ctx>,
+        tail_position: bool,
+    ) -> Result<inkwell::values::StructValue<'ctx>, String> {
+        let forms = self.collect_args(args)?;
+        let Some((last, rest)) = forms.split_last() else {
+            return Ok(codegen.compile_nil());
+        };
+
+        for form in rest {
+            self.compile_value(codegen, form, env, lambdas, compiled_fns, false)?;
+        }
+        self.compile_value(codegen, last, env, lambdas, compiled_fns, tail_position)
     }
 
-    /// Compile a label expression: (label name lambda-expr)
-    fn compile_label<'ctx>(
+    /// Compile a constant definition: (define-constant name literal)
+    ///
+    /// The constant is recorded at compile time, so the value must be a
+    /// literal; computed values are left to the interpreter.
+    fn compile_define_constant<'ctx>(
         &self,
         codegen: &Codegen<'ctx>,
         args: &Value,
-        _env: &JitEnv<'ctx>,
-        _lambdas: &LambdaStore,
-        _compiled_fns: &CompiledFns<'ctx>,
     ) -> Result<inkwell::values::StructValue<'ctx>, String> {
         let arg_values = self.collect_args(args)?;
         if arg_values.len() != 2 {
-            return Err("label requires exactly 2 arguments: name and lambda".to_string());
+            return Err("define-constant requires exactly 2 arguments: name and value".to_string());
         }
 
-        // Get the name
-        let _name = match &arg_values[0] {
+        let name = match &arg_values[0] {
             Value::Atom(AtomType::Symbol(SymbolType::Symbol(sym))) => *sym,
-            _ => return Err("label name must be a symbol".to_string()),
+            _ => return Err("define-constant: first argument must be a symbol".to_string()),
         };
 
-        // The result of label is the lambda itself, which we compile
-        // but we want to return a nil since label is typically used for its side effect
-        // Actually, in Consair, (label name fn) evaluates to the fn value
-        // For JIT, since we can't return lambdas as values, return nil
-        Ok(codegen.compile_nil())
+        let value = match &arg_values[1] {
+            Value::Atom(AtomType::Symbol(SymbolType::Symbol(sym))) if sym.resolve() == "t" => {
+                Value::Atom(AtomType::Bool(true))
+            }
+            Value::Atom(AtomType::Symbol(SymbolType::Symbol(sym))) if sym.resolve() == "nil" => {
+                Value::Nil
+            }
+            Value::Atom(AtomType::Symbol(_)) | Value::Cons(_) => {
+                return Err("JIT define-constant requires a literal value".to_string());
+            }
+            literal => literal.clone(),
+        };
+
+        self.define_constant(name, value.clone())?;
+        match value {
+            Value::Nil => Ok(codegen.compile_nil()),
+            Value::Atom(AtomType::Bool(b)) => Ok(codegen.compile_bool(b)),
+            Value::Atom(AtomType::Number(NumericType::Int(n))) => Ok(codegen.compile_int(n)),
+            Value::Atom(AtomType::Number(NumericType::Float(f))) => Ok(codegen.compile_float(f)),
+            _ => unreachable!("define_constant only accepts literal values"),
+        }
     }
 
     /// Compile a lambda call: ((lambda (params) body) args)
@@ -842,20 +1458,10 @@ impl JitEngine {
         }
 
         let params = &parts[0];
-        let body = &parts[1];
+        let body = &lambda_body(&parts[1..]);
 
         // Collect parameter names
-        let param_names = self.collect_args(params)?;
-        let param_symbols: Vec<InternedSymbol> = param_names
-            .iter()
-            .map(|p| {
-                if let Value::Atom(AtomType::Symbol(SymbolType::Symbol(sym))) = p {
-                    Ok(*sym)
-                } else {
-                    Err("lambda parameters must be symbols".to_string())
-                }
-            })
-            .collect::<Result<Vec<_>, _>>()?;
+        let param_symbols = self.collect_param_symbols(params)?;
 
         // Compile arguments
         let arg_values = self.collect_args(args)?;
@@ -904,20 +1510,10 @@ impl JitEngine {
         }
 
         let params = &parts[0];
-        let body = &parts[1];
+        let body = &lambda_body(&parts[1..]);
 
         // Collect parameter names
-        let param_names = self.collect_args(params)?;
-        let param_symbols: Vec<InternedSymbol> = param_names
-            .iter()
-            .map(|p| {
-                if let Value::Atom(AtomType::Symbol(SymbolType::Symbol(sym))) = p {
-                    Ok(*sym)
-                } else {
-                    Err("lambda parameters must be symbols".to_string())
-                }
-            })
-            .collect::<Result<Vec<_>, _>>()?;
+        let param_symbols = self.collect_param_symbols(params)?;
 
         // Find free variables in the body
         let mut bound_vars: HashSet<InternedSymbol> = param_symbols.iter().cloned().collect();
@@ -926,7 +1522,11 @@ impl JitEngine {
             bound_vars.insert(*key);
         }
         let free_vars = find_free_variables(body, &bound_vars);
-        let free_var_list: Vec<InternedSymbol> = free_vars.into_iter().collect();
+        // Constants are inlined rather than captured
+        let free_var_list: Vec<InternedSymbol> = free_vars
+            .into_iter()
+            .filter(|sym| self.constant(*sym).is_none())
+            .collect();
 
         // Generate a unique function name for the closure
         let counter = EXPR_COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
@@ -1373,6 +1973,27 @@ impl JitEngine {
         Ok(result)
     }
 
+    /// Compile an operator that takes exactly two arguments. Any other count
+    /// is left to the interpreter, which reports it as an arity error, rather
+    /// than folded left to right.
+    #[allow(clippy::too_many_arguments)]
+    fn compile_exact_binary_op<'ctx>(
+        &self,
+        name: &str,
+        codegen: &Codegen<'ctx>,
+        args: &Value,
+        func: inkwell::values::FunctionValue<'ctx>,
+        env: &JitEnv<'ctx>,
+        lambdas: &LambdaStore,
+        compiled_fns: &CompiledFns<'ctx>,
+    ) -> Result<inkwell::values::StructValue<'ctx>, String> {
+        let count = self.collect_args(args)?.len();
+        if count != 2 {
+            return Err(format!("{name} expects 2 arguments, got {count}"));
+        }
+        self.compile_binary_op(codegen, args, func, env, lambdas, compiled_fns)
+    }
+
     /// Compile the minus operator, which can be unary or binary.
     fn compile_minus<'ctx>(
         &self,
@@ -1409,8 +2030,9 @@ impl JitEngine {
 
     /// Compile a cond expression with branching.
     ///
-    /// `tail_position` indicates whether the cond expression itself is in tail position,
-    /// which propagates to the result expressions of each clause for TCO.
+    /// Each clause's body is compiled like a `do`. `tail_position` indicates
+    /// whether the cond expression itself is in tail position, which
+    /// propagates to the last body form of each clause for TCO.
     fn compile_cond<'ctx>(
         &self,
         codegen: &Codegen<'ctx>,
@@ -1447,33 +2069,32 @@ impl JitEngine {
 
         // Process each clause
         for (i, clause) in clauses.iter().enumerate() {
-            // Each clause should be a list of (test result)
-            let clause_parts = self.collect_args(clause)?;
-            if clause_parts.len() < 2 {
-                return Err("cond clause must have at least 2 elements".to_string());
+            // Each clause is a test followed by any number of body forms
+            let (test_expr, body) = match clause {
+                Value::Cons(cell) => (&cell.car, &cell.cdr),
+                _ => return Err("cond clause must be a list".to_string()),
+            };
+            let last = i == clauses.len() - 1;
+
+            // A 't' or final 'else' test is always true
+            let always = is_else(test_expr)
+                || matches!(
+                    test_expr,
+                    Value::Atom(AtomType::Symbol(SymbolType::Symbol(sym)))
+                        if sym.resolve() == "t"
+                );
+            if is_else(test_expr) && !last {
+                return Err("cond: else must be the last clause".to_string());
             }
 
-            let test_expr = &clause_parts[0];
-            let result_expr = &clause_parts[1];
-
-            // Check if this is the final 't' clause (always true)
-            let is_final_t = matches!(
-                test_expr,
-                Value::Atom(AtomType::Symbol(SymbolType::Symbol(sym)))
-                    if sym.resolve() == "t"
-            );
-
-            if is_final_t || i == clauses.len() - 1 {
-                // This is the final else clause - compile result and branch to merge
-                // Result expression is in tail position if the cond is
-                let result_val = self.compile_value(
-                    codegen,
-                    result_expr,
-                    env,
-                    lambdas,
-                    compiled_fns,
-                    tail_position,
-                )?;
+            if always {
+                // The body is in tail position if the cond is; with no body
+                // the value is t
+                let result_val = if matches!(body, Value::Nil) {
+                    codegen.compile_bool(true)
+                } else {
+                    self.compile_do(codegen, body, env, lambdas, compiled_fns, tail_position)?
+                };
                 let current = codegen
                     .builder
                     .get_insert_block()
@@ -1490,66 +2111,7 @@ impl JitEngine {
             let test_val =
                 self.compile_value(codegen, test_expr, env, lambdas, compiled_fns, false)?;
 
-            // Check if test is truthy (not nil and not false)
-            // We need to extract the tag and data from the struct
-            let tag = codegen
-                .builder
-                .build_extract_value(test_val, 0, "tag")
-                .map_err(|e| e.to_string())?
-                .into_int_value();
-
-            let data = codegen
-                .builder
-                .build_extract_value(test_val, 1, "data")
-                .map_err(|e| e.to_string())?
-                .into_int_value();
-
-            // Check if tag == TAG_NIL (0)
-            let is_nil = codegen
-                .builder
-                .build_int_compare(
-                    inkwell::IntPredicate::EQ,
-                    tag,
-                    codegen
-                        .i8_type()
-                        .const_int(crate::runtime::TAG_NIL as u64, false),
-                    "is_nil",
-                )
-                .map_err(|e| e.to_string())?;
-
-            // Check if tag == TAG_BOOL (1) and data == 0 (false)
-            let is_bool = codegen
-                .builder
-                .build_int_compare(
-                    inkwell::IntPredicate::EQ,
-                    tag,
-                    codegen
-                        .i8_type()
-                        .const_int(crate::runtime::TAG_BOOL as u64, false),
-                    "is_bool",
-                )
-                .map_err(|e| e.to_string())?;
-
-            let is_false_data = codegen
-                .builder
-                .build_int_compare(
-                    inkwell::IntPredicate::EQ,
-                    data,
-                    codegen.i64_type().const_int(0, false),
-                    "is_false_data",
-                )
-                .map_err(|e| e.to_string())?;
-
-            let is_false = codegen
-                .builder
-                .build_and(is_bool, is_false_data, "is_false")
-                .map_err(|e| e.to_string())?;
-
-            // Falsy if nil OR (bool AND data==0)
-            let is_falsy = codegen
-                .builder
-                .build_or(is_nil, is_false, "is_falsy")
-                .map_err(|e| e.to_string())?;
+            let is_falsy = codegen.build_is_falsy(test_val)?;
 
             // Create blocks for then and else
             let then_block = self
@@ -1565,16 +2127,14 @@ impl JitEngine {
                 .build_conditional_branch(is_falsy, else_block, then_block)
                 .map_err(|e| e.to_string())?;
 
-            // Compile the then block (result is in tail position if cond is)
+            // Compile the body like a do; only its last form is in tail
+            // position. A clause with no body returns the test's value
             codegen.builder.position_at_end(then_block);
-            let result_val = self.compile_value(
-                codegen,
-                result_expr,
-                env,
-                lambdas,
-                compiled_fns,
-                tail_position,
-            )?;
+            let result_val = if matches!(body, Value::Nil) {
+                test_val
+            } else {
+                self.compile_do(codegen, body, env, lambdas, compiled_fns, tail_position)?
+            };
             let then_end = codegen
                 .builder
                 .get_insert_block()
@@ -1624,6 +2184,97 @@ impl JitEngine {
         Ok(phi.as_basic_value().into_struct_value())
     }
 
+    /// Compile `(and forms...)` or `(or forms...)`, which evaluate forms
+    /// left to right and stop at the first false one (`and`) or the first
+    /// true one (`or`), returning the last value evaluated. `(and)` is t and
+    /// `(or)` is nil.
+    ///
+    /// Each form that can decide the result branches to a merge block, where
+    /// a phi picks the value, as in `compile_cond`.
+    #[allow(clippy::too_many_arguments)]
+    fn compile_and_or<'ctx>(
+        &self,
+        codegen: &Codegen<'ctx>,
+        args: &Value,
+        is_and: bool,
+        env: &JitEnv<'ctx>,
+        lambdas: &LambdaStore,
+        compiled_fns: &CompiledFns<'ctx>,
+        tail_position: bool,
+    ) -> Result<inkwell::values::StructValue<'ctx>, String> {
+        let forms = self.collect_args(args)?;
+        let Some((last, rest)) = forms.split_last() else {
+            return Ok(if is_and {
+                codegen.compile_bool(true)
+            } else {
+                codegen.compile_nil()
+            });
+        };
+        let name = if is_and { "and" } else { "or" };
+
+        let function = codegen
+            .builder
+            .get_insert_block()
+            .and_then(|block| block.get_parent())
+            .ok_or("No current function")?;
+        let merge_block = self
+            .context
+            .append_basic_block(function, &format!("{name}_merge"));
+
+        let mut phi_incoming: Vec<(
+            inkwell::values::BasicValueEnum<'_>,
+            inkwell::basic_block::BasicBlock<'_>,
+        )> = Vec::new();
+
+        for (i, form) in rest.iter().enumerate() {
+            // Only the last form is in tail position
+            let value = self.compile_value(codegen, form, env, lambdas, compiled_fns, false)?;
+            let is_falsy = codegen.build_is_falsy(value)?;
+            let current = codegen
+                .builder
+                .get_insert_block()
+                .ok_or("No current block")?;
+            phi_incoming.push((value.into(), current));
+
+            let next_block = self
+                .context
+                .append_basic_block(function, &format!("{name}_next_{i}"));
+            // A false value decides `and`; a true one decides `or`
+            let (on_falsy, on_truthy) = if is_and {
+                (merge_block, next_block)
+            } else {
+                (next_block, merge_block)
+            };
+            codegen
+                .builder
+                .build_conditional_branch(is_falsy, on_falsy, on_truthy)
+                .map_err(|e| e.to_string())?;
+            codegen.builder.position_at_end(next_block);
+        }
+
+        let value = self.compile_value(codegen, last, env, lambdas, compiled_fns, tail_position)?;
+        let current = codegen
+            .builder
+            .get_insert_block()
+            .ok_or("No current block")?;
+        phi_incoming.push((value.into(), current));
+        codegen
+            .builder
+            .build_unconditional_branch(merge_block)
+            .map_err(|e| e.to_string())?;
+
+        codegen.builder.position_at_end(merge_block);
+        let phi = codegen
+            .builder
+            .build_phi(codegen.value_type, &format!("{name}_result"))
+            .map_err(|e| e.to_string())?;
+        for (val, block) in &phi_incoming {
+            phi.add_incoming(&[(val, *block)]);
+        }
+
+        Ok(phi.as_basic_value().into_struct_value())
+    }
+
     /// Compile an if expression: (if test then else)
     fn compile_if<'ctx>(
         &self,
@@ -1657,65 +2308,7 @@ impl JitEngine {
         // Compile the test expression (test is NOT in tail position)
         let test_val = self.compile_value(codegen, test_expr, env, lambdas, compiled_fns, false)?;
 
-        // Check if test is truthy (not nil and not false)
-        let tag = codegen
-            .builder
-            .build_extract_value(test_val, 0, "tag")
-            .map_err(|e| e.to_string())?
-            .into_int_value();
-
-        let data = codegen
-            .builder
-            .build_extract_value(test_val, 1, "data")
-            .map_err(|e| e.to_string())?
-            .into_int_value();
-
-        // Check if tag == TAG_NIL
-        let is_nil = codegen
-            .builder
-            .build_int_compare(
-                inkwell::IntPredicate::EQ,
-                tag,
-                codegen
-                    .i8_type()
-                    .const_int(crate::runtime::TAG_NIL as u64, false),
-                "is_nil",
-            )
-            .map_err(|e| e.to_string())?;
-
-        // Check if tag == TAG_BOOL and data == 0 (false)
-        let is_bool = codegen
-            .builder
-            .build_int_compare(
-                inkwell::IntPredicate::EQ,
-                tag,
-                codegen
-                    .i8_type()
-                    .const_int(crate::runtime::TAG_BOOL as u64, false),
-                "is_bool",
-            )
-            .map_err(|e| e.to_string())?;
-
-        let is_false_data = codegen
-            .builder
-            .build_int_compare(
-                inkwell::IntPredicate::EQ,
-                data,
-                codegen.i64_type().const_int(0, false),
-                "is_false_data",
-            )
-            .map_err(|e| e.to_string())?;
-
-        let is_false = codegen
-            .builder
-            .build_and(is_bool, is_false_data, "is_false")
-            .map_err(|e| e.to_string())?;
-
-        // Falsy if nil OR (bool AND data==0)
-        let is_falsy = codegen
-            .builder
-            .build_or(is_nil, is_false, "is_falsy")
-            .map_err(|e| e.to_string())?;
+        let is_falsy = codegen.build_is_falsy(test_val)?;
 
         // Create blocks
         let then_block = self.context.append_basic_block(function, "if_then");
@@ -1785,6 +2378,25 @@ impl JitEngine {
         Ok(phi.as_basic_value().into_struct_value())
     }
 
+    /// Collect the parameter names of a lambda.
+    ///
+    /// Only fixed parameter lists are compiled; `&opt`, `&rest` and `&key`
+    /// lambdas are left to the interpreter.
+    fn collect_param_symbols(&self, params: &Value) -> Result<Vec<InternedSymbol>, String> {
+        self.collect_args(params)?
+            .iter()
+            .map(|p| match p {
+                Value::Atom(AtomType::Symbol(SymbolType::Symbol(sym)))
+                    if sym.resolve().starts_with('&') =>
+                {
+                    Err(format!("JIT does not support {sym} parameters"))
+                }
+                Value::Atom(AtomType::Symbol(SymbolType::Symbol(sym))) => Ok(*sym),
+                _ => Err("lambda parameters must be symbols".to_string()),
+            })
+            .collect()
+    }
+
     /// Compile a quote expression - returns the argument unevaluated.
     fn compile_quote<'ctx>(
         &self,
@@ -1802,7 +2414,6 @@ impl JitEngine {
     }
 
     /// Compile a quoted value (builds data structures without evaluating).
-    #[allow(clippy::only_used_in_recursion)]
     fn compile_quoted_value<'ctx>(
         &self,
         codegen: &Codegen<'ctx>,
@@ -1838,19 +2449,18 @@ impl JitEngine {
                 Ok(codegen.compile_symbol(key))
             }
 
-            Value::Cons(cell) => {
-                // Build the cons cell at runtime using rt_cons
-                let car_val = self.compile_quoted_value(codegen, &cell.car)?;
-                let cdr_val = self.compile_quoted_value(codegen, &cell.cdr)?;
+            Value::Cons(_) => {
+                // Quoted data is immutable, so the list is built once here
+                // and every execution returns a new reference to it instead
+                // of rebuilding it with rt_cons.
+                let list = RuntimeValue::from_value(value)?;
+                self.quoted.borrow_mut().push(list);
 
-                let result = codegen
+                let result = codegen.const_runtime_value(list.tag, list.data);
+                codegen
                     .builder
-                    .build_call(codegen.rt_cons, &[car_val.into(), cdr_val.into()], "cons")
-                    .map_err(|e| e.to_string())?
-                    .try_as_basic_value()
-                    .left()
-                    .ok_or_else(|| "cons did not return a value".to_string())?
-                    .into_struct_value();
+                    .build_call(codegen.rt_incref, &[result.into()], "quoted_ref")
+                    .map_err(|e| e.to_string())?;
 
                 Ok(result)
             }
@@ -1859,6 +2469,10 @@ impl JitEngine {
                 Err("JIT does not yet support quoted strings".to_string())
             }
 
+            Value::Atom(AtomType::Char(_)) => {
+                Err("JIT does not yet support quoted characters".to_string())
+            }
+
             Value::Vector(_) => Err("JIT does not yet support quoted vectors".to_string()),
 
             Value::PersistentVector(_) => {
@@ -1879,7 +2493,11 @@ impl JitEngine {
 
             Value::Reduced(_) => Err("Cannot quote reduced values in JIT".to_string()),
 
-            Value::NativeFn(_) => Err("Cannot quote native functions".to_string()),
+            Value::NativeFn(_) | Value::NativeClosure(_) => {
+                Err("Cannot quote native functions".to_string())
+            }
+
+            Value::Ref(_) => Err(JitError::unsupported_type("JIT does not support refs").into()),
         }
     }
 
@@ -2063,6 +2681,14 @@ impl JitEngine {
         engine.add_global_mapping(&codegen.rt_mul, rt_mul as usize);
         engine.add_global_mapping(&codegen.rt_div, rt_div as usize);
         engine.add_global_mapping(&codegen.rt_neg, rt_neg as usize);
+        engine.add_global_mapping(&codegen.rt_mod, rt_mod as usize);
+        engine.add_global_mapping(&codegen.rt_abs, rt_abs as usize);
+        engine.add_global_mapping(&codegen.rt_bit_and, rt_bit_and as usize);
+        engine.add_global_mapping(&codegen.rt_bit_or, rt_bit_or as usize);
+        engine.add_global_mapping(&codegen.rt_bit_xor, rt_bit_xor as usize);
+        engine.add_global_mapping(&codegen.rt_bit_not, rt_bit_not as usize);
+        engine.add_global_mapping(&codegen.rt_bit_shift_left, rt_bit_shift_left as usize);
+        engine.add_global_mapping(&codegen.rt_bit_shift_right, rt_bit_shift_right as usize);
         engine.add_global_mapping(&codegen.rt_num_eq, rt_num_eq as usize);
         engine.add_global_mapping(&codegen.rt_lt, rt_lt as usize);
         engine.add_global_mapping(&codegen.rt_gt, rt_gt as usize);
@@ -2100,10 +2726,18 @@ impl Default for JitEngine {
     }
 }
 
+impl Drop for JitEngine {
+    fn drop(&mut self) {
+        for list in self.quoted.get_mut().drain(..) {
+            crate::runtime::rt_decref(list);
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::jit::{JitError, JitErrorKind};
+    use crate::jit::{EvictionPolicy, JitError, JitErrorKind};
     use consair::parser::parse;
 
     #[test]
@@ -2220,45 +2854,127 @@ mod tests {
     }
 
     #[test]
-    fn test_eval_multiplication() {
+    fn test_eval_multiplication() {
+        let engine = JitEngine::new().unwrap();
+        let expr = parse("(* 6 7)").unwrap();
+        let result = engine.eval(&expr).unwrap();
+        assert_eq!(result.to_int(), Some(42));
+    }
+
+    #[test]
+    fn test_eval_multiplication_multiple() {
+        let engine = JitEngine::new().unwrap();
+        let expr = parse("(* 2 3 4)").unwrap();
+        let result = engine.eval(&expr).unwrap();
+        assert_eq!(result.to_int(), Some(24));
+    }
+
+    #[test]
+    fn test_eval_division() {
+        let engine = JitEngine::new().unwrap();
+        let expr = parse("(/ 20 4)").unwrap();
+        let result = engine.eval(&expr).unwrap();
+        assert_eq!(result.to_int(), Some(5));
+    }
+
+    #[test]
+    fn test_eval_division_float() {
+        let engine = JitEngine::new().unwrap();
+        let expr = parse("(/ 7 2)").unwrap();
+        let result = engine.eval(&expr).unwrap();
+        let val = result.to_float().unwrap();
+        assert!((val - 3.5).abs() < 1e-10);
+    }
+
+    #[test]
+    fn test_eval_mod() {
+        let engine = JitEngine::new().unwrap();
+        let expr = parse("(mod -7 2)").unwrap();
+        let result = engine.eval(&expr).unwrap();
+        assert_eq!(result.to_int(), Some(1));
+    }
+
+    #[test]
+    fn test_eval_abs() {
+        let engine = JitEngine::new().unwrap();
+        let expr = parse("(abs (- 3 10))").unwrap();
+        let result = engine.eval(&expr).unwrap();
+        assert_eq!(result.to_int(), Some(7));
+    }
+
+    #[test]
+    fn test_eval_bitwise() {
+        let engine = JitEngine::new().unwrap();
+        let expr = parse("(bit-xor (bit-and 255 -16 60) (bit-shift-left 1 3))").unwrap();
+        let result = engine.eval(&expr).unwrap();
+        assert_eq!(result.to_int(), Some(56));
+
+        let expr = parse("(bit-shift-right (bit-not 15) 2)").unwrap();
+        let result = engine.eval(&expr).unwrap();
+        assert_eq!(result.to_int(), Some(-4));
+    }
+
+    #[test]
+    fn test_eval_do() {
+        let engine = JitEngine::new().unwrap();
+        let expr = parse("(do 1 2 (+ 1 2))").unwrap();
+        assert_eq!(engine.eval(&expr).unwrap().to_int(), Some(3));
+
+        let expr = parse("(do)").unwrap();
+        assert!(engine.eval(&expr).unwrap().is_nil());
+    }
+
+    #[test]
+    fn test_eval_lambda_with_several_body_forms() {
         let engine = JitEngine::new().unwrap();
-        let expr = parse("(* 6 7)").unwrap();
-        let result = engine.eval(&expr).unwrap();
-        assert_eq!(result.to_int(), Some(42));
+        let expr = parse("((lambda (x) (+ x 100) (* x x)) 7)").unwrap();
+        assert_eq!(engine.eval(&expr).unwrap().to_int(), Some(49));
     }
 
     #[test]
-    fn test_eval_multiplication_multiple() {
+    fn test_eval_nested_arithmetic() {
         let engine = JitEngine::new().unwrap();
-        let expr = parse("(* 2 3 4)").unwrap();
+        let expr = parse("(+ (* 2 3) (- 10 5))").unwrap();
         let result = engine.eval(&expr).unwrap();
-        assert_eq!(result.to_int(), Some(24));
+        // (2 * 3) + (10 - 5) = 6 + 5 = 11
+        assert_eq!(result.to_int(), Some(11));
     }
 
     #[test]
-    fn test_eval_division() {
+    fn test_eval_integer_overflow_fails() {
         let engine = JitEngine::new().unwrap();
-        let expr = parse("(/ 20 4)").unwrap();
-        let result = engine.eval(&expr).unwrap();
-        assert_eq!(result.to_int(), Some(5));
+        for src in [
+            "(* 9223372036854775807 2)",
+            "(- (- 0 9223372036854775807) 2)",
+            "(if (> (+ 9223372036854775807 1) 0) 1 2)",
+        ] {
+            let err = engine.eval(&parse(src).unwrap()).unwrap_err();
+            assert!(err.contains("integer overflow"), "{src}: {err}");
+        }
+        // Nothing was cached, and the engine still evaluates
+        assert_eq!(engine.cache_stats().entries, 0);
+        let result = engine.eval(&parse("(+ 9223372036854775806 1)").unwrap());
+        assert_eq!(result.unwrap().to_int(), Some(i64::MAX));
     }
 
     #[test]
-    fn test_eval_division_float() {
+    fn test_eval_length_of_improper_list_fails() {
         let engine = JitEngine::new().unwrap();
-        let expr = parse("(/ 7 2)").unwrap();
-        let result = engine.eval(&expr).unwrap();
-        let val = result.to_float().unwrap();
-        assert!((val - 3.5).abs() < 1e-10);
+        let err = engine
+            .eval(&parse("(length (cons 1 2))").unwrap())
+            .unwrap_err();
+        assert!(err.contains("expected a proper list"), "{err}");
+        let result = engine.eval(&parse("(length (cons 1 (cons 2 nil)))").unwrap());
+        assert_eq!(result.unwrap().to_int(), Some(2));
     }
 
     #[test]
-    fn test_eval_nested_arithmetic() {
+    fn test_eval_rejects_refs() {
         let engine = JitEngine::new().unwrap();
-        let expr = parse("(+ (* 2 3) (- 10 5))").unwrap();
-        let result = engine.eval(&expr).unwrap();
-        // (2 * 3) + (10 - 5) = 6 + 5 = 11
-        assert_eq!(result.to_int(), Some(11));
+        let r = Value::Ref(Arc::new(consair::RefValue::new(Value::Nil)));
+        let err = engine.eval(&r).unwrap_err();
+        assert!(err.contains("JIT does not support refs"), "{err}");
+        assert!(err.contains("J002_UNSUPPORTED_TYPE"), "{err}");
     }
 
     #[test]
@@ -2413,6 +3129,20 @@ mod tests {
         assert!(result.is_cons());
     }
 
+    #[test]
+    fn test_quoted_list_is_built_once() {
+        let engine = JitEngine::new().unwrap();
+        let compiled = engine.compile(&parse("(quote (1 2 3))").unwrap()).unwrap();
+        let first = compiled.execute();
+        let second = compiled.execute();
+        assert!(first.is_cons());
+        assert_eq!(first.data, second.data);
+        assert_eq!(
+            first.to_value().unwrap().to_string(),
+            second.to_value().unwrap().to_string()
+        );
+    }
+
     #[test]
     fn test_eval_cons() {
         let engine = JitEngine::new().unwrap();
@@ -2551,6 +3281,177 @@ mod tests {
         assert_eq!(result.to_int(), Some(999));
     }
 
+    #[test]
+    fn test_eval_cond_clause_bodies() {
+        let engine = JitEngine::new().unwrap();
+        let eval = |src: &str| engine.eval(&parse(src).unwrap()).unwrap().to_int();
+        // Every body form runs and the last is the value
+        assert_eq!(eval("(cond ((= 1 1) (+ 1 1) (* 3 4)) (t 0))"), Some(12));
+        assert_eq!(eval("(cond ((= 1 2) 1) (else 5 6))"), Some(6));
+        // A clause with only a test returns the test's value
+        assert_eq!(eval("(cond ((= 1 2)) ((+ 1 2)) (t 0))"), Some(3));
+        // The last clause is tested like any other
+        let result = engine.eval(&parse("(cond ((= 1 2) 1))").unwrap()).unwrap();
+        assert!(result.is_nil());
+    }
+
+    #[test]
+    fn test_eval_cond_else_must_be_last() {
+        let engine = JitEngine::new().unwrap();
+        let err = engine
+            .eval(&parse("(cond (else 1) (t 2))").unwrap())
+            .unwrap_err();
+        assert!(err.contains("else must be the last clause"), "{err}");
+    }
+
+    #[test]
+    fn test_eval_cond_body_recursion_is_tail_call() {
+        let engine = JitEngine::new().unwrap();
+        let src = "((label count (lambda (n acc) \
+                     (cond ((= n 0) acc) \
+                           (else (+ acc 1) (count (- n 1) (+ acc 1)))))) \
+                   1000000 0)";
+        let result = engine.eval(&parse(src).unwrap()).unwrap();
+        assert_eq!(result.to_int(), Some(1000000));
+    }
+
+    #[test]
+    fn test_eval_if_without_else() {
+        let engine = JitEngine::new().unwrap();
+        let result = engine.eval(&parse("(if (= 1 1) 5)").unwrap()).unwrap();
+        assert_eq!(result.to_int(), Some(5));
+        let result = engine.eval(&parse("(if (= 1 2) 5)").unwrap()).unwrap();
+        assert!(result.is_nil());
+    }
+
+    // ========================================================================
+    // And / Or Tests
+    // ========================================================================
+
+    #[test]
+    fn test_eval_and_returns_last_value_or_first_false() {
+        let engine = JitEngine::new().unwrap();
+        let result = engine.eval(&parse("(and 1 2 3)").unwrap()).unwrap();
+        assert_eq!(result.to_int(), Some(3));
+        let result = engine.eval(&parse("(and 1 nil 3)").unwrap()).unwrap();
+        assert!(result.is_nil());
+        let result = engine.eval(&parse("(and)").unwrap()).unwrap();
+        assert_eq!(result.to_bool(), Some(true));
+        let result = engine.eval(&parse("(and 4)").unwrap()).unwrap();
+        assert_eq!(result.to_int(), Some(4));
+    }
+
+    #[test]
+    fn test_eval_or_returns_first_true_value() {
+        let engine = JitEngine::new().unwrap();
+        let result = engine.eval(&parse("(or nil nil 7)").unwrap()).unwrap();
+        assert_eq!(result.to_int(), Some(7));
+        let result = engine.eval(&parse("(or (= 1 2) 8 9)").unwrap()).unwrap();
+        assert_eq!(result.to_int(), Some(8));
+        let result = engine.eval(&parse("(or)").unwrap()).unwrap();
+        assert!(result.is_nil());
+        let result = engine.eval(&parse("(or nil (= 1 2))").unwrap()).unwrap();
+        assert!(!result.is_truthy());
+    }
+
+    #[test]
+    fn test_compile_function_called_with_many_inputs() {
+        let engine = JitEngine::new().unwrap();
+        let square = engine
+            .compile_function(&parse("(lambda (n) (* n n))").unwrap())
+            .unwrap();
+        assert_eq!(square.arity(), 1);
+        for n in 1..=10 {
+            let result = square.call(&[RuntimeValue::from_int(n)]).unwrap();
+            assert_eq!(result.to_int(), Some(n * n));
+        }
+        assert_eq!(square.stats().exec_count, 10);
+    }
+
+    #[test]
+    fn test_compile_function_with_two_arguments() {
+        let engine = JitEngine::new().unwrap();
+        let source = "(lambda (xs x) (cons x (reverse xs)))";
+        let push = engine.compile_function(&parse(source).unwrap()).unwrap();
+        let list = RuntimeValue::from_value(&parse("(1 2)").unwrap()).unwrap();
+        let result = push.call(&[list, RuntimeValue::from_int(3)]).unwrap();
+        assert_eq!(result.to_value().unwrap().to_string(), "(3 2 1)");
+
+        let err = push.call(&[list]).unwrap_err();
+        assert_eq!(err, "lambda expects 2 arguments, got 1");
+    }
+
+    #[test]
+    fn test_compile_function_requires_a_lambda() {
+        let engine = JitEngine::new().unwrap();
+        let err = engine
+            .compile_function(&parse("(+ 1 2)").unwrap())
+            .err()
+            .unwrap();
+        assert!(err.contains("expects a lambda"), "{err}");
+        assert!(
+            engine
+                .compile_function(&parse("(lambda (&rest xs) xs)").unwrap())
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_eval_results_convert_back_to_values() {
+        let engine = JitEngine::new().unwrap();
+        for (source, expected) in [
+            ("(cons 1 (cons 2 nil))", "(1 2)"),
+            ("(vector 1 2 3)", "<<1 2 3>>"),
+            ("(cons (vector 1) (cons 2.5 3))", "(<<1>> 2.5 . 3)"),
+            ("(lambda (x) x)", "<compiled-fn>"),
+        ] {
+            let result = engine.eval(&parse(source).unwrap()).unwrap();
+            assert_eq!(result.to_value().unwrap().to_string(), expected, "{source}");
+        }
+    }
+
+    #[test]
+    fn test_eval_reclaims_garbage_between_evals() {
+        use crate::runtime::live_allocations;
+
+        let engine = JitEngine::new().unwrap();
+        let source = "(label build (lambda (n acc) (cond ((= n 0) (length acc)) (t (build (- n 1) (cons n acc))))))";
+        engine.eval(&parse(source).unwrap()).unwrap();
+        let expr = parse("(build 1000000 nil)").unwrap();
+        let before = live_allocations();
+        for _ in 0..3 {
+            let result = engine.eval(&expr).unwrap();
+            assert_eq!(result.to_int(), Some(1_000_000));
+            assert_eq!(live_allocations(), before);
+        }
+
+        // A list that is returned survives
+        let result = engine
+            .eval(&parse("(cons 1 (cons 2 nil))").unwrap())
+            .unwrap();
+        assert_eq!(live_allocations(), before + 2);
+        assert_eq!(result.to_value().unwrap().to_string(), "(1 2)");
+    }
+
+    #[test]
+    fn test_and_or_short_circuit() {
+        let engine = JitEngine::new().unwrap();
+        // Operands after the one that decides are never evaluated, so the
+        // division by zero does not raise
+        let result = engine.eval(&parse("(and nil (/ 1 0))").unwrap()).unwrap();
+        assert!(result.is_nil());
+        let result = engine.eval(&parse("(or 2 (/ 1 0))").unwrap()).unwrap();
+        assert_eq!(result.to_int(), Some(2));
+        let result = engine.eval(&parse("(and 1 (/ 1.0 0))").unwrap()).unwrap();
+        assert_eq!(result.to_float(), Some(f64::INFINITY));
+
+        // In a recursive function the last operand is a tail call
+        let source = "(label count-down (lambda (n) (or (= n 0) (count-down (- n 1)))))";
+        engine.eval(&parse(source).unwrap()).unwrap();
+        let result = engine.eval(&parse("(count-down 100000)").unwrap()).unwrap();
+        assert_eq!(result.to_bool(), Some(true));
+    }
+
     // ========================================================================
     // Lambda Expression Tests
     // ========================================================================
@@ -2860,6 +3761,40 @@ mod tests {
         assert_eq!(result.to_int(), Some(3628800));
     }
 
+    #[test]
+    fn test_eval_let() {
+        let engine = JitEngine::new().unwrap();
+        let eval_int = |src: &str| engine.eval(&parse(src).unwrap()).unwrap().to_int();
+        assert_eq!(eval_int("(let ((x 1) (y 2)) (+ x y))"), Some(3));
+        // Later bindings see earlier ones
+        assert_eq!(eval_int("(let ((x 2) (y (* x 10))) (+ x y))"), Some(22));
+        assert_eq!(eval_int("(let () 7)"), Some(7));
+        // The body runs like a do
+        assert_eq!(eval_int("(let ((x 1)) (+ x 1) (* x 10))"), Some(10));
+        assert!(
+            engine
+                .eval(&parse("(let ((x 1)))").unwrap())
+                .unwrap()
+                .is_nil()
+        );
+        // Shadowing an outer binding
+        assert_eq!(eval_int("((lambda (x) (let ((x (+ x 1))) x)) 5)"), Some(6));
+    }
+
+    #[test]
+    fn test_eval_let_preserves_tail_calls() {
+        let engine = JitEngine::new().unwrap();
+        let result = engine
+            .eval(
+                &parse(
+                    "((label count (lambda (n acc) (let ((m (- n 1))) (cond ((= n 0) acc) (t (count m (+ acc 1))))))) 100000 0)",
+                )
+                .unwrap(),
+            )
+            .unwrap();
+        assert_eq!(result.to_int(), Some(100000));
+    }
+
     // ========================================================================
     // Standard Library Function Tests
     // ========================================================================
@@ -3147,6 +4082,29 @@ mod tests {
         assert!(result.is_nil());
     }
 
+    #[test]
+    fn test_eval_with_env_prelude_macros() {
+        let engine = JitEngine::new().unwrap();
+        let mut env = env_with_macros();
+
+        // No defmacro needed: register_stdlib loads the prelude
+        let cases = [
+            ("(when (< 1 2) 1 2 3)", Some(3)),
+            ("(when (> 1 2) 1)", None),
+            ("(unless (> 1 2) 4)", Some(4)),
+            ("(and 1 2 3)", Some(3)),
+            ("(and 1 nil 3)", None),
+            ("(or nil 7 8)", Some(7)),
+            ("(or nil nil)", None),
+        ];
+        for (source, expected) in cases {
+            let result = engine
+                .eval_with_env(&parse(source).unwrap(), &mut env)
+                .unwrap();
+            assert_eq!(result.to_int(), expected, "{source}");
+        }
+    }
+
     #[test]
     fn test_eval_with_env_arithmetic_macro() {
         let engine = JitEngine::new().unwrap();
@@ -3247,6 +4205,7 @@ mod tests {
         let stats = engine.cache_stats();
         assert_eq!(stats.misses, 3); // Initial evaluations
         assert_eq!(stats.hits, 3); // Re-evaluations
+        assert_eq!(stats.entries, 3);
     }
 
     #[test]
@@ -3274,7 +4233,7 @@ mod tests {
     fn test_cache_disabled() {
         let config = CacheConfig {
             enabled: false,
-            max_entries: 1000,
+            ..CacheConfig::default()
         };
         let engine = JitEngine::with_config(config).unwrap();
 
@@ -3332,6 +4291,7 @@ mod tests {
         let config = CacheConfig {
             enabled: true,
             max_entries: 2, // Very small cache
+            eviction: EvictionPolicy::None,
         };
         let engine = JitEngine::with_config(config).unwrap();
 
@@ -3339,7 +4299,7 @@ mod tests {
         engine.eval(&parse("(+ 1 1)").unwrap()).unwrap();
         engine.eval(&parse("(+ 2 2)").unwrap()).unwrap();
 
-        // This should not be cached (at capacity)
+        // This should not be cached (at capacity, no eviction)
         engine.eval(&parse("(+ 3 3)").unwrap()).unwrap();
 
         // Re-evaluate first two - should hit
@@ -3352,6 +4312,96 @@ mod tests {
         let stats = engine.cache_stats();
         assert_eq!(stats.hits, 2);
         assert_eq!(stats.misses, 4); // 3 initial + 1 re-eval of (+ 3 3)
+        assert_eq!(stats.evictions, 0);
+    }
+
+    #[test]
+    fn test_cache_hash_collision_is_a_miss() {
+        let engine = JitEngine::new().unwrap();
+        let expr = parse("(* 2 5)").unwrap();
+        // Plant another expression's result under this expression's hash
+        let planted = RuntimeValue::from_int(3);
+        engine.cache.lock().unwrap().insert(
+            hash_form(&canonical_form(&expr)),
+            "(+ 1 2)".to_string(),
+            (planted.tag, planted.data),
+            &engine.cache_config,
+        );
+
+        assert_eq!(engine.eval(&expr).unwrap().to_int(), Some(10));
+        let stats = engine.cache_stats();
+        assert_eq!((stats.hits, stats.misses, stats.entries), (0, 1, 1));
+        // The colliding entry was replaced
+        assert_eq!(engine.eval(&expr).unwrap().to_int(), Some(10));
+        assert_eq!(engine.cache_stats().hits, 1);
+    }
+
+    #[test]
+    fn test_cache_ignores_print_limits() {
+        use consair::language::{PrintLimits, set_print_limits};
+
+        let engine = JitEngine::new().unwrap();
+        // Both print as (+ 1 …) under these limits
+        let previous = set_print_limits(PrintLimits {
+            length: Some(2),
+            depth: None,
+            total: None,
+        });
+        let first = engine.eval(&parse("(+ 1 1 1)").unwrap()).unwrap();
+        let second = engine.eval(&parse("(+ 1 1 2)").unwrap()).unwrap();
+        set_print_limits(previous);
+        assert_eq!(first.to_int(), Some(3));
+        assert_eq!(second.to_int(), Some(4));
+    }
+
+    #[test]
+    fn test_cache_evicts_least_recently_used() {
+        let config = CacheConfig {
+            max_entries: 3,
+            ..CacheConfig::default()
+        };
+        let engine = JitEngine::with_config(config).unwrap();
+        let exprs: Vec<Value> = (1..=4)
+            .map(|n| parse(&format!("(+ {n} {n})")).unwrap())
+            .collect();
+
+        // The fourth result evicts the first
+        for expr in &exprs {
+            engine.eval(expr).unwrap();
+        }
+        let stats = engine.cache_stats();
+        assert_eq!((stats.misses, stats.evictions, stats.entries), (4, 1, 3));
+
+        // The newest three all hit
+        for expr in &exprs[1..] {
+            engine.eval(expr).unwrap();
+        }
+        assert_eq!(engine.cache_stats().hits, 3);
+
+        // The oldest misses, and evicts (+ 2 2), the least recently used
+        assert_eq!(engine.eval(&exprs[0]).unwrap().to_int(), Some(2));
+        let stats = engine.cache_stats();
+        assert_eq!((stats.misses, stats.evictions), (5, 2));
+        engine.eval(&exprs[2]).unwrap();
+        assert_eq!(engine.cache_stats().hits, 4);
+    }
+
+    #[test]
+    fn test_cache_hit_refreshes_recency() {
+        let config = CacheConfig {
+            max_entries: 2,
+            ..CacheConfig::default()
+        };
+        let engine = JitEngine::with_config(config).unwrap();
+        let first = parse("(+ 1 1)").unwrap();
+        engine.eval(&first).unwrap();
+        engine.eval(&parse("(+ 2 2)").unwrap()).unwrap();
+        // Using the first result makes (+ 2 2) the one to evict
+        engine.eval(&first).unwrap();
+        engine.eval(&parse("(+ 3 3)").unwrap()).unwrap();
+        engine.eval(&first).unwrap();
+        let stats = engine.cache_stats();
+        assert_eq!((stats.hits, stats.evictions), (2, 1));
     }
 
     // Error handling tests
@@ -3381,7 +4431,10 @@ mod tests {
     #[test]
     fn test_jit_error_display() {
         let err = JitError::unsupported("JIT does not support BigInt");
-        assert_eq!(err.to_string(), "JIT does not support BigInt");
+        assert_eq!(
+            err.to_string(),
+            "JIT does not support BigInt [J001_UNSUPPORTED]"
+        );
 
         let expr = parse("(+ 1 2)").unwrap();
         let err_with_expr = JitError::unsupported("test error").with_expression(&expr);
@@ -3434,4 +4487,257 @@ mod tests {
         assert!(display.contains("..."));
         assert!(display.len() < 200); // Should be truncated
     }
+
+    #[test]
+    fn test_compiled_expr_stats() {
+        let engine = JitEngine::new().unwrap();
+        let compiled = engine.compile(&parse("(+ 1 2)").unwrap()).unwrap();
+        assert_eq!(compiled.stats().exec_count, 0);
+        for _ in 0..3 {
+            assert_eq!(compiled.execute().to_int(), Some(3));
+        }
+        assert_eq!(compiled.stats().exec_count, 3);
+    }
+
+    #[test]
+    fn test_compile_shared_requires_pure_expression() {
+        let engine = JitEngine::new().unwrap();
+        assert!(!engine.compile(&parse("(now)").unwrap()).unwrap().is_pure());
+        assert!(engine.compile_shared(&parse("(now)").unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_shared_compiled_expr_from_threads() {
+        let engine = JitEngine::new().unwrap();
+        let expr = parse("(+ (* 6 7) (car (cons 100 '(2 3))))").unwrap();
+        let shared = engine.compile_shared(&expr).unwrap();
+
+        let results: Vec<Vec<Option<i64>>> = std::thread::scope(|scope| {
+            let handles: Vec<_> = (0..4)
+                .map(|_| scope.spawn(|| (0..1000).map(|_| shared.execute().to_int()).collect()))
+                .collect();
+            handles.into_iter().map(|h| h.join().unwrap()).collect()
+        });
+
+        for thread_results in &results {
+            assert!(thread_results.iter().all(|r| *r == Some(142)));
+        }
+        assert_eq!(shared.stats().exec_count, 4000);
+    }
+
+    #[test]
+    fn test_shared_compiled_expr_is_send_and_outlives_engine() {
+        fn assert_send_sync<T: Send + Sync>(_: &T) {}
+
+        let shared = {
+            let engine = JitEngine::new().unwrap();
+            engine
+                .compile_shared(&parse("(car (cdr '(1 2 3)))").unwrap())
+                .unwrap()
+        };
+        assert_send_sync(&shared);
+        let shared = Arc::new(shared);
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let shared = Arc::clone(&shared);
+                std::thread::spawn(move || shared.execute().to_int())
+            })
+            .collect();
+        for handle in handles {
+            assert_eq!(handle.join().unwrap(), Some(2));
+        }
+        // The last reference may be dropped on any thread
+        std::thread::spawn(move || drop(shared)).join().unwrap();
+    }
+
+    #[test]
+    fn test_define_constant_inlined() {
+        let engine = JitEngine::new().unwrap();
+        let defined = engine
+            .eval(&parse("(define-constant pi 3.5)").unwrap())
+            .unwrap();
+        assert_eq!(defined.to_float(), Some(3.5));
+
+        let result = engine.eval(&parse("(* pi 2)").unwrap()).unwrap();
+        assert_eq!(result.to_float(), Some(7.0));
+
+        // Closures inline constants instead of capturing them
+        let result = engine
+            .eval(&parse("((lambda (r) (* pi r)) 2)").unwrap())
+            .unwrap();
+        assert_eq!(result.to_float(), Some(7.0));
+    }
+
+    #[test]
+    fn test_label_persists_across_evals() {
+        let engine = JitEngine::new().unwrap();
+        let eval = |source: &str| engine.eval(&parse(source).unwrap());
+
+        assert!(
+            eval("(label square (lambda (x) (* x x)))")
+                .unwrap()
+                .is_nil()
+        );
+        assert_eq!(eval("(square 12)").unwrap().to_int(), Some(144));
+
+        eval("(label fact (lambda (n) (cond ((= n 0) 1) (t (* n (fact (- n 1)))))))").unwrap();
+        eval("(label fact-sq (lambda (n) (square (fact n))))").unwrap();
+        assert_eq!(eval("(fact-sq 4)").unwrap().to_int(), Some(576));
+
+        // Redefinition takes effect on the next eval, including in callers
+        eval("(label square (lambda (x) (+ x x)))").unwrap();
+        assert_eq!(eval("(square 12)").unwrap().to_int(), Some(24));
+        assert_eq!(eval("(fact-sq 4)").unwrap().to_int(), Some(48));
+
+        assert!(
+            eval("(square 1 2)")
+                .unwrap_err()
+                .contains("expects 1 arguments")
+        );
+        // Parameters shadow top-level functions
+        assert!(eval("((lambda (square) (square 2)) 3)").is_err());
+    }
+
+    #[test]
+    fn test_label_of_unsupported_lambda_is_not_recorded() {
+        let engine = JitEngine::new().unwrap();
+        let square = InternedSymbol::new("square");
+        engine
+            .eval(&parse("(label square (lambda (x) (* x x)))").unwrap())
+            .unwrap();
+
+        for source in [
+            "(label square (lambda (x) (undefined-fn x)))",
+            "(label square (lambda (x &opt y) x))",
+            "(label square 4)",
+        ] {
+            assert!(engine.eval(&parse(source).unwrap()).is_err(), "{source}");
+        }
+        let result = engine.eval(&parse("(square 3)").unwrap()).unwrap();
+        assert_eq!(result.to_int(), Some(9));
+        assert!(engine.has_function(square));
+    }
+
+    const EVEN: &str = "(label even? (lambda (n) (cond ((= n 0) t) (t (odd? (- n 1))))))";
+    const ODD: &str = "(label odd? (lambda (n) (cond ((= n 0) nil) (t (even? (- n 1))))))";
+
+    #[test]
+    fn test_mutually_recursive_labels() {
+        let engine = JitEngine::new().unwrap();
+        let source = format!("(do {EVEN} {ODD} (even? 100))");
+        let result = engine.eval(&parse(&source).unwrap()).unwrap();
+        assert_eq!(result.to_bool(), Some(true));
+
+        // Both stay defined for later expressions
+        let result = engine.eval(&parse("(odd? 7)").unwrap()).unwrap();
+        assert_eq!(result.to_bool(), Some(true));
+        let result = engine.eval(&parse("(even? 7)").unwrap()).unwrap();
+        assert!(result.is_nil());
+    }
+
+    #[test]
+    fn test_mutually_recursive_functions_from_interpreter() {
+        let engine = JitEngine::new().unwrap();
+        let mut env = env_with_macros();
+        for (name, source) in [("even?", EVEN), ("odd?", ODD)] {
+            let value = eval(parse(source).unwrap(), &mut env).unwrap();
+            engine.define_function(InternedSymbol::new(name), &value);
+        }
+        let result = engine.eval(&parse("(even? 100)").unwrap()).unwrap();
+        assert_eq!(result.to_bool(), Some(true));
+    }
+
+    #[test]
+    fn test_failed_label_group_is_forgotten() {
+        let engine = JitEngine::new().unwrap();
+        let source = "(do (label f (lambda (x) (g x))) (label g (lambda (x) (undefined-fn x))))";
+        assert!(engine.eval(&parse(source).unwrap()).is_err());
+        assert!(!engine.has_function(InternedSymbol::new("f")));
+        assert!(!engine.has_function(InternedSymbol::new("g")));
+    }
+
+    #[test]
+    fn test_define_function_from_interpreter() {
+        let engine = JitEngine::new().unwrap();
+        let mut env = env_with_macros();
+        let double = InternedSymbol::new("double");
+
+        let value = eval(
+            parse("(label double (lambda (n) (* 2 n)))").unwrap(),
+            &mut env,
+        )
+        .unwrap();
+        engine.define_function(double, &value);
+        let result = engine.eval(&parse("(double 21)").unwrap()).unwrap();
+        assert_eq!(result.to_int(), Some(42));
+
+        // Any other value forgets the function
+        engine.define_function(double, &parse("5").unwrap());
+        assert!(!engine.has_function(double));
+        assert!(engine.eval(&parse("(double 21)").unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_closures_over_locals_are_not_compiled() {
+        let engine = JitEngine::new().unwrap();
+        let mut env = env_with_macros();
+        let add = InternedSymbol::new("add");
+        let value = eval(
+            parse("(def add (let ((y 100)) (lambda (x) (+ x y))))").unwrap(),
+            &mut env,
+        )
+        .unwrap();
+        engine.define_function(add, &value);
+        assert!(!engine.has_function(add));
+    }
+
+    #[test]
+    fn test_refresh_functions_follows_set() {
+        let engine = JitEngine::new().unwrap();
+        let mut env = env_with_macros();
+        let f = InternedSymbol::new("f");
+        let value = eval(parse("(label f (lambda (x) (+ x 1)))").unwrap(), &mut env).unwrap();
+        engine.define_function(f, &value);
+
+        eval(parse("(set! f (lambda (x) (* x 10)))").unwrap(), &mut env).unwrap();
+        engine.refresh_functions(&env);
+        let result = engine.eval(&parse("(f 2)").unwrap()).unwrap();
+        assert_eq!(result.to_int(), Some(20));
+
+        eval(parse("(set! f 5)").unwrap(), &mut env).unwrap();
+        engine.refresh_functions(&env);
+        assert!(!engine.has_function(f));
+    }
+
+    #[test]
+    fn test_define_constant_redefinition() {
+        let engine = JitEngine::new().unwrap();
+        let pi = InternedSymbol::new("pi");
+        let value = parse("3.5").unwrap();
+        engine.define_constant(pi, value.clone()).unwrap();
+        assert!(engine.define_constant(pi, value).is_ok());
+
+        let err = engine
+            .eval(&parse("(define-constant pi 3)").unwrap())
+            .unwrap_err();
+        assert!(err.contains("pi is already defined as 3.5"));
+        assert!(engine.eval(&parse("(label pi 1)").unwrap()).is_err());
+        assert!(
+            engine
+                .eval(&parse("(define-constant tau (* 2 pi))").unwrap())
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_loops_are_left_to_interpreter() {
+        let engine = JitEngine::new().unwrap();
+        for source in ["(dotimes (i 3) i)", "(while nil 1)", "(set! x 1)"] {
+            let err = engine.eval(&parse(source).unwrap()).unwrap_err();
+            assert!(
+                err.contains("does not yet support operator"),
+                "{source}: {err}"
+            );
+        }
+    }
 }
@@ -2,6 +2,7 @@
 
 use std::fmt;
 
+use consair::error::codes;
 use consair::language::Value;
 
 /// Categories of JIT compilation errors.
@@ -57,6 +58,18 @@ impl JitError {
         self
     }
 
+    /// The stable code for this kind of error.
+    pub fn code(&self) -> &'static str {
+        match self.kind {
+            JitErrorKind::UnsupportedExpression => codes::JIT_UNSUPPORTED,
+            JitErrorKind::UnsupportedType => codes::JIT_UNSUPPORTED_TYPE,
+            JitErrorKind::InvalidSyntax => codes::JIT_SYNTAX,
+            JitErrorKind::UnboundVariable => codes::JIT_UNBOUND,
+            JitErrorKind::CompilationError => codes::JIT_COMPILATION,
+            JitErrorKind::ExecutionError => codes::JIT_EXECUTION,
+        }
+    }
+
     /// Create an unsupported expression error.
     pub fn unsupported(what: impl Into<String>) -> Self {
         Self::new(JitErrorKind::UnsupportedExpression, what)
@@ -106,7 +119,7 @@ impl fmt::Display for JitError {
         if let Some(ref suggestion) = self.suggestion {
             write!(f, " ({})", suggestion)?;
         }
-        Ok(())
+        write!(f, " [{}]", self.code())
     }
 }
 
@@ -125,7 +138,7 @@ mod tests {
     #[test]
     fn test_jit_error_display() {
         let err = JitError::unsupported("test feature");
-        assert_eq!(err.to_string(), "test feature");
+        assert_eq!(err.to_string(), "test feature [J001_UNSUPPORTED]");
     }
 
     #[test]
@@ -138,6 +151,7 @@ mod tests {
     fn test_jit_error_kind() {
         let err = JitError::unbound("x");
         assert_eq!(err.kind, JitErrorKind::UnboundVariable);
+        assert_eq!(err.code(), "J004_UNBOUND");
     }
 
     #[test]
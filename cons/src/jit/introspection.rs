@@ -0,0 +1,64 @@
+//! Lisp access to the JIT's result cache.
+//!
+//! `register_jit_introspection` defines `(jit-stats)` and `(jit-clear-cache)`,
+//! which share the engine's cache, so Lisp code can see whether repeated
+//! evaluations are being answered from it.
+
+use std::sync::{Arc, Mutex};
+
+use consair::{Environment, Value};
+
+use super::JitEngine;
+use super::cache::{CacheStats, ResultCache};
+use crate::native::{check_arity_exact, make_int, make_symbol, vec_to_alist};
+
+/// Define `(jit-stats)` and `(jit-clear-cache)` in `env`.
+///
+/// `(jit-stats)` returns the cache statistics as an alist with the keys
+/// `hits`, `misses`, `compilations-avoided`, `entries` and `evictions`, and
+/// `(jit-clear-cache)` empties the result cache. Without an engine, for
+/// example under `--no-jit`, both return nil.
+pub fn register_jit_introspection(env: &mut Environment, engine: Option<&JitEngine>) {
+    let cache = engine.map(JitEngine::shared_cache);
+
+    let stats_cache = cache.clone();
+    env.register_native("jit-stats", move |args, _env| {
+        check_arity_exact("jit-stats", args, 0)?;
+        Ok(with_cache(&stats_cache, |cache| {
+            stats_alist(&cache.stats())
+        }))
+    });
+    env.register_native("jit-clear-cache", move |args, _env| {
+        check_arity_exact("jit-clear-cache", args, 0)?;
+        Ok(with_cache(&cache, |cache| {
+            cache.clear();
+            Value::Nil
+        }))
+    });
+}
+
+fn with_cache(
+    cache: &Option<Arc<Mutex<ResultCache>>>,
+    f: impl FnOnce(&mut ResultCache) -> Value,
+) -> Value {
+    cache
+        .as_ref()
+        .map_or(Value::Nil, |cache| f(&mut cache.lock().unwrap()))
+}
+
+/// The statistics as `((hits . n) (misses . n) ...)`.
+fn stats_alist(stats: &CacheStats) -> Value {
+    let pairs = [
+        ("hits", stats.hits),
+        ("misses", stats.misses),
+        ("compilations-avoided", stats.compilations_avoided),
+        ("entries", stats.entries),
+        ("evictions", stats.evictions),
+    ];
+    vec_to_alist(
+        pairs
+            .into_iter()
+            .map(|(name, count)| (make_symbol(name), make_int(count as i64)))
+            .collect(),
+    )
+}
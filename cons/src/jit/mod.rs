@@ -8,7 +8,8 @@
 //! The JIT engine supports optional expression caching to avoid recompiling
 //! the same expression multiple times. When caching is enabled:
 //! - Expressions are normalized to a canonical string form
-//! - A hash of the normalized expression is used as the cache key
+//! - A keyed hash of the normalized expression is used as the cache key, and
+//!   the normalized form is compared on lookup so a collision is a miss
 //! - Compiled function pointers and execution engines are cached
 //! - Subsequent evaluations of the same expression reuse the cached code
 //!
@@ -27,8 +28,10 @@ mod cache;
 mod compiled;
 mod engine;
 mod error;
+mod introspection;
 
-pub use cache::{CacheConfig, CacheStats};
-pub use compiled::CompiledExpr;
+pub use cache::{CacheConfig, CacheStats, EvictionPolicy};
+pub use compiled::{CompiledExpr, CompiledFn, ExecStats, SharedCompiledExpr};
 pub use engine::JitEngine;
 pub use error::{JitError, JitErrorKind};
+pub use introspection::register_jit_introspection;
@@ -6,21 +6,38 @@
 //! - Standard library functions
 //! - Runtime helpers for compiled code
 
+pub mod cli;
 pub mod codegen;
+pub mod doc;
+pub mod embed;
 pub mod interpreter;
 pub mod jit;
 pub mod native;
+pub mod prelude;
+pub mod repl;
+pub mod runner;
 pub mod runtime;
 pub mod stdlib;
 
 // Re-export JIT types
-pub use jit::{CompiledExpr, JitError, JitErrorKind};
+pub use jit::{
+    CompiledExpr, CompiledFn, ExecStats, JitError, JitErrorKind, SharedCompiledExpr,
+    register_jit_introspection,
+};
 
 // Re-export interpreter types
 pub use interpreter::{Environment, eval, expand_all_macros, expand_macros};
 
 // Re-export stdlib registration
-pub use stdlib::register_stdlib;
+pub use prelude::register_prelude;
+pub use stdlib::{
+    StdlibConfig, StdlibGroup, register_core, register_io, register_process, register_stdlib,
+    register_stdlib_sandboxed, register_stdlib_with, register_time,
+};
+
+// Re-export the embedding API
+pub use embed::{run, run_as, run_with};
+pub use native::FromValue;
 
 // Re-export codegen for cadr to use
 pub use codegen::Codegen;
@@ -3,61 +3,116 @@
 //! This module provides utility functions for implementing native Rust functions
 //! that can be called from Lisp code.
 
+use std::collections::HashMap;
+
+use consair::abstractions;
 use consair::interner::InternedSymbol;
 use consair::language::{AtomType, StringType, SymbolType, Value, cons};
 use consair::numeric::NumericType;
+use consair::{Arity, EvalError};
 
 // ============================================================================
 // Value Extraction Helpers
 // ============================================================================
 
 /// Extract a string from a Value
-pub fn extract_string(value: &Value) -> Result<String, String> {
+pub fn extract_string(value: &Value) -> Result<String, EvalError> {
     match value {
         Value::Atom(AtomType::String(StringType::Basic(s))) => Ok(s.clone()),
-        _ => Err(format!("Expected string, got {value}")),
+        _ => Err(type_error("string", value)),
     }
 }
 
 /// Extract an integer from a Value
-pub fn extract_int(value: &Value) -> Result<i64, String> {
+pub fn extract_int(value: &Value) -> Result<i64, EvalError> {
     match value {
         Value::Atom(AtomType::Number(NumericType::Int(n))) => Ok(*n),
-        _ => Err(format!("Expected integer, got {value}")),
+        _ => Err(type_error("integer", value)),
     }
 }
 
 /// Extract a float from a Value (converting integers if needed)
-pub fn extract_float(value: &Value) -> Result<f64, String> {
+pub fn extract_float(value: &Value) -> Result<f64, EvalError> {
     match value {
         Value::Atom(AtomType::Number(NumericType::Int(n))) => Ok(*n as f64),
         Value::Atom(AtomType::Number(NumericType::Float(f))) => Ok(*f),
-        _ => Err(format!("Expected number, got {value}")),
+        _ => Err(type_error("number", value)),
     }
 }
 
 /// Extract a boolean from a Value
-pub fn extract_bool(value: &Value) -> Result<bool, String> {
+pub fn extract_bool(value: &Value) -> Result<bool, EvalError> {
     match value {
         Value::Atom(AtomType::Bool(b)) => Ok(*b),
         Value::Nil => Ok(false),
-        _ => Err(format!("Expected boolean, got {value}")),
+        _ => Err(type_error("boolean", value)),
     }
 }
 
 /// Extract bytes from a Value
-pub fn extract_bytes(value: &Value) -> Result<Vec<u8>, String> {
+pub fn extract_bytes(value: &Value) -> Result<Vec<u8>, EvalError> {
     match value {
         Value::Atom(AtomType::String(StringType::Basic(s))) => Ok(s.as_bytes().to_vec()),
-        _ => Err(format!("Expected string, got {value}")),
+        _ => Err(type_error("string", value)),
     }
 }
 
 /// Extract a symbol name from a Value
-pub fn extract_symbol(value: &Value) -> Result<String, String> {
+pub fn extract_symbol(value: &Value) -> Result<String, EvalError> {
     match value {
         Value::Atom(AtomType::Symbol(SymbolType::Symbol(s))) => Ok(s.resolve()),
-        _ => Err(format!("Expected symbol, got {value}")),
+        _ => Err(type_error("symbol", value)),
+    }
+}
+
+/// Conversion from a Lisp value to a Rust type, used by `run_as`.
+pub trait FromValue: Sized {
+    fn from_value(value: &Value) -> Result<Self, EvalError>;
+}
+
+impl FromValue for Value {
+    fn from_value(value: &Value) -> Result<Self, EvalError> {
+        Ok(value.clone())
+    }
+}
+
+impl FromValue for i64 {
+    fn from_value(value: &Value) -> Result<Self, EvalError> {
+        extract_int(value)
+    }
+}
+
+impl FromValue for f64 {
+    fn from_value(value: &Value) -> Result<Self, EvalError> {
+        extract_float(value)
+    }
+}
+
+impl FromValue for bool {
+    fn from_value(value: &Value) -> Result<Self, EvalError> {
+        extract_bool(value)
+    }
+}
+
+impl FromValue for String {
+    fn from_value(value: &Value) -> Result<Self, EvalError> {
+        extract_string(value)
+    }
+}
+
+/// The elements of any seqable value, each converted to `T`
+impl<T: FromValue> FromValue for Vec<T> {
+    fn from_value(value: &Value) -> Result<Self, EvalError> {
+        seq_to_vec(value)?.iter().map(T::from_value).collect()
+    }
+}
+
+/// The error for a value that is not of the `expected` type.
+fn type_error(expected: &str, found: &Value) -> EvalError {
+    EvalError::TypeError {
+        callee: None,
+        expected: expected.to_string(),
+        found: found.clone(),
     }
 }
 
@@ -82,6 +137,30 @@ pub fn list_to_vec(list: &Value) -> Result<Vec<Value>, String> {
     Ok(result)
 }
 
+/// Collect the elements of any seqable value (list, vector, map, set or string)
+pub fn seq_to_vec(value: &Value) -> Result<Vec<Value>, String> {
+    match value {
+        Value::Cons(_) => list_to_vec(value),
+        Value::Vector(vec) => Ok(vec.elements.clone()),
+        Value::Nil
+        | Value::PersistentVector(_)
+        | Value::Map(_)
+        | Value::PersistentMap(_)
+        | Value::Set(_)
+        | Value::PersistentSet(_)
+        | Value::Atom(AtomType::String(_)) => {
+            let mut items = Vec::new();
+            let mut current = abstractions::seq(value);
+            while let Some(seq) = current {
+                items.push(seq.first());
+                current = seq.next();
+            }
+            Ok(items)
+        }
+        _ => Err(format!("Expected a collection, got {value}")),
+    }
+}
+
 /// Convert a Vec<Value> to a Lisp list
 pub fn vec_to_list(items: Vec<Value>) -> Value {
     items
@@ -91,13 +170,13 @@ pub fn vec_to_list(items: Vec<Value>) -> Value {
 }
 
 /// Extract a list of strings from a Value
-pub fn extract_string_list(value: &Value) -> Result<Vec<String>, String> {
+pub fn extract_string_list(value: &Value) -> Result<Vec<String>, EvalError> {
     let vec = list_to_vec(value)?;
     vec.iter().map(extract_string).collect()
 }
 
 /// Extract a list of integers from a Value
-pub fn extract_int_list(value: &Value) -> Result<Vec<i64>, String> {
+pub fn extract_int_list(value: &Value) -> Result<Vec<i64>, EvalError> {
     let vec = list_to_vec(value)?;
     vec.iter().map(extract_int).collect()
 }
@@ -144,38 +223,203 @@ pub fn vec_to_alist(pairs: Vec<(Value, Value)>) -> Value {
 // ============================================================================
 
 /// Check that the number of arguments is exactly n
-pub fn check_arity_exact(name: &str, args: &[Value], expected: usize) -> Result<(), String> {
-    if args.len() != expected {
-        return Err(format!(
-            "{name}: expected {expected} argument{}, got {}",
-            if expected == 1 { "" } else { "s" },
-            args.len()
-        ));
-    }
-    Ok(())
+pub fn check_arity_exact(name: &str, args: &[Value], expected: usize) -> Result<(), EvalError> {
+    check_arity(name, args, Arity::Exact(expected), args.len() == expected)
 }
 
 /// Check that the number of arguments is at least n
-pub fn check_arity_min(name: &str, args: &[Value], min: usize) -> Result<(), String> {
-    if args.len() < min {
-        return Err(format!(
-            "{name}: expected at least {min} argument{}, got {}",
-            if min == 1 { "" } else { "s" },
-            args.len()
-        ));
-    }
-    Ok(())
+pub fn check_arity_min(name: &str, args: &[Value], min: usize) -> Result<(), EvalError> {
+    check_arity(name, args, Arity::AtLeast(min), args.len() >= min)
 }
 
 /// Check that the number of arguments is in range [min, max]
-pub fn check_arity_range(name: &str, args: &[Value], min: usize, max: usize) -> Result<(), String> {
-    if args.len() < min || args.len() > max {
-        return Err(format!(
-            "{name}: expected {min}-{max} arguments, got {}",
-            args.len()
-        ));
+pub fn check_arity_range(
+    name: &str,
+    args: &[Value],
+    min: usize,
+    max: usize,
+) -> Result<(), EvalError> {
+    let ok = (min..=max).contains(&args.len());
+    check_arity(name, args, Arity::Range(min, max), ok)
+}
+
+fn check_arity(name: &str, args: &[Value], expected: Arity, ok: bool) -> Result<(), EvalError> {
+    if ok {
+        return Ok(());
+    }
+    Err(EvalError::ArityMismatch {
+        callee: name.to_string(),
+        expected,
+        got: args.len(),
+    })
+}
+
+// ============================================================================
+// Keyword Options
+// ============================================================================
+//
+// Natives that take options accept them after their positional arguments,
+// either as keyword/value pairs or as a single map:
+//
+//   (spit "log.txt" "line" :append t)
+//   (spit "log.txt" "line" (%hash-map :append t))
+//
+// Declare the allowed options and their kinds, then call `parse_opts` on the
+// arguments after the positional ones. Option names are given without the
+// leading colon.
+
+/// The kind of value an option accepts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OptKind {
+    /// Any value, interpreted by truthiness
+    Flag,
+    /// An integer
+    Int,
+    /// A string
+    String,
+    /// A keyword such as `:latin1`
+    Keyword,
+    /// Any value, unchecked
+    Any,
+}
+
+impl OptKind {
+    fn describe(self) -> &'static str {
+        match self {
+            OptKind::Flag => "a flag",
+            OptKind::Int => "an integer",
+            OptKind::String => "a string",
+            OptKind::Keyword => "a keyword",
+            OptKind::Any => "a value",
+        }
+    }
+
+    fn accepts(self, value: &Value) -> bool {
+        match self {
+            OptKind::Flag | OptKind::Any => true,
+            OptKind::Int => matches!(value, Value::Atom(AtomType::Number(NumericType::Int(_)))),
+            OptKind::String => matches!(value, Value::Atom(AtomType::String(_))),
+            OptKind::Keyword => extract_symbol(value).is_ok_and(|s| s.starts_with(':')),
+        }
+    }
+}
+
+/// Options parsed by `parse_opts`, keyed by name without the leading colon.
+#[derive(Debug, Clone, Default)]
+pub struct OptMap {
+    values: HashMap<String, Value>,
+}
+
+impl OptMap {
+    /// The raw value of an option, if it was given.
+    pub fn get(&self, name: &str) -> Option<&Value> {
+        self.values.get(name)
+    }
+
+    /// True if a flag option was given with a truthy value.
+    pub fn flag(&self, name: &str) -> bool {
+        self.get(name).is_some_and(is_truthy)
+    }
+
+    /// The value of an integer option.
+    pub fn int(&self, name: &str) -> Option<i64> {
+        self.get(name).and_then(|v| extract_int(v).ok())
+    }
+
+    /// The value of a string option.
+    pub fn string(&self, name: &str) -> Option<String> {
+        self.get(name).and_then(|v| extract_string(v).ok())
     }
-    Ok(())
+
+    /// The value of a keyword option, including its leading colon.
+    pub fn keyword(&self, name: &str) -> Option<String> {
+        self.get(name).and_then(|v| extract_symbol(v).ok())
+    }
+}
+
+/// Parse keyword options from the arguments after a native's positional ones.
+///
+/// Accepts trailing `:key value` pairs or a single map of keyword keys.
+/// Unknown options are rejected with a suggestion for the closest allowed
+/// name, and values are checked against their declared `OptKind`.
+pub fn parse_opts(
+    fn_name: &str,
+    args: &[Value],
+    allowed: &[(&str, OptKind)],
+) -> Result<OptMap, String> {
+    let pairs: Vec<(Value, Value)> = match args {
+        [Value::Map(map)] => map
+            .entries
+            .iter()
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect(),
+        _ => {
+            if !args.len().is_multiple_of(2) {
+                return Err(format!(
+                    "{fn_name}: options must be keyword/value pairs, got odd number of arguments"
+                ));
+            }
+            args.chunks(2)
+                .map(|pair| (pair[0].clone(), pair[1].clone()))
+                .collect()
+        }
+    };
+
+    let mut opts = OptMap::default();
+    for (key, value) in pairs {
+        let name = match extract_symbol(&key) {
+            Ok(s) if s.starts_with(':') => s[1..].to_string(),
+            _ => return Err(format!("{fn_name}: expected keyword option, got {key}")),
+        };
+
+        let Some(&(_, kind)) = allowed.iter().find(|(allowed, _)| *allowed == name) else {
+            let mut message = format!("{fn_name}: unknown option :{name}");
+            if let Some(suggestion) = closest_option(&name, allowed) {
+                message.push_str(&format!(", did you mean :{suggestion}?"));
+            }
+            return Err(message);
+        };
+
+        if !kind.accepts(&value) {
+            return Err(format!(
+                "{fn_name}: option :{name} expects {}, got {value}",
+                kind.describe()
+            ));
+        }
+
+        opts.values.insert(name, value);
+    }
+
+    Ok(opts)
+}
+
+/// Find the allowed option closest to a misspelled name.
+fn closest_option<'a>(name: &str, allowed: &[(&'a str, OptKind)]) -> Option<&'a str> {
+    allowed
+        .iter()
+        .map(|(candidate, _)| (*candidate, edit_distance(name, candidate)))
+        // Only suggest names that are at least half right
+        .filter(|(candidate, distance)| *distance <= name.len().max(candidate.len()) / 2)
+        .min_by_key(|(_, distance)| *distance)
+        .map(|(candidate, _)| candidate)
+}
+
+/// Levenshtein distance between two strings.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, ca) in a.chars().enumerate() {
+        let mut prev = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = prev + usize::from(ca != *cb);
+            prev = row[j + 1];
+            row[j + 1] = substitution.min(prev + 1).min(row[j] + 1);
+        }
+    }
+
+    row[b.len()]
 }
 
 // ============================================================================
@@ -222,7 +466,7 @@ pub fn make_bytes(bytes: Vec<u8>) -> Value {
 
 /// Check if a value is truthy (everything except nil and false)
 pub fn is_truthy(value: &Value) -> bool {
-    !matches!(value, Value::Nil | Value::Atom(AtomType::Bool(false)))
+    value.is_truthy()
 }
 
 /// Check if a value is falsy (nil or false)
@@ -0,0 +1,32 @@
+; The prelude: macros every environment starts with.
+;
+; Loaded by register_stdlib after the natives are defined. Everything here
+; expands to special forms the interpreter and the JIT both understand, so
+; prelude forms compile once macros are expanded. Any of these can be
+; redefined with defmacro.
+
+;; Evaluate body when test is true; otherwise nil.
+(defmacro when (test &rest body)
+  `(cond (,test (do ,@body)) (t nil)))
+
+;; Evaluate body when test is false; otherwise nil.
+(defmacro unless (test &rest body)
+  `(cond (,test nil) (t (do ,@body))))
+
+;; Evaluate forms left to right, stopping at the first false one.
+;; Returns that value, or the last value if none is false. (and) is t.
+(defmacro and (&rest forms)
+  (cond ((nil? forms) t)
+        ((nil? (cdr forms)) (car forms))
+        (t (let ((value (gensym "and")))
+             `(let ((,value ,(car forms)))
+                (cond (,value (and ,@(cdr forms))) (t ,value)))))))
+
+;; Evaluate forms left to right, stopping at the first true one.
+;; Returns that value, or the last value if none is true. (or) is nil.
+(defmacro or (&rest forms)
+  (cond ((nil? forms) nil)
+        ((nil? (cdr forms)) (car forms))
+        (t (let ((value (gensym "or")))
+             `(let ((,value ,(car forms)))
+                (cond (,value ,value) (t (or ,@(cdr forms)))))))))
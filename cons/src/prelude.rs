@@ -0,0 +1,22 @@
+//! Prelude macros
+//!
+//! `when`, `unless`, `and` and `or` are written in Consair itself, in
+//! `prelude.lisp`, and defined with `defmacro` like any user macro.
+//! `register_stdlib` loads them; `register_stdlib_with` can leave them out
+//! for a bare environment.
+
+use crate::embed::run_with;
+use crate::interpreter::Environment;
+
+/// Source of the prelude, embedded in the crate.
+pub const PRELUDE: &str = include_str!("prelude.lisp");
+
+/// Define the prelude macros in `env`.
+///
+/// The natives must already be registered, since the macros use them while
+/// expanding.
+pub fn register_prelude(env: &mut Environment) {
+    if let Err(e) = run_with(env, PRELUDE) {
+        panic!("prelude failed to load: {e}");
+    }
+}
@@ -0,0 +1,804 @@
+//! REPL session state
+//!
+//! `ReplSession` owns the interpreter environment and the optional JIT engine
+//! used by the interactive REPL. It is kept separate from the line-editing
+//! loop in the `cons` binary so that engine selection and fallback behaviour
+//! can be tested without a terminal.
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use consair::language::{AtomType, PrintLimits, SymbolType, set_print_limits};
+use consair::lexer::Lexer;
+use consair::parser::Parser;
+use consair::{
+    Environment, EvalError, ParseErrorKind, Value, binding_source, parse, parse_all, parse_iter,
+    pretty_print,
+};
+
+use crate::doc::describe;
+use crate::interpreter::{
+    SPECIAL_FORMS, TAIL_POSITIONS, eval, expand_all_macros, format_elapsed, record_expansions,
+};
+use crate::jit::analysis::find_free_variables;
+use crate::jit::{JitEngine, register_jit_introspection};
+use crate::runner::{
+    ErrorStage, EvalReport, FormEngine, FormError, FormEval, SourceSpan, constant_name, enter_file,
+    form_error, function_name, is_definition_expr, read_source, skip_whitespace_and_comments,
+    snippet,
+};
+use crate::runtime::RuntimeValue;
+use crate::stdlib::{PPRINT_WIDTH, StdlibConfig, register_stdlib_with};
+
+/// Constructor used to build the session's JIT engine.
+///
+/// Production code uses `JitEngine::new`; tests inject a failing constructor.
+pub type EngineFactory = fn() -> Result<JitEngine, String>;
+
+/// How the session should treat the JIT engine at startup.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JitMode {
+    /// Construct the engine but start in interpreter mode (the default).
+    Available,
+    /// Construct the engine and start in JIT mode (`--jit`).
+    Enabled,
+    /// Never construct the engine (`--no-jit`).
+    Disabled,
+}
+
+/// Print limits installed by a REPL session, so that evaluating a huge value
+/// does not flood the terminal. `write` and `write-str` still print in full.
+pub const REPL_PRINT_LIMITS: PrintLimits = PrintLimits {
+    length: Some(100),
+    depth: Some(10),
+    total: Some(100_000),
+};
+
+/// Widest value `ReplSession::env_listing` shows before cutting it short.
+pub const ENV_VALUE_WIDTH: usize = 60;
+
+/// The REPL's commands, offered by `complete` at the start of a line.
+pub const REPL_COMMANDS: &[&str] = &[
+    ":help",
+    ":quit",
+    ":env",
+    ":jit",
+    ":jit-status",
+    ":jit-stats",
+    ":jit-clear",
+    ":expand",
+    ":doc",
+    ":pretty",
+    ":time",
+    ":save",
+    ":load-session",
+];
+
+/// Interpreter environment plus optional JIT engine for a REPL.
+pub struct ReplSession {
+    env: Environment,
+    mode: JitMode,
+    engine: Option<JitEngine>,
+    init_error: Option<String>,
+    jit_enabled: bool,
+    /// Init file that defined each name, for provenance
+    init_sources: HashMap<String, PathBuf>,
+    /// Forms last evaluated by `eval_incremental`, by buffer name
+    buffers: HashMap<String, Vec<BufferForm>>,
+    /// Bindings the standard library made, hidden by `:env` unless rebound
+    builtins: HashMap<String, Value>,
+    /// Whether results are printed with `pretty_print`
+    pretty: bool,
+    /// Whether `eval` reports how long each form took, for `:time`
+    timing: bool,
+}
+
+/// A form from the last `eval_incremental` pass over a buffer.
+struct BufferForm {
+    hash: u64,
+    /// Name the form defines, if it is a definition
+    defined: Option<String>,
+    result: FormEval,
+}
+
+impl ReplSession {
+    /// Create a session, constructing the JIT engine with `JitEngine::new`.
+    pub fn new(mode: JitMode) -> Self {
+        Self::with_engine_factory(mode, JitEngine::new)
+    }
+
+    /// Create a session using a custom JIT engine constructor.
+    ///
+    /// The constructor is not called at all when `mode` is `JitMode::Disabled`.
+    /// Creating a session installs `REPL_PRINT_LIMITS` on the current thread.
+    pub fn with_engine_factory(mode: JitMode, factory: EngineFactory) -> Self {
+        Self::with_stdlib(mode, factory, &StdlibConfig::default())
+    }
+
+    /// Create a session whose environment has the parts of the standard
+    /// library that `config` includes, e.g. `StdlibConfig::sandboxed()`.
+    pub fn with_stdlib(mode: JitMode, factory: EngineFactory, config: &StdlibConfig) -> Self {
+        set_print_limits(REPL_PRINT_LIMITS);
+        let mut env = Environment::new();
+        register_stdlib_with(&mut env, config);
+
+        let (engine, init_error) = match mode {
+            JitMode::Disabled => (None, None),
+            JitMode::Available | JitMode::Enabled => match factory() {
+                Ok(engine) => (Some(engine), None),
+                Err(e) => (None, Some(e)),
+            },
+        };
+        register_jit_introspection(&mut env, engine.as_ref());
+        let builtins = env.iter_local().collect();
+
+        let jit_enabled = mode == JitMode::Enabled && engine.is_some();
+
+        ReplSession {
+            env,
+            mode,
+            engine,
+            init_error,
+            jit_enabled,
+            init_sources: HashMap::new(),
+            buffers: HashMap::new(),
+            builtins,
+            pretty: false,
+            timing: false,
+        }
+    }
+
+    /// The session's interpreter environment.
+    pub fn env_mut(&mut self) -> &mut Environment {
+        &mut self.env
+    }
+
+    /// True if a JIT engine was constructed successfully.
+    pub fn jit_available(&self) -> bool {
+        self.engine.is_some()
+    }
+
+    /// True if expressions are currently evaluated with the JIT.
+    pub fn jit_enabled(&self) -> bool {
+        self.jit_enabled
+    }
+
+    /// The error reported by the engine constructor, if it failed.
+    pub fn jit_init_error(&self) -> Option<&str> {
+        self.init_error.as_deref()
+    }
+
+    /// Error to report at startup when the JIT was explicitly requested
+    /// (`--jit`) but could not be initialized.
+    pub fn startup_error(&self) -> Option<String> {
+        match (&self.mode, &self.init_error) {
+            (JitMode::Enabled, Some(e)) => Some(format!(
+                "JIT compilation was requested but the engine failed to initialize: {e}"
+            )),
+            _ => None,
+        }
+    }
+
+    /// Human-readable description of the JIT state, shown by `:jit-status`.
+    pub fn jit_status(&self) -> String {
+        if self.mode == JitMode::Disabled {
+            return "JIT disabled (--no-jit); engine was not constructed".to_string();
+        }
+        if let Some(ref e) = self.init_error {
+            return format!("JIT not available: engine failed to initialize: {e}");
+        }
+        format!(
+            "JIT available (mode: {})",
+            if self.jit_enabled {
+                "enabled"
+            } else {
+                "disabled"
+            }
+        )
+    }
+
+    /// The JIT result cache statistics, shown by `:jit-stats`.
+    pub fn jit_stats(&self) -> String {
+        match &self.engine {
+            Some(engine) => {
+                let stats = engine.cache_stats();
+                format!(
+                    "hits: {}, misses: {}, compilations avoided: {}, entries: {}, evictions: {}",
+                    stats.hits,
+                    stats.misses,
+                    stats.compilations_avoided,
+                    stats.entries,
+                    stats.evictions
+                )
+            }
+            None => self.jit_status(),
+        }
+    }
+
+    /// Empty the JIT result cache, for `:jit-clear`.
+    pub fn jit_clear(&self) -> String {
+        match &self.engine {
+            Some(engine) => {
+                engine.clear_cache();
+                "JIT cache cleared".to_string()
+            }
+            None => self.jit_status(),
+        }
+    }
+
+    /// Toggle JIT mode, returning the new state.
+    ///
+    /// Fails with the reason the engine is unavailable if there is none.
+    pub fn toggle_jit(&mut self) -> Result<bool, String> {
+        if self.engine.is_none() {
+            return Err(self.jit_status());
+        }
+        self.jit_enabled = !self.jit_enabled;
+        Ok(self.jit_enabled)
+    }
+
+    /// Evaluate every form of an init file into the session environment.
+    ///
+    /// Forms are evaluated by the interpreter, whatever the JIT mode. A failing
+    /// form does not stop the rest of the file; the returned messages describe
+    /// each failure. A file that cannot be read or parsed produces a single
+    /// message and leaves the forms before the parse error evaluated.
+    pub fn load_init_file(&mut self, path: &Path) -> Vec<String> {
+        let source = match read_source(&path.to_string_lossy()) {
+            Ok(source) => source,
+            Err(e) => return vec![e],
+        };
+        // Relative loads and requires in the file resolve against its directory
+        enter_file(&mut self.env, path);
+        let mut errors = Vec::new();
+        for (index, expr) in parse_iter(&source).enumerate() {
+            let expr = match expr {
+                Ok(expr) => expr,
+                Err(e) => {
+                    errors.push(format!("{}: {e}", path.display()));
+                    break;
+                }
+            };
+            let index = index + 1;
+            let form = snippet(&expr);
+            let defined = defined_name(&expr);
+            match self.eval_interpreted(expr) {
+                Ok(_) => {
+                    if let Some(name) = defined {
+                        self.init_sources.insert(name, path.to_path_buf());
+                    }
+                }
+                Err(e) => errors.push(format!(
+                    "{}: {}",
+                    path.display(),
+                    form_error(index, &form, &e)
+                )),
+            }
+        }
+        self.env.end_file();
+        errors
+    }
+
+    /// Write the bindings `:env` lists to `path`, as source that
+    /// `load_session` evaluates to make them again.
+    ///
+    /// Macros are written first so that the forms after them can use them.
+    /// A binding with no source form, such as a native function, is left
+    /// out; the returned warnings name each one.
+    pub fn save_session(&self, path: &Path) -> Result<Vec<String>, String> {
+        let mut bindings: Vec<(String, Value)> = self
+            .env
+            .iter_local()
+            .filter(|(name, value)| self.builtins.get(name) != Some(value))
+            .collect();
+        bindings.sort_by_key(|(_, value)| !matches!(value, Value::Macro(_)));
+
+        let mut source = String::from("; Consair REPL session\n");
+        let mut warnings = Vec::new();
+        for (name, value) in bindings {
+            match binding_source(&name, &value, self.env.is_constant(&name)) {
+                Ok(form) => {
+                    source.push_str(&form);
+                    source.push('\n');
+                }
+                Err(e) => warnings.push(format!("not saved: {e}")),
+            }
+        }
+        fs::write(path, source)
+            .map_err(|e| format!("Failed to write '{}': {e}", path.display()))?;
+        Ok(warnings)
+    }
+
+    /// Evaluate a file written by `save_session` into this session.
+    ///
+    /// This loads the file as `load_init_file` does, so a form that fails
+    /// is reported without stopping the rest.
+    pub fn load_session(&mut self, path: &Path) -> Vec<String> {
+        self.load_init_file(path)
+    }
+
+    /// The init file that defined `name`, if it came from one.
+    pub fn init_source(&self, name: &str) -> Option<&Path> {
+        self.init_sources.get(name).map(PathBuf::as_path)
+    }
+
+    /// Every name `complete` offers in this session: the special forms and
+    /// everything bound in the environment, sorted.
+    pub fn completion_names(&self) -> Vec<String> {
+        let mut names = self.env.names();
+        names.extend(
+            SPECIAL_FORMS
+                .iter()
+                .chain(TAIL_POSITIONS.iter().map(|(form, _)| form))
+                .map(|form| form.to_string()),
+        );
+        names.sort();
+        names.dedup();
+        names
+    }
+
+    /// One line per binding for `:env`, as `name = value`, sorted by name.
+    ///
+    /// Standard library bindings are left out unless `all` is set or the
+    /// user has rebound them. Values are cut to one line of at most
+    /// `ENV_VALUE_WIDTH` characters.
+    pub fn env_listing(&self, all: bool) -> Vec<String> {
+        self.env
+            .iter_local()
+            .filter(|(name, value)| all || self.builtins.get(name) != Some(value))
+            .map(|(name, value)| format!("{name} = {}", one_line(&value.to_string())))
+            .collect()
+    }
+
+    /// Whether results are pretty-printed across lines, for `:pretty`.
+    pub fn pretty(&self) -> bool {
+        self.pretty
+    }
+
+    /// Turn pretty-printing of results on or off.
+    pub fn set_pretty(&mut self, on: bool) {
+        self.pretty = on;
+    }
+
+    /// Whether `eval` reports timings, for `:time`.
+    pub fn timing(&self) -> bool {
+        self.timing
+    }
+
+    /// Turn timing of each evaluated form on or off.
+    pub fn set_timing(&mut self, on: bool) {
+        self.timing = on;
+    }
+
+    /// `value` as `eval` shows it: on one line, or across lines `PPRINT_WIDTH`
+    /// wide when pretty-printing is on.
+    fn show(&self, value: &Value) -> String {
+        if self.pretty {
+            pretty_print(value, PPRINT_WIDTH)
+        } else {
+            value.to_string()
+        }
+    }
+
+    /// Evaluate an expression and format the result for display.
+    ///
+    /// In JIT mode, expressions the JIT cannot handle fall back to the
+    /// interpreter with a warning on stderr.
+    ///
+    /// With timing on, the engine that ran the form and how long it took are
+    /// written to stderr. The JIT's compile and execute times are reported
+    /// separately, so timed forms bypass its result cache.
+    pub fn eval(&mut self, expr: Value) -> Result<String, String> {
+        // Definitions are evaluated by the interpreter and shared with the
+        // JIT, even while it is toggled off, so both engines see the same
+        // constants and functions
+        if is_definition_expr(&expr) {
+            let start = Instant::now();
+            let value = self.eval_interpreted(expr)?;
+            if self.timing {
+                eprintln!("; interpreter: {}", format_elapsed(start.elapsed()));
+            }
+            return Ok(self.show(&value));
+        }
+
+        let expr = match self.engine {
+            Some(ref engine) if self.jit_enabled => {
+                // Errors expanding macros are the user's; only code the JIT
+                // cannot compile, or that overflows when run, falls back to
+                // the interpreter
+                let expanded = expand_all_macros(expr, &mut self.env, 0)?;
+                // An earlier form may have changed a function with set!
+                engine.refresh_functions(&self.env);
+                let outcome = if self.timing {
+                    let start = Instant::now();
+                    engine
+                        .compile(&expanded)
+                        .map(|compiled| {
+                            let compile_time = start.elapsed();
+                            let start = Instant::now();
+                            let rv = compiled.execute();
+                            eprintln!(
+                                "; jit: compiled in {}, executed in {}",
+                                format_elapsed(compile_time),
+                                format_elapsed(start.elapsed())
+                            );
+                            rv
+                        })
+                        .and_then(|rv| match rv.to_error() {
+                            Some(error) => Err(format!("{error} in compiled code")),
+                            None => Ok(rv),
+                        })
+                } else {
+                    engine.eval(&expanded)
+                };
+                match outcome {
+                    Ok(rv) => {
+                        return Ok(match rv.to_value() {
+                            Ok(value) => self.show(&value),
+                            Err(e) => format!("<JIT error: {e}>"),
+                        });
+                    }
+                    Err(e) => eprintln!("⚠ JIT fallback: {e}"),
+                }
+                expanded
+            }
+            _ => expr,
+        };
+        let start = Instant::now();
+        let value = eval(expr, &mut self.env)?;
+        if self.timing {
+            eprintln!("; interpreter: {}", format_elapsed(start.elapsed()));
+        }
+        Ok(self.show(&value))
+    }
+
+    /// Evaluate every form in a complete piece of REPL input, in order.
+    ///
+    /// Input that does not parse is not evaluated at all. Otherwise each form
+    /// is evaluated as `eval` would, stopping at the first one that fails.
+    pub fn eval_input(&mut self, input: &str) -> InputResult {
+        let forms = match parse_all(input) {
+            Ok(forms) => forms,
+            Err(e) => {
+                return InputResult {
+                    results: Vec::new(),
+                    forms: 0,
+                    error: Some(format!("Parse error: {e}")),
+                };
+            }
+        };
+        let mut result = InputResult {
+            results: Vec::new(),
+            forms: forms.len(),
+            error: None,
+        };
+        for expr in forms {
+            match self.eval(expr) {
+                Ok(printed) => result.results.push(printed),
+                Err(e) => {
+                    result.error = Some(format!("Error: {e}"));
+                    break;
+                }
+            }
+        }
+        result
+    }
+
+    /// Fully expand the macros in the form in `source`, for `:expand`.
+    ///
+    /// Returns the expansion followed by a comment line giving the number of
+    /// expansion steps it took.
+    pub fn expand(&mut self, source: &str) -> Result<String, String> {
+        let expr = parse(source)?;
+        let (expanded, steps) = record_expansions(|| expand_all_macros(expr, &mut self.env, 0));
+        let plural = if steps.len() == 1 { "" } else { "s" };
+        Ok(format!(
+            "{}\n; {} expansion step{plural}",
+            expanded?,
+            steps.len()
+        ))
+    }
+
+    /// What `name` is bound to, with its signature and docstring, for `:doc`.
+    pub fn describe(&self, name: &str) -> String {
+        describe(name, self.env.lookup(name).as_ref())
+    }
+
+    /// Evaluate every top-level form in `source`, reporting each one.
+    ///
+    /// Forms are evaluated as `eval` would, except that definitions always
+    /// use the interpreter. A failing form does not stop the ones after it;
+    /// a parse error is reported as the last form.
+    pub fn eval_all(&mut self, source: &str) -> EvalReport {
+        let (forms, parse_error) = parse_forms(source);
+        let mut report = EvalReport::default();
+        for (span, expr) in forms {
+            let index = report.forms.len() + 1;
+            report.forms.push(self.eval_form(index, span, expr));
+        }
+        report.forms.extend(parse_error);
+        report
+    }
+
+    /// Evaluate `source` as the new contents of `buffer`, reusing the results
+    /// of forms that have not changed since the buffer was last evaluated.
+    ///
+    /// Forms are compared by structural hash, so whitespace and comments do
+    /// not matter. A form is evaluated again if it is new, if it failed last
+    /// time, or if it refers to a name whose definition in the buffer was
+    /// evaluated again or removed; this repeats through dependents of
+    /// dependents. The first call for a buffer evaluates every form.
+    ///
+    /// Cached forms are not run again, so their side effects do not repeat.
+    /// Definitions removed from the buffer stay bound in the session.
+    pub fn eval_incremental(&mut self, buffer: &str, source: &str) -> EvalReport {
+        let previous = self.buffers.remove(buffer).unwrap_or_default();
+        let (forms, parse_error) = parse_forms(source);
+        let defined: Vec<Option<String>> =
+            forms.iter().map(|(_, expr)| defined_name(expr)).collect();
+
+        // Names whose definition changed in this pass, starting with removed ones
+        let mut changed: HashSet<String> = previous
+            .iter()
+            .filter_map(|form| form.defined.clone())
+            .filter(|name| !defined.contains(&Some(name.clone())))
+            .collect();
+
+        let mut report = EvalReport::default();
+        let mut evaluated = Vec::new();
+        for ((span, expr), defined) in forms.into_iter().zip(defined) {
+            let index = report.forms.len() + 1;
+            let hash = structural_hash(&expr);
+            let depends_on_change = find_free_variables(&expr, &HashSet::new())
+                .iter()
+                .map(|sym| sym.resolve())
+                .any(|name| Some(&name) != defined.as_ref() && changed.contains(&name));
+            let cached = previous
+                .iter()
+                .find(|form| form.hash == hash && form.result.result.is_ok())
+                .filter(|_| !depends_on_change);
+
+            let result = match cached {
+                Some(form) => FormEval {
+                    index,
+                    span,
+                    duration: Duration::ZERO,
+                    cached: true,
+                    ..form.result.clone()
+                },
+                None => {
+                    if let Some(ref name) = defined {
+                        changed.insert(name.clone());
+                    }
+                    self.eval_form(index, span, expr)
+                }
+            };
+            report.forms.push(result.clone());
+            evaluated.push(BufferForm {
+                hash,
+                defined,
+                result,
+            });
+        }
+        report.forms.extend(parse_error);
+        self.buffers.insert(buffer.to_string(), evaluated);
+        report
+    }
+
+    /// Evaluate one form for an `EvalReport`, recording the engine that ran it.
+    fn eval_form(&mut self, index: usize, span: SourceSpan, expr: Value) -> FormEval {
+        let start = Instant::now();
+        let mut fallback_reason = None;
+        let (engine, outcome) = if is_definition_expr(&expr) {
+            (FormEngine::Definition, self.eval_interpreted(expr))
+        } else {
+            match self.engine {
+                Some(ref engine) if self.jit_enabled => {
+                    // An earlier form may have changed a function with set!
+                    engine.refresh_functions(&self.env);
+                    match expand_all_macros(expr, &mut self.env, 0) {
+                        Ok(expanded) => match engine.eval(&expanded) {
+                            Ok(rv) => (FormEngine::Jit, rv.to_value().map_err(EvalError::from)),
+                            Err(reason) => {
+                                fallback_reason = Some(reason);
+                                (FormEngine::Interpreter, eval(expanded, &mut self.env))
+                            }
+                        },
+                        // Macros are expanded by the interpreter
+                        Err(e) => (FormEngine::Interpreter, Err(e)),
+                    }
+                }
+                _ => (FormEngine::Interpreter, eval(expr, &mut self.env)),
+            }
+        };
+        FormEval {
+            index,
+            span,
+            engine: Some(engine),
+            duration: start.elapsed(),
+            fallback_reason,
+            result: match outcome {
+                Ok(ref value) => Ok(format!("{value}")),
+                Err(ref error) => Err(FormError {
+                    stage: ErrorStage::Eval,
+                    message: error.to_string(),
+                }),
+            },
+            value: outcome.ok(),
+            cached: false,
+        }
+    }
+
+    /// Evaluate with the interpreter, sharing any constant or function it
+    /// defines with the JIT.
+    fn eval_interpreted(&mut self, expr: Value) -> Result<Value, EvalError> {
+        let constant = constant_name(&expr);
+        let function = function_name(&expr);
+        let value = eval(expr, &mut self.env)?;
+        if let Some(engine) = &self.engine {
+            if let Some(name) = constant {
+                engine.define_constant(name, value.clone())?;
+            }
+            if let Some(name) = function {
+                engine.define_function(name, &value);
+            }
+        }
+        Ok(value)
+    }
+}
+
+/// What `ReplSession::eval_input` did with a piece of input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InputResult {
+    /// The printed result of each form that was evaluated successfully
+    pub results: Vec<String>,
+    /// Number of forms in the input; zero if it did not parse
+    pub forms: usize,
+    /// The parse error, or the error from the form that failed
+    pub error: Option<String>,
+}
+
+/// Completions for the word that ends at byte `pos` of `line`, with the
+/// offset where that word starts.
+///
+/// Words are delimited by whitespace, brackets and quote characters. A word
+/// starting with `:` at the start of the line completes to the REPL's
+/// commands; any other word completes to those of `names` it begins.
+pub fn complete(line: &str, pos: usize, names: &[String]) -> (usize, Vec<String>) {
+    let before = &line[..pos];
+    let start = before
+        .rfind(|c: char| c.is_whitespace() || "()[]{}'`,\"".contains(c))
+        .map_or(0, |i| i + 1);
+    let word = &before[start..];
+    let candidates = if word.starts_with(':') {
+        if before[..start].trim().is_empty() {
+            REPL_COMMANDS
+                .iter()
+                .filter(|command| command.starts_with(word))
+                .map(|command| command.to_string())
+                .collect()
+        } else {
+            Vec::new()
+        }
+    } else {
+        names
+            .iter()
+            .filter(|name| name.starts_with(word))
+            .cloned()
+            .collect()
+    };
+    (start, candidates)
+}
+
+/// Check if REPL input is complete, rather than still missing a closing
+/// delimiter or the form after a quote or `#_`.
+///
+/// Input with any other syntax error, such as an extra `)`, counts as
+/// complete: more lines cannot fix it, so it is reported straight away.
+pub fn is_complete_expression(input: &str) -> bool {
+    match parse_all(input) {
+        Ok(_) => true,
+        Err(e) => !matches!(
+            e.kind,
+            ParseErrorKind::UnclosedDelimiter | ParseErrorKind::UnexpectedEof
+        ),
+    }
+}
+
+/// The init file to load at REPL startup: `$CONSAIR_INIT` if it is set,
+/// otherwise `~/.consairrc`, and only if that file exists.
+///
+/// Takes the variable's value and the home directory so callers control
+/// where they come from.
+pub fn default_init_file(consair_init: Option<PathBuf>, home: Option<PathBuf>) -> Option<PathBuf> {
+    consair_init
+        .or_else(|| home.map(|h| h.join(".consairrc")))
+        .filter(|path| path.is_file())
+}
+
+/// Split `source` into top-level forms with their spans.
+///
+/// Parsing stops at the first error, which is returned as the report entry
+/// for the text from the failing form to the end of the source.
+fn parse_forms(source: &str) -> (Vec<(SourceSpan, Value)>, Option<FormEval>) {
+    let mut forms = Vec::new();
+    let mut lexer = Lexer::new(source);
+    let mut parser = Parser::new(&mut lexer);
+    let mut end = 0;
+    while !parser.is_at_end() {
+        match parser.parse_spanned_expression() {
+            Ok(form) => {
+                end = form.end.offset;
+                let span = SourceSpan::new(source, form.span.offset, end);
+                forms.push((span, form.value));
+            }
+            Err(e) => {
+                let rest = skip_whitespace_and_comments(&source[end..]);
+                let start = source.len() - rest.len();
+                let span = SourceSpan::new(source, start, source.len());
+                let failure = parse_failure(forms.len() + 1, span, e.to_string());
+                return (forms, Some(failure));
+            }
+        }
+    }
+    (forms, None)
+}
+
+/// Report entry for text that could not be parsed.
+fn parse_failure(index: usize, span: SourceSpan, message: String) -> FormEval {
+    FormEval {
+        index,
+        span,
+        engine: None,
+        duration: Duration::ZERO,
+        fallback_reason: None,
+        result: Err(FormError {
+            stage: ErrorStage::Parse,
+            message,
+        }),
+        value: None,
+        cached: false,
+    }
+}
+
+/// Hash of a form's structure, independent of layout and comments.
+fn structural_hash(expr: &Value) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    expr.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// The name bound by a top-level definition form.
+fn defined_name(expr: &Value) -> Option<String> {
+    if is_definition_expr(expr)
+        && let Value::Cons(cell) = expr
+        && let Value::Cons(args) = &cell.cdr
+        && let Value::Atom(AtomType::Symbol(SymbolType::Symbol(name))) = &args.car
+    {
+        return Some(name.resolve());
+    }
+    None
+}
+
+/// The first line of `text`, cut to `ENV_VALUE_WIDTH` characters, with `...`
+/// marking anything left out.
+fn one_line(text: &str) -> String {
+    let line = text.lines().next().unwrap_or("");
+    let mut shown: String = line.chars().take(ENV_VALUE_WIDTH).collect();
+    if shown.len() < text.len() {
+        shown.push_str("...");
+    }
+    shown
+}
+
+/// Convert RuntimeValue to string for display
+pub fn runtime_value_to_string(val: RuntimeValue) -> String {
+    // Convert RuntimeValue back to Value for display
+    match val.to_value() {
+        Ok(v) => format!("{v}"),
+        Err(e) => format!("<JIT error: {e}>"),
+    }
+}
@@ -0,0 +1,636 @@
+//! File runner
+//!
+//! Splits source files into top-level forms and evaluates them one at a time.
+//! In JIT mode each form is tried with the JIT first and falls back to the
+//! interpreter; `run_file_jit_report` records what happened to every form so
+//! the CLI's `--verbose` mode and benchmarks can share the same data.
+//!
+//! A file that defines a `main` function is a script: once every top-level
+//! form has been evaluated, `main` is called with the command-line arguments
+//! as a list of strings. See `call_main`.
+
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use consair::interner::InternedSymbol;
+use consair::language::{AtomType, SymbolType, Value};
+use consair::{Environment, EvalError, parse_iter};
+
+use crate::interpreter::{apply, eval, expand_all_macros};
+use crate::jit::{JitEngine, register_jit_introspection};
+use crate::native::{make_string, vec_to_list};
+use crate::repl::runtime_value_to_string;
+use crate::stdlib::{StdlibConfig, register_stdlib_with};
+
+/// Maximum number of characters of a form shown in a report line.
+const SNIPPET_LEN: usize = 50;
+
+// ============================================================================
+// Run Reports
+// ============================================================================
+
+/// Which engine evaluated a top-level form.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FormEngine {
+    /// Compiled and executed by the JIT
+    Jit,
+    /// Evaluated by the interpreter, after the JIT declined it in JIT mode
+    Interpreter,
+    /// A definition (label, defmacro), always evaluated by the interpreter
+    Definition,
+}
+
+impl fmt::Display for FormEngine {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FormEngine::Jit => write!(f, "jit"),
+            FormEngine::Interpreter => write!(f, "interp"),
+            FormEngine::Definition => write!(f, "definition"),
+        }
+    }
+}
+
+/// Telemetry for a single top-level form.
+#[derive(Debug, Clone)]
+pub struct FormReport {
+    /// 1-based position of the form in the file
+    pub index: usize,
+    /// The first characters of the form
+    pub snippet: String,
+    /// Engine that produced the result
+    pub engine: FormEngine,
+    /// Time spent expanding macros and compiling (including failed attempts)
+    pub compile_time: Duration,
+    /// Time spent executing the compiled code or interpreting
+    pub execute_time: Duration,
+    /// Why the JIT declined the form, if it fell back
+    pub fallback_reason: Option<String>,
+    /// The form's result, formatted for display
+    pub result: String,
+}
+
+impl fmt::Display for FormReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "[{:>3}] {:<width$} {:<10} compile {:>9.3}ms  exec {:>9.3}ms",
+            self.index,
+            self.snippet,
+            self.engine.to_string(),
+            millis(self.compile_time),
+            millis(self.execute_time),
+            width = SNIPPET_LEN
+        )?;
+        if let Some(ref reason) = self.fallback_reason {
+            write!(f, "  fallback: {reason}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Telemetry for a whole file run in JIT mode.
+#[derive(Debug, Clone, Default)]
+pub struct RunReport {
+    /// One entry per top-level form, in file order
+    pub forms: Vec<FormReport>,
+    /// What `main` returned, formatted for display, if the file defined it
+    /// and it was called
+    pub main_result: Option<String>,
+}
+
+impl RunReport {
+    /// Number of forms executed by the JIT.
+    pub fn compiled_count(&self) -> usize {
+        self.count(FormEngine::Jit)
+    }
+
+    /// Number of forms that fell back to the interpreter.
+    pub fn fallback_count(&self) -> usize {
+        self.count(FormEngine::Interpreter)
+    }
+
+    /// Number of definitions.
+    pub fn definition_count(&self) -> usize {
+        self.count(FormEngine::Definition)
+    }
+
+    /// Total compile plus execute time for forms handled by `engine`.
+    pub fn total_time(&self, engine: FormEngine) -> Duration {
+        self.forms
+            .iter()
+            .filter(|form| form.engine == engine)
+            .map(|form| form.compile_time + form.execute_time)
+            .sum()
+    }
+
+    /// The result of `main` if it was called, otherwise of the last form.
+    pub fn last_result(&self) -> Option<&str> {
+        self.main_result
+            .as_deref()
+            .or_else(|| self.forms.last().map(|form| form.result.as_str()))
+    }
+
+    /// One-line summary of the run.
+    pub fn summary(&self) -> String {
+        format!(
+            "{} forms: {} compiled ({:.3}ms), {} fell back ({:.3}ms), {} definitions ({:.3}ms)",
+            self.forms.len(),
+            self.compiled_count(),
+            millis(self.total_time(FormEngine::Jit)),
+            self.fallback_count(),
+            millis(self.total_time(FormEngine::Interpreter)),
+            self.definition_count(),
+            millis(self.total_time(FormEngine::Definition)),
+        )
+    }
+
+    fn count(&self, engine: FormEngine) -> usize {
+        self.forms
+            .iter()
+            .filter(|form| form.engine == engine)
+            .count()
+    }
+}
+
+// ============================================================================
+// Eval Reports
+// ============================================================================
+
+/// Where a top-level form appears in its source text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SourceSpan {
+    /// Byte offset of the form's first character
+    pub start: usize,
+    /// Byte offset just past the form's last character
+    pub end: usize,
+    /// 1-based line of `start`
+    pub line: usize,
+    /// 1-based column of `start`, in characters
+    pub column: usize,
+}
+
+impl SourceSpan {
+    /// The span of `source[start..end]`.
+    pub fn new(source: &str, start: usize, end: usize) -> Self {
+        let before = &source[..start];
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        SourceSpan {
+            start,
+            end,
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+        }
+    }
+}
+
+/// The step at which a top-level form failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ErrorStage {
+    /// The text could not be read as a form; nothing after it was evaluated
+    Parse,
+    /// Evaluating the form (including macro expansion) failed
+    Eval,
+}
+
+/// A failed top-level form.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FormError {
+    pub stage: ErrorStage,
+    pub message: String,
+}
+
+/// The outcome of one top-level form in an `EvalReport`.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FormEval {
+    /// 1-based position of the form in the source
+    pub index: usize,
+    pub span: SourceSpan,
+    /// Engine that produced the result; `None` if the form was never run
+    pub engine: Option<FormEngine>,
+    /// Time spent expanding, compiling and evaluating the form
+    pub duration: Duration,
+    /// Why the JIT declined the form, if it fell back
+    pub fallback_reason: Option<String>,
+    /// The printed result, or why the form failed
+    pub result: Result<String, FormError>,
+    /// The result itself, for Rust callers; not serialized
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub value: Option<Value>,
+    /// True if `eval_incremental` reused the result from a previous pass
+    pub cached: bool,
+}
+
+/// Per-form results of evaluating a whole buffer.
+///
+/// Unlike `RunReport`, evaluation continues past failing forms, so a report
+/// describes every form up to the end of the source or the first parse error.
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EvalReport {
+    /// One entry per top-level form, in source order
+    pub forms: Vec<FormEval>,
+}
+
+impl EvalReport {
+    /// True if every form evaluated without error.
+    pub fn is_ok(&self) -> bool {
+        self.forms.iter().all(|form| form.result.is_ok())
+    }
+
+    /// The forms that failed.
+    pub fn errors(&self) -> impl Iterator<Item = &FormEval> {
+        self.forms.iter().filter(|form| form.result.is_err())
+    }
+
+    /// The forms that were evaluated rather than reused from a previous pass.
+    pub fn evaluated(&self) -> impl Iterator<Item = &FormEval> {
+        self.forms.iter().filter(|form| !form.cached)
+    }
+}
+
+/// Describe an evaluation error in the top-level form at `index` (1-based).
+pub fn form_error(index: usize, snippet: &str, error: impl fmt::Display) -> String {
+    format!("Evaluation error in form {index} `{snippet}`: {error}")
+}
+
+fn millis(d: Duration) -> f64 {
+    d.as_secs_f64() * 1000.0
+}
+
+/// Shorten a form's printed representation for report lines and errors.
+pub fn snippet(expr: &Value) -> String {
+    let text = format!("{expr}");
+    if text.chars().count() <= SNIPPET_LEN {
+        text
+    } else {
+        let mut short: String = text.chars().take(SNIPPET_LEN - 3).collect();
+        short.push_str("...");
+        short
+    }
+}
+
+// ============================================================================
+// Running Files
+// ============================================================================
+
+/// Run a file with the JIT, recording per-form telemetry.
+///
+/// With `main_args`, the file's `main` is then called with them, as
+/// described at `call_main`.
+pub fn run_file_jit_report(
+    filename: &str,
+    main_args: Option<&[String]>,
+) -> Result<RunReport, String> {
+    run_file_jit_report_with(filename, main_args, &StdlibConfig::default())
+}
+
+/// Run a file with the JIT like `run_file_jit_report`, with the parts of the
+/// standard library that `config` includes.
+pub fn run_file_jit_report_with(
+    filename: &str,
+    main_args: Option<&[String]>,
+    config: &StdlibConfig,
+) -> Result<RunReport, String> {
+    let contents = read_source(filename)?;
+    run_jit_report(&contents, Some(Path::new(filename)), main_args, config)
+}
+
+/// Run source text with the JIT, recording per-form telemetry.
+///
+/// Definitions always use the interpreter so their bindings are stored in the
+/// environment; every other form is compiled if possible and falls back to the
+/// interpreter otherwise. `main` is called by the interpreter.
+pub fn run_source_jit_report(
+    source: &str,
+    main_args: Option<&[String]>,
+) -> Result<RunReport, String> {
+    run_source_jit_report_with(source, main_args, &StdlibConfig::default())
+}
+
+/// Run source text with the JIT like `run_source_jit_report`, with the parts
+/// of the standard library that `config` includes.
+pub fn run_source_jit_report_with(
+    source: &str,
+    main_args: Option<&[String]>,
+    config: &StdlibConfig,
+) -> Result<RunReport, String> {
+    run_jit_report(source, None, main_args, config)
+}
+
+/// Run source text with the JIT, with `file` as the current file that
+/// `load` and `require` resolve relative paths against.
+fn run_jit_report(
+    source: &str,
+    file: Option<&Path>,
+    main_args: Option<&[String]>,
+    config: &StdlibConfig,
+) -> Result<RunReport, String> {
+    let mut env = Environment::new();
+    register_stdlib_with(&mut env, config);
+    if let Some(file) = file {
+        enter_file(&mut env, file);
+    }
+    define_script_args(&mut env, main_args.unwrap_or_default());
+
+    let jit_engine = JitEngine::new().map_err(|e| format!("Failed to initialize JIT: {e}"))?;
+    register_jit_introspection(&mut env, Some(&jit_engine));
+
+    let mut report = RunReport::default();
+
+    for expr in parse_iter(source) {
+        let expr = expr?;
+        let index = report.forms.len() + 1;
+        let snippet = snippet(&expr);
+
+        if is_definition_expr(&expr) {
+            let start = Instant::now();
+            let constant = constant_name(&expr);
+            let function = function_name(&expr);
+            let result = eval(expr, &mut env).map_err(|e| form_error(index, &snippet, &e))?;
+            if let Some(name) = constant {
+                jit_engine
+                    .define_constant(name, result.clone())
+                    .map_err(|e| form_error(index, &snippet, &e))?;
+            }
+            if let Some(name) = function {
+                jit_engine.define_function(name, &result);
+            }
+            report.forms.push(FormReport {
+                index,
+                snippet,
+                engine: FormEngine::Definition,
+                compile_time: Duration::ZERO,
+                execute_time: start.elapsed(),
+                fallback_reason: None,
+                result: format!("{result}"),
+            });
+            continue;
+        }
+
+        // Try JIT first, timing compilation and execution separately. A macro
+        // expansion error is the program's fault, so it is reported rather
+        // than retried by the interpreter.
+        let compile_start = Instant::now();
+        let expanded =
+            expand_all_macros(expr, &mut env, 0).map_err(|e| form_error(index, &snippet, &e))?;
+        // An earlier form may have changed a function with set!
+        jit_engine.refresh_functions(&env);
+        let compiled = jit_engine.compile(&expanded);
+        let compile_time = compile_start.elapsed();
+        let executed = compiled.and_then(|compiled| {
+            let start = Instant::now();
+            let rv = compiled.execute();
+            match rv.to_error() {
+                Some(error) => Err(format!("{error} in compiled code")),
+                None => Ok((rv, start.elapsed())),
+            }
+        });
+
+        let form = match executed {
+            Ok((rv, execute_time)) => FormReport {
+                index,
+                snippet,
+                engine: FormEngine::Jit,
+                compile_time,
+                execute_time,
+                fallback_reason: None,
+                result: runtime_value_to_string(rv),
+            },
+            Err(reason) => {
+                // Fall back to interpreter for unsupported expressions and
+                // for integer overflow, which the interpreter promotes to a
+                // bignum
+                let start = Instant::now();
+                let result =
+                    eval(expanded, &mut env).map_err(|e| form_error(index, &snippet, &e))?;
+                FormReport {
+                    index,
+                    snippet,
+                    engine: FormEngine::Interpreter,
+                    compile_time,
+                    execute_time: start.elapsed(),
+                    fallback_reason: Some(reason),
+                    result: format!("{result}"),
+                }
+            }
+        };
+        report.forms.push(form);
+    }
+
+    if let Some(args) = main_args {
+        report.main_result = call_main(&mut env, args)
+            .map_err(|e| format!("main: {e}"))?
+            .map(|result| result.to_string());
+    }
+
+    Ok(report)
+}
+
+// ============================================================================
+// Scripts
+// ============================================================================
+
+/// Name of the function a script defines to be called after it is loaded.
+pub const MAIN: &str = "main";
+
+/// Name of the variable that holds a script's command-line arguments.
+pub const ARGS: &str = "*args*";
+
+/// Bind `*args*` in `env` to `args` as a list of strings, so a script can
+/// read its arguments at top level as well as in `main`.
+pub fn define_script_args(env: &mut Environment, args: &[String]) {
+    let args = vec_to_list(args.iter().map(|arg| make_string(arg.as_str())).collect());
+    env.define(ARGS.to_string(), args);
+}
+
+/// Call the `main` function defined in `env`, if there is one, with `args`
+/// as a list of strings, returning what it returns.
+///
+/// Files run from the command line get this call after all of their
+/// top-level forms have been evaluated, unless `--no-main` is given. Loading
+/// source any other way never calls `main`, so a script can also be used as
+/// a library. The AOT compiler follows the same convention, passing the
+/// compiled program's arguments.
+pub fn call_main(env: &mut Environment, args: &[String]) -> Result<Option<Value>, EvalError> {
+    let Some(main) = env.lookup(MAIN) else {
+        return Ok(None);
+    };
+    if !matches!(
+        main,
+        Value::Lambda(_) | Value::NativeFn(_) | Value::NativeClosure(_)
+    ) {
+        return Err(format!("{MAIN} must be a function of the argument list, got {main}").into());
+    }
+    let args = vec_to_list(args.iter().map(|arg| make_string(arg.as_str())).collect());
+    apply(&main, &[args], env).map(Some)
+}
+
+// ============================================================================
+// Reading Source Files
+// ============================================================================
+
+/// Read a source file as UTF-8 text with normalized line endings.
+///
+/// Invalid UTF-8 is reported with its line and byte offset instead of the
+/// bare io::Error from `read_to_string`.
+pub fn read_source(filename: &str) -> Result<String, String> {
+    let bytes = fs::read(filename).map_err(|e| format!("Failed to read file '{filename}': {e}"))?;
+    decode_source(&bytes).map_err(|e| format!("{filename}: {e}"))
+}
+
+/// Decode source bytes as UTF-8 and normalize line endings to `\n`.
+///
+/// If the invalid byte is inside a string literal, the error points at the
+/// start of the string, which is where the problem is usually visible.
+pub fn decode_source(bytes: &[u8]) -> Result<String, String> {
+    match std::str::from_utf8(bytes) {
+        Ok(text) => Ok(normalize_line_endings(text)),
+        Err(e) => {
+            let offset = e.valid_up_to();
+            // Everything before the first invalid byte is valid by definition
+            let valid = std::str::from_utf8(&bytes[..offset]).unwrap_or_default();
+            Err(match open_string_start(valid) {
+                Some(start) => format!(
+                    "invalid UTF-8 in string literal starting at line {} (byte offset {start})",
+                    line_number(&valid[..start])
+                ),
+                None => format!(
+                    "invalid UTF-8 byte 0x{:02X} at line {} (byte offset {offset})",
+                    bytes[offset],
+                    line_number(valid)
+                ),
+            })
+        }
+    }
+}
+
+/// Replace `\r\n` and lone `\r` line terminators with `\n`.
+pub fn normalize_line_endings(text: &str) -> String {
+    if !text.contains('\r') {
+        return text.to_string();
+    }
+    text.replace("\r\n", "\n").replace('\r', "\n")
+}
+
+/// 1-based line number of the position just after `before`.
+fn line_number(before: &str) -> usize {
+    normalize_line_endings(before).matches('\n').count() + 1
+}
+
+/// Byte offset of the string literal still open at the end of `text`, if any.
+fn open_string_start(text: &str) -> Option<usize> {
+    let mut string_start = None;
+    let mut in_comment = false;
+    let mut escape_next = false;
+    for (i, ch) in text.char_indices() {
+        if in_comment {
+            in_comment = ch != '\n' && ch != '\r';
+        } else if string_start.is_some() {
+            if escape_next {
+                escape_next = false;
+            } else if ch == '\\' {
+                escape_next = true;
+            } else if ch == '"' {
+                string_start = None;
+            }
+        } else if ch == ';' {
+            in_comment = true;
+        } else if ch == '"' {
+            string_start = Some(i);
+        }
+    }
+    string_start
+}
+
+// ============================================================================
+// Splitting Files into Forms
+// ============================================================================
+
+/// Check if an expression is a definition (label, def, defmacro, define-constant)
+/// or a `load` or `require` of definitions, and so must use the interpreter
+pub fn is_definition_expr(expr: &Value) -> bool {
+    if let Value::Cons(cell) = expr
+        && let Value::Atom(AtomType::Symbol(SymbolType::Symbol(sym))) = &cell.car
+    {
+        return matches!(
+            sym.resolve().as_str(),
+            "label" | "def" | "defmacro" | "define-constant" | "load" | "require"
+        );
+    }
+    false
+}
+
+/// Make `path` the file being evaluated in `env`, so that `load` and
+/// `require` resolve relative paths against its directory.
+pub fn enter_file(env: &mut Environment, path: &Path) {
+    let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    // A fresh environment has no file loading yet, so there is no cycle
+    let _ = env.begin_file(path);
+}
+
+/// The name defined by a `(define-constant name value)` form.
+///
+/// Definitions are evaluated by the interpreter; callers pass the resulting
+/// value to `JitEngine::define_constant` so compiled code can inline it.
+pub fn constant_name(expr: &Value) -> Option<InternedSymbol> {
+    if let Value::Cons(cell) = expr
+        && let Value::Atom(AtomType::Symbol(SymbolType::Symbol(sym))) = &cell.car
+        && sym.resolve() == "define-constant"
+        && let Value::Cons(args) = &cell.cdr
+        && let Value::Atom(AtomType::Symbol(SymbolType::Symbol(name))) = &args.car
+    {
+        return Some(*name);
+    }
+    None
+}
+
+/// The name defined by a `(label name value)` or `(def name value)` form.
+///
+/// Callers pass the value the interpreter gives it to
+/// `JitEngine::define_function`, so compiled code can call the function.
+pub fn function_name(expr: &Value) -> Option<InternedSymbol> {
+    if let Value::Cons(cell) = expr
+        && let Value::Atom(AtomType::Symbol(SymbolType::Symbol(sym))) = &cell.car
+        && matches!(sym.resolve().as_str(), "label" | "def")
+        && let Value::Cons(args) = &cell.cdr
+        && let Value::Atom(AtomType::Symbol(SymbolType::Symbol(name))) = &args.car
+    {
+        return Some(*name);
+    }
+    None
+}
+
+// Helper function to skip whitespace and comments between expressions
+// Note: Comments WITHIN expressions are now handled natively by the lexer
+// This function is only needed to skip comments BETWEEN top-level expressions
+pub fn skip_whitespace_and_comments(input: &str) -> &str {
+    let mut remaining = input;
+    loop {
+        // Skip whitespace
+        remaining = remaining.trim_start();
+        if remaining.is_empty() {
+            return remaining;
+        }
+
+        // Skip comments (from ; to end of line, which may end in \r\n or \r)
+        if remaining.starts_with(';') {
+            if let Some(newline_pos) = remaining.find(['\n', '\r']) {
+                remaining = &remaining[newline_pos + 1..];
+            } else {
+                // Comment to end of file
+                return "";
+            }
+        } else {
+            // No more comments or whitespace
+            break;
+        }
+    }
+    remaining
+}
@@ -3,6 +3,8 @@
 //! This module provides a C-compatible value representation that can be used
 //! by compiled code to pass values to and from runtime functions.
 
+use std::cell::{Cell, RefCell};
+use std::collections::HashSet;
 use std::sync::Arc;
 use std::sync::atomic::AtomicU32;
 
@@ -10,6 +12,8 @@ use consair::interner::InternedSymbol;
 use consair::language::{AtomType, ConsCell, StringType, SymbolType, Value, VectorValue};
 use consair::numeric::NumericType;
 
+use crate::jit::JitError;
+
 // ============================================================================
 // Tag Constants
 // ============================================================================
@@ -32,6 +36,17 @@ pub const TAG_CLOSURE: u8 = 6;
 pub const TAG_STRING: u8 = 7;
 /// Tag for vector pointers
 pub const TAG_VECTOR: u8 = 8;
+/// Tag for runtime errors raised by compiled code; `data` is an error code
+pub const TAG_ERROR: u8 = 9;
+
+/// Error code for an integer result that does not fit in an i64
+pub const ERR_INT_OVERFLOW: u64 = 1;
+
+/// Error code for a list operation on an improper list or a non-list
+pub const ERR_NOT_A_LIST: u64 = 2;
+
+/// Error code for an exact division, or any `mod`, by zero
+pub const ERR_DIV_BY_ZERO: u64 = 3;
 
 // ============================================================================
 // RuntimeValue
@@ -94,6 +109,15 @@ impl RuntimeValue {
         }
     }
 
+    /// Create a runtime error value from an error code.
+    #[inline]
+    pub fn error(code: u64) -> Self {
+        RuntimeValue {
+            tag: TAG_ERROR,
+            data: code,
+        }
+    }
+
     /// Create a symbol value from an interned symbol key.
     #[inline]
     pub fn from_symbol(key: u64) -> Self {
@@ -215,6 +239,12 @@ impl RuntimeValue {
         self.tag == TAG_CLOSURE
     }
 
+    /// Check if this value is a runtime error.
+    #[inline]
+    pub fn is_error(&self) -> bool {
+        self.tag == TAG_ERROR
+    }
+
     /// Check if this value is an atom (not a cons cell).
     #[inline]
     pub fn is_atom(&self) -> bool {
@@ -265,6 +295,21 @@ impl RuntimeValue {
         }
     }
 
+    /// Get the error message if this is a runtime error.
+    #[inline]
+    pub fn to_error(&self) -> Option<&'static str> {
+        if self.tag == TAG_ERROR {
+            Some(match self.data {
+                ERR_INT_OVERFLOW => "integer overflow",
+                ERR_NOT_A_LIST => "expected a proper list",
+                ERR_DIV_BY_ZERO => "division by zero",
+                _ => "runtime error",
+            })
+        } else {
+            None
+        }
+    }
+
     /// Get the symbol key if this is a symbol.
     #[inline]
     pub fn to_symbol_key(&self) -> Option<u64> {
@@ -351,6 +396,8 @@ impl RuntimeValue {
 
             Value::Atom(AtomType::Bool(b)) => Ok(RuntimeValue::from_bool(*b)),
 
+            Value::Atom(AtomType::Char(_)) => Err("JIT does not support characters".to_string()),
+
             Value::Atom(AtomType::Number(num)) => match num {
                 NumericType::Int(n) => Ok(RuntimeValue::from_int(*n)),
                 NumericType::Float(f) => Ok(RuntimeValue::from_float(*f)),
@@ -394,19 +441,25 @@ impl RuntimeValue {
                     len: len as u64,
                     refcount: AtomicU32::new(1),
                 });
-                Ok(unsafe { RuntimeValue::from_string_ptr(Box::into_raw(rt_string)) })
+                Ok(track(unsafe {
+                    RuntimeValue::from_string_ptr(Box::into_raw(rt_string))
+                }))
             }
 
-            Value::Cons(cell) => {
-                // Recursively convert car and cdr
-                let car = RuntimeValue::from_value(&cell.car)?;
-                let cdr = RuntimeValue::from_value(&cell.cdr)?;
-                let rt_cons = Box::new(RuntimeConsCell {
-                    car,
-                    cdr,
-                    refcount: AtomicU32::new(1),
-                });
-                Ok(unsafe { RuntimeValue::from_cons_ptr(Box::into_raw(rt_cons)) })
+            Value::Cons(_) => {
+                // Convert the spine iteratively so long lists don't exhaust
+                // the stack
+                let mut cars = Vec::new();
+                let mut current = v;
+                while let Value::Cons(cell) = current {
+                    cars.push(RuntimeValue::from_value(&cell.car)?);
+                    current = &cell.cdr;
+                }
+                let tail = RuntimeValue::from_value(current)?;
+                Ok(cars
+                    .into_iter()
+                    .rev()
+                    .fold(tail, |cdr, car| rt_cons(car, cdr)))
             }
 
             Value::Vector(vec) => {
@@ -422,7 +475,9 @@ impl RuntimeValue {
                     len: len as u64,
                     refcount: AtomicU32::new(1),
                 });
-                Ok(unsafe { RuntimeValue::from_vector_ptr(Box::into_raw(rt_vec)) })
+                Ok(track(unsafe {
+                    RuntimeValue::from_vector_ptr(Box::into_raw(rt_vec))
+                }))
             }
 
             Value::Lambda(_) => {
@@ -450,9 +505,11 @@ impl RuntimeValue {
 
             Value::Reduced(_) => Err("JIT reduced conversion not yet supported".to_string()),
 
-            Value::NativeFn(_) => {
+            Value::NativeFn(_) | Value::NativeClosure(_) => {
                 Err("Native functions cannot be converted to RuntimeValue".to_string())
             }
+
+            Value::Ref(_) => Err(JitError::unsupported_type("JIT does not support refs").into()),
         }
     }
 
@@ -462,9 +519,20 @@ impl RuntimeValue {
 
     /// Convert a RuntimeValue back to an interpreter Value.
     ///
+    /// Cons cells and vectors are rebuilt as `Value::Cons` and `Value::Vector`
+    /// trees. A compiled closure cannot outlive the code that implements it,
+    /// so it converts to the symbol `<compiled-fn>`, which prints the same.
+    /// A structure that contains itself is an error.
+    ///
     /// # Safety
     /// For pointer types (cons, string, vector), this assumes the pointers are valid.
     pub fn to_value(&self) -> Result<Value, String> {
+        self.to_value_within(&mut HashSet::new())
+    }
+
+    /// `to_value`, where `open` holds the cons cells and vectors whose
+    /// conversion is in progress, so that meeting one again is a cycle.
+    fn to_value_within(self, open: &mut HashSet<u64>) -> Result<Value, String> {
         match self.tag {
             TAG_NIL => Ok(Value::Nil),
 
@@ -496,16 +564,31 @@ impl RuntimeValue {
             }
 
             TAG_CONS => {
-                let ptr = self.data as *mut RuntimeConsCell;
-                if ptr.is_null() {
-                    return Err("Null cons cell pointer".to_string());
+                // Walk the spine iteratively so long lists don't exhaust the
+                // stack
+                let mut cars = Vec::new();
+                let mut spine = Vec::new();
+                let mut current = self;
+                while current.tag == TAG_CONS {
+                    let ptr = current.data as *const RuntimeConsCell;
+                    if ptr.is_null() {
+                        return Err("Null cons cell pointer".to_string());
+                    }
+                    if !open.insert(current.data) {
+                        return Err("Cannot convert a cyclic list".to_string());
+                    }
+                    spine.push(current.data);
+                    let cell = unsafe { &*ptr };
+                    cars.push(cell.car.to_value_within(open)?);
+                    current = cell.cdr;
                 }
-                unsafe {
-                    let cell = &*ptr;
-                    let car = cell.car.to_value()?;
-                    let cdr = cell.cdr.to_value()?;
-                    Ok(Value::Cons(Arc::new(ConsCell { car, cdr })))
+                let tail = current.to_value_within(open)?;
+                for cell in spine {
+                    open.remove(&cell);
                 }
+                Ok(cars.into_iter().rev().fold(tail, |cdr, car| {
+                    Value::Cons(Arc::new(ConsCell { car, cdr }))
+                }))
             }
 
             TAG_STRING => {
@@ -526,22 +609,23 @@ impl RuntimeValue {
                 if ptr.is_null() {
                     return Err("Null vector pointer".to_string());
                 }
-                unsafe {
-                    let rt_vec = &*ptr;
-                    let slice = std::slice::from_raw_parts(rt_vec.elements, rt_vec.len as usize);
-                    let mut elements = Vec::with_capacity(slice.len());
-                    for elem in slice {
-                        elements.push(elem.to_value()?);
-                    }
-                    // Runtime creates fast vectors
-                    Ok(Value::Vector(Arc::new(VectorValue { elements })))
+                if !open.insert(self.data) {
+                    return Err("Cannot convert a cyclic vector".to_string());
                 }
+                let mut elements = Vec::new();
+                for elem in unsafe { children(self) } {
+                    elements.push(elem.to_value_within(open)?);
+                }
+                open.remove(&self.data);
+                // Runtime creates fast vectors
+                Ok(Value::Vector(Arc::new(VectorValue { elements })))
             }
 
-            TAG_CLOSURE => {
-                // Closure conversion requires additional context - deferred
-                Err("Closure to Value conversion not yet implemented".to_string())
-            }
+            TAG_CLOSURE => Ok(Value::Atom(AtomType::Symbol(SymbolType::Symbol(
+                InternedSymbol::new("<compiled-fn>"),
+            )))),
+
+            TAG_ERROR => Err(self.to_error().unwrap_or_default().to_string()),
 
             _ => Err(format!("Unknown RuntimeValue tag: {}", self.tag)),
         }
@@ -560,6 +644,11 @@ impl std::fmt::Debug for RuntimeValue {
             TAG_STRING => write!(f, "RuntimeValue::String(ptr={:#x})", self.data),
             TAG_VECTOR => write!(f, "RuntimeValue::Vector(ptr={:#x})", self.data),
             TAG_CLOSURE => write!(f, "RuntimeValue::Closure(ptr={:#x})", self.data),
+            TAG_ERROR => write!(
+                f,
+                "RuntimeValue::Error({})",
+                self.to_error().unwrap_or_default()
+            ),
             _ => write!(
                 f,
                 "RuntimeValue::Unknown(tag={}, data={})",
@@ -649,7 +738,7 @@ pub extern "C" fn rt_cons(car: RuntimeValue, cdr: RuntimeValue) -> RuntimeValue
         cdr,
         refcount: AtomicU32::new(1),
     });
-    unsafe { RuntimeValue::from_cons_ptr(Box::into_raw(cell)) }
+    track(unsafe { RuntimeValue::from_cons_ptr(Box::into_raw(cell)) })
 }
 
 /// Get the car (first element) of a cons cell.
@@ -763,8 +852,7 @@ pub extern "C" fn rt_decref(val: RuntimeValue) {
                         // Recursively decref car and cdr
                         rt_decref((*ptr).car);
                         rt_decref((*ptr).cdr);
-                        // Free the cons cell
-                        drop(Box::from_raw(ptr));
+                        free_heap_value(val);
                     }
                 }
             }
@@ -776,16 +864,7 @@ pub extern "C" fn rt_decref(val: RuntimeValue) {
                     let prev = (*ptr).refcount.fetch_sub(1, Ordering::Release);
                     if prev == 1 {
                         std::sync::atomic::fence(Ordering::Acquire);
-                        // Free the string data
-                        let data_ptr = (*ptr).data;
-                        let len = (*ptr).len as usize;
-                        if !data_ptr.is_null() {
-                            drop(Box::from_raw(std::ptr::slice_from_raw_parts_mut(
-                                data_ptr, len,
-                            )));
-                        }
-                        // Free the RuntimeString
-                        drop(Box::from_raw(ptr));
+                        free_heap_value(val);
                     }
                 }
             }
@@ -798,18 +877,10 @@ pub extern "C" fn rt_decref(val: RuntimeValue) {
                     if prev == 1 {
                         std::sync::atomic::fence(Ordering::Acquire);
                         // Recursively decref all elements
-                        let elements = (*ptr).elements;
-                        let len = (*ptr).len as usize;
-                        if !elements.is_null() {
-                            for i in 0..len {
-                                rt_decref(*elements.add(i));
-                            }
-                            drop(Box::from_raw(std::ptr::slice_from_raw_parts_mut(
-                                elements, len,
-                            )));
+                        for element in children(val) {
+                            rt_decref(element);
                         }
-                        // Free the RuntimeVector
-                        drop(Box::from_raw(ptr));
+                        free_heap_value(val);
                     }
                 }
             }
@@ -822,18 +893,10 @@ pub extern "C" fn rt_decref(val: RuntimeValue) {
                     if prev == 1 {
                         std::sync::atomic::fence(Ordering::Acquire);
                         // Recursively decref captured values
-                        let env = (*ptr).env;
-                        let env_size = (*ptr).env_size as usize;
-                        if !env.is_null() {
-                            for i in 0..env_size {
-                                rt_decref(*env.add(i));
-                            }
-                            drop(Box::from_raw(std::ptr::slice_from_raw_parts_mut(
-                                env, env_size,
-                            )));
+                        for captured in children(val) {
+                            rt_decref(captured);
                         }
-                        // Free the RuntimeClosure
-                        drop(Box::from_raw(ptr));
+                        free_heap_value(val);
                     }
                 }
             }
@@ -843,10 +906,192 @@ pub extern "C" fn rt_decref(val: RuntimeValue) {
     }
 }
 
+// ============================================================================
+// Allocation Epochs
+// ============================================================================
+//
+// Compiled code never calls `rt_decref` on the values it allocates. Instead
+// each evaluation runs in an epoch: the runtime records every heap value
+// allocated during it, and at the end frees the ones the result does not
+// reach. Values allocated before the epoch, such as quoted lists, are left
+// alone; since values are immutable they cannot refer to newer ones.
+
+thread_local! {
+    /// Heap values allocated on this thread and not yet freed
+    static LIVE_ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+    /// Heap values allocated during the running epoch, if there is one
+    static EPOCH: RefCell<Option<Vec<RuntimeValue>>> = const { RefCell::new(None) };
+    /// The first error raised by the compiled code running on this thread
+    static RAISED: Cell<Option<u64>> = const { Cell::new(None) };
+}
+
+/// Number of heap values (cons cells, strings, vectors and closures)
+/// allocated on this thread that have not been freed.
+pub fn live_allocations() -> usize {
+    LIVE_ALLOCATIONS.with(Cell::get)
+}
+
+/// Run `f` in an allocation epoch and return its result.
+///
+/// Every heap value allocated while `f` runs is freed afterwards unless the
+/// result reaches it. Epochs nest: the values an inner epoch keeps belong to
+/// the outer one.
+pub fn with_epoch(f: impl FnOnce() -> RuntimeValue) -> RuntimeValue {
+    let outer = EPOCH.with(|epoch| epoch.borrow_mut().replace(Vec::new()));
+    let result = f();
+    let allocated = EPOCH
+        .with(|epoch| std::mem::replace(&mut *epoch.borrow_mut(), outer))
+        .unwrap_or_default();
+    let kept = reclaim(allocated, result);
+    EPOCH.with(|epoch| {
+        if let Some(outer) = epoch.borrow_mut().as_mut() {
+            outer.extend(kept);
+        }
+    });
+    result
+}
+
+/// Run compiled code `f` in an allocation epoch, like `with_epoch`.
+///
+/// If the code raised a runtime error, the result is the error value,
+/// whether or not it reached the code's own result: an error tested by a
+/// conditional would otherwise vanish. Everything the code allocated is then
+/// freed.
+pub fn run_compiled(f: impl FnOnce() -> RuntimeValue) -> RuntimeValue {
+    let outer = RAISED.with(Cell::take);
+    let result = with_epoch(|| {
+        let result = f();
+        match RAISED.with(Cell::get) {
+            Some(code) => RuntimeValue::error(code),
+            None => result,
+        }
+    });
+    RAISED.with(|raised| raised.set(outer));
+    result
+}
+
+/// Record the error `code` for `run_compiled` and return it as a value.
+fn raise(code: u64) -> RuntimeValue {
+    RAISED.with(|raised| {
+        if raised.get().is_none() {
+            raised.set(Some(code));
+        }
+    });
+    RuntimeValue::error(code)
+}
+
+/// Count a newly allocated heap value and record it in the running epoch.
+fn track(val: RuntimeValue) -> RuntimeValue {
+    LIVE_ALLOCATIONS.with(|live| live.set(live.get() + 1));
+    EPOCH.with(|epoch| {
+        if let Some(allocated) = epoch.borrow_mut().as_mut() {
+            allocated.push(val);
+        }
+    });
+    val
+}
+
+/// Free the values in `allocated` that `result` does not reach and return
+/// the rest.
+fn reclaim(allocated: Vec<RuntimeValue>, result: RuntimeValue) -> Vec<RuntimeValue> {
+    let candidates: HashSet<u64> = allocated.iter().map(|val| val.data).collect();
+    let mut reached = HashSet::new();
+    let mut pending = vec![result];
+    while let Some(val) = pending.pop() {
+        // Older values cannot refer to ones from this epoch, so tracing
+        // stops at them
+        if is_heap(val) && candidates.contains(&val.data) && reached.insert(val.data) {
+            pending.extend(unsafe { children(val) });
+        }
+    }
+    let (kept, garbage): (Vec<_>, Vec<_>) = allocated
+        .into_iter()
+        .partition(|val| reached.contains(&val.data));
+    for val in garbage {
+        unsafe { free_heap_value(val) };
+    }
+    kept
+}
+
+fn is_heap(val: RuntimeValue) -> bool {
+    matches!(val.tag, TAG_CONS | TAG_STRING | TAG_VECTOR | TAG_CLOSURE)
+}
+
+/// The values a heap value refers to directly.
+///
+/// # Safety
+/// A heap value must point to a live allocation.
+unsafe fn children(val: RuntimeValue) -> Vec<RuntimeValue> {
+    unsafe fn values(ptr: *const RuntimeValue, len: usize) -> Vec<RuntimeValue> {
+        if ptr.is_null() {
+            Vec::new()
+        } else {
+            unsafe { std::slice::from_raw_parts(ptr, len).to_vec() }
+        }
+    }
+    unsafe {
+        match val.tag {
+            TAG_CONS => {
+                let cell = &*(val.data as *const RuntimeConsCell);
+                vec![cell.car, cell.cdr]
+            }
+            TAG_VECTOR => {
+                let vec = &*(val.data as *const RuntimeVector);
+                values(vec.elements, vec.len as usize)
+            }
+            TAG_CLOSURE => {
+                let closure = &*(val.data as *const RuntimeClosure);
+                values(closure.env, closure.env_size as usize)
+            }
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// Free the memory of one heap value, leaving the values it refers to.
+///
+/// # Safety
+/// The value must point to a live allocation that nothing uses afterwards.
+unsafe fn free_heap_value(val: RuntimeValue) {
+    unsafe fn free_slice<T>(ptr: *mut T, len: usize) {
+        if !ptr.is_null() {
+            unsafe { drop(Box::from_raw(std::ptr::slice_from_raw_parts_mut(ptr, len))) };
+        }
+    }
+    if val.data == 0 {
+        return;
+    }
+    unsafe {
+        match val.tag {
+            TAG_CONS => drop(Box::from_raw(val.data as *mut RuntimeConsCell)),
+            TAG_STRING => {
+                let string = Box::from_raw(val.data as *mut RuntimeString);
+                free_slice(string.data, string.len as usize);
+            }
+            TAG_VECTOR => {
+                let vec = Box::from_raw(val.data as *mut RuntimeVector);
+                free_slice(vec.elements, vec.len as usize);
+            }
+            TAG_CLOSURE => {
+                let closure = Box::from_raw(val.data as *mut RuntimeClosure);
+                free_slice(closure.env, closure.env_size as usize);
+            }
+            _ => return,
+        }
+    }
+    LIVE_ALLOCATIONS.with(|live| live.set(live.get().saturating_sub(1)));
+}
+
 // ============================================================================
 // Runtime Arithmetic Functions
 // ============================================================================
 
+// Integer results that do not fit in an i64 raise `ERR_INT_OVERFLOW`
+// rather than wrapping or turning into floats, so that callers can hand the
+// expression to the interpreter, which promotes them to bignums. The checked
+// operations compile to LLVM's `*.with.overflow` intrinsics. An error operand
+// is passed through.
+
 /// Helper to extract a numeric value for arithmetic.
 fn get_numeric(val: RuntimeValue) -> Result<f64, &'static str> {
     match val.tag {
@@ -856,6 +1101,11 @@ fn get_numeric(val: RuntimeValue) -> Result<f64, &'static str> {
     }
 }
 
+/// The first operand that is an error, if either is
+fn error_operand(a: RuntimeValue, b: RuntimeValue) -> Option<RuntimeValue> {
+    [a, b].into_iter().find(RuntimeValue::is_error)
+}
+
 /// Helper to create result - returns int if whole, float otherwise.
 fn make_numeric_result(val: f64) -> RuntimeValue {
     if val.fract() == 0.0 && val >= i64::MIN as f64 && val <= i64::MAX as f64 {
@@ -868,76 +1118,60 @@ fn make_numeric_result(val: f64) -> RuntimeValue {
 /// Add two numbers.
 #[unsafe(no_mangle)]
 pub extern "C" fn rt_add(a: RuntimeValue, b: RuntimeValue) -> RuntimeValue {
-    let a_val = match get_numeric(a) {
-        Ok(v) => v,
-        Err(_) => return RuntimeValue::nil(), // Error case
-    };
-    let b_val = match get_numeric(b) {
-        Ok(v) => v,
-        Err(_) => return RuntimeValue::nil(),
-    };
-
-    // If both are ints and no overflow, return int
-    if a.tag == TAG_INT && b.tag == TAG_INT {
-        let a_int = a.data as i64;
-        let b_int = b.data as i64;
-        if let Some(result) = a_int.checked_add(b_int) {
-            return RuntimeValue::from_int(result);
-        }
+    if let Some((a, b)) = int_operands(a, b) {
+        return a
+            .checked_add(b)
+            .map_or_else(|| raise(ERR_INT_OVERFLOW), RuntimeValue::from_int);
+    }
+    if let Some(error) = error_operand(a, b) {
+        return error;
+    }
+    match (get_numeric(a), get_numeric(b)) {
+        (Ok(a_val), Ok(b_val)) => make_numeric_result(a_val + b_val),
+        _ => RuntimeValue::nil(),
     }
-
-    make_numeric_result(a_val + b_val)
 }
 
 /// Subtract two numbers.
 #[unsafe(no_mangle)]
 pub extern "C" fn rt_sub(a: RuntimeValue, b: RuntimeValue) -> RuntimeValue {
-    let a_val = match get_numeric(a) {
-        Ok(v) => v,
-        Err(_) => return RuntimeValue::nil(),
-    };
-    let b_val = match get_numeric(b) {
-        Ok(v) => v,
-        Err(_) => return RuntimeValue::nil(),
-    };
-
-    if a.tag == TAG_INT && b.tag == TAG_INT {
-        let a_int = a.data as i64;
-        let b_int = b.data as i64;
-        if let Some(result) = a_int.checked_sub(b_int) {
-            return RuntimeValue::from_int(result);
-        }
+    if let Some((a, b)) = int_operands(a, b) {
+        return a
+            .checked_sub(b)
+            .map_or_else(|| raise(ERR_INT_OVERFLOW), RuntimeValue::from_int);
+    }
+    if let Some(error) = error_operand(a, b) {
+        return error;
+    }
+    match (get_numeric(a), get_numeric(b)) {
+        (Ok(a_val), Ok(b_val)) => make_numeric_result(a_val - b_val),
+        _ => RuntimeValue::nil(),
     }
-
-    make_numeric_result(a_val - b_val)
 }
 
 /// Multiply two numbers.
 #[unsafe(no_mangle)]
 pub extern "C" fn rt_mul(a: RuntimeValue, b: RuntimeValue) -> RuntimeValue {
-    let a_val = match get_numeric(a) {
-        Ok(v) => v,
-        Err(_) => return RuntimeValue::nil(),
-    };
-    let b_val = match get_numeric(b) {
-        Ok(v) => v,
-        Err(_) => return RuntimeValue::nil(),
-    };
-
-    if a.tag == TAG_INT && b.tag == TAG_INT {
-        let a_int = a.data as i64;
-        let b_int = b.data as i64;
-        if let Some(result) = a_int.checked_mul(b_int) {
-            return RuntimeValue::from_int(result);
-        }
+    if let Some((a, b)) = int_operands(a, b) {
+        return a
+            .checked_mul(b)
+            .map_or_else(|| raise(ERR_INT_OVERFLOW), RuntimeValue::from_int);
+    }
+    if let Some(error) = error_operand(a, b) {
+        return error;
+    }
+    match (get_numeric(a), get_numeric(b)) {
+        (Ok(a_val), Ok(b_val)) => make_numeric_result(a_val * b_val),
+        _ => RuntimeValue::nil(),
     }
-
-    make_numeric_result(a_val * b_val)
 }
 
 /// Divide two numbers.
 #[unsafe(no_mangle)]
 pub extern "C" fn rt_div(a: RuntimeValue, b: RuntimeValue) -> RuntimeValue {
+    if let Some(error) = error_operand(a, b) {
+        return error;
+    }
     let a_val = match get_numeric(a) {
         Ok(v) => v,
         Err(_) => return RuntimeValue::nil(),
@@ -948,16 +1182,22 @@ pub extern "C" fn rt_div(a: RuntimeValue, b: RuntimeValue) -> RuntimeValue {
     };
 
     if b_val == 0.0 {
-        // Division by zero - return float infinity or NaN
+        // Exact division by zero is an error, as in the interpreter; with a
+        // float operand it is infinity or NaN
+        if int_operands(a, b).is_some() {
+            return raise(ERR_DIV_BY_ZERO);
+        }
         return RuntimeValue::from_float(a_val / b_val);
     }
 
     // If both are ints and divides evenly, return int
-    if a.tag == TAG_INT && b.tag == TAG_INT {
-        let a_int = a.data as i64;
-        let b_int = b.data as i64;
+    if let Some((a_int, b_int)) = int_operands(a, b) {
+        // Only i64::MIN / -1 overflows
+        let Some(quotient) = a_int.checked_div(b_int) else {
+            return raise(ERR_INT_OVERFLOW);
+        };
         if a_int % b_int == 0 {
-            return RuntimeValue::from_int(a_int / b_int);
+            return RuntimeValue::from_int(quotient);
         }
     }
 
@@ -973,10 +1213,118 @@ pub extern "C" fn rt_neg(a: RuntimeValue) -> RuntimeValue {
             if let Some(result) = val.checked_neg() {
                 RuntimeValue::from_int(result)
             } else {
-                RuntimeValue::from_float(-(val as f64))
+                raise(ERR_INT_OVERFLOW)
             }
         }
         TAG_FLOAT => RuntimeValue::from_float(-f64::from_bits(a.data)),
+        TAG_ERROR => a,
+        _ => RuntimeValue::nil(),
+    }
+}
+
+/// Modulus with the sign of the divisor. Any zero divisor raises
+/// `ERR_DIV_BY_ZERO`.
+#[unsafe(no_mangle)]
+pub extern "C" fn rt_mod(a: RuntimeValue, b: RuntimeValue) -> RuntimeValue {
+    if a.tag == TAG_INT && b.tag == TAG_INT {
+        let a_int = a.data as i64;
+        let b_int = b.data as i64;
+        if b_int == 0 {
+            return raise(ERR_DIV_BY_ZERO);
+        }
+        let r = a_int.checked_rem(b_int).unwrap_or(0);
+        let r = if r != 0 && (r < 0) != (b_int < 0) {
+            r + b_int
+        } else {
+            r
+        };
+        return RuntimeValue::from_int(r);
+    }
+
+    let (Ok(a_val), Ok(b_val)) = (get_numeric(a), get_numeric(b)) else {
+        return RuntimeValue::nil();
+    };
+    if b_val == 0.0 {
+        return raise(ERR_DIV_BY_ZERO);
+    }
+    let r = a_val % b_val;
+    let r = if r != 0.0 && (r < 0.0) != (b_val < 0.0) {
+        r + b_val
+    } else {
+        r
+    };
+    RuntimeValue::from_float(r)
+}
+
+/// Absolute value of a number.
+#[unsafe(no_mangle)]
+pub extern "C" fn rt_abs(a: RuntimeValue) -> RuntimeValue {
+    match a.tag {
+        TAG_INT => {
+            let val = a.data as i64;
+            if let Some(result) = val.checked_abs() {
+                RuntimeValue::from_int(result)
+            } else {
+                raise(ERR_INT_OVERFLOW)
+            }
+        }
+        TAG_FLOAT => RuntimeValue::from_float(f64::from_bits(a.data).abs()),
+        TAG_ERROR => a,
+        _ => RuntimeValue::nil(),
+    }
+}
+
+// ============================================================================
+// Runtime Bitwise Functions
+// ============================================================================
+
+/// Both operands as integers, if they are
+fn int_operands(a: RuntimeValue, b: RuntimeValue) -> Option<(i64, i64)> {
+    (a.tag == TAG_INT && b.tag == TAG_INT).then_some((a.data as i64, b.data as i64))
+}
+
+/// Bitwise and. Non-integers return nil.
+#[unsafe(no_mangle)]
+pub extern "C" fn rt_bit_and(a: RuntimeValue, b: RuntimeValue) -> RuntimeValue {
+    int_operands(a, b).map_or_else(RuntimeValue::nil, |(a, b)| RuntimeValue::from_int(a & b))
+}
+
+/// Bitwise inclusive or. Non-integers return nil.
+#[unsafe(no_mangle)]
+pub extern "C" fn rt_bit_or(a: RuntimeValue, b: RuntimeValue) -> RuntimeValue {
+    int_operands(a, b).map_or_else(RuntimeValue::nil, |(a, b)| RuntimeValue::from_int(a | b))
+}
+
+/// Bitwise exclusive or. Non-integers return nil.
+#[unsafe(no_mangle)]
+pub extern "C" fn rt_bit_xor(a: RuntimeValue, b: RuntimeValue) -> RuntimeValue {
+    int_operands(a, b).map_or_else(RuntimeValue::nil, |(a, b)| RuntimeValue::from_int(a ^ b))
+}
+
+/// Bitwise complement. Non-integers return nil.
+#[unsafe(no_mangle)]
+pub extern "C" fn rt_bit_not(a: RuntimeValue) -> RuntimeValue {
+    if a.tag == TAG_INT {
+        RuntimeValue::from_int(!(a.data as i64))
+    } else {
+        RuntimeValue::nil()
+    }
+}
+
+/// Shift left. Non-integers and counts outside 0 to 63 return nil.
+#[unsafe(no_mangle)]
+pub extern "C" fn rt_bit_shift_left(a: RuntimeValue, b: RuntimeValue) -> RuntimeValue {
+    match int_operands(a, b) {
+        Some((n, count @ 0..=63)) => RuntimeValue::from_int(n << count),
+        _ => RuntimeValue::nil(),
+    }
+}
+
+/// Arithmetic shift right. Non-integers and counts outside 0 to 63 return nil.
+#[unsafe(no_mangle)]
+pub extern "C" fn rt_bit_shift_right(a: RuntimeValue, b: RuntimeValue) -> RuntimeValue {
+    match int_operands(a, b) {
+        Some((n, count @ 0..=63)) => RuntimeValue::from_int(n >> count),
         _ => RuntimeValue::nil(),
     }
 }
@@ -1159,7 +1507,7 @@ pub extern "C" fn rt_make_closure(
         refcount: AtomicU32::new(1),
     });
 
-    unsafe { RuntimeValue::from_closure_ptr(Box::into_raw(closure)) }
+    track(unsafe { RuntimeValue::from_closure_ptr(Box::into_raw(closure)) })
 }
 
 /// Get the function pointer from a closure.
@@ -1237,9 +1585,13 @@ pub extern "C" fn rt_now() -> RuntimeValue {
 }
 
 /// Get the length of a list.
-/// Returns 0 for non-list values.
+/// Raises `ERR_NOT_A_LIST` for an improper list or any other value, like the
+/// interpreter's `length`.
 #[unsafe(no_mangle)]
 pub extern "C" fn rt_length(val: RuntimeValue) -> RuntimeValue {
+    if val.is_error() {
+        return val;
+    }
     let mut count: i64 = 0;
     let mut current = val;
 
@@ -1252,6 +1604,9 @@ pub extern "C" fn rt_length(val: RuntimeValue) -> RuntimeValue {
         current = unsafe { (*ptr).cdr };
     }
 
+    if current.tag != TAG_NIL {
+        return raise(ERR_NOT_A_LIST);
+    }
     RuntimeValue::from_int(count)
 }
 
@@ -1379,7 +1734,7 @@ pub extern "C" fn rt_make_vector(elements: *const RuntimeValue, len: u32) -> Run
         refcount: AtomicU32::new(1),
     });
 
-    unsafe { RuntimeValue::from_vector_ptr(Box::into_raw(vector)) }
+    track(unsafe { RuntimeValue::from_vector_ptr(Box::into_raw(vector)) })
 }
 
 /// Get the length of a vector.
@@ -1794,6 +2149,59 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_round_trip_preserves_structure() {
+        for source in [
+            "nil",
+            "t",
+            "-42",
+            "2.5",
+            "\"text\"",
+            "(1 2.5 \"s\" (nested (deeper)) nil)",
+            "<<1 <<2 3>> (4 5) <<>>>>",
+        ] {
+            let value = consair::parse(source).unwrap();
+            let rt = RuntimeValue::from_value(&value).unwrap();
+            assert_eq!(rt.to_value().unwrap(), value, "{source}");
+        }
+
+        let dotted =
+            consair::language::cons(consair::parse("1").unwrap(), consair::parse("2").unwrap());
+        let rt = RuntimeValue::from_value(&dotted).unwrap();
+        assert_eq!(rt.to_value().unwrap(), dotted);
+    }
+
+    #[test]
+    fn test_convert_long_list() {
+        let value = consair::parse(&format!("({})", "1 ".repeat(200_000))).unwrap();
+        let rt = RuntimeValue::from_value(&value).unwrap();
+        assert_eq!(rt_length(rt).to_int(), Some(200_000));
+        assert_eq!(rt.to_value().unwrap(), value);
+    }
+
+    #[test]
+    fn test_convert_cyclic_list_is_an_error() {
+        let cell = rt_cons(RuntimeValue::from_int(1), RuntimeValue::nil());
+        let list = rt_cons(RuntimeValue::from_int(0), cell);
+        let ptr = cell.data as *mut RuntimeConsCell;
+        unsafe { (*ptr).cdr = list };
+        let err = list.to_value().unwrap_err();
+        assert!(err.contains("cyclic"), "{err}");
+
+        // Shared, acyclic structure is fine
+        unsafe { (*ptr).cdr = RuntimeValue::nil() };
+        let shared = rt_make_vector([list, list].as_ptr(), 2);
+        assert_eq!(shared.to_value().unwrap().to_string(), "<<(0 1) (0 1)>>");
+    }
+
+    #[test]
+    fn test_convert_closure_and_empty_runtime_vector() {
+        let closure = rt_make_closure(dummy_closure_fn as *const (), std::ptr::null(), 0);
+        assert_eq!(closure.to_value().unwrap().to_string(), "<compiled-fn>");
+        let empty = rt_make_vector(std::ptr::null(), 0);
+        assert_eq!(empty.to_value().unwrap().to_string(), "<<>>");
+    }
+
     // ========================================================================
     // Runtime FFI Function Tests
     // ========================================================================
@@ -1964,6 +2372,21 @@ mod tests {
         assert!((val - 3.333333333333333).abs() < 1e-10);
     }
 
+    #[test]
+    fn test_rt_div_by_zero() {
+        let int = RuntimeValue::from_int;
+        let float = RuntimeValue::from_float;
+        assert_eq!(rt_div(int(1), int(0)).to_error(), Some("division by zero"));
+        assert_eq!(rt_div(int(0), int(0)).to_error(), Some("division by zero"));
+        // A float operand divides per IEEE
+        assert_eq!(rt_div(float(1.0), int(0)).to_float(), Some(f64::INFINITY));
+        assert_eq!(
+            rt_div(int(-1), float(0.0)).to_float(),
+            Some(f64::NEG_INFINITY)
+        );
+        assert!(rt_div(float(0.0), int(0)).to_float().unwrap().is_nan());
+    }
+
     #[test]
     fn test_rt_neg() {
         let a = RuntimeValue::from_int(42);
@@ -1975,6 +2398,111 @@ mod tests {
         assert_eq!(result2.to_float(), Some(-3.125));
     }
 
+    #[test]
+    fn test_rt_mod() {
+        let m = |a, b| rt_mod(RuntimeValue::from_int(a), RuntimeValue::from_int(b)).to_int();
+        assert_eq!(m(7, 2), Some(1));
+        assert_eq!(m(-7, 2), Some(1));
+        assert_eq!(m(7, -2), Some(-1));
+        assert_eq!(m(i64::MIN, -1), Some(0));
+        assert_eq!(
+            rt_mod(RuntimeValue::from_int(7), RuntimeValue::from_int(0)).to_error(),
+            Some("division by zero")
+        );
+        assert_eq!(
+            rt_mod(RuntimeValue::from_float(7.5), RuntimeValue::from_int(0)).to_error(),
+            Some("division by zero")
+        );
+
+        let f = rt_mod(RuntimeValue::from_float(-5.5), RuntimeValue::from_int(2));
+        assert_eq!(f.to_float(), Some(0.5));
+    }
+
+    #[test]
+    fn test_rt_bitwise() {
+        let int = RuntimeValue::from_int;
+        assert_eq!(rt_bit_and(int(12), int(10)).to_int(), Some(8));
+        assert_eq!(rt_bit_or(int(12), int(10)).to_int(), Some(14));
+        assert_eq!(rt_bit_xor(int(12), int(10)).to_int(), Some(6));
+        assert_eq!(rt_bit_not(int(0)).to_int(), Some(-1));
+        assert_eq!(rt_bit_shift_left(int(1), int(63)).to_int(), Some(i64::MIN));
+        assert_eq!(rt_bit_shift_right(int(-16), int(2)).to_int(), Some(-4));
+        assert!(rt_bit_shift_left(int(1), int(64)).is_nil());
+        assert!(rt_bit_shift_right(int(1), int(-1)).is_nil());
+        assert!(rt_bit_and(int(1), RuntimeValue::from_float(1.0)).is_nil());
+    }
+
+    #[test]
+    fn test_rt_abs() {
+        assert_eq!(rt_abs(RuntimeValue::from_int(-42)).to_int(), Some(42));
+        assert_eq!(rt_abs(RuntimeValue::from_int(42)).to_int(), Some(42));
+        assert_eq!(
+            rt_abs(RuntimeValue::from_float(-3.125)).to_float(),
+            Some(3.125)
+        );
+    }
+
+    #[test]
+    fn test_int_overflow_raises() {
+        let int = RuntimeValue::from_int;
+        let overflows = [
+            rt_add(int(i64::MAX), int(1)),
+            rt_sub(int(i64::MIN), int(1)),
+            rt_mul(int(i64::MAX), int(2)),
+            rt_mul(int(i64::MIN), int(-1)),
+            rt_div(int(i64::MIN), int(-1)),
+            rt_neg(int(i64::MIN)),
+            rt_abs(int(i64::MIN)),
+        ];
+        for result in overflows {
+            assert_eq!(result.to_error(), Some("integer overflow"), "{result:?}");
+        }
+        assert_eq!(rt_add(int(i64::MAX - 1), int(1)).to_int(), Some(i64::MAX));
+        assert_eq!(rt_sub(int(i64::MIN + 1), int(1)).to_int(), Some(i64::MIN));
+        assert_eq!(rt_neg(int(-i64::MAX)).to_int(), Some(i64::MAX));
+
+        // An error passes through later arithmetic
+        let error = rt_mul(int(i64::MAX), int(2));
+        assert!(rt_add(int(1), error).is_error());
+        assert!(rt_neg(error).is_error());
+        assert!(error.to_value().unwrap_err().contains("integer overflow"));
+    }
+
+    #[test]
+    fn test_length_of_an_improper_list_raises() {
+        let int = RuntimeValue::from_int;
+        let pair = run_compiled(|| rt_length(rt_cons(int(1), int(2))));
+        assert_eq!(pair.to_error(), Some("expected a proper list"));
+        let atom = run_compiled(|| rt_length(int(5)));
+        assert_eq!(atom.to_error(), Some("expected a proper list"));
+        assert_eq!(run_compiled(|| rt_length(int_list(0..3))).to_int(), Some(3));
+        assert_eq!(rt_length(RuntimeValue::nil()).to_int(), Some(0));
+    }
+
+    #[test]
+    fn test_refs_are_not_converted() {
+        let r = Value::Ref(Arc::new(consair::RefValue::new(Value::Nil)));
+        let err = RuntimeValue::from_value(&r).unwrap_err();
+        assert!(err.contains("J002_UNSUPPORTED_TYPE"), "{err}");
+    }
+
+    #[test]
+    fn test_run_compiled_reports_a_raised_error() {
+        let int = RuntimeValue::from_int;
+        let before = live_allocations();
+        // The overflow is tested and discarded, but the run still fails
+        let result = run_compiled(|| {
+            let _ = int_list(0..10);
+            let overflow = rt_add(int(i64::MAX), int(1));
+            if overflow.is_truthy() { int(1) } else { int(2) }
+        });
+        assert_eq!(result.to_error(), Some("integer overflow"));
+        assert_eq!(live_allocations(), before);
+
+        // The error does not leak into the next run
+        assert_eq!(run_compiled(|| int(3)).to_int(), Some(3));
+    }
+
     // ========================================================================
     // Comparison Function Tests
     // ========================================================================
@@ -2295,4 +2823,73 @@ mod tests {
         rt_decref(closure);
         // After decref closure, the cons should also be freed (can't check after free)
     }
+
+    // ========================================================================
+    // Allocation Epoch Tests
+    // ========================================================================
+
+    fn int_list(values: std::ops::Range<i64>) -> RuntimeValue {
+        values.rev().fold(RuntimeValue::nil(), |list, i| {
+            rt_cons(RuntimeValue::from_int(i), list)
+        })
+    }
+
+    #[test]
+    fn test_decref_updates_live_allocations() {
+        let before = live_allocations();
+        let list = int_list(0..3);
+        let vector = rt_make_vector([list].as_ptr(), 1);
+        assert_eq!(live_allocations(), before + 4);
+        rt_decref(list);
+        rt_decref(vector);
+        assert_eq!(live_allocations(), before);
+    }
+
+    #[test]
+    fn test_epoch_frees_unreachable_allocations() {
+        let before = live_allocations();
+        let result = with_epoch(|| rt_length(int_list(0..1000)));
+        assert_eq!(result.to_int(), Some(1000));
+        assert_eq!(live_allocations(), before);
+    }
+
+    #[test]
+    fn test_epoch_keeps_what_the_result_reaches() {
+        let before = live_allocations();
+        let result = with_epoch(|| {
+            int_list(0..100);
+            let inner = int_list(0..2);
+            let closure = rt_make_closure(dummy_closure_fn as *const (), [inner].as_ptr(), 1);
+            rt_make_vector([closure, RuntimeValue::from_int(7)].as_ptr(), 2)
+        });
+        // The vector, the closure and the two cells it captures
+        assert_eq!(live_allocations(), before + 4);
+        let inner = rt_closure_env_get(rt_vector_ref(result, RuntimeValue::from_int(0)), 0);
+        assert_eq!(rt_length(inner).to_int(), Some(2));
+    }
+
+    #[test]
+    fn test_epoch_leaves_older_values_alone() {
+        let quoted = int_list(0..3);
+        let before = live_allocations();
+        let result = with_epoch(|| {
+            rt_cons(RuntimeValue::from_int(9), rt_cdr(quoted));
+            rt_cons(RuntimeValue::from_int(-1), quoted)
+        });
+        assert_eq!(live_allocations(), before + 1);
+        assert_eq!(rt_length(result).to_int(), Some(4));
+        assert_eq!(rt_length(quoted).to_int(), Some(3));
+    }
+
+    #[test]
+    fn test_nested_epochs() {
+        let before = live_allocations();
+        let result = with_epoch(|| {
+            let kept = with_epoch(|| int_list(0..5));
+            assert_eq!(live_allocations(), before + 5);
+            rt_length(kept)
+        });
+        assert_eq!(result.to_int(), Some(5));
+        assert_eq!(live_allocations(), before);
+    }
 }
@@ -3,21 +3,37 @@
 //! This module provides the core native functions that are available
 //! in the Consair Lisp environment.
 
+use std::collections::HashSet;
 use std::fs;
-use std::io::{self, Write};
-use std::process::Command;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output, Stdio};
 use std::sync::Arc;
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
-use crate::interpreter::Environment;
-use crate::native::{extract_string, make_int, make_string, vec_to_alist};
+use crate::doc;
+use crate::interpreter::{
+    Environment, apply, eval, expand_all_macros, expand_macro_once, expand_macros,
+};
+use crate::native::{
+    OptKind, check_arity_exact, check_arity_min, check_arity_range, extract_string, is_truthy,
+    list_to_vec, make_bool, make_float, make_int, make_string, make_symbol, parse_opts, seq_to_vec,
+    vec_to_alist, vec_to_list,
+};
+use crate::prelude::register_prelude;
+use crate::runner::{ARGS, form_error, read_source, snippet};
 
+use consair::EvalError;
 use consair::abstractions;
 use consair::interner::InternedSymbol;
 use consair::language::{
-    AtomType, MapValue, SetValue, StringType, SymbolType, Value, VectorValue, cons,
+    self, AtomType, MapValue, NativeFn, PersistentMap, PersistentSet, PersistentVector,
+    PrintLimits, RefValue, SetValue, StringType, SymbolType, Value, VectorValue, cons,
 };
-use consair::numeric::NumericType;
+use consair::numeric::{NumericType, print_float_digits, set_print_float_digits};
+use consair::pretty::pretty_print;
 
 // ============================================================================
 // Standard I/O
@@ -25,18 +41,120 @@ use consair::numeric::NumericType;
 
 /// Print values to stdout with newline
 /// Usage: (println "hello" "world") => prints "hello world\n", returns nil
-pub fn println(args: &[Value], _env: &mut Environment) -> Result<Value, String> {
+pub fn println(args: &[Value], _env: &mut Environment) -> Result<Value, EvalError> {
     print_impl(args, true)
 }
 
 /// Print values to stdout without newline
 /// Usage: (print "hello" "world") => prints "hello world", returns nil
-pub fn print(args: &[Value], _env: &mut Environment) -> Result<Value, String> {
+pub fn print(args: &[Value], _env: &mut Environment) -> Result<Value, EvalError> {
     print_impl(args, false)
 }
 
+/// Print values to stdout in readable form, ignoring *print-float-digits*
+/// and the print limits
+/// Strings are quoted, floats keep full precision and collections print in
+/// full, so the output reads back as the same values.
+/// Usage: (write "hi" 0.1) => prints "\"hi\" 0.1", returns nil
+pub fn write_readable(args: &[Value], _env: &mut Environment) -> Result<Value, EvalError> {
+    let text = readable_text(args);
+    let stdout = io::stdout();
+    let mut handle = stdout.lock();
+    write!(handle, "{text}").map_err(|e| format!("write: I/O error: {e}"))?;
+    handle
+        .flush()
+        .map_err(|e| format!("write: I/O error: {e}"))?;
+    Ok(Value::Nil)
+}
+
+/// Return what `write` would print, as a string
+/// Usage: (write-str "hi" '(1 2)) => "\"hi\" (1 2)"
+pub fn write_str(args: &[Value], _env: &mut Environment) -> Result<Value, EvalError> {
+    Ok(make_string(readable_text(args)))
+}
+
+/// Return what `write` would print, as a string, under Clojure's name
+/// Usage: (pr-str "hi" '(1 2)) => "\"hi\" (1 2)"
+pub fn pr_str(args: &[Value], _env: &mut Environment) -> Result<Value, EvalError> {
+    Ok(make_string(readable_text(args)))
+}
+
+/// The first form written in a string, as data
+/// Whatever follows the first form is ignored.
+/// Usage: (read-string "(1 \"a\" {:b 2})") => (1 "a" {:b 2})
+pub fn read_string(args: &[Value], _env: &mut Environment) -> Result<Value, EvalError> {
+    check_arity_exact("read-string", args, 1)?;
+    let source = string_arg("read-string", &args[0])?;
+    match consair::parse_iter(source).next() {
+        Some(form) => Ok(form.map_err(|e| format!("read-string: {e}"))?),
+        None => Err(format!("read-string: no form in {}", args[0]).into()),
+    }
+}
+
+/// Every form written in a string, as a list of data
+/// Usage: (read-all-string "1 (2) x") => (1 (2) x)
+pub fn read_all_string(args: &[Value], _env: &mut Environment) -> Result<Value, EvalError> {
+    check_arity_exact("read-all-string", args, 1)?;
+    let source = string_arg("read-all-string", &args[0])?;
+    let forms = consair::parse_all(source).map_err(|e| format!("read-all-string: {e}"))?;
+    Ok(vec_to_list(forms))
+}
+
+/// Default line width of pprint and pprint-str
+pub const PPRINT_WIDTH: usize = 80;
+
+/// Pretty-print a value to stdout across lines, followed by a newline
+/// Strings are quoted, as write prints them; the print limits apply.
+/// Usage: (pprint nested) or (pprint nested 40), returns nil
+pub fn pprint(args: &[Value], _env: &mut Environment) -> Result<Value, EvalError> {
+    let text = pprint_text("pprint", args)?;
+    let stdout = io::stdout();
+    let mut handle = stdout.lock();
+    writeln!(handle, "{text}").map_err(|e| format!("pprint: I/O error: {e}"))?;
+    handle
+        .flush()
+        .map_err(|e| format!("pprint: I/O error: {e}"))?;
+    Ok(Value::Nil)
+}
+
+/// Return what pprint would print, without the final newline
+/// Usage: (pprint-str nested 40) => "(a\n b)"
+pub fn pprint_str(args: &[Value], _env: &mut Environment) -> Result<Value, EvalError> {
+    Ok(make_string(pprint_text("pprint-str", args)?))
+}
+
+fn pprint_text(fn_name: &str, args: &[Value]) -> Result<String, EvalError> {
+    check_arity_range(fn_name, args, 1, 2)?;
+    let width = match args.get(1) {
+        None => PPRINT_WIDTH,
+        Some(Value::Atom(AtomType::Number(NumericType::Int(n)))) if *n > 0 => *n as usize,
+        Some(other) => {
+            return Err(EvalError::TypeError {
+                callee: Some(fn_name.to_string()),
+                expected: "a positive integer width".to_string(),
+                found: other.clone(),
+            });
+        }
+    };
+    Ok(pretty_print(&args[0], width))
+}
+
+/// Values separated by spaces, displayed without precision or print limits
+fn readable_text(args: &[Value]) -> String {
+    let previous_digits = set_print_float_digits(None);
+    let previous_limits = language::set_print_limits(PrintLimits::UNLIMITED);
+    let text = args
+        .iter()
+        .map(|arg| format!("{arg}"))
+        .collect::<Vec<_>>()
+        .join(" ");
+    language::set_print_limits(previous_limits);
+    set_print_float_digits(previous_digits);
+    text
+}
+
 /// Internal implementation for print/println
-fn print_impl(args: &[Value], newline: bool) -> Result<Value, String> {
+fn print_impl(args: &[Value], newline: bool) -> Result<Value, EvalError> {
     let stdout = io::stdout();
     let mut handle = stdout.lock();
 
@@ -62,10 +180,11 @@ fn print_impl(args: &[Value], newline: bool) -> Result<Value, String> {
 }
 
 /// Convert a Value to its display string
-/// Strings are printed without quotes, everything else uses Display
+/// Strings and characters are printed as their text, everything else uses Display
 fn value_to_display_string(value: &Value) -> String {
     match value {
         Value::Atom(AtomType::String(StringType::Basic(s))) => s.clone(),
+        Value::Atom(AtomType::Char(c)) => c.to_string(),
         _ => format!("{value}"),
     }
 }
@@ -75,55 +194,256 @@ fn value_to_display_string(value: &Value) -> String {
 // ============================================================================
 
 /// Read entire file as string (Clojure's slurp)
-/// Usage: (slurp "path/to/file.txt") => "file contents"
-pub fn slurp(args: &[Value], _env: &mut Environment) -> Result<Value, String> {
-    if args.len() != 1 {
-        return Err("slurp: expected 1 argument (path)".to_string());
+/// Usage: (slurp "path/to/file.txt") => "file contents" (strict UTF-8)
+///        (slurp "legacy.log" :encoding :latin1) => "file contents"
+///        (slurp "mixed.log" :encoding :utf8-lossy) => invalid sequences become U+FFFD
+///        (slurp "image.png" :bytes t) => <<137 80 78 71 ...>> (same as slurp-bytes)
+pub fn slurp(args: &[Value], env: &mut Environment) -> Result<Value, EvalError> {
+    if args.is_empty() {
+        return Err("slurp: expected 1 argument (path) plus keyword options".into());
     }
 
     let path = extract_string(&args[0])?;
-
-    let content =
-        fs::read_to_string(&path).map_err(|e| format!("slurp: failed to read '{path}': {e}"))?;
+    let opts = parse_opts(
+        "slurp",
+        &args[1..],
+        &[("encoding", OptKind::Keyword), ("bytes", OptKind::Flag)],
+    )?;
+    let encoding = opts
+        .keyword("encoding")
+        .unwrap_or_else(|| ":utf8".to_string());
+
+    if opts.flag("bytes") {
+        return slurp_bytes(&args[..1], env);
+    }
+
+    let data = fs::read(&path).map_err(|e| format!("slurp: failed to read '{path}': {e}"))?;
+
+    let content = match encoding.as_str() {
+        ":utf8" => String::from_utf8(data).map_err(|e| {
+            format!(
+                "slurp: '{path}' is not valid UTF-8 (invalid byte sequence at offset {}); \
+                 use :encoding :utf8-lossy or :encoding :latin1 to read it anyway",
+                e.utf8_error().valid_up_to()
+            )
+        })?,
+        ":utf8-lossy" => String::from_utf8_lossy(&data).into_owned(),
+        // Latin-1 maps each byte directly to the code point of the same value
+        ":latin1" => data.iter().map(|&b| b as char).collect(),
+        _ => {
+            return Err(format!(
+                "slurp: unsupported encoding {encoding} (expected :utf8, :utf8-lossy or :latin1)"
+            )
+            .into());
+        }
+    };
 
     Ok(make_string(content))
 }
 
+/// Read entire file as a vector of byte values
+/// Usage: (slurp-bytes "path/to/file.bin") => <<0 255 16>>
+pub fn slurp_bytes(args: &[Value], _env: &mut Environment) -> Result<Value, EvalError> {
+    if args.len() != 1 {
+        return Err("slurp-bytes: expected 1 argument (path)".into());
+    }
+
+    let path = extract_string(&args[0])?;
+    let data = fs::read(&path).map_err(|e| format!("slurp-bytes: failed to read '{path}': {e}"))?;
+
+    let elements = data.into_iter().map(|b| make_int(b as i64)).collect();
+    Ok(Value::Vector(Arc::new(VectorValue { elements })))
+}
+
 /// Write string to file (Clojure's spit)
 /// Usage: (spit "path/to/file.txt" "content") => nil
-pub fn spit(args: &[Value], _env: &mut Environment) -> Result<Value, String> {
-    if args.len() != 2 {
-        return Err("spit: expected 2 arguments (path, content)".to_string());
+///        (spit "log.txt" "line\n" :append t) => nil (appends instead of truncating)
+///        (spit "app.conf" "..." :atomic t) => nil (writes a temp file, then renames it)
+///        (spit "out/data.txt" "..." :create-dirs t) => nil (creates parent directories)
+pub fn spit(args: &[Value], _env: &mut Environment) -> Result<Value, EvalError> {
+    if args.len() < 2 {
+        return Err("spit: expected 2 arguments (path, content) plus keyword options".into());
     }
 
     let path = extract_string(&args[0])?;
     let content = extract_string(&args[1])?;
-
-    fs::write(&path, content).map_err(|e| format!("spit: failed to write '{path}': {e}"))?;
+    let opts = parse_opts(
+        "spit",
+        &args[2..],
+        &[
+            ("append", OptKind::Flag),
+            ("atomic", OptKind::Flag),
+            ("create-dirs", OptKind::Flag),
+        ],
+    )?;
+    let append = opts.flag("append");
+    let atomic = opts.flag("atomic");
+    let create_dirs = opts.flag("create-dirs");
+
+    if append && atomic {
+        return Err("spit: :append and :atomic cannot be combined".into());
+    }
+
+    if create_dirs
+        && let Some(parent) = Path::new(&path).parent()
+        && !parent.as_os_str().is_empty()
+    {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("spit: failed to create directories for '{path}': {e}"))?;
+    }
+
+    let result = if append {
+        fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .and_then(|mut file| file.write_all(content.as_bytes()))
+    } else if atomic {
+        write_atomic(Path::new(&path), content.as_bytes())
+    } else {
+        fs::write(&path, content)
+    };
+    result.map_err(|e| format!("spit: failed to write '{path}': {e}"))?;
 
     Ok(Value::Nil)
 }
 
+/// Write to a temp file in the target's directory, then rename it over the
+/// target so readers never observe a partially written file.
+fn write_atomic(path: &Path, content: &[u8]) -> io::Result<()> {
+    static TEMP_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "path has no file name"))?;
+    let temp_name = format!(
+        ".{}.{}.{}.tmp",
+        file_name.to_string_lossy(),
+        std::process::id(),
+        TEMP_COUNTER.fetch_add(1, Ordering::Relaxed)
+    );
+    let temp_path = path.with_file_name(temp_name);
+
+    let written = fs::File::create(&temp_path).and_then(|mut file| {
+        file.write_all(content)?;
+        file.sync_all()
+    });
+    let result = written.and_then(|_| fs::rename(&temp_path, path));
+    if result.is_err() {
+        let _ = fs::remove_file(&temp_path);
+    }
+    result
+}
+
+// ============================================================================
+// Loading Source Files
+// ============================================================================
+
+/// Evaluate every form of a source file in the current environment
+/// Usage: (load "lib/util.lisp") => value of the file's last form
+pub fn load(args: &[Value], env: &mut Environment) -> Result<Value, EvalError> {
+    check_arity_exact("load", args, 1)?;
+    let path = source_path(&extract_string(&args[0])?, env);
+    load_file(&path, env)
+}
+
+/// Load a source file unless `require` has already loaded it
+/// Usage: (require "lib/util.lisp") => t the first time, nil after that
+pub fn require(args: &[Value], env: &mut Environment) -> Result<Value, EvalError> {
+    check_arity_exact("require", args, 1)?;
+    let path = source_path(&extract_string(&args[0])?, env);
+    let path = path
+        .canonicalize()
+        .map_err(|e| format!("require: failed to read file '{}': {e}", path.display()))?;
+    if env.is_required(&path) {
+        return Ok(make_bool(false));
+    }
+    load_file(&path, env)?;
+    env.mark_required(path);
+    Ok(make_bool(true))
+}
+
+/// `name` resolved against the directory of the file being evaluated, if
+/// it is relative and there is one.
+fn source_path(name: &str, env: &Environment) -> PathBuf {
+    match env.current_file() {
+        Some(current) if Path::new(name).is_relative() => current
+            .parent()
+            .map_or_else(|| PathBuf::from(name), |dir| dir.join(name)),
+        _ => PathBuf::from(name),
+    }
+}
+
+/// Parse all of `path`, then evaluate its forms in order in `env` with
+/// `path` as the current file, returning the last value.
+///
+/// Loading a file that is already being loaded is an error naming the
+/// chain of files that leads back to it.
+pub fn load_file(path: &Path, env: &mut Environment) -> Result<Value, EvalError> {
+    let name = path.display().to_string();
+    let source = read_source(&name)?;
+    let forms = consair::parse_all(&source).map_err(|e| format!("{name}: {e}"))?;
+
+    let canonical = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    env.begin_file(canonical).map_err(|chain| {
+        let chain: Vec<String> = chain.iter().map(|p| p.display().to_string()).collect();
+        format!("Circular load: {}", chain.join(" -> "))
+    })?;
+    let mut result = Ok(Value::Nil);
+    for (index, form) in forms.into_iter().enumerate() {
+        let form_snippet = snippet(&form);
+        result = eval(form, env)
+            .map_err(|e| format!("{name}: {}", form_error(index + 1, &form_snippet, e)).into());
+        if result.is_err() {
+            break;
+        }
+    }
+    env.end_file();
+    result
+}
+
 // ============================================================================
 // Process Execution
 // ============================================================================
 
 /// Execute shell command and return output
-/// Usage: (shell "ls -la") => ((:out . "...") (:err . "...") (:exit . 0) (:success . true))
-pub fn shell(args: &[Value], _env: &mut Environment) -> Result<Value, String> {
-    if args.len() != 1 {
-        return Err("shell: expected 1 argument (command)".to_string());
+/// Usage: (shell "ls -la") => ((out . "...") (err . "...") (exit . 0) (success . t))
+///        (shell "make" :dir "build") => runs in the given working directory
+///        (shell "sleep 10" :timeout-ms 500) => error after 500ms, the command is killed
+pub fn shell(args: &[Value], _env: &mut Environment) -> Result<Value, EvalError> {
+    if args.is_empty() {
+        return Err("shell: expected 1 argument (command) plus keyword options".into());
     }
 
     let command = extract_string(&args[0])?;
-
-    let output = if cfg!(target_os = "windows") {
-        Command::new("cmd").args(["/C", &command]).output()
+    let opts = parse_opts(
+        "shell",
+        &args[1..],
+        &[("dir", OptKind::String), ("timeout-ms", OptKind::Int)],
+    )?;
+
+    let mut cmd = if cfg!(target_os = "windows") {
+        let mut cmd = Command::new("cmd");
+        cmd.args(["/C", &command]);
+        cmd
     } else {
-        Command::new("sh").arg("-c").arg(&command).output()
+        let mut cmd = Command::new("sh");
+        cmd.arg("-c").arg(&command);
+        cmd
     };
+    if let Some(dir) = opts.string("dir") {
+        cmd.current_dir(dir);
+    }
 
-    let output = output.map_err(|e| format!("shell: failed to execute command: {e}"))?;
+    let output = match opts.int("timeout-ms") {
+        Some(ms) if ms < 0 => {
+            return Err(format!("shell: :timeout-ms must be non-negative, got {ms}").into());
+        }
+        Some(ms) => output_with_timeout(cmd, Duration::from_millis(ms as u64))?,
+        None => cmd
+            .output()
+            .map_err(|e| format!("shell: failed to execute command: {e}"))?,
+    };
 
     // Convert stdout/stderr to strings
     let stdout_str = String::from_utf8_lossy(&output.stdout).to_string();
@@ -162,15 +482,78 @@ pub fn shell(args: &[Value], _env: &mut Environment) -> Result<Value, String> {
     Ok(vec_to_alist(result_pairs))
 }
 
+/// Run a command, killing it if it has not exited before `timeout`.
+fn output_with_timeout(mut cmd: Command, timeout: Duration) -> Result<Output, String> {
+    let mut child = cmd
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("shell: failed to execute command: {e}"))?;
+
+    // Drain the pipes on separate threads so a chatty command can't block
+    // on a full pipe while we wait for it
+    let drain = |pipe: Option<Box<dyn Read + Send>>| {
+        thread::spawn(move || {
+            let mut buf = Vec::new();
+            if let Some(mut pipe) = pipe {
+                let _ = pipe.read_to_end(&mut buf);
+            }
+            buf
+        })
+    };
+    let stdout = drain(
+        child
+            .stdout
+            .take()
+            .map(|p| Box::new(p) as Box<dyn Read + Send>),
+    );
+    let stderr = drain(
+        child
+            .stderr
+            .take()
+            .map(|p| Box::new(p) as Box<dyn Read + Send>),
+    );
+
+    let deadline = Instant::now() + timeout;
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) if Instant::now() >= deadline => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(format!(
+                    "shell: command timed out after {}ms",
+                    timeout.as_millis()
+                ));
+            }
+            Ok(None) => thread::sleep(Duration::from_millis(5)),
+            Err(e) => return Err(format!("shell: failed to wait for command: {e}")),
+        }
+    };
+
+    Ok(Output {
+        status,
+        stdout: stdout.join().unwrap_or_default(),
+        stderr: stderr.join().unwrap_or_default(),
+    })
+}
+
+/// The arguments given after the script's file name, as `*args*` holds them
+/// Usage: (command-line-args) => ("arg1" "arg2")
+pub fn command_line_args(args: &[Value], env: &mut Environment) -> Result<Value, EvalError> {
+    check_arity_exact("command-line-args", args, 0)?;
+    Ok(env.global().lookup(ARGS).unwrap_or(Value::Nil))
+}
+
 // ============================================================================
 // Time and Date
 // ============================================================================
 
 /// Get current Unix timestamp (seconds since epoch)
 /// Usage: (now) => 1699564800
-pub fn now(args: &[Value], _env: &mut Environment) -> Result<Value, String> {
+pub fn now(args: &[Value], _env: &mut Environment) -> Result<Value, EvalError> {
     if !args.is_empty() {
-        return Err("now: expected 0 arguments".to_string());
+        return Err("now: expected 0 arguments".into());
     }
 
     let duration = SystemTime::now()
@@ -192,83 +575,53 @@ static GENSYM_COUNTER: std::sync::atomic::AtomicUsize = std::sync::atomic::Atomi
 /// Generate a unique symbol (for macro hygiene)
 /// Usage: (gensym) => g__123
 /// Usage: (gensym "prefix") => prefix__123
-pub fn gensym(args: &[Value], _env: &mut Environment) -> Result<Value, String> {
+pub fn gensym(args: &[Value], _env: &mut Environment) -> Result<Value, EvalError> {
     let prefix = if args.is_empty() {
         "g".to_string()
     } else if args.len() == 1 {
         extract_string(&args[0])?
     } else {
-        return Err("gensym: expected 0 or 1 arguments".to_string());
+        return Err("gensym: expected 0 or 1 arguments".into());
     };
 
-    let counter = GENSYM_COUNTER.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
-    let symbol = format!("{prefix}__{counter}");
-
     Ok(Value::Atom(AtomType::Symbol(SymbolType::Symbol(
-        InternedSymbol::new(&symbol),
+        fresh_symbol(&prefix),
     ))))
 }
 
+/// A symbol no other call returns, named `prefix__N`
+pub fn fresh_symbol(prefix: &str) -> InternedSymbol {
+    let counter = GENSYM_COUNTER.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+    InternedSymbol::new(&format!("{prefix}__{counter}"))
+}
+
 /// Expand a macro call once
 /// Usage: (macroexpand-1 '(when condition body)) => (cond (condition body))
-pub fn macroexpand_1(args: &[Value], env: &mut Environment) -> Result<Value, String> {
+pub fn macroexpand_1(args: &[Value], env: &mut Environment) -> Result<Value, EvalError> {
     if args.len() != 1 {
-        return Err("macroexpand-1: expected 1 argument".to_string());
-    }
-
-    let expr = args[0].clone();
-
-    // Use the internal expand_macro_once function
-    if let Value::Cons(cell) = &expr
-        && let Value::Atom(AtomType::Symbol(SymbolType::Symbol(name))) = &cell.car
-        && let Some(Value::Macro(macro_cell)) = env.lookup(&name.resolve())
-    {
-        // Collect unevaluated arguments
-        let mut macro_args = Vec::new();
-        let mut current = cell.cdr.clone();
-        while let Value::Cons(ref arg_cell) = current {
-            macro_args.push(arg_cell.car.clone());
-            current = arg_cell.cdr.clone();
-        }
-
-        // Check argument count
-        if macro_args.len() != macro_cell.params.len() {
-            return Err(format!(
-                "macro: expected {} arguments, got {}",
-                macro_cell.params.len(),
-                macro_args.len()
-            ));
-        }
-
-        // Create environment for macro expansion
-        let mut macro_env = macro_cell.env.extend(&macro_cell.params, &macro_args);
-
-        // Evaluate macro body to get expanded code
-        return crate::interpreter::eval(macro_cell.body.clone(), &mut macro_env);
+        return Err("macroexpand-1: expected 1 argument".into());
     }
 
-    // Not a macro call, return unchanged
-    Ok(expr)
+    // A form that is not a macro call comes back unchanged
+    let (expanded, _) = expand_macro_once(args[0].clone(), env, 0)?;
+    Ok(expanded)
 }
 
 /// Fully expand all macros in an expression
 /// Usage: (macroexpand '(when condition body)) => fully expanded form
-pub fn macroexpand(args: &[Value], env: &mut Environment) -> Result<Value, String> {
+pub fn macroexpand(args: &[Value], env: &mut Environment) -> Result<Value, EvalError> {
     if args.len() != 1 {
-        return Err("macroexpand: expected 1 argument".to_string());
+        return Err("macroexpand: expected 1 argument".into());
     }
 
-    let mut expr = args[0].clone();
-    let mut expanded = true;
-
-    // Keep expanding until no more macros
-    while expanded {
-        let new_expr = macroexpand_1(&[expr.clone()], env)?;
-        expanded = new_expr != expr;
-        expr = new_expr;
-    }
+    expand_macros(args[0].clone(), env, 0)
+}
 
-    Ok(expr)
+/// Expand every macro call in a form, including those nested in sub-forms
+/// Usage: (macroexpand-all '(when a (when b c))) => (cond (a (cond (b c))))
+pub fn macroexpand_all(args: &[Value], env: &mut Environment) -> Result<Value, EvalError> {
+    check_arity_exact("macroexpand-all", args, 1)?;
+    expand_all_macros(args[0].clone(), env, 0)
 }
 
 // ============================================================================
@@ -276,53 +629,80 @@ pub fn macroexpand(args: &[Value], env: &mut Environment) -> Result<Value, Strin
 // ============================================================================
 
 /// Test if value is an atom
-pub fn atom(args: &[Value], _env: &mut Environment) -> Result<Value, String> {
+pub fn atom(args: &[Value], _env: &mut Environment) -> Result<Value, EvalError> {
     if args.len() != 1 {
-        return Err("atom: expected 1 argument".to_string());
+        return Err("atom: expected 1 argument".into());
     }
     let is_atom = matches!(args[0], Value::Atom(_) | Value::Nil);
     Ok(Value::Atom(AtomType::Bool(is_atom)))
 }
 
-/// Test equality of two atoms
-pub fn eq(args: &[Value], _env: &mut Environment) -> Result<Value, String> {
+/// Test equality of two atoms (functions compare by identity)
+/// Usage: (eq 'a 'a) => t, (eq f f) => t
+pub fn eq(args: &[Value], _env: &mut Environment) -> Result<Value, EvalError> {
+    if args.len() != 2 {
+        return Err("eq: expected 2 arguments".into());
+    }
+    Ok(Value::Atom(AtomType::Bool(language::eq(
+        &args[0], &args[1],
+    ))))
+}
+
+/// Test whether two values are the same object
+/// Atoms compare by value; lists, collections and functions by reference.
+/// Usage: (identical? f f) => t, (identical? '(1) '(1)) => nil
+pub fn identical_p(args: &[Value], _env: &mut Environment) -> Result<Value, EvalError> {
     if args.len() != 2 {
-        return Err("eq: expected 2 arguments".to_string());
+        return Err("identical?: expected 2 arguments".into());
     }
     let result = match (&args[0], &args[1]) {
-        (Value::Atom(a1), Value::Atom(a2)) => a1 == a2,
-        (Value::Nil, Value::Nil) => true,
-        _ => false,
+        (Value::Cons(a), Value::Cons(b)) => Arc::ptr_eq(a, b),
+        (Value::Vector(a), Value::Vector(b)) => Arc::ptr_eq(a, b),
+        (Value::Map(a), Value::Map(b)) => Arc::ptr_eq(a, b),
+        (Value::Set(a), Value::Set(b)) => Arc::ptr_eq(a, b),
+        (Value::PersistentVector(a), Value::PersistentVector(b)) => Arc::ptr_eq(a, b),
+        (Value::PersistentMap(a), Value::PersistentMap(b)) => Arc::ptr_eq(a, b),
+        (Value::PersistentSet(a), Value::PersistentSet(b)) => Arc::ptr_eq(a, b),
+        (a, b) => language::eq(a, b),
     };
     Ok(Value::Atom(AtomType::Bool(result)))
 }
 
+/// Test structural equality (functions still compare by identity)
+/// Usage: (equal? '(1 (2)) '(1 (2))) => t
+pub fn equal_p(args: &[Value], _env: &mut Environment) -> Result<Value, EvalError> {
+    if args.len() != 2 {
+        return Err("equal?: expected 2 arguments".into());
+    }
+    Ok(Value::Atom(AtomType::Bool(args[0] == args[1])))
+}
+
 /// Get first element of a list
-pub fn car(args: &[Value], _env: &mut Environment) -> Result<Value, String> {
+pub fn car(args: &[Value], _env: &mut Environment) -> Result<Value, EvalError> {
     if args.len() != 1 {
-        return Err("car: expected 1 argument".to_string());
+        return Err("car: expected 1 argument".into());
     }
     match &args[0] {
         Value::Cons(cell) => Ok(cell.car.clone()),
-        _ => Err(format!("car: expected cons cell, got {}", args[0])),
+        _ => Err(format!("car: expected cons cell, got {}", args[0]).into()),
     }
 }
 
 /// Get rest of a list
-pub fn cdr(args: &[Value], _env: &mut Environment) -> Result<Value, String> {
+pub fn cdr(args: &[Value], _env: &mut Environment) -> Result<Value, EvalError> {
     if args.len() != 1 {
-        return Err("cdr: expected 1 argument".to_string());
+        return Err("cdr: expected 1 argument".into());
     }
     match &args[0] {
         Value::Cons(cell) => Ok(cell.cdr.clone()),
-        _ => Err(format!("cdr: expected cons cell, got {}", args[0])),
+        _ => Err(format!("cdr: expected cons cell, got {}", args[0]).into()),
     }
 }
 
 /// Construct a cons cell
-pub fn cons_fn(args: &[Value], _env: &mut Environment) -> Result<Value, String> {
+pub fn cons_fn(args: &[Value], _env: &mut Environment) -> Result<Value, EvalError> {
     if args.len() != 2 {
-        return Err("cons: expected 2 arguments".to_string());
+        return Err("cons: expected 2 arguments".into());
     }
     Ok(cons(args[0].clone(), args[1].clone()))
 }
@@ -332,17 +712,17 @@ pub fn cons_fn(args: &[Value], _env: &mut Environment) -> Result<Value, String>
 // ============================================================================
 
 /// Test if value is nil
-pub fn nil_p(args: &[Value], _env: &mut Environment) -> Result<Value, String> {
+pub fn nil_p(args: &[Value], _env: &mut Environment) -> Result<Value, EvalError> {
     if args.len() != 1 {
-        return Err("nil?: expected 1 argument".to_string());
+        return Err("nil?: expected 1 argument".into());
     }
     Ok(Value::Atom(AtomType::Bool(matches!(args[0], Value::Nil))))
 }
 
 /// Test if value is a cons cell (list)
-pub fn cons_p(args: &[Value], _env: &mut Environment) -> Result<Value, String> {
+pub fn cons_p(args: &[Value], _env: &mut Environment) -> Result<Value, EvalError> {
     if args.len() != 1 {
-        return Err("cons?: expected 1 argument".to_string());
+        return Err("cons?: expected 1 argument".into());
     }
     Ok(Value::Atom(AtomType::Bool(matches!(
         args[0],
@@ -351,21 +731,20 @@ pub fn cons_p(args: &[Value], _env: &mut Environment) -> Result<Value, String> {
 }
 
 /// Test if value is a number
-pub fn number_p(args: &[Value], _env: &mut Environment) -> Result<Value, String> {
+pub fn number_p(args: &[Value], _env: &mut Environment) -> Result<Value, EvalError> {
     if args.len() != 1 {
-        return Err("number?: expected 1 argument".to_string());
+        return Err("number?: expected 1 argument".into());
     }
     let is_num = matches!(args[0], Value::Atom(AtomType::Number(_)));
     Ok(Value::Atom(AtomType::Bool(is_num)))
 }
 
 /// Logical not
-pub fn not_fn(args: &[Value], _env: &mut Environment) -> Result<Value, String> {
+pub fn not_fn(args: &[Value], _env: &mut Environment) -> Result<Value, EvalError> {
     if args.len() != 1 {
-        return Err("not: expected 1 argument".to_string());
+        return Err("not: expected 1 argument".into());
     }
-    let is_false = matches!(args[0], Value::Nil | Value::Atom(AtomType::Bool(false)));
-    Ok(Value::Atom(AtomType::Bool(is_false)))
+    Ok(Value::Atom(AtomType::Bool(!args[0].is_truthy())))
 }
 
 // ============================================================================
@@ -373,9 +752,9 @@ pub fn not_fn(args: &[Value], _env: &mut Environment) -> Result<Value, String> {
 // ============================================================================
 
 /// Get length of a list
-pub fn length(args: &[Value], _env: &mut Environment) -> Result<Value, String> {
+pub fn length(args: &[Value], _env: &mut Environment) -> Result<Value, EvalError> {
     if args.len() != 1 {
-        return Err("length: expected 1 argument".to_string());
+        return Err("length: expected 1 argument".into());
     }
     let mut count: i64 = 0;
     let mut current = &args[0];
@@ -383,13 +762,20 @@ pub fn length(args: &[Value], _env: &mut Environment) -> Result<Value, String> {
         count += 1;
         current = &cell.cdr;
     }
+    if !matches!(current, Value::Nil) {
+        return Err(EvalError::TypeError {
+            callee: Some("length".to_string()),
+            expected: "a proper list".to_string(),
+            found: args[0].clone(),
+        });
+    }
     Ok(make_int(count))
 }
 
 /// Append two lists
-pub fn append(args: &[Value], _env: &mut Environment) -> Result<Value, String> {
+pub fn append(args: &[Value], _env: &mut Environment) -> Result<Value, EvalError> {
     if args.len() != 2 {
-        return Err("append: expected 2 arguments".to_string());
+        return Err("append: expected 2 arguments".into());
     }
 
     // If first list is nil, return second
@@ -413,10 +799,15 @@ pub fn append(args: &[Value], _env: &mut Environment) -> Result<Value, String> {
     Ok(result)
 }
 
-/// Reverse a list
-pub fn reverse(args: &[Value], _env: &mut Environment) -> Result<Value, String> {
+/// Reverse a list or vector
+pub fn reverse(args: &[Value], _env: &mut Environment) -> Result<Value, EvalError> {
     if args.len() != 1 {
-        return Err("reverse: expected 1 argument".to_string());
+        return Err("reverse: expected 1 argument".into());
+    }
+    if let Value::Vector(_) | Value::PersistentVector(_) = &args[0] {
+        let mut items = seq_to_vec(&args[0])?;
+        items.reverse();
+        return Ok(abstractions::rebuild_like(&args[0], items));
     }
 
     let mut result = Value::Nil;
@@ -429,7 +820,7 @@ pub fn reverse(args: &[Value], _env: &mut Environment) -> Result<Value, String>
 }
 
 /// Create a list from arguments
-pub fn list(args: &[Value], _env: &mut Environment) -> Result<Value, String> {
+pub fn list(args: &[Value], _env: &mut Environment) -> Result<Value, EvalError> {
     let mut result = Value::Nil;
     for arg in args.iter().rev() {
         result = cons(arg.clone(), result);
@@ -438,14 +829,17 @@ pub fn list(args: &[Value], _env: &mut Environment) -> Result<Value, String> {
 }
 
 /// Get nth element of a list (0-indexed)
-pub fn nth(args: &[Value], _env: &mut Environment) -> Result<Value, String> {
-    if args.len() != 2 {
-        return Err("nth: expected 2 arguments (list, index)".to_string());
+/// Usage: (nth '(a b c) 1) => b
+///        (nth '(a b c) 5 :default 'z) => z (nil without :default)
+pub fn nth(args: &[Value], _env: &mut Environment) -> Result<Value, EvalError> {
+    if args.len() < 2 {
+        return Err("nth: expected 2 arguments (list, index) plus keyword options".into());
     }
+    let opts = parse_opts("nth", &args[2..], &[("default", OptKind::Any)])?;
 
     let n = match &args[1] {
         Value::Atom(AtomType::Number(NumericType::Int(i))) => *i as usize,
-        _ => return Err("nth: index must be an integer".to_string()),
+        _ => return Err("nth: index must be an integer".into()),
     };
 
     let mut current = &args[0];
@@ -458,7 +852,8 @@ pub fn nth(args: &[Value], _env: &mut Environment) -> Result<Value, String> {
         current = &cell.cdr;
     }
 
-    Ok(Value::Nil) // Return nil if index out of bounds
+    // Out of bounds: the :default option, or nil
+    Ok(opts.get("default").cloned().unwrap_or(Value::Nil))
 }
 
 // ============================================================================
@@ -466,30 +861,30 @@ pub fn nth(args: &[Value], _env: &mut Environment) -> Result<Value, String> {
 // ============================================================================
 
 /// Get length of a vector
-pub fn vector_length(args: &[Value], _env: &mut Environment) -> Result<Value, String> {
+pub fn vector_length(args: &[Value], _env: &mut Environment) -> Result<Value, EvalError> {
     if args.len() != 1 {
-        return Err("vector-length: expected 1 argument".to_string());
+        return Err("vector-length: expected 1 argument".into());
     }
     match &args[0] {
         Value::Vector(v) => Ok(make_int(v.elements.len() as i64)),
-        _ => Err(format!("vector-length: expected vector, got {}", args[0])),
+        _ => Err(format!("vector-length: expected vector, got {}", args[0]).into()),
     }
 }
 
 /// Get element from vector by index
-pub fn vector_ref(args: &[Value], _env: &mut Environment) -> Result<Value, String> {
+pub fn vector_ref(args: &[Value], _env: &mut Environment) -> Result<Value, EvalError> {
     if args.len() != 2 {
-        return Err("vector-ref: expected 2 arguments (vector, index)".to_string());
+        return Err("vector-ref: expected 2 arguments (vector, index)".into());
     }
 
     let vec = match &args[0] {
         Value::Vector(v) => v,
-        _ => return Err(format!("vector-ref: expected vector, got {}", args[0])),
+        _ => return Err(format!("vector-ref: expected vector, got {}", args[0]).into()),
     };
 
     let idx = match &args[1] {
         Value::Atom(AtomType::Number(NumericType::Int(i))) => *i as usize,
-        _ => return Err("vector-ref: index must be an integer".to_string()),
+        _ => return Err("vector-ref: index must be an integer".into()),
     };
 
     if idx < vec.elements.len() {
@@ -499,7 +894,8 @@ pub fn vector_ref(args: &[Value], _env: &mut Environment) -> Result<Value, Strin
             "vector-ref: index {} out of bounds for vector of length {}",
             idx,
             vec.elements.len()
-        ))
+        )
+        .into())
     }
 }
 
@@ -508,20 +904,20 @@ pub fn vector_ref(args: &[Value], _env: &mut Environment) -> Result<Value, Strin
 // ============================================================================
 
 /// Addition
-pub fn add(args: &[Value], _env: &mut Environment) -> Result<Value, String> {
+pub fn add(args: &[Value], _env: &mut Environment) -> Result<Value, EvalError> {
     if args.len() < 2 {
-        return Err("+: expected at least 2 arguments".to_string());
+        return Err("+: expected at least 2 arguments".into());
     }
 
     let mut result = match &args[0] {
         Value::Atom(AtomType::Number(n)) => n.clone(),
-        _ => return Err(format!("+: expected number, got {}", args[0])),
+        _ => return Err(format!("+: expected number, got {}", args[0]).into()),
     };
 
     for arg in &args[1..] {
         let num = match arg {
             Value::Atom(AtomType::Number(n)) => n,
-            _ => return Err(format!("+: expected number, got {}", arg)),
+            _ => return Err(format!("+: expected number, got {}", arg).into()),
         };
         result = result.add(num)?;
     }
@@ -530,20 +926,20 @@ pub fn add(args: &[Value], _env: &mut Environment) -> Result<Value, String> {
 }
 
 /// Subtraction (variadic: subtracts successive arguments from first)
-pub fn sub(args: &[Value], _env: &mut Environment) -> Result<Value, String> {
+pub fn sub(args: &[Value], _env: &mut Environment) -> Result<Value, EvalError> {
     if args.len() < 2 {
-        return Err("-: expected at least 2 arguments".to_string());
+        return Err("-: expected at least 2 arguments".into());
     }
 
     let mut result = match &args[0] {
         Value::Atom(AtomType::Number(n)) => n.clone(),
-        _ => return Err(format!("-: expected number, got {}", args[0])),
+        _ => return Err(format!("-: expected number, got {}", args[0]).into()),
     };
 
     for arg in &args[1..] {
         let num = match arg {
             Value::Atom(AtomType::Number(n)) => n,
-            _ => return Err(format!("-: expected number, got {}", arg)),
+            _ => return Err(format!("-: expected number, got {}", arg).into()),
         };
         result = result.sub(num)?;
     }
@@ -552,20 +948,20 @@ pub fn sub(args: &[Value], _env: &mut Environment) -> Result<Value, String> {
 }
 
 /// Multiplication
-pub fn mul(args: &[Value], _env: &mut Environment) -> Result<Value, String> {
+pub fn mul(args: &[Value], _env: &mut Environment) -> Result<Value, EvalError> {
     if args.len() < 2 {
-        return Err("*: expected at least 2 arguments".to_string());
+        return Err("*: expected at least 2 arguments".into());
     }
 
     let mut result = match &args[0] {
         Value::Atom(AtomType::Number(n)) => n.clone(),
-        _ => return Err(format!("*: expected number, got {}", args[0])),
+        _ => return Err(format!("*: expected number, got {}", args[0]).into()),
     };
 
     for arg in &args[1..] {
         let num = match arg {
             Value::Atom(AtomType::Number(n)) => n,
-            _ => return Err(format!("*: expected number, got {}", arg)),
+            _ => return Err(format!("*: expected number, got {}", arg).into()),
         };
         result = result.mul(num)?;
     }
@@ -574,20 +970,20 @@ pub fn mul(args: &[Value], _env: &mut Environment) -> Result<Value, String> {
 }
 
 /// Division (variadic: divides first by successive arguments)
-pub fn div(args: &[Value], _env: &mut Environment) -> Result<Value, String> {
+pub fn div(args: &[Value], _env: &mut Environment) -> Result<Value, EvalError> {
     if args.len() < 2 {
-        return Err("/: expected at least 2 arguments".to_string());
+        return Err("/: expected at least 2 arguments".into());
     }
 
     let mut result = match &args[0] {
         Value::Atom(AtomType::Number(n)) => n.clone(),
-        _ => return Err(format!("/: expected number, got {}", args[0])),
+        _ => return Err(format!("/: expected number, got {}", args[0]).into()),
     };
 
     for arg in &args[1..] {
         let num = match arg {
             Value::Atom(AtomType::Number(n)) => n,
-            _ => return Err(format!("/: expected number, got {}", arg)),
+            _ => return Err(format!("/: expected number, got {}", arg).into()),
         };
         result = result.div(num)?;
     }
@@ -595,111 +991,360 @@ pub fn div(args: &[Value], _env: &mut Environment) -> Result<Value, String> {
     Ok(Value::Atom(AtomType::Number(result)))
 }
 
+/// Apply a two-number operation, prefixing errors with the function name
+fn binary_numeric(
+    fn_name: &str,
+    args: &[Value],
+    op: fn(&NumericType, &NumericType) -> Result<NumericType, String>,
+) -> Result<Value, EvalError> {
+    check_arity_exact(fn_name, args, 2)?;
+    let a = number_arg(fn_name, &args[0])?;
+    let b = number_arg(fn_name, &args[1])?;
+    let result = op(a, b).map_err(|e| format!("{fn_name}: {e}"))?;
+    Ok(Value::Atom(AtomType::Number(result)))
+}
+
+/// Modulus, with the sign of the divisor
+/// Usage: (mod -7 2) => 1
+pub fn mod_fn(args: &[Value], _env: &mut Environment) -> Result<Value, EvalError> {
+    binary_numeric("mod", args, NumericType::modulo)
+}
+
+/// Remainder, with the sign of the dividend
+/// Usage: (rem -7 2) => -1
+pub fn rem_fn(args: &[Value], _env: &mut Environment) -> Result<Value, EvalError> {
+    binary_numeric("rem", args, NumericType::rem)
+}
+
+/// Quotient rounded toward zero
+/// Usage: (quot -7 2) => -3
+pub fn quot_fn(args: &[Value], _env: &mut Environment) -> Result<Value, EvalError> {
+    binary_numeric("quot", args, NumericType::quot)
+}
+
+/// Power
+/// Usage: (expt 2 10) => 1024, (expt 2 -1) => 1/2
+pub fn expt_fn(args: &[Value], _env: &mut Environment) -> Result<Value, EvalError> {
+    binary_numeric("expt", args, NumericType::expt)
+}
+
+/// Absolute value
+/// Usage: (abs -3/4) => 3/4
+pub fn abs_fn(args: &[Value], _env: &mut Environment) -> Result<Value, EvalError> {
+    check_arity_exact("abs", args, 1)?;
+    let n = number_arg("abs", &args[0])?;
+    Ok(Value::Atom(AtomType::Number(n.abs())))
+}
+
+/// Fold one or more numbers with `NumericType::min` or `NumericType::max`
+fn extreme(
+    fn_name: &str,
+    args: &[Value],
+    pick: fn(&NumericType, &NumericType) -> NumericType,
+) -> Result<Value, EvalError> {
+    check_arity_min(fn_name, args, 1)?;
+    let mut result = number_arg(fn_name, &args[0])?.clone();
+    for arg in &args[1..] {
+        result = pick(&result, number_arg(fn_name, arg)?);
+    }
+    Ok(Value::Atom(AtomType::Number(result)))
+}
+
+/// Smallest of the arguments
+/// Usage: (min 3 1 2) => 1
+pub fn min_fn(args: &[Value], _env: &mut Environment) -> Result<Value, EvalError> {
+    extreme("min", args, NumericType::min)
+}
+
+/// Largest of the arguments
+/// Usage: (max 3 1 2) => 3
+pub fn max_fn(args: &[Value], _env: &mut Environment) -> Result<Value, EvalError> {
+    extreme("max", args, NumericType::max)
+}
+
 // ============================================================================
 // Comparison Operations (de-sugared from special forms)
 // ============================================================================
 
 /// Less than
-pub fn lt(args: &[Value], _env: &mut Environment) -> Result<Value, String> {
+pub fn lt(args: &[Value], _env: &mut Environment) -> Result<Value, EvalError> {
     if args.len() != 2 {
-        return Err("<: expected 2 arguments".to_string());
+        return Err("<: expected 2 arguments".into());
     }
 
     let num1 = match &args[0] {
         Value::Atom(AtomType::Number(n)) => n,
-        _ => return Err(format!("<: expected number, got {}", args[0])),
+        _ => return Err(format!("<: expected number, got {}", args[0]).into()),
     };
 
     let num2 = match &args[1] {
         Value::Atom(AtomType::Number(n)) => n,
-        _ => return Err(format!("<: expected number, got {}", args[1])),
+        _ => return Err(format!("<: expected number, got {}", args[1]).into()),
     };
 
     Ok(Value::Atom(AtomType::Bool(num1 < num2)))
 }
 
 /// Greater than
-pub fn gt(args: &[Value], _env: &mut Environment) -> Result<Value, String> {
+pub fn gt(args: &[Value], _env: &mut Environment) -> Result<Value, EvalError> {
     if args.len() != 2 {
-        return Err(">: expected 2 arguments".to_string());
+        return Err(">: expected 2 arguments".into());
     }
 
     let num1 = match &args[0] {
         Value::Atom(AtomType::Number(n)) => n,
-        _ => return Err(format!(">: expected number, got {}", args[0])),
+        _ => return Err(format!(">: expected number, got {}", args[0]).into()),
     };
 
     let num2 = match &args[1] {
         Value::Atom(AtomType::Number(n)) => n,
-        _ => return Err(format!(">: expected number, got {}", args[1])),
+        _ => return Err(format!(">: expected number, got {}", args[1]).into()),
     };
 
     Ok(Value::Atom(AtomType::Bool(num1 > num2)))
 }
 
 /// Less than or equal
-pub fn lte(args: &[Value], _env: &mut Environment) -> Result<Value, String> {
+pub fn lte(args: &[Value], _env: &mut Environment) -> Result<Value, EvalError> {
     if args.len() != 2 {
-        return Err("<=: expected 2 arguments".to_string());
+        return Err("<=: expected 2 arguments".into());
     }
 
     let num1 = match &args[0] {
         Value::Atom(AtomType::Number(n)) => n,
-        _ => return Err(format!("<=: expected number, got {}", args[0])),
+        _ => return Err(format!("<=: expected number, got {}", args[0]).into()),
     };
 
     let num2 = match &args[1] {
         Value::Atom(AtomType::Number(n)) => n,
-        _ => return Err(format!("<=: expected number, got {}", args[1])),
+        _ => return Err(format!("<=: expected number, got {}", args[1]).into()),
     };
 
     Ok(Value::Atom(AtomType::Bool(num1 <= num2)))
 }
 
 /// Greater than or equal
-pub fn gte(args: &[Value], _env: &mut Environment) -> Result<Value, String> {
+pub fn gte(args: &[Value], _env: &mut Environment) -> Result<Value, EvalError> {
     if args.len() != 2 {
-        return Err(">=: expected 2 arguments".to_string());
+        return Err(">=: expected 2 arguments".into());
     }
 
     let num1 = match &args[0] {
         Value::Atom(AtomType::Number(n)) => n,
-        _ => return Err(format!(">=: expected number, got {}", args[0])),
+        _ => return Err(format!(">=: expected number, got {}", args[0]).into()),
     };
 
     let num2 = match &args[1] {
         Value::Atom(AtomType::Number(n)) => n,
-        _ => return Err(format!(">=: expected number, got {}", args[1])),
+        _ => return Err(format!(">=: expected number, got {}", args[1]).into()),
     };
 
     Ok(Value::Atom(AtomType::Bool(num1 >= num2)))
 }
 
 /// Numeric equality
-pub fn num_eq(args: &[Value], _env: &mut Environment) -> Result<Value, String> {
+pub fn num_eq(args: &[Value], _env: &mut Environment) -> Result<Value, EvalError> {
     if args.len() != 2 {
-        return Err("=: expected 2 arguments".to_string());
+        return Err("=: expected 2 arguments".into());
     }
 
     let num1 = match &args[0] {
         Value::Atom(AtomType::Number(n)) => n,
-        _ => return Err(format!("=: expected number, got {}", args[0])),
+        _ => return Err(format!("=: expected number, got {}", args[0]).into()),
     };
 
     let num2 = match &args[1] {
         Value::Atom(AtomType::Number(n)) => n,
-        _ => return Err(format!("=: expected number, got {}", args[1])),
+        _ => return Err(format!("=: expected number, got {}", args[1]).into()),
     };
 
     Ok(Value::Atom(AtomType::Bool(num1 == num2)))
 }
 
+// ============================================================================
+// Numeric Conversion
+// ============================================================================
+
+/// The number in a numeric argument
+fn number_arg<'a>(fn_name: &str, value: &'a Value) -> Result<&'a NumericType, EvalError> {
+    match value {
+        Value::Atom(AtomType::Number(n)) => Ok(n),
+        other => Err(EvalError::TypeError {
+            callee: Some(fn_name.to_string()),
+            expected: "a number".to_string(),
+            found: other.clone(),
+        }),
+    }
+}
+
+/// The number a string holds, written as the reader writes numbers, or nil
+/// Usage: (parse-number "3/4") => 3/4, (parse-number "abc") => nil
+pub fn parse_number(args: &[Value], _env: &mut Environment) -> Result<Value, EvalError> {
+    check_arity_exact("parse-number", args, 1)?;
+    let text = string_arg("parse-number", &args[0])?;
+    Ok(NumericType::parse(text).map_or(Value::Nil, |n| Value::Atom(AtomType::Number(n))))
+}
+
+/// Integer part of a number, rounding toward zero
+/// Usage: (int 7/2) => 3, (int -2.9) => -2
+pub fn int_fn(args: &[Value], _env: &mut Environment) -> Result<Value, EvalError> {
+    check_arity_exact("int", args, 1)?;
+    let n = number_arg("int", &args[0])?
+        .truncate()
+        .map_err(|e| format!("int: {e}"))?;
+    Ok(Value::Atom(AtomType::Number(n)))
+}
+
+/// Nearest float to a number
+/// Usage: (float 1/4) => 0.25
+pub fn float_fn(args: &[Value], _env: &mut Environment) -> Result<Value, EvalError> {
+    check_arity_exact("float", args, 1)?;
+    Ok(make_float(number_arg("float", &args[0])?.to_float()))
+}
+
+/// True if x is the float ##NaN
+/// Usage: (nan? (/ 0.0 0.0)) => t, (nan? 1) => nil
+pub fn nan_p(args: &[Value], _env: &mut Environment) -> Result<Value, EvalError> {
+    classify_float("nan?", args, f64::is_nan, false)
+}
+
+/// True if x is ##Inf or ##-Inf
+/// Usage: (infinite? (/ -1 0.0)) => t
+pub fn infinite_p(args: &[Value], _env: &mut Environment) -> Result<Value, EvalError> {
+    classify_float("infinite?", args, f64::is_infinite, false)
+}
+
+/// True if x is neither infinite nor NaN; exact numbers always are
+/// Usage: (finite? 1.5) => t, (finite? ##Inf) => nil
+pub fn finite_p(args: &[Value], _env: &mut Environment) -> Result<Value, EvalError> {
+    classify_float("finite?", args, f64::is_finite, true)
+}
+
+/// Test a float with `test`, answering `exact` for integers and ratios
+fn classify_float(
+    fn_name: &str,
+    args: &[Value],
+    test: fn(f64) -> bool,
+    exact: bool,
+) -> Result<Value, EvalError> {
+    check_arity_exact(fn_name, args, 1)?;
+    let result = match number_arg(fn_name, &args[0])? {
+        NumericType::Float(x) => test(*x),
+        _ => exact,
+    };
+    Ok(Value::Atom(AtomType::Bool(result)))
+}
+
+/// A number written as a string, with integers optionally in another radix
+/// Usage: (format-number 255 16) => "ff", (format-number 1/2) => "1/2"
+pub fn format_number(args: &[Value], _env: &mut Environment) -> Result<Value, EvalError> {
+    check_arity_range("format-number", args, 1, 2)?;
+    let n = number_arg("format-number", &args[0])?;
+    let text = match args.get(1) {
+        None => n.to_string(),
+        Some(Value::Atom(AtomType::Number(NumericType::Int(radix)))) => {
+            let radix = u32::try_from(*radix).unwrap_or(0);
+            n.to_str_radix(radix)
+                .map_err(|e| format!("format-number: {e}"))?
+        }
+        Some(other) => {
+            return Err(EvalError::TypeError {
+                callee: Some("format-number".to_string()),
+                expected: "an integer radix".to_string(),
+                found: other.clone(),
+            });
+        }
+    };
+    Ok(make_string(text))
+}
+
+// ============================================================================
+// Bitwise Operations
+// ============================================================================
+
+/// A fixnum operand of a bitwise operation, as 64-bit two's complement
+fn bits_arg(fn_name: &str, value: &Value) -> Result<i64, EvalError> {
+    match value {
+        Value::Atom(AtomType::Number(NumericType::Int(n))) => Ok(*n),
+        Value::Atom(AtomType::Number(NumericType::BigInt(_))) => {
+            Err(format!("{fn_name}: big integers are not supported, got {value}").into())
+        }
+        other => Err(EvalError::TypeError {
+            callee: Some(fn_name.to_string()),
+            expected: "an integer".to_string(),
+            found: other.clone(),
+        }),
+    }
+}
+
+/// Fold two or more integers with a bitwise operation
+fn fold_bits(fn_name: &str, args: &[Value], op: fn(i64, i64) -> i64) -> Result<Value, EvalError> {
+    check_arity_min(fn_name, args, 2)?;
+    let mut result = bits_arg(fn_name, &args[0])?;
+    for arg in &args[1..] {
+        result = op(result, bits_arg(fn_name, arg)?);
+    }
+    Ok(make_int(result))
+}
+
+/// A shift count, which must be between 0 and 63
+fn shift_arg(fn_name: &str, value: &Value) -> Result<u32, EvalError> {
+    let count = bits_arg(fn_name, value)?;
+    u32::try_from(count)
+        .ok()
+        .filter(|count| *count < i64::BITS)
+        .ok_or_else(|| {
+            format!("{fn_name}: shift count must be between 0 and 63, got {count}").into()
+        })
+}
+
+/// Bitwise and
+/// Usage: (bit-and 12 10) => 8
+pub fn bit_and(args: &[Value], _env: &mut Environment) -> Result<Value, EvalError> {
+    fold_bits("bit-and", args, |a, b| a & b)
+}
+
+/// Bitwise inclusive or
+/// Usage: (bit-or 12 10) => 14
+pub fn bit_or(args: &[Value], _env: &mut Environment) -> Result<Value, EvalError> {
+    fold_bits("bit-or", args, |a, b| a | b)
+}
+
+/// Bitwise exclusive or
+/// Usage: (bit-xor 12 10) => 6
+pub fn bit_xor(args: &[Value], _env: &mut Environment) -> Result<Value, EvalError> {
+    fold_bits("bit-xor", args, |a, b| a ^ b)
+}
+
+/// Bitwise complement
+/// Usage: (bit-not 0) => -1
+pub fn bit_not(args: &[Value], _env: &mut Environment) -> Result<Value, EvalError> {
+    check_arity_exact("bit-not", args, 1)?;
+    Ok(make_int(!bits_arg("bit-not", &args[0])?))
+}
+
+/// Shift left, discarding bits shifted past the sign bit
+/// Usage: (bit-shift-left 1 4) => 16
+pub fn bit_shift_left(args: &[Value], _env: &mut Environment) -> Result<Value, EvalError> {
+    check_arity_exact("bit-shift-left", args, 2)?;
+    let n = bits_arg("bit-shift-left", &args[0])?;
+    Ok(make_int(n << shift_arg("bit-shift-left", &args[1])?))
+}
+
+/// Arithmetic shift right, copying the sign bit
+/// Usage: (bit-shift-right -16 2) => -4
+pub fn bit_shift_right(args: &[Value], _env: &mut Environment) -> Result<Value, EvalError> {
+    check_arity_exact("bit-shift-right", args, 2)?;
+    let n = bits_arg("bit-shift-right", &args[0])?;
+    Ok(make_int(n >> shift_arg("bit-shift-right", &args[1])?))
+}
+
 // ============================================================================
 // Vector Constructor (de-sugared from << >> syntax)
 // ============================================================================
 
 /// Construct a fast vector from arguments
-pub fn vector(args: &[Value], _env: &mut Environment) -> Result<Value, String> {
+pub fn vector(args: &[Value], _env: &mut Environment) -> Result<Value, EvalError> {
     Ok(Value::Vector(Arc::new(VectorValue {
         elements: args.to_vec(),
     })))
@@ -712,61 +1357,66 @@ pub fn vector(args: &[Value], _env: &mut Environment) -> Result<Value, String> {
 /// Sequence abstraction - return a seq over a collection
 /// Usage: (%seq '(1 2 3)) => (1 2 3)
 /// Usage: (%seq <<1 2 3>>) => (1 2 3)
-pub fn builtin_seq(args: &[Value], _env: &mut Environment) -> Result<Value, String> {
+pub fn builtin_seq(args: &[Value], _env: &mut Environment) -> Result<Value, EvalError> {
     if args.len() != 1 {
-        return Err("%seq: expected 1 argument".to_string());
+        return Err("%seq: expected 1 argument".into());
     }
+    check_proper("%seq", &args[0])?;
     Ok(abstractions::seq(&args[0]).map_or(Value::Nil, |s| s.to_list()))
 }
 
 /// First element of a sequence
 /// Usage: (%first '(1 2 3)) => 1
-pub fn builtin_first(args: &[Value], _env: &mut Environment) -> Result<Value, String> {
+pub fn builtin_first(args: &[Value], _env: &mut Environment) -> Result<Value, EvalError> {
     if args.len() != 1 {
-        return Err("%first: expected 1 argument".to_string());
+        return Err("%first: expected 1 argument".into());
     }
     Ok(abstractions::first(&args[0]))
 }
 
 /// Next elements of a sequence (rest, but returns nil for empty)
 /// Usage: (%next '(1 2 3)) => (2 3)
-pub fn builtin_next(args: &[Value], _env: &mut Environment) -> Result<Value, String> {
+pub fn builtin_next(args: &[Value], _env: &mut Environment) -> Result<Value, EvalError> {
     if args.len() != 1 {
-        return Err("%next: expected 1 argument".to_string());
+        return Err("%next: expected 1 argument".into());
     }
+    check_proper("%next", &args[0])?;
     Ok(abstractions::next(&args[0]))
 }
 
 /// Rest of a sequence (like next but returns () for empty)
 /// Usage: (%rest '(1 2 3)) => (2 3)
-pub fn builtin_rest(args: &[Value], _env: &mut Environment) -> Result<Value, String> {
+pub fn builtin_rest(args: &[Value], _env: &mut Environment) -> Result<Value, EvalError> {
     if args.len() != 1 {
-        return Err("%rest: expected 1 argument".to_string());
+        return Err("%rest: expected 1 argument".into());
     }
+    check_proper("%rest", &args[0])?;
     Ok(abstractions::rest(&args[0]))
 }
 
 /// Count elements in a collection
 /// Usage: (%count '(1 2 3)) => 3
-pub fn builtin_count(args: &[Value], _env: &mut Environment) -> Result<Value, String> {
+pub fn builtin_count(args: &[Value], _env: &mut Environment) -> Result<Value, EvalError> {
     if args.len() != 1 {
-        return Err("%count: expected 1 argument".to_string());
+        return Err("%count: expected 1 argument".into());
     }
     abstractions::count(&args[0])
         .map(|n| Value::Atom(AtomType::Number(NumericType::Int(n as i64))))
-        .ok_or_else(|| format!("%count: cannot count {}", args[0]))
+        .ok_or_else(|| format!("%count: cannot count {}", args[0]).into())
 }
 
 /// Get nth element of a collection
 /// Usage: (%nth <<1 2 3>> 1) => 2
 /// Usage: (%nth <<1 2 3>> 5 :default) => :default
-pub fn builtin_nth(args: &[Value], _env: &mut Environment) -> Result<Value, String> {
+pub fn builtin_nth(args: &[Value], _env: &mut Environment) -> Result<Value, EvalError> {
     if args.len() < 2 || args.len() > 3 {
-        return Err("%nth: expected 2-3 arguments (coll, index, [default])".to_string());
+        return Err("%nth: expected 2-3 arguments (coll, index, [default])".into());
     }
     let index = match &args[1] {
         Value::Atom(AtomType::Number(NumericType::Int(n))) if *n >= 0 => *n as usize,
-        _ => return Err("%nth: index must be a non-negative integer".to_string()),
+        _ => {
+            return Err("%nth: index must be a non-negative integer".into());
+        }
     };
     let default = args.get(2);
     Ok(abstractions::nth(&args[0], index, default))
@@ -775,9 +1425,9 @@ pub fn builtin_nth(args: &[Value], _env: &mut Environment) -> Result<Value, Stri
 /// Get value by key from collection
 /// Usage: (%get {:a 1 :b 2} :a) => 1
 /// Usage: (%get <<1 2 3>> 0) => 1
-pub fn builtin_get(args: &[Value], _env: &mut Environment) -> Result<Value, String> {
+pub fn builtin_get(args: &[Value], _env: &mut Environment) -> Result<Value, EvalError> {
     if args.len() < 2 || args.len() > 3 {
-        return Err("%get: expected 2-3 arguments (coll, key, [default])".to_string());
+        return Err("%get: expected 2-3 arguments (coll, key, [default])".into());
     }
     let default = args.get(2);
     Ok(abstractions::get(&args[0], &args[1], default))
@@ -786,11 +1436,9 @@ pub fn builtin_get(args: &[Value], _env: &mut Environment) -> Result<Value, Stri
 /// Associate a key with a value in a collection
 /// Usage: (%assoc {:a 1} :b 2) => {:a 1 :b 2}
 /// Usage: (%assoc <<1 2 3>> 0 10) => <<10 2 3>>
-pub fn builtin_assoc(args: &[Value], _env: &mut Environment) -> Result<Value, String> {
+pub fn builtin_assoc(args: &[Value], _env: &mut Environment) -> Result<Value, EvalError> {
     if args.len() < 3 || args.len().is_multiple_of(2) {
-        return Err(
-            "%assoc: expected odd number of arguments >= 3 (coll, key, val, ...)".to_string(),
-        );
+        return Err("%assoc: expected odd number of arguments >= 3 (coll, key, val, ...)".into());
     }
     let mut result = args[0].clone();
     for chunk in args[1..].chunks(2) {
@@ -802,9 +1450,9 @@ pub fn builtin_assoc(args: &[Value], _env: &mut Environment) -> Result<Value, St
 /// Add item(s) to a collection
 /// Usage: (%conj '(2 3) 1) => (1 2 3)
 /// Usage: (%conj <<1 2>> 3) => <<1 2 3>>
-pub fn builtin_conj(args: &[Value], _env: &mut Environment) -> Result<Value, String> {
+pub fn builtin_conj(args: &[Value], _env: &mut Environment) -> Result<Value, EvalError> {
     if args.len() < 2 {
-        return Err("%conj: expected at least 2 arguments (coll, item, ...)".to_string());
+        return Err("%conj: expected at least 2 arguments (coll, item, ...)".into());
     }
     let mut result = args[0].clone();
     for item in &args[1..] {
@@ -815,18 +1463,18 @@ pub fn builtin_conj(args: &[Value], _env: &mut Environment) -> Result<Value, Str
 
 /// Wrap a value in Reduced for early termination
 /// Usage: (%reduced 42) => #reduced(42)
-pub fn builtin_reduced(args: &[Value], _env: &mut Environment) -> Result<Value, String> {
+pub fn builtin_reduced(args: &[Value], _env: &mut Environment) -> Result<Value, EvalError> {
     if args.len() != 1 {
-        return Err("%reduced: expected 1 argument".to_string());
+        return Err("%reduced: expected 1 argument".into());
     }
     Ok(abstractions::reduced(args[0].clone()))
 }
 
 /// Check if a value is reduced
 /// Usage: (%reduced? #reduced(42)) => t
-pub fn builtin_reduced_p(args: &[Value], _env: &mut Environment) -> Result<Value, String> {
+pub fn builtin_reduced_p(args: &[Value], _env: &mut Environment) -> Result<Value, EvalError> {
     if args.len() != 1 {
-        return Err("%reduced?: expected 1 argument".to_string());
+        return Err("%reduced?: expected 1 argument".into());
     }
     Ok(Value::Atom(AtomType::Bool(abstractions::is_reduced(
         &args[0],
@@ -835,18 +1483,21 @@ pub fn builtin_reduced_p(args: &[Value], _env: &mut Environment) -> Result<Value
 
 /// Unwrap a reduced value
 /// Usage: (%unreduced #reduced(42)) => 42
-pub fn builtin_unreduced(args: &[Value], _env: &mut Environment) -> Result<Value, String> {
+pub fn builtin_unreduced(args: &[Value], _env: &mut Environment) -> Result<Value, EvalError> {
     if args.len() != 1 {
-        return Err("%unreduced: expected 1 argument".to_string());
+        return Err("%unreduced: expected 1 argument".into());
     }
     Ok(abstractions::unreduced(&args[0]))
 }
 
 /// Create a hash map from key-value pairs
 /// Usage: (%hash-map :a 1 :b 2) => {:a 1, :b 2}
-pub fn builtin_hash_map(args: &[Value], _env: &mut Environment) -> Result<Value, String> {
+pub fn builtin_hash_map(args: &[Value], _env: &mut Environment) -> Result<Value, EvalError> {
     if !args.len().is_multiple_of(2) {
-        return Err("%hash-map: expected even number of arguments (key-value pairs)".to_string());
+        return Err("%hash-map: expected even number of arguments (key-value pairs)".into());
+    }
+    for key in args.iter().step_by(2) {
+        abstractions::check_hash_key(key).map_err(|e| format!("%hash-map: {e}"))?;
     }
     let pairs: Vec<(Value, Value)> = args
         .chunks(2)
@@ -857,16 +1508,19 @@ pub fn builtin_hash_map(args: &[Value], _env: &mut Environment) -> Result<Value,
 
 /// Create a hash set from elements
 /// Usage: (%hash-set 1 2 3) => #{1 2 3}
-pub fn builtin_hash_set(args: &[Value], _env: &mut Environment) -> Result<Value, String> {
+pub fn builtin_hash_set(args: &[Value], _env: &mut Environment) -> Result<Value, EvalError> {
+    for item in args {
+        abstractions::check_hash_key(item).map_err(|e| format!("%hash-set: {e}"))?;
+    }
     Ok(abstractions::hash_set(args.to_vec()))
 }
 
 /// Check if a value is empty
 /// Usage: (%empty? '()) => t
 /// Usage: (%empty? <<>>) => t
-pub fn builtin_empty_p(args: &[Value], _env: &mut Environment) -> Result<Value, String> {
+pub fn builtin_empty_p(args: &[Value], _env: &mut Environment) -> Result<Value, EvalError> {
     if args.len() != 1 {
-        return Err("%empty?: expected 1 argument".to_string());
+        return Err("%empty?: expected 1 argument".into());
     }
     let is_empty = match &args[0] {
         Value::Nil => true,
@@ -883,9 +1537,10 @@ pub fn builtin_empty_p(args: &[Value], _env: &mut Environment) -> Result<Value,
 /// Check if a value contains a key/element
 /// Usage: (%contains? {:a 1} :a) => t
 /// Usage: (%contains? #{1 2 3} 2) => t
-pub fn builtin_contains_p(args: &[Value], _env: &mut Environment) -> Result<Value, String> {
+/// Usage: (%contains? "hello" "ell") => t
+pub fn builtin_contains_p(args: &[Value], _env: &mut Environment) -> Result<Value, EvalError> {
     if args.len() != 2 {
-        return Err("%contains?: expected 2 arguments (coll, key)".to_string());
+        return Err("%contains?: expected 2 arguments (coll, key)".into());
     }
     let contains = match &args[0] {
         Value::Map(m) => m.entries.contains_key(&args[1]),
@@ -897,6 +1552,15 @@ pub fn builtin_contains_p(args: &[Value], _env: &mut Environment) -> Result<Valu
                 false
             }
         }
+        // Substrings are members; an integer is a valid char index
+        Value::Atom(AtomType::String(StringType::Basic(s))) => match &args[1] {
+            Value::Atom(AtomType::String(StringType::Basic(sub))) => s.contains(sub.as_str()),
+            Value::Atom(AtomType::Char(c)) => s.contains(*c),
+            Value::Atom(AtomType::Number(NumericType::Int(idx))) => {
+                *idx >= 0 && (*idx as usize) < s.chars().count()
+            }
+            _ => false,
+        },
         _ => false,
     };
     Ok(Value::Atom(AtomType::Bool(contains)))
@@ -904,9 +1568,9 @@ pub fn builtin_contains_p(args: &[Value], _env: &mut Environment) -> Result<Valu
 
 /// Get keys from a map
 /// Usage: (%keys {:a 1 :b 2}) => (:a :b)
-pub fn builtin_keys(args: &[Value], _env: &mut Environment) -> Result<Value, String> {
+pub fn builtin_keys(args: &[Value], _env: &mut Environment) -> Result<Value, EvalError> {
     if args.len() != 1 {
-        return Err("%keys: expected 1 argument".to_string());
+        return Err("%keys: expected 1 argument".into());
     }
     match &args[0] {
         Value::Map(m) => {
@@ -916,15 +1580,15 @@ pub fn builtin_keys(args: &[Value], _env: &mut Environment) -> Result<Value, Str
             }
             Ok(result)
         }
-        _ => Err(format!("%keys: expected map, got {}", args[0])),
+        _ => Err(format!("%keys: expected map, got {}", args[0]).into()),
     }
 }
 
 /// Get values from a map
 /// Usage: (%vals {:a 1 :b 2}) => (1 2)
-pub fn builtin_vals(args: &[Value], _env: &mut Environment) -> Result<Value, String> {
+pub fn builtin_vals(args: &[Value], _env: &mut Environment) -> Result<Value, EvalError> {
     if args.len() != 1 {
-        return Err("%vals: expected 1 argument".to_string());
+        return Err("%vals: expected 1 argument".into());
     }
     match &args[0] {
         Value::Map(m) => {
@@ -934,7 +1598,7 @@ pub fn builtin_vals(args: &[Value], _env: &mut Environment) -> Result<Value, Str
             }
             Ok(result)
         }
-        _ => Err(format!("%vals: expected map, got {}", args[0])),
+        _ => Err(format!("%vals: expected map, got {}", args[0]).into()),
     }
 }
 
@@ -942,9 +1606,9 @@ pub fn builtin_vals(args: &[Value], _env: &mut Environment) -> Result<Value, Str
 /// Usage: (%dissoc {:a 1 :b 2} :a) => {:b 2}
 /// Usage: (%disj #{1 2 3} 2) => #{1 3}
 #[allow(clippy::mutable_key_type)]
-pub fn builtin_dissoc(args: &[Value], _env: &mut Environment) -> Result<Value, String> {
+pub fn builtin_dissoc(args: &[Value], _env: &mut Environment) -> Result<Value, EvalError> {
     if args.len() < 2 {
-        return Err("%dissoc: expected at least 2 arguments (map, key, ...)".to_string());
+        return Err("%dissoc: expected at least 2 arguments (map, key, ...)".into());
     }
     match &args[0] {
         Value::Map(m) => {
@@ -954,16 +1618,23 @@ pub fn builtin_dissoc(args: &[Value], _env: &mut Environment) -> Result<Value, S
             }
             Ok(Value::Map(Arc::new(MapValue { entries })))
         }
-        _ => Err(format!("%dissoc: expected map, got {}", args[0])),
+        Value::PersistentMap(m) => {
+            let mut entries = m.entries.clone();
+            for key in &args[1..] {
+                entries.remove(key);
+            }
+            Ok(Value::PersistentMap(Arc::new(PersistentMap { entries })))
+        }
+        _ => Err(format!("%dissoc: expected map, got {}", args[0]).into()),
     }
 }
 
 /// Remove an element from a set
 /// Usage: (%disj #{1 2 3} 2) => #{1 3}
 #[allow(clippy::mutable_key_type)]
-pub fn builtin_disj(args: &[Value], _env: &mut Environment) -> Result<Value, String> {
+pub fn builtin_disj(args: &[Value], _env: &mut Environment) -> Result<Value, EvalError> {
     if args.len() < 2 {
-        return Err("%disj: expected at least 2 arguments (set, elem, ...)".to_string());
+        return Err("%disj: expected at least 2 arguments (set, elem, ...)".into());
     }
     match &args[0] {
         Value::Set(s) => {
@@ -973,97 +1644,2068 @@ pub fn builtin_disj(args: &[Value], _env: &mut Environment) -> Result<Value, Str
             }
             Ok(Value::Set(Arc::new(SetValue { elements })))
         }
-        _ => Err(format!("%disj: expected set, got {}", args[0])),
+        Value::PersistentSet(s) => {
+            let mut elements = s.elements.clone();
+            for elem in &args[1..] {
+                elements.remove(elem);
+            }
+            Ok(Value::PersistentSet(Arc::new(PersistentSet { elements })))
+        }
+        _ => Err(format!("%disj: expected set, got {}", args[0]).into()),
     }
 }
 
 // ============================================================================
-// Registration
+// Function Application
 // ============================================================================
 
-/// Register all standard library functions in the given environment
-pub fn register_stdlib(env: &mut Environment) {
-    // Standard I/O
-    env.define("print".to_string(), Value::NativeFn(print));
-    env.define("println".to_string(), Value::NativeFn(println));
+/// Call f with the elements of a list as its arguments, after any leading ones
+/// Usage: (apply + '(1 2 3)) => 6
+/// Usage: (apply list 1 2 '(3 4)) => (1 2 3 4)
+pub fn builtin_apply(args: &[Value], env: &mut Environment) -> Result<Value, EvalError> {
+    check_arity_min("apply", args, 2)?;
+    let (func, rest) = args.split_first().expect("arity checked");
+    let (spread, leading) = rest.split_last().expect("arity checked");
+    if !matches!(
+        func,
+        Value::Lambda(_) | Value::NativeFn(_) | Value::NativeClosure(_)
+    ) {
+        return Err(EvalError::TypeError {
+            callee: Some("apply".to_string()),
+            expected: "a function".to_string(),
+            found: func.clone(),
+        });
+    }
+    let mut call_args = leading.to_vec();
+    call_args.extend(list_to_vec(spread).map_err(|_| EvalError::TypeError {
+        callee: Some("apply".to_string()),
+        expected: "a list as the last argument".to_string(),
+        found: spread.clone(),
+    })?);
+    apply(func, &call_args, env)
+}
 
-    // File I/O
-    env.define("slurp".to_string(), Value::NativeFn(slurp));
-    env.define("spit".to_string(), Value::NativeFn(spit));
+// ============================================================================
+// Refs
+// ============================================================================
 
-    // Process execution
-    env.define("shell".to_string(), Value::NativeFn(shell));
+/// The ref argument of a ref function
+fn ref_arg<'a>(fn_name: &str, value: &'a Value) -> Result<&'a Arc<RefValue>, EvalError> {
+    match value {
+        Value::Ref(r) => Ok(r),
+        _ => Err(EvalError::TypeError {
+            callee: Some(fn_name.to_string()),
+            expected: "a ref".to_string(),
+            found: value.clone(),
+        }),
+    }
+}
 
-    // Time
-    env.define("now".to_string(), Value::NativeFn(now));
+/// A new ref holding init
+/// Usage: (ref 0) => #ref[0]
+pub fn builtin_ref(args: &[Value], _env: &mut Environment) -> Result<Value, EvalError> {
+    check_arity_exact("ref", args, 1)?;
+    Ok(Value::Ref(Arc::new(RefValue::new(args[0].clone()))))
+}
 
-    // Macro support
-    env.define("gensym".to_string(), Value::NativeFn(gensym));
-    env.define("macroexpand-1".to_string(), Value::NativeFn(macroexpand_1));
-    env.define("macroexpand".to_string(), Value::NativeFn(macroexpand));
+/// The value a ref holds; `@r` reads as `(deref r)`
+/// Usage: (deref (ref 1)) => 1
+pub fn deref(args: &[Value], _env: &mut Environment) -> Result<Value, EvalError> {
+    check_arity_exact("deref", args, 1)?;
+    Ok(ref_arg("deref", &args[0])?.get())
+}
 
-    // List operations (de-sugaring special forms)
-    env.define("atom".to_string(), Value::NativeFn(atom));
-    env.define("eq".to_string(), Value::NativeFn(eq));
-    env.define("car".to_string(), Value::NativeFn(car));
-    env.define("cdr".to_string(), Value::NativeFn(cdr));
-    env.define("cons".to_string(), Value::NativeFn(cons_fn));
+/// Replace the value a ref holds, returning the new value
+/// Usage: (ref-set! r 5) => 5
+pub fn ref_set(args: &[Value], _env: &mut Environment) -> Result<Value, EvalError> {
+    check_arity_exact("ref-set!", args, 2)?;
+    ref_arg("ref-set!", &args[0])?.set(args[1].clone());
+    Ok(args[1].clone())
+}
 
-    // Type predicates (for JIT/AOT parity)
-    env.define("nil?".to_string(), Value::NativeFn(nil_p));
-    env.define("cons?".to_string(), Value::NativeFn(cons_p));
-    env.define("number?".to_string(), Value::NativeFn(number_p));
-    env.define("not".to_string(), Value::NativeFn(not_fn));
+/// Replace the value of a ref with f applied to it and any further
+/// arguments, returning the new value. If the ref changes while f runs, f is
+/// applied again to the newer value.
+/// Usage: (swap! counter + 1) => 1
+pub fn swap(args: &[Value], env: &mut Environment) -> Result<Value, EvalError> {
+    check_arity_min("swap!", args, 2)?;
+    let r = ref_arg("swap!", &args[0])?;
+    loop {
+        let (old, version) = r.snapshot();
+        let mut call_args = vec![old];
+        call_args.extend_from_slice(&args[2..]);
+        let new = apply(&args[1], &call_args, env)?;
+        if r.compare_and_set(version, new.clone()) {
+            return Ok(new);
+        }
+    }
+}
 
-    // List operations (for JIT/AOT parity)
-    env.define("length".to_string(), Value::NativeFn(length));
-    env.define("append".to_string(), Value::NativeFn(append));
-    env.define("reverse".to_string(), Value::NativeFn(reverse));
-    env.define("list".to_string(), Value::NativeFn(list));
-    env.define("nth".to_string(), Value::NativeFn(nth));
+// ============================================================================
+// Sequence Functions
+// ============================================================================
 
-    // Vector operations (for JIT/AOT parity)
-    env.define("vector-length".to_string(), Value::NativeFn(vector_length));
-    env.define("vector-ref".to_string(), Value::NativeFn(vector_ref));
+/// Collect the elements of a seqable argument, prefixing errors with the function name
+fn seq_arg(fn_name: &str, value: &Value) -> Result<Vec<Value>, String> {
+    seq_to_vec(value).map_err(|e| format!("{fn_name}: {e}"))
+}
 
-    // Arithmetic operations (de-sugaring special forms)
-    env.define("+".to_string(), Value::NativeFn(add));
-    env.define("-".to_string(), Value::NativeFn(sub));
-    env.define("*".to_string(), Value::NativeFn(mul));
-    env.define("/".to_string(), Value::NativeFn(div));
+/// Apply f to each element, collecting the results in a list
+/// Usage: (map (lambda (x) (* x x)) <<1 2 3>>) => (1 4 9)
+pub fn map(args: &[Value], env: &mut Environment) -> Result<Value, EvalError> {
+    check_arity_exact("map", args, 2)?;
+    let mut results = Vec::new();
+    for item in seq_arg("map", &args[1])? {
+        results.push(apply(&args[0], &[item], env)?);
+    }
+    Ok(vec_to_list(results))
+}
 
-    // Comparison operations (de-sugaring special forms)
-    env.define("<".to_string(), Value::NativeFn(lt));
-    env.define(">".to_string(), Value::NativeFn(gt));
-    env.define("<=".to_string(), Value::NativeFn(lte));
-    env.define(">=".to_string(), Value::NativeFn(gte));
-    env.define("=".to_string(), Value::NativeFn(num_eq));
+/// Stack size of the threads `par-map` starts: that of a main thread, since
+/// evaluation recurses as deeply there
+const PAR_MAP_STACK_SIZE: usize = 8 * 1024 * 1024;
+
+/// `map` with the elements split into contiguous chunks, one per available
+/// core, each mapped on its own thread. The environment is frozen while they
+/// run, so f cannot change bindings; if f fails, the error is that of the
+/// first element it failed on. Each thread prints floats and collections as
+/// the caller does.
+/// Usage: (par-map (lambda (x) (* x x)) <<1 2 3>>) => (1 4 9)
+pub fn par_map(args: &[Value], env: &mut Environment) -> Result<Value, EvalError> {
+    check_arity_exact("par-map", args, 2)?;
+    let items = seq_arg("par-map", &args[1])?;
+    if items.is_empty() {
+        return Ok(Value::Nil);
+    }
+    let threads = thread::available_parallelism()
+        .map_or(1, |n| n.get())
+        .min(items.len());
+    let chunk_size = items.len().div_ceil(threads);
+
+    let _freeze = env.freeze();
+    let f = &args[0];
+    // Print settings are per thread, so each worker takes the caller's
+    let float_digits = print_float_digits();
+    let limits = language::print_limits();
+    let chunks: Vec<Result<Vec<Value>, EvalError>> =
+        thread::scope(|scope| -> Result<_, EvalError> {
+            // Threads already started are joined when the scope ends, even if a
+            // later one could not be
+            let mut handles = Vec::new();
+            for chunk in items.chunks(chunk_size) {
+                let mut env = env.clone();
+                let handle = thread::Builder::new()
+                    .stack_size(PAR_MAP_STACK_SIZE)
+                    .spawn_scoped(scope, move || {
+                        set_print_float_digits(float_digits);
+                        language::set_print_limits(limits);
+                        chunk
+                            .iter()
+                            .map(|item| apply(f, std::slice::from_ref(item), &mut env))
+                            .collect()
+                    })
+                    .map_err(|e| format!("par-map: could not start a thread: {e}"))?;
+                handles.push(handle);
+            }
+            Ok(handles
+                .into_iter()
+                .map(|handle| {
+                    handle
+                        .join()
+                        .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+                })
+                .collect())
+        })?;
+
+    let mut results = Vec::with_capacity(items.len());
+    for chunk in chunks {
+        results.extend(chunk?);
+    }
+    Ok(vec_to_list(results))
+}
 
-    // Vector constructor (de-sugaring vector syntax)
-    env.define("vector".to_string(), Value::NativeFn(vector));
+/// The elements for which pred is truthy, in order, as a list
+/// Usage: (filter (lambda (x) (> x 1)) '(1 2 3)) => (2 3)
+pub fn filter(args: &[Value], env: &mut Environment) -> Result<Value, EvalError> {
+    check_arity_exact("filter", args, 2)?;
+    let mut results = Vec::new();
+    for item in seq_arg("filter", &args[1])? {
+        if is_truthy(&apply(&args[0], std::slice::from_ref(&item), env)?) {
+            results.push(item);
+        }
+    }
+    Ok(vec_to_list(results))
+}
 
-    // Engine abstractions (Clojure-inspired)
-    env.define("%seq".to_string(), Value::NativeFn(builtin_seq));
-    env.define("%first".to_string(), Value::NativeFn(builtin_first));
-    env.define("%next".to_string(), Value::NativeFn(builtin_next));
-    env.define("%rest".to_string(), Value::NativeFn(builtin_rest));
-    env.define("%count".to_string(), Value::NativeFn(builtin_count));
-    env.define("%nth".to_string(), Value::NativeFn(builtin_nth));
-    env.define("%get".to_string(), Value::NativeFn(builtin_get));
-    env.define("%assoc".to_string(), Value::NativeFn(builtin_assoc));
-    env.define("%conj".to_string(), Value::NativeFn(builtin_conj));
-    env.define("%reduced".to_string(), Value::NativeFn(builtin_reduced));
-    env.define("%reduced?".to_string(), Value::NativeFn(builtin_reduced_p));
-    env.define("%unreduced".to_string(), Value::NativeFn(builtin_unreduced));
-    env.define("%hash-map".to_string(), Value::NativeFn(builtin_hash_map));
-    env.define("%hash-set".to_string(), Value::NativeFn(builtin_hash_set));
-    env.define("%empty?".to_string(), Value::NativeFn(builtin_empty_p));
-    env.define(
-        "%contains?".to_string(),
-        Value::NativeFn(builtin_contains_p),
-    );
-    env.define("%keys".to_string(), Value::NativeFn(builtin_keys));
-    env.define("%vals".to_string(), Value::NativeFn(builtin_vals));
-    env.define("%dissoc".to_string(), Value::NativeFn(builtin_dissoc));
-    env.define("%disj".to_string(), Value::NativeFn(builtin_disj));
+/// Combine the elements with f from left to right, starting from init or
+/// the first element. f can stop early by returning (%reduced acc).
+/// Usage: (reduce + '(1 2 3)) => 6
+/// Usage: (reduce + 10 '(1 2 3)) => 16
+pub fn reduce(args: &[Value], env: &mut Environment) -> Result<Value, EvalError> {
+    check_arity_range("reduce", args, 2, 3)?;
+    let f = &args[0];
+    let (init, coll) = match args {
+        [_, init, coll] => (Some(init.clone()), coll),
+        _ => (None, &args[1]),
+    };
+    let mut items = seq_arg("reduce", coll)?.into_iter();
+    let mut acc = match init.or_else(|| items.next()) {
+        Some(acc) => acc,
+        // With no init, an empty collection reduces to (f)
+        None => return apply(f, &[], env),
+    };
+    for item in items {
+        acc = apply(f, &[acc, item], env)?;
+        if abstractions::is_reduced(&acc) {
+            return Ok(abstractions::unreduced(&acc));
+        }
+    }
+    Ok(acc)
+}
+
+/// Group consecutive elements for which f returns equal values
+/// Usage: (partition-by odd? '(1 3 2 4 5)) => ((1 3) (2 4) (5))
+pub fn partition_by(args: &[Value], env: &mut Environment) -> Result<Value, EvalError> {
+    if args.len() != 2 {
+        return Err("partition-by: expected 2 arguments (f, coll)".into());
+    }
+    let mut groups = Vec::new();
+    let mut group = Vec::new();
+    let mut group_key = None;
+    for item in seq_arg("partition-by", &args[1])? {
+        let key = apply(&args[0], std::slice::from_ref(&item), env)?;
+        if group_key.as_ref().is_some_and(|k| *k != key) {
+            groups.push(vec_to_list(std::mem::take(&mut group)));
+        }
+        group_key = Some(key);
+        group.push(item);
+    }
+    if !group.is_empty() {
+        groups.push(vec_to_list(group));
+    }
+    Ok(vec_to_list(groups))
+}
+
+/// Split a collection where pred first fails: (list (take-while ...) (drop-while ...))
+/// Usage: (split-with neg? '(-1 -2 3 -4)) => ((-1 -2) (3 -4))
+pub fn split_with(args: &[Value], env: &mut Environment) -> Result<Value, EvalError> {
+    if args.len() != 2 {
+        return Err("split-with: expected 2 arguments (pred, coll)".into());
+    }
+    let mut items = seq_arg("split-with", &args[1])?;
+    let mut split = items.len();
+    for (i, item) in items.iter().enumerate() {
+        if !is_truthy(&apply(&args[0], std::slice::from_ref(item), env)?) {
+            split = i;
+            break;
+        }
+    }
+    let dropped = items.split_off(split);
+    Ok(vec_to_list(vec![vec_to_list(items), vec_to_list(dropped)]))
+}
+
+/// An integer argument such as a count or a range bound
+fn int_arg(fn_name: &str, value: &Value) -> Result<i64, EvalError> {
+    match value {
+        Value::Atom(AtomType::Number(NumericType::Int(n))) => Ok(*n),
+        other => Err(EvalError::TypeError {
+            callee: Some(fn_name.to_string()),
+            expected: "an integer".to_string(),
+            found: other.clone(),
+        }),
+    }
+}
+
+/// Fail for an improper list such as `(1 . 2)`, whose last element a seq
+/// would otherwise silently drop.
+fn check_proper(fn_name: &str, coll: &Value) -> Result<(), String> {
+    match abstractions::improper_tail(coll) {
+        Some(_) => Err(format!("{fn_name}: expected a proper list, got {coll}")),
+        None => Ok(()),
+    }
+}
+
+/// The elements of a collection one at a time, without copying it first
+fn seq_walk(fn_name: &str, coll: &Value) -> Result<impl Iterator<Item = Value>, String> {
+    match coll {
+        Value::Nil
+        | Value::Cons(_)
+        | Value::Vector(_)
+        | Value::PersistentVector(_)
+        | Value::Map(_)
+        | Value::PersistentMap(_)
+        | Value::Set(_)
+        | Value::PersistentSet(_)
+        | Value::Atom(AtomType::String(_)) => {}
+        _ => return Err(format!("{fn_name}: Expected a collection, got {coll}")),
+    }
+    check_proper(fn_name, coll)?;
+    let mut current = abstractions::seq(coll);
+    Ok(std::iter::from_fn(move || {
+        let seq = current.take()?;
+        let item = seq.first();
+        current = seq.next();
+        Some(item)
+    }))
+}
+
+/// A list of integers from start (default 0) up to but not including end
+/// Usage: (range 5) => (0 1 2 3 4)
+/// Usage: (range 0 10 3) => (0 3 6 9)
+/// Usage: (range 3 0 -1) => (3 2 1)
+pub fn range(args: &[Value], _env: &mut Environment) -> Result<Value, EvalError> {
+    check_arity_range("range", args, 1, 3)?;
+    let (start, end) = match args {
+        [end] => (0, int_arg("range", end)?),
+        [start, end, ..] => (int_arg("range", start)?, int_arg("range", end)?),
+        [] => unreachable!(),
+    };
+    let step = match args.get(2) {
+        Some(step) => int_arg("range", step)?,
+        None => 1,
+    };
+    if step == 0 {
+        return Err("range: step must not be zero".into());
+    }
+    let mut items = Vec::new();
+    let mut n = start;
+    while (step > 0 && n < end) || (step < 0 && n > end) {
+        items.push(make_int(n));
+        match n.checked_add(step) {
+            Some(next) => n = next,
+            None => break,
+        }
+    }
+    Ok(vec_to_list(items))
+}
+
+/// The first n elements of a collection, or all of them if there are fewer
+/// Usage: (take 2 <<1 2 3>>) => (1 2)
+pub fn take(args: &[Value], _env: &mut Environment) -> Result<Value, EvalError> {
+    check_arity_exact("take", args, 2)?;
+    let n = int_arg("take", &args[0])?.max(0) as usize;
+    Ok(vec_to_list(seq_walk("take", &args[1])?.take(n).collect()))
+}
+
+/// Everything after the first n elements of a collection
+/// Usage: (drop 2 <<1 2 3>>) => (3)
+pub fn drop(args: &[Value], _env: &mut Environment) -> Result<Value, EvalError> {
+    check_arity_exact("drop", args, 2)?;
+    let n = int_arg("drop", &args[0])?.max(0) as usize;
+    Ok(vec_to_list(seq_walk("drop", &args[1])?.skip(n).collect()))
+}
+
+/// The longest prefix of a collection whose elements all satisfy pred
+/// Usage: (take-while (lambda (x) (< x 3)) '(1 2 3 1)) => (1 2)
+pub fn take_while(args: &[Value], env: &mut Environment) -> Result<Value, EvalError> {
+    check_arity_exact("take-while", args, 2)?;
+    let mut taken = Vec::new();
+    for item in seq_walk("take-while", &args[1])? {
+        if !is_truthy(&apply(&args[0], std::slice::from_ref(&item), env)?) {
+            break;
+        }
+        taken.push(item);
+    }
+    Ok(vec_to_list(taken))
+}
+
+/// A collection from the first element that fails pred onwards
+/// Usage: (drop-while (lambda (x) (< x 3)) '(1 2 3 1)) => (3 1)
+pub fn drop_while(args: &[Value], env: &mut Environment) -> Result<Value, EvalError> {
+    check_arity_exact("drop-while", args, 2)?;
+    let mut items = seq_walk("drop-while", &args[1])?;
+    for item in items.by_ref() {
+        if !is_truthy(&apply(&args[0], std::slice::from_ref(&item), env)?) {
+            let rest = std::iter::once(item).chain(items).collect();
+            return Ok(vec_to_list(rest));
+        }
+    }
+    Ok(Value::Nil)
+}
+
+/// The last element of a collection, or nil if it is empty
+/// Usage: (last '(1 2 3)) => 3
+pub fn last(args: &[Value], _env: &mut Environment) -> Result<Value, EvalError> {
+    check_arity_exact("last", args, 1)?;
+    Ok(seq_walk("last", &args[0])?.last().unwrap_or(Value::Nil))
+}
+
+/// Every element of a collection but the last
+/// Usage: (butlast '(1 2 3)) => (1 2)
+pub fn butlast(args: &[Value], _env: &mut Environment) -> Result<Value, EvalError> {
+    check_arity_exact("butlast", args, 1)?;
+    let mut items: Vec<Value> = seq_walk("butlast", &args[0])?.collect();
+    items.pop();
+    Ok(vec_to_list(items))
+}
+
+/// Remove duplicates, keeping the first occurrence of each element
+/// Vectors stay vectors.
+/// Usage: (distinct '(1 2 1 3 2)) => (1 2 3)
+#[allow(clippy::mutable_key_type)]
+pub fn distinct(args: &[Value], _env: &mut Environment) -> Result<Value, EvalError> {
+    if args.len() != 1 {
+        return Err("distinct: expected 1 argument".into());
+    }
+    let mut seen = HashSet::new();
+    let items = seq_arg("distinct", &args[0])?
+        .into_iter()
+        .filter(|item| seen.insert(item.clone()))
+        .collect();
+    Ok(abstractions::rebuild_like(&args[0], items))
+}
+
+/// Remove consecutive duplicates. Vectors stay vectors.
+/// Usage: (dedupe '(1 1 2 1 1)) => (1 2 1)
+pub fn dedupe(args: &[Value], _env: &mut Environment) -> Result<Value, EvalError> {
+    if args.len() != 1 {
+        return Err("dedupe: expected 1 argument".into());
+    }
+    let mut items = seq_arg("dedupe", &args[0])?;
+    items.dedup();
+    Ok(abstractions::rebuild_like(&args[0], items))
+}
+
+/// Flatten nested lists and vectors into a single list
+/// Maps, sets and strings are treated as single elements. The walk uses an
+/// explicit stack, so arbitrarily deep nesting does not overflow.
+/// Usage: (flatten '(1 (2 <<3 (4)>>) 5)) => (1 2 3 4 5)
+pub fn flatten(args: &[Value], _env: &mut Environment) -> Result<Value, EvalError> {
+    if args.len() != 1 {
+        return Err("flatten: expected 1 argument".into());
+    }
+    let mut result = Vec::new();
+    let mut stack = vec![seq_arg("flatten", &args[0])?.into_iter()];
+    while let Some(top) = stack.last_mut() {
+        match top.next() {
+            Some(item @ (Value::Cons(_) | Value::Vector(_) | Value::PersistentVector(_))) => {
+                stack.push(seq_arg("flatten", &item)?.into_iter());
+            }
+            Some(item) => result.push(item),
+            None => {
+                stack.pop();
+            }
+        }
+    }
+    Ok(vec_to_list(result))
+}
+
+/// Convert any seqable to a vector. Vectors are returned unchanged.
+/// Usage: (vec '(1 2 3)) => <<1 2 3>>
+pub fn vec(args: &[Value], _env: &mut Environment) -> Result<Value, EvalError> {
+    if args.len() != 1 {
+        return Err("vec: expected 1 argument".into());
+    }
+    match &args[0] {
+        Value::Vector(_) => Ok(args[0].clone()),
+        coll => Ok(Value::Vector(Arc::new(VectorValue {
+            elements: seq_arg("vec", coll)?,
+        }))),
+    }
+}
+
+/// The persistent counterpart of a collection: maps and sets keep their
+/// kind, anything else seqable becomes a persistent vector. Persistent
+/// collections share structure, so conj and assoc take O(log n).
+/// Usage: (persistent! <<1 2 3>>) => #pvec[1 2 3]
+pub fn persistent(args: &[Value], _env: &mut Environment) -> Result<Value, EvalError> {
+    check_arity_exact("persistent!", args, 1)?;
+    Ok(match &args[0] {
+        Value::PersistentVector(_) | Value::PersistentMap(_) | Value::PersistentSet(_) => {
+            args[0].clone()
+        }
+        Value::Map(map) => Value::PersistentMap(Arc::new(PersistentMap {
+            entries: map.entries.clone().into_iter().collect(),
+        })),
+        Value::Set(set) => Value::PersistentSet(Arc::new(PersistentSet {
+            elements: set.elements.iter().cloned().collect(),
+        })),
+        coll => Value::PersistentVector(Arc::new(PersistentVector {
+            elements: seq_arg("persistent!", coll)?.into_iter().collect(),
+        })),
+    })
+}
+
+/// The fast counterpart of a persistent collection. Other values are
+/// returned unchanged.
+/// Usage: (transient (persistent! <<1 2 3>>)) => <<1 2 3>>
+#[allow(clippy::mutable_key_type)]
+pub fn transient(args: &[Value], _env: &mut Environment) -> Result<Value, EvalError> {
+    check_arity_exact("transient", args, 1)?;
+    Ok(match &args[0] {
+        Value::PersistentVector(vec) => Value::Vector(Arc::new(VectorValue {
+            elements: vec.elements.iter().cloned().collect(),
+        })),
+        Value::PersistentMap(map) => Value::Map(Arc::new(MapValue {
+            entries: map.entries.clone().into_iter().collect(),
+        })),
+        Value::PersistentSet(set) => Value::Set(Arc::new(SetValue {
+            elements: set.elements.iter().cloned().collect(),
+        })),
+        other => other.clone(),
+    })
+}
+
+/// Add every element of from to to, using conj semantics
+/// Usage: (into <<1>> '(2 3)) => <<1 2 3>>
+/// Usage: (into '(1) '(2 3)) => (3 2 1)
+pub fn into(args: &[Value], _env: &mut Environment) -> Result<Value, EvalError> {
+    if args.len() != 2 {
+        return Err("into: expected 2 arguments (to, from)".into());
+    }
+    let items = seq_arg("into", &args[1])?;
+    if let Value::Vector(vec) = &args[0] {
+        let mut elements = vec.elements.clone();
+        elements.extend(items);
+        return Ok(Value::Vector(Arc::new(VectorValue { elements })));
+    }
+    items
+        .into_iter()
+        .try_fold(args[0].clone(), |acc, item| abstractions::conj(&acc, item))
+        .map_err(|e| format!("into: {e}").into())
+}
+
+/// Sort a collection, stably. Vectors stay vectors.
+/// Without a predicate, numbers, strings and symbols sort in their natural order.
+/// Usage: (sort '(3 1 2)) => (1 2 3)
+/// Usage: (sort (lambda (a b) (> a b)) <<3 1 2>>) => <<3 2 1>>
+pub fn sort(args: &[Value], env: &mut Environment) -> Result<Value, EvalError> {
+    let (less, coll) = match args {
+        [coll] => (None, coll),
+        [less, coll] => (Some(less), coll),
+        _ => {
+            return Err("sort: expected 1 or 2 arguments ([less?] coll)".into());
+        }
+    };
+    let items = seq_arg("sort", coll)?;
+    let sorted = merge_sort(items, &mut |a, b| sort_less("sort", less, a, b, env))?;
+    Ok(abstractions::rebuild_like(coll, sorted))
+}
+
+/// Sort a collection stably by the result of key on each element, calling
+/// key once per element. Keys are ordered naturally or by less?.
+/// Usage: (sort-by car '((2 b) (1 a))) => ((1 a) (2 b))
+/// Usage: (sort-by str-length (lambda (a b) (> a b)) <<"a" "abc">>) => <<"abc" "a">>
+pub fn sort_by(args: &[Value], env: &mut Environment) -> Result<Value, EvalError> {
+    let (key, less, coll) = match args {
+        [key, coll] => (key, None, coll),
+        [key, less, coll] => (key, Some(less), coll),
+        _ => {
+            return Err("sort-by: expected 2 or 3 arguments (key [less?] coll)".into());
+        }
+    };
+    let mut keyed = Vec::new();
+    for item in seq_arg("sort-by", coll)? {
+        keyed.push((apply(key, std::slice::from_ref(&item), env)?, item));
+    }
+    let sorted = merge_sort(keyed, &mut |(a, _), (b, _)| {
+        sort_less("sort-by", less, a, b, env)
+    })?;
+    let items = sorted.into_iter().map(|(_, item)| item).collect();
+    Ok(abstractions::rebuild_like(coll, items))
+}
+
+/// Whether a sorts before b, by a user predicate or in natural order
+fn sort_less(
+    fn_name: &str,
+    less: Option<&Value>,
+    a: &Value,
+    b: &Value,
+    env: &mut Environment,
+) -> Result<bool, EvalError> {
+    match less {
+        Some(f) => Ok(is_truthy(&apply(f, &[a.clone(), b.clone()], env)?)),
+        None => natural_less(fn_name, a, b).map_err(EvalError::from),
+    }
+}
+
+/// Natural ordering used by sort: numbers, strings and symbols with their own kind
+fn natural_less(fn_name: &str, a: &Value, b: &Value) -> Result<bool, String> {
+    match (a, b) {
+        // NaN sorts after every other number, giving numbers a total order
+        (Value::Atom(AtomType::Number(x)), Value::Atom(AtomType::Number(y))) => {
+            Ok(!x.is_nan() && (y.is_nan() || x < y))
+        }
+        (
+            Value::Atom(AtomType::String(StringType::Basic(x))),
+            Value::Atom(AtomType::String(StringType::Basic(y))),
+        ) => Ok(x < y),
+        (
+            Value::Atom(AtomType::Symbol(SymbolType::Symbol(x))),
+            Value::Atom(AtomType::Symbol(SymbolType::Symbol(y))),
+        ) => Ok(x.resolve() < y.resolve()),
+        _ => Err(format!("{fn_name}: cannot compare {a} and {b}")),
+    }
+}
+
+/// Stable bottom-up merge sort with a fallible comparison.
+/// Unlike slice::sort_by, an inconsistent user predicate cannot cause a panic,
+/// and the first error the comparison returns stops the sort.
+fn merge_sort<T: Clone, E>(
+    mut items: Vec<T>,
+    less: &mut dyn FnMut(&T, &T) -> Result<bool, E>,
+) -> Result<Vec<T>, E> {
+    let mut width = 1;
+    while width < items.len() {
+        let mut merged = Vec::with_capacity(items.len());
+        let mut rest = items.as_slice();
+        while !rest.is_empty() {
+            let (left, tail) = rest.split_at(width.min(rest.len()));
+            let (right, tail) = tail.split_at(width.min(tail.len()));
+            let (mut i, mut j) = (0, 0);
+            while i < left.len() && j < right.len() {
+                // Take from the right only when strictly less, keeping the sort stable
+                if less(&right[j], &left[i])? {
+                    merged.push(right[j].clone());
+                    j += 1;
+                } else {
+                    merged.push(left[i].clone());
+                    i += 1;
+                }
+            }
+            merged.extend_from_slice(&left[i..]);
+            merged.extend_from_slice(&right[j..]);
+            rest = tail;
+        }
+        items = merged;
+        width *= 2;
+    }
+    Ok(items)
+}
+
+/// First truthy result of pred, or nil. Stops at the first truthy result.
+/// Note this returns pred's result, not the element (as in Clojure).
+/// Usage: (some (lambda (x) (cond ((> x 2) (* x 10)))) '(1 3 5)) => 30
+pub fn some(args: &[Value], env: &mut Environment) -> Result<Value, EvalError> {
+    first_truthy("some", args, env)
+}
+
+/// Shared implementation of some/not-any?
+fn first_truthy(fn_name: &str, args: &[Value], env: &mut Environment) -> Result<Value, EvalError> {
+    if args.len() != 2 {
+        return Err(format!("{fn_name}: expected 2 arguments (pred, coll)").into());
+    }
+    for item in seq_arg(fn_name, &args[1])? {
+        let result = apply(&args[0], &[item], env)?;
+        if is_truthy(&result) {
+            return Ok(result);
+        }
+    }
+    Ok(Value::Nil)
+}
+
+/// True if pred holds for every element. Stops at the first failure.
+/// Usage: (every? (lambda (x) (> x 0)) '(1 2 3)) => t
+pub fn every_p(args: &[Value], env: &mut Environment) -> Result<Value, EvalError> {
+    if args.len() != 2 {
+        return Err("every?: expected 2 arguments (pred, coll)".into());
+    }
+    for item in seq_arg("every?", &args[1])? {
+        if !is_truthy(&apply(&args[0], &[item], env)?) {
+            return Ok(Value::Atom(AtomType::Bool(false)));
+        }
+    }
+    Ok(Value::Atom(AtomType::Bool(true)))
+}
+
+/// True if pred holds for no element. Stops at the first match.
+/// Usage: (not-any? (lambda (x) (< x 0)) '(1 2 3)) => t
+pub fn not_any_p(args: &[Value], env: &mut Environment) -> Result<Value, EvalError> {
+    let found = first_truthy("not-any?", args, env)?;
+    Ok(Value::Atom(AtomType::Bool(!is_truthy(&found))))
+}
+
+/// Apply f to each element, dropping nil results
+/// Usage: (keep (lambda (x) (cond ((> x 1) (* x x)))) '(1 2 3)) => (4 9)
+pub fn keep(args: &[Value], env: &mut Environment) -> Result<Value, EvalError> {
+    if args.len() != 2 {
+        return Err("keep: expected 2 arguments (f, coll)".into());
+    }
+    let mut result = Vec::new();
+    for item in seq_arg("keep", &args[1])? {
+        match apply(&args[0], &[item], env)? {
+            Value::Nil => {}
+            value => result.push(value),
+        }
+    }
+    Ok(vec_to_list(result))
+}
+
+/// The element of coll for which (f x) is greatest, or nil if coll is empty
+/// Usage: (max-key length '((1) (1 2 3) (1 2))) => (1 2 3)
+pub fn max_key(args: &[Value], env: &mut Environment) -> Result<Value, EvalError> {
+    extreme_key("max-key", args, env, std::cmp::Ordering::Greater)
+}
+
+/// The element of coll for which (f x) is least, or nil if coll is empty
+/// Usage: (min-key length '((1) (1 2 3) (1 2))) => (1)
+pub fn min_key(args: &[Value], env: &mut Environment) -> Result<Value, EvalError> {
+    extreme_key("min-key", args, env, std::cmp::Ordering::Less)
+}
+
+/// Shared implementation of max-key/min-key. Ties keep the earliest element.
+fn extreme_key(
+    fn_name: &str,
+    args: &[Value],
+    env: &mut Environment,
+    wanted: std::cmp::Ordering,
+) -> Result<Value, EvalError> {
+    if args.len() != 2 {
+        return Err(format!("{fn_name}: expected 2 arguments (f, coll)").into());
+    }
+    let mut best: Option<(Value, NumericType)> = None;
+    for item in seq_arg(fn_name, &args[1])? {
+        let key = match apply(&args[0], std::slice::from_ref(&item), env)? {
+            Value::Atom(AtomType::Number(n)) => n,
+            other => return Err(format!("{fn_name}: f must return a number, got {other}").into()),
+        };
+        let better = match &best {
+            None => true,
+            Some((_, best_key)) => key.partial_cmp(best_key) == Some(wanted),
+        };
+        if better {
+            best = Some((item, key));
+        }
+    }
+    Ok(best.map_or(Value::Nil, |(item, _)| item))
+}
+
+// ============================================================================
+// Association Lists
+// ============================================================================
+
+/// The pairs of an association list argument
+fn alist_arg(fn_name: &str, value: &Value) -> Result<Vec<(Value, Value)>, EvalError> {
+    abstractions::alist_pairs(value).ok_or_else(|| EvalError::TypeError {
+        callee: Some(fn_name.to_string()),
+        expected: "an association list".to_string(),
+        found: value.clone(),
+    })
+}
+
+/// Value paired with key in an association list, or default if it is missing
+/// Usage: (alist-get (shell "echo hi") 'out) => "hi\n"
+/// Usage: (alist-get (list (cons 'a 1)) 'b 0) => 0
+pub fn alist_get(args: &[Value], _env: &mut Environment) -> Result<Value, EvalError> {
+    check_arity_range("alist-get", args, 2, 3)?;
+    let pairs = alist_arg("alist-get", &args[0])?;
+    Ok(pairs
+        .into_iter()
+        .find(|(key, _)| *key == args[1])
+        .map_or_else(|| args.get(2).cloned().unwrap_or(Value::Nil), |(_, v)| v))
+}
+
+/// Association list with key set to value, replacing its first pair or
+/// adding one at the end
+/// Usage: (alist-set (list (cons 'a 1) (cons 'b 2)) 'a 9) => ((a . 9) (b . 2))
+pub fn alist_set(args: &[Value], _env: &mut Environment) -> Result<Value, EvalError> {
+    check_arity_exact("alist-set", args, 3)?;
+    let mut pairs = alist_arg("alist-set", &args[0])?;
+    match pairs.iter_mut().find(|(key, _)| *key == args[1]) {
+        Some(pair) => pair.1 = args[2].clone(),
+        None => pairs.push((args[1].clone(), args[2].clone())),
+    }
+    Ok(vec_to_alist(pairs))
+}
+
+/// Keys of an association list, in order
+/// Usage: (alist-keys (list (cons 'a 1) (cons 'b 2))) => (a b)
+pub fn alist_keys(args: &[Value], _env: &mut Environment) -> Result<Value, EvalError> {
+    check_arity_exact("alist-keys", args, 1)?;
+    let pairs = alist_arg("alist-keys", &args[0])?;
+    Ok(vec_to_list(pairs.into_iter().map(|(key, _)| key).collect()))
+}
+
+// ============================================================================
+// Strings
+// ============================================================================
+
+/// The text of a string argument
+fn string_arg<'a>(fn_name: &str, value: &'a Value) -> Result<&'a str, EvalError> {
+    match value {
+        Value::Atom(AtomType::String(StringType::Basic(s))) => Ok(s),
+        other => Err(EvalError::TypeError {
+            callee: Some(fn_name.to_string()),
+            expected: "a string".to_string(),
+            found: other.clone(),
+        }),
+    }
+}
+
+/// A character index argument, which must lie within 0..=len
+fn char_index_arg(fn_name: &str, value: &Value, len: usize) -> Result<usize, EvalError> {
+    let Value::Atom(AtomType::Number(NumericType::Int(n))) = value else {
+        return Err(EvalError::TypeError {
+            callee: Some(fn_name.to_string()),
+            expected: "an integer index".to_string(),
+            found: value.clone(),
+        });
+    };
+    usize::try_from(*n)
+        .ok()
+        .filter(|&i| i <= len)
+        .ok_or_else(|| {
+            format!("{fn_name}: index {n} is out of range for a string of length {len}").into()
+        })
+}
+
+/// Concatenation of the display text of each value
+/// Usage: (str "n = " 42 #\!) => "n = 42!"
+pub fn str_fn(args: &[Value], _env: &mut Environment) -> Result<Value, EvalError> {
+    Ok(make_string(
+        args.iter().map(value_to_display_string).collect::<String>(),
+    ))
+}
+
+/// Number of characters in a string
+/// Usage: (str-length "héllo") => 5
+pub fn str_length(args: &[Value], _env: &mut Environment) -> Result<Value, EvalError> {
+    check_arity_exact("str-length", args, 1)?;
+    let s = string_arg("str-length", &args[0])?;
+    Ok(make_int(s.chars().count() as i64))
+}
+
+/// The characters of a string from start up to (not including) end
+/// Usage: (substring "hello" 1 3) => "el", (substring "hello" 3) => "lo"
+pub fn substring(args: &[Value], _env: &mut Environment) -> Result<Value, EvalError> {
+    check_arity_range("substring", args, 2, 3)?;
+    let s = string_arg("substring", &args[0])?;
+    let len = s.chars().count();
+    let start = char_index_arg("substring", &args[1], len)?;
+    let end = match args.get(2) {
+        Some(end) => char_index_arg("substring", end, len)?,
+        None => len,
+    };
+    if start > end {
+        return Err(format!("substring: start {start} is after end {end}").into());
+    }
+    Ok(make_string(
+        s.chars().skip(start).take(end - start).collect::<String>(),
+    ))
+}
+
+/// Pieces of a string between occurrences of a separator, or between runs
+/// of whitespace if no separator is given
+/// Usage: (str-split "a,b,,c" ",") => ("a" "b" "" "c")
+///        (str-split "  one two ") => ("one" "two")
+pub fn str_split(args: &[Value], _env: &mut Environment) -> Result<Value, EvalError> {
+    check_arity_range("str-split", args, 1, 2)?;
+    let s = string_arg("str-split", &args[0])?;
+    let pieces: Vec<&str> = match args.get(1) {
+        Some(sep) => {
+            let sep = string_arg("str-split", sep)?;
+            if sep.is_empty() {
+                return Err("str-split: separator must not be empty".into());
+            }
+            s.split(sep).collect()
+        }
+        None => s.split_whitespace().collect(),
+    };
+    Ok(vec_to_list(pieces.into_iter().map(make_string).collect()))
+}
+
+/// The display text of each element of a seq, with a separator between them
+/// Usage: (str-join ", " '(1 2 3)) => "1, 2, 3"
+pub fn str_join(args: &[Value], _env: &mut Environment) -> Result<Value, EvalError> {
+    check_arity_exact("str-join", args, 2)?;
+    let sep = string_arg("str-join", &args[0])?;
+    let parts: Vec<String> = seq_arg("str-join", &args[1])?
+        .iter()
+        .map(value_to_display_string)
+        .collect();
+    Ok(make_string(parts.join(sep)))
+}
+
+/// A string without leading and trailing whitespace
+/// Usage: (str-trim "  hi\n") => "hi"
+pub fn str_trim(args: &[Value], _env: &mut Environment) -> Result<Value, EvalError> {
+    check_arity_exact("str-trim", args, 1)?;
+    Ok(make_string(string_arg("str-trim", &args[0])?.trim()))
+}
+
+/// A string with every occurrence of from replaced by to
+/// Usage: (str-replace "a-b-c" "-" "+") => "a+b+c"
+pub fn str_replace(args: &[Value], _env: &mut Environment) -> Result<Value, EvalError> {
+    check_arity_exact("str-replace", args, 3)?;
+    let s = string_arg("str-replace", &args[0])?;
+    let from = string_arg("str-replace", &args[1])?;
+    let to = string_arg("str-replace", &args[2])?;
+    if from.is_empty() {
+        return Err("str-replace: the string to replace must not be empty".into());
+    }
+    Ok(make_string(s.replace(from, to)))
+}
+
+/// A string in upper case
+/// Usage: (str-upper "straße") => "STRASSE"
+pub fn str_upper(args: &[Value], _env: &mut Environment) -> Result<Value, EvalError> {
+    check_arity_exact("str-upper", args, 1)?;
+    Ok(make_string(
+        string_arg("str-upper", &args[0])?.to_uppercase(),
+    ))
+}
+
+/// A string in lower case
+/// Usage: (str-lower "HeLLo") => "hello"
+pub fn str_lower(args: &[Value], _env: &mut Environment) -> Result<Value, EvalError> {
+    check_arity_exact("str-lower", args, 1)?;
+    Ok(make_string(
+        string_arg("str-lower", &args[0])?.to_lowercase(),
+    ))
+}
+
+/// A string built from a control string and arguments, after Common Lisp
+/// Directives: ~a displays an argument, ~s writes it readably, ~d takes an
+/// integer, ~f a number as a float (~,2f with two decimals), ~% is a newline
+/// and ~~ a tilde. A destination of nil returns the string; t prints it and
+/// returns nil.
+/// Usage: (format nil "~a has ~d items" "cart" 3) => "cart has 3 items"
+pub fn format_fn(args: &[Value], _env: &mut Environment) -> Result<Value, EvalError> {
+    check_arity_min("format", args, 2)?;
+    let control = string_arg("format", &args[1])?;
+    let text = format_text(control, &args[2..])?;
+    match &args[0] {
+        Value::Nil | Value::Atom(AtomType::Bool(false)) => Ok(make_string(text)),
+        Value::Atom(AtomType::Bool(true)) => {
+            let stdout = io::stdout();
+            let mut handle = stdout.lock();
+            write!(handle, "{text}").map_err(|e| format!("format: I/O error: {e}"))?;
+            handle
+                .flush()
+                .map_err(|e| format!("format: I/O error: {e}"))?;
+            Ok(Value::Nil)
+        }
+        other => Err(EvalError::TypeError {
+            callee: Some("format".to_string()),
+            expected: "nil or t as the destination".to_string(),
+            found: other.clone(),
+        }),
+    }
+}
+
+/// The output of `format`. Errors name the directive by its position among
+/// the directives in `control`, counting from 1.
+fn format_text(control: &str, args: &[Value]) -> Result<String, String> {
+    let mut out = String::new();
+    let mut used = 0;
+    let mut index = 0;
+    let mut chars = control.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '~' {
+            out.push(c);
+            continue;
+        }
+        index += 1;
+        let mut spec = String::from("~");
+        let mut precision = None;
+        if chars.next_if_eq(&',').is_some() {
+            spec.push(',');
+            let mut digits = String::new();
+            while let Some(digit) = chars.next_if(char::is_ascii_digit) {
+                digits.push(digit);
+            }
+            spec.push_str(&digits);
+            precision = Some(digits.parse::<usize>().map_err(|_| {
+                format!("format: directive {index} ({spec}) needs a number of decimals")
+            })?);
+        }
+        let Some(directive) = chars.next() else {
+            return Err(format!(
+                "format: directive {index} ({spec}) is cut off by the end of the control string"
+            ));
+        };
+        spec.push(directive);
+        let directive = directive.to_ascii_lowercase();
+        if precision.is_some() && directive != 'f' {
+            return Err(format!(
+                "format: directive {index} ({spec}) takes no number of decimals"
+            ));
+        }
+        if let Some(literal) = match directive {
+            '%' => Some('\n'),
+            '~' => Some('~'),
+            _ => None,
+        } {
+            out.push(literal);
+            continue;
+        }
+        if !matches!(directive, 'a' | 's' | 'd' | 'f') {
+            return Err(format!("format: directive {index} ({spec}) is unknown"));
+        }
+
+        let Some(arg) = args.get(used) else {
+            return Err(format!(
+                "format: directive {index} ({spec}) has no argument; {} given",
+                args.len()
+            ));
+        };
+        used += 1;
+        match (directive, arg) {
+            ('a', _) => out.push_str(&value_to_display_string(arg)),
+            ('s', _) => out.push_str(&readable_text(std::slice::from_ref(arg))),
+            (
+                'd',
+                Value::Atom(AtomType::Number(n @ (NumericType::Int(_) | NumericType::BigInt(_)))),
+            ) => out.push_str(&n.to_string()),
+            ('f', Value::Atom(AtomType::Number(n))) => match precision {
+                Some(decimals) => out.push_str(&format!("{:.decimals$}", n.to_float())),
+                None => out.push_str(&make_float(n.to_float()).to_string()),
+            },
+            _ => {
+                let expected = if directive == 'd' {
+                    "an integer"
+                } else {
+                    "a number"
+                };
+                return Err(format!(
+                    "format: directive {index} ({spec}) expects {expected}, got {arg}"
+                ));
+            }
+        }
+    }
+    if used < args.len() {
+        return Err(format!(
+            "format: {} arguments given but the directives use {used}",
+            args.len()
+        ));
+    }
+    Ok(out)
+}
+
+// ============================================================================
+// Characters
+// ============================================================================
+
+/// True if x is a character
+/// Usage: (char? #\a) => t, (char? "a") => nil
+pub fn char_p(args: &[Value], _env: &mut Environment) -> Result<Value, EvalError> {
+    check_arity_exact("char?", args, 1)?;
+    Ok(make_bool(matches!(args[0], Value::Atom(AtomType::Char(_)))))
+}
+
+/// Unicode code point of a character
+/// Usage: (char->int #\A) => 65
+pub fn char_to_int(args: &[Value], _env: &mut Environment) -> Result<Value, EvalError> {
+    check_arity_exact("char->int", args, 1)?;
+    match &args[0] {
+        Value::Atom(AtomType::Char(c)) => Ok(make_int(*c as i64)),
+        other => Err(EvalError::TypeError {
+            callee: Some("char->int".to_string()),
+            expected: "a character".to_string(),
+            found: other.clone(),
+        }),
+    }
+}
+
+/// Character with a Unicode code point
+/// Usage: (int->char 955) => #\λ
+pub fn int_to_char(args: &[Value], _env: &mut Environment) -> Result<Value, EvalError> {
+    check_arity_exact("int->char", args, 1)?;
+    let Value::Atom(AtomType::Number(NumericType::Int(n))) = &args[0] else {
+        return Err(EvalError::TypeError {
+            callee: Some("int->char".to_string()),
+            expected: "an integer".to_string(),
+            found: args[0].clone(),
+        });
+    };
+    u32::try_from(*n)
+        .ok()
+        .and_then(char::from_u32)
+        .map(|c| Value::Atom(AtomType::Char(c)))
+        .ok_or_else(|| format!("int->char: {n} is not a Unicode code point").into())
+}
+
+// ============================================================================
+// Errors
+// ============================================================================
+
+/// Raise an error with a message, to be caught by try
+/// Usage: (error "disk full") => error: disk full
+pub fn error(args: &[Value], _env: &mut Environment) -> Result<Value, EvalError> {
+    check_arity_exact("error", args, 1)?;
+    Err(match &args[0] {
+        Value::Atom(AtomType::String(StringType::Basic(message))) => message.clone().into(),
+        other => other.to_string().into(),
+    })
+}
+
+/// Message of an error caught by try
+/// Usage: (try (car 1) (catch e (error-message e))) => "car: expected cons cell, got 1"
+pub fn error_message(args: &[Value], _env: &mut Environment) -> Result<Value, EvalError> {
+    check_arity_exact("error-message", args, 1)?;
+    abstractions::alist_pairs(&args[0])
+        .and_then(|pairs| {
+            pairs
+                .into_iter()
+                .find(|(key, _)| *key == make_symbol("message"))
+                .map(|(_, message)| message)
+        })
+        .ok_or_else(|| EvalError::TypeError {
+            callee: Some("error-message".to_string()),
+            expected: "a caught error".to_string(),
+            found: args[0].clone(),
+        })
+}
+
+// ============================================================================
+// Documentation
+// ============================================================================
+/// Docstring of a function or macro
+/// Usage: (doc length) => "Number of elements in a list."
+///        (doc (lambda (x) "Identity." x)) => "Identity."
+pub fn doc(args: &[Value], _env: &mut Environment) -> Result<Value, EvalError> {
+    if args.len() != 1 {
+        return Err("doc: expected 1 argument".into());
+    }
+    Ok(match &args[0] {
+        Value::NativeFn(f) => native_entry(*f).map_or(Value::Nil, |entry| make_string(entry.doc)),
+        Value::Lambda(lambda) => lambda.doc.as_deref().map_or(Value::Nil, make_string),
+        Value::Macro(mc) => mc.doc.as_deref().map_or(Value::Nil, make_string),
+        _ => Value::Nil,
+    })
+}
+
+/// What a symbol is bound to, with the call signature and docstring of a
+/// function or macro
+/// Usage: (describe 'length) => "length: native function\n(length list)\nNumber of elements in a list."
+pub fn describe(args: &[Value], env: &mut Environment) -> Result<Value, EvalError> {
+    check_arity_exact("describe", args, 1)?;
+    let Value::Atom(AtomType::Symbol(SymbolType::Symbol(name))) = &args[0] else {
+        return Err(EvalError::TypeError {
+            callee: Some("describe".to_string()),
+            expected: "a symbol".to_string(),
+            found: args[0].clone(),
+        });
+    };
+    let name = name.resolve();
+    Ok(make_string(doc::describe(
+        &name,
+        env.lookup(&name).as_ref(),
+    )))
+}
+
+/// Every binding visible where it is called, as an alist sorted by name
+/// Usage: (alist-get (env) 'x) => 1
+pub fn env_fn(args: &[Value], env: &mut Environment) -> Result<Value, EvalError> {
+    check_arity_exact("env", args, 0)?;
+    let pairs = env
+        .names()
+        .into_iter()
+        .filter_map(|name| {
+            let value = env.lookup(&name)?;
+            Some((make_symbol(name), value))
+        })
+        .collect();
+    Ok(vec_to_alist(pairs))
+}
+// ============================================================================
+// Registration
+// ============================================================================
+/// A standard library function with the documentation shown by `doc` and
+/// `cons doc --stdlib`.
+pub struct NativeEntry {
+    /// Name the function is bound to
+    pub name: &'static str,
+    /// The implementation
+    pub func: NativeFn,
+    /// Parameter list, in lambda syntax
+    pub params: &'static str,
+    /// One-line description
+    pub doc: &'static str,
+}
+
+impl NativeEntry {
+    const fn new(
+        name: &'static str,
+        func: NativeFn,
+        params: &'static str,
+        doc: &'static str,
+    ) -> Self {
+        NativeEntry {
+            name,
+            func,
+            params,
+            doc,
+        }
+    }
+}
+/// Every standard library function, in registration order.
+/// Names starting with `%` are engine internals used by the prelude macros.
+pub const STDLIB: &[NativeEntry] = &[
+    // Standard I/O
+    NativeEntry::new(
+        "print",
+        print,
+        "&rest values",
+        "Print values separated by spaces, without a newline. Strings print without quotes.",
+    ),
+    NativeEntry::new(
+        "println",
+        println,
+        "&rest values",
+        "Print values separated by spaces, followed by a newline.",
+    ),
+    NativeEntry::new(
+        "write",
+        write_readable,
+        "&rest values",
+        "Print values in readable form, without a newline. Ignores *print-float-digits* and the print limits.",
+    ),
+    NativeEntry::new(
+        "write-str",
+        write_str,
+        "&rest values",
+        "Return what write would print, as a string.",
+    ),
+    NativeEntry::new(
+        "pr-str",
+        pr_str,
+        "&rest values",
+        "Return what write would print, as a string; the same as write-str.",
+    ),
+    NativeEntry::new(
+        "read-string",
+        read_string,
+        "s",
+        "The first form written in s, as data. Reads back what pr-str writes.",
+    ),
+    NativeEntry::new(
+        "read-all-string",
+        read_all_string,
+        "s",
+        "A list of every form written in s, as data.",
+    ),
+    NativeEntry::new(
+        "pprint",
+        pprint,
+        "x &opt width",
+        "Print x across lines at most width characters wide (default 80), followed by a newline.",
+    ),
+    NativeEntry::new(
+        "pprint-str",
+        pprint_str,
+        "x &opt width",
+        "Return what pprint would print, without the final newline.",
+    ),
+    // File I/O
+    NativeEntry::new(
+        "slurp",
+        slurp,
+        "path &key encoding bytes",
+        "Read a whole file as a string. :encoding is :utf8, :utf8-lossy or :latin1; :bytes t returns a byte vector.",
+    ),
+    NativeEntry::new(
+        "slurp-bytes",
+        slurp_bytes,
+        "path",
+        "Read a whole file as a vector of byte values.",
+    ),
+    NativeEntry::new(
+        "spit",
+        spit,
+        "path content &key append atomic create-dirs",
+        "Write a string to a file, replacing it unless :append is true.",
+    ),
+    NativeEntry::new(
+        "load",
+        load,
+        "path",
+        "Evaluate every form of a source file, relative to the current file, and return the last value.",
+    ),
+    NativeEntry::new(
+        "require",
+        require,
+        "path",
+        "Load a source file unless require already has; t if it loaded the file.",
+    ),
+    // Process execution
+    NativeEntry::new(
+        "shell",
+        shell,
+        "command &key dir timeout-ms",
+        "Run a shell command and return an alist of out, err, exit and success.",
+    ),
+    NativeEntry::new(
+        "command-line-args",
+        command_line_args,
+        "",
+        "The arguments given after the script's file name, as a list of strings.",
+    ),
+    // Time
+    NativeEntry::new("now", now, "", "Current Unix time in seconds."),
+    // Macro support
+    NativeEntry::new("gensym", gensym, "&opt prefix", "Generate a unique symbol."),
+    NativeEntry::new(
+        "macroexpand-1",
+        macroexpand_1,
+        "form",
+        "Expand a macro call once.",
+    ),
+    NativeEntry::new(
+        "macroexpand",
+        macroexpand,
+        "form",
+        "Expand a form until it is no longer a macro call.",
+    ),
+    NativeEntry::new(
+        "macroexpand-all",
+        macroexpand_all,
+        "form",
+        "Expand every macro call in a form, including nested ones.",
+    ),
+    // Errors
+    NativeEntry::new(
+        "error",
+        error,
+        "message",
+        "Raise an error with message, to be caught by try.",
+    ),
+    NativeEntry::new(
+        "error-message",
+        error_message,
+        "e",
+        "Message of an error bound by a try's catch clause.",
+    ),
+    // Documentation
+    NativeEntry::new("doc", doc, "f", "Docstring of a function or macro, or nil."),
+    NativeEntry::new(
+        "describe",
+        describe,
+        "name",
+        "Describe what a symbol is bound to: its kind and, for a function or macro, its signature and docstring.",
+    ),
+    NativeEntry::new(
+        "env",
+        env_fn,
+        "",
+        "Every binding visible here, as an alist of (name . value) sorted by name.",
+    ),
+    // List operations (de-sugaring special forms)
+    NativeEntry::new("atom", atom, "x", "True if x is an atom."),
+    NativeEntry::new(
+        "eq",
+        eq,
+        "a b",
+        "True if a and b are the same atom. Functions compare by identity.",
+    ),
+    NativeEntry::new(
+        "identical?",
+        identical_p,
+        "a b",
+        "True if a and b are the same object.",
+    ),
+    NativeEntry::new(
+        "equal?",
+        equal_p,
+        "a b",
+        "True if a and b are structurally equal.",
+    ),
+    NativeEntry::new("car", car, "list", "First element of a list."),
+    NativeEntry::new(
+        "cdr",
+        cdr,
+        "list",
+        "Rest of a list after the first element.",
+    ),
+    NativeEntry::new("cons", cons_fn, "x list", "Construct a cons cell."),
+    // Type predicates (for JIT/AOT parity)
+    NativeEntry::new("nil?", nil_p, "x", "True if x is nil."),
+    NativeEntry::new("cons?", cons_p, "x", "True if x is a cons cell."),
+    NativeEntry::new("number?", number_p, "x", "True if x is a number."),
+    NativeEntry::new("not", not_fn, "x", "True if x is nil."),
+    // List operations (for JIT/AOT parity)
+    NativeEntry::new("length", length, "list", "Number of elements in a list."),
+    NativeEntry::new("append", append, "a b", "Concatenate two lists."),
+    NativeEntry::new("reverse", reverse, "coll", "Reverse a list or vector."),
+    NativeEntry::new(
+        "list",
+        list,
+        "&rest items",
+        "Create a list from the arguments.",
+    ),
+    NativeEntry::new(
+        "nth",
+        nth,
+        "list n &key default",
+        "Element n of a list (0-indexed), or :default when out of range.",
+    ),
+    // Vector operations (for JIT/AOT parity)
+    NativeEntry::new(
+        "vector-length",
+        vector_length,
+        "v",
+        "Number of elements in a vector.",
+    ),
+    NativeEntry::new(
+        "vector-ref",
+        vector_ref,
+        "v i",
+        "Element i of a vector (0-indexed).",
+    ),
+    // Arithmetic operations (de-sugaring special forms)
+    NativeEntry::new("+", add, "&rest numbers", "Sum of the arguments."),
+    NativeEntry::new(
+        "-",
+        sub,
+        "x &rest numbers",
+        "Subtract successive arguments from x, or negate a single argument.",
+    ),
+    NativeEntry::new("*", mul, "&rest numbers", "Product of the arguments."),
+    NativeEntry::new(
+        "/",
+        div,
+        "x &rest numbers",
+        "Divide x by successive arguments.",
+    ),
+    NativeEntry::new(
+        "mod",
+        mod_fn,
+        "a b",
+        "Modulus of a by b, with the sign of b.",
+    ),
+    NativeEntry::new(
+        "rem",
+        rem_fn,
+        "a b",
+        "Remainder of a by b, with the sign of a.",
+    ),
+    NativeEntry::new(
+        "quot",
+        quot_fn,
+        "a b",
+        "Quotient of a by b, rounded toward zero.",
+    ),
+    NativeEntry::new("expt", expt_fn, "base power", "base raised to power."),
+    NativeEntry::new("abs", abs_fn, "n", "Absolute value of n."),
+    NativeEntry::new(
+        "min",
+        min_fn,
+        "x &rest numbers",
+        "Smallest of the arguments.",
+    ),
+    NativeEntry::new(
+        "max",
+        max_fn,
+        "x &rest numbers",
+        "Largest of the arguments.",
+    ),
+    // Comparison operations (de-sugaring special forms)
+    NativeEntry::new("<", lt, "a b", "True if a is less than b."),
+    NativeEntry::new(">", gt, "a b", "True if a is greater than b."),
+    NativeEntry::new("<=", lte, "a b", "True if a is less than or equal to b."),
+    NativeEntry::new(">=", gte, "a b", "True if a is greater than or equal to b."),
+    NativeEntry::new("=", num_eq, "a b", "True if two numbers are equal."),
+    // Vector constructor (de-sugaring vector syntax)
+    NativeEntry::new(
+        "vector",
+        vector,
+        "&rest items",
+        "Create a vector from the arguments.",
+    ),
+    // Engine abstractions (Clojure-inspired)
+    NativeEntry::new(
+        "%seq",
+        builtin_seq,
+        "coll",
+        "A seq over a collection, or nil if it is empty.",
+    ),
+    NativeEntry::new(
+        "%first",
+        builtin_first,
+        "coll",
+        "First element of a collection.",
+    ),
+    NativeEntry::new(
+        "%next",
+        builtin_next,
+        "coll",
+        "Elements after the first, or nil if there are none.",
+    ),
+    NativeEntry::new(
+        "%rest",
+        builtin_rest,
+        "coll",
+        "Elements after the first, as a possibly empty seq.",
+    ),
+    NativeEntry::new(
+        "%count",
+        builtin_count,
+        "coll",
+        "Number of elements in a collection.",
+    ),
+    NativeEntry::new(
+        "%nth",
+        builtin_nth,
+        "coll n &opt default",
+        "Element n of a collection.",
+    ),
+    NativeEntry::new(
+        "%get",
+        builtin_get,
+        "coll key &opt default",
+        "Value for key in a map, set, vector or association list.",
+    ),
+    NativeEntry::new(
+        "%assoc",
+        builtin_assoc,
+        "coll key val &rest kvs",
+        "Associate keys with values in a map or vector.",
+    ),
+    NativeEntry::new(
+        "%conj",
+        builtin_conj,
+        "coll item &rest items",
+        "Add items to a collection in its natural position.",
+    ),
+    NativeEntry::new(
+        "%reduced",
+        builtin_reduced,
+        "x",
+        "Wrap a value to stop a reduction early.",
+    ),
+    NativeEntry::new(
+        "%reduced?",
+        builtin_reduced_p,
+        "x",
+        "True if x is a reduced value.",
+    ),
+    NativeEntry::new(
+        "%unreduced",
+        builtin_unreduced,
+        "x",
+        "Unwrap a reduced value.",
+    ),
+    NativeEntry::new(
+        "%hash-map",
+        builtin_hash_map,
+        "&rest kvs",
+        "Create a hash map from key-value pairs.",
+    ),
+    NativeEntry::new(
+        "%hash-set",
+        builtin_hash_set,
+        "&rest items",
+        "Create a hash set from the arguments.",
+    ),
+    NativeEntry::new(
+        "%empty?",
+        builtin_empty_p,
+        "coll",
+        "True if a collection has no elements.",
+    ),
+    NativeEntry::new(
+        "%contains?",
+        builtin_contains_p,
+        "coll key",
+        "True if a map, set or vector contains key.",
+    ),
+    NativeEntry::new("%keys", builtin_keys, "map", "Keys of a map."),
+    NativeEntry::new("%vals", builtin_vals, "map", "Values of a map."),
+    NativeEntry::new(
+        "%dissoc",
+        builtin_dissoc,
+        "map &rest keys",
+        "Remove keys from a map.",
+    ),
+    NativeEntry::new(
+        "%disj",
+        builtin_disj,
+        "set &rest items",
+        "Remove items from a set.",
+    ),
+    // Function application
+    NativeEntry::new(
+        "apply",
+        builtin_apply,
+        "f &rest args list",
+        "Call f with args followed by the elements of list.",
+    ),
+    // Refs
+    NativeEntry::new("ref", builtin_ref, "init", "A new ref holding init."),
+    NativeEntry::new(
+        "deref",
+        deref,
+        "r",
+        "The value ref r holds. @r reads as (deref r).",
+    ),
+    NativeEntry::new(
+        "ref-set!",
+        ref_set,
+        "r value",
+        "Make ref r hold value, and return it.",
+    ),
+    NativeEntry::new(
+        "swap!",
+        swap,
+        "r f &rest args",
+        "Make ref r hold (f current args...), and return the new value.",
+    ),
+    // Sequence functions
+    NativeEntry::new(
+        "map",
+        map,
+        "f coll",
+        "List of the results of f applied to each element of coll.",
+    ),
+    NativeEntry::new(
+        "par-map",
+        par_map,
+        "f coll",
+        "map, with coll split across threads. f cannot change bindings.",
+    ),
+    NativeEntry::new(
+        "filter",
+        filter,
+        "pred coll",
+        "List of the elements of coll for which pred is truthy.",
+    ),
+    NativeEntry::new(
+        "reduce",
+        reduce,
+        "f &opt init coll",
+        "Combine the elements of coll with f, stopping early at a reduced value.",
+    ),
+    NativeEntry::new(
+        "partition-by",
+        partition_by,
+        "f coll",
+        "Split coll into runs of consecutive elements for which f returns equal values.",
+    ),
+    NativeEntry::new(
+        "split-with",
+        split_with,
+        "pred coll",
+        "Split coll into the longest prefix satisfying pred and the rest.",
+    ),
+    NativeEntry::new(
+        "range",
+        range,
+        "&opt start end step",
+        "A list of integers from start (default 0) below end, counting by step.",
+    ),
+    NativeEntry::new("take", take, "n coll", "The first n elements of coll."),
+    NativeEntry::new(
+        "drop",
+        drop,
+        "n coll",
+        "The elements of coll after the first n.",
+    ),
+    NativeEntry::new(
+        "take-while",
+        take_while,
+        "pred coll",
+        "The longest prefix of coll satisfying pred.",
+    ),
+    NativeEntry::new(
+        "drop-while",
+        drop_while,
+        "pred coll",
+        "The elements of coll from the first that fails pred.",
+    ),
+    NativeEntry::new("last", last, "coll", "The last element of coll, or nil."),
+    NativeEntry::new(
+        "butlast",
+        butlast,
+        "coll",
+        "Every element of coll but the last.",
+    ),
+    NativeEntry::new(
+        "distinct",
+        distinct,
+        "coll",
+        "Elements of coll with duplicates removed, keeping first occurrences.",
+    ),
+    NativeEntry::new(
+        "dedupe",
+        dedupe,
+        "coll",
+        "Elements of coll with consecutive duplicates removed.",
+    ),
+    NativeEntry::new(
+        "flatten",
+        flatten,
+        "coll",
+        "All non-collection elements of a nested collection, in order.",
+    ),
+    NativeEntry::new("vec", vec, "coll", "A vector with the elements of coll."),
+    NativeEntry::new(
+        "persistent!",
+        persistent,
+        "coll",
+        "The persistent, structure-sharing counterpart of coll.",
+    ),
+    NativeEntry::new(
+        "transient",
+        transient,
+        "coll",
+        "The fast counterpart of a persistent collection.",
+    ),
+    NativeEntry::new(
+        "into",
+        into,
+        "to from",
+        "Add every element of from to to with conj.",
+    ),
+    NativeEntry::new(
+        "sort",
+        sort,
+        "&opt less? coll",
+        "Stable sort, in natural order or by a less? predicate.",
+    ),
+    NativeEntry::new(
+        "sort-by",
+        sort_by,
+        "key &opt less? coll",
+        "Stable sort by the result of key on each element.",
+    ),
+    NativeEntry::new(
+        "some",
+        some,
+        "pred coll",
+        "The first truthy result of pred over coll, or nil.",
+    ),
+    NativeEntry::new(
+        "every?",
+        every_p,
+        "pred coll",
+        "True if pred is truthy for every element of coll.",
+    ),
+    NativeEntry::new(
+        "not-any?",
+        not_any_p,
+        "pred coll",
+        "True if pred is nil for every element of coll.",
+    ),
+    NativeEntry::new(
+        "keep",
+        keep,
+        "f coll",
+        "The non-nil results of f over coll.",
+    ),
+    NativeEntry::new(
+        "max-key",
+        max_key,
+        "f coll",
+        "The element of coll for which (f x) is greatest.",
+    ),
+    NativeEntry::new(
+        "min-key",
+        min_key,
+        "f coll",
+        "The element of coll for which (f x) is least.",
+    ),
+    // Association lists
+    NativeEntry::new(
+        "alist-get",
+        alist_get,
+        "alist key &opt default",
+        "Value paired with key in an association list, or default if missing.",
+    ),
+    NativeEntry::new(
+        "alist-set",
+        alist_set,
+        "alist key value",
+        "Association list with key set to value.",
+    ),
+    NativeEntry::new(
+        "alist-keys",
+        alist_keys,
+        "alist",
+        "Keys of an association list, in order.",
+    ),
+    // Numeric conversion
+    NativeEntry::new(
+        "parse-number",
+        parse_number,
+        "s",
+        "The number written in string s, or nil if s is not a number.",
+    ),
+    NativeEntry::new(
+        "int",
+        int_fn,
+        "n",
+        "Integer part of n, rounding toward zero.",
+    ),
+    NativeEntry::new("float", float_fn, "n", "Nearest float to n."),
+    NativeEntry::new("nan?", nan_p, "x", "True if x is the float ##NaN."),
+    NativeEntry::new(
+        "infinite?",
+        infinite_p,
+        "x",
+        "True if x is ##Inf or ##-Inf.",
+    ),
+    NativeEntry::new(
+        "finite?",
+        finite_p,
+        "x",
+        "True if x is a number other than ##Inf, ##-Inf and ##NaN.",
+    ),
+    NativeEntry::new(
+        "format-number",
+        format_number,
+        "n &opt radix",
+        "n as a string; integers may be written in radix 2 to 36.",
+    ),
+    // Bitwise operations
+    NativeEntry::new(
+        "bit-and",
+        bit_and,
+        "a b &rest integers",
+        "Bitwise and of the arguments.",
+    ),
+    NativeEntry::new(
+        "bit-or",
+        bit_or,
+        "a b &rest integers",
+        "Bitwise inclusive or of the arguments.",
+    ),
+    NativeEntry::new(
+        "bit-xor",
+        bit_xor,
+        "a b &rest integers",
+        "Bitwise exclusive or of the arguments.",
+    ),
+    NativeEntry::new("bit-not", bit_not, "n", "Bitwise complement of n."),
+    NativeEntry::new(
+        "bit-shift-left",
+        bit_shift_left,
+        "n count",
+        "n shifted left by count bits, where count is 0 to 63.",
+    ),
+    NativeEntry::new(
+        "bit-shift-right",
+        bit_shift_right,
+        "n count",
+        "n shifted right by count bits, keeping its sign.",
+    ),
+    // Strings
+    NativeEntry::new(
+        "str",
+        str_fn,
+        "&rest values",
+        "Concatenation of the display text of each argument.",
+    ),
+    NativeEntry::new(
+        "format",
+        format_fn,
+        "destination control &rest args",
+        "The control string with each directive (~a ~s ~d ~f ~,Nf ~% ~~) filled in; returned when destination is nil, printed when it is t.",
+    ),
+    NativeEntry::new(
+        "str-length",
+        str_length,
+        "s",
+        "Number of characters in a string.",
+    ),
+    NativeEntry::new(
+        "substring",
+        substring,
+        "s start &opt end",
+        "The characters of s from start up to end, or to the end of s.",
+    ),
+    NativeEntry::new(
+        "str-split",
+        str_split,
+        "s &opt sep",
+        "Pieces of s between occurrences of sep, or between runs of whitespace.",
+    ),
+    NativeEntry::new(
+        "str-join",
+        str_join,
+        "sep coll",
+        "The display text of each element of coll, separated by sep.",
+    ),
+    NativeEntry::new(
+        "str-trim",
+        str_trim,
+        "s",
+        "s without leading and trailing whitespace.",
+    ),
+    NativeEntry::new(
+        "str-replace",
+        str_replace,
+        "s from to",
+        "s with every occurrence of from replaced by to.",
+    ),
+    NativeEntry::new("str-upper", str_upper, "s", "s in upper case."),
+    NativeEntry::new("str-lower", str_lower, "s", "s in lower case."),
+    // Characters
+    NativeEntry::new("char?", char_p, "x", "True if x is a character."),
+    NativeEntry::new(
+        "char->int",
+        char_to_int,
+        "c",
+        "Unicode code point of a character.",
+    ),
+    NativeEntry::new(
+        "int->char",
+        int_to_char,
+        "n",
+        "Character with a Unicode code point.",
+    ),
+];
+
+/// Look up the standard library entry for a native function.
+pub fn native_entry(func: NativeFn) -> Option<&'static NativeEntry> {
+    STDLIB
+        .iter()
+        .find(|entry| std::ptr::fn_addr_eq(entry.func, func))
+}
+
+/// A group of standard library functions that reach outside the
+/// interpreter, which `StdlibConfig` can leave out. Every other function is
+/// always registered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StdlibGroup {
+    /// Printing to stdout
+    Io,
+    /// Reading and writing files
+    Files,
+    /// Running shell commands
+    Process,
+    /// Reading the clock
+    Time,
+}
+
+impl StdlibGroup {
+    /// Names of the functions in the group
+    pub fn names(self) -> &'static [&'static str] {
+        match self {
+            StdlibGroup::Io => &["print", "println", "write", "pprint"],
+            StdlibGroup::Files => &["slurp", "slurp-bytes", "spit", "load", "require"],
+            StdlibGroup::Process => &["shell"],
+            StdlibGroup::Time => &["now"],
+        }
+    }
+}
+
+/// Which parts of the standard library `register_stdlib_with` defines.
+///
+/// The default is everything; builder methods take parts away.
+///
+/// ```
+/// use cons::{Environment, StdlibConfig, StdlibGroup, register_stdlib_with, run_with};
+///
+/// let mut env = Environment::new();
+/// let config = StdlibConfig::new()
+///     .without(StdlibGroup::Process)
+///     .prelude(false);
+/// register_stdlib_with(&mut env, &config);
+/// assert!(run_with(&mut env, "(shell \"ls\")").is_err());
+/// assert!(run_with(&mut env, "(when t 1)").is_err());
+/// assert_eq!(run_with(&mut env, "(+ 1 2)").unwrap().to_string(), "3");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StdlibConfig {
+    excluded: Vec<StdlibGroup>,
+    prelude: bool,
+}
+
+impl Default for StdlibConfig {
+    fn default() -> Self {
+        StdlibConfig {
+            excluded: Vec::new(),
+            prelude: true,
+        }
+    }
+}
+
+impl StdlibConfig {
+    /// Every group and the prelude
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// No file access or shell commands, for code that is not trusted.
+    ///
+    /// Printing and the clock stay, since neither reaches files or other
+    /// processes.
+    pub fn sandboxed() -> Self {
+        Self::new()
+            .without(StdlibGroup::Files)
+            .without(StdlibGroup::Process)
+    }
+
+    /// Leave out the functions in `group`
+    pub fn without(mut self, group: StdlibGroup) -> Self {
+        if !self.excluded.contains(&group) {
+            self.excluded.push(group);
+        }
+        self
+    }
+
+    /// Whether to load the prelude macros (`when`, `unless`, `and`, `or`, ...)
+    pub fn prelude(mut self, load: bool) -> Self {
+        self.prelude = load;
+        self
+    }
+
+    /// True if the function `name` is registered under this configuration
+    pub fn includes(&self, name: &str) -> bool {
+        !self
+            .excluded
+            .iter()
+            .any(|group| group.names().contains(&name))
+    }
+}
+
+/// Register all standard library functions and the prelude macros in the given environment
+pub fn register_stdlib(env: &mut Environment) {
+    register_stdlib_with(env, &StdlibConfig::default());
+}
+
+/// Register the parts of the standard library that `config` includes.
+pub fn register_stdlib_with(env: &mut Environment, config: &StdlibConfig) {
+    for entry in STDLIB.iter().filter(|entry| config.includes(entry.name)) {
+        env.define(entry.name.to_string(), Value::NativeFn(entry.func));
+    }
+    if config.prelude {
+        register_prelude(env);
+    }
+}
+
+/// Register the standard library for code that is not trusted, as
+/// configured by `StdlibConfig::sandboxed`.
+///
+/// Calling a function that was left out is the usual unbound symbol error.
+pub fn register_stdlib_sandboxed(env: &mut Environment) {
+    register_stdlib_with(env, &StdlibConfig::sandboxed());
+}
+
+/// Register the functions that belong to no `StdlibGroup`: lists, numbers,
+/// strings, sequences, macros and the rest of the pure core. The prelude is
+/// not loaded.
+pub fn register_core(env: &mut Environment) {
+    let groups = [
+        StdlibGroup::Io,
+        StdlibGroup::Files,
+        StdlibGroup::Process,
+        StdlibGroup::Time,
+    ];
+    let config = groups
+        .into_iter()
+        .fold(StdlibConfig::new().prelude(false), StdlibConfig::without);
+    register_stdlib_with(env, &config);
+}
+
+/// Register printing and file access (`StdlibGroup::Io` and `StdlibGroup::Files`).
+pub fn register_io(env: &mut Environment) {
+    register_groups(env, &[StdlibGroup::Io, StdlibGroup::Files]);
+}
+
+/// Register `shell` (`StdlibGroup::Process`).
+pub fn register_process(env: &mut Environment) {
+    register_groups(env, &[StdlibGroup::Process]);
+}
+
+/// Register `now` (`StdlibGroup::Time`).
+pub fn register_time(env: &mut Environment) {
+    register_groups(env, &[StdlibGroup::Time]);
+}
+
+fn register_groups(env: &mut Environment, groups: &[StdlibGroup]) {
+    let in_groups = |name: &str| groups.iter().any(|group| group.names().contains(&name));
+    for entry in STDLIB.iter().filter(|entry| in_groups(entry.name)) {
+        env.define(entry.name.to_string(), Value::NativeFn(entry.func));
+    }
 }
@@ -7,7 +7,7 @@ fn run(code: &str) -> Result<Value, String> {
     let mut env = Environment::new();
     register_stdlib(&mut env);
     let expr = parse(code)?;
-    eval(expr, &mut env)
+    Ok(eval(expr, &mut env)?)
 }
 
 fn run_bool(code: &str) -> bool {
@@ -147,6 +147,18 @@ fn test_hash_map_with_values() {
     assert_eq!(run_int("(%count (%hash-map 1 2 3 4))"), 2);
 }
 
+#[test]
+fn test_map_literal() {
+    assert_eq!(run_int("(%get {:a 1 :b 2} :b)"), 2);
+    assert_eq!(
+        run("{:a 1 :b 2}").unwrap(),
+        run("(%hash-map :b 2 :a 1)").unwrap()
+    );
+    assert_eq!(run_int("(%count {})"), 0);
+    // Keys and values are not evaluated
+    assert_eq!(run("(%get {x (+ 1 2)} 'x)").unwrap().to_string(), "(+ 1 2)");
+}
+
 #[test]
 fn test_get_from_map() {
     assert_eq!(run_int("(%get (%hash-map 1 100 2 200) 1)"), 100);
@@ -229,6 +241,15 @@ fn test_contains_set() {
     assert!(!run_bool("(%contains? (%hash-set 1 2 3) 999)"));
 }
 
+#[test]
+fn test_set_literal() {
+    assert_eq!(run_int("(%count (quote #{1 2 3}))"), 3);
+    assert_eq!(run("#{1 2 3}").unwrap(), run("(%hash-set 3 2 1)").unwrap());
+    assert!(run_bool("(%contains? #{<<1 2>> {:a 1}} {:a 1})"));
+    // Elements are not evaluated
+    assert!(run_bool("(%contains? #{(+ 1 2)} '(+ 1 2))"));
+}
+
 // ============================================================================
 // Vector Abstraction Tests
 // ============================================================================
@@ -363,7 +384,7 @@ fn test_seq_set() {
 #[test]
 fn test_seq_string() {
     let result = run("(%first \"abc\")").unwrap();
-    assert_eq!(format!("{}", result), "\"a\"");
+    assert_eq!(format!("{}", result), r"#\a");
 }
 
 #[test]
@@ -371,3 +392,40 @@ fn test_count_string_unicode() {
     // Unicode string with multi-byte characters
     assert_eq!(run_int("(%count \"日本語\")"), 3);
 }
+
+#[test]
+fn test_string_seq_is_per_character() {
+    assert_eq!(run_int("(%count \"héllo\")"), 5);
+    assert_eq!(run("(%nth \"héllo\" 1)").unwrap().to_string(), r"#\é");
+    assert_eq!(run("(%first \"héllo\")").unwrap().to_string(), r"#\h");
+    assert_eq!(
+        run("(%rest \"héllo\")").unwrap().to_string(),
+        r"(#\é #\l #\l #\o)"
+    );
+    assert_eq!(run("(%seq \"ab\")").unwrap().to_string(), r"(#\a #\b)");
+    assert_eq!(run_int("(%count (%seq \"ab\"))"), 2);
+}
+
+#[test]
+fn test_conj_char_onto_string() {
+    assert_eq!(
+        run(r#"(%conj "hé" #\l #\l)"#).unwrap().to_string(),
+        "\"héll\""
+    );
+    assert_eq!(
+        run(r#"(into "" '(#\a #\b))"#).unwrap().to_string(),
+        "\"ab\""
+    );
+    let err = run(r#"(%conj "ab" "c")"#).unwrap_err();
+    assert!(err.contains("expected a character"), "{err}");
+}
+
+#[test]
+fn test_contains_string() {
+    assert!(run_bool(r#"(%contains? "héllo" "éll")"#));
+    assert!(!run_bool(r#"(%contains? "héllo" "elo")"#));
+    assert!(run_bool(r#"(%contains? "héllo" #\é)"#));
+    assert!(run_bool(r#"(%contains? "héllo" 4)"#));
+    assert!(!run_bool(r#"(%contains? "héllo" 5)"#));
+    assert!(!run_bool(r#"(%contains? "héllo" -1)"#));
+}
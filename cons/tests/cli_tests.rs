@@ -0,0 +1,103 @@
+use cons::cli::{Cli, Mode};
+use cons::repl::JitMode;
+
+fn parse(args: &[&str]) -> Result<Cli, String> {
+    let args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+    Cli::parse(&args)
+}
+
+fn mode(args: &[&str], stdin_is_terminal: bool) -> Result<Mode, String> {
+    parse(args)?.mode(stdin_is_terminal)
+}
+
+#[test]
+fn test_no_arguments_starts_the_repl_at_a_terminal() {
+    assert_eq!(mode(&[], true), Ok(Mode::Repl));
+    assert_eq!(parse(&[]).unwrap().jit_mode(), JitMode::Available);
+    assert_eq!(mode(&["--no-init"], true), Ok(Mode::Repl));
+}
+
+#[test]
+fn test_piped_stdin_runs_as_a_program() {
+    assert_eq!(mode(&[], false), Ok(Mode::Stdin));
+    assert_eq!(mode(&["--jit", "-v"], false), Ok(Mode::Stdin));
+    assert_eq!(mode(&["--no-main"], false), Ok(Mode::Stdin));
+    // A file still wins over stdin
+    assert_eq!(
+        mode(&["prog.lisp"], false),
+        Ok(Mode::File("prog.lisp".into()))
+    );
+}
+
+#[test]
+fn test_eval_with_jit() {
+    let cli = parse(&["--jit", "-e", "(+ 1 2)"]).unwrap();
+    assert!(cli.jit);
+    assert_eq!(cli.jit_mode(), JitMode::Enabled);
+    assert_eq!(cli.mode(false), Ok(Mode::Eval("(+ 1 2)".into())));
+    assert_eq!(
+        mode(&["-e", "1", "--jit", "--verbose"], true),
+        Ok(Mode::Eval("1".into()))
+    );
+}
+
+#[test]
+fn test_several_evals_run_in_order() {
+    let cli = parse(&["-e", "(def x 6)", "--eval", "(* x 7)"]).unwrap();
+    assert_eq!(cli.eval, ["(def x 6)", "(* x 7)"]);
+    assert_eq!(cli.mode(true), Ok(Mode::Eval("(def x 6)\n(* x 7)".into())));
+}
+
+#[test]
+fn test_arguments_after_the_file_belong_to_the_script() {
+    let cli = parse(&["--no-jit", "prog.lisp", "-e", "--jit", "x"]).unwrap();
+    assert_eq!(cli.file.as_deref(), Some("prog.lisp"));
+    assert!(cli.eval.is_empty() && !cli.jit);
+    assert_eq!(cli.script_args, ["-e", "--jit", "x"]);
+    assert_eq!(
+        cli.main_args(),
+        Some(["-e".to_string(), "--jit".into(), "x".into()].as_slice())
+    );
+    assert_eq!(
+        parse(&["--no-main", "prog.lisp"]).unwrap().main_args(),
+        None
+    );
+}
+
+#[test]
+fn test_conflicting_arguments() {
+    for (args, message) in [
+        (&["--jit", "--no-jit"][..], "--jit and --no-jit"),
+        (
+            &["-e", "1", "prog.lisp"],
+            "--eval cannot be used with a file",
+        ),
+        (&["-e"], "-e requires an expression"),
+        (&["--init"], "--init requires a file"),
+        (&["--frobnicate"], "Unknown option '--frobnicate'"),
+        (&["-x"], "Unknown option '-x'"),
+    ] {
+        let err = parse(args).unwrap_err();
+        assert!(err.contains(message), "{args:?}: {err}");
+    }
+    for (args, message) in [
+        (&["-e", "1", "--no-main"][..], "--no-main only applies"),
+        (&["-e", "1", "--no-init"], "only apply to the REPL"),
+        (
+            &["--init", "rc.lisp", "prog.lisp"],
+            "only apply to the REPL",
+        ),
+        (&["--verbose", "prog.lisp"], "--verbose is only supported"),
+        (&["--jit", "--verbose"], "--verbose is only supported"),
+        (&["--no-main"], "--no-main only applies"),
+    ] {
+        let err = mode(args, true).unwrap_err();
+        assert!(err.contains(message), "{args:?}: {err}");
+    }
+}
+
+#[test]
+fn test_help_stops_parsing() {
+    assert!(parse(&["--help", "--frobnicate"]).unwrap().help);
+    assert!(parse(&["-h"]).unwrap().help);
+}
@@ -0,0 +1,164 @@
+use std::fs;
+use std::path::PathBuf;
+
+use cons::doc::{DocKind, extract_docs, render_markdown, stdlib_docs};
+use cons::repl::{JitMode, ReplSession};
+use cons::stdlib::STDLIB;
+use cons::{eval, register_stdlib};
+use consair::{Environment, parse};
+
+fn fixture(name: &str) -> String {
+    let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    path.push("tests");
+    path.push("fixtures");
+    path.push(name);
+    fs::read_to_string(path).unwrap()
+}
+
+#[test]
+fn test_markdown_matches_golden_file() {
+    let entries = extract_docs(&fixture("doc_sample.lisp")).unwrap();
+    let markdown = render_markdown("doc_sample.lisp", &entries);
+    assert_eq!(markdown, fixture("doc_sample.md"));
+}
+
+#[test]
+fn test_extract_docs_skips_private_and_non_definitions() {
+    let entries = extract_docs(&fixture("doc_sample.lisp")).unwrap();
+    let names: Vec<_> = entries.iter().map(|e| e.name.as_str()).collect();
+    assert_eq!(names, ["square", "connect", "unless"]);
+    assert_eq!(
+        entries[1].doc.as_deref(),
+        Some("Connect to a database.\nPort and timeout have defaults.")
+    );
+    assert_eq!(entries[2].kind, DocKind::Macro);
+    assert_eq!(entries[2].doc, None);
+}
+
+#[test]
+fn test_stdlib_docs_cover_public_natives() {
+    let entries = stdlib_docs();
+    assert!(
+        entries
+            .iter()
+            .any(|e| e.signature() == "(nth list n &key default)")
+    );
+    assert!(
+        entries
+            .iter()
+            .any(|e| e.kind == DocKind::Macro && e.signature() == "(when test &rest body)")
+    );
+    assert!(entries.iter().all(|e| !e.name.starts_with('%')));
+    assert!(STDLIB.iter().all(|e| !e.doc.is_empty()));
+}
+
+#[test]
+fn test_doc_native_shares_stdlib_text() {
+    let mut env = Environment::new();
+    register_stdlib(&mut env);
+    let result = eval(parse("(doc length)").unwrap(), &mut env).unwrap();
+    assert_eq!(result.to_string(), "\"Number of elements in a list.\"");
+    let result = eval(parse("(doc (lambda (x) x))").unwrap(), &mut env).unwrap();
+    assert_eq!(result.to_string(), "nil");
+}
+
+fn run(env: &mut Environment, source: &str) -> String {
+    match eval(parse(source).unwrap(), env) {
+        Ok(value) => value.to_string(),
+        Err(e) => format!("Error: {e}"),
+    }
+}
+
+#[test]
+fn test_docstrings_are_kept_out_of_the_body() {
+    let mut env = Environment::new();
+    register_stdlib(&mut env);
+    run(
+        &mut env,
+        r#"(label square (lambda (x) "Square a number." (* x x)))"#,
+    );
+    assert_eq!(run(&mut env, "(square 5)"), "25");
+    assert_eq!(run(&mut env, "(doc square)"), "\"Square a number.\"");
+    // A lone string is the body, not a docstring
+    assert_eq!(run(&mut env, r#"((lambda () "hi"))"#), "\"hi\"");
+    assert_eq!(run(&mut env, r#"(doc (lambda () "hi"))"#), "nil");
+
+    run(
+        &mut env,
+        r#"(defmacro twice (form) "Run form twice." (list 'do form form))"#,
+    );
+    assert_eq!(run(&mut env, "(twice 1)"), "1");
+    let doc = run(&mut env, "(describe 'twice)");
+    assert_eq!(doc, r#""twice: macro\n(twice form)\nRun form twice.""#);
+}
+
+#[test]
+fn test_describe_reports_kind_signature_and_docstring() {
+    let mut env = Environment::new();
+    register_stdlib(&mut env);
+    run(
+        &mut env,
+        r#"(label connect (lambda (host &key (port 5432)) "Connect to a database." host))"#,
+    );
+    run(&mut env, "(label helper (lambda (a b) (+ a b)))");
+    run(&mut env, "(def limit 100)");
+
+    assert_eq!(
+        run(&mut env, "(describe 'connect)"),
+        r#""connect: function\n(connect host &key (port 5432))\nConnect to a database.""#
+    );
+    assert_eq!(
+        run(&mut env, "(describe 'helper)"),
+        r#""helper: function\n(helper a b)\nno documentation""#
+    );
+    assert_eq!(
+        run(&mut env, "(describe 'slurp)"),
+        format!(
+            r#""slurp: native function\n(slurp path &key encoding bytes)\n{}""#,
+            run(&mut env, "(doc slurp)").trim_matches('"')
+        )
+    );
+    assert_eq!(run(&mut env, "(describe 'limit)"), "\"limit: number\"");
+    assert_eq!(run(&mut env, "(describe 'nothing)"), "\"nothing: unbound\"");
+    assert!(run(&mut env, "(describe limit)").contains("expected a symbol"));
+}
+
+#[test]
+fn test_repl_doc_command_describes_a_name() {
+    let mut session = ReplSession::new(JitMode::Disabled);
+    session
+        .eval(parse(r#"(label square (lambda (x) "Square a number." (* x x)))"#).unwrap())
+        .unwrap();
+    assert_eq!(
+        session.describe("square"),
+        "square: function\n(square x)\nSquare a number."
+    );
+    assert!(
+        session
+            .describe("slurp")
+            .starts_with("slurp: native function\n(slurp path &key encoding bytes)\n")
+    );
+}
+
+#[test]
+fn test_extract_docs_falls_back_to_docstrings() {
+    let source = r#"
+;; From the comment.
+(label a (lambda (x) "From the docstring." x))
+(label b (lambda (x) "From the docstring." x))
+(defmacro c (x) "Macro docstring." x)
+"#;
+    let docs: Vec<_> = extract_docs(source)
+        .unwrap()
+        .into_iter()
+        .map(|entry| entry.doc)
+        .collect();
+    assert_eq!(
+        docs,
+        [
+            Some("From the comment.".to_string()),
+            Some("From the docstring.".to_string()),
+            Some("Macro docstring.".to_string()),
+        ]
+    );
+}
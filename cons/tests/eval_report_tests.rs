@@ -0,0 +1,194 @@
+use cons::jit::JitEngine;
+use cons::repl::{JitMode, ReplSession};
+use cons::runner::{ErrorStage, EvalReport, FormEngine, SourceSpan};
+
+fn unreachable_engine() -> Result<JitEngine, String> {
+    panic!("engine constructor should not be called with --no-jit");
+}
+
+fn eval_all(source: &str) -> EvalReport {
+    let mut session = ReplSession::with_engine_factory(JitMode::Disabled, unreachable_engine);
+    session.eval_all(source)
+}
+
+const BUFFER: &str = "; setup\n(label sq (lambda (x) (* x x)))\n(sq 4) (car 5)\n  (+ (sq 2) 1)\n";
+
+#[test]
+fn test_eval_all_reports_every_form() {
+    let report = eval_all(BUFFER);
+    assert_eq!(report.forms.len(), 4);
+
+    let results: Vec<_> = report
+        .forms
+        .iter()
+        .map(|form| form.result.as_deref().map_err(|e| e.stage))
+        .collect();
+    assert_eq!(
+        results,
+        vec![Ok("<lambda (x)>"), Ok("16"), Err(ErrorStage::Eval), Ok("5")]
+    );
+    assert_eq!(report.forms[0].engine, Some(FormEngine::Definition));
+    assert_eq!(report.forms[1].engine, Some(FormEngine::Interpreter));
+    assert_eq!(report.forms[3].value.as_ref().unwrap().to_string(), "5");
+    assert!(!report.is_ok());
+    assert_eq!(report.errors().count(), 1);
+}
+
+#[test]
+fn test_eval_all_spans() {
+    let report = eval_all(BUFFER);
+    let spans: Vec<_> = report.forms.iter().map(|form| form.span).collect();
+    assert_eq!(&BUFFER[spans[1].start..spans[1].end], "(sq 4)");
+    assert_eq!(&BUFFER[spans[2].start..spans[2].end], "(car 5)");
+    assert_eq!(
+        spans[2],
+        SourceSpan {
+            start: 47,
+            end: 54,
+            line: 3,
+            column: 8
+        }
+    );
+    assert_eq!((spans[3].line, spans[3].column), (4, 3));
+}
+
+#[test]
+fn test_eval_all_stops_at_parse_error() {
+    let report = eval_all("(+ 1 2)\n(+ 3");
+    assert_eq!(report.forms.len(), 2);
+    let error = report.forms[1].result.as_ref().unwrap_err();
+    assert_eq!(error.stage, ErrorStage::Parse);
+    assert_eq!(report.forms[1].engine, None);
+    assert_eq!(report.forms[1].span.line, 2);
+}
+
+#[test]
+fn test_eval_all_invalid_token_after_form() {
+    let report = eval_all("(+ 1 2) ; sum\n  #bad");
+    assert_eq!(report.forms.len(), 2);
+    assert!(report.forms[0].result.is_ok());
+    let error = report.forms[1].result.as_ref().unwrap_err();
+    assert_eq!(error.stage, ErrorStage::Parse);
+    assert_eq!(
+        (report.forms[1].span.line, report.forms[1].span.column),
+        (2, 3)
+    );
+}
+
+#[test]
+fn test_eval_all_empty_source() {
+    assert!(eval_all("  ; nothing here\n").forms.is_empty());
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_eval_report_json_round_trip() {
+    let report = eval_all(BUFFER);
+    let json = serde_json::to_string(&report).unwrap();
+    let decoded: EvalReport = serde_json::from_str(&json).unwrap();
+
+    assert_eq!(decoded.forms.len(), report.forms.len());
+    for (original, copy) in report.forms.iter().zip(&decoded.forms) {
+        assert_eq!(copy.index, original.index);
+        assert_eq!(copy.span, original.span);
+        assert_eq!(copy.engine, original.engine);
+        assert_eq!(copy.duration, original.duration);
+        assert_eq!(copy.result, original.result);
+        // Values stay on the Rust side
+        assert!(copy.value.is_none());
+    }
+}
+
+/// A buffer of 50 independent definitions, one function built on `g10`,
+/// and a call to it.
+fn large_buffer(g10_body: &str) -> String {
+    let mut source = String::new();
+    for i in 0..50 {
+        let body = if i == 10 {
+            g10_body.to_string()
+        } else {
+            format!("(+ x {i})")
+        };
+        source.push_str(&format!("(label g{i} (lambda (x) {body}))\n"));
+    }
+    source.push_str("(label h (lambda (x) (* (g10 x) 2)))\n(h 1)\n(g3 1)\n");
+    source
+}
+
+fn evaluated_snippets(report: &EvalReport, source: &str) -> Vec<String> {
+    report
+        .evaluated()
+        .map(|form| source[form.span.start..form.span.end].to_string())
+        .collect()
+}
+
+#[test]
+fn test_eval_incremental_reevaluates_changed_form_and_dependents() {
+    let mut session = ReplSession::with_engine_factory(JitMode::Disabled, unreachable_engine);
+    let original = large_buffer("(+ x 10)");
+    let first = session.eval_incremental("buffer.lisp", &original);
+    assert_eq!(first.evaluated().count(), 53);
+    assert_eq!(first.forms[51].result.as_deref(), Ok("22"));
+
+    let edited = large_buffer("(+ x 100)");
+    let second = session.eval_incremental("buffer.lisp", &edited);
+    assert_eq!(second.forms.len(), 53);
+    assert_eq!(
+        evaluated_snippets(&second, &edited),
+        vec![
+            "(label g10 (lambda (x) (+ x 100)))",
+            "(label h (lambda (x) (* (g10 x) 2)))",
+            "(h 1)",
+        ]
+    );
+    assert_eq!(second.forms[51].result.as_deref(), Ok("202"));
+    // Cached forms keep their results at their new positions
+    assert!(second.forms[52].cached);
+    assert_eq!(second.forms[52].result.as_deref(), Ok("4"));
+}
+
+#[test]
+fn test_eval_incremental_ignores_layout_and_comments() {
+    let mut session = ReplSession::with_engine_factory(JitMode::Disabled, unreachable_engine);
+    session.eval_incremental("b", "(label a 1)\n(+ a 1)");
+    let report = session.eval_incremental("b", "; the value\n(label a\n  1)\n\n(+ a   1)");
+    assert_eq!(report.evaluated().count(), 0);
+    assert_eq!(report.forms[1].span.line, 5);
+}
+
+#[test]
+fn test_eval_incremental_retries_failed_and_removed() {
+    let mut session = ReplSession::with_engine_factory(JitMode::Disabled, unreachable_engine);
+    let report = session.eval_incremental("b", "(label a 1)\n(+ a b)");
+    assert!(report.forms[1].result.is_err());
+
+    // The failed form runs again once `b` is defined
+    let report = session.eval_incremental("b", "(label a 1)\n(label b 2)\n(+ a b)");
+    assert_eq!(report.evaluated().count(), 2);
+    assert_eq!(report.forms[2].result.as_deref(), Ok("3"));
+
+    // Removing a definition re-evaluates the forms that use it
+    let report = session.eval_incremental("b", "(label b 2)\n(+ a b)");
+    assert_eq!(report.evaluated().count(), 1);
+    assert!(!report.forms[1].cached);
+}
+
+#[test]
+fn test_eval_incremental_buffers_are_independent() {
+    let mut session = ReplSession::with_engine_factory(JitMode::Disabled, unreachable_engine);
+    session.eval_incremental("one", "(+ 1 2)");
+    assert_eq!(
+        session
+            .eval_incremental("two", "(+ 1 2)")
+            .evaluated()
+            .count(),
+        1
+    );
+    assert_eq!(
+        session
+            .eval_incremental("one", "(+ 1 2)")
+            .evaluated()
+            .count(),
+        0
+    );
+}
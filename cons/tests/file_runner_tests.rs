@@ -12,14 +12,20 @@ fn cons_binary() -> PathBuf {
     path
 }
 
-// Helper function to create a temp file and run it
+// Helper function to create a temp file and run it, echoing the last value
 fn run_lisp_file(content: &str) -> Result<String, String> {
+    run_lisp_file_with_args(content, &["--print-last"])
+}
+
+// Helper function to create a temp file and run it with extra arguments
+fn run_lisp_file_with_args(content: impl AsRef<[u8]>, args: &[&str]) -> Result<String, String> {
     let temp_dir = std::env::temp_dir();
     let file_path = temp_dir.join(format!("test_{}.lisp", rand::random::<u32>()));
 
     fs::write(&file_path, content).map_err(|e| e.to_string())?;
 
     let output = Command::new(cons_binary())
+        .args(args)
         .arg(&file_path)
         .output()
         .map_err(|e| e.to_string())?;
@@ -46,6 +52,12 @@ fn test_multiple_expressions() {
     assert_eq!(result.unwrap(), "(5 . 6)");
 }
 
+#[test]
+fn test_several_expressions_on_one_line() {
+    let result = run_lisp_file("(def a 1) (def b (+ a 1)) (print a) (print b) (cons a b)");
+    assert_eq!(result.unwrap(), "12(1 . 2)");
+}
+
 #[test]
 fn test_string_with_parentheses() {
     let result = run_lisp_file(
@@ -90,8 +102,46 @@ fn test_comment_only_file() {
 ; Nothing else
 "#,
     );
-    // Comment-only files strip to empty, which returns "No expression found" error
-    assert!(result.is_err());
+    // A file with no forms runs and does nothing
+    assert_eq!(result.unwrap(), "");
+}
+
+#[test]
+fn test_crlf_line_endings() {
+    let result = run_lisp_file("; header\r\n(label x 2) ; note\r\n(* x 21)\r\n");
+    assert_eq!(result.unwrap(), "42");
+}
+
+#[test]
+fn test_mixed_line_endings() {
+    // Old Mac \r, Windows \r\n and Unix \n in one file; the last comment has no terminator
+    let result = run_lisp_file("; a\r(label x 2)\r\n; b\n(+ x 1) ; last");
+    assert_eq!(result.unwrap(), "3");
+}
+
+#[test]
+fn test_crlf_inside_string_is_normalized() {
+    let result = run_lisp_file("(length (quote (\"a\r\nb\")))\r\n(println \"a\r\nb\")");
+    assert_eq!(result.unwrap(), "a\nb\nnil");
+}
+
+#[test]
+fn test_invalid_utf8_in_comment() {
+    let err = run_lisp_file_with_args(b"(+ 1 2)\r\n; caf\xe9\r\n(+ 3 4)\r\n", &[]).unwrap_err();
+    assert!(err.contains("invalid UTF-8 byte 0xE9 at line 2 (byte offset 14)"));
+    assert!(!err.contains("stream did not contain valid UTF-8"));
+}
+
+#[test]
+fn test_invalid_utf8_in_string_points_at_string_start() {
+    let err = run_lisp_file_with_args(b"(+ 1 2)\n\n(println \"caf\xe9\")\n", &[]).unwrap_err();
+    assert!(err.contains("invalid UTF-8 in string literal starting at line 3 (byte offset 18)"));
+}
+
+#[test]
+fn test_trailing_comment_without_newline() {
+    assert_eq!(run_lisp_file("(+ 1 2)\n; done").unwrap(), "3");
+    assert_eq!(run_lisp_file("(+ 1 2)\r\n; done\r\n").unwrap(), "3");
 }
 
 #[test]
@@ -151,6 +201,36 @@ r#"raw string with "quotes""#
     assert!(result.is_err());
 }
 
+#[test]
+fn test_comment_skips_side_effects() {
+    let content = "
+(println \"before\")
+(comment
+  (println \"inside\")
+  (no-such-function 1 2))
+(println \"after\")
+";
+    let result = run_lisp_file(content).unwrap();
+    assert_eq!(result, "before\nafter\nnil");
+}
+
+#[test]
+fn test_float_print_precision() {
+    let content = "
+(with-precision 3 (println 3.14159 -2.71828))
+(binding ((*print-float-digits* 2)) (println '(0.333 1)))
+(binding ((*print-float-digits* 3)) (write 3.14159))
+(println)
+(println 3.14159)
+(println ##NaN ##Inf ##-Inf)
+";
+    let result = run_lisp_file(content).unwrap();
+    assert_eq!(
+        result,
+        "3.142 -2.718\n(0.33 1)\n3.14159\n3.14159\n##NaN ##Inf ##-Inf\nnil"
+    );
+}
+
 #[test]
 fn test_quoted_expression_with_parens() {
     let content = "
@@ -426,3 +506,272 @@ fn test_recursive_deep_nesting() {
     );
     assert_eq!(result.unwrap(), "0");
 }
+
+#[test]
+fn test_no_jit_flag_runs_file() {
+    let result = run_lisp_file_with_args("(+ 40 2)", &["--no-jit", "--print-last"]);
+    assert_eq!(result.unwrap(), "42");
+}
+
+#[test]
+fn test_jit_and_no_jit_conflict() {
+    let output = Command::new(cons_binary())
+        .arg("--jit")
+        .arg("--no-jit")
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("cannot be used together"));
+}
+
+// ============================================================================
+// Last Value and Error Context Tests
+// ============================================================================
+
+#[test]
+fn test_last_value_not_printed_by_default() {
+    let result = run_lisp_file_with_args("(cons 1 2)\n(+ 1 2)", &[]);
+    assert_eq!(result.unwrap(), "");
+}
+
+#[test]
+fn test_trailing_println_has_no_stray_nil() {
+    let result = run_lisp_file_with_args(r#"(println "done")"#, &[]);
+    assert_eq!(result.unwrap(), "done");
+}
+
+#[test]
+fn test_trailing_definition_prints_nothing() {
+    let result = run_lisp_file_with_args("(label f (lambda (x) x))", &[]);
+    assert_eq!(result.unwrap(), "");
+}
+
+#[test]
+fn test_print_last_flag() {
+    let result = run_lisp_file_with_args("(label f (lambda (x) x))\n(f 7)", &["--print-last"]);
+    assert_eq!(result.unwrap(), "7");
+}
+
+#[test]
+fn test_error_reports_failing_form() {
+    let result = run_lisp_file_with_args(
+        r#"
+(label f (lambda (x) x))
+(f 1)
+(car 42)
+(f 2)
+"#,
+        &[],
+    );
+    let err = result.unwrap_err();
+    assert!(err.contains("form 3"), "missing form index: {err}");
+    assert!(err.contains("(car 42)"), "missing form snippet: {err}");
+    assert!(err.contains("line 4, column 1"), "missing location: {err}");
+}
+
+#[test]
+fn test_unclosed_paren_reports_location() {
+    let err = run_lisp_file(
+        "(+ 1 2)
+
+  (cons 1
+  (car '(1))",
+    )
+    .unwrap_err();
+    assert!(
+        err.contains("Unclosed parenthesis starting at line 3, column 3"),
+        "{err}"
+    );
+}
+
+#[test]
+fn test_error_output_before_failure_is_kept() {
+    let temp_dir = std::env::temp_dir();
+    let file_path = temp_dir.join(format!("test_{}.lisp", rand::random::<u32>()));
+    fs::write(&file_path, "(println \"before\")\n(car 42)").unwrap();
+
+    let output = Command::new(cons_binary())
+        .arg(&file_path)
+        .output()
+        .unwrap();
+    fs::remove_file(&file_path).ok();
+
+    assert!(!output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "before");
+    assert!(String::from_utf8_lossy(&output.stderr).contains("form 2"));
+}
+
+#[test]
+fn test_time_writes_to_stderr() {
+    let temp_dir = std::env::temp_dir();
+    let file_path = temp_dir.join(format!("test_{}.lisp", rand::random::<u32>()));
+    fs::write(&file_path, "(print (time (+ 1 2)))").unwrap();
+
+    let output = Command::new(cons_binary())
+        .arg(&file_path)
+        .output()
+        .unwrap();
+    fs::remove_file(&file_path).ok();
+
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "3");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.starts_with("Elapsed time: ") && stderr.trim_end().ends_with(" ms"),
+        "{stderr}"
+    );
+}
+
+#[test]
+fn test_eval_prints_the_last_value() {
+    for flags in [&[][..], &["--jit"]] {
+        let output = Command::new(cons_binary())
+            .args(flags)
+            .args(["-e", "(def x 6)", "-e", "(print 'a) (* x 7)"])
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{flags:?}");
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "a42\n");
+    }
+}
+
+#[test]
+fn test_eval_error_exits_with_one() {
+    let output = Command::new(cons_binary())
+        .args(["-e", "(car 1)"])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    assert!(output.stdout.is_empty());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("<eval>"));
+
+    let output = Command::new(cons_binary())
+        .args(["-e", "(+ 1"])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(1));
+}
+
+// Runs the cons binary with `input` piped to stdin
+fn run_piped(flags: &[&str], input: &str) -> std::process::Output {
+    use std::io::Write;
+    use std::process::Stdio;
+
+    let mut child = Command::new(cons_binary())
+        .args(flags)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(input.as_bytes())
+        .unwrap();
+    child.wait_with_output().unwrap()
+}
+
+#[test]
+fn test_piped_stdin_runs_like_a_file() {
+    let output = run_piped(
+        &[],
+        "(println (+ 1 2))\n(label main (lambda (args) (println 'main)))",
+    );
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "3\nmain\n");
+
+    let output = run_piped(&["--print-last"], "(+ 1 2) (* 6 7)");
+    assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "42");
+
+    let output = run_piped(&[], "(println 1)\n(car 1)");
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "1\n");
+    assert!(String::from_utf8_lossy(&output.stderr).contains("<stdin>, line 2"));
+}
+
+// ============================================================================
+// Script main Tests
+// ============================================================================
+
+const SCRIPT_WITH_MAIN: &str = r#"
+(label main (lambda (args) (cond ((println (str-join "," args)) nil) (t (length args)))))
+(println "loaded")
+"#;
+
+// Runs a script with arguments after its file name, returning stdout
+fn run_script(flags: &[&str], script_args: &[&str]) -> String {
+    let temp_dir = std::env::temp_dir();
+    let file_path = temp_dir.join(format!("test_{}.lisp", rand::random::<u32>()));
+    fs::write(&file_path, SCRIPT_WITH_MAIN).unwrap();
+
+    let output = Command::new(cons_binary())
+        .args(flags)
+        .arg(&file_path)
+        .args(script_args)
+        .output()
+        .unwrap();
+    fs::remove_file(&file_path).ok();
+
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8_lossy(&output.stdout).trim().to_string()
+}
+
+#[test]
+fn test_shebang_script_sees_its_args() {
+    let script = "#!/usr/bin/env cons\n(println (str-join \",\" *args*))\n(println (length (command-line-args)))";
+    // Without arguments *args* is empty
+    for flags in [&[][..], &["--jit"]] {
+        assert_eq!(
+            run_lisp_file_with_args(script, flags).unwrap(),
+            "0",
+            "{flags:?}"
+        );
+    }
+
+    let temp_dir = std::env::temp_dir();
+    let file_path = temp_dir.join(format!("test_{}.lisp", rand::random::<u32>()));
+    fs::write(&file_path, script).unwrap();
+    let output = Command::new(cons_binary())
+        .arg(&file_path)
+        .args(["one", "--two", "three four"])
+        .output()
+        .unwrap();
+    fs::remove_file(&file_path).ok();
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "one,--two,three four\n3\n"
+    );
+}
+
+#[test]
+fn test_main_called_with_script_args() {
+    assert_eq!(run_script(&[], &["a", "b"]), "loaded\na,b");
+    // Options after the file name belong to the script
+    assert_eq!(
+        run_script(&["--print-last"], &["--print-last", "x"]),
+        "loaded\n--print-last,x\n2"
+    );
+    assert_eq!(run_script(&["--jit"], &["a"]), "loaded\na");
+}
+
+#[test]
+fn test_no_main_flag_skips_main() {
+    assert_eq!(run_script(&["--no-main"], &["a"]), "loaded");
+    assert_eq!(
+        run_script(&["--no-main", "--print-last"], &[]),
+        "loaded\nnil"
+    );
+}
+
+#[test]
+fn test_main_error_is_reported() {
+    let err = run_lisp_file_with_args("(label main (lambda (args) (car args)))", &[]).unwrap_err();
+    assert!(err.contains("main: car"), "{err}");
+}
@@ -0,0 +1,16 @@
+; Fixture for cons doc: two documented definitions, one undocumented,
+; one private helper and a top-level call that fails if it is run.
+
+;; Square a number.
+(label square (lambda (x) (* x x)))
+
+;; Connect to a database.
+;; Port and timeout have defaults.
+(label connect (lambda (host &key (port 5432) (timeout 30))
+  (list host port timeout)))
+
+(label %helper (lambda () nil))
+
+(defmacro unless (test body) (list 'cond (list test nil) (list t body)))
+
+(car (quote not-a-list))
@@ -0,0 +1,11 @@
+; Fixture for JIT run reports: two definitions, two forms the JIT
+; compiles, and one call the JIT declines, applying a label-defined function.
+(label square (lambda (x) (* x x)))
+
+(defmacro twice (x) (list '+ x x))
+
+(+ 1 2)
+
+(twice 21)
+
+(apply square '(5))
@@ -0,0 +1,127 @@
+//! Golden-file tests: run every program in `tests/programs` through the `cons`
+//! binary and compare stdout, stderr and exit code with the expected files.
+//! See `tests/programs/README.md` for the file layout.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+// Helper function to get the path to the cons binary
+fn cons_binary() -> PathBuf {
+    let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    path.pop(); // Go to workspace root
+    path.push("target");
+    path.push("debug");
+    path.push("cons");
+    path
+}
+
+fn programs_dir() -> PathBuf {
+    let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    path.push("tests");
+    path.push("programs");
+    path
+}
+
+fn read_optional(path: &Path) -> Option<String> {
+    fs::read_to_string(path).ok()
+}
+
+/// Run one program with the given extra flags, returning a description of
+/// every mismatch with its expected files.
+fn check_program(program: &Path, flags: &[String]) -> Vec<String> {
+    let name = program.file_name().unwrap().to_string_lossy();
+    let label = if flags.is_empty() {
+        name.to_string()
+    } else {
+        format!("{name} [{}]", flags.join(" "))
+    };
+
+    let expected_out = read_optional(&program.with_extension("out"))
+        .unwrap_or_else(|| panic!("{name}: missing .out file"));
+    let expected_err = read_optional(&program.with_extension("err"));
+    let expected_exit: i32 = read_optional(&program.with_extension("exit"))
+        .map(|s| s.trim().parse().expect("invalid .exit file"))
+        .unwrap_or(0);
+
+    let argv: Vec<String> = read_optional(&program.with_extension("argv"))
+        .map(|s| s.split_whitespace().map(String::from).collect())
+        .unwrap_or_default();
+
+    let output = Command::new(cons_binary())
+        .args(flags)
+        .arg(program)
+        .args(argv)
+        .output()
+        .expect("failed to run cons binary");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let mut failures = Vec::new();
+
+    if stdout != expected_out {
+        failures.push(format!(
+            "{label}: stdout differs\n--- expected\n{expected_out}--- actual\n{stdout}---"
+        ));
+    }
+
+    match expected_err {
+        Some(patterns) => {
+            for pattern in patterns.lines().filter(|l| !l.trim().is_empty()) {
+                if !stderr.contains(pattern) {
+                    failures.push(format!(
+                        "{label}: stderr does not contain {pattern:?}\n--- stderr\n{stderr}---"
+                    ));
+                }
+            }
+        }
+        None if !stderr.is_empty() => {
+            failures.push(format!(
+                "{label}: unexpected stderr (add a .err file if intended)\n--- stderr\n{stderr}---"
+            ));
+        }
+        None => {}
+    }
+
+    if output.status.code() != Some(expected_exit) {
+        failures.push(format!(
+            "{label}: expected exit code {expected_exit}, got {:?}",
+            output.status.code()
+        ));
+    }
+
+    failures
+}
+
+#[test]
+fn test_golden_programs() {
+    let mut programs: Vec<PathBuf> = fs::read_dir(programs_dir())
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "lisp"))
+        .collect();
+    programs.sort();
+    assert!(!programs.is_empty(), "no golden programs found");
+
+    let mut failures = Vec::new();
+    for program in &programs {
+        let flags: Vec<String> = read_optional(&program.with_extension("args"))
+            .map(|s| s.split_whitespace().map(String::from).collect())
+            .unwrap_or_default();
+
+        failures.extend(check_program(program, &flags));
+
+        if program.with_extension("jit").exists() {
+            let mut jit_flags = vec!["--jit".to_string()];
+            jit_flags.extend(flags);
+            failures.extend(check_program(program, &jit_flags));
+        }
+    }
+
+    assert!(
+        failures.is_empty(),
+        "{} golden mismatch(es):\n\n{}",
+        failures.len(),
+        failures.join("\n\n")
+    );
+}
@@ -1,4 +1,5 @@
-use cons::{eval, register_stdlib};
+use cons::{eval, register_stdlib, run, run_with};
+use consair::language::{PrintLimits, set_print_limits};
 use consair::{Environment, parse};
 
 fn eval_expr(expr: &str) -> String {
@@ -21,6 +22,378 @@ fn test_quote() {
     assert_eq!(eval_expr("'(1 2 3)"), "(1 2 3)");
 }
 
+#[test]
+fn test_dotted_pairs() {
+    assert_eq!(eval_expr("'(1 . 2)"), "(1 . 2)");
+    assert_eq!(eval_expr("'(1 2 . 3)"), "(1 2 . 3)");
+    assert_eq!(eval_expr("(cdr '(1 . 2))"), "2");
+    assert_eq!(eval_expr("(cdr (cdr '(1 2 . 3)))"), "3");
+    assert_eq!(eval_expr("(equal? '(1 2 . 3) (cons 1 (cons 2 3)))"), "t");
+    assert_eq!(eval_expr("(equal? '(1 . 2) '(1 . 3))"), "nil");
+    assert_eq!(eval_expr("(equal? '(1 . 2) '(1 2))"), "nil");
+    assert_eq!(eval_expr("(alist-get '((a . 1) (b . 2)) 'b)"), "2");
+}
+
+#[test]
+fn test_improper_lists_are_not_walked_as_sequences() {
+    for (expr, message) in [
+        (
+            "(length '(1 . 2))",
+            "length: expected a proper list, got (1 . 2)",
+        ),
+        ("(length 5)", "length: expected a proper list, got 5"),
+        ("(%count '(1 2 . 3))", "%count: cannot count (1 2 . 3)"),
+        (
+            "(%seq '(1 . 2))",
+            "%seq: expected a proper list, got (1 . 2)",
+        ),
+        ("(%rest '(1 2 . 3))", "%rest: expected a proper list"),
+        ("(map (lambda (x) x) '(1 . 2))", "map:"),
+    ] {
+        let result = eval_expr(expr);
+        assert!(result.contains(message), "{expr}: {result}");
+    }
+    assert_eq!(eval_expr("(length '(1 2))"), "2");
+    assert_eq!(eval_expr("(%first '(1 . 2))"), "1");
+}
+
+#[test]
+fn test_keywords_self_evaluate() {
+    assert_eq!(eval_expr(":append"), ":append");
+    assert_eq!(eval_expr("(quote :a)"), ":a");
+    assert_eq!(eval_expr("(eq :a :a)"), "t");
+    assert_eq!(eval_expr("(eq :a :b)"), "nil");
+    assert_eq!(eval_expr("(cons :a 1)"), "(:a . 1)");
+    assert!(eval_expr(":").starts_with("Parse error"));
+}
+
+#[test]
+fn test_unexpected_character_is_parse_error() {
+    assert_eq!(eval_expr("(quote &rest)"), "&rest");
+    assert!(eval_expr("(quote (a ^ b))").starts_with("Parse error"));
+}
+
+#[test]
+fn test_nth_default_option() {
+    assert_eq!(eval_expr("(nth '(a b c) 1)"), "b");
+    assert_eq!(eval_expr("(nth '(a b c) 5)"), "nil");
+    assert_eq!(eval_expr("(nth '(a b c) 5 :default 'z)"), "z");
+    assert_eq!(eval_expr("(nth '(a b c) 0 :default 'z)"), "a");
+    assert!(eval_expr("(nth '(a b c) 5 :defualt 'z)").contains("did you mean :default?"));
+}
+
+#[test]
+fn test_comment_returns_nil() {
+    assert_eq!(eval_expr("(comment)"), "nil");
+    assert_eq!(
+        eval_expr("(comment (undefined-fn 1 2) unbound-symbol)"),
+        "nil"
+    );
+    assert_eq!(eval_expr("(cons 1 (comment 2))"), "(1)");
+}
+
+#[test]
+fn test_time_returns_the_value() {
+    assert_eq!(eval_expr("(time (+ 1 2))"), "3");
+    assert_eq!(eval_expr("(time '(a b))"), "(a b)");
+    assert!(eval_expr("(time)").starts_with("Error:"));
+    assert!(eval_expr("(time 1 2)").starts_with("Error:"));
+}
+
+#[test]
+fn test_print_float_digits_binding() {
+    assert_eq!(eval_expr("*print-float-digits*"), "nil");
+    assert_eq!(eval_expr("(with-precision 3 *print-float-digits*)"), "3");
+    assert!(eval_expr("(binding ((x 1)) x)").contains("binding: x is not a dynamic variable"));
+    assert!(eval_expr("(with-precision -1 1)").contains("non-negative integer"));
+
+    // The previous precision is restored when the body fails
+    assert!(eval_expr("(with-precision 2 unbound-symbol)").contains("Unbound symbol"));
+    assert_eq!(consair::numeric::print_float_digits(), None);
+}
+
+#[test]
+fn test_print_limits() {
+    assert_eq!(eval_expr("*print-length*"), "nil");
+    assert_eq!(
+        eval_expr("(binding ((*print-depth* 4)) *print-depth*)"),
+        "4"
+    );
+    assert!(eval_expr("(binding ((*print-total* 'a)) 1)").contains("non-negative integer"));
+
+    let previous = set_print_limits(PrintLimits {
+        length: Some(2),
+        depth: Some(2),
+        total: None,
+    });
+    assert_eq!(eval_expr("'(1 2 3 4)"), "(1 2 … 2 more)");
+    assert_eq!(eval_expr("<<1 2 3>>"), "<<1 2 … 1 more>>");
+    assert_eq!(eval_expr("'(1 (2 (3)))"), "(1 (2 (…)))");
+    assert_eq!(eval_expr("(cons 1 (cons 2 3))"), "(1 2 . 3)");
+    // write-str ignores the limits
+    assert_eq!(
+        eval_expr("(write-str '(1 (2 (3 4 5))))"),
+        "\"(1 (2 (3 4 5)))\""
+    );
+
+    set_print_limits(PrintLimits {
+        total: Some(5),
+        ..PrintLimits::UNLIMITED
+    });
+    assert_eq!(
+        eval_expr("'(1 2 3 4 5)"),
+        "(1 2 … (truncated at 5 characters)"
+    );
+    set_print_limits(previous);
+}
+
+#[test]
+fn test_symbolic_float_values() {
+    assert_eq!(eval_expr("##Inf"), "##Inf");
+    assert_eq!(eval_expr("##-Inf"), "##-Inf");
+    assert_eq!(eval_expr("##NaN"), "##NaN");
+    assert_eq!(eval_expr("'(1.5 ##Inf)"), "(1.5 ##Inf)");
+    assert!(eval_expr("##Infinity").starts_with("Parse error"));
+}
+
+#[test]
+fn test_symbolic_float_values_round_trip() {
+    // Overflow and Inf arithmetic print in a form the reader accepts
+    assert_eq!(eval_expr("(* 1e308 10.0)"), "##Inf");
+    assert_eq!(eval_expr("(* -1e308 10.0)"), "##-Inf");
+    assert_eq!(eval_expr("(- ##Inf ##Inf)"), "##NaN");
+    assert_eq!(eval_expr("(+ ##-Inf 1)"), "##-Inf");
+}
+
+#[test]
+fn test_nan_comparisons() {
+    assert_eq!(eval_expr("(= ##NaN ##NaN)"), "nil");
+    assert_eq!(eval_expr("(< ##NaN 1.0)"), "nil");
+    assert_eq!(eval_expr("(> ##NaN 1.0)"), "nil");
+    assert_eq!(eval_expr("(= ##Inf ##Inf)"), "t");
+    assert_eq!(eval_expr("(< ##-Inf -1e300)"), "t");
+}
+
+#[test]
+fn test_nan_rejected_as_map_key() {
+    assert!(eval_expr("(%hash-map ##NaN 1)").contains("NaN cannot be used as a map key"));
+    assert!(eval_expr("(%assoc (%hash-map) ##NaN 1)").contains("NaN cannot be used"));
+    assert!(eval_expr("(%conj (%hash-set) ##NaN)").contains("NaN cannot be used"));
+    assert!(eval_expr("(%hash-set 1 ##NaN)").contains("%hash-set: NaN cannot be used"));
+    assert_eq!(eval_expr("(%get (%hash-map ##Inf 1) ##Inf)"), "1");
+}
+
+#[test]
+fn test_let() {
+    assert_eq!(eval_expr("(let ((x 1) (y 2)) (+ x y))"), "3");
+    assert_eq!(eval_expr("(let ((x 2) (y (* x 10))) (list x y))"), "(2 20)");
+    assert_eq!(eval_expr("(let () 'empty)"), "empty");
+    // Several body forms run like a do, and an empty body is nil
+    assert_eq!(
+        eval_expr("(let ((r (ref 0))) (swap! r + 1) (swap! r + 10) @r)"),
+        "11"
+    );
+    assert_eq!(eval_expr("(let ((x 1)) (str x) x)"), "1");
+    assert_eq!(eval_expr("(let ((x 1)))"), "nil");
+    assert_eq!(eval_expr("(let ())"), "nil");
+    assert_eq!(
+        eval_expr("((lambda (x) (list (let ((x (+ x 1))) x) x)) 5)"),
+        "(6 5)"
+    );
+    assert!(eval_expr("(let (x 1) x)").contains("let: expected (name value), got x"));
+    assert!(eval_expr("(let ((1 2)) 1)").contains("binding name must be a symbol"));
+}
+
+#[test]
+fn test_let_body_is_tail_position() {
+    // Far deeper than the interpreter's recursion limit, so the let body must be a tail call
+    assert_eq!(
+        eval_expr(
+            "((label count (lambda (n acc) (let ((m (- n 1))) \
+             (cond ((= n 0) acc) (t (count m (+ acc 1))))))) 50000 0)"
+        ),
+        "50000"
+    );
+}
+
+const GREETER: &str = "(label greet (lambda () 'hello)) (label call (lambda () (greet)))";
+
+#[test]
+fn test_with_redefs() {
+    let mut env = Environment::new();
+    register_stdlib(&mut env);
+    run_with(&mut env, GREETER).unwrap();
+    let mut eval_str = |source: &str| run_with(&mut env, source).map(|v| v.to_string());
+
+    // Functions defined earlier see the new value, and the old one comes back
+    assert_eq!(
+        eval_str("(with-redefs ((greet (lambda () 'fake))) (call))").unwrap(),
+        "fake"
+    );
+    assert_eq!(eval_str("(call)").unwrap(), "hello");
+    assert_eq!(
+        eval_str("(with-redefs ((now (lambda () 1234567890))) (now))").unwrap(),
+        "1234567890"
+    );
+    assert_eq!(
+        eval_str("(with-redefs ((greet (lambda () 'a))) (greet) (list (call) 'b))").unwrap(),
+        "(a b)"
+    );
+}
+
+#[test]
+fn test_nested_with_redefs() {
+    let source = format!(
+        "{GREETER} \
+         (with-redefs ((greet (lambda () 'outer))) \
+           (list (call) (with-redefs ((greet (lambda () 'inner))) (call)) (call)))"
+    );
+    assert_eq!(run(&source).unwrap().to_string(), "(outer inner outer)");
+}
+
+#[test]
+fn test_with_redefs_restores_after_error() {
+    let mut env = Environment::new();
+    register_stdlib(&mut env);
+    run_with(&mut env, GREETER).unwrap();
+    let err = run_with(
+        &mut env,
+        "(with-redefs ((greet (lambda () 'fake)) (call (lambda () 'also-fake))) (car 1))",
+    );
+    assert!(err.is_err());
+    assert_eq!(run_with(&mut env, "(call)").unwrap().to_string(), "hello");
+}
+
+#[test]
+fn test_with_redefs_errors() {
+    assert_eq!(
+        run("(with-redefs ((no-such-fn 1)) 1)").unwrap_err().code(),
+        "E001_UNBOUND_SYMBOL"
+    );
+    assert!(
+        eval_expr("(with-redefs ((quote 1)) 1)")
+            .contains("with-redefs: cannot redefine special form quote")
+    );
+    assert!(
+        run("(define-constant limit 3) (with-redefs ((limit 4)) limit)")
+            .unwrap_err()
+            .to_string()
+            .contains("cannot redefine constant limit")
+    );
+}
+
+#[test]
+fn test_do() {
+    assert_eq!(eval_expr("(do 1 2 3)"), "3");
+    assert_eq!(eval_expr("(do)"), "nil");
+    assert_eq!(eval_expr("(do (quote only))"), "only");
+
+    // Earlier forms run for their effects, in order
+    let mut env = Environment::new();
+    register_stdlib(&mut env);
+    let result = run_with(
+        &mut env,
+        "(label log (quote ()))
+         (do (label log (cons 1 log)) (label log (cons 2 log)) log)",
+    );
+    assert_eq!(result.unwrap().to_string(), "(2 1)");
+}
+
+#[test]
+fn test_lambda_with_several_body_forms() {
+    assert_eq!(eval_expr("((lambda (x) (+ x 100) (* x x)) 7)"), "49");
+    assert_eq!(eval_expr("((lambda (x)) 7)"), "nil");
+    // Earlier forms can define names for later ones
+    assert_eq!(eval_expr("((lambda (x) (label y (* x 2)) (+ y 1)) 4)"), "9");
+}
+
+#[test]
+fn test_with_sandbox() {
+    let mut env = Environment::new();
+    register_stdlib(&mut env);
+    run_with(&mut env, GREETER).unwrap();
+    let mut eval_str = |source: &str| run_with(&mut env, source).map(|v| v.to_string());
+
+    // Definitions are visible inside the sandbox and gone afterwards. They
+    // shadow outer ones for code in the sandbox only; `call` was defined
+    // outside, so it still sees the outer `greet`.
+    assert_eq!(
+        eval_str("(with-sandbox (label scratch 41) (label greet (lambda () 'fake)) (list (+ scratch 1) (greet) (call)))")
+            .unwrap(),
+        "(42 fake hello)"
+    );
+    assert_eq!(eval_str("(greet)").unwrap(), "hello");
+    assert_eq!(
+        eval_str("scratch").unwrap_err().code(),
+        "E001_UNBOUND_SYMBOL"
+    );
+    assert!(eval_str("(with-sandbox (defmacro m () 1)) (m)").is_err());
+    assert_eq!(eval_str("(with-sandbox)").unwrap(), "nil");
+}
+
+#[test]
+fn test_nested_with_sandbox() {
+    let source = format!(
+        "{GREETER} \
+         (list \
+           (with-sandbox \
+             (label greet (lambda () 'outer)) \
+             (list (greet) (with-sandbox (label greet (lambda () 'inner)) (greet)) (greet))) \
+           (greet))"
+    );
+    assert_eq!(
+        run(&source).unwrap().to_string(),
+        "((outer inner outer) hello)"
+    );
+}
+
+#[test]
+fn test_with_sandbox_below_the_top_level() {
+    let mut env = Environment::new();
+    register_stdlib(&mut env);
+    let mut eval_str = |source: &str| run_with(&mut env, source).map(|v| v.to_string());
+
+    // def defines globally and set! reaches outer scopes, from inside a let
+    // or a function body alike
+    assert_eq!(
+        eval_str("(do (def g 1) (let ((a 1)) (with-sandbox (def x 2) (set! g 10) (set! a 5) (list x g a))))")
+            .unwrap(),
+        "(2 10 5)"
+    );
+    assert_eq!(eval_str("x").unwrap_err().code(), "E001_UNBOUND_SYMBOL");
+    assert_eq!(eval_str("g").unwrap(), "1");
+
+    eval_str("(label try-it (lambda (n) (with-sandbox (def y n) (set! g n) (+ y g))))").unwrap();
+    assert_eq!(eval_str("(try-it 4)").unwrap(), "8");
+    assert_eq!(eval_str("y").unwrap_err().code(), "E001_UNBOUND_SYMBOL");
+    assert_eq!(eval_str("g").unwrap(), "1");
+
+    // The enclosing let sees its own binding restored, even after an error
+    assert_eq!(
+        eval_str("(let ((a 1)) (do (with-sandbox (set! a 5)) a))").unwrap(),
+        "1"
+    );
+    assert_eq!(
+        eval_str("(let ((a 1)) (do (try (with-sandbox (set! a 5) (car 1)) (catch e nil)) a))")
+            .unwrap(),
+        "1"
+    );
+}
+
+#[test]
+fn test_with_sandbox_restores_after_error() {
+    let mut env = Environment::new();
+    register_stdlib(&mut env);
+    run_with(&mut env, GREETER).unwrap();
+    let err = run_with(
+        &mut env,
+        "(with-sandbox (label greet (lambda () 'fake)) (label extra 1) (car 1))",
+    );
+    assert!(err.is_err());
+    assert_eq!(run_with(&mut env, "(call)").unwrap().to_string(), "hello");
+    assert!(run_with(&mut env, "extra").is_err());
+}
+
 #[test]
 fn test_atom() {
     assert_eq!(eval_expr("(atom 'a)"), "t");
@@ -38,6 +411,37 @@ fn test_eq() {
     assert_eq!(eval_expr("(eq nil nil)"), "t");
 }
 
+#[test]
+fn test_lambda_identity() {
+    let with_f = |body: &str| format!("((lambda (f) {body}) (lambda (x y) x))");
+    assert_eq!(eval_expr(&with_f("(eq f f)")), "t");
+    assert_eq!(eval_expr(&with_f("(identical? f f)")), "t");
+    assert_eq!(eval_expr(&with_f("(equal? f f)")), "t");
+    assert_eq!(eval_expr(&with_f("f")), "<lambda (x y)>");
+    assert_eq!(eval_expr(&with_f("(%count (%conj (%hash-set) f f))")), "1");
+    assert_eq!(eval_expr(&with_f("(%get (%hash-map f 1) f)")), "1");
+    assert_eq!(eval_expr("(eq (lambda (x) x) (lambda (x) x))"), "nil");
+    assert_eq!(eval_expr("(equal? (lambda (x) x) (lambda (x) x))"), "nil");
+    assert_eq!(eval_expr("(eq car car)"), "t");
+}
+
+#[test]
+fn test_identical_and_equal() {
+    assert_eq!(eval_expr("(equal? '(1 (2)) '(1 (2)))"), "t");
+    assert_eq!(eval_expr("(identical? '(1 (2)) '(1 (2)))"), "nil");
+    assert_eq!(eval_expr("((lambda (l) (identical? l l)) '(1 2))"), "t");
+    assert_eq!(eval_expr("(identical? 'a 'a)"), "t");
+}
+
+#[test]
+fn test_quote_returns_shared_structure() {
+    // Each evaluation of a quote yields the same list, not a copy
+    assert_eq!(
+        eval_expr("((lambda (f) (identical? (f) (f))) (lambda () '(1 2 3)))"),
+        "t"
+    );
+}
+
 #[test]
 fn test_car_cdr() {
     assert_eq!(eval_expr("(car '(1 2 3))"), "1");
@@ -60,6 +464,32 @@ fn test_cond() {
     assert_eq!(eval_expr("(cond (nil 'a) (t 'b))"), "b");
 }
 
+#[test]
+fn test_cond_clause_bodies() {
+    // Every body form runs in order and the last is the value
+    assert_eq!(
+        eval_expr("(let ((x 0)) (cond ((= x 0) (set! x 5) (+ x 1)) (t 'no)))"),
+        "6"
+    );
+    assert_eq!(
+        eval_expr("(let ((x nil)) (cond (t (set! x (cons 1 x)) (set! x (cons 2 x)) x)))"),
+        "(2 1)"
+    );
+    // A clause with only a test returns the test's value
+    assert_eq!(eval_expr("(cond (nil) ((+ 1 2)) (t 'no))"), "3");
+    assert_eq!(eval_expr("(cond ((= 1 2)))"), "nil");
+}
+
+#[test]
+fn test_cond_else() {
+    assert_eq!(eval_expr("(cond ((= 1 2) 'a) (else 'b))"), "b");
+    assert_eq!(eval_expr("(cond ((= 1 1) 'a) (else 'b))"), "a");
+    assert_eq!(eval_expr("(cond (else 1 2))"), "2");
+    assert_eq!(eval_expr("(cond (else))"), "t");
+    // else is only special as the last clause's test
+    assert!(eval_expr("(cond (else 1) (t 2))").contains("else must be the last clause"));
+}
+
 #[test]
 fn test_lambda() {
     assert_eq!(eval_expr("((lambda (x) x) 42)"), "42");
@@ -0,0 +1,173 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use cons::runner::run_file_jit_report;
+use cons::{StdlibConfig, eval, register_stdlib, register_stdlib_with};
+use consair::{Environment, parse};
+
+/// A temporary directory of source files, removed when dropped.
+struct Fixture {
+    dir: PathBuf,
+}
+
+impl Fixture {
+    fn new(files: &[(&str, &str)]) -> Self {
+        let dir = std::env::temp_dir().join(format!("load_test_{}", rand::random::<u32>()));
+        for (name, content) in files {
+            let path = dir.join(name);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        }
+        Fixture { dir }
+    }
+
+    fn path(&self, name: &str) -> PathBuf {
+        self.dir.join(name)
+    }
+}
+
+impl Drop for Fixture {
+    fn drop(&mut self) {
+        fs::remove_dir_all(&self.dir).ok();
+    }
+}
+
+fn eval_in(env: &mut Environment, src: &str) -> Result<String, String> {
+    eval(parse(src).unwrap(), env)
+        .map(|value| value.to_string())
+        .map_err(|e| e.to_string())
+}
+
+fn load_path(env: &mut Environment, path: &Path) -> Result<String, String> {
+    eval_in(env, &format!("(load {:?})", path.display().to_string()))
+}
+
+#[test]
+fn test_load_returns_last_value() {
+    let fixture = Fixture::new(&[("main.lisp", "(def x 20)\n(+ x 22)")]);
+    let mut env = Environment::new();
+    register_stdlib(&mut env);
+
+    assert_eq!(
+        load_path(&mut env, &fixture.path("main.lisp")).unwrap(),
+        "42"
+    );
+    assert_eq!(eval_in(&mut env, "x").unwrap(), "20");
+}
+
+#[test]
+fn test_nested_loads_resolve_relative_to_the_loading_file() {
+    let fixture = Fixture::new(&[
+        ("main.lisp", "(load \"lib/a.lisp\")\n(a-fn 1)"),
+        (
+            "lib/a.lisp",
+            "(load \"b.lisp\")\n(label a-fn (lambda (x) (b-fn (+ x 1))))",
+        ),
+        ("lib/b.lisp", "(label b-fn (lambda (x) (* x 10)))"),
+    ]);
+    let mut env = Environment::new();
+    register_stdlib(&mut env);
+
+    assert_eq!(
+        load_path(&mut env, &fixture.path("main.lisp")).unwrap(),
+        "20"
+    );
+    // The current file is restored once a load finishes
+    assert_eq!(env.current_file(), None);
+}
+
+#[test]
+fn test_require_loads_once() {
+    let fixture = Fixture::new(&[
+        (
+            "main.lisp",
+            "(require \"counter.lisp\")\n(require \"lib/../counter.lisp\")\n(list (require \"counter.lisp\") count)",
+        ),
+        ("counter.lisp", "(set! count (+ count 1))"),
+    ]);
+    let mut env = Environment::new();
+    register_stdlib(&mut env);
+    eval_in(&mut env, "(def count 0)").unwrap();
+    fs::create_dir_all(fixture.path("lib")).unwrap();
+
+    assert_eq!(
+        load_path(&mut env, &fixture.path("main.lisp")).unwrap(),
+        "(nil 1)"
+    );
+}
+
+#[test]
+fn test_circular_require_names_the_chain() {
+    let fixture = Fixture::new(&[
+        ("a.lisp", "(require \"b.lisp\")"),
+        ("b.lisp", "(require \"a.lisp\")"),
+    ]);
+    let mut env = Environment::new();
+    register_stdlib(&mut env);
+
+    let err = eval_in(
+        &mut env,
+        &format!(
+            "(require {:?})",
+            fixture.path("a.lisp").display().to_string()
+        ),
+    )
+    .unwrap_err();
+    let a = fixture.path("a.lisp").canonicalize().unwrap();
+    let b = fixture.path("b.lisp").canonicalize().unwrap();
+    let chain = format!("{} -> {} -> {}", a.display(), b.display(), a.display());
+    assert!(err.contains("Circular load"), "{err}");
+    assert!(err.contains(&chain), "{err}");
+}
+
+#[test]
+fn test_missing_file_names_the_resolved_path() {
+    let fixture = Fixture::new(&[("main.lisp", "(load \"missing.lisp\")")]);
+    let mut env = Environment::new();
+    register_stdlib(&mut env);
+
+    let err = load_path(&mut env, &fixture.path("main.lisp")).unwrap_err();
+    let missing = fixture.dir.join("missing.lisp");
+    assert!(err.contains(&missing.display().to_string()), "{err}");
+}
+
+#[test]
+fn test_error_in_loaded_file_names_it() {
+    let fixture = Fixture::new(&[("bad.lisp", "(def ok 1)\n(car 1)")]);
+    let mut env = Environment::new();
+    register_stdlib(&mut env);
+
+    let err = load_path(&mut env, &fixture.path("bad.lisp")).unwrap_err();
+    assert!(err.contains("bad.lisp"), "{err}");
+    assert!(err.contains("form 2"), "{err}");
+}
+
+#[test]
+fn test_running_a_file_loads_relative_to_it() {
+    let fixture = Fixture::new(&[
+        (
+            "main.lisp",
+            "(require \"lib.lisp\")\n(label main (lambda (args) (helper 2)))\n(helper 1)",
+        ),
+        (
+            "lib.lisp",
+            "(label helper (lambda (x) (* x 100)))\n(label main (lambda (args) (car 1)))",
+        ),
+    ]);
+    let report =
+        run_file_jit_report(&fixture.path("main.lisp").to_string_lossy(), Some(&[])).unwrap();
+
+    assert_eq!(report.forms.last().unwrap().result, "100");
+    // Only the file being run has its main called; lib.lisp's would fail
+    assert_eq!(report.main_result.as_deref(), Some("200"));
+}
+
+#[test]
+fn test_load_is_unbound_in_the_sandbox() {
+    let fixture = Fixture::new(&[("main.lisp", "1")]);
+    let mut env = Environment::new();
+    register_stdlib_with(&mut env, &StdlibConfig::sandboxed());
+
+    let err = load_path(&mut env, &fixture.path("main.lisp")).unwrap_err();
+    assert!(err.contains("load"), "{err}");
+}
@@ -0,0 +1,87 @@
+//! `dotimes` and `while`
+//!
+//! Both loops run iteratively in the interpreter, so a long loop uses no
+//! more stack than a short one.
+
+use cons::{register_stdlib, run_with};
+use consair::Environment;
+
+fn eval_str(source: &str) -> Result<String, String> {
+    let mut env = Environment::new();
+    register_stdlib(&mut env);
+    Ok(run_with(&mut env, source)?.to_string())
+}
+
+#[test]
+fn test_while_sums_one_to_n() {
+    let source = "(def i 1) (def total 0) \
+                  (while (<= i 100) \
+                    (set! total (+ total i)) \
+                    (set! i (+ i 1))) \
+                  total";
+    assert_eq!(eval_str(source).unwrap(), "5050");
+}
+
+#[test]
+fn test_while_returns_nil() {
+    assert_eq!(eval_str("(while nil 1)").unwrap(), "nil");
+}
+
+#[test]
+fn test_dotimes_counts_from_zero() {
+    let source = "(def seen nil) \
+                  (dotimes (i 4) (set! seen (cons i seen))) \
+                  seen";
+    assert_eq!(eval_str(source).unwrap(), "(3 2 1 0)");
+}
+
+#[test]
+fn test_dotimes_zero_iterations() {
+    let source = "(def runs 0) \
+                  (dotimes (i 0) (set! runs (+ runs 1))) \
+                  (dotimes (i -3) (set! runs (+ runs 1))) \
+                  runs";
+    assert_eq!(eval_str(source).unwrap(), "0");
+}
+
+#[test]
+fn test_dotimes_counter_is_scoped_to_loop() {
+    let source = "(def i 'outer) (dotimes (i 3) nil) i";
+    assert_eq!(eval_str(source).unwrap(), "outer");
+}
+
+#[test]
+fn test_nested_loops() {
+    let source = "(def pairs 0) \
+                  (dotimes (i 3) \
+                    (dotimes (j 4) \
+                      (set! pairs (+ pairs (* i j))))) \
+                  pairs";
+    // (0 + 1 + 2) * (0 + 1 + 2 + 3)
+    assert_eq!(eval_str(source).unwrap(), "18");
+
+    let source = "(def n 0) (def total 0) \
+                  (while (< n 3) \
+                    (dotimes (k n) (set! total (+ total 1))) \
+                    (set! n (+ n 1))) \
+                  total";
+    assert_eq!(eval_str(source).unwrap(), "3");
+}
+
+#[test]
+fn test_long_loop_does_not_grow_stack() {
+    let mut env = Environment::new();
+    register_stdlib(&mut env);
+    env.set_recursion_limit(100);
+    let source = "(def total 0) (dotimes (i 100000) (set! total (+ total 1))) total";
+    assert_eq!(run_with(&mut env, source).unwrap().to_string(), "100000");
+}
+
+#[test]
+fn test_dotimes_rejects_non_integer_count() {
+    let err = eval_str("(dotimes (i 'x) nil)").unwrap_err();
+    assert!(
+        err.contains("dotimes: expected integer count, got x"),
+        "{err}"
+    );
+}
@@ -1,5 +1,6 @@
+use cons::interpreter::{captured_symbols, record_expansions};
 use cons::{eval, register_stdlib};
-use consair::{Environment, parse};
+use consair::{Environment, Value, parse};
 
 fn eval_str(input: &str) -> Result<String, String> {
     let mut env = Environment::new();
@@ -44,6 +45,50 @@ fn test_quasiquote_with_unquote_splicing() {
     );
 }
 
+#[test]
+fn test_quasiquote_splices_into_surrounding_list() {
+    assert_eq!(
+        eval_str("`(1 ,(+ 1 1) ,@(quote (3 4)))").unwrap(),
+        "(1 2 3 4)"
+    );
+    assert_eq!(eval_str("`(1 ,@nil 2)").unwrap(), "(1 2)");
+}
+
+#[test]
+fn test_quasiquote_splicing_non_list_errors() {
+    let err = eval_str("`(1 ,@5)").unwrap_err();
+    assert!(
+        err.contains("unquote-splicing: expected list, got 5"),
+        "{err}"
+    );
+    assert!(eval_str("`(1 ,@(cons 2 3))").is_err());
+}
+
+#[test]
+fn test_quasiquote_dotted_tail() {
+    assert_eq!(eval_str("`(a b . c)").unwrap(), "(a b . c)");
+    assert_eq!(eval_str("(let ((x 5)) `(a . ,x))").unwrap(), "(a . 5)");
+    assert_eq!(
+        eval_str("(let ((xs '(1 2)) (x 3)) `(,@xs . ,x))").unwrap(),
+        "(1 2 . 3)"
+    );
+}
+
+#[test]
+fn test_quasiquote_atom_is_quote() {
+    assert_eq!(eval_str("`a").unwrap(), eval_str("'a").unwrap());
+    assert_eq!(eval_str("`5").unwrap(), "5");
+}
+
+#[test]
+fn test_nested_quasiquote_keeps_inner_level() {
+    // Only the doubly nested unquote belongs to the outer quasiquote
+    assert_eq!(
+        eval_str("`(a `(b ,(c ,(+ 1 2))))").unwrap(),
+        "(a (quasiquote (b (unquote (c 3)))))"
+    );
+}
+
 #[test]
 fn test_defmacro_when() {
     let code = r#"
@@ -51,7 +96,7 @@ fn test_defmacro_when() {
             (defmacro when (condition body)
                 `(cond (,condition ,body) (t nil))))
     "#;
-    assert_eq!(eval_str(code).unwrap(), "<macro>");
+    assert_eq!(eval_str(code).unwrap(), "<macro (condition body)>");
 }
 
 #[test]
@@ -140,3 +185,194 @@ fn test_macroexpand_full() {
     .unwrap();
     assert!(result.contains("cond"));
 }
+
+/// Three macros, each expanding to a call to the next
+const CHAIN: &[&str] = &[
+    "(defmacro my-unless (c body) `(my-when (not ,c) ,body))",
+    "(defmacro my-when (c body) `(my-if ,c ,body nil))",
+    "(defmacro my-if (c a b) `(cond (,c ,a) (t ,b)))",
+];
+
+#[test]
+fn test_macroexpand_all_expands_nested_forms() {
+    let mut inputs = CHAIN.to_vec();
+    inputs.push("(macroexpand-all '(my-unless x (my-when y 1)))");
+    assert_eq!(
+        eval_multi(&inputs).unwrap(),
+        "(cond ((not x) (cond (y 1) (t nil))) (t nil))"
+    );
+
+    // macroexpand only expands the head
+    inputs.push("(macroexpand '(my-unless x (my-when y 1)))");
+    assert_eq!(
+        eval_multi(&inputs).unwrap(),
+        "(cond ((not x) (my-when y 1)) (t nil))"
+    );
+}
+
+#[test]
+fn test_expansion_trace() {
+    let (result, steps) = record_expansions(|| {
+        eval_multi(&[CHAIN, &["(macroexpand-all '(my-unless x (my-when y 1)))"]].concat())
+    });
+    result.unwrap();
+    let steps: Vec<String> = steps.iter().map(|step| step.to_string()).collect();
+    assert_eq!(
+        steps,
+        [
+            "my-unless: (my-unless x (my-when y 1)) => (my-when (not x) (my-when y 1))",
+            "my-when: (my-when (not x) (my-when y 1)) => (my-if (not x) (my-when y 1) nil)",
+            "my-if: (my-if (not x) (my-when y 1) nil) => (cond ((not x) (my-when y 1)) (t nil))",
+            "my-when: (my-when y 1) => (my-if y 1 nil)",
+            "my-if: (my-if y 1 nil) => (cond (y 1) (t nil))",
+        ]
+    );
+}
+
+#[test]
+fn test_macro_rest_parameter() {
+    let when = "(defmacro my-when (test &rest body) `(cond (,test (do ,@body)) (t nil)))";
+    assert_eq!(
+        eval_multi(&[when, "(my-when t (label x 1) (+ x 41))"]).unwrap(),
+        "42"
+    );
+    assert_eq!(eval_multi(&[when, "(my-when nil 1 2)"]).unwrap(), "nil");
+    assert_eq!(
+        eval_multi(&[when, "(macroexpand-1 '(my-when (> n 0) (f n) (g n)))"]).unwrap(),
+        "(cond ((> n 0) (do (f n) (g n))) (t nil))"
+    );
+}
+
+#[test]
+fn test_macro_rest_parameter_with_no_arguments() {
+    let count = "(defmacro count-args (&rest forms) (length forms))";
+    assert_eq!(eval_multi(&[count, "(count-args)"]).unwrap(), "0");
+    assert_eq!(eval_multi(&[count, "(count-args a (b c))"]).unwrap(), "2");
+    assert_eq!(
+        eval_multi(&[count, "(macroexpand-1 '(count-args))"]).unwrap(),
+        "0"
+    );
+
+    let err = eval_multi(&["(defmacro two (a b &rest more) a)", "(two 1)"]).unwrap_err();
+    assert!(err.contains("two"), "{err}");
+}
+
+#[test]
+fn test_macro_with_several_body_forms() {
+    let code = [
+        "(defmacro swap-args (f a b) \"Call f with a and b swapped.\" (label ignored 1) `(,f ,b ,a))",
+        "(swap-args - 1 10)",
+    ];
+    assert_eq!(eval_multi(&code).unwrap(), "9");
+    assert_eq!(
+        eval_multi(&[code[0], "(macroexpand-1 '(swap-args cons x y))"]).unwrap(),
+        "(cons y x)"
+    );
+}
+
+#[test]
+fn test_macroexpand_trace_variable() {
+    assert_eq!(eval_str("*macroexpand-trace*").unwrap(), "nil");
+    assert_eq!(
+        eval_str("(binding ((*macroexpand-trace* t)) *macroexpand-trace*)").unwrap(),
+        "t"
+    );
+    assert_eq!(eval_str("*macroexpand-trace*").unwrap(), "nil");
+}
+
+#[test]
+fn test_runaway_expansion_is_stopped() {
+    let err = eval_multi(&["(defmacro forever () '(forever))", "(forever)"]).unwrap_err();
+    assert!(err.contains("Macro expansion did not finish"), "{err}");
+}
+
+// ============================================================================
+// Auto-gensym and Capture Warnings
+// ============================================================================
+
+const BAD_OR: &str = "(defmacro bad-or (a b) `(let ((result ,a)) (cond (result result) (t ,b))))";
+const GOOD_OR: &str =
+    "(defmacro good-or (a b) `(let ((result# ,a)) (cond (result# result#) (t ,b))))";
+
+#[test]
+fn test_auto_gensym_is_consistent_within_a_template() {
+    let expansion = eval_multi(&[
+        "(defmacro twice (e) `(let ((x# ,e)) (+ x# x#)))",
+        "(macroexpand-1 '(twice 5))",
+    ])
+    .unwrap();
+    let name = expansion
+        .strip_prefix("(let ((")
+        .and_then(|rest| rest.split(' ').next())
+        .unwrap();
+    assert!(name.starts_with("x__"), "{expansion}");
+    assert_eq!(expansion, format!("(let (({name} 5)) (+ {name} {name}))"));
+    assert_eq!(eval_str("(quote x#)").unwrap(), "x#");
+}
+
+#[test]
+fn test_auto_gensym_differs_between_templates() {
+    let names = eval_multi(&[
+        "(defmacro names () (list 'quote (list `x# `x#)))",
+        "(names)",
+    ])
+    .unwrap();
+    let names: Vec<&str> = names.trim_matches(['(', ')']).split(' ').collect();
+    assert_eq!(names.len(), 2);
+    assert_ne!(names[0], names[1]);
+
+    // Each expansion evaluates the template again
+    let first = eval_multi(&[GOOD_OR, "(macroexpand-1 '(good-or 1 2))"]).unwrap();
+    let second = eval_multi(&[GOOD_OR, "(macroexpand-1 '(good-or 1 2))"]).unwrap();
+    assert_ne!(first, second);
+}
+
+#[test]
+fn test_auto_gensym_prevents_capture() {
+    let call = "(let ((result 5)) (bad-or nil result))";
+    assert_eq!(eval_multi(&[BAD_OR, call]).unwrap(), "nil");
+    let call = "(let ((result 5)) (good-or nil result))";
+    assert_eq!(eval_multi(&[GOOD_OR, call]).unwrap(), "5");
+}
+
+#[test]
+fn test_captured_symbols() {
+    let mut env = Environment::new();
+    register_stdlib(&mut env);
+    for source in [
+        BAD_OR,
+        GOOD_OR,
+        "(defmacro my-let (name value body) `(let ((,name ,value)) ,body))",
+    ] {
+        eval(parse(source).unwrap(), &mut env).unwrap();
+    }
+    let check = |env: &mut Environment, macro_name: &str, call: &str| -> Vec<String> {
+        let Some(Value::Macro(cell)) = env.lookup(macro_name) else {
+            panic!("{macro_name} is not a macro");
+        };
+        let call = parse(call).unwrap();
+        let args: Vec<Value> =
+            cons::native::list_to_vec(&consair::language::cdr(&call).unwrap()).unwrap();
+        let expansion = cons::expand_macros(call, env, 0).unwrap();
+        captured_symbols(&cell, &args, &expansion)
+            .iter()
+            .map(|sym| sym.resolve())
+            .collect()
+    };
+
+    assert_eq!(check(&mut env, "bad-or", "(bad-or nil result)"), ["result"]);
+    assert!(check(&mut env, "bad-or", "(bad-or nil other)").is_empty());
+    assert!(check(&mut env, "good-or", "(good-or nil result)").is_empty());
+    // A binding whose name comes from the arguments is the caller's choice
+    assert!(check(&mut env, "my-let", "(my-let result 1 result)").is_empty());
+}
+
+#[test]
+fn test_macro_debug_variable() {
+    assert_eq!(eval_str("*macro-debug*").unwrap(), "nil");
+    let result = eval_multi(&[
+        BAD_OR,
+        "(binding ((*macro-debug* t)) (list *macro-debug* (bad-or 1 2)))",
+    ]);
+    assert_eq!(result.unwrap(), "(t 1)");
+}
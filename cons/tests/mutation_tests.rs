@@ -0,0 +1,83 @@
+//! `def` and `set!`
+//!
+//! `def` binds a value in the global environment from anywhere; `set!`
+//! changes the nearest existing binding, and every closure that shares that
+//! binding sees the change.
+
+use cons::{register_stdlib, run_with};
+use consair::Environment;
+
+fn eval_str(source: &str) -> Result<String, String> {
+    let mut env = Environment::new();
+    register_stdlib(&mut env);
+    Ok(run_with(&mut env, source)?.to_string())
+}
+
+#[test]
+fn test_def_binds_any_value() {
+    assert_eq!(eval_str("(def x 42) x").unwrap(), "42");
+    assert_eq!(eval_str("(def xs (list 1 2)) (cdr xs)").unwrap(), "(2)");
+    assert_eq!(eval_str("(def x (+ 1 2))").unwrap(), "3");
+}
+
+#[test]
+fn test_def_inside_function_is_global() {
+    let source = "(label setup (lambda () (def configured 'yes))) \
+                  (setup) \
+                  configured";
+    assert_eq!(eval_str(source).unwrap(), "yes");
+}
+
+#[test]
+fn test_set_increments_across_calls() {
+    let source = "(def counter 0) \
+                  (label inc! (lambda () (set! counter (+ counter 1)))) \
+                  (inc!) (inc!) (inc!) \
+                  counter";
+    assert_eq!(eval_str(source).unwrap(), "3");
+}
+
+#[test]
+fn test_set_is_visible_through_captured_environments() {
+    // Both closures share the let's binding of n
+    let source = "(label make (lambda () \
+                    (let ((n 0)) \
+                      (list (lambda () (set! n (+ n 1))) (lambda () n))))) \
+                  (def pair (make)) \
+                  ((car pair)) ((car pair)) \
+                  ((car (cdr pair)))";
+    assert_eq!(eval_str(source).unwrap(), "2");
+}
+
+#[test]
+fn test_separate_closures_have_separate_bindings() {
+    let source = "(label make-counter (lambda () \
+                    (let ((n 0)) (lambda () (set! n (+ n 1)))))) \
+                  (def a (make-counter)) \
+                  (def b (make-counter)) \
+                  (a) (a) (b) \
+                  (list (a) (b))";
+    assert_eq!(eval_str(source).unwrap(), "(3 2)");
+}
+
+#[test]
+fn test_set_changes_parameter_not_global() {
+    let source = "(def x 1) \
+                  (label f (lambda (x) (set! x 10) x)) \
+                  (list (f 5) x)";
+    assert_eq!(eval_str(source).unwrap(), "(10 1)");
+}
+
+#[test]
+fn test_set_unbound_errors() {
+    let err = eval_str("(set! nowhere 1)").unwrap_err();
+    assert!(err.contains("Unbound symbol: nowhere"), "{err}");
+}
+
+#[test]
+fn test_constants_cannot_change() {
+    let err = eval_str("(define-constant limit 3) (set! limit 4)").unwrap_err();
+    assert!(err.contains("set!: cannot change constant limit"), "{err}");
+    let err = eval_str("(define-constant limit 3) (def limit 4)").unwrap_err();
+    assert!(err.contains("def: cannot redefine constant limit"), "{err}");
+}
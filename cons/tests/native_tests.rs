@@ -1,26 +1,29 @@
-use cons::eval;
-use cons::native::{check_arity_exact, make_int, make_string};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicI64, Ordering};
+
+use cons::native::{OptKind, check_arity_exact, list_to_vec, make_int, make_string, parse_opts};
+use cons::{StdlibConfig, StdlibGroup, eval, register_stdlib_with};
 use consair::language::{AtomType, StringType, Value};
-use consair::{Environment, parse};
+use consair::{Arity, Environment, EvalError, parse};
 
 // ============================================================================
 // Example Native Functions
 // ============================================================================
 
 /// A simple native function that adds 1 to an integer
-fn add_one(args: &[Value], _env: &mut Environment) -> Result<Value, String> {
+fn add_one(args: &[Value], _env: &mut Environment) -> Result<Value, EvalError> {
     use consair::numeric::NumericType;
 
     check_arity_exact("add-one", args, 1)?;
 
     match &args[0] {
         Value::Atom(AtomType::Number(NumericType::Int(n))) => Ok(make_int(n + 1)),
-        _ => Err("add-one: expected integer".to_string()),
+        _ => Err("add-one: expected integer".into()),
     }
 }
 
 /// A native function that concatenates strings
-fn str_concat(args: &[Value], _env: &mut Environment) -> Result<Value, String> {
+fn str_concat(args: &[Value], _env: &mut Environment) -> Result<Value, EvalError> {
     let mut result = String::new();
 
     for arg in args {
@@ -28,7 +31,7 @@ fn str_concat(args: &[Value], _env: &mut Environment) -> Result<Value, String> {
             Value::Atom(AtomType::String(StringType::Basic(s))) => {
                 result.push_str(s);
             }
-            _ => return Err(format!("str-concat: expected string, got {arg}")),
+            _ => return Err(format!("str-concat: expected string, got {arg}").into()),
         }
     }
 
@@ -36,7 +39,7 @@ fn str_concat(args: &[Value], _env: &mut Environment) -> Result<Value, String> {
 }
 
 /// A native function that returns the length of a list
-fn list_length(args: &[Value], _env: &mut Environment) -> Result<Value, String> {
+fn list_length(args: &[Value], _env: &mut Environment) -> Result<Value, EvalError> {
     check_arity_exact("list-length", args, 1)?;
 
     let mut count = 0;
@@ -48,7 +51,7 @@ fn list_length(args: &[Value], _env: &mut Environment) -> Result<Value, String>
     }
 
     if current != Value::Nil {
-        return Err("list-length: expected proper list".to_string());
+        return Err("list-length: expected proper list".into());
     }
 
     Ok(make_int(count))
@@ -129,7 +132,20 @@ fn test_native_function_arity_check() {
     let result = eval(parse("(add-one 1 2)").unwrap(), &mut env);
 
     assert!(result.is_err());
-    assert!(result.unwrap_err().contains("add-one: expected 1 argument"));
+    let err = result.unwrap_err();
+    assert_eq!(
+        err,
+        EvalError::ArityMismatch {
+            callee: "add-one".to_string(),
+            expected: Arity::Exact(1),
+            got: 2,
+        }
+    );
+    assert_eq!(err.code(), "E010_ARITY");
+    assert_eq!(
+        err.to_string(),
+        "add-one: expected 1 argument, got 2 [E010_ARITY]"
+    );
 }
 
 #[test]
@@ -141,7 +157,7 @@ fn test_native_function_type_check() {
     let result = eval(parse(r#"(add-one "not a number")"#).unwrap(), &mut env);
 
     assert!(result.is_err());
-    assert!(result.unwrap_err().contains("expected integer"));
+    assert!(result.unwrap_err().to_string().contains("expected integer"));
 }
 
 #[test]
@@ -179,3 +195,134 @@ fn test_native_function_with_lambda() {
         _ => panic!("Expected number, got {result:?}"),
     }
 }
+
+// ============================================================================
+// Native Closure Tests
+// ============================================================================
+
+#[test]
+fn test_native_closure_captures_state() {
+    let mut env = Environment::new();
+    cons::register_stdlib(&mut env);
+    let counter = Arc::new(AtomicI64::new(0));
+    let captured = counter.clone();
+    env.register_native("bump!", move |args, _env| {
+        check_arity_exact("bump!", args, 0)?;
+        Ok(make_int(captured.fetch_add(1, Ordering::SeqCst) + 1))
+    });
+
+    let result = eval(parse("(list (bump!) (bump!) (bump!))").unwrap(), &mut env).unwrap();
+    assert_eq!(result.to_string(), "(1 2 3)");
+    assert_eq!(counter.load(Ordering::SeqCst), 3);
+
+    // Closures work anywhere functions do
+    let result = eval(
+        parse("(map (lambda (f) (f)) (list bump!))").unwrap(),
+        &mut env,
+    )
+    .unwrap();
+    assert_eq!(result.to_string(), "(4)");
+    assert!(eval(parse("(bump! 1)").unwrap(), &mut env).is_err());
+}
+
+#[test]
+fn test_fn_pointer_registers_as_closure() {
+    let mut env = Environment::new();
+    env.register_native("add-one", add_one);
+    let result = eval(parse("(add-one 41)").unwrap(), &mut env).unwrap();
+    assert_eq!(result.to_string(), "42");
+}
+
+#[test]
+fn test_sandboxed_stdlib_leaves_out_files_and_shell() {
+    let mut env = Environment::new();
+    register_stdlib_with(&mut env, &StdlibConfig::sandboxed());
+    for name in ["slurp", "spit", "shell"] {
+        assert!(env.lookup(name).is_none(), "{name} registered");
+    }
+    assert!(env.lookup("println").is_some());
+    assert!(env.lookup("when").is_some());
+
+    let config = StdlibConfig::new().without(StdlibGroup::Io);
+    assert!(!config.includes("println"));
+    assert!(config.includes("car"));
+}
+
+// ============================================================================
+// Keyword Option Tests
+// ============================================================================
+
+const SHELL_OPTS: &[(&str, OptKind)] = &[
+    ("timeout-ms", OptKind::Int),
+    ("dir", OptKind::String),
+    ("verbose", OptKind::Flag),
+];
+
+fn parse_args(src: &str) -> Vec<Value> {
+    let mut env = Environment::new();
+    cons::register_stdlib(&mut env);
+    // Evaluate (list ...) so keywords and maps arrive as natives see them
+    list_to_vec(&eval(parse(&format!("(list {src})")).unwrap(), &mut env).unwrap()).unwrap()
+}
+
+#[test]
+fn test_parse_opts_keyword_pairs() {
+    let args = parse_args(r#":timeout-ms 500 :dir "/tmp" :verbose t"#);
+    let opts = parse_opts("shell", &args, SHELL_OPTS).unwrap();
+    assert_eq!(opts.int("timeout-ms"), Some(500));
+    assert_eq!(opts.string("dir"), Some("/tmp".to_string()));
+    assert!(opts.flag("verbose"));
+}
+
+#[test]
+fn test_parse_opts_map() {
+    let args = parse_args(r#"(%hash-map :timeout-ms 500 :verbose nil)"#);
+    let opts = parse_opts("shell", &args, SHELL_OPTS).unwrap();
+    assert_eq!(opts.int("timeout-ms"), Some(500));
+    assert!(!opts.flag("verbose"));
+    assert_eq!(opts.string("dir"), None);
+}
+
+#[test]
+fn test_parse_opts_empty() {
+    let opts = parse_opts("shell", &[], SHELL_OPTS).unwrap();
+    assert!(opts.get("timeout-ms").is_none());
+    assert!(!opts.flag("verbose"));
+}
+
+#[test]
+fn test_parse_opts_typo_suggestion() {
+    let args = parse_args(":timout 5");
+    let err = parse_opts("shell", &args, SHELL_OPTS).unwrap_err();
+    assert_eq!(
+        err,
+        "shell: unknown option :timout, did you mean :timeout-ms?"
+    );
+}
+
+#[test]
+fn test_parse_opts_unknown_without_suggestion() {
+    let args = parse_args(":color t");
+    let err = parse_opts("shell", &args, SHELL_OPTS).unwrap_err();
+    assert_eq!(err, "shell: unknown option :color");
+}
+
+#[test]
+fn test_parse_opts_type_check() {
+    let args = parse_args(r#":timeout-ms "soon""#);
+    let err = parse_opts("shell", &args, SHELL_OPTS).unwrap_err();
+    assert!(err.contains(":timeout-ms expects an integer"));
+}
+
+#[test]
+fn test_parse_opts_odd_arguments() {
+    let args = parse_args(":verbose");
+    assert!(parse_opts("shell", &args, SHELL_OPTS).is_err());
+}
+
+#[test]
+fn test_parse_opts_non_keyword_key() {
+    let args = parse_args("'verbose t");
+    let err = parse_opts("shell", &args, SHELL_OPTS).unwrap_err();
+    assert!(err.contains("expected keyword option"));
+}
@@ -1,6 +1,7 @@
 use cons::{eval, register_stdlib};
 use consair::{
-    AtomType, Environment, NumericType, Value, interner::InternedSymbol, language, parse,
+    AtomType, Environment, NumericType, ParseErrorKind, Value, interner::InternedSymbol, language,
+    parse,
 };
 
 // ============================================================================
@@ -159,6 +160,19 @@ fn test_division_by_zero() {
 
     let ratio = NumericType::Ratio(5, 2);
     assert!(ratio.div(&zero).is_err());
+
+    // Float division follows IEEE 754 instead
+    let inf = five.div(&NumericType::Float(0.0)).unwrap();
+    assert_eq!(inf, NumericType::Float(f64::INFINITY));
+    let neg_inf = NumericType::Float(-1.0).div(&zero).unwrap();
+    assert_eq!(neg_inf, NumericType::Float(f64::NEG_INFINITY));
+    assert!(
+        NumericType::Float(0.0)
+            .div(&zero)
+            .unwrap()
+            .to_float()
+            .is_nan()
+    );
 }
 
 #[test]
@@ -211,15 +225,17 @@ fn test_bigint_arithmetic() {
     use num_bigint::BigInt;
     use std::sync::Arc;
 
-    let big1 = NumericType::BigInt(Arc::new(BigInt::from(1000000000000i64)));
-    let big2 = NumericType::BigInt(Arc::new(BigInt::from(2000000000000i64)));
+    let big1 = NumericType::BigInt(Arc::new(BigInt::from(i64::MAX) + 1));
+    let big2 = NumericType::BigInt(Arc::new(BigInt::from(i64::MAX) * 2));
 
     match big1.add(&big2).unwrap() {
         NumericType::BigInt(result) => {
-            assert_eq!(*result, BigInt::from(3000000000000i64));
+            assert_eq!(*result, BigInt::from(i64::MAX) * 3 + 1);
         }
         _ => panic!("Expected BigInt result"),
     }
+    // A result that fits in 64 bits is an Int again
+    assert!(matches!(big2.sub(&big2).unwrap(), NumericType::Int(0)));
 }
 
 // ============================================================================
@@ -310,6 +326,60 @@ fn test_parse_ratio_reduces_to_int() {
     }
 }
 
+#[test]
+fn test_parse_negative_and_big_ratios() {
+    assert_eq!(parse("-3/4").unwrap(), parse("-6/8").unwrap());
+    assert_eq!(parse("-3/4").unwrap().to_string(), "-3/4");
+    assert_eq!(
+        parse("1/99999999999999999999").unwrap().to_string(),
+        "1/99999999999999999999"
+    );
+}
+
+#[test]
+fn test_ratio_with_zero_denominator_is_a_read_error() {
+    for source in ["1/0", "-3/00", "(quote (a 5/0))"] {
+        let err = parse(source).unwrap_err();
+        assert!(
+            err.to_string().contains("zero denominator"),
+            "{source}: {err}"
+        );
+    }
+    // Symbols that merely contain a slash are untouched
+    assert!(matches!(
+        parse("a/0").unwrap(),
+        Value::Atom(AtomType::Symbol(_))
+    ));
+}
+
+#[test]
+fn test_zero_denominator_error_gives_its_position() {
+    // The position is where the ratio starts, however deep in a form
+    let err = parse("(list 1\n  '(a 1/2\n     -3/0))").unwrap_err();
+    assert_eq!(err.kind, ParseErrorKind::InvalidToken);
+    assert_eq!(
+        err.to_string(),
+        "Ratio -3/0 has a zero denominator at line 3, column 6 [P004_INVALID_TOKEN]"
+    );
+    assert!(
+        parse("1/0")
+            .unwrap_err()
+            .message
+            .ends_with("at line 1, column 1")
+    );
+}
+
+#[test]
+fn test_parse_big_integer() {
+    let result = parse("123456789012345678901234567890").unwrap();
+    match result {
+        Value::Atom(AtomType::Number(NumericType::BigInt(_))) => {
+            assert_eq!(result.to_string(), "123456789012345678901234567890");
+        }
+        _ => panic!("Expected BigInt, got {result:?}"),
+    }
+}
+
 // ============================================================================
 // Interpreter Integration Tests
 // ============================================================================
@@ -525,6 +595,332 @@ fn test_numeric_precision_preservation() {
     }
 }
 
+// ============================================================================
+// Conversion Tests
+// ============================================================================
+
+fn eval_str(source: &str) -> Result<String, String> {
+    let mut env = Environment::new();
+    register_stdlib(&mut env);
+    eval(parse(source).unwrap(), &mut env)
+        .map(|v| v.to_string())
+        .map_err(|e| e.to_string())
+}
+
+#[test]
+fn test_parse_number_round_trips() {
+    for text in ["42", "-7", "3.5", "-0.25", "1e3", "3/4", "-5/2"] {
+        let printed = eval_str(&format!("(format-number (parse-number \"{text}\"))")).unwrap();
+        let reparsed = eval_str(&format!("(parse-number {printed})")).unwrap();
+        assert_eq!(reparsed, eval_str(text).unwrap(), "round trip of {text}");
+    }
+    assert_eq!(eval_str("(parse-number \"6/8\")").unwrap(), "3/4");
+    assert_eq!(eval_str("(parse-number \"10/5\")").unwrap(), "2");
+}
+
+#[test]
+fn test_parse_number_big_integer() {
+    assert_eq!(
+        eval_str("(* (parse-number \"123456789012345678901234567890\") 10)").unwrap(),
+        "1234567890123456789012345678900"
+    );
+}
+
+#[test]
+fn test_parse_number_invalid_input() {
+    for text in ["", "abc", "1x", "1/0", "--1", "1.2.3", " 1"] {
+        assert_eq!(
+            eval_str(&format!("(parse-number \"{text}\")")).unwrap(),
+            "nil",
+            "parse of {text:?}"
+        );
+    }
+    assert!(
+        eval_str("(parse-number 42)")
+            .unwrap_err()
+            .contains("E020_TYPE")
+    );
+}
+
+#[test]
+fn test_int_truncates_toward_zero() {
+    assert_eq!(eval_str("(int 7/2)").unwrap(), "3");
+    assert_eq!(eval_str("(int -7/2)").unwrap(), "-3");
+    assert_eq!(eval_str("(int 2.9)").unwrap(), "2");
+    assert_eq!(eval_str("(int -2.9)").unwrap(), "-2");
+    assert_eq!(eval_str("(int 5)").unwrap(), "5");
+    assert_eq!(eval_str("(int 1e20)").unwrap(), "100000000000000000000");
+    assert!(eval_str("(int (/ 1.0 0.0))").is_err());
+    assert!(eval_str("(int \"5\")").is_err());
+}
+
+#[test]
+fn test_float_conversion() {
+    assert_eq!(eval_str("(float 1/4)").unwrap(), "0.25");
+    assert_eq!(eval_str("(float 7/2)").unwrap(), "3.5");
+    assert_eq!(eval_str("(float 2.5)").unwrap(), "2.5");
+    assert!(eval_str("(float 'x)").is_err());
+}
+
+#[test]
+fn test_format_number_radix() {
+    assert_eq!(eval_str("(format-number 10 2)").unwrap(), "\"1010\"");
+    assert_eq!(eval_str("(format-number 64 8)").unwrap(), "\"100\"");
+    assert_eq!(eval_str("(format-number 255 16)").unwrap(), "\"ff\"");
+    assert_eq!(eval_str("(format-number -255 16)").unwrap(), "\"-ff\"");
+    assert_eq!(
+        eval_str("(format-number (* 4294967296 4294967296) 16)").unwrap(),
+        "\"10000000000000000\""
+    );
+    assert_eq!(eval_str("(format-number 1/2)").unwrap(), "\"1/2\"");
+    assert!(eval_str("(format-number 10 1)").is_err());
+    assert!(eval_str("(format-number 10 37)").is_err());
+    assert!(eval_str("(format-number 1.5 2)").is_err());
+}
+
+// ============================================================================
+// Integer Division, Powers and Ordering Tests
+// ============================================================================
+
+#[test]
+fn test_mod_rem_quot_signs() {
+    for (a, b, quot, rem, modulo) in [
+        (7, 2, "3", "1", "1"),
+        (-7, 2, "-3", "-1", "1"),
+        (7, -2, "-3", "1", "-1"),
+        (-7, -2, "3", "-1", "-1"),
+        (6, 3, "2", "0", "0"),
+    ] {
+        assert_eq!(eval_str(&format!("(quot {a} {b})")).unwrap(), quot);
+        assert_eq!(eval_str(&format!("(rem {a} {b})")).unwrap(), rem);
+        assert_eq!(eval_str(&format!("(mod {a} {b})")).unwrap(), modulo);
+    }
+}
+
+#[test]
+fn test_mod_mixed_types() {
+    assert_eq!(eval_str("(mod 7/2 1)").unwrap(), "1/2");
+    assert_eq!(eval_str("(mod -7/2 1)").unwrap(), "1/2");
+    assert_eq!(eval_str("(rem -7/2 1)").unwrap(), "-1/2");
+    assert_eq!(eval_str("(quot 7/2 1/3)").unwrap(), "10");
+    assert_eq!(eval_str("(mod -5.5 2)").unwrap(), "0.5");
+    assert_eq!(eval_str("(quot 7.5 2)").unwrap(), "3.0");
+    assert_eq!(
+        eval_str("(mod (* 4294967296 4294967296) 7)").unwrap(),
+        eval_str("(mod (+ (* 7 2635249153387078802) 2) 7)").unwrap()
+    );
+    assert_eq!(
+        eval_str("(quot (* 4294967296 4294967296) 4294967296)").unwrap(),
+        "4294967296"
+    );
+}
+
+#[test]
+fn test_division_by_zero_is_an_error() {
+    for source in [
+        "(mod 5 0)",
+        "(rem 5 0)",
+        "(quot 5 0)",
+        "(mod 5.0 0.0)",
+        "(quot 1/2 0)",
+    ] {
+        let err = eval_str(source).unwrap_err();
+        assert!(err.contains("Division by zero"), "{source}: {err}");
+    }
+}
+
+#[test]
+fn test_float_division_by_zero() {
+    assert_eq!(eval_str("(/ 1.0 0.0)").unwrap(), "##Inf");
+    assert_eq!(eval_str("(/ -1 0.0)").unwrap(), "##-Inf");
+    assert_eq!(eval_str("(/ 0.0 0)").unwrap(), "##NaN");
+    assert_eq!(eval_str("(/ 1/2 0.0)").unwrap(), "##Inf");
+    assert_eq!(
+        eval_str("(/ 99999999999999999999 2.0)").unwrap(),
+        "50000000000000000000.0"
+    );
+    assert!(
+        eval_str("(/ 1 0)")
+            .unwrap_err()
+            .contains("Division by zero")
+    );
+    assert!(
+        eval_str("(/ 1/2 0)")
+            .unwrap_err()
+            .contains("Division by zero")
+    );
+}
+
+#[test]
+fn test_float_class_predicates() {
+    assert_eq!(eval_str("(nan? (/ 0.0 0.0))").unwrap(), "t");
+    assert_eq!(eval_str("(nan? ##Inf)").unwrap(), "nil");
+    assert_eq!(eval_str("(nan? 1/2)").unwrap(), "nil");
+    assert_eq!(eval_str("(infinite? (/ -1 0.0))").unwrap(), "t");
+    assert_eq!(eval_str("(infinite? ##NaN)").unwrap(), "nil");
+    assert_eq!(
+        eval_str("(infinite? 99999999999999999999999)").unwrap(),
+        "nil"
+    );
+    assert_eq!(eval_str("(finite? 1.5)").unwrap(), "t");
+    assert_eq!(eval_str("(finite? 7)").unwrap(), "t");
+    assert_eq!(eval_str("(finite? ##-Inf)").unwrap(), "nil");
+    assert_eq!(eval_str("(finite? ##NaN)").unwrap(), "nil");
+    assert!(
+        eval_str("(nan? 'x)")
+            .unwrap_err()
+            .contains("expected a number")
+    );
+}
+
+#[test]
+fn test_whole_floats_print_as_floats() {
+    assert_eq!(eval_str("(+ 1.5 2.5)").unwrap(), "4.0");
+    assert_eq!(eval_str("(float 3)").unwrap(), "3.0");
+    assert_eq!(eval_str("(* 1.0 -2)").unwrap(), "-2.0");
+}
+
+#[test]
+fn test_int_boundaries_promote_to_bignums() {
+    let max = i64::MAX;
+    let min = i64::MIN;
+    for (src, expected) in [
+        (format!("(+ {max} 1)"), "9223372036854775808".to_string()),
+        (format!("(+ {max} 0)"), max.to_string()),
+        (format!("(- {min} 1)"), "-9223372036854775809".to_string()),
+        (format!("(- {min} 0)"), min.to_string()),
+        (format!("(- 0 {min})"), "9223372036854775808".to_string()),
+        (format!("(- {max} -1)"), "9223372036854775808".to_string()),
+        (format!("(* {max} 2)"), "18446744073709551614".to_string()),
+        (format!("(* {min} -1)"), "9223372036854775808".to_string()),
+        (format!("(* {min} 1)"), min.to_string()),
+        (format!("(/ {min} -1)"), "9223372036854775808".to_string()),
+        ("(expt 2 63)".to_string(), "9223372036854775808".to_string()),
+        ("(expt -2 63)".to_string(), min.to_string()),
+        (
+            "(expt 2 64)".to_string(),
+            "18446744073709551616".to_string(),
+        ),
+        (
+            "(expt 3 40)".to_string(),
+            "12157665459056928801".to_string(),
+        ),
+    ] {
+        assert_eq!(eval_str(&src).unwrap(), expected, "{src}");
+    }
+    // Results back in range are ints again
+    assert_eq!(
+        eval_str(&format!("(- (+ {max} 1) 1)")).unwrap(),
+        max.to_string()
+    );
+    assert_eq!(
+        eval_str(&format!("(= (+ (- {min} 1) 1) {min})")).unwrap(),
+        "t"
+    );
+}
+
+#[test]
+fn test_abs() {
+    assert_eq!(eval_str("(abs -5)").unwrap(), "5");
+    assert_eq!(eval_str("(abs 5)").unwrap(), "5");
+    assert_eq!(eval_str("(abs -3/4)").unwrap(), "3/4");
+    assert_eq!(eval_str("(abs -2.5)").unwrap(), "2.5");
+    assert_eq!(
+        eval_str("(abs (- 0 9223372036854775807 1))").unwrap(),
+        "9223372036854775808"
+    );
+    assert!(eval_str("(abs 'x)").is_err());
+}
+
+#[test]
+fn test_min_max_variadic() {
+    assert_eq!(eval_str("(min 3)").unwrap(), "3");
+    assert_eq!(eval_str("(min 3 1 2)").unwrap(), "1");
+    assert_eq!(eval_str("(max 3 1 2)").unwrap(), "3");
+    assert_eq!(eval_str("(min 1/2 0.25 1)").unwrap(), "0.25");
+    assert_eq!(eval_str("(max 1/2 0.25 1/3)").unwrap(), "1/2");
+    assert_eq!(
+        eval_str("(max 1 (* 4294967296 4294967296))").unwrap(),
+        "18446744073709551616"
+    );
+    assert!(eval_str("(min)").is_err());
+    assert!(eval_str("(max 1 \"2\")").is_err());
+}
+
+#[test]
+fn test_expt() {
+    assert_eq!(eval_str("(expt 2 10)").unwrap(), "1024");
+    assert_eq!(eval_str("(expt 2 0)").unwrap(), "1");
+    assert_eq!(eval_str("(expt 2 -2)").unwrap(), "1/4");
+    assert_eq!(eval_str("(expt 2/3 2)").unwrap(), "4/9");
+    assert_eq!(eval_str("(expt 2 64)").unwrap(), "18446744073709551616");
+    assert_eq!(eval_str("(expt 4 0.5)").unwrap(), "2.0");
+    assert_eq!(eval_str("(expt 1.5 2)").unwrap(), "2.25");
+    assert!(eval_str("(expt 0 -1)").is_err());
+}
+
+// ============================================================================
+// Bitwise Operation Tests
+// ============================================================================
+
+#[test]
+fn test_bitwise_basics() {
+    assert_eq!(eval_str("(bit-and 12 10)").unwrap(), "8");
+    assert_eq!(eval_str("(bit-or 12 10)").unwrap(), "14");
+    assert_eq!(eval_str("(bit-xor 12 10)").unwrap(), "6");
+    assert_eq!(eval_str("(bit-not 0)").unwrap(), "-1");
+    assert_eq!(eval_str("(bit-shift-left 1 10)").unwrap(), "1024");
+    assert_eq!(eval_str("(bit-shift-right 1024 3)").unwrap(), "128");
+}
+
+#[test]
+fn test_bitwise_variadic_chains() {
+    assert_eq!(eval_str("(bit-and 255 60 15)").unwrap(), "12");
+    assert_eq!(eval_str("(bit-or 1 2 4 8)").unwrap(), "15");
+    assert_eq!(eval_str("(bit-xor 1 3 7)").unwrap(), "5");
+    assert!(eval_str("(bit-and 1)").is_err());
+}
+
+#[test]
+fn test_bitwise_twos_complement() {
+    assert_eq!(eval_str("(bit-and -1 255)").unwrap(), "255");
+    assert_eq!(eval_str("(bit-and -16 255)").unwrap(), "240");
+    assert_eq!(eval_str("(bit-or -8 3)").unwrap(), "-5");
+    assert_eq!(eval_str("(bit-xor -1 5)").unwrap(), "-6");
+    assert_eq!(eval_str("(bit-not -6)").unwrap(), "5");
+    // Right shifts copy the sign bit
+    assert_eq!(eval_str("(bit-shift-right -16 2)").unwrap(), "-4");
+    assert_eq!(eval_str("(bit-shift-right -1 63)").unwrap(), "-1");
+    // Left shifts discard bits past the sign bit
+    assert_eq!(
+        eval_str("(bit-shift-left 1 63)").unwrap(),
+        "-9223372036854775808"
+    );
+    assert_eq!(
+        eval_str("(bit-shift-left 3 63)").unwrap(),
+        "-9223372036854775808"
+    );
+}
+
+#[test]
+fn test_bitwise_rejects_bad_operands() {
+    for source in ["(bit-shift-left 1 64)", "(bit-shift-right 1 -1)"] {
+        let err = eval_str(source).unwrap_err();
+        assert!(
+            err.contains("shift count must be between 0 and 63"),
+            "{source}: {err}"
+        );
+    }
+    for source in ["(bit-and 1.0 1)", "(bit-or 1/2 1)", "(bit-not \"1\")"] {
+        assert!(
+            eval_str(source).unwrap_err().contains("E020_TYPE"),
+            "{source}"
+        );
+    }
+    let err = eval_str("(bit-and (* 4294967296 4294967296) 1)").unwrap_err();
+    assert!(err.contains("big integers are not supported"), "{err}");
+}
+
 // ============================================================================
 // Edge Cases and Error Handling
 // ============================================================================
@@ -549,6 +945,83 @@ fn test_error_arithmetic_on_non_number() {
     assert!(result.is_err());
 }
 
+#[test]
+fn test_ratio_arithmetic_normalizes() {
+    assert_eq!(eval_str("(+ 1/3 2/3)").unwrap(), "1");
+    assert_eq!(eval_str("(- 1/2 1/2)").unwrap(), "0");
+    assert_eq!(eval_str("(* 2/3 3/2)").unwrap(), "1");
+    assert_eq!(eval_str("(/ 1/2 1/4)").unwrap(), "2");
+    assert_eq!(eval_str("(+ 1/6 1/3)").unwrap(), "1/2");
+    assert_eq!(eval_str("(* -2/3 3/4)").unwrap(), "-1/2");
+    assert_eq!(eval_str("(/ 3 -6)").unwrap(), "-1/2");
+    assert!(matches!(
+        NumericType::Ratio(1, 3)
+            .add(&NumericType::Ratio(2, 3))
+            .unwrap(),
+        NumericType::Int(1)
+    ));
+}
+
+#[test]
+fn test_ratio_overflow_promotes_to_big_ratio() {
+    let max = i64::MAX;
+    assert_eq!(
+        eval_str(&format!("(+ 1/{max} 1/{})", max - 1)).unwrap(),
+        "18446744073709551613/85070591730234615838173535747377725442"
+    );
+    assert!(matches!(
+        NumericType::Ratio(max, 2)
+            .mul(&NumericType::Int(3))
+            .unwrap(),
+        NumericType::BigRatio(_)
+    ));
+    // A big result that reduces to an integer is demoted
+    assert_eq!(
+        eval_str(&format!("(* {max}/2 4)")).unwrap(),
+        "18446744073709551614"
+    );
+    assert_eq!(
+        eval_str(&format!("(- (* {max}/2 4) (* {max}/2 4))")).unwrap(),
+        "0"
+    );
+    // Exact mixtures of bignums and ratios stay exact
+    assert_eq!(
+        eval_str("(+ 99999999999999999999 1/2)").unwrap(),
+        "199999999999999999999/2"
+    );
+    assert_eq!(
+        eval_str("(/ 99999999999999999999 3)").unwrap(),
+        "33333333333333333333"
+    );
+}
+
+#[test]
+fn test_ordering_across_number_types() {
+    assert_eq!(eval_str("(< 1/3 0.34)").unwrap(), "t");
+    assert_eq!(eval_str("(> 1/3 0.33)").unwrap(), "t");
+    let ascending = ["1/3", "1/2", "1", "1.5", "99999999999999999999"];
+    for pair in ascending.windows(2) {
+        let (a, b) = (pair[0], pair[1]);
+        assert_eq!(eval_str(&format!("(< {a} {b})")).unwrap(), "t", "{a} < {b}");
+        assert_eq!(
+            eval_str(&format!("(> {a} {b})")).unwrap(),
+            "nil",
+            "{a} > {b}"
+        );
+    }
+    assert_eq!(eval_str("(= 1/2 0.5)").unwrap(), "t");
+    assert_eq!(eval_str("(< 99999999999999999999 1/2)").unwrap(), "nil");
+    assert_eq!(eval_str("(> 99999999999999999999 1.0)").unwrap(), "t");
+    assert_eq!(
+        eval_str("(< 1/99999999999999999999 1/99999999999999999998)").unwrap(),
+        "t"
+    );
+    assert_eq!(
+        eval_str(&format!("(< {}/2 {}/3)", i64::MAX, i64::MAX)).unwrap(),
+        "nil"
+    );
+}
+
 #[test]
 fn test_zero_denominator_in_ratio() {
     let result = NumericType::make_ratio(5, 0);
@@ -0,0 +1,120 @@
+//! `par-map`: `map` across threads
+
+fn eval_expr(source: &str) -> String {
+    match cons::run(source) {
+        Ok(value) => value.to_string(),
+        Err(e) => format!("Error: {e}"),
+    }
+}
+
+#[test]
+fn test_par_map_matches_map() {
+    assert_eq!(
+        eval_expr(
+            "(do (def square (lambda (x) (* x x)))
+                 (equal? (par-map square (range 1000)) (map square (range 1000))))"
+        ),
+        "t"
+    );
+    assert_eq!(
+        eval_expr("(par-map (lambda (x) (+ x 1)) <<1 2 3>>)"),
+        "(2 3 4)"
+    );
+    assert_eq!(eval_expr("(par-map list '(1))"), "((1))");
+    assert_eq!(eval_expr("(par-map list nil)"), "nil");
+    assert_eq!(eval_expr("(par-map list <<>>)"), "nil");
+}
+
+#[test]
+fn test_par_map_keeps_order() {
+    assert_eq!(
+        eval_expr(
+            "(do (def out (par-map (lambda (x) (list x (* 2 x))) (range 1000)))
+                 (list (%first out) (nth out 999) (length out)))"
+        ),
+        "((0 0) (999 1998) 1000)"
+    );
+}
+
+#[test]
+fn test_first_error_is_reported() {
+    // Elements from 100 on fail, in every chunk but the first; the error is
+    // always the one for 100, whichever thread finishes first
+    for _ in 0..20 {
+        assert_eq!(
+            eval_expr(
+                r#"(par-map (lambda (x) (if (< x 100) x (error (str "bad " x))))
+                            (range 1000))"#
+            ),
+            "Error: bad 100"
+        );
+    }
+    assert!(eval_expr("(par-map car '(1))").contains("car"));
+}
+
+#[test]
+fn test_bindings_cannot_change_inside_par_map() {
+    for form in [
+        "(def y x)",
+        "(label f (lambda () x))",
+        "(set! z x)",
+        "(define-constant k 1)",
+        "(defmacro m () x)",
+        "(with-redefs ((z 5)) z)",
+    ] {
+        let source = format!("(do (def z 0) (par-map (lambda (x) {form}) '(1 2)))");
+        let result = eval_expr(&source);
+        assert!(
+            result.contains("cannot change bindings while the environment is frozen"),
+            "{form}: {result}"
+        );
+    }
+    // The environment thaws once par-map returns, even after an error
+    assert_eq!(
+        eval_expr(
+            "(do (def z 0)
+                 (try (par-map (lambda (x) (set! z x)) '(1)) (catch e nil))
+                 (set! z 5)
+                 z)"
+        ),
+        "5"
+    );
+}
+
+#[test]
+fn test_local_bindings_and_refs_work_inside_par_map() {
+    assert_eq!(
+        eval_expr("(par-map (lambda (x) (let ((y (* x 10))) (+ x y))) '(1 2))"),
+        "(11 22)"
+    );
+    assert_eq!(
+        eval_expr(
+            "(do (def total (ref 0))
+                 (par-map (lambda (x) (swap! total + x)) (range 101))
+                 @total)"
+        ),
+        "5050"
+    );
+}
+
+#[test]
+fn test_par_map_argument_errors() {
+    assert!(eval_expr("(par-map car)").contains("par-map"));
+    assert!(eval_expr("(par-map car 5)").contains("par-map:"));
+}
+
+#[test]
+fn test_par_map_threads_print_as_the_caller_does() {
+    assert_eq!(
+        eval_expr(
+            "(do (def show (lambda (x) (list *print-length* *print-float-digits* (str x))))
+                 (def xs (list 3.14159 2.71828 1.41421))
+                 (binding ((*print-float-digits* 3) (*print-length* 2))
+                   (list (equal? (par-map show xs) (map show xs))
+                         (%first (par-map show xs)))))"
+        ),
+        r#"(t (2 3 "3.142"))"#
+    );
+    // Outside the binding, the threads print with the defaults again
+    assert_eq!(eval_expr("(par-map str (list 3.14159))"), r#"("3.14159")"#);
+}
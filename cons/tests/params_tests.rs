@@ -0,0 +1,190 @@
+use cons::interpreter::apply;
+use cons::{eval, register_stdlib};
+use consair::{Environment, Value, parse};
+
+fn eval_multi(inputs: &[&str]) -> Result<String, String> {
+    let mut env = Environment::new();
+    register_stdlib(&mut env);
+    let mut result = String::new();
+    for input in inputs {
+        let expr = parse(input)?;
+        let val = eval(expr, &mut env)?;
+        result = format!("{}", val);
+    }
+    Ok(result)
+}
+
+const CONNECT: &str = "(label connect (lambda (host &key (port 5432) (timeout 30)) \
+                       (list host port timeout)))";
+
+fn value(src: &str) -> Value {
+    parse(src).unwrap()
+}
+
+#[test]
+fn test_key_defaults() {
+    assert_eq!(
+        eval_multi(&[CONNECT, "(connect \"db\")"]).unwrap(),
+        "(\"db\" 5432 30)"
+    );
+    assert_eq!(
+        eval_multi(&[CONNECT, "(connect \"db\" :timeout 5)"]).unwrap(),
+        "(\"db\" 5432 5)"
+    );
+    assert_eq!(
+        eval_multi(&[CONNECT, "(connect \"db\" :timeout 5 :port 1)"]).unwrap(),
+        "(\"db\" 1 5)"
+    );
+}
+
+#[test]
+fn test_unknown_keyword_names_function() {
+    let err = eval_multi(&[CONNECT, "(connect \"db\" :tiemout 5)"]).unwrap_err();
+    assert_eq!(
+        err,
+        "connect: unknown keyword argument :tiemout (accepts :port, :timeout)"
+    );
+}
+
+#[test]
+fn test_keyword_argument_errors() {
+    let err = eval_multi(&[CONNECT, "(connect \"db\" :port)"]).unwrap_err();
+    assert!(err.contains("missing value for keyword argument :port"));
+    let err = eval_multi(&[CONNECT, "(connect \"db\" 5432)"]).unwrap_err();
+    assert!(err.contains("expected keyword argument, got 5432"));
+    let err = eval_multi(&[CONNECT, "(connect \"db\" :port 1 :port 2)"]).unwrap_err();
+    assert!(err.contains("given more than once"));
+    let err = eval_multi(&[CONNECT, "(connect)"]).unwrap_err();
+    assert_eq!(
+        err,
+        "connect: expected at least 1 argument, got 0 [E010_ARITY]"
+    );
+}
+
+#[test]
+fn test_defaults_reference_earlier_parameters() {
+    assert_eq!(
+        eval_multi(&[
+            "(label span (lambda (start &opt (end (+ start 10)) &key (step (- end start))) \
+             (list start end step)))",
+            "(span 1)",
+        ])
+        .unwrap(),
+        "(1 11 10)"
+    );
+    assert_eq!(
+        eval_multi(&[
+            "(label span (lambda (start &opt (end (+ start 10)) &key (step (- end start))) \
+             (list start end step)))",
+            "(span 1 3)",
+        ])
+        .unwrap(),
+        "(1 3 2)"
+    );
+}
+
+#[test]
+fn test_defaults_use_closure_environment() {
+    assert_eq!(
+        eval_multi(&[
+            "(label make (lambda (base) (lambda (&opt (n base)) n)))",
+            "(label f (make 7))",
+            "(label base 100)",
+            "(f)",
+        ])
+        .unwrap(),
+        "7"
+    );
+}
+
+#[test]
+fn test_optional_parameters() {
+    let def = "(label f (lambda (a &opt b (c 3)) (list a b c)))";
+    assert_eq!(eval_multi(&[def, "(f 1)"]).unwrap(), "(1 nil 3)");
+    assert_eq!(eval_multi(&[def, "(f 1 2 4)"]).unwrap(), "(1 2 4)");
+    let err = eval_multi(&[def, "(f 1 2 3 4)"]).unwrap_err();
+    assert_eq!(err, "f: expected at most 3 arguments, got 4 [E010_ARITY]");
+}
+
+#[test]
+fn test_rest_parameter() {
+    let def = "(label f (lambda (a &rest more) (cons a more)))";
+    assert_eq!(eval_multi(&[def, "(f 1)"]).unwrap(), "(1)");
+    assert_eq!(eval_multi(&[def, "(f 1 2 3)"]).unwrap(), "(1 2 3)");
+}
+
+#[test]
+fn test_fixed_arity_error_names_function() {
+    let err = eval_multi(&["(label f (lambda (a b) a))", "(f 1)"]).unwrap_err();
+    assert_eq!(err, "f: expected 2 arguments, got 1 [E010_ARITY]");
+}
+
+#[test]
+fn test_invalid_parameter_lists() {
+    assert!(
+        eval_multi(&["(lambda (&key a &opt b) a)"])
+            .unwrap_err()
+            .contains("misplaced &opt")
+    );
+    assert!(
+        eval_multi(&["(lambda (&rest) nil)"])
+            .unwrap_err()
+            .contains("&rest takes exactly one parameter")
+    );
+    assert!(
+        eval_multi(&["(lambda (&optional a) a)"])
+            .unwrap_err()
+            .contains("unknown parameter marker &optional")
+    );
+    assert!(
+        eval_multi(&["(lambda (&opt (a 1 2)) a)"])
+            .unwrap_err()
+            .contains("expected (a default)")
+    );
+}
+
+#[test]
+fn test_lambda_prints_full_parameter_list() {
+    assert_eq!(
+        eval_multi(&["(lambda (a &opt (b 2) &rest r &key c) a)"]).unwrap(),
+        "<lambda (a &opt (b 2) &rest r &key c)>"
+    );
+}
+
+#[test]
+fn test_macro_keyword_parameters() {
+    assert_eq!(
+        eval_multi(&[
+            "(defmacro unless (test &key (then nil) (else nil)) \
+             `(cond (,test ,else) (t ,then)))",
+            "(unless nil :then 'yes)",
+        ])
+        .unwrap(),
+        "yes"
+    );
+    assert!(
+        eval_multi(&[
+            "(defmacro unless (test &key (then nil)) `(cond (,test nil) (t ,then)))",
+            "(unless nil :than 'yes)",
+        ])
+        .unwrap_err()
+        .contains("unless: unknown keyword argument :than")
+    );
+}
+
+#[test]
+fn test_apply_with_keyword_arguments() {
+    let mut env = Environment::new();
+    register_stdlib(&mut env);
+    eval(parse(CONNECT).unwrap(), &mut env).unwrap();
+    let connect = env.lookup("connect").unwrap();
+
+    let args = [value("\"db\""), value(":port"), value("1")];
+    let result = apply(&connect, &args, &mut env).unwrap();
+    assert_eq!(result.to_string(), "(\"db\" 1 30)");
+
+    let err = apply(&connect, &[value("\"db\""), value(":bogus")], &mut env)
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("lambda: unknown keyword argument :bogus"));
+}
@@ -0,0 +1,366 @@
+//! Interpreter and JIT parity
+//!
+//! Every expression here is evaluated by the interpreter and by
+//! `JitEngine::eval_with_env`, and the two results must be the same value.
+//! Forms the JIT does not compile are skipped, since the runner would hand
+//! them to the interpreter anyway.
+
+use cons::jit::JitEngine;
+use cons::{eval, register_stdlib};
+use consair::{Environment, parse, parse_iter};
+use proptest::prelude::*;
+
+/// How an expression fared in `assert_parity`.
+#[derive(Debug, PartialEq)]
+enum Parity {
+    /// Both engines gave the same value
+    Same,
+    /// The JIT declined to compile the expression
+    Skipped,
+}
+
+/// Evaluate `src` in both engines and assert that they agree.
+///
+/// An error from the JIT means the form is one it leaves to the interpreter:
+/// it either does not compile it or, like integer overflow, cannot finish
+/// it. The interpreter failing where the JIT returns a value is a
+/// divergence.
+fn assert_parity(src: &str) -> Parity {
+    let expr = parse(src).unwrap_or_else(|e| panic!("{src} does not parse: {e}"));
+
+    let mut env = Environment::new();
+    register_stdlib(&mut env);
+    let interpreted = eval(expr.clone(), &mut env);
+
+    let engine = JitEngine::new().unwrap();
+    let mut env = Environment::new();
+    register_stdlib(&mut env);
+    let compiled = match engine.eval_with_env(&expr, &mut env) {
+        Ok(rv) => rv
+            .to_value()
+            .unwrap_or_else(|e| panic!("JIT result of {src} does not convert: {e}")),
+        Err(reason) => {
+            eprintln!("skipping {src}: the JIT does not compile it ({reason})");
+            return Parity::Skipped;
+        }
+    };
+
+    match interpreted {
+        Ok(value) => assert_eq!(
+            compiled, value,
+            "{src}: JIT gave {compiled}, interpreter gave {value}"
+        ),
+        Err(e) => panic!("{src}: JIT gave {compiled}, interpreter failed with {e}"),
+    }
+    Parity::Same
+}
+
+const CASES: &[&str] = &[
+    // Literals
+    "42",
+    "-7",
+    "2.5",
+    "nil",
+    "t",
+    // Arithmetic
+    "(+ 1 2)",
+    "(+ 1 2 3 4)",
+    "(- 10 3)",
+    "(- 10 3 2)",
+    "(* 6 7)",
+    "(* 2 3 4)",
+    "(/ 20 4)",
+    "(/ 7.0 2)",
+    "(/ 1.0 0.0)",
+    "(/ -1 0.0)",
+    "(+ 1 2.5)",
+    "(* 1.5 2)",
+    "(- 2.5 1)",
+    "(mod 7 3)",
+    "(mod -7 2)",
+    "(mod 7 -2)",
+    "(mod 7.5 2)",
+    "(abs -3)",
+    "(abs (- 3 10))",
+    "(abs -2.5)",
+    "(+ 9223372036854775806 1)",
+    "(- (- 0 9223372036854775807) 1)",
+    "(* 4611686018427387904 -2)",
+    "(+ (* 2 3) (- 10 5))",
+    "(* (+ 1 2) (- 8 (/ 10 2)))",
+    "(bit-and 12 10)",
+    "(bit-or 12 10)",
+    "(bit-xor 12 10)",
+    "(bit-not 15)",
+    "(bit-shift-left 1 10)",
+    "(bit-shift-right -64 2)",
+    // Comparisons and predicates
+    "(= 5 5)",
+    "(= 5 6)",
+    "(< 3 5)",
+    "(< 5 3)",
+    "(> 5 3)",
+    "(<= 5 5)",
+    "(>= 3 5)",
+    "(< 1.5 2)",
+    "(eq 'a 'a)",
+    "(eq 'a 'b)",
+    "(eq 3 3)",
+    "(not nil)",
+    "(not 0)",
+    "(atom 42)",
+    "(atom '(1))",
+    "(nil? nil)",
+    "(nil? 0)",
+    "(number? 1.5)",
+    "(number? 'a)",
+    "(cons? '(1))",
+    "(cons? 1)",
+    // Lists
+    "'(1 2 3)",
+    "(quote foo)",
+    "'((1 2) (3 (4)))",
+    "(cons 1 2)",
+    "(cons 1 (cons 2 nil))",
+    "(cons '(1) '(2))",
+    "(car '(1 2))",
+    "(cdr '(1 2))",
+    "(car (cdr '(1 2 3)))",
+    "(length nil)",
+    "(length '(1 2 3))",
+    "(append '(1 2) '(3 4))",
+    "(append nil '(1))",
+    "(reverse '(1 2 3))",
+    "(reverse nil)",
+    "(nth '(10 20 30) 1)",
+    "(nth '(10 20 30) 5)",
+    // Conditionals and sequencing
+    "(cond ((= 1 2) 100) ((= 2 2) 200) (t 300))",
+    "(cond ((= 1 2) 1))",
+    "(cond ((> 5 3) (+ 10 20)) (t 0))",
+    "(cond ((> 5 3) (+ 1 2) (* 10 20)) (t 0))",
+    "(cond ((< 5 3) 1) (else 2 3))",
+    "(cond ((< 5 3) 1) (else))",
+    "(cond (nil) ((* 6 7)) (t 0))",
+    "(cond ((< 5 3)))",
+    "((lambda (x) (cond ((= x 0) 'zero) ((< x 0) (- 0 x) 'negative) (else x 'positive))) -4)",
+    "(if t 1 2)",
+    "(if nil 1 2)",
+    "(if (> 5 3) 'big)",
+    "(if (< 5 3) 'small)",
+    "(if 0 'zero 'none)",
+    "(and 1 2 3)",
+    "(and 1 nil 3)",
+    "(and)",
+    "(or nil nil 7)",
+    "(or)",
+    "(when (> 2 1) 'yes)",
+    "(unless (> 2 1) 'yes)",
+    "(do 1 2 3)",
+    "(do)",
+    "(let ((x 2) (y (* x 3))) (+ x y))",
+    "(let ((x 1)) (let ((x 2)) x))",
+    "(let ((x 1)) (+ x 1) (* x 10))",
+    "(let ((x 1)))",
+    "(let ())",
+    // Lambdas and closures
+    "((lambda (x) (* x x)) 7)",
+    "((lambda (x y) (- x y)) 10 4)",
+    "((lambda () 5))",
+    "((lambda (x) (+ x 100) (* x x)) 7)",
+    "((lambda (x) ((lambda (x) x) 99)) 1)",
+    "(((lambda (x) (lambda (y) (+ x y))) 5) 10)",
+    "(((lambda (a b) (lambda (c) (+ a (+ b c)))) 1 2) 3)",
+    "((((lambda (x) (lambda (y) (lambda (z) (cons x (cons y (cons z nil)))))) 1) 2) 3)",
+    "(((lambda () (lambda (x) (* x x)))) 5)",
+    // Recursion
+    "((label fac (lambda (n) (cond ((= n 0) 1) (t (* n (fac (- n 1))))))) 10)",
+    "((label sum (lambda (n) (cond ((= n 0) 0) (t (+ n (sum (- n 1))))))) 100)",
+    "((label fib (lambda (n) (if (< n 2) n (+ (fib (- n 1)) (fib (- n 2)))))) 15)",
+    "((label count (lambda (n acc) (if (= n 0) acc (count (- n 1) (+ acc n))))) 10000 0)",
+    "((label len (lambda (xs) (if (nil? xs) 0 (+ 1 (len (cdr xs)))))) '(a b c d))",
+    "((label rev (lambda (xs acc) (if (nil? xs) acc (rev (cdr xs) (cons (car xs) acc))))) '(1 2 3) nil)",
+    "((label gcd (lambda (a b) (if (= b 0) a (gcd b (mod a b))))) 1071 462)",
+    // Vectors
+    "(vector)",
+    "(vector 1 2 3)",
+    "(vector (+ 1 2) (* 3 4))",
+    "(vector 'a '(1 2) 2.5)",
+    "(vector-length (vector))",
+    "(vector-length (vector 1 2 3))",
+    "(vector-ref (vector 10 20 30) 1)",
+    "(cons (vector 1) nil)",
+];
+
+#[test]
+fn test_interpreter_and_jit_agree() {
+    let skipped: Vec<&str> = CASES
+        .iter()
+        .copied()
+        .filter(|src| assert_parity(src) == Parity::Skipped)
+        .collect();
+    assert!(
+        skipped.len() < CASES.len() / 2,
+        "the JIT compiled too few cases; skipped {skipped:?}"
+    );
+}
+
+#[test]
+fn test_wrong_arity_is_not_folded() {
+    // Binary operators compile only with two arguments; the interpreter
+    // reports any other count as an error
+    for src in [
+        "(= 1 1 1)",
+        "(< 1 2 3)",
+        "(cons 1 2 3)",
+        "(nth '(1 2) 5 :default 0)",
+    ] {
+        assert_eq!(assert_parity(src), Parity::Skipped, "{src}");
+    }
+}
+
+#[test]
+fn test_redefinition_through_set() {
+    // Run form by form as the runner does, sharing definitions with the JIT,
+    // and compare each result with the interpreter's alone
+    let src = "(label f (lambda (x) (+ x 1)))
+               (f 1)
+               (set! f (lambda (x) (* x 10)))
+               (f 2)
+               (def reset! (lambda () (set! f (lambda (x) (- x 1)))))
+               (reset!)
+               (f 5)
+               (with-redefs ((f (lambda (x) 0))) (f 3))
+               (f 5)
+               (def y 1)
+               (def g (let ((y 100)) (lambda (x) (+ x y))))
+               (g 1)";
+
+    let mut env = Environment::new();
+    register_stdlib(&mut env);
+    let interpreted: Vec<String> = parse_iter(src)
+        .map(|expr| eval(expr.unwrap(), &mut env).unwrap().to_string())
+        .collect();
+
+    let report = cons::runner::run_source_jit_report(src, None).unwrap();
+    let compiled: Vec<String> = report.forms.into_iter().map(|form| form.result).collect();
+    assert_eq!(compiled, interpreted);
+    assert_eq!(interpreted[3], "20");
+    assert_eq!(interpreted[6], "4");
+    assert_eq!(interpreted[11], "101");
+}
+
+// Known divergences. Each fails today and documents a difference to close.
+
+#[test]
+#[ignore = "the JIT has no ratios: (/ 7 2) is 3.5 rather than 7/2"]
+fn test_division_to_ratio() {
+    assert_parity("(/ 7 2)");
+}
+
+#[test]
+fn test_ratios_are_floats_in_the_jit() {
+    // The JIT has no ratios, so an exact result comes back as the nearest
+    // float; the two still compare equal as numbers
+    for (src, exact, float) in [("(+ 1/2 1/4)", "3/4", 0.75), ("(* 1/3 3)", "1", 1.0)] {
+        let expr = parse(src).unwrap();
+        let mut env = Environment::new();
+        register_stdlib(&mut env);
+        assert_eq!(eval(expr.clone(), &mut env).unwrap().to_string(), exact);
+
+        let engine = JitEngine::new().unwrap();
+        match engine.eval_with_env(&expr, &mut env) {
+            Ok(rv) => assert_eq!(rv.to_float(), Some(float), "{src}"),
+            Err(reason) => eprintln!("skipping {src}: the JIT does not compile it ({reason})"),
+        }
+    }
+}
+
+#[test]
+fn test_overflow_is_left_to_the_interpreter() {
+    // The JIT has no bignums, so an integer result that does not fit in an
+    // i64 fails the evaluation and the interpreter's bignum is the answer
+    for (src, exact) in [
+        ("(* 9999999999 9999999999)", "99999999980000000001"),
+        ("(+ 9223372036854775807 1)", "9223372036854775808"),
+        ("(- (- 0 9223372036854775807) 2)", "-9223372036854775809"),
+        (
+            "(abs (- (- 0 9223372036854775807) 1))",
+            "9223372036854775808",
+        ),
+        ("(if (> (* 4611686018427387904 2) 0) 'big 'small)", "big"),
+    ] {
+        let expr = parse(src).unwrap();
+        let mut env = Environment::new();
+        register_stdlib(&mut env);
+        assert_eq!(eval(expr.clone(), &mut env).unwrap().to_string(), exact);
+
+        let engine = JitEngine::new().unwrap();
+        match engine.eval_with_env(&expr, &mut env) {
+            Ok(rv) => panic!("{src}: JIT gave {rv:?} rather than failing"),
+            Err(reason) if reason.contains("integer overflow") => {}
+            Err(reason) => eprintln!("skipping {src}: the JIT does not compile it ({reason})"),
+        }
+    }
+}
+
+#[test]
+fn test_division_by_zero() {
+    // The JIT raises the error, and the interpreter reports it
+    for src in ["(/ 1 0)", "(/ (- 3 3) 0)", "(mod 7 0)", "(mod 7.5 0)"] {
+        assert_eq!(assert_parity(src), Parity::Skipped, "{src}");
+    }
+    assert_parity("(/ 1.0 0)");
+}
+
+#[test]
+#[ignore = "the JIT raises no error for an index out of range: it is nil"]
+fn test_vector_ref_out_of_range() {
+    assert_parity("(vector-ref (vector 1 2) 5)");
+}
+
+#[test]
+#[ignore = "the JIT negates with a single argument; the interpreter wants two"]
+fn test_unary_minus() {
+    assert_parity("(- 42)");
+}
+
+// ============================================================================
+// Random Arithmetic
+// ============================================================================
+
+/// Integer arithmetic small enough never to overflow: at most eight leaves
+/// below 100 in magnitude, and `mod` only by a non-zero literal.
+fn arithmetic() -> impl Strategy<Value = String> {
+    let leaf = (-99i64..100).prop_map(|n| n.to_string());
+    leaf.prop_recursive(3, 8, 2, |inner| {
+        prop_oneof![
+            (
+                prop_oneof![Just("+"), Just("-"), Just("*")],
+                inner.clone(),
+                inner.clone()
+            )
+                .prop_map(|(op, a, b)| format!("({op} {a} {b})")),
+            inner.clone().prop_map(|a| format!("(abs {a})")),
+            (
+                inner,
+                (1i64..20).prop_map(|n| if n % 2 == 0 { -n } else { n })
+            )
+                .prop_map(|(a, n)| format!("(mod {a} {n})")),
+        ]
+    })
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(64))]
+
+    #[test]
+    fn prop_arithmetic_agrees(src in arithmetic()) {
+        assert_parity(&src);
+    }
+
+    #[test]
+    fn prop_comparisons_agree(a in arithmetic(), b in arithmetic()) {
+        assert_parity(&format!("(if (< {a} {b}) 'less (if (= {a} {b}) 'same 'more))"));
+    }
+}
@@ -101,3 +101,59 @@ fn test_persistent_in_interpreter() {
     // Original should be unchanged
     assert_eq!(count(&vec), Some(3));
 }
+
+#[test]
+fn test_persistent_conversions() {
+    let mut env = setup_env();
+
+    let printed = |code: &str, env: &mut Environment| eval_str(code, env).to_string();
+    assert_eq!(printed("(persistent! '(1 2 3))", &mut env), "#pvec[1 2 3]");
+    assert_eq!(printed("(persistent! {:a 1})", &mut env), "#pmap{:a 1}");
+    assert_eq!(printed("(persistent! #{1})", &mut env), "#pset{1}");
+    assert_eq!(
+        printed("(transient (persistent! <<1 2 3>>))", &mut env),
+        "<<1 2 3>>"
+    );
+    assert_eq!(printed("(transient '(1 2))", &mut env), "(1 2)");
+    assert_eq!(
+        eval_str("(persistent! <<1 2>>)", &mut env),
+        eval_str("(persistent! (persistent! '(1 2)))", &mut env)
+    );
+}
+
+#[test]
+fn test_seq_operations_on_persistent_collections() {
+    let mut env = setup_env();
+    eval_str("(label v (persistent! <<1 2 3>>))", &mut env);
+
+    assert_eq!(
+        eval_str("(%conj v 4)", &mut env).to_string(),
+        "#pvec[1 2 3 4]"
+    );
+    assert_eq!(
+        eval_str("(%assoc v 0 9)", &mut env).to_string(),
+        "#pvec[9 2 3]"
+    );
+    assert_eq!(eval_str("(%nth v 2)", &mut env), parse("3").unwrap());
+    assert_eq!(eval_str("(%count v)", &mut env), parse("3").unwrap());
+    assert_eq!(eval_str("v", &mut env).to_string(), "#pvec[1 2 3]");
+
+    let dissoc = eval_str("(%dissoc (persistent! {:a 1 :b 2}) :a)", &mut env);
+    assert_eq!(dissoc.to_string(), "#pmap{:b 2}");
+    let disj = eval_str("(%disj (persistent! #{1 2}) 1)", &mut env);
+    assert_eq!(disj.to_string(), "#pset{2}");
+}
+
+#[test]
+fn test_building_a_large_persistent_vector() {
+    // Each conj shares structure with the last, so this is not quadratic
+    let mut env = setup_env();
+    let source = "(label build (lambda (v n) (cond ((= n 0) v) (t (build (%conj v n) (- n 1))))))";
+    eval_str(source, &mut env);
+    let vec = eval_str("(build (persistent! nil) 100000)", &mut env);
+    assert_eq!(count(&vec), Some(100_000));
+    assert_eq!(
+        eval_str("(%nth (build (persistent! nil) 5) 0)", &mut env),
+        parse("5").unwrap()
+    );
+}
@@ -0,0 +1,87 @@
+//! Prelude macros
+//!
+//! `when`, `unless`, `and` and `or` come with every environment that
+//! `register_stdlib` sets up, and expand to forms the JIT compiles.
+
+use cons::{StdlibConfig, expand_all_macros, register_stdlib, register_stdlib_with, run_with};
+use consair::{Environment, parse};
+
+fn eval_str(source: &str) -> Result<String, String> {
+    let mut env = Environment::new();
+    register_stdlib(&mut env);
+    Ok(run_with(&mut env, source)?.to_string())
+}
+
+/// Whether `head` appears as the operator of any call in `source`
+fn calls(source: &str, head: &str) -> bool {
+    source.contains(&format!("({head} "))
+}
+
+#[test]
+fn test_when_and_unless() {
+    assert_eq!(eval_str("(when (< 1 2) 1 2 3)").unwrap(), "3");
+    assert_eq!(eval_str("(when (> 1 2) 1)").unwrap(), "nil");
+    assert_eq!(eval_str("(unless (> 1 2) 'a 'b)").unwrap(), "b");
+    assert_eq!(eval_str("(unless (< 1 2) 'a)").unwrap(), "nil");
+    assert_eq!(eval_str("(when t)").unwrap(), "nil");
+}
+
+#[test]
+fn test_and_returns_deciding_value() {
+    assert_eq!(eval_str("(and)").unwrap(), "t");
+    assert_eq!(eval_str("(and 1)").unwrap(), "1");
+    assert_eq!(eval_str("(and 1 2 3)").unwrap(), "3");
+    assert_eq!(eval_str("(and 1 nil 3)").unwrap(), "nil");
+    assert_eq!(eval_str("(and (quote a) (quote b))").unwrap(), "b");
+}
+
+#[test]
+fn test_or_returns_deciding_value() {
+    assert_eq!(eval_str("(or)").unwrap(), "nil");
+    assert_eq!(eval_str("(or nil 7 8)").unwrap(), "7");
+    assert_eq!(eval_str("(or nil (cdr (list 1)))").unwrap(), "nil");
+    assert_eq!(eval_str("(or 0 nil)").unwrap(), "0");
+}
+
+#[test]
+fn test_and_or_short_circuit() {
+    assert_eq!(eval_str("(and nil (car 1))").unwrap(), "nil");
+    assert_eq!(eval_str("(or 1 (car 1))").unwrap(), "1");
+    assert!(eval_str("(and 1 (car 1))").is_err());
+}
+
+#[test]
+fn test_and_does_not_capture_user_names() {
+    let source = "(let ((value 5)) (and value (+ value 1)))";
+    assert_eq!(eval_str(source).unwrap(), "6");
+}
+
+#[test]
+fn test_prelude_can_be_redefined() {
+    let source = "(defmacro when (test body) `(cond (,test 'mine) (t nil))) (when t 1)";
+    assert_eq!(eval_str(source).unwrap(), "mine");
+}
+
+#[test]
+fn test_bare_environment_has_no_prelude() {
+    let mut env = Environment::new();
+    register_stdlib_with(&mut env, &StdlibConfig::new().prelude(false));
+    assert!(run_with(&mut env, "(when t 1)").is_err());
+    assert_eq!(run_with(&mut env, "(+ 1 2)").unwrap().to_string(), "3");
+}
+
+#[test]
+fn test_prelude_expands_to_compilable_forms() {
+    // The JIT sees forms after expand_all_macros, so no prelude macro may
+    // survive expansion
+    let mut env = Environment::new();
+    register_stdlib(&mut env);
+    let source = "(lambda (x) (when (and (> x 0) (or (< x 10) (= x 20))) \
+                  (unless (= x 5) x)))";
+    let expanded = expand_all_macros(parse(source).unwrap(), &mut env, 0)
+        .unwrap()
+        .to_string();
+    for head in ["when", "unless", "and", "or"] {
+        assert!(!calls(&expanded, head), "{head} left in {expanded}");
+    }
+}
@@ -0,0 +1,7 @@
+; Closures capture their defining environment
+(label make-adder (lambda (n) (lambda (x) (+ x n))))
+(label add5 (make-adder 5))
+(println (add5 10))
+(println ((make-adder 100) 1))
+(label compose (lambda (f g) (lambda (x) (f (g x)))))
+(println ((compose add5 add5) 0))
@@ -0,0 +1,10 @@
+; Block comments nest, and #_ drops the form after it
+#|
+(println "never printed")
+#| (println "nested") |#
+|#
+(println "start")
+#_(println "skipped")
+(println (+ 1 #_(/ 1 0) 2))
+(println (list 'a #_'b 'c)) #| trailing |#
+#_(println "last form skipped")
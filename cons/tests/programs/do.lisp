@@ -0,0 +1,20 @@
+; do evaluates its forms in order and returns the last one. A lambda with
+; several body forms runs them as a do.
+
+(label square-noisily
+  (lambda (x)
+    (println "squaring" x)
+    (* x x)))
+
+(println "result:" (square-noisily 3))
+
+(println (do (println "first") (println "second") 'third))
+
+(println "empty do:" (do))
+
+(label count-down
+  (lambda (n)
+    (cond ((= n 0) 'done)
+          (t (do (print n "") (count-down (- n 1)))))))
+
+(println (count-down 3))
@@ -0,0 +1,4 @@
+; A runtime error stops the program and names the failing form
+(println "before")
+(car 42)
+(println "after")
@@ -0,0 +1,13 @@
+; Recursion: factorial and fibonacci
+(label factorial (lambda (n)
+  (cond ((= n 0) 1)
+        (t (* n (factorial (- n 1)))))))
+
+(label fib (lambda (n)
+  (cond ((< n 2) n)
+        (t (+ (fib (- n 1)) (fib (- n 2)))))))
+
+(println "5! =" (factorial 5))
+(println "20! =" (factorial 20))
+(println "25! =" (factorial 25))
+(println "fib 15 =" (fib 15))
@@ -0,0 +1,11 @@
+; Macros: definition, expansion and use
+(defmacro unless (test body)
+  `(cond (,test nil) (t ,body)))
+
+(defmacro swap-pair (p)
+  `(cons (cdr ,p) (car ,p)))
+
+(println (unless nil "ran"))
+(println (unless t "skipped"))
+(println (swap-pair (cons 1 2)))
+(println (macroexpand-1 '(unless x y)))
@@ -0,0 +1,10 @@
+; A script with a main function. Every top-level form is evaluated first,
+; then main is called with the command-line arguments as a list of strings.
+
+(label greeting "hello")
+
+(label main
+  (lambda (args)
+    (println (str greeting ", " (str-join " and " args) " (" (length args) " arguments)"))))
+
+(println "loaded")
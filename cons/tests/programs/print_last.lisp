@@ -0,0 +1,4 @@
+; --print-last echoes the value of the final form
+(label square (lambda (x) (* x x)))
+(println "squaring")
+(square 12)
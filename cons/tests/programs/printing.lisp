@@ -0,0 +1,11 @@
+; Printing rules: strings are unquoted, other values use their printed form
+(println "plain string")
+(println "several" "values" 1 2.5 'sym)
+(print "no newline")
+(print " - ")
+(println "then newline")
+(println '(1 2 (3 4)) (cons 1 2))
+(println <<1 2 3>>)
+(println nil t)
+(println 1/2 (/ 1 3))
+(println :keyword)
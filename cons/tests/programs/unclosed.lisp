@@ -0,0 +1,2 @@
+(println "ok")
+(println "missing paren"
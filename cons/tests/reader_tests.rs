@@ -0,0 +1,141 @@
+//! Reading data back from strings
+//!
+//! `pr-str` writes a value and `read-string` reads it back; for data with no
+//! functions in it the two must round-trip to an `equal?` value.
+
+use cons::{register_stdlib, run_with};
+use consair::abstractions::{hash_map, hash_set, vector};
+use consair::language::{AtomType, StringType};
+use consair::{Environment, NumericType, Value, cons, parse};
+use proptest::prelude::*;
+
+fn eval_expr(source: &str) -> String {
+    match cons::run(source) {
+        Ok(value) => value.to_string(),
+        Err(e) => format!("Error: {e}"),
+    }
+}
+
+#[test]
+fn test_read_string_returns_the_first_form() {
+    assert_eq!(
+        eval_expr(r#"(read-string "(1 \"a\" (b))")"#),
+        r#"(1 "a" (b))"#
+    );
+    assert_eq!(eval_expr(r#"(read-string "x y z")"#), "x");
+    assert_eq!(eval_expr(r#"(read-string "'q")"#), "(quote q)");
+    assert_eq!(
+        eval_expr(r#"(equal? (read-string "{:b <<2>> :a #{1}}") {:a #{1} :b <<2>>})"#),
+        "t"
+    );
+}
+
+#[test]
+fn test_read_all_string_returns_every_form() {
+    assert_eq!(
+        eval_expr(r#"(read-all-string "1 (2) x ; done")"#),
+        "(1 (2) x)"
+    );
+    assert_eq!(eval_expr(r#"(read-all-string "")"#), "nil");
+}
+
+#[test]
+fn test_read_string_errors() {
+    assert!(eval_expr(r#"(read-string " ; only a comment")"#).contains("read-string: no form"));
+    assert!(eval_expr(r#"(read-string "(1 2")"#).contains("Unclosed parenthesis"));
+    assert!(eval_expr(r#"(read-all-string "1 )")"#).contains("read-all-string:"));
+    assert!(eval_expr("(read-string 'x)").contains("expected a string"));
+}
+
+#[test]
+fn test_pr_str_quotes_strings() {
+    assert_eq!(
+        eval_expr(r#"(pr-str "say \"hi\"" #\a '(1 "b"))"#),
+        r#""\"say \\\"hi\\\"\" #\\a (1 \"b\")""#
+    );
+    assert_eq!(
+        eval_expr(r#"(equal? (pr-str 1 "x") (write-str 1 "x"))"#),
+        "t"
+    );
+}
+
+#[test]
+fn test_maps_and_sets_round_trip() {
+    assert_eq!(
+        eval_expr(
+            r#"(do (def x {:a #{1 "two"} "k" {} :v <<#{} nil>>})
+                   (equal? x (read-string (pr-str x))))"#
+        ),
+        "t"
+    );
+}
+
+// ============================================================================
+// Random Data
+// ============================================================================
+
+fn string(s: &str) -> Value {
+    Value::Atom(AtomType::String(StringType::Basic(s.to_string())))
+}
+
+/// Values that can be map keys and set elements
+fn key() -> impl Strategy<Value = Value> {
+    prop_oneof![
+        any::<i64>().prop_map(|n| Value::Atom(AtomType::Number(NumericType::Int(n)))),
+        "[a-z\"\\\\ \n\té]{0,8}".prop_map(|s| string(&s)),
+        "[a-z][a-z0-9?!-]{0,6}".prop_map(|s| parse(&format!(":{s}")).unwrap()),
+    ]
+}
+
+/// Atoms that print readably: numbers of every kind, strings with escapes,
+/// characters, symbols, keywords, t and nil
+fn leaf() -> impl Strategy<Value = Value> {
+    prop_oneof![
+        key(),
+        // Floats with a fraction, since 2.0 still prints as 2
+        (-1000i64..1000, 1u32..100).prop_map(|(n, f)| parse(&format!("{n}.{f:02}1")).unwrap()),
+        (1u32..1000, 2u32..1000).prop_map(|(n, d)| parse(&format!("{n}/{d}")).unwrap()),
+        "[1-9][0-9]{19,30}".prop_map(|digits| parse(&digits).unwrap()),
+        prop::sample::select(vec!['a', ' ', '\n', '(', '"', '\\', 'λ'])
+            .prop_map(|c| Value::Atom(AtomType::Char(c))),
+        // No < or >, since a symbol ending in > runs into the >> closing
+        // a vector
+        "[a-z][a-z0-9*+=-]{0,6}".prop_map(|s| parse(&s).unwrap()),
+        Just(parse("t").unwrap()),
+        Just(Value::Nil),
+    ]
+}
+
+/// Nested lists, vectors, maps and sets of leaves
+fn data() -> impl Strategy<Value = Value> {
+    leaf().prop_recursive(4, 48, 5, |inner| {
+        prop_oneof![
+            prop::collection::vec(inner.clone(), 0..5).prop_map(|items| {
+                items
+                    .into_iter()
+                    .rev()
+                    .fold(Value::Nil, |tail, item| cons(item, tail))
+            }),
+            prop::collection::vec(inner.clone(), 0..5).prop_map(vector),
+            prop::collection::vec((key(), inner), 0..4).prop_map(hash_map),
+            prop::collection::hash_set(key(), 0..4)
+                .prop_map(|keys| hash_set(keys.into_iter().collect())),
+        ]
+    })
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(128))]
+
+    #[test]
+    fn prop_pr_str_round_trips(value in data()) {
+        let mut env = Environment::new();
+        register_stdlib(&mut env);
+        env.define("x".to_string(), value.clone());
+
+        let read = run_with(&mut env, "(read-string (pr-str x))").unwrap();
+        prop_assert_eq!(&read, &value);
+        let same = run_with(&mut env, "(equal? x (read-string (pr-str x)))").unwrap();
+        prop_assert_eq!(same.to_string(), "t", "{} does not read back", value);
+    }
+}
@@ -0,0 +1,88 @@
+//! Recursion depth limit
+//!
+//! Non-tail recursion fails with a Lisp error once it is as deep as the
+//! environment's recursion limit, rather than overflowing the stack. Tail
+//! calls do not count toward the limit.
+
+use cons::{expand_all_macros, register_stdlib, run_with};
+use consair::{DEFAULT_RECURSION_LIMIT, Environment, parse};
+
+const COUNT_DOWN: &str = "(label count (lambda (n) (cond ((= n 0) 0) (t (+ 1 (count (- n 1)))))))";
+
+fn env_with_limit(limit: usize) -> Environment {
+    let mut env = Environment::new();
+    register_stdlib(&mut env);
+    env.set_recursion_limit(limit);
+    run_with(&mut env, COUNT_DOWN).unwrap();
+    env
+}
+
+#[test]
+fn test_default_limit_stops_infinite_recursion() {
+    let mut env = Environment::new();
+    register_stdlib(&mut env);
+    assert_eq!(env.recursion_limit(), DEFAULT_RECURSION_LIMIT);
+
+    let err = run_with(&mut env, "(label f (lambda (n) (+ 1 (f n)))) (f 1)").unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        format!("maximum recursion depth {DEFAULT_RECURSION_LIMIT} exceeded")
+    );
+
+    // The environment is still usable afterwards
+    assert_eq!(run_with(&mut env, "(+ 1 2)").unwrap().to_string(), "3");
+}
+
+#[test]
+fn test_custom_limits() {
+    let mut env = env_with_limit(100);
+    assert_eq!(run_with(&mut env, "(count 50)").unwrap().to_string(), "50");
+    let err = run_with(&mut env, "(count 200)").unwrap_err();
+    assert!(err.to_string().contains("maximum recursion depth 100"));
+
+    // Deeper than the default limit allows
+    let depth = DEFAULT_RECURSION_LIMIT + 500;
+    let mut env = env_with_limit(DEFAULT_RECURSION_LIMIT + 1000);
+    assert_eq!(
+        run_with(&mut env, &format!("(count {depth})"))
+            .unwrap()
+            .to_string(),
+        depth.to_string()
+    );
+}
+
+#[test]
+fn test_limit_applies_to_environments_extended_from_the_global_one() {
+    let env = env_with_limit(100);
+    let mut inner = env.extend(&[], &[]);
+    env.set_recursion_limit(20);
+    assert_eq!(inner.recursion_limit(), 20);
+    assert!(run_with(&mut inner, "(count 50)").is_err());
+}
+
+#[test]
+fn test_tail_calls_do_not_count() {
+    let mut env = env_with_limit(100);
+    let source = "(label loop (lambda (n) (cond ((= n 0) 'done) (t (loop (- n 1)))))) (loop 10000)";
+    assert_eq!(run_with(&mut env, source).unwrap().to_string(), "done");
+}
+
+#[test]
+fn test_recursion_through_native_functions_counts() {
+    let mut env = env_with_limit(100);
+    let source = "(label g (lambda (n) (car (map g (list n))))) (g 1)";
+    let err = run_with(&mut env, source).unwrap_err();
+    assert!(err.to_string().contains("maximum recursion depth 100"));
+}
+
+#[test]
+fn test_macro_expansion_shares_the_limit() {
+    let mut env = env_with_limit(50);
+    let nested = format!("{}0{}", "(+ 1 ".repeat(100), ")".repeat(100));
+    let err = expand_all_macros(parse(&nested).unwrap(), &mut env, 0).unwrap_err();
+    assert!(err.to_string().contains("maximum recursion depth 50"));
+
+    // Long lists are not deep ones
+    let long = format!("(list {})", "1 ".repeat(100_000));
+    assert!(expand_all_macros(parse(&long).unwrap(), &mut env, 0).is_ok());
+}
@@ -0,0 +1,173 @@
+//! Refs: mutable places read with `deref` and changed with `ref-set!` and
+//! `swap!`
+
+use std::sync::Arc;
+
+use consair::{RefValue, Value, parse};
+
+fn eval_expr(source: &str) -> String {
+    match cons::run(source) {
+        Ok(value) => value.to_string(),
+        Err(e) => format!("Error: {e}"),
+    }
+}
+
+#[test]
+fn test_counter_with_swap() {
+    assert_eq!(
+        eval_expr(
+            "(do (def counter (ref 0))
+                 (dotimes (i 5) (swap! counter + 1))
+                 @counter)"
+        ),
+        "5"
+    );
+    assert_eq!(eval_expr("(do (def c (ref 1)) (swap! c * 10))"), "10");
+}
+
+#[test]
+fn test_swap_passes_extra_args() {
+    assert_eq!(eval_expr("(do (def r (ref 1)) (swap! r + 2 3) @r)"), "6");
+    assert_eq!(
+        eval_expr("(do (def r (ref '(c))) (swap! r (lambda (l a b) (cons a (cons b l))) 'a 'b))"),
+        "(a b c)"
+    );
+}
+
+#[test]
+fn test_deref_sugar_parses() {
+    assert_eq!(parse("@r").unwrap(), parse("(deref r)").unwrap());
+    assert_eq!(
+        parse("(+ @a @(f))").unwrap(),
+        parse("(+ (deref a) (deref (f)))").unwrap()
+    );
+    assert_eq!(parse("@@r").unwrap(), parse("(deref (deref r))").unwrap());
+    assert!(
+        parse("@")
+            .unwrap_err()
+            .to_string()
+            .contains("Unexpected end of input")
+    );
+}
+
+#[test]
+fn test_mutation_is_seen_by_the_caller() {
+    assert_eq!(
+        eval_expr(
+            "(do (def r (ref 1))
+                 (def double! (lambda (x) (ref-set! x (* 2 @x))))
+                 (double! r)
+                 (double! r)
+                 @r)"
+        ),
+        "4"
+    );
+    // A closure holding a ref shares it with whoever else holds it
+    assert_eq!(
+        eval_expr(
+            "(do (def seen (ref nil))
+                 (def record (lambda (x) (swap! seen (lambda (l) (cons x l)))))
+                 (record 1)
+                 (record 2)
+                 @seen)"
+        ),
+        "(2 1)"
+    );
+}
+
+#[test]
+fn test_ref_set_returns_the_new_value() {
+    assert_eq!(eval_expr("(do (def r (ref 1)) (ref-set! r 'x))"), "x");
+    assert_eq!(
+        eval_expr("(do (def r (ref 1)) (ref-set! r 'x) (deref r))"),
+        "x"
+    );
+}
+
+#[test]
+fn test_refs_print_their_value() {
+    assert_eq!(eval_expr("(ref 1)"), "#ref[1]");
+    assert_eq!(eval_expr("(ref '(1 \"a\"))"), "#ref[(1 \"a\")]");
+    assert_eq!(eval_expr("(ref (ref nil))"), "#ref[#ref[nil]]");
+    // A ref that holds itself prints the inner one as …
+    assert_eq!(
+        eval_expr("(do (def r (ref nil)) (ref-set! r (list 1 r)) r)"),
+        "#ref[(1 #ref[…])]"
+    );
+}
+
+#[test]
+fn test_refs_are_equal_only_to_themselves() {
+    assert_eq!(eval_expr("(equal? (ref 1) (ref 1))"), "nil");
+    assert_eq!(eval_expr("(do (def r (ref 1)) (equal? r r))"), "t");
+    assert_eq!(eval_expr("(do (def r (ref 1)) (eq r r))"), "t");
+
+    let r = Arc::new(RefValue::new(Value::Nil));
+    assert_eq!(Value::Ref(r.clone()), Value::Ref(r.clone()));
+    assert_ne!(
+        Value::Ref(r),
+        Value::Ref(Arc::new(RefValue::new(Value::Nil)))
+    );
+}
+
+#[test]
+fn test_ref_functions_reject_other_values() {
+    assert!(eval_expr("(deref 5)").contains("deref: expected a ref, got 5"));
+    assert!(eval_expr("@'(1)").contains("deref: expected a ref"));
+    assert!(eval_expr("(ref-set! nil 1)").contains("ref-set!: expected a ref"));
+    assert!(eval_expr("(swap! 1 +)").contains("swap!: expected a ref"));
+    assert!(eval_expr("(do (def r (ref 1)) (swap! r 'x))").contains("Error"));
+}
+
+#[test]
+fn test_swap_retries_when_the_ref_changes() {
+    // f changes the ref itself the first time it runs, so the result it
+    // returned is stale and f runs again on the newer value
+    assert_eq!(
+        eval_expr(
+            "(do (def r (ref 1))
+                 (def calls (ref 0))
+                 (swap! r (lambda (x)
+                   (swap! calls + 1)
+                   (if (= @calls 1) (ref-set! r 10))
+                   (+ x 1)))
+                 (list @r @calls))"
+        ),
+        "(11 2)"
+    );
+}
+
+#[test]
+#[allow(clippy::mutable_key_type)]
+fn test_ref_cycles_print_compare_and_hash() {
+    use std::collections::HashSet;
+
+    // r holds a list holding r
+    let cycle = || {
+        let r = Arc::new(RefValue::new(Value::Nil));
+        r.set(consair::cons(Value::Ref(r.clone()), Value::Nil));
+        r
+    };
+    let r = cycle();
+    let list = r.get();
+    assert_eq!(Value::Ref(r.clone()).to_string(), "#ref[(#ref[…])]");
+    assert_eq!(list.to_string(), "(#ref[(#ref[…])])");
+    assert!(format!("{list:?}").contains("RefValue("));
+
+    assert_eq!(list, r.get());
+    assert_ne!(list, cycle().get());
+
+    let mut set = HashSet::new();
+    set.insert(list.clone());
+    assert!(set.contains(&r.get()));
+    assert!(!set.contains(&cycle().get()));
+
+    assert_eq!(
+        eval_expr(
+            "(do (def r (ref nil))
+                 (ref-set! r (list 1 r))
+                 (list (equal? @r @r) (%count (%hash-set @r @r)) (pr-str r)))"
+        ),
+        r##"(t 1 "#ref[(1 #ref[…])]")"##
+    );
+}
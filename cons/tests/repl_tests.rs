@@ -0,0 +1,503 @@
+use cons::jit::JitEngine;
+use std::fs;
+use std::path::PathBuf;
+
+use cons::eval;
+use cons::native::{make_int, vec_to_list};
+use cons::repl::{JitMode, ReplSession, complete, default_init_file, is_complete_expression};
+use consair::language::{AtomType, StringType};
+use consair::{Value, parse};
+
+fn failing_engine() -> Result<JitEngine, String> {
+    Err("native target initialization failed: no target for host".to_string())
+}
+
+fn unreachable_engine() -> Result<JitEngine, String> {
+    panic!("engine constructor should not be called with --no-jit");
+}
+
+fn eval_in(session: &mut ReplSession, input: &str) -> Result<String, String> {
+    session.eval(parse(input).unwrap())
+}
+
+#[test]
+fn test_failed_engine_records_init_error() {
+    let session = ReplSession::with_engine_factory(JitMode::Available, failing_engine);
+    assert!(!session.jit_available());
+    assert!(!session.jit_enabled());
+    assert_eq!(
+        session.jit_init_error(),
+        Some("native target initialization failed: no target for host")
+    );
+}
+
+#[test]
+fn test_jit_status_reports_init_error() {
+    let session = ReplSession::with_engine_factory(JitMode::Available, failing_engine);
+    let status = session.jit_status();
+    assert!(status.contains("failed to initialize"));
+    assert!(status.contains("no target for host"));
+}
+
+#[test]
+fn test_toggle_without_engine_reports_reason() {
+    let mut session = ReplSession::with_engine_factory(JitMode::Available, failing_engine);
+    let err = session.toggle_jit().unwrap_err();
+    assert!(err.contains("no target for host"));
+    assert!(!session.jit_enabled());
+}
+
+#[test]
+fn test_failed_engine_falls_back_to_interpreter() {
+    let mut session = ReplSession::with_engine_factory(JitMode::Available, failing_engine);
+    assert_eq!(eval_in(&mut session, "(+ 1 2)").unwrap(), "3");
+}
+
+#[test]
+fn test_explicit_jit_with_failed_engine_is_startup_error() {
+    let session = ReplSession::with_engine_factory(JitMode::Enabled, failing_engine);
+    let err = session.startup_error().unwrap();
+    assert!(err.contains("no target for host"));
+    assert!(!session.jit_enabled());
+}
+
+#[test]
+fn test_default_mode_with_failed_engine_is_not_startup_error() {
+    let session = ReplSession::with_engine_factory(JitMode::Available, failing_engine);
+    assert!(session.startup_error().is_none());
+}
+
+#[test]
+fn test_no_jit_never_constructs_engine() {
+    let mut session = ReplSession::with_engine_factory(JitMode::Disabled, unreachable_engine);
+    assert!(!session.jit_available());
+    assert!(session.jit_init_error().is_none());
+    assert!(session.startup_error().is_none());
+    assert!(session.jit_status().contains("--no-jit"));
+    assert!(session.toggle_jit().is_err());
+    assert_eq!(eval_in(&mut session, "(* 6 7)").unwrap(), "42");
+}
+
+#[test]
+fn test_session_keeps_definitions() {
+    let mut session = ReplSession::with_engine_factory(JitMode::Disabled, unreachable_engine);
+    eval_in(&mut session, "(label sq (lambda (x) (* x x)))").unwrap();
+    assert_eq!(eval_in(&mut session, "(sq 5)").unwrap(), "25");
+}
+
+#[test]
+fn test_complete_expression() {
+    assert!(is_complete_expression("(+ 1 2)"));
+    assert!(is_complete_expression("(+ 1 2) (+ 3 4)"));
+    assert!(is_complete_expression("42 ; comment"));
+    assert!(!is_complete_expression("(+ 1"));
+    assert!(!is_complete_expression("(+ 1 2) (list"));
+    assert!(!is_complete_expression("'"));
+    assert!(!is_complete_expression("\"(unterminated"));
+    // Reading more lines cannot balance an extra close, so it is complete
+    // and reported rather than waited on
+    assert!(is_complete_expression("(+ 1 2))"));
+    assert!(is_complete_expression(")"));
+}
+
+#[test]
+fn test_eval_input_prints_every_form() {
+    let mut session = ReplSession::with_engine_factory(JitMode::Disabled, unreachable_engine);
+    let result = session.eval_input("(+ 1 2) (+ 3 4)\n'done");
+    assert_eq!(result.results, ["3", "7", "done"]);
+    assert_eq!(result.forms, 3);
+    assert_eq!(result.error, None);
+}
+
+#[test]
+fn test_eval_input_stops_at_first_error() {
+    let mut session = ReplSession::with_engine_factory(JitMode::Disabled, unreachable_engine);
+    let result = session.eval_input("(def a 1) (car 1) (def b 2)");
+    assert_eq!(result.results, ["1"]);
+    assert_eq!(result.forms, 3);
+    assert!(result.error.unwrap().starts_with("Error:"));
+    assert!(eval_in(&mut session, "b").is_err());
+}
+
+#[test]
+fn test_eval_input_with_extra_close_evaluates_nothing() {
+    let mut session = ReplSession::with_engine_factory(JitMode::Disabled, unreachable_engine);
+    let result = session.eval_input("(def a 1))");
+    assert!(result.results.is_empty());
+    assert!(result.error.unwrap().starts_with("Parse error:"));
+    assert!(eval_in(&mut session, "a").is_err());
+}
+
+#[test]
+fn test_timing_leaves_results_unchanged() {
+    let mut session = ReplSession::with_engine_factory(JitMode::Disabled, unreachable_engine);
+    assert!(!session.timing());
+    session.set_timing(true);
+    assert!(session.timing());
+    // Timings go to stderr, not into the printed result
+    assert_eq!(eval_in(&mut session, "(+ 1 2)").unwrap(), "3");
+    assert_eq!(eval_in(&mut session, "(def x 4)").unwrap(), "4");
+}
+
+fn names(names: &[&str]) -> Vec<String> {
+    names.iter().map(|name| name.to_string()).collect()
+}
+
+#[test]
+fn test_complete_names() {
+    let names = names(&["vector", "vector-length", "vector-ref", "reverse"]);
+    assert_eq!(
+        complete("(vector-l", 9, &names),
+        (1, vec!["vector-length".to_string()])
+    );
+    assert_eq!(complete("(+ 1 (vec", 9, &names).1.len(), 3);
+    assert_eq!(
+        complete("'(rev", 5, &names),
+        (2, vec!["reverse".to_string()])
+    );
+    // Only the word before the cursor counts
+    assert_eq!(complete("(rev (vector 1))", 4, &names).1, ["reverse"]);
+    assert!(complete("(car", 4, &names).1.is_empty());
+}
+
+#[test]
+fn test_complete_commands() {
+    let names = names(&[":not-a-command"]);
+    assert_eq!(
+        complete(":ji", 3, &names).1,
+        [":jit", ":jit-status", ":jit-stats", ":jit-clear"]
+    );
+    assert_eq!(
+        complete("  :pre", 6, &names),
+        (2, vec![":pretty".to_string()])
+    );
+    // A keyword inside a form is not a command
+    assert!(complete("(nth l 1 :ti", 12, &names).1.is_empty());
+}
+
+#[test]
+fn test_completion_names() {
+    let mut session = ReplSession::with_engine_factory(JitMode::Disabled, unreachable_engine);
+    eval_in(&mut session, "(label my-square (lambda (x) (* x x)))").unwrap();
+    let names = session.completion_names();
+    for name in ["my-square", "vector-length", "lambda", "if", "defmacro"] {
+        assert!(names.iter().any(|n| n == name), "{name}");
+    }
+    assert!(names.is_sorted());
+    assert_eq!(complete("(my-s", 5, &names).1, ["my-square"]);
+}
+
+#[test]
+fn test_save_and_restore_session() {
+    let path = std::env::temp_dir().join(format!("session_{}.lisp", rand::random::<u32>()));
+    let mut session = ReplSession::with_engine_factory(JitMode::Disabled, unreachable_engine);
+    for form in [
+        "(label square (lambda (x) (* x x)))",
+        "(label sum-squares (lambda (xs) (reduce + 0 (map square xs))))",
+        "(def numbers '(1 2 3))",
+        "(def pair (cons 'a 1))",
+        "(define-constant limit 10)",
+        "(def add-limit (let ((n limit) (m 5)) (lambda (x) (+ x n m))))",
+        "(defmacro twice (form) `(do ,form ,form))",
+        "(def first-fn car)",
+    ] {
+        eval_in(&mut session, form).unwrap();
+    }
+    let warnings = session.save_session(&path).unwrap();
+    assert_eq!(warnings.len(), 1, "{warnings:?}");
+    assert!(warnings[0].contains("first-fn"), "{warnings:?}");
+
+    let mut restored = ReplSession::with_engine_factory(JitMode::Disabled, unreachable_engine);
+    let errors = restored.load_session(&path);
+    fs::remove_file(&path).ok();
+    assert!(errors.is_empty(), "{errors:?}");
+
+    assert_eq!(
+        eval_in(&mut restored, "(sum-squares numbers)").unwrap(),
+        "14"
+    );
+    assert_eq!(eval_in(&mut restored, "pair").unwrap(), "(a . 1)");
+    assert_eq!(eval_in(&mut restored, "(add-limit 1)").unwrap(), "16");
+    assert_eq!(eval_in(&mut restored, "(twice (square 3))").unwrap(), "9");
+    assert!(eval_in(&mut restored, "(def limit 11)").is_err());
+}
+
+#[test]
+fn test_define_constant() {
+    let mut session = ReplSession::with_engine_factory(JitMode::Disabled, unreachable_engine);
+    assert_eq!(
+        eval_in(&mut session, "(define-constant pi 3.5)").unwrap(),
+        "3.5"
+    );
+    assert_eq!(eval_in(&mut session, "(* pi 2)").unwrap(), "7.0");
+    // Redefining with the same value is allowed, so files can be reloaded
+    assert!(eval_in(&mut session, "(define-constant pi 3.5)").is_ok());
+
+    let err = eval_in(&mut session, "(define-constant pi 3)").unwrap_err();
+    assert_eq!(err, "define-constant: pi is already defined as 3.5");
+    let err = eval_in(&mut session, "(label pi 3)").unwrap_err();
+    assert_eq!(err, "label: cannot redefine constant pi");
+    assert_eq!(eval_in(&mut session, "pi").unwrap(), "3.5");
+}
+
+#[test]
+fn test_define_constant_in_function_is_global() {
+    let mut session = ReplSession::with_engine_factory(JitMode::Disabled, unreachable_engine);
+    eval_in(&mut session, "(def f (lambda () (define-constant k 1)))").unwrap();
+    assert_eq!(eval_in(&mut session, "(f)").unwrap(), "1");
+    assert_eq!(eval_in(&mut session, "k").unwrap(), "1");
+    let err = eval_in(&mut session, "(def k 2)").unwrap_err();
+    assert_eq!(err, "def: cannot redefine constant k");
+}
+
+#[test]
+fn test_define_constant_values() {
+    let mut session = ReplSession::with_engine_factory(JitMode::Disabled, unreachable_engine);
+    assert_eq!(
+        eval_in(&mut session, "(define-constant limit (* 4 25))").unwrap(),
+        "100"
+    );
+    assert_eq!(
+        eval_in(&mut session, "(define-constant on t)").unwrap(),
+        "t"
+    );
+    let err = eval_in(&mut session, "(define-constant name \"x\")").unwrap_err();
+    assert!(err.contains("name must be an integer, float, t or nil"));
+    // Parameters still shadow constants
+    assert_eq!(
+        eval_in(&mut session, "((lambda (limit) limit) 5)").unwrap(),
+        "5"
+    );
+}
+
+fn write_init_file(name: &str, contents: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("consair_init_{name}_{}", std::process::id()));
+    fs::write(&path, contents).unwrap();
+    path
+}
+
+#[test]
+fn test_init_file_definitions_are_available() {
+    let path = write_init_file(
+        "defs",
+        "; helpers\n(label sq (lambda (x) (* x x)))\n(defmacro twice (e) `(+ ,e ,e))\n",
+    );
+    let mut session = ReplSession::with_engine_factory(JitMode::Disabled, unreachable_engine);
+    assert!(session.load_init_file(&path).is_empty());
+    assert_eq!(eval_in(&mut session, "(twice (sq 3))").unwrap(), "18");
+    assert_eq!(session.init_source("sq"), Some(path.as_path()));
+    assert_eq!(session.init_source("twice"), Some(path.as_path()));
+
+    eval_in(&mut session, "(label cube (lambda (x) (* x x x)))").unwrap();
+    assert_eq!(session.init_source("cube"), None);
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_init_file_errors_are_not_fatal() {
+    let path = write_init_file("errors", "(label a 1)\n(car 5)\n(label b (+ a 1))\n");
+    let mut session = ReplSession::with_engine_factory(JitMode::Disabled, unreachable_engine);
+    let errors = session.load_init_file(&path);
+    assert_eq!(errors.len(), 1);
+    assert!(errors[0].contains("form 2"), "{}", errors[0]);
+    assert_eq!(eval_in(&mut session, "b").unwrap(), "2");
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_missing_init_file_is_reported() {
+    let path = std::env::temp_dir().join("consair_init_does_not_exist.lisp");
+    let mut session = ReplSession::with_engine_factory(JitMode::Disabled, unreachable_engine);
+    assert_eq!(session.load_init_file(&path).len(), 1);
+    assert_eq!(eval_in(&mut session, "(+ 1 2)").unwrap(), "3");
+}
+
+#[test]
+fn test_default_init_file_resolution() {
+    let home = std::env::temp_dir().join(format!("consair_home_{}", std::process::id()));
+    fs::create_dir_all(&home).unwrap();
+    assert_eq!(default_init_file(None, Some(home.clone())), None);
+
+    let rc = home.join(".consairrc");
+    fs::write(&rc, "(label x 1)").unwrap();
+    assert_eq!(
+        default_init_file(None, Some(home.clone())),
+        Some(rc.clone())
+    );
+
+    // $CONSAIR_INIT takes precedence, and is only used if it exists
+    let custom = write_init_file("env", "(label y 2)");
+    assert_eq!(
+        default_init_file(Some(custom.clone()), Some(home.clone())),
+        Some(custom.clone())
+    );
+    assert_eq!(
+        default_init_file(Some(home.join("missing")), Some(home.clone())),
+        None
+    );
+
+    fs::remove_file(&custom).unwrap();
+    fs::remove_dir_all(&home).unwrap();
+}
+
+#[test]
+fn test_repl_print_limits_bound_large_results() {
+    let mut session = ReplSession::with_engine_factory(JitMode::Disabled, unreachable_engine);
+    let big = vec_to_list((0..1_000_000).map(make_int).collect());
+    session.env_mut().define("big".to_string(), big);
+
+    let shown = eval_in(&mut session, "big").unwrap();
+    assert!(shown.len() < 1_000);
+    assert!(shown.starts_with("(0 1 2 "));
+    assert!(shown.ends_with(" 99 … 999900 more)"));
+
+    // write-str is never limited
+    let full = eval(parse("(write-str big)").unwrap(), session.env_mut()).unwrap();
+    let Value::Atom(AtomType::String(StringType::Basic(text))) = full else {
+        panic!("write-str should return a string");
+    };
+    assert_eq!(text.len(), 6_888_891);
+    assert!(text.ends_with(" 999998 999999)"));
+}
+
+#[test]
+fn test_repl_pretty_printing() {
+    let mut session = ReplSession::new(JitMode::Disabled);
+    let source =
+        "'(alpha-beta-gamma (delta-epsilon zeta-eta) theta-iota kappa-lambda mu-nu xi-omicron)";
+    assert!(!session.pretty());
+    assert!(!eval_in(&mut session, source).unwrap().contains('\n'));
+
+    session.set_pretty(true);
+    let shown = eval_in(&mut session, source).unwrap();
+    assert!(shown.contains('\n'), "{shown}");
+    assert!(shown.lines().all(|line| line.chars().count() <= 80));
+}
+
+#[test]
+fn test_expand_command() {
+    let mut session = ReplSession::new(JitMode::Disabled);
+    eval_in(
+        &mut session,
+        "(defmacro my-when (c body) `(my-if ,c ,body nil))",
+    )
+    .unwrap();
+    eval_in(
+        &mut session,
+        "(defmacro my-if (c a b) `(cond (,c ,a) (t ,b)))",
+    )
+    .unwrap();
+    assert_eq!(
+        session.expand("(my-when x (my-when y 1))").unwrap(),
+        "(cond (x (cond (y 1) (t nil))) (t nil))\n; 4 expansion steps"
+    );
+    assert_eq!(
+        session.expand("(+ 1 2)").unwrap(),
+        "(+ 1 2)\n; 0 expansion steps"
+    );
+    assert!(session.expand("(+ 1").is_err());
+}
+
+#[test]
+fn test_session_survives_runaway_recursion() {
+    let mut session = ReplSession::with_engine_factory(JitMode::Disabled, unreachable_engine);
+    session.env_mut().set_recursion_limit(500);
+    eval_in(&mut session, "(label f (lambda (n) (+ 1 (f n))))").unwrap();
+    let err = eval_in(&mut session, "(f 1)").unwrap_err();
+    assert!(
+        err.contains("maximum recursion depth 500 exceeded"),
+        "{err}"
+    );
+    assert_eq!(eval_in(&mut session, "(+ 1 2)").unwrap(), "3");
+}
+
+#[test]
+fn test_env_listing_shows_user_bindings() {
+    let mut session = ReplSession::with_engine_factory(JitMode::Disabled, unreachable_engine);
+    assert!(session.env_listing(false).is_empty());
+
+    eval_in(&mut session, "(label x 42)").unwrap();
+    eval_in(&mut session, "(label car (lambda (l) 'mine))").unwrap();
+    let long = format!("(label long \"{}\")", "a".repeat(100));
+    eval_in(&mut session, &long).unwrap();
+    let listing = session.env_listing(false);
+    assert_eq!(listing.len(), 3);
+    assert_eq!(listing[0], "car = <lambda (l)>");
+    assert_eq!(listing[1], format!("long = \"{}...", "a".repeat(59)));
+    assert_eq!(listing[2], "x = 42");
+
+    let all = session.env_listing(true);
+    assert!(all.len() > 70);
+    assert!(all.iter().any(|line| line.starts_with("cdr = ")));
+}
+
+#[test]
+fn test_env_native_returns_alist() {
+    let mut session = ReplSession::with_engine_factory(JitMode::Disabled, unreachable_engine);
+    eval_in(&mut session, "(label x 42)").unwrap();
+    assert_eq!(eval_in(&mut session, "(alist-get (env) 'x)").unwrap(), "42");
+    assert_eq!(
+        eval_in(&mut session, "((lambda (x) (alist-get (env) 'x)) 7)").unwrap(),
+        "7"
+    );
+}
+
+#[test]
+fn test_jit_introspection_without_engine() {
+    let mut session = ReplSession::with_engine_factory(JitMode::Disabled, unreachable_engine);
+    assert_eq!(eval_in(&mut session, "(jit-stats)").unwrap(), "nil");
+    assert_eq!(eval_in(&mut session, "(jit-clear-cache)").unwrap(), "nil");
+    assert!(eval_in(&mut session, "(jit-stats 1)").is_err());
+    assert!(session.jit_stats().contains("JIT disabled"));
+    assert!(session.jit_clear().contains("JIT disabled"));
+    // They are part of the session's built-in bindings
+    assert!(session.env_listing(false).is_empty());
+}
+
+#[test]
+fn test_jit_stats_count_repeated_pure_evaluation() {
+    let mut session = ReplSession::new(JitMode::Enabled);
+    for _ in 0..3 {
+        assert_eq!(eval_in(&mut session, "(+ 1 2)").unwrap(), "3");
+    }
+    assert_eq!(
+        eval_in(&mut session, "(jit-stats)").unwrap(),
+        "((hits . 2) (misses . 1) (compilations-avoided . 2) (entries . 1) (evictions . 0))"
+    );
+    assert_eq!(
+        session.jit_stats(),
+        "hits: 2, misses: 1, compilations avoided: 2, entries: 1, evictions: 0"
+    );
+
+    eval_in(&mut session, "(jit-clear-cache)").unwrap();
+    assert_eq!(
+        eval_in(&mut session, "(alist-get (jit-stats) 'entries)").unwrap(),
+        "0"
+    );
+    eval_in(&mut session, "(+ 1 2)").unwrap();
+    assert_eq!(
+        eval_in(&mut session, "(alist-get (jit-stats) 'misses)").unwrap(),
+        "2"
+    );
+    assert_eq!(session.jit_clear(), "JIT cache cleared");
+}
+
+#[test]
+fn test_jit_overflow_falls_back_to_the_interpreter() {
+    let mut session = ReplSession::new(JitMode::Enabled);
+    for _ in 0..2 {
+        assert_eq!(
+            eval_in(&mut session, "(* 9223372036854775807 2)").unwrap(),
+            "18446744073709551614"
+        );
+    }
+    assert_eq!(
+        eval_in(&mut session, "(- (- 0 9223372036854775807) 2)").unwrap(),
+        "-9223372036854775809"
+    );
+    // The overflowing form was never cached
+    assert_eq!(
+        eval_in(&mut session, "(alist-get (jit-stats) 'entries)").unwrap(),
+        "0"
+    );
+}
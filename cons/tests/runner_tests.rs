@@ -0,0 +1,165 @@
+use std::path::PathBuf;
+
+use cons::runner::{FormEngine, decode_source, run_file_jit_report, run_source_jit_report};
+
+fn fixture(name: &str) -> String {
+    let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    path.push("tests");
+    path.push("fixtures");
+    path.push(name);
+    path.to_string_lossy().into_owned()
+}
+
+#[test]
+fn test_report_counts() {
+    let report = run_file_jit_report(&fixture("telemetry.lisp"), None).unwrap();
+    assert_eq!(report.forms.len(), 5);
+    assert_eq!(report.definition_count(), 2);
+    assert_eq!(report.compiled_count(), 2);
+    assert_eq!(report.fallback_count(), 1);
+    assert_eq!(report.last_result(), Some("25"));
+}
+
+#[test]
+fn test_report_form_details() {
+    let report = run_file_jit_report(&fixture("telemetry.lisp"), None).unwrap();
+    let indexes: Vec<usize> = report.forms.iter().map(|f| f.index).collect();
+    assert_eq!(indexes, vec![1, 2, 3, 4, 5]);
+
+    assert_eq!(report.forms[0].engine, FormEngine::Definition);
+    assert_eq!(report.forms[1].engine, FormEngine::Definition);
+    assert!(report.forms[0].snippet.starts_with("(label square"));
+
+    let fallback = &report.forms[4];
+    assert_eq!(fallback.engine, FormEngine::Interpreter);
+    assert!(fallback.fallback_reason.is_some());
+    assert!(
+        report.forms[..2]
+            .iter()
+            .all(|f| f.fallback_reason.is_none())
+    );
+}
+
+#[test]
+fn test_report_summary() {
+    let report = run_file_jit_report(&fixture("telemetry.lisp"), None).unwrap();
+    let summary = report.summary();
+    assert!(summary.starts_with("5 forms:"));
+    assert!(summary.contains("2 definitions"));
+}
+
+#[test]
+fn test_mutually_recursive_labels_compile() {
+    let source = "(label even? (lambda (n) (cond ((= n 0) t) (t (odd? (- n 1))))))\n\
+                  (label odd? (lambda (n) (cond ((= n 0) nil) (t (even? (- n 1))))))\n\
+                  (even? 100)";
+    let report = run_source_jit_report(source, None).unwrap();
+    assert_eq!(report.forms[2].engine, FormEngine::Jit);
+    assert_eq!(report.last_result(), Some("t"));
+}
+
+#[test]
+fn test_report_snippet_truncated() {
+    let source = format!("(quote ({}))", "abcdefghij ".repeat(10));
+    let report = run_source_jit_report(&source, None).unwrap();
+    let snippet = &report.forms[0].snippet;
+    assert_eq!(snippet.chars().count(), 50);
+    assert!(snippet.ends_with("..."));
+}
+
+#[test]
+fn test_report_empty_source() {
+    let report = run_source_jit_report("; only a comment\n", None).unwrap();
+    assert!(report.forms.is_empty());
+    assert_eq!(report.last_result(), None);
+}
+
+#[test]
+fn test_report_missing_file() {
+    let err = run_file_jit_report("/nonexistent/telemetry.lisp", None).unwrap_err();
+    assert!(err.contains("Failed to read file"));
+}
+
+#[test]
+fn test_report_evaluation_error() {
+    let err = run_source_jit_report("(label f (lambda (x) x))\n(car 1)", None).unwrap_err();
+    assert!(err.contains("Evaluation error in form 2"));
+    assert!(err.contains("(car 1)"));
+}
+
+#[test]
+fn test_decode_source_normalizes_line_endings() {
+    assert_eq!(
+        decode_source(b"; c\r\n(+ 1 2)\r\n").unwrap(),
+        "; c\n(+ 1 2)\n"
+    );
+    assert_eq!(
+        decode_source(b"; c\r(+ 1 2)\r\n3\n").unwrap(),
+        "; c\n(+ 1 2)\n3\n"
+    );
+}
+
+#[test]
+fn test_decode_source_reports_invalid_byte_position() {
+    let err = decode_source(b"(+ 1 2)\r\n; bad \xff byte\r\n").unwrap_err();
+    assert_eq!(err, "invalid UTF-8 byte 0xFF at line 2 (byte offset 15)");
+}
+
+#[test]
+fn test_decode_source_points_at_string_start() {
+    let err = decode_source(b"(+ 1 2)\n(println \"a;b\n \xc3(\")\n").unwrap_err();
+    assert_eq!(
+        err,
+        "invalid UTF-8 in string literal starting at line 2 (byte offset 17)"
+    );
+}
+
+#[test]
+fn test_crlf_source_runs() {
+    let report = run_source_jit_report("; comment\r\n(+ 1 2) ; trailing\r(* 2 3)", None).unwrap();
+    assert_eq!(report.forms.len(), 2);
+    assert_eq!(report.last_result(), Some("6"));
+}
+
+const SCRIPT: &str = "(label greeting \"hello\")\n\
+                      (label main (lambda (args) (cons greeting args)))\n\
+                      (+ 1 2)";
+
+#[test]
+fn test_report_calls_main_after_loading() {
+    let args = ["a".to_string(), "b".to_string()];
+    let report = run_source_jit_report(SCRIPT, Some(&args)).unwrap();
+    assert_eq!(report.forms.len(), 3);
+    assert_eq!(report.main_result.as_deref(), Some(r#"("hello" "a" "b")"#));
+    assert_eq!(report.last_result(), Some(r#"("hello" "a" "b")"#));
+}
+
+#[test]
+fn test_report_without_main() {
+    let report = run_source_jit_report(SCRIPT, None).unwrap();
+    assert_eq!(report.main_result, None);
+    assert_eq!(report.last_result(), Some("3"));
+
+    let report = run_source_jit_report("(+ 1 2)", Some(&[])).unwrap();
+    assert_eq!(report.main_result, None);
+}
+
+#[test]
+fn test_report_main_errors() {
+    let err = run_source_jit_report("(label main (lambda (args) (car 1)))", Some(&[])).unwrap_err();
+    assert!(err.starts_with("main: "), "{err}");
+
+    let err = run_source_jit_report("(label main 42)", Some(&[])).unwrap_err();
+    assert!(err.contains("main must be a function"), "{err}");
+}
+
+#[test]
+fn test_integer_overflow_falls_back_to_the_interpreter() {
+    let source = "(def big 9223372036854775807)\n(* big 2)\n(+ 1 2)";
+    let report = run_source_jit_report(source, None).unwrap();
+    let overflow = &report.forms[1];
+    assert_eq!(overflow.engine, FormEngine::Interpreter);
+    assert_eq!(overflow.result, "18446744073709551614");
+    assert!(overflow.fallback_reason.is_some());
+    assert_eq!(report.last_result(), Some("3"));
+}
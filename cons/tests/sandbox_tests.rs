@@ -0,0 +1,97 @@
+//! Sandboxed standard library
+//!
+//! `register_stdlib_sandboxed` and `cons --sandbox` leave out file access
+//! and shell commands, so calling them is an ordinary unbound symbol error.
+
+use cons::jit::JitEngine;
+use cons::repl::{JitMode, ReplSession};
+use cons::runner::run_source_jit_report_with;
+use cons::{
+    StdlibConfig, register_core, register_io, register_process, register_stdlib,
+    register_stdlib_sandboxed, register_time, run_with,
+};
+use consair::error::codes;
+use consair::{Environment, EvalError, parse};
+
+const PURE_PROGRAM: &str = "
+    (label fact (lambda (n) (cond ((= n 0) 1) (t (* n (fact (- n 1)))))))
+    (label squares (map (lambda (x) (* x x)) (list 1 2 3 4)))
+    (list (fact 10) squares (str-join \"-\" (list \"a\" \"b\")) (when (> 2 1) 'yes))
+";
+
+fn unreachable_engine() -> Result<JitEngine, String> {
+    panic!("engine constructor should not be called with --no-jit");
+}
+
+fn sandboxed_env() -> Environment {
+    let mut env = Environment::new();
+    register_stdlib_sandboxed(&mut env);
+    env
+}
+
+#[test]
+fn test_shell_is_unbound_under_sandbox() {
+    let mut env = sandboxed_env();
+    let err = run_with(&mut env, "(shell \"ls\")").unwrap_err();
+    assert!(matches!(err, EvalError::UnboundSymbol(_)), "{err}");
+    assert_eq!(err.code(), codes::UNBOUND_SYMBOL);
+
+    for source in ["(slurp \"Cargo.toml\")", "(spit \"/tmp/x\" \"y\")"] {
+        assert!(run_with(&mut env, source).is_err(), "{source} ran");
+    }
+}
+
+#[test]
+fn test_pure_programs_run_identically() {
+    let mut full = Environment::new();
+    register_stdlib(&mut full);
+    let expected = run_with(&mut full, PURE_PROGRAM).unwrap().to_string();
+
+    let mut sandboxed = sandboxed_env();
+    let result = run_with(&mut sandboxed, PURE_PROGRAM).unwrap().to_string();
+    assert_eq!(result, expected);
+    assert_eq!(result, "(3628800 (1 4 9 16) \"a-b\" yes)");
+}
+
+#[test]
+fn test_capability_groups() {
+    let mut env = Environment::new();
+    register_core(&mut env);
+    assert!(env.lookup("car").is_some());
+    assert!(env.lookup("when").is_none(), "prelude loaded");
+    for name in ["println", "slurp", "shell", "now"] {
+        assert!(env.lookup(name).is_none(), "{name} registered");
+    }
+
+    register_io(&mut env);
+    assert!(env.lookup("println").is_some());
+    assert!(env.lookup("spit").is_some());
+    assert!(env.lookup("shell").is_none());
+
+    register_process(&mut env);
+    register_time(&mut env);
+    assert!(env.lookup("shell").is_some());
+    assert!(env.lookup("now").is_some());
+}
+
+#[test]
+fn test_sandboxed_repl_session() {
+    let mut session = ReplSession::with_stdlib(
+        JitMode::Disabled,
+        unreachable_engine,
+        &StdlibConfig::sandboxed(),
+    );
+    assert!(session.eval(parse("(shell \"ls\")").unwrap()).is_err());
+    assert_eq!(session.eval(parse("(+ 1 2)").unwrap()).unwrap(), "3");
+}
+
+#[test]
+fn test_sandboxed_jit_run() {
+    let err =
+        run_source_jit_report_with("(shell \"ls\")", None, &StdlibConfig::sandboxed()).unwrap_err();
+    assert!(err.contains("Unbound symbol: shell"), "{err}");
+
+    let report =
+        run_source_jit_report_with(PURE_PROGRAM, None, &StdlibConfig::sandboxed()).unwrap();
+    assert_eq!(report.forms.len(), 3);
+}
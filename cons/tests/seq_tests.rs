@@ -0,0 +1,381 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use cons::native::vec_to_list;
+use cons::stdlib::flatten;
+use cons::{eval, register_stdlib};
+use consair::language::{AtomType, cons};
+use consair::numeric::NumericType;
+use consair::{Environment, EvalError, Value, parse};
+
+fn eval_expr(expr: &str) -> String {
+    let mut env = Environment::new();
+    register_stdlib(&mut env);
+    match eval(parse(expr).unwrap(), &mut env) {
+        Ok(result) => result.to_string(),
+        Err(e) => format!("Error: {e}"),
+    }
+}
+
+#[test]
+fn test_map() {
+    assert_eq!(eval_expr("(map (lambda (x) (* x x)) '(1 2 3))"), "(1 4 9)");
+    assert_eq!(eval_expr("(map (lambda (x) (+ x 1)) <<1 2 3>>)"), "(2 3 4)");
+    assert_eq!(eval_expr("(map car '((a 1) (b 2)))"), "(a b)");
+    assert_eq!(eval_expr("(map (lambda (c) c) \"ab\")"), r"(#\a #\b)");
+    assert_eq!(eval_expr("(map car nil)"), "nil");
+}
+
+#[test]
+fn test_filter_preserves_order() {
+    assert_eq!(
+        eval_expr("(filter (lambda (x) (> x 2)) '(5 1 4 2 3))"),
+        "(5 4 3)"
+    );
+    assert_eq!(
+        eval_expr("(filter (lambda (x) (< x 2)) <<1 2 0>>)"),
+        "(1 0)"
+    );
+    assert_eq!(eval_expr("(filter (lambda (x) nil) '(1 2))"), "nil");
+}
+
+#[test]
+fn test_reduce() {
+    assert_eq!(eval_expr("(reduce + '(1 2 3))"), "6");
+    assert_eq!(eval_expr("(reduce + 10 <<1 2 3>>)"), "16");
+    assert_eq!(
+        eval_expr("(reduce (lambda (acc x) (cons x acc)) nil '(1 2 3))"),
+        "(3 2 1)"
+    );
+    assert_eq!(eval_expr("(reduce + '(5))"), "5");
+}
+
+#[test]
+fn test_reduce_empty_collection() {
+    assert_eq!(eval_expr("(reduce + 7 nil)"), "7");
+    assert_eq!(eval_expr("(reduce list <<>>)"), "nil");
+    assert_eq!(eval_expr("(reduce (lambda () 'empty) nil)"), "empty");
+}
+
+#[test]
+fn test_reduce_stops_at_reduced() {
+    let (result, calls) = eval_counting(
+        "(reduce (lambda (acc x) (cond ((> (tick x) 2) (%reduced acc)) (t (+ acc x)))) 0 '(1 2 3 4 5))",
+    );
+    assert_eq!((result.as_str(), calls), ("3", 3));
+}
+
+#[test]
+fn test_map_filter_reduce_long_list() {
+    let mut env = Environment::new();
+    register_stdlib(&mut env);
+    let items = (0..100_000)
+        .map(|n| Value::Atom(AtomType::Number(NumericType::Int(n))))
+        .collect();
+    env.define("xs".to_string(), vec_to_list(items));
+    let result = eval(
+        parse("(reduce + (filter (lambda (x) (< x 10)) (map (lambda (x) (- x 1)) xs)))").unwrap(),
+        &mut env,
+    )
+    .unwrap();
+    assert_eq!(result.to_string(), "44");
+}
+
+#[test]
+fn test_map_filter_reduce_errors() {
+    assert!(eval_expr("(map car)").contains("map: expected 2 arguments, got 1"));
+    assert!(eval_expr("(filter car 5)").contains("filter: Expected a collection, got 5"));
+    assert!(eval_expr("(reduce +)").contains("reduce: expected 2-3 arguments, got 1"));
+}
+
+#[test]
+fn test_partition_by() {
+    assert_eq!(
+        eval_expr("(partition-by (lambda (x) (> x 2)) '(1 2 3 4 1))"),
+        "((1 2) (3 4) (1))"
+    );
+    assert_eq!(
+        eval_expr("(partition-by (lambda (x) x) '(7 7 7))"),
+        "((7 7 7))"
+    );
+    assert_eq!(eval_expr("(partition-by (lambda (x) x) nil)"), "nil");
+    assert_eq!(
+        eval_expr("(partition-by (lambda (x) (= x 2)) <<1 2 3>>)"),
+        "((1) (2) (3))"
+    );
+}
+
+#[test]
+fn test_split_with() {
+    assert_eq!(
+        eval_expr("(split-with (lambda (x) (< x 0)) '(-1 -2 3 -4))"),
+        "((-1 -2) (3 -4))"
+    );
+    assert_eq!(
+        eval_expr("(split-with (lambda (x) (< x 0)) '(-1 -2))"),
+        "((-1 -2) nil)"
+    );
+    assert_eq!(eval_expr("(split-with (lambda (x) t) nil)"), "(nil nil)");
+}
+
+#[test]
+fn test_range() {
+    assert_eq!(eval_expr("(range 5)"), "(0 1 2 3 4)");
+    assert_eq!(eval_expr("(range 2 6)"), "(2 3 4 5)");
+    assert_eq!(eval_expr("(range 0 10 3)"), "(0 3 6 9)");
+    assert_eq!(eval_expr("(range 3 0 -1)"), "(3 2 1)");
+    assert_eq!(eval_expr("(range 0)"), "nil");
+    assert_eq!(eval_expr("(range 5 0)"), "nil");
+    assert!(eval_expr("(range 0 5 0)").contains("range: step must not be zero"));
+    assert!(eval_expr("(range 1.5)").contains("expected an integer"));
+}
+
+#[test]
+fn test_take_and_drop() {
+    assert_eq!(eval_expr("(take 2 '(1 2 3))"), "(1 2)");
+    assert_eq!(eval_expr("(take 5 <<1 2>>)"), "(1 2)");
+    assert_eq!(eval_expr("(take -1 '(1 2))"), "nil");
+    assert_eq!(eval_expr("(drop 2 <<1 2 3>>)"), "(3)");
+    assert_eq!(eval_expr("(drop 5 '(1 2))"), "nil");
+    assert_eq!(eval_expr("(drop -1 '(1 2))"), "(1 2)");
+    assert_eq!(eval_expr("(take 2 \"héllo\")"), r"(#\h #\é)");
+    assert_eq!(eval_expr("(take 3 (drop 10 (range 100000)))"), "(10 11 12)");
+}
+
+#[test]
+fn test_take_while_and_drop_while() {
+    let small = "(lambda (x) (< x 3))";
+    assert_eq!(
+        eval_expr(&format!("(take-while {small} '(1 2 3 1))")),
+        "(1 2)"
+    );
+    assert_eq!(
+        eval_expr(&format!("(drop-while {small} <<1 2 3 1>>)")),
+        "(3 1)"
+    );
+    assert_eq!(eval_expr(&format!("(take-while {small} '(1 2))")), "(1 2)");
+    assert_eq!(eval_expr(&format!("(drop-while {small} '(1 2))")), "nil");
+    assert!(eval_expr("(take-while (lambda (x) (car x)) '(1))").starts_with("Error:"));
+
+    // pred stops being called at the first failure
+    let (result, calls) = eval_counting("(take-while tick '(1 2 nil 4 5))");
+    assert_eq!((result.as_str(), calls), ("(1 2)", 3));
+}
+
+#[test]
+fn test_last_and_butlast() {
+    assert_eq!(eval_expr("(last '(1 2 3))"), "3");
+    assert_eq!(eval_expr("(last <<a>>)"), "a");
+    assert_eq!(eval_expr("(last nil)"), "nil");
+    assert_eq!(eval_expr("(butlast <<1 2 3>>)"), "(1 2)");
+    assert_eq!(eval_expr("(butlast '(1))"), "nil");
+    assert_eq!(eval_expr("(butlast nil)"), "nil");
+    assert!(eval_expr("(last 5)").contains("last: Expected a collection, got 5"));
+}
+
+#[test]
+fn test_distinct() {
+    assert_eq!(eval_expr("(distinct '(1 2 1 3 2))"), "(1 2 3)");
+    assert_eq!(eval_expr("(distinct '(5 5 5))"), "(5)");
+    assert_eq!(eval_expr("(distinct '((1 2) (1 2) 3))"), "((1 2) 3)");
+    assert_eq!(eval_expr("(distinct nil)"), "nil");
+    assert_eq!(eval_expr("(distinct \"abca\")"), r"(#\a #\b #\c)");
+}
+
+#[test]
+fn test_dedupe() {
+    assert_eq!(eval_expr("(dedupe '(1 1 2 1 1))"), "(1 2 1)");
+    assert_eq!(eval_expr("(dedupe '(4 4 4))"), "(4)");
+    assert_eq!(eval_expr("(dedupe <<>>)"), "<<>>");
+}
+
+#[test]
+fn test_flatten() {
+    assert_eq!(eval_expr("(flatten '(1 (2 <<3 (4)>>) 5))"), "(1 2 3 4 5)");
+    assert_eq!(eval_expr("(flatten '(1 \"ab\" (2)))"), "(1 \"ab\" 2)");
+    assert_eq!(eval_expr("(flatten nil)"), "nil");
+    assert_eq!(eval_expr("(flatten '(((1))))"), "(1)");
+}
+
+#[test]
+fn test_flatten_deeply_nested_list() {
+    let mut env = Environment::new();
+    register_stdlib(&mut env);
+
+    let mut nested = cons(
+        Value::Atom(AtomType::Number(NumericType::Int(1))),
+        Value::Nil,
+    );
+    for _ in 0..10_000 {
+        nested = cons(nested, Value::Nil);
+    }
+    let result = flatten(&[nested], &mut env).unwrap();
+    assert_eq!(result.to_string(), "(1)");
+}
+
+#[test]
+fn test_sequence_functions_reject_non_collections() {
+    assert!(eval_expr("(distinct 5)").contains("distinct: Expected a collection, got 5"));
+    assert!(eval_expr("(flatten 'a)").contains("flatten: Expected a collection"));
+}
+
+static CALLS: AtomicUsize = AtomicUsize::new(0);
+
+/// Native that records each call and returns its argument
+fn tick(args: &[Value], _env: &mut Environment) -> Result<Value, EvalError> {
+    CALLS.fetch_add(1, Ordering::SeqCst);
+    Ok(args[0].clone())
+}
+
+/// Evaluate `expr` with `tick` bound, returning the result and the number of ticks
+fn eval_counting(expr: &str) -> (String, usize) {
+    let mut env = Environment::new();
+    register_stdlib(&mut env);
+    env.define("tick".to_string(), Value::NativeFn(tick));
+    CALLS.store(0, Ordering::SeqCst);
+    let result = eval(parse(expr).unwrap(), &mut env).unwrap().to_string();
+    (result, CALLS.load(Ordering::SeqCst))
+}
+
+#[test]
+fn test_some_returns_pred_result() {
+    assert_eq!(
+        eval_expr("(some (lambda (x) (cond ((> x 2) (* x 10)))) '(1 3 5))"),
+        "30"
+    );
+    assert_eq!(eval_expr("(some (lambda (x) (> x 9)) <<1 2>>)"), "nil");
+    assert_eq!(eval_expr("(some (lambda (x) x) nil)"), "nil");
+}
+
+#[test]
+fn test_every_and_not_any() {
+    assert_eq!(eval_expr("(every? (lambda (x) (> x 0)) '(1 2 3))"), "t");
+    assert_eq!(eval_expr("(every? (lambda (x) (> x 1)) '(1 2 3))"), "nil");
+    assert_eq!(eval_expr("(every? (lambda (x) nil) nil)"), "t");
+    assert_eq!(eval_expr("(not-any? (lambda (x) (< x 0)) '(1 2 3))"), "t");
+    assert_eq!(eval_expr("(not-any? (lambda (x) (< x 2)) '(1 2 3))"), "nil");
+}
+
+#[test]
+fn test_predicates_short_circuit() {
+    // The predicate ticks once per call; (tick x) returns x.
+    let (result, calls) = eval_counting("(some (lambda (x) (> (tick x) 1)) '(1 2 3 4 5))");
+    assert_eq!((result.as_str(), calls), ("t", 2));
+
+    let (result, calls) = eval_counting("(every? (lambda (x) (< (tick x) 2)) '(1 2 3 4 5))");
+    assert_eq!((result.as_str(), calls), ("nil", 2));
+
+    let (result, calls) = eval_counting("(not-any? (lambda (x) (= (tick x) 3)) '(1 2 3 4 5))");
+    assert_eq!((result.as_str(), calls), ("nil", 3));
+}
+
+#[test]
+fn test_keep() {
+    assert_eq!(
+        eval_expr("(keep (lambda (x) (cond ((> x 1) (* x x)))) '(1 2 3))"),
+        "(4 9)"
+    );
+    assert_eq!(eval_expr("(keep (lambda (x) nil) '(1 2))"), "nil");
+    assert_eq!(eval_expr("(keep (lambda (x) (= x 1)) '(1 2))"), "(t nil)");
+}
+
+#[test]
+fn test_max_key_and_min_key() {
+    assert_eq!(
+        eval_expr("(max-key length '((1) (1 2 3) (1 2)))"),
+        "(1 2 3)"
+    );
+    assert_eq!(eval_expr("(min-key length '((1) (1 2 3) (2)))"), "(1)");
+    assert_eq!(eval_expr("(max-key length nil)"), "nil");
+    assert!(eval_expr("(max-key car '((a)))").contains("max-key: f must return a number"));
+}
+
+#[test]
+fn test_vector_operations_preserve_vectors() {
+    assert_eq!(eval_expr("(reverse <<1 2 3>>)"), "<<3 2 1>>");
+    assert_eq!(eval_expr("(reverse '(1 2 3))"), "(3 2 1)");
+    assert_eq!(eval_expr("(distinct <<1 2 1>>)"), "<<1 2>>");
+    assert_eq!(eval_expr("(dedupe <<1 1 2>>)"), "<<1 2>>");
+    assert_eq!(eval_expr("(sort <<3 1 2>>)"), "<<1 2 3>>");
+    assert_eq!(eval_expr("(%conj <<1 2>> 3)"), "<<1 2 3>>");
+    assert_eq!(eval_expr("(%assoc <<1 2>> 0 9)"), "<<9 2>>");
+    assert_eq!(eval_expr("(into <<1>> '(2 3))"), "<<1 2 3>>");
+    assert_eq!(eval_expr("(into '(1) <<2 3>>)"), "(3 2 1)");
+}
+
+#[test]
+fn test_vec() {
+    assert_eq!(eval_expr("(vec '(1 2 3))"), "<<1 2 3>>");
+    assert_eq!(eval_expr("(vec nil)"), "<<>>");
+    assert_eq!(eval_expr("(vec (%rest <<1 2 3>>))"), "<<2 3>>");
+    assert_eq!(
+        eval_expr("((lambda (v) (identical? v (vec v))) <<1 2>>)"),
+        "t"
+    );
+}
+
+#[test]
+fn test_sort() {
+    assert_eq!(eval_expr("(sort '(3 1 2))"), "(1 2 3)");
+    assert_eq!(
+        eval_expr("(sort (lambda (a b) (> a b)) '(3 1 2))"),
+        "(3 2 1)"
+    );
+    assert_eq!(eval_expr("(sort '(\"b\" \"a\"))"), "(\"a\" \"b\")");
+    // Stable: elements that compare equal keep their order
+    assert_eq!(
+        eval_expr("(sort (lambda (a b) (< (car a) (car b))) '((1 x) (0 y) (1 z)))"),
+        "((0 y) (1 x) (1 z))"
+    );
+    assert!(eval_expr("(sort '(1 a))").contains("sort: cannot compare"));
+}
+
+#[test]
+fn test_sort_by() {
+    assert_eq!(
+        eval_expr("(sort-by (lambda (x) (car x)) '((2 b) (1 a) (2 c) (0 d)))"),
+        "((0 d) (1 a) (2 b) (2 c))"
+    );
+    assert_eq!(
+        eval_expr("(sort-by str-length (lambda (a b) (> a b)) <<\"a\" \"abc\" \"ab\">>)"),
+        "<<\"abc\" \"ab\" \"a\">>"
+    );
+    assert_eq!(eval_expr("(sort-by car nil)"), "nil");
+    assert!(eval_expr("(sort-by car '((1) (a)))").contains("sort-by: cannot compare"));
+
+    // key is called once per element, not once per comparison
+    let (result, calls) = eval_counting("(sort-by tick '(5 3 4 1 2))");
+    assert_eq!((result.as_str(), calls), ("(1 2 3 4 5)", 5));
+}
+
+#[test]
+fn test_sort_stops_at_comparator_error() {
+    let (result, calls) = eval_counting(
+        "(try (sort (lambda (a b) (cond ((tick a) (error \"boom\")))) '(3 1 2)) \
+         (catch e (error-message e)))",
+    );
+    assert_eq!((result.as_str(), calls), ("\"boom\"", 1));
+    assert_eq!(
+        eval_expr("(sort-by (lambda (x) (car x)) '(1 2))"),
+        eval_expr("(car 1)")
+    );
+}
+
+#[test]
+fn test_sort_puts_nan_last() {
+    assert_eq!(
+        eval_expr("(sort '(2.0 ##NaN ##-Inf 1 ##NaN ##Inf))"),
+        "(##-Inf 1 2.0 ##Inf ##NaN ##NaN)"
+    );
+}
+
+#[test]
+fn test_vector_pipeline_stays_vector() {
+    assert_eq!(
+        eval_expr("(sort (distinct (reverse (into (%conj <<3 1>> 2) '(1 5)))))"),
+        "<<1 2 3 5>>"
+    );
+    assert_eq!(
+        eval_expr("(vector-ref (sort (distinct (reverse (into (%conj <<3 1>> 2) '(1 5))))) 3)"),
+        "5"
+    );
+}
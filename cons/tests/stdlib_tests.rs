@@ -94,7 +94,7 @@ fn test_slurp_nonexistent_file() {
     );
 
     assert!(result.is_err());
-    assert!(result.unwrap_err().contains("slurp"));
+    assert!(result.unwrap_err().to_string().contains("slurp"));
 }
 
 #[test]
@@ -121,6 +121,201 @@ fn test_spit_multiline() {
     fs::remove_file(&test_file).unwrap();
 }
 
+fn write_fixture(name: &str, bytes: &[u8]) -> std::path::PathBuf {
+    let path = std::env::temp_dir().join(format!("consair_test_{name}_{}", std::process::id()));
+    fs::write(&path, bytes).unwrap();
+    path
+}
+
+// "café" in Latin-1, followed by an invalid UTF-8 continuation
+const LATIN1_BYTES: &[u8] = &[b'c', b'a', b'f', 0xE9];
+
+#[test]
+fn test_slurp_strict_utf8_error_has_offset_and_hint() {
+    let mut env = create_test_env();
+    let path = write_fixture("slurp_strict", LATIN1_BYTES);
+    let code = format!(r#"(slurp "{}")"#, path.to_str().unwrap());
+
+    let err = eval(parse(&code).unwrap(), &mut env)
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("offset 3"), "missing offset: {err}");
+    assert!(err.contains(":utf8-lossy"), "missing hint: {err}");
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_slurp_explicit_utf8() {
+    let mut env = create_test_env();
+    let path = write_fixture("slurp_utf8", "café".as_bytes());
+    let code = format!(r#"(slurp "{}" :encoding :utf8)"#, path.to_str().unwrap());
+
+    let result = eval(parse(&code).unwrap(), &mut env).unwrap();
+    assert_eq!(extract_string(&result), "café");
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_slurp_utf8_lossy() {
+    let mut env = create_test_env();
+    let path = write_fixture("slurp_lossy", LATIN1_BYTES);
+    let code = format!(
+        r#"(slurp "{}" :encoding :utf8-lossy)"#,
+        path.to_str().unwrap()
+    );
+
+    let result = eval(parse(&code).unwrap(), &mut env).unwrap();
+    assert_eq!(extract_string(&result), "caf\u{FFFD}");
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_slurp_latin1() {
+    let mut env = create_test_env();
+    let path = write_fixture("slurp_latin1", &[b'c', b'a', b'f', 0xE9, 0xFF]);
+    let code = format!(r#"(slurp "{}" :encoding :latin1)"#, path.to_str().unwrap());
+
+    let result = eval(parse(&code).unwrap(), &mut env).unwrap();
+    assert_eq!(extract_string(&result), "café\u{FF}");
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_slurp_bytes() {
+    let mut env = create_test_env();
+    let path = write_fixture("slurp_bytes", &[0, 255, 16]);
+    let path_str = path.to_str().unwrap();
+
+    let result = eval(
+        parse(&format!(r#"(slurp "{path_str}" :bytes t)"#)).unwrap(),
+        &mut env,
+    )
+    .unwrap();
+    assert_eq!(result.to_string(), "<<0 255 16>>");
+
+    let result = eval(
+        parse(&format!(r#"(slurp-bytes "{path_str}")"#)).unwrap(),
+        &mut env,
+    )
+    .unwrap();
+    assert_eq!(result.to_string(), "<<0 255 16>>");
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_slurp_unknown_encoding() {
+    let mut env = create_test_env();
+    let path = write_fixture("slurp_unknown", b"abc");
+    let code = format!(r#"(slurp "{}" :encoding :ebcdic)"#, path.to_str().unwrap());
+
+    let err = eval(parse(&code).unwrap(), &mut env)
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("unsupported encoding :ebcdic"));
+    fs::remove_file(&path).unwrap();
+}
+
+fn spit_test_dir(name: &str) -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join(format!("consair_test_{name}_{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[test]
+fn test_spit_append_twice_concatenates() {
+    let mut env = create_test_env();
+    let dir = spit_test_dir("spit_append");
+    let file = dir.join("log.txt");
+    let file_str = file.to_str().unwrap();
+
+    for line in ["first ", "second"] {
+        let code = format!(r#"(spit "{file_str}" "{line}" :append t)"#);
+        eval(parse(&code).unwrap(), &mut env).unwrap();
+    }
+
+    assert_eq!(fs::read_to_string(&file).unwrap(), "first second");
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_spit_atomic_leaves_no_temp_file() {
+    let mut env = create_test_env();
+    let dir = spit_test_dir("spit_atomic");
+    let file = dir.join("config.txt");
+    let file_str = file.to_str().unwrap();
+    fs::write(&file, "old contents").unwrap();
+
+    let code = format!(r#"(spit "{file_str}" "new" :atomic t)"#);
+    eval(parse(&code).unwrap(), &mut env).unwrap();
+
+    assert_eq!(fs::read_to_string(&file).unwrap(), "new");
+    let entries: Vec<_> = fs::read_dir(&dir).unwrap().collect();
+    assert_eq!(entries.len(), 1, "temp file left behind: {entries:?}");
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_spit_atomic_and_append_rejected() {
+    let mut env = create_test_env();
+    let dir = spit_test_dir("spit_conflict");
+    let file = dir.join("out.txt");
+    let file_str = file.to_str().unwrap();
+
+    let code = format!(r#"(spit "{file_str}" "x" :append t :atomic t)"#);
+    let err = eval(parse(&code).unwrap(), &mut env)
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains(":append and :atomic"));
+    assert!(!file.exists());
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_spit_create_dirs() {
+    let mut env = create_test_env();
+    let dir = spit_test_dir("spit_dirs");
+    let file = dir.join("a").join("b").join("out.txt");
+    let file_str = file.to_str().unwrap();
+
+    // Without :create-dirs the missing parent is an error naming the path
+    let code = format!(r#"(spit "{file_str}" "x")"#);
+    let err = eval(parse(&code).unwrap(), &mut env)
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains(file_str));
+
+    let code = format!(r#"(spit "{file_str}" "x" :create-dirs t)"#);
+    eval(parse(&code).unwrap(), &mut env).unwrap();
+    assert_eq!(fs::read_to_string(&file).unwrap(), "x");
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_spit_unknown_option() {
+    let mut env = create_test_env();
+    let err = eval(parse(r#"(spit "a" "b" :apend t)"#).unwrap(), &mut env)
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("unknown option :apend, did you mean :append?"));
+}
+
+#[test]
+fn test_spit_options_map() {
+    let mut env = create_test_env();
+    let dir = spit_test_dir("spit_map");
+    let file = dir.join("log.txt");
+    let file_str = file.to_str().unwrap();
+
+    for line in ["a", "b"] {
+        let code = format!(r#"(spit "{file_str}" "{line}" (%hash-map :append t))"#);
+        eval(parse(&code).unwrap(), &mut env).unwrap();
+    }
+
+    assert_eq!(fs::read_to_string(&file).unwrap(), "ab");
+    fs::remove_dir_all(&dir).unwrap();
+}
+
 // ============================================================================
 // Shell Command Tests
 // ============================================================================
@@ -192,6 +387,186 @@ fn test_shell_with_stderr() {
     assert!(stderr_str.contains("error"));
 }
 
+#[test]
+fn test_shell_dir_option() {
+    let mut env = create_test_env();
+    let dir = std::env::temp_dir().canonicalize().unwrap();
+    let code = format!(r#"(shell "pwd" :dir "{}")"#, dir.to_str().unwrap());
+    let result = eval(parse(&code).unwrap(), &mut env).unwrap();
+    let out = extract_string(&alist_get(&result, "out").unwrap());
+    assert_eq!(
+        std::path::Path::new(out.trim()).canonicalize().unwrap(),
+        dir
+    );
+}
+
+#[test]
+fn test_shell_timeout_kills_command() {
+    let mut env = create_test_env();
+    let err = eval(
+        parse(r#"(shell "sleep 5" :timeout-ms 100)"#).unwrap(),
+        &mut env,
+    )
+    .unwrap_err()
+    .to_string();
+    assert!(err.contains("timed out after 100ms"));
+}
+
+#[test]
+fn test_shell_timeout_not_reached() {
+    let mut env = create_test_env();
+    let result = eval(
+        parse(r#"(shell "echo fast" :timeout-ms 5000)"#).unwrap(),
+        &mut env,
+    )
+    .unwrap();
+    let out = extract_string(&alist_get(&result, "out").unwrap());
+    assert_eq!(out.trim(), "fast");
+    assert_eq!(extract_int(&alist_get(&result, "exit").unwrap()), 0);
+}
+
+#[test]
+fn test_shell_option_typo() {
+    let mut env = create_test_env();
+    let err = eval(parse(r#"(shell "true" :timout 5)"#).unwrap(), &mut env)
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("did you mean :timeout-ms?"));
+}
+
+// ============================================================================
+// Association List Tests
+// ============================================================================
+
+fn eval_to_string(env: &mut Environment, code: &str) -> String {
+    match eval(parse(code).unwrap(), env) {
+        Ok(value) => value.to_string(),
+        Err(e) => format!("Error: {e}"),
+    }
+}
+
+#[test]
+#[cfg(not(target_os = "windows"))]
+fn test_alist_get_on_shell_result() {
+    let mut env = create_test_env();
+    assert_eq!(
+        eval_to_string(&mut env, r#"(alist-get (shell "echo hi") 'out)"#),
+        r#""hi\n""#
+    );
+    assert_eq!(
+        eval_to_string(&mut env, r#"(alist-get (shell "echo hi") 'exit)"#),
+        "0"
+    );
+    assert_eq!(
+        eval_to_string(&mut env, r#"(alist-keys (shell "true"))"#),
+        "(out err exit success)"
+    );
+    assert_eq!(
+        eval_to_string(&mut env, r#"(%get (shell "echo hi") 'success)"#),
+        "t"
+    );
+}
+
+/// Environment with `ab` bound to ((a . 1) (b . 2)), since the reader has no
+/// dotted pair syntax
+fn alist_env() -> Environment {
+    let mut env = create_test_env();
+    eval_to_string(&mut env, "(label ab (list (cons 'a 1) (cons 'b 2)))");
+    env
+}
+
+#[test]
+fn test_alist_get_missing_key_defaults() {
+    let mut env = alist_env();
+    assert_eq!(eval_to_string(&mut env, "(alist-get ab 'b)"), "2");
+    assert_eq!(eval_to_string(&mut env, "(alist-get ab 'c)"), "nil");
+    assert_eq!(eval_to_string(&mut env, "(alist-get ab 'c 0)"), "0");
+    assert_eq!(eval_to_string(&mut env, "(alist-get nil :a 5)"), "5");
+    assert_eq!(
+        eval_to_string(&mut env, "(alist-get (list (cons :k 1) (cons :k 2)) :k)"),
+        "1"
+    );
+    assert_eq!(eval_to_string(&mut env, "(%get ab 'a)"), "1");
+    assert_eq!(eval_to_string(&mut env, "(%get ab 'c 7)"), "7");
+    // A list of non-pairs is not an alist
+    assert_eq!(eval_to_string(&mut env, "(%get '(1 2) 1 :none)"), ":none");
+    assert!(
+        eval_to_string(&mut env, "(alist-get '(1 2) 1)")
+            .contains("alist-get: expected an association list, got (1 2)")
+    );
+}
+
+#[test]
+fn test_alist_set_and_keys() {
+    let mut env = alist_env();
+    assert_eq!(
+        eval_to_string(&mut env, "(alist-set ab 'a 9)"),
+        "((a . 9) (b . 2))"
+    );
+    assert_eq!(
+        eval_to_string(&mut env, "(alist-set ab :c 3)"),
+        "((a . 1) (b . 2) (:c . 3))"
+    );
+    // The original is unchanged
+    assert_eq!(eval_to_string(&mut env, "ab"), "((a . 1) (b . 2))");
+    assert_eq!(
+        eval_to_string(&mut env, "(alist-set nil 'a 1)"),
+        "((a . 1))"
+    );
+    assert_eq!(eval_to_string(&mut env, "(alist-keys ab)"), "(a b)");
+    assert_eq!(eval_to_string(&mut env, "(alist-keys nil)"), "nil");
+}
+
+// ============================================================================
+// Apply Tests
+// ============================================================================
+
+#[test]
+fn test_apply_native_and_lambda() {
+    let mut env = create_test_env();
+    assert_eq!(eval_to_string(&mut env, "(apply + '(1 2 3))"), "6");
+    assert_eq!(
+        eval_to_string(&mut env, "(apply (lambda (x y) (* x y)) (list 3 4))"),
+        "12"
+    );
+    assert_eq!(eval_to_string(&mut env, "(apply list nil)"), "nil");
+    assert_eq!(
+        eval_to_string(&mut env, "(apply (lambda (&rest xs) xs) 1 2 '(3 4))"),
+        "(1 2 3 4)"
+    );
+    assert_eq!(eval_to_string(&mut env, "(apply + 1 2 nil)"), "3");
+    assert_eq!(
+        eval_to_string(&mut env, "(apply apply (list + '(1 2)))"),
+        "3"
+    );
+}
+
+#[test]
+fn test_apply_errors() {
+    let mut env = create_test_env();
+    assert_eq!(
+        eval_to_string(&mut env, "(apply (lambda (x y) x) '(1 2 3))"),
+        "Error: lambda: expected 2 arguments, got 3 [E010_ARITY]"
+    );
+    assert_eq!(
+        eval_to_string(&mut env, "(apply car)"),
+        "Error: apply: expected at least 2 arguments, got 1 [E010_ARITY]"
+    );
+    assert_eq!(
+        eval_to_string(&mut env, "(apply + 1 2)"),
+        "Error: apply: expected a list as the last argument, got 2 [E020_TYPE]"
+    );
+    assert_eq!(
+        eval_to_string(&mut env, "(apply 'car '(1))"),
+        "Error: apply: expected a function, got car [E020_TYPE]"
+    );
+    eval_to_string(&mut env, "(defmacro twice (x) (list '+ x x))");
+    assert!(
+        eval_to_string(&mut env, "(apply twice '(1))")
+            .starts_with("Error: apply: expected a function")
+    );
+}
+
 // ============================================================================
 // Time Tests
 // ============================================================================
@@ -220,7 +595,12 @@ fn test_now_with_args_fails() {
     let result = eval(parse("(now 123)").unwrap(), &mut env);
 
     assert!(result.is_err());
-    assert!(result.unwrap_err().contains("expected 0 arguments"));
+    assert!(
+        result
+            .unwrap_err()
+            .to_string()
+            .contains("expected 0 arguments")
+    );
 }
 
 // ============================================================================
@@ -266,6 +646,32 @@ fn test_println_with_numbers() {
     assert_eq!(result.unwrap(), Value::Nil);
 }
 
+#[test]
+fn test_pprint_str_breaks_long_forms() {
+    let mut env = create_test_env();
+
+    let short = eval(parse("(pprint-str '(a (b c)))").unwrap(), &mut env).unwrap();
+    assert_eq!(extract_string(&short), "(a (b c))");
+
+    let source = "(pprint-str '(define (f x) (cond ((= x 0) 1) (t (* x (f (- x 1)))))) 30)";
+    let result = eval(parse(source).unwrap(), &mut env).unwrap();
+    assert_eq!(
+        extract_string(&result),
+        "(define (f x)\n (cond ((= x 0) 1)\n  (t (* x (f (- x 1))))))"
+    );
+}
+
+#[test]
+fn test_pprint_returns_nil() {
+    let mut env = create_test_env();
+
+    let result = eval(parse("(pprint '(1 2 3))").unwrap(), &mut env).unwrap();
+    assert_eq!(result, Value::Nil);
+
+    let err = eval(parse("(pprint-str '(1 2) 0)").unwrap(), &mut env).unwrap_err();
+    assert!(err.to_string().contains("positive integer width"), "{err}");
+}
+
 // ============================================================================
 // Integration Tests
 // ============================================================================
@@ -88,26 +88,44 @@ fn test_string_with_unicode_escape() {
 //     }
 // }
 
-// Character literal syntax has been removed in favor of maximum minimalism
-// Use single-character strings instead: "a" instead of #\a
-// #[test]
-// fn test_character_literal() {
-//     let result = parse(r"#\a").unwrap();
-//     match result {
-//         Value::Atom(AtomType::Char('a')) => {}
-//         _ => panic!("Expected character 'a', got {result:?}"),
-//     }
-// }
+#[test]
+fn test_character_literal() {
+    let result = parse(r"#\a").unwrap();
+    match result {
+        Value::Atom(AtomType::Char('a')) => {}
+        _ => panic!("Expected character 'a', got {result:?}"),
+    }
+}
 
-// Character literal syntax has been removed
-// #[test]
-// fn test_character_named() {
-//     let result = parse(r"#\newline").unwrap();
-//     match result {
-//         Value::Atom(AtomType::Char('\n')) => {}
-//         _ => panic!("Expected newline character, got {result:?}"),
-//     }
-// }
+#[test]
+fn test_character_named() {
+    let result = parse(r"#\newline").unwrap();
+    match result {
+        Value::Atom(AtomType::Char('\n')) => {}
+        _ => panic!("Expected newline character, got {result:?}"),
+    }
+}
+
+#[test]
+fn test_character_literals_print_in_reader_syntax() {
+    for (source, expected, printed) in [
+        (r"#\space", ' ', r"#\space"),
+        (r"#\tab", '\t', r"#\tab"),
+        (r"#\u03BB", 'λ', r"#\λ"),
+        (r"#\λ", 'λ', r"#\λ"),
+        (r"#\u0007", '\u{7}', r"#\u0007"),
+        (r"#\(", '(', r"#\("),
+        (r"#\u", 'u', r"#\u"),
+    ] {
+        let value = parse(source).unwrap();
+        assert_eq!(value, Value::Atom(AtomType::Char(expected)), "{source}");
+        assert_eq!(value.to_string(), printed);
+        assert_eq!(parse(printed).unwrap(), value, "{printed} reads back");
+    }
+    assert_eq!(parse(r"(#\( #\))").unwrap().to_string(), r"(#\( #\))");
+    assert!(parse(r"#\bogus").is_err());
+    assert!(parse(r"#\uD800").is_err());
+}
 
 // Keyword syntax has been removed in favor of maximum minimalism
 // #[test]
@@ -298,14 +316,241 @@ fn test_strings_are_self_evaluating() {
     }
 }
 
-// Character literal syntax has been removed
-// #[test]
-// fn test_chars_are_self_evaluating() {
-//     let mut env = Environment::new();
-//     let result = eval(parse(r"#\a").unwrap(), &mut env).unwrap();
-//
-//     match result {
-//         Value::Atom(AtomType::Char('a')) => {}
-//         _ => panic!("Expected char to be self-evaluating, got {result:?}"),
-//     }
-// }
+#[test]
+fn test_chars_are_self_evaluating() {
+    let mut env = Environment::new();
+    let result = eval(parse(r"#\a").unwrap(), &mut env).unwrap();
+
+    match result {
+        Value::Atom(AtomType::Char('a')) => {}
+        _ => panic!("Expected char to be self-evaluating, got {result:?}"),
+    }
+}
+
+fn eval_chars(source: &str) -> String {
+    match cons::run(source) {
+        Ok(value) => value.to_string(),
+        Err(e) => format!("Error: {e}"),
+    }
+}
+
+#[test]
+fn test_char_functions() {
+    assert_eq!(eval_chars(r"(char? #\a)"), "t");
+    assert_eq!(eval_chars(r#"(char? "a")"#), "nil");
+    assert_eq!(eval_chars(r"(char->int #\A)"), "65");
+    assert_eq!(eval_chars(r"(char->int #\u03BB)"), "955");
+    assert_eq!(eval_chars("(int->char 955)"), r"#\λ");
+    assert_eq!(eval_chars(r"(eq #\a (int->char 97))"), "t");
+    assert_eq!(eval_chars(r"(eq #\a #\b)"), "nil");
+    assert_eq!(
+        eval_chars("(int->char -1)"),
+        "Error: int->char: -1 is not a Unicode code point"
+    );
+    assert!(eval_chars("(int->char 55296)").contains("not a Unicode code point"));
+    assert!(eval_chars(r#"(char->int "a")"#).contains("[E020_TYPE]"));
+
+    // `->` inside a name does not end the symbol, but `->>` still closes a vector
+    assert_eq!(parse("char->int").unwrap().to_string(), "char->int");
+    assert_eq!(parse("<<x->>").unwrap().to_string(), "<<x->>");
+}
+
+#[test]
+fn test_strings_yield_chars() {
+    assert_eq!(eval_chars(r#"(%nth "héllo" 1)"#), r"#\é");
+    assert_eq!(eval_chars(r#"(%first "ab")"#), r"#\a");
+    assert_eq!(eval_chars(r#"(map char->int (%seq "a b"))"#), "(97 32 98)");
+    assert_eq!(eval_chars(r#"(%get "abc" 2)"#), r"#\c");
+}
+
+#[test]
+fn test_quoted_string_with_escaped_quotes_round_trips() {
+    let mut env = Environment::new();
+    let result = eval(parse(r#"(quote "say \"hi\"\t(ok)")"#).unwrap(), &mut env).unwrap();
+    assert_eq!(result.to_string(), r#""say \"hi\"\t(ok)""#);
+    assert_eq!(parse(&result.to_string()).unwrap(), result);
+}
+
+#[test]
+fn test_string_containing_parentheses() {
+    let list = parse(r#"("(" ")" "a (b) c")"#).unwrap();
+    assert_eq!(list.to_string(), r#"("(" ")" "a (b) c")"#);
+}
+
+#[test]
+fn test_unterminated_string_is_an_error() {
+    let err = parse(r#"(quote "hello)"#).unwrap_err();
+    assert_eq!(err.code(), "P001_UNCLOSED_DELIM", "{err}");
+}
+
+#[test]
+fn test_strings_compare_by_content() {
+    let mut env = Environment::new();
+    cons::register_stdlib(&mut env);
+    let result = eval(parse(r#"(eq "a\nb" (quote "a\nb"))"#).unwrap(), &mut env).unwrap();
+    assert_eq!(result.to_string(), "t");
+}
+
+#[test]
+fn test_str() {
+    assert_eq!(eval_chars("(str)"), r#""""#);
+    assert_eq!(
+        eval_chars(r#"(str "n = " 42 #\! " " 'sym)"#),
+        r#""n = 42! sym""#
+    );
+    assert_eq!(
+        eval_chars("(str '(1 \"a\") \" \" 1/2)"),
+        r#""(1 \"a\") 1/2""#
+    );
+}
+
+#[test]
+fn test_str_length_counts_characters() {
+    assert_eq!(eval_chars(r#"(str-length "")"#), "0");
+    assert_eq!(eval_chars(r#"(str-length "héllo")"#), "5");
+    assert_eq!(eval_chars(r#"(str-length "日本語")"#), "3");
+}
+
+#[test]
+fn test_substring() {
+    assert_eq!(eval_chars(r#"(substring "hello" 1 3)"#), r#""el""#);
+    assert_eq!(eval_chars(r#"(substring "hello" 3)"#), r#""lo""#);
+    assert_eq!(eval_chars(r#"(substring "hello" 5)"#), r#""""#);
+    assert_eq!(eval_chars(r#"(substring "日本語" 1 2)"#), r#""本""#);
+    assert_eq!(
+        eval_chars(r#"(substring "hello" -1)"#),
+        "Error: substring: index -1 is out of range for a string of length 5"
+    );
+    assert_eq!(
+        eval_chars(r#"(substring "héllo" 2 9)"#),
+        "Error: substring: index 9 is out of range for a string of length 5"
+    );
+    assert_eq!(
+        eval_chars(r#"(substring "hello" 3 1)"#),
+        "Error: substring: start 3 is after end 1"
+    );
+}
+
+#[test]
+fn test_str_split_and_join() {
+    assert_eq!(
+        eval_chars(r#"(str-split "a,b,,c" ",")"#),
+        r#"("a" "b" "" "c")"#
+    );
+    assert_eq!(
+        eval_chars(
+            r#"(str-split "  one	two
+three ")"#
+        ),
+        r#"("one" "two" "three")"#
+    );
+    assert_eq!(eval_chars(r#"(str-split "α→β→γ" "→")"#), r#"("α" "β" "γ")"#);
+    assert!(eval_chars(r#"(str-split "abc" "")"#).contains("separator must not be empty"));
+    assert_eq!(
+        eval_chars(r#"(str-join ", " '(1 "b" #\c))"#),
+        r#""1, b, c""#
+    );
+    assert_eq!(eval_chars(r#"(str-join "" <<"x" "y">>)"#), r#""xy""#);
+    assert_eq!(eval_chars(r#"(str-join "-" nil)"#), r#""""#);
+    assert_eq!(
+        eval_chars(r#"(str-join " " (str-split "a b c" " "))"#),
+        r#""a b c""#
+    );
+}
+
+#[test]
+fn test_str_trim_replace_and_case() {
+    assert_eq!(eval_chars("(str-trim \"  hi there \n\")"), r#""hi there""#);
+    assert_eq!(eval_chars(r#"(str-replace "a-b-c" "-" "+")"#), r#""a+b+c""#);
+    assert_eq!(eval_chars(r#"(str-replace "ééé" "é" "e")"#), r#""eee""#);
+    assert!(eval_chars(r#"(str-replace "abc" "" "x")"#).contains("must not be empty"));
+    assert_eq!(eval_chars(r#"(str-upper "straße")"#), r#""STRASSE""#);
+    assert_eq!(eval_chars(r#"(str-lower "ÀB")"#), r#""àb""#);
+}
+
+#[test]
+fn test_string_functions_reject_non_strings() {
+    assert_eq!(
+        eval_chars("(str-upper 'abc)"),
+        "Error: str-upper: expected a string, got abc [E020_TYPE]"
+    );
+    assert!(eval_chars(r#"(substring "abc" "1")"#).contains("expected an integer index"));
+    assert!(eval_chars(r#"(str-trim)"#).contains("[E010_ARITY]"));
+}
+
+#[test]
+fn test_format_directives() {
+    assert_eq!(
+        eval_chars(r#"(format nil "~a has ~d items~%" "cart" 3)"#),
+        r#""cart has 3 items\n""#
+    );
+    assert_eq!(
+        eval_chars(r#"(format nil "100~~ ~A" 'done)"#),
+        r#""100~ done""#
+    );
+    assert_eq!(
+        eval_chars(r#"(format nil "no directives")"#),
+        r#""no directives""#
+    );
+    assert_eq!(
+        eval_chars(r#"(format nil "~d" 99999999999999999999)"#),
+        r#""99999999999999999999""#
+    );
+    assert_eq!(eval_chars(r#"(format t "")"#), "nil");
+}
+
+#[test]
+fn test_format_readable_directive() {
+    // ~s writes strings quoted and escaped, as they read back
+    assert_eq!(
+        eval_chars(r#"(format nil "~s" "say \"hi\"")"#),
+        r#""\"say \\\"hi\\\"\"""#
+    );
+    assert_eq!(
+        eval_chars(r#"(str-length (format nil "~s" "say \"hi\""))"#),
+        "12"
+    );
+    assert_eq!(
+        eval_chars(r#"(format nil "~s / ~a" '(1 "a" (b #\c)) '(1 "a" (b #\c)))"#),
+        r#""(1 \"a\" (b #\\c)) / (1 \"a\" (b #\\c))""#
+    );
+    let written = cons::run(r#"(format nil "~s" '(1 "say \"hi\"" (b)))"#).unwrap();
+    let Value::Atom(AtomType::String(StringType::Basic(written))) = written else {
+        panic!("format returned {written}");
+    };
+    assert_eq!(
+        parse(&written).unwrap(),
+        parse(r#"(1 "say \"hi\"" (b))"#).unwrap()
+    );
+}
+
+#[test]
+fn test_format_float_precision() {
+    assert_eq!(eval_chars(r#"(format nil "~,2f" 3.14159)"#), r#""3.14""#);
+    assert_eq!(eval_chars(r#"(format nil "~,2f" 0.6666)"#), r#""0.67""#);
+    assert_eq!(eval_chars(r#"(format nil "~,0f" 2.7)"#), r#""3""#);
+    assert_eq!(eval_chars(r#"(format nil "~,3f" 1/8)"#), r#""0.125""#);
+    assert_eq!(eval_chars(r#"(format nil "~,1f" -0.96)"#), r#""-1.0""#);
+    assert_eq!(eval_chars(r#"(format nil "~,2f" 5)"#), r#""5.00""#);
+    assert_eq!(eval_chars(r#"(format nil "~f" 1/4)"#), r#""0.25""#);
+}
+
+#[test]
+fn test_format_errors_name_the_directive() {
+    assert_eq!(
+        eval_chars(r#"(format nil "~a ~d" 1 2.5)"#),
+        "Error: format: directive 2 (~d) expects an integer, got 2.5"
+    );
+    assert!(eval_chars(r#"(format nil "~a ~q" 1 2)"#).contains("directive 2 (~q) is unknown"));
+    assert!(
+        eval_chars(r#"(format nil "~% ~a ~a" 1)"#).contains("directive 3 (~a) has no argument")
+    );
+    assert!(
+        eval_chars(r#"(format nil "~a" 1 2)"#)
+            .contains("2 arguments given but the directives use 1")
+    );
+    assert!(eval_chars(r#"(format nil "~f" "x")"#).contains("directive 1 (~f) expects a number"));
+    assert!(eval_chars(r#"(format nil "~,2d" 1)"#).contains("takes no number of decimals"));
+    assert!(eval_chars(r#"(format nil "50~")"#).contains("end of the control string"));
+    assert!(eval_chars(r#"(format 'out "x")"#).contains("nil or t as the destination"));
+}
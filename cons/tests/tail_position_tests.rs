@@ -0,0 +1,114 @@
+use std::thread;
+
+use cons::interpreter::TAIL_POSITIONS;
+use cons::{eval, register_stdlib};
+use consair::{Environment, parse};
+
+/// Small enough that recursing through a non-tail position overflows long
+/// before the test's iteration count.
+const SMALL_STACK: usize = 1024 * 1024;
+
+const ITERATIONS: usize = 100_000;
+
+/// Run `(f iterations)` on a thread with a small stack, where `src` defines
+/// `f` to count down to zero through the construct under test and return
+/// `done`.
+fn assert_tail_safe(src: &str, iterations: usize) {
+    let program = src.to_string();
+    let result = thread::Builder::new()
+        .stack_size(SMALL_STACK)
+        .spawn(move || {
+            let mut env = Environment::new();
+            register_stdlib(&mut env);
+            eval(parse(&program).unwrap(), &mut env).map_err(|e| e.to_string())?;
+            let call = parse(&format!("(f {iterations})")).unwrap();
+            eval(call, &mut env)
+                .map(|value| value.to_string())
+                .map_err(|e| e.to_string())
+        })
+        .unwrap()
+        .join()
+        .expect("evaluation thread panicked");
+    assert_eq!(result.as_deref(), Ok("done"), "not tail safe: {src}");
+}
+
+/// One program per control-flow form, recursing through each of its tail
+/// positions.
+const CASES: &[(&str, &str)] = &[
+    (
+        "cond",
+        "(label f (lambda (n) (cond ((= n 0) 'done) ((> n 1) (f (- n 1))) (t (f (- n 1))))))",
+    ),
+    (
+        "if",
+        "(label f (lambda (n) (if (> n 0) (if (> n 1) (f (- n 1)) (f 0)) 'done)))",
+    ),
+    (
+        "let",
+        "(label f (lambda (n) (let ((m (- n 1))) (cond ((< m 0) 'done) (t (let () (f m)))))))",
+    ),
+    (
+        "do",
+        "(label f (lambda (n) (do n (cond ((= n 0) 'done) (t (do (- n 1) (f (- n 1))))))))",
+    ),
+];
+
+#[test]
+fn test_every_control_form_has_a_tail_case() {
+    for (form, _) in TAIL_POSITIONS {
+        assert!(
+            CASES.iter().any(|(name, _)| name == form),
+            "no tail-safety case for {form}"
+        );
+    }
+}
+
+#[test]
+fn test_cond_branches_are_tail_positions() {
+    assert_tail_safe(CASES[0].1, ITERATIONS);
+    // Only the last form of a clause's body is in tail position
+    assert_tail_safe(
+        "(label f (lambda (n) (cond ((= n 0) 'done) (else n (f (- n 1))))))",
+        ITERATIONS,
+    );
+}
+
+#[test]
+fn test_if_branches_are_tail_positions() {
+    assert_tail_safe(CASES[1].1, ITERATIONS);
+    assert_tail_safe(
+        "(label f (lambda (n) (if (= n 0) 'done (f (- n 1)))))",
+        ITERATIONS,
+    );
+}
+
+#[test]
+fn test_let_body_is_tail_position() {
+    assert_tail_safe(CASES[2].1, ITERATIONS);
+    // Only the last of several body forms is in tail position
+    assert_tail_safe(
+        "(label f (lambda (n) (let ((m (- n 1))) m (cond ((< m 0) 'done) (t (f m))))))",
+        ITERATIONS,
+    );
+}
+
+#[test]
+fn test_do_last_form_is_tail_position() {
+    assert_tail_safe(CASES[3].1, ITERATIONS);
+}
+
+#[test]
+fn test_last_of_several_lambda_body_forms_is_tail_position() {
+    assert_tail_safe(
+        "(label f (lambda (n) n (cond ((= n 0) 'done) (t (f (- n 1))))))",
+        ITERATIONS,
+    );
+}
+
+#[test]
+fn test_lambda_body_is_tail_position() {
+    assert_tail_safe(
+        "(label f (lambda (n) (cond ((= n 0) 'done) (t ((lambda (m) (f m)) (- n 1))))))",
+        ITERATIONS,
+    );
+}
@@ -0,0 +1,80 @@
+//! Truthiness conformance
+//!
+//! Only nil and false are falsy. Every other value, including 0, the empty
+//! string and empty collections, is truthy in both the interpreter and the
+//! JIT.
+
+use cons::jit::JitEngine;
+use cons::{eval, register_stdlib};
+use consair::{Environment, parse};
+
+/// A representative value of each kind, and whether it is truthy
+const CASES: &[(&str, bool)] = &[
+    ("nil", false),
+    ("(not t)", false),
+    ("t", true),
+    ("0", true),
+    ("-1", true),
+    ("0.0", true),
+    ("1/2", true),
+    ("'a", true),
+    ("'(1)", true),
+    ("(cons 1 2)", true),
+    ("(vector)", true),
+    ("(vector 1)", true),
+    ("(lambda (x) x)", true),
+];
+
+/// Values the JIT cannot compile, checked in the interpreter only
+const INTERPRETER_CASES: &[(&str, bool)] = &[
+    ("\"\"", true),
+    ("\"nil\"", true),
+    ("<<>>", true),
+    ("(%hash-map)", true),
+    ("(%hash-set)", true),
+    ("car", true),
+    ("(* 9999999999 9999999999)", true),
+];
+
+fn branch(value: &str) -> String {
+    format!("(cond ({value} 1) (t 2))")
+}
+
+fn interpret(source: &str) -> String {
+    let mut env = Environment::new();
+    register_stdlib(&mut env);
+    eval(parse(source).unwrap(), &mut env).unwrap().to_string()
+}
+
+#[test]
+fn test_interpreter_truthiness() {
+    for &(value, truthy) in CASES.iter().chain(INTERPRETER_CASES) {
+        let expected = if truthy { "1" } else { "2" };
+        assert_eq!(interpret(&branch(value)), expected, "truthiness of {value}");
+        assert_eq!(
+            interpret(&format!("(not {value})")),
+            if truthy { "nil" } else { "t" },
+            "(not {value})"
+        );
+    }
+}
+
+#[test]
+fn test_jit_truthiness_matches_interpreter() {
+    let engine = JitEngine::new().unwrap();
+    for &(value, _) in CASES {
+        let source = branch(value);
+        let jit = engine
+            .eval(&parse(&source).unwrap())
+            .unwrap_or_else(|e| panic!("JIT failed on {source}: {e}"));
+        assert_eq!(
+            jit.to_int().map(|n| n.to_string()),
+            Some(interpret(&source)),
+            "truthiness of {value}"
+        );
+
+        let if_source = format!("(if {value} 1 2)");
+        let jit_if = engine.eval(&parse(&if_source).unwrap()).unwrap();
+        assert_eq!(jit_if.to_int(), jit.to_int(), "{if_source}");
+    }
+}
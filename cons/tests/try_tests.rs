@@ -0,0 +1,79 @@
+//! `try`, `error` and `error-message`
+
+use cons::{register_stdlib, run_with};
+use consair::Environment;
+
+fn eval_str(source: &str) -> Result<String, String> {
+    let mut env = Environment::new();
+    register_stdlib(&mut env);
+    Ok(run_with(&mut env, source)?.to_string())
+}
+
+#[test]
+fn test_catch_car_of_atom() {
+    let source = "(try (car 1) (catch e (error-message e)))";
+    assert_eq!(
+        eval_str(source).unwrap(),
+        "\"car: expected cons cell, got 1\""
+    );
+}
+
+#[test]
+fn test_catch_user_error() {
+    let source = "(try (error \"disk full\") (catch e (error-message e)))";
+    assert_eq!(eval_str(source).unwrap(), "\"disk full\"");
+    let source = "(try (error \"disk full\") (catch e (alist-get e 'code)))";
+    assert_eq!(eval_str(source).unwrap(), "\"E000_CUSTOM\"");
+}
+
+#[test]
+fn test_uncaught_error_keeps_message() {
+    assert_eq!(eval_str("(error \"disk full\")").unwrap_err(), "disk full");
+}
+
+#[test]
+fn test_try_without_error_returns_body_value() {
+    assert_eq!(eval_str("(try 1 2 (catch e 'failed))").unwrap(), "2");
+}
+
+#[test]
+fn test_side_effects_before_error_happen() {
+    let source = "(def log nil) \
+                  (try (do (set! log (cons 'before log)) \
+                           (car 1) \
+                           (set! log (cons 'after log))) \
+                       (catch e (set! log (cons 'caught log)))) \
+                  log";
+    assert_eq!(eval_str(source).unwrap(), "(caught before)");
+}
+
+#[test]
+fn test_nested_try_unwinds_to_nearest_catch() {
+    let source = "(try (list 'outer (try (car 1) (catch e 'inner))) \
+                       (catch e 'outer-caught))";
+    assert_eq!(eval_str(source).unwrap(), "(outer inner)");
+}
+
+#[test]
+fn test_handler_errors_propagate() {
+    let source = "(try (try (car 1) (catch e (error \"rethrown\"))) \
+                       (catch e (error-message e)))";
+    assert_eq!(eval_str(source).unwrap(), "\"rethrown\"");
+    assert!(eval_str("(try (car 1) (catch e (cdr 1)))").is_err());
+}
+
+#[test]
+fn test_catch_from_called_function() {
+    let source = "(label risky (lambda (x) (+ x (car x)))) \
+                  (try (risky 5) (catch e 'recovered))";
+    assert_eq!(eval_str(source).unwrap(), "recovered");
+}
+
+#[test]
+fn test_try_requires_catch_clause() {
+    let err = eval_str("(try (car 1))").unwrap_err();
+    assert!(
+        err.contains("try: expected (catch name handler...)"),
+        "{err}"
+    );
+}
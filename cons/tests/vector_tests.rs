@@ -12,7 +12,7 @@ fn eval_vector_result(expr: &str) -> Result<Value, String> {
     let mut env = Environment::new();
     register_stdlib(&mut env);
     let parsed = parse(expr)?;
-    eval(parsed, &mut env)
+    Ok(eval(parsed, &mut env)?)
 }
 
 // ============================================================================
@@ -3,6 +3,21 @@
 //! This module provides polymorphic behaviors that collections and values implement,
 //! enabling uniform operations across different data types. These abstractions are
 //! engine-level and dialect-agnostic.
+//!
+//! # Collection preservation
+//!
+//! Operations that build a new collection of the same shape return the input's
+//! collection type, so a vector stays a vector: `conj`, `assoc`, `into`,
+//! `reverse`, `sort`, `distinct` and `dedupe`. Sequence views and
+//! transformations (`rest`, `next`, `seq`, `map`, `filter`, ...) may return
+//! lists; use `vec` to turn the result back into a vector. [`rebuild_like`]
+//! implements the preserving case for operations that work on a flat list of
+//! elements.
+//!
+//! # NaN keys
+//!
+//! NaN is not equal to itself, so a NaN map key or set element could never be
+//! looked up again. Maps and sets reject NaN; see [`check_hash_key`].
 
 // Value types can be used as FxHashMap/FxHashSet keys. While Value contains Arc<LambdaCell>
 // which has interior mutability, lambdas as keys is an unusual use case and the Hash/Eq
@@ -239,6 +254,7 @@ impl Lookup for MapValue {
 
 impl Associative for MapValue {
     fn assoc(&self, key: Value, val: Value) -> Result<Self, String> {
+        check_hash_key(&key)?;
         let mut new_entries = self.entries.clone();
         new_entries.insert(key, val);
         Ok(MapValue {
@@ -304,6 +320,7 @@ impl Lookup for PersistentMap {
 
 impl Associative for PersistentMap {
     fn assoc(&self, key: Value, val: Value) -> Result<Self, String> {
+        check_hash_key(&key)?;
         let new_entries = self.entries.update(key, val);
         Ok(PersistentMap {
             entries: new_entries,
@@ -373,6 +390,7 @@ impl Lookup for SetValue {
 
 impl Conjable for SetValue {
     fn conj(&self, item: Value) -> Result<Self, String> {
+        check_hash_key(&item)?;
         let mut new_elements = self.elements.clone();
         new_elements.insert(item);
         Ok(SetValue {
@@ -415,6 +433,7 @@ impl Lookup for PersistentSet {
 
 impl Conjable for PersistentSet {
     fn conj(&self, item: Value) -> Result<Self, String> {
+        check_hash_key(&item)?;
         let new_elements = self.elements.update(item);
         Ok(PersistentSet {
             elements: new_elements,
@@ -487,9 +506,9 @@ impl Seq {
             Seq::SetSeq { elements, index } | Seq::PersistentSetSeq { elements, index } => {
                 elements.get(*index).cloned().unwrap_or(Value::Nil)
             }
-            Seq::StringSeq { chars, index } => chars.get(*index).map_or(Value::Nil, |c| {
-                Value::Atom(AtomType::String(StringType::Basic(c.to_string())))
-            }),
+            Seq::StringSeq { chars, index } => chars
+                .get(*index)
+                .map_or(Value::Nil, |c| Value::Atom(AtomType::Char(*c))),
         }
     }
 
@@ -649,8 +668,21 @@ pub fn rest(value: &Value) -> Value {
         .map_or(Value::Nil, |s| s.to_list())
 }
 
+/// The value other than nil that ends a list, as 2 ends `(1 . 2)`, or None
+/// if `list` is a proper list or not a list at all.
+pub fn improper_tail(list: &Value) -> Option<&Value> {
+    let Value::Cons(_) = list else {
+        return None;
+    };
+    let mut current = list;
+    while let Value::Cons(cell) = current {
+        current = &cell.cdr;
+    }
+    (!matches!(current, Value::Nil)).then_some(current)
+}
+
 /// Count the number of elements in a collection.
-/// Returns None for uncountable types.
+/// Returns None for uncountable types, including improper lists.
 pub fn count(value: &Value) -> Option<usize> {
     match value {
         Value::Nil => Some(0),
@@ -662,7 +694,7 @@ pub fn count(value: &Value) -> Option<usize> {
                 count += 1;
                 current = cell.cdr.clone();
             }
-            Some(count)
+            matches!(current, Value::Nil).then_some(count)
         }
         Value::Vector(vec) => Some(vec.count()),
         Value::PersistentVector(vec) => Some(vec.count()),
@@ -698,11 +730,10 @@ pub fn nth(value: &Value, index: usize, default_val: Option<&Value>) -> Value {
                 default
             }
         }
-        Value::Atom(AtomType::String(StringType::Basic(s))) => {
-            s.chars().nth(index).map_or(default, |c| {
-                Value::Atom(AtomType::String(StringType::Basic(c.to_string())))
-            })
-        }
+        Value::Atom(AtomType::String(StringType::Basic(s))) => s
+            .chars()
+            .nth(index)
+            .map_or(default, |c| Value::Atom(AtomType::Char(c))),
         _ => default,
     }
 }
@@ -718,13 +749,14 @@ pub fn get(coll: &Value, key: &Value, default_val: Option<&Value>) -> Value {
         Value::PersistentSet(set) => set.get_value(key).unwrap_or(default),
         Value::Vector(vec) => vec.get_value(key).unwrap_or(default),
         Value::PersistentVector(vec) => vec.get_value(key).unwrap_or(default),
+        Value::Cons(_) => alist_get(coll, key).unwrap_or(default),
         Value::Atom(AtomType::String(StringType::Basic(s))) => {
             // String lookup by integer index
             if let Value::Atom(AtomType::Number(NumericType::Int(idx))) = key {
                 if *idx >= 0 {
-                    s.chars().nth(*idx as usize).map_or(default, |c| {
-                        Value::Atom(AtomType::String(StringType::Basic(c.to_string())))
-                    })
+                    s.chars()
+                        .nth(*idx as usize)
+                        .map_or(default, |c| Value::Atom(AtomType::Char(c)))
                 } else {
                     default
                 }
@@ -746,6 +778,7 @@ pub fn assoc(coll: &Value, key: Value, val: Value) -> Result<Value, String> {
         Value::PersistentVector(vec) => Ok(Value::PersistentVector(Arc::new(vec.assoc(key, val)?))),
         Value::Nil => {
             // Assoc on nil creates a new fast map
+            check_hash_key(&key)?;
             let mut entries = FxHashMap::default();
             entries.insert(key, val);
             Ok(Value::Map(Arc::new(MapValue { entries })))
@@ -760,6 +793,7 @@ pub fn assoc(coll: &Value, key: Value, val: Value) -> Result<Value, String> {
 /// - Vector: adds at end
 /// - Set: adds element
 /// - Map: expects a [key value] pair
+/// - String: appends a character
 pub fn conj(coll: &Value, item: Value) -> Result<Value, String> {
     match coll {
         Value::Nil => {
@@ -776,10 +810,84 @@ pub fn conj(coll: &Value, item: Value) -> Result<Value, String> {
         Value::PersistentSet(set) => Ok(Value::PersistentSet(Arc::new(set.conj(item)?))),
         Value::Map(map) => Ok(Value::Map(Arc::new(map.conj(item)?))),
         Value::PersistentMap(map) => Ok(Value::PersistentMap(Arc::new(map.conj(item)?))),
+        Value::Atom(AtomType::String(StringType::Basic(s))) => match item {
+            Value::Atom(AtomType::Char(c)) => {
+                let mut s = s.clone();
+                s.push(c);
+                Ok(Value::Atom(AtomType::String(StringType::Basic(s))))
+            }
+            _ => Err(format!(
+                "Cannot conj {} onto a string, expected a character",
+                item
+            )),
+        },
         _ => Err(format!("Cannot conj onto {}", coll)),
     }
 }
 
+/// Check that a value can be used as a map key or set element.
+/// NaN is rejected because it never compares equal to itself.
+pub fn check_hash_key(key: &Value) -> Result<(), String> {
+    match key {
+        Value::Atom(AtomType::Number(n)) if n.is_nan() => {
+            Err("NaN cannot be used as a map key or set element".to_string())
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Build a collection of the same sequential type as `template` from `items`.
+/// Vectors produce vectors of the same kind; anything else produces a list.
+pub fn rebuild_like(template: &Value, items: Vec<Value>) -> Value {
+    match template {
+        Value::Vector(_) => Value::Vector(Arc::new(VectorValue { elements: items })),
+        Value::PersistentVector(_) => Value::PersistentVector(Arc::new(PersistentVector {
+            elements: items.into_iter().collect(),
+        })),
+        _ => items
+            .into_iter()
+            .rev()
+            .fold(Value::Nil, |acc, item| cons(item, acc)),
+    }
+}
+
+// ============================================================================
+// Association Lists - ((key . value) ...)
+// ============================================================================
+
+/// The pairs of an association list, in order, or None if `value` is not a
+/// proper list of cons pairs.
+pub fn alist_pairs(value: &Value) -> Option<Vec<(Value, Value)>> {
+    let mut pairs = Vec::new();
+    let mut current = value;
+    while let Value::Cons(cell) = current {
+        let Value::Cons(pair) = &cell.car else {
+            return None;
+        };
+        pairs.push((pair.car.clone(), pair.cdr.clone()));
+        current = &cell.cdr;
+    }
+    matches!(current, Value::Nil).then_some(pairs)
+}
+
+/// The value of the first pair in an association list whose key is `key`.
+///
+/// Returns None if the key is missing or the list stops being an
+/// association list before it is found.
+pub fn alist_get(alist: &Value, key: &Value) -> Option<Value> {
+    let mut current = alist;
+    while let Value::Cons(cell) = current {
+        let Value::Cons(pair) = &cell.car else {
+            return None;
+        };
+        if pair.car == *key {
+            return Some(pair.cdr.clone());
+        }
+        current = &cell.cdr;
+    }
+    None
+}
+
 // ============================================================================
 // Reduced - Early termination in folds/reductions
 // ============================================================================
@@ -898,6 +1006,7 @@ pub fn is_callable(value: &Value) -> bool {
         value,
         Value::Lambda(_)
             | Value::NativeFn(_)
+            | Value::NativeClosure(_)
             | Value::Map(_)
             | Value::PersistentMap(_)
             | Value::Set(_)
@@ -1095,9 +1204,9 @@ mod tests {
     fn test_seq_string() {
         let s = make_string("abc");
         let seq_s = seq(&s).unwrap();
-        assert_eq!(seq_s.first(), make_string("a"));
+        assert_eq!(seq_s.first(), Value::Atom(AtomType::Char('a')));
         let seq_s2 = seq_s.next().unwrap();
-        assert_eq!(seq_s2.first(), make_string("b"));
+        assert_eq!(seq_s2.first(), Value::Atom(AtomType::Char('b')));
     }
 
     #[test]
@@ -3,20 +3,105 @@
 //! The Environment is a lexical scope that holds variable bindings.
 //! It forms a chain of scopes, with child environments referencing their parents.
 
-use std::collections::HashMap;
-use std::sync::{Arc, RwLock};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
 
+use crate::error::EvalError;
 use crate::interner::InternedSymbol;
-use crate::language::Value;
+use crate::language::{NativeClosure, Value};
 
 // ============================================================================
 // Environment
 // ============================================================================
 
+/// How deeply evaluation may nest before it fails, unless changed with
+/// `Environment::set_recursion_limit`.
+pub const DEFAULT_RECURSION_LIMIT: usize = 10_000;
+
 // Internal state holding the data and parent pointer
 struct EnvironmentState {
     data: HashMap<String, Value>,
+    /// Names in `data` bound with define-constant
+    constants: HashSet<String>,
     parent: Option<Arc<Environment>>,
+    /// Undo log of changes to `data`, kept while a snapshot is outstanding
+    journal: Vec<JournalEntry>,
+    /// Number of snapshots taken and not yet restored
+    open_snapshots: usize,
+}
+
+/// The binding a name had before a change, for `Environment::restore`
+struct JournalEntry {
+    name: String,
+    previous: Option<Value>,
+    was_constant: bool,
+}
+
+impl EnvironmentState {
+    fn new(data: HashMap<String, Value>, parent: Option<Arc<Environment>>) -> Self {
+        EnvironmentState {
+            data,
+            constants: HashSet::new(),
+            parent,
+            journal: Vec::new(),
+            open_snapshots: 0,
+        }
+    }
+
+    /// Record the current binding of `name` if a snapshot may need it back
+    fn record(&mut self, name: &str) {
+        if self.open_snapshots > 0 {
+            self.journal.push(JournalEntry {
+                name: name.to_string(),
+                previous: self.data.get(name).cloned(),
+                was_constant: self.constants.contains(name),
+            });
+        }
+    }
+}
+
+/// Source files being loaded, innermost last, and the files loaded by
+/// `require`
+#[derive(Default)]
+struct SourceFiles {
+    loading: Vec<PathBuf>,
+    required: HashSet<PathBuf>,
+}
+
+/// A point to roll an environment's bindings back to.
+///
+/// Taken with `Environment::snapshot` and consumed by `Environment::restore`.
+/// Only the snapshotted scope is covered; its parents and children are not.
+/// Dropping a snapshot without restoring it keeps the changes made since.
+#[must_use = "a snapshot keeps its environment journaling until it is restored or dropped"]
+pub struct EnvSnapshot {
+    state: Arc<RwLock<EnvironmentState>>,
+    mark: usize,
+}
+
+impl Drop for EnvSnapshot {
+    fn drop(&mut self) {
+        let mut state = self.state.write().unwrap_or_else(|e| e.into_inner());
+        state.open_snapshots -= 1;
+        if state.open_snapshots == 0 {
+            state.journal.clear();
+        }
+    }
+}
+
+/// Keeps the bindings of an environment read-only, from
+/// `Environment::freeze` until it is dropped.
+#[must_use = "the environment is only frozen until this is dropped"]
+pub struct EnvFreeze {
+    frozen: Arc<AtomicUsize>,
+}
+
+impl Drop for EnvFreeze {
+    fn drop(&mut self) {
+        self.frozen.fetch_sub(1, Ordering::SeqCst);
+    }
 }
 
 /// Environment for variable bindings.
@@ -26,6 +111,12 @@ struct EnvironmentState {
 #[derive(Clone)]
 pub struct Environment {
     state: Arc<RwLock<EnvironmentState>>,
+    /// Shared by every environment extended from the same global one
+    recursion_limit: Arc<AtomicUsize>,
+    /// Shared like `recursion_limit`
+    files: Arc<Mutex<SourceFiles>>,
+    /// Number of `EnvFreeze` guards outstanding; shared like `recursion_limit`
+    frozen: Arc<AtomicUsize>,
 }
 
 impl Default for Environment {
@@ -38,10 +129,10 @@ impl Environment {
     /// Create a new, empty global environment
     pub fn new() -> Self {
         Environment {
-            state: Arc::new(RwLock::new(EnvironmentState {
-                data: HashMap::new(),
-                parent: None,
-            })),
+            state: Arc::new(RwLock::new(EnvironmentState::new(HashMap::new(), None))),
+            recursion_limit: Arc::new(AtomicUsize::new(DEFAULT_RECURSION_LIMIT)),
+            files: Arc::default(),
+            frozen: Arc::default(),
         }
     }
 
@@ -53,20 +144,267 @@ impl Environment {
         }
 
         Environment {
-            state: Arc::new(RwLock::new(EnvironmentState {
+            // The child holds a reference to the parent's wrapper
+            state: Arc::new(RwLock::new(EnvironmentState::new(
                 data,
-                // The child holds a reference to the parent's wrapper
-                parent: Some(Arc::new(self.clone())),
-            })),
+                Some(Arc::new(self.clone())),
+            ))),
+            recursion_limit: self.recursion_limit.clone(),
+            files: self.files.clone(),
+            frozen: self.frozen.clone(),
+        }
+    }
+
+    /// How deeply evaluation in this environment may nest before it fails
+    /// with a "maximum recursion depth" error.
+    pub fn recursion_limit(&self) -> usize {
+        self.recursion_limit.load(Ordering::Relaxed)
+    }
+
+    /// Change the recursion limit for this environment and every environment
+    /// extended from the same global one.
+    pub fn set_recursion_limit(&self, limit: usize) {
+        self.recursion_limit.store(limit, Ordering::Relaxed);
+    }
+
+    /// Make the bindings of this environment, and of every environment
+    /// extended from the same global one, read-only until the returned guard
+    /// is dropped.
+    ///
+    /// Clones of an environment can be evaluated in on several threads at
+    /// once; freezing it first keeps `def`, `label`, `set!` and the like from
+    /// changing the bindings those threads share. The interpreter checks
+    /// `is_frozen` before changing a binding; `define` and `set` themselves
+    /// do not, so new scopes can still be filled in. Freezes nest.
+    pub fn freeze(&self) -> EnvFreeze {
+        self.frozen.fetch_add(1, Ordering::SeqCst);
+        EnvFreeze {
+            frozen: self.frozen.clone(),
         }
     }
 
+    /// True while a guard returned by `freeze` is outstanding.
+    pub fn is_frozen(&self) -> bool {
+        self.frozen.load(Ordering::SeqCst) > 0
+    }
+
+    /// The source file being evaluated: the innermost one being loaded, or
+    /// the file being run. None in the REPL and for source given as text.
+    pub fn current_file(&self) -> Option<PathBuf> {
+        self.files.lock().unwrap().loading.last().cloned()
+    }
+
+    /// Note that `path` is being evaluated, until the matching `end_file`.
+    ///
+    /// Fails if `path` is already being evaluated, returning the chain of
+    /// files that leads back to it, outermost first and ending with `path`.
+    pub fn begin_file(&self, path: PathBuf) -> Result<(), Vec<PathBuf>> {
+        let mut files = self.files.lock().unwrap();
+        if let Some(start) = files.loading.iter().position(|open| *open == path) {
+            let mut chain = files.loading[start..].to_vec();
+            chain.push(path);
+            return Err(chain);
+        }
+        files.loading.push(path);
+        Ok(())
+    }
+
+    /// Finish evaluating the file passed to the last `begin_file`.
+    pub fn end_file(&self) {
+        self.files.lock().unwrap().loading.pop();
+    }
+
+    /// True if `mark_required` has recorded `path`.
+    pub fn is_required(&self, path: &Path) -> bool {
+        self.files.lock().unwrap().required.contains(path)
+    }
+
+    /// Record that `require` has loaded `path`, so it is not loaded again.
+    pub fn mark_required(&self, path: PathBuf) {
+        self.files.lock().unwrap().required.insert(path);
+    }
+
     /// Define a variable in the CURRENT scope (mutating the shared state)
     pub fn define(&self, name: String, value: Value) {
         let mut state = self.state.write().unwrap();
+        state.record(&name);
         state.data.insert(name, value);
     }
 
+    /// Define `name` in the CURRENT scope as a native function backed by a
+    /// Rust closure, which may capture state from the embedding application.
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    /// use std::sync::atomic::{AtomicI64, Ordering};
+    /// use consair::{Environment, Value, parse};
+    ///
+    /// let env = Environment::new();
+    /// let calls = Arc::new(AtomicI64::new(0));
+    /// let counter = calls.clone();
+    /// env.register_native("tick", move |_args, _env| {
+    ///     let n = counter.fetch_add(1, Ordering::SeqCst) + 1;
+    ///     Ok(parse(&n.to_string())?)
+    /// });
+    /// assert!(matches!(env.lookup("tick"), Some(Value::NativeClosure(_))));
+    /// ```
+    pub fn register_native<F>(&self, name: &str, func: F)
+    where
+        F: Fn(&[Value], &mut Environment) -> Result<Value, EvalError> + Send + Sync + 'static,
+    {
+        let closure = NativeClosure::new(name, func);
+        self.define(name.to_string(), Value::NativeClosure(Arc::new(closure)));
+    }
+
+    /// Define a constant in the CURRENT scope
+    pub fn define_constant(&self, name: String, value: Value) {
+        let mut state = self.state.write().unwrap();
+        state.record(&name);
+        state.constants.insert(name.clone());
+        state.data.insert(name, value);
+    }
+
+    /// Check whether the nearest binding of `name` is a constant
+    pub fn is_constant(&self, name: &str) -> bool {
+        let state = self.state.read().unwrap();
+
+        if state.data.contains_key(name) {
+            return state.constants.contains(name);
+        }
+
+        match &state.parent {
+            Some(parent) => parent.is_constant(name),
+            None => false,
+        }
+    }
+
+    /// Replace the value of the nearest binding of `name`, returning the old
+    /// value, or None (changing nothing) if `name` is unbound
+    pub fn set(&self, name: &str, value: Value) -> Option<Value> {
+        let mut state = self.state.write().unwrap();
+
+        if state.data.contains_key(name) {
+            state.record(name);
+            return state.data.insert(name.to_string(), value);
+        }
+
+        match &state.parent {
+            Some(parent) => parent.set(name, value),
+            None => None,
+        }
+    }
+
+    /// Mark the current bindings of this scope so `restore` can return to them.
+    ///
+    /// Taking a snapshot is O(1). While any snapshot of a scope is
+    /// outstanding, each change to it is logged, so `restore` costs one step
+    /// per change made since. Snapshots nest; restore them innermost first.
+    /// Dropping a snapshot instead of restoring it stops the journaling it
+    /// needed, keeping the changes.
+    pub fn snapshot(&self) -> EnvSnapshot {
+        let mut state = self.state.write().unwrap();
+        state.open_snapshots += 1;
+        EnvSnapshot {
+            state: self.state.clone(),
+            mark: state.journal.len(),
+        }
+    }
+
+    /// Undo every definition, constant and `set` made in this scope since
+    /// `snapshot` was taken.
+    ///
+    /// Bindings are restored, not values: a value that was changed in place
+    /// stays changed.
+    ///
+    /// # Panics
+    ///
+    /// If `snapshot` was taken of a different environment.
+    pub fn restore(&self, snapshot: EnvSnapshot) {
+        assert!(
+            Arc::ptr_eq(&self.state, &snapshot.state),
+            "snapshot restored into a different environment"
+        );
+        let mut state = self.state.write().unwrap();
+        while state.journal.len() > snapshot.mark {
+            let entry = state.journal.pop().unwrap();
+            match entry.previous {
+                Some(value) => state.data.insert(entry.name.clone(), value),
+                None => state.data.remove(&entry.name),
+            };
+            if entry.was_constant {
+                state.constants.insert(entry.name);
+            } else {
+                state.constants.remove(&entry.name);
+            }
+        }
+        // Dropping the snapshot ends its journaling
+        drop(state);
+        drop(snapshot);
+    }
+
+    /// This scope and each scope above it, innermost first, ending with the
+    /// global environment
+    pub fn scopes(&self) -> Vec<Environment> {
+        let mut scopes = vec![self.clone()];
+        loop {
+            let parent = scopes.last().unwrap().state.read().unwrap().parent.clone();
+            match parent {
+                Some(parent) => scopes.push((*parent).clone()),
+                None => return scopes,
+            }
+        }
+    }
+
+    /// Every name visible from this scope, sorted, each listed once even if
+    /// an inner scope shadows it
+    pub fn names(&self) -> Vec<String> {
+        let mut names = HashSet::new();
+        let mut scope = Some(self.clone());
+        while let Some(env) = scope {
+            let state = env.state.read().unwrap();
+            names.extend(state.data.keys().cloned());
+            scope = state.parent.as_deref().cloned();
+        }
+        let mut names: Vec<String> = names.into_iter().collect();
+        names.sort();
+        names
+    }
+
+    /// The bindings made in this scope itself, not its parents, sorted by name
+    pub fn iter_local(&self) -> impl Iterator<Item = (String, Value)> + use<> {
+        let state = self.state.read().unwrap();
+        let mut bindings: Vec<(String, Value)> = state
+            .data
+            .iter()
+            .map(|(name, value)| (name.clone(), value.clone()))
+            .collect();
+        bindings.sort_by(|a, b| a.0.cmp(&b.0));
+        bindings.into_iter()
+    }
+
+    /// The global environment at the root of this scope's parent chain
+    pub fn global(&self) -> Environment {
+        let mut env = self.clone();
+        loop {
+            let parent = env.state.read().unwrap().parent.clone();
+            match parent {
+                Some(parent) => env = (*parent).clone(),
+                None => return env,
+            }
+        }
+    }
+
+    /// Number of scopes above this one; 0 for a global environment
+    pub fn depth(&self) -> usize {
+        let mut depth = 0;
+        let mut scope = self.state.read().unwrap().parent.clone();
+        while let Some(env) = scope {
+            depth += 1;
+            scope = env.state.read().unwrap().parent.clone();
+        }
+        depth
+    }
+
     /// Look up a variable, walking up the parent chain
     pub fn lookup(&self, name: &str) -> Option<Value> {
         let state = self.state.read().unwrap();
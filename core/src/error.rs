@@ -0,0 +1,266 @@
+//! Evaluation and parse errors
+//!
+//! `EvalError` is the error type of `eval`, native functions and the list
+//! primitives. The common failures have their own variants so embedding code
+//! can tell them apart without matching on message text; everything else is
+//! `Custom`. Every variant displays as the message the REPL prints.
+//!
+//! `ParseError` is the error type of the lexer and parser.
+//!
+//! Each kind of error has a stable code, listed in [`codes`], which is shown
+//! in brackets after the message. Code that needs to react to a particular
+//! error should compare codes, since message wording may change.
+
+use std::fmt;
+
+use crate::interner::InternedSymbol;
+use crate::language::Value;
+
+/// An error raised while evaluating Lisp code.
+#[derive(Debug, Clone, PartialEq)]
+pub enum EvalError {
+    /// A symbol with no binding was evaluated
+    UnboundSymbol(InternedSymbol),
+    /// A function was called with the wrong number of arguments
+    ArityMismatch {
+        /// Name of the function, or `lambda` for anonymous ones
+        callee: String,
+        expected: Arity,
+        got: usize,
+    },
+    /// A value of the wrong type was passed
+    TypeError {
+        /// The operation that rejected the value, if known
+        callee: Option<String>,
+        /// Description of the accepted type, e.g. `cons cell`
+        expected: String,
+        found: Value,
+    },
+    /// Source text could not be read
+    Parse(ParseError),
+    /// Any other error, described by its message
+    Custom(String),
+}
+
+impl EvalError {
+    /// The stable code for this kind of error.
+    ///
+    /// `Custom` errors share `E000_CUSTOM`, which is not shown in their
+    /// message since their text is all there is to tell them apart.
+    pub fn code(&self) -> &'static str {
+        match self {
+            EvalError::UnboundSymbol(_) => codes::UNBOUND_SYMBOL,
+            EvalError::ArityMismatch { .. } => codes::ARITY,
+            EvalError::TypeError { .. } => codes::TYPE,
+            EvalError::Parse(error) => error.code(),
+            EvalError::Custom(_) => codes::CUSTOM,
+        }
+    }
+
+    /// The error's message, without the code shown after it.
+    pub fn message(&self) -> String {
+        match self {
+            EvalError::UnboundSymbol(name) => format!("Unbound symbol: {name}"),
+            EvalError::ArityMismatch {
+                callee,
+                expected,
+                got,
+            } => format!("{callee}: expected {expected}, got {got}"),
+            EvalError::TypeError {
+                callee: Some(callee),
+                expected,
+                found,
+            } => format!("{callee}: expected {expected}, got {found}"),
+            EvalError::TypeError {
+                callee: None,
+                expected,
+                found,
+            } => format!("Expected {expected}, got {found}"),
+            EvalError::Parse(error) => error.message.clone(),
+            EvalError::Custom(message) => message.clone(),
+        }
+    }
+}
+
+/// The number of arguments a function accepts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Arity {
+    Exact(usize),
+    AtLeast(usize),
+    AtMost(usize),
+    Range(usize, usize),
+}
+
+impl fmt::Display for Arity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Arity::Exact(n) => write!(f, "{n} {}", arguments(*n)),
+            Arity::AtLeast(n) => write!(f, "at least {n} {}", arguments(*n)),
+            Arity::AtMost(n) => write!(f, "at most {n} {}", arguments(*n)),
+            Arity::Range(min, max) => write!(f, "{min}-{max} arguments"),
+        }
+    }
+}
+
+fn arguments(n: usize) -> &'static str {
+    if n == 1 { "argument" } else { "arguments" }
+}
+
+impl fmt::Display for EvalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EvalError::Parse(error) => write!(f, "{error}"),
+            EvalError::Custom(message) => write!(f, "{message}"),
+            _ => write!(f, "{} [{}]", self.message(), self.code()),
+        }
+    }
+}
+
+impl std::error::Error for EvalError {}
+
+impl From<String> for EvalError {
+    fn from(message: String) -> Self {
+        EvalError::Custom(message)
+    }
+}
+
+impl From<&str> for EvalError {
+    fn from(message: &str) -> Self {
+        EvalError::Custom(message.to_string())
+    }
+}
+
+impl From<ParseError> for EvalError {
+    fn from(error: ParseError) -> Self {
+        EvalError::Parse(error)
+    }
+}
+
+/// Lets code that still reports errors as strings use `?` on evaluation results.
+impl From<EvalError> for String {
+    fn from(error: EvalError) -> Self {
+        error.to_string()
+    }
+}
+
+// ============================================================================
+// Parse Errors
+// ============================================================================
+
+/// An error raised while reading source text.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    pub kind: ParseErrorKind,
+    /// Description of the error, including where it occurred
+    pub message: String,
+}
+
+/// The ways source text can fail to parse.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseErrorKind {
+    /// A list, vector, map, set or string was not closed
+    UnclosedDelimiter,
+    /// A closing delimiter with no matching opening one
+    UnexpectedClose,
+    /// The input ended where a form was expected
+    UnexpectedEof,
+    /// Text that is not a valid token
+    InvalidToken,
+    /// A map literal with an odd number of forms or an invalid key
+    InvalidMapLiteral,
+    /// A set literal with a repeated or invalid element
+    InvalidSetLiteral,
+    /// A `.` that is not between the last two forms of a list
+    InvalidDottedList,
+}
+
+impl ParseError {
+    pub fn new(kind: ParseErrorKind, message: impl Into<String>) -> Self {
+        ParseError {
+            kind,
+            message: message.into(),
+        }
+    }
+
+    /// The stable code for this kind of error.
+    pub fn code(&self) -> &'static str {
+        match self.kind {
+            ParseErrorKind::UnclosedDelimiter => codes::UNCLOSED_DELIM,
+            ParseErrorKind::UnexpectedClose => codes::UNEXPECTED_CLOSE,
+            ParseErrorKind::UnexpectedEof => codes::UNEXPECTED_EOF,
+            ParseErrorKind::InvalidToken => codes::INVALID_TOKEN,
+            ParseErrorKind::InvalidMapLiteral => codes::INVALID_MAP_LITERAL,
+            ParseErrorKind::InvalidSetLiteral => codes::INVALID_SET_LITERAL,
+            ParseErrorKind::InvalidDottedList => codes::INVALID_DOTTED_LIST,
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} [{}]", self.message, self.code())
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Lets code that reports errors as strings use `?` on parse results.
+impl From<ParseError> for String {
+    fn from(error: ParseError) -> Self {
+        error.to_string()
+    }
+}
+
+// ============================================================================
+// Error Codes
+// ============================================================================
+
+/// The registry of error codes.
+///
+/// Every code used by an error type in this workspace is defined here, so
+/// codes cannot collide. The letter gives the stage: `E` for evaluation, `P`
+/// for parsing and `J` for the JIT. A code never changes meaning once
+/// released; retired codes are not reused.
+pub mod codes {
+    /// `EvalError::Custom`
+    pub const CUSTOM: &str = "E000_CUSTOM";
+    pub const UNBOUND_SYMBOL: &str = "E001_UNBOUND_SYMBOL";
+    pub const ARITY: &str = "E010_ARITY";
+    pub const TYPE: &str = "E020_TYPE";
+
+    pub const UNCLOSED_DELIM: &str = "P001_UNCLOSED_DELIM";
+    pub const UNEXPECTED_CLOSE: &str = "P002_UNEXPECTED_CLOSE";
+    pub const UNEXPECTED_EOF: &str = "P003_UNEXPECTED_EOF";
+    pub const INVALID_TOKEN: &str = "P004_INVALID_TOKEN";
+    pub const INVALID_MAP_LITERAL: &str = "P005_INVALID_MAP_LITERAL";
+    pub const INVALID_SET_LITERAL: &str = "P006_INVALID_SET_LITERAL";
+    pub const INVALID_DOTTED_LIST: &str = "P007_INVALID_DOTTED_LIST";
+
+    pub const JIT_UNSUPPORTED: &str = "J001_UNSUPPORTED";
+    pub const JIT_UNSUPPORTED_TYPE: &str = "J002_UNSUPPORTED_TYPE";
+    pub const JIT_SYNTAX: &str = "J003_SYNTAX";
+    pub const JIT_UNBOUND: &str = "J004_UNBOUND";
+    pub const JIT_COMPILATION: &str = "J005_COMPILATION";
+    pub const JIT_EXECUTION: &str = "J006_EXECUTION";
+
+    /// Every registered code
+    pub const ALL: &[&str] = &[
+        CUSTOM,
+        UNBOUND_SYMBOL,
+        ARITY,
+        TYPE,
+        UNCLOSED_DELIM,
+        UNEXPECTED_CLOSE,
+        UNEXPECTED_EOF,
+        INVALID_TOKEN,
+        INVALID_MAP_LITERAL,
+        INVALID_SET_LITERAL,
+        INVALID_DOTTED_LIST,
+        JIT_UNSUPPORTED,
+        JIT_UNSUPPORTED_TYPE,
+        JIT_SYNTAX,
+        JIT_UNBOUND,
+        JIT_COMPILATION,
+        JIT_EXECUTION,
+    ];
+}
@@ -0,0 +1,44 @@
+//! A per-thread freelist of cons cell allocations
+//!
+//! Enabled by the `cons-freelist` feature. When `ConsCell::drop` unlinks the
+//! cells of a list it owned, it hands their emptied `Arc`s here instead of
+//! freeing them, and `cons` takes one back before allocating a new one, so
+//! list-heavy code such as `reverse` and `append` reuses the memory of the
+//! lists it drops. Only cells nothing else refers to are kept, so reuse
+//! cannot be observed; the list is per thread, so it needs no locking.
+
+use std::cell::RefCell;
+use std::sync::Arc;
+
+use crate::language::{ConsCell, Value};
+
+/// Most cells kept per thread; beyond this, dropped cells are freed as usual
+const MAX_FREE_CELLS: usize = 1 << 16;
+
+thread_local! {
+    static FREE_CELLS: RefCell<Vec<Arc<ConsCell>>> = const { RefCell::new(Vec::new()) };
+}
+
+/// A cell with no other references, its car and cdr both nil, if one is free.
+pub(crate) fn take() -> Option<Arc<ConsCell>> {
+    FREE_CELLS
+        .try_with(|cells| cells.borrow_mut().pop())
+        .ok()
+        .flatten()
+}
+
+/// Keep `cell` for reuse, which must have no other references and a nil car
+/// and cdr. Returns it back if the freelist is full or already gone.
+pub(crate) fn recycle(cell: Arc<ConsCell>) -> Option<Arc<ConsCell>> {
+    debug_assert!(Arc::strong_count(&cell) == 1 && Arc::weak_count(&cell) == 0);
+    debug_assert!(matches!((&cell.car, &cell.cdr), (Value::Nil, Value::Nil)));
+    let mut cell = Some(cell);
+    let _ = FREE_CELLS.try_with(|cells| {
+        let mut cells = cells.borrow_mut();
+        if cells.len() < MAX_FREE_CELLS {
+            cells.extend(cell.take());
+        }
+    });
+    // Whatever was not kept is dropped by the caller, outside the borrow
+    cell
+}
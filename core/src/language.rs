@@ -1,11 +1,13 @@
+use std::cell::Cell;
 use std::fmt;
 use std::hash::{Hash, Hasher};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 
 use im::{HashMap as ImHashMap, HashSet as ImHashSet, Vector as ImVector};
 use rustc_hash::{FxHashMap, FxHashSet};
 
 use crate::environment::Environment;
+use crate::error::EvalError;
 use crate::interner::InternedSymbol;
 use crate::numeric::NumericType;
 
@@ -44,8 +46,17 @@ pub enum AtomType {
     Number(NumericType),
     String(StringType),
     Bool(bool),
+    Char(char),
 }
 
+/// Characters with a name in reader syntax, written `#\space` and so on
+pub const CHAR_NAMES: &[(&str, char)] = &[
+    ("space", ' '),
+    ("newline", '\n'),
+    ("tab", '\t'),
+    ("return", '\r'),
+];
+
 // Implement PartialEq manually to handle NumericType comparison
 impl PartialEq for AtomType {
     fn eq(&self, other: &Self) -> bool {
@@ -54,6 +65,7 @@ impl PartialEq for AtomType {
             (AtomType::Number(a), AtomType::Number(b)) => a == b,
             (AtomType::String(a), AtomType::String(b)) => a == b,
             (AtomType::Bool(a), AtomType::Bool(b)) => a == b,
+            (AtomType::Char(a), AtomType::Char(b)) => a == b,
             _ => false,
         }
     }
@@ -69,21 +81,271 @@ impl Hash for AtomType {
             AtomType::Number(n) => n.hash(state),
             AtomType::String(s) => s.hash(state),
             AtomType::Bool(b) => b.hash(state),
+            AtomType::Char(c) => c.hash(state),
         }
     }
 }
 
-#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+/// A pair of values.
+///
+/// Cells are immutable once shared, so following cars and cdrs alone never
+/// leads back to where it started. Values can still form cycles, but only
+/// through something mutable: a ref, as in `(ref-set! r (list r))`, or a
+/// closure's environment. Equality, hashing and `Debug` take refs and
+/// closures by identity, so they never follow such a cycle, and the printer
+/// shows a ref it is already inside as `#ref[…]`.
+#[derive(Clone, Debug)]
 pub struct ConsCell {
     pub car: Value,
     pub cdr: Value,
 }
 
+// Equality and hashing follow the cdr chain in a loop, as printing does, so
+// long lists cannot overflow the stack
+impl PartialEq for ConsCell {
+    fn eq(&self, other: &Self) -> bool {
+        if self.car != other.car {
+            return false;
+        }
+        let (mut a, mut b) = (&self.cdr, &other.cdr);
+        loop {
+            match (a, b) {
+                (Value::Cons(x), Value::Cons(y)) => {
+                    if Arc::ptr_eq(x, y) {
+                        return true;
+                    }
+                    if x.car != y.car {
+                        return false;
+                    }
+                    a = &x.cdr;
+                    b = &y.cdr;
+                }
+                _ => return a == b,
+            }
+        }
+    }
+}
+
+impl Eq for ConsCell {}
+
+impl Hash for ConsCell {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        let mut cell = self;
+        loop {
+            cell.car.hash(state);
+            match &cell.cdr {
+                Value::Cons(next) => {
+                    std::mem::discriminant(&cell.cdr).hash(state);
+                    cell = next;
+                }
+                last => {
+                    last.hash(state);
+                    return;
+                }
+            }
+        }
+    }
+}
+
+impl Drop for ConsCell {
+    fn drop(&mut self) {
+        // Dropping a long or deeply nested list recursively would overflow the
+        // stack, so cells owned only by this one are unlinked iteratively.
+        fn detach(value: &mut Value, pending: &mut Vec<Arc<ConsCell>>) {
+            if matches!(value, Value::Cons(cell) if Arc::strong_count(cell) == 1)
+                && let Value::Cons(cell) = std::mem::replace(value, Value::Nil)
+            {
+                pending.push(cell);
+            }
+        }
+
+        // Unlinks the car and cdr of a cell owned only here and puts the cell
+        // on the freelist, returning it if it could not be kept
+        #[cfg(feature = "cons-freelist")]
+        fn recycle(
+            mut cell: Arc<ConsCell>,
+            pending: &mut Vec<Arc<ConsCell>>,
+        ) -> Option<Arc<ConsCell>> {
+            let Some(inner) = Arc::get_mut(&mut cell) else {
+                return Some(cell);
+            };
+            detach(&mut inner.car, pending);
+            detach(&mut inner.cdr, pending);
+            inner.car = Value::Nil;
+            inner.cdr = Value::Nil;
+            crate::freelist::recycle(cell)
+        }
+
+        let mut pending = Vec::new();
+        detach(&mut self.car, &mut pending);
+        detach(&mut self.cdr, &mut pending);
+        while let Some(cell) = pending.pop() {
+            #[cfg(feature = "cons-freelist")]
+            let Some(cell) = recycle(cell, &mut pending) else {
+                continue;
+            };
+            if let Some(mut cell) = Arc::into_inner(cell) {
+                detach(&mut cell.car, &mut pending);
+                detach(&mut cell.cdr, &mut pending);
+            }
+        }
+    }
+}
+
+/// An `&opt` or `&key` parameter and the expression evaluated when the caller omits it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DefaultedParam {
+    pub name: InternedSymbol,
+    pub default: Value,
+}
+
+/// Parameter list of a lambda or macro.
+///
+/// Written as `(a b &opt (c 1) &rest more &key (d 2) e)`: required
+/// parameters first, then optional positionals, a rest parameter that
+/// collects the remaining arguments as a list, and keyword parameters
+/// passed as `:d 3`. Parameters without a default expression default to nil.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ParamSpec {
+    pub required: Vec<InternedSymbol>,
+    pub optional: Vec<DefaultedParam>,
+    pub rest: Option<InternedSymbol>,
+    pub keys: Vec<DefaultedParam>,
+}
+
+impl ParamSpec {
+    /// Parse a parameter list. `owner` ("lambda", "defmacro") prefixes errors.
+    pub fn parse(params: &Value, owner: &str) -> Result<Self, String> {
+        #[derive(PartialEq, PartialOrd)]
+        enum Section {
+            Required,
+            Optional,
+            Rest,
+            Key,
+        }
+
+        let mut spec = ParamSpec::default();
+        let mut section = Section::Required;
+        let mut current = params.clone();
+        while let Value::Cons(cell) = current {
+            let param = &cell.car;
+            current = cell.cdr.clone();
+
+            if let Value::Atom(AtomType::Symbol(SymbolType::Symbol(sym))) = param
+                && sym.resolve().starts_with('&')
+            {
+                let next = match sym.resolve().as_str() {
+                    "&opt" => Section::Optional,
+                    "&rest" => Section::Rest,
+                    "&key" => Section::Key,
+                    name => return Err(format!("{owner}: unknown parameter marker {name}")),
+                };
+                if next <= section {
+                    return Err(format!("{owner}: misplaced {sym} in parameter list"));
+                }
+                if section == Section::Rest && spec.rest.is_none() {
+                    return Err(format!("{owner}: &rest takes exactly one parameter"));
+                }
+                section = next;
+                continue;
+            }
+
+            match section {
+                Section::Required => spec.required.push(param_symbol(param, owner)?),
+                Section::Optional => spec.optional.push(defaulted_param(param, owner)?),
+                Section::Rest if spec.rest.is_none() => {
+                    spec.rest = Some(param_symbol(param, owner)?)
+                }
+                Section::Rest => return Err(format!("{owner}: &rest takes exactly one parameter")),
+                Section::Key => spec.keys.push(defaulted_param(param, owner)?),
+            }
+        }
+
+        if section == Section::Rest && spec.rest.is_none() {
+            return Err(format!("{owner}: &rest takes exactly one parameter"));
+        }
+        Ok(spec)
+    }
+
+    /// True if the list has only required parameters.
+    pub fn is_fixed(&self) -> bool {
+        self.optional.is_empty() && self.rest.is_none() && self.keys.is_empty()
+    }
+
+    /// Every name the list binds, in order.
+    pub fn names(&self) -> impl Iterator<Item = InternedSymbol> + '_ {
+        let defaulted = |p: &DefaultedParam| p.name;
+        self.required
+            .iter()
+            .copied()
+            .chain(self.optional.iter().map(defaulted))
+            .chain(self.rest)
+            .chain(self.keys.iter().map(defaulted))
+    }
+}
+
+fn param_symbol(param: &Value, owner: &str) -> Result<InternedSymbol, String> {
+    match param {
+        Value::Atom(AtomType::Symbol(SymbolType::Symbol(sym)))
+            if !sym.resolve().starts_with(':') =>
+        {
+            Ok(*sym)
+        }
+        _ => Err(format!("{owner} parameters must be symbols")),
+    }
+}
+
+/// Parse `name` or `(name default-expr)`.
+fn defaulted_param(param: &Value, owner: &str) -> Result<DefaultedParam, String> {
+    let Value::Cons(cell) = param else {
+        return Ok(DefaultedParam {
+            name: param_symbol(param, owner)?,
+            default: Value::Nil,
+        });
+    };
+    let name = param_symbol(&cell.car, owner)?;
+    match &cell.cdr {
+        Value::Cons(rest) if matches!(rest.cdr, Value::Nil) => Ok(DefaultedParam {
+            name,
+            default: rest.car.clone(),
+        }),
+        _ => Err(format!(
+            "{owner}: expected ({name} default) in parameter list"
+        )),
+    }
+}
+
+impl fmt::Display for ParamSpec {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let defaulted = |p: &DefaultedParam| match p.default {
+            Value::Nil => p.name.resolve(),
+            ref d => format!("({} {d})", p.name),
+        };
+
+        let mut parts: Vec<String> = self.required.iter().map(|p| p.resolve()).collect();
+        if !self.optional.is_empty() {
+            parts.push("&opt".to_string());
+            parts.extend(self.optional.iter().map(defaulted));
+        }
+        if let Some(rest) = &self.rest {
+            parts.push("&rest".to_string());
+            parts.push(rest.resolve());
+        }
+        if !self.keys.is_empty() {
+            parts.push("&key".to_string());
+            parts.extend(self.keys.iter().map(defaulted));
+        }
+        write!(f, "({})", parts.join(" "))
+    }
+}
+
 #[derive(Clone)]
 pub struct LambdaCell {
-    pub params: Vec<InternedSymbol>,
+    pub params: ParamSpec,
     pub body: Value,
     pub env: Environment,
+    /// The docstring written before the body, if any
+    pub doc: Option<String>,
 }
 
 // Manual implementations since Environment uses RwLock (doesn't impl Debug/PartialEq)
@@ -92,6 +354,7 @@ impl std::fmt::Debug for LambdaCell {
         f.debug_struct("LambdaCell")
             .field("params", &self.params)
             .field("body", &self.body)
+            .field("doc", &self.doc)
             .field("env", &"<environment>")
             .finish()
     }
@@ -101,15 +364,17 @@ impl PartialEq for LambdaCell {
     fn eq(&self, other: &Self) -> bool {
         // Compare only params and body, not environment
         // (environments with same bindings but different Arc pointers would differ)
-        self.params == other.params && self.body == other.body
+        self.params == other.params && self.body == other.body && self.doc == other.doc
     }
 }
 
 #[derive(Clone)]
 pub struct MacroCell {
-    pub params: Vec<InternedSymbol>,
+    pub params: ParamSpec,
     pub body: Value,
     pub env: Environment,
+    /// The docstring written before the body, if any
+    pub doc: Option<String>,
 }
 
 // Manual implementations since Environment uses RwLock (doesn't impl Debug/PartialEq)
@@ -118,6 +383,7 @@ impl std::fmt::Debug for MacroCell {
         f.debug_struct("MacroCell")
             .field("params", &self.params)
             .field("body", &self.body)
+            .field("doc", &self.doc)
             .field("env", &"<environment>")
             .finish()
     }
@@ -126,7 +392,7 @@ impl std::fmt::Debug for MacroCell {
 impl PartialEq for MacroCell {
     fn eq(&self, other: &Self) -> bool {
         // Compare only params and body, not environment
-        self.params == other.params && self.body == other.body
+        self.params == other.params && self.body == other.body && self.doc == other.doc
     }
 }
 
@@ -266,7 +532,96 @@ impl Hash for PersistentSet {
 }
 
 /// Native function type - Rust functions callable from Lisp
-pub type NativeFn = fn(&[Value], &mut Environment) -> Result<Value, String>;
+pub type NativeFn = fn(&[Value], &mut Environment) -> Result<Value, EvalError>;
+
+/// The closure inside a `NativeClosure`
+pub type NativeClosureFn =
+    dyn Fn(&[Value], &mut Environment) -> Result<Value, EvalError> + Send + Sync;
+
+/// A Rust closure callable from Lisp.
+///
+/// Unlike a `NativeFn`, it can capture state from the embedding
+/// application. Create one with `Environment::register_native`.
+pub struct NativeClosure {
+    /// Name it was registered under, for error messages
+    pub name: String,
+    func: Box<NativeClosureFn>,
+}
+
+impl NativeClosure {
+    pub fn new<F>(name: impl Into<String>, func: F) -> Self
+    where
+        F: Fn(&[Value], &mut Environment) -> Result<Value, EvalError> + Send + Sync + 'static,
+    {
+        NativeClosure {
+            name: name.into(),
+            func: Box::new(func),
+        }
+    }
+
+    /// Call the closure with already-evaluated arguments.
+    pub fn call(&self, args: &[Value], env: &mut Environment) -> Result<Value, EvalError> {
+        (self.func)(args, env)
+    }
+}
+
+impl fmt::Debug for NativeClosure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "NativeClosure({})", self.name)
+    }
+}
+
+/// A mutable place holding one value: what `(ref init)` returns.
+///
+/// Refs are read with `deref` and changed with `ref-set!` and `swap!`;
+/// everything else in the data model is immutable. They compare and hash by
+/// identity, like functions, so two refs holding equal values still differ.
+pub struct RefValue {
+    /// The value, and how many times it has been set
+    value: Mutex<(Value, u64)>,
+}
+
+impl RefValue {
+    pub fn new(value: Value) -> Self {
+        RefValue {
+            value: Mutex::new((value, 0)),
+        }
+    }
+
+    /// The current value.
+    pub fn get(&self) -> Value {
+        self.value.lock().unwrap().0.clone()
+    }
+
+    /// The current value, with a version to pass to `compare_and_set`.
+    pub fn snapshot(&self) -> (Value, u64) {
+        self.value.lock().unwrap().clone()
+    }
+
+    /// Replace the value.
+    pub fn set(&self, value: Value) {
+        let mut current = self.value.lock().unwrap();
+        *current = (value, current.1 + 1);
+    }
+
+    /// Replace the value with `new` unless it has been set since `version`
+    /// was read, returning whether it was replaced.
+    pub fn compare_and_set(&self, version: u64, new: Value) -> bool {
+        let mut current = self.value.lock().unwrap();
+        if current.1 != version {
+            return false;
+        }
+        *current = (new, version + 1);
+        true
+    }
+}
+
+impl fmt::Debug for RefValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // The value may hold the ref itself
+        write!(f, "RefValue({:p})", self)
+    }
+}
 
 #[derive(Clone, Debug)]
 pub enum Value {
@@ -290,6 +645,20 @@ pub enum Value {
     /// Reduced wrapper - signals early termination in fold/reduce
     Reduced(Box<Value>),
     NativeFn(NativeFn),
+    NativeClosure(Arc<NativeClosure>),
+    /// Mutable reference cell, created with `ref`
+    Ref(Arc<RefValue>),
+}
+
+impl Value {
+    /// True for every value except nil and false.
+    ///
+    /// This is the single definition of truthiness shared by the interpreter,
+    /// the standard library and the JIT: 0, the empty string and empty
+    /// collections are all truthy.
+    pub fn is_truthy(&self) -> bool {
+        !matches!(self, Value::Nil | Value::Atom(AtomType::Bool(false)))
+    }
 }
 
 // Manual PartialEq implementation because function pointers need special handling
@@ -297,10 +666,12 @@ impl PartialEq for Value {
     fn eq(&self, other: &Self) -> bool {
         match (self, other) {
             (Value::Atom(a), Value::Atom(b)) => a == b,
-            (Value::Cons(a), Value::Cons(b)) => a == b,
+            (Value::Cons(a), Value::Cons(b)) => Arc::ptr_eq(a, b) || a == b,
             (Value::Nil, Value::Nil) => true,
-            (Value::Lambda(a), Value::Lambda(b)) => a == b,
-            (Value::Macro(a), Value::Macro(b)) => a == b,
+            // Functions compare by identity: two lambdas with the same
+            // source are still different closures
+            (Value::Lambda(a), Value::Lambda(b)) => Arc::ptr_eq(a, b),
+            (Value::Macro(a), Value::Macro(b)) => Arc::ptr_eq(a, b),
             (Value::Vector(a), Value::Vector(b)) => a == b,
             (Value::Map(a), Value::Map(b)) => a == b,
             (Value::Set(a), Value::Set(b)) => a == b,
@@ -310,8 +681,10 @@ impl PartialEq for Value {
             (Value::Reduced(a), Value::Reduced(b)) => a == b,
             (Value::NativeFn(a), Value::NativeFn(b)) => {
                 // Compare function pointers
-                std::ptr::eq(a as *const _, b as *const _)
+                std::ptr::fn_addr_eq(*a, *b)
             }
+            (Value::NativeClosure(a), Value::NativeClosure(b)) => Arc::ptr_eq(a, b),
+            (Value::Ref(a), Value::Ref(b)) => Arc::ptr_eq(a, b),
             _ => false,
         }
     }
@@ -326,16 +699,9 @@ impl Hash for Value {
             Value::Atom(a) => a.hash(state),
             Value::Cons(cell) => cell.hash(state),
             Value::Nil => {}
-            Value::Lambda(lc) => {
-                // Hash params and body (consistent with PartialEq)
-                lc.params.hash(state);
-                lc.body.hash(state);
-            }
-            Value::Macro(mc) => {
-                // Hash params and body (consistent with PartialEq)
-                mc.params.hash(state);
-                mc.body.hash(state);
-            }
+            // Hash by identity (consistent with PartialEq)
+            Value::Lambda(lc) => Arc::as_ptr(lc).hash(state),
+            Value::Macro(mc) => Arc::as_ptr(mc).hash(state),
             Value::Vector(v) => v.hash(state),
             Value::Map(m) => m.hash(state),
             Value::Set(s) => s.hash(state),
@@ -347,6 +713,8 @@ impl Hash for Value {
                 // Hash function pointer address
                 (*f as usize).hash(state);
             }
+            Value::NativeClosure(f) => Arc::as_ptr(f).hash(state),
+            Value::Ref(r) => Arc::as_ptr(r).hash(state),
         }
     }
 }
@@ -396,103 +764,267 @@ fn escape_string(s: &str) -> String {
 
 impl fmt::Display for Value {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match self {
-            Value::Atom(AtomType::Symbol(s)) => write!(f, "{s}"),
-            Value::Atom(AtomType::Number(n)) => write!(f, "{n}"),
-            Value::Atom(AtomType::String(s)) => write!(f, "{s}"),
-            Value::Atom(AtomType::Bool(b)) => write!(f, "{}", if *b { "t" } else { "nil" }),
-            Value::Nil => write!(f, "nil"),
-            Value::Cons(_) => {
-                write!(f, "(")?;
-                let mut current = self.clone();
-                while let Value::Cons(ref cell) = current {
-                    write!(f, "{}", cell.car)?;
-                    match cell.cdr {
-                        Value::Nil => break,
-                        Value::Cons(_) => {
-                            write!(f, " ")?;
-                            current = cell.cdr.clone();
-                        }
-                        ref other => {
-                            write!(f, " . {other}")?;
-                            break;
-                        }
-                    }
+        let limits = print_limits();
+        let Some(total) = limits.total else {
+            return Printer::new(f, limits).value(self);
+        };
+        let mut capped = Capped {
+            out: &mut *f,
+            remaining: total,
+            truncated: false,
+        };
+        match Printer::new(&mut capped, limits).value(self) {
+            Err(_) if capped.truncated => write!(f, "… (truncated at {total} characters)"),
+            result => result,
+        }
+    }
+}
+
+// ============================================================================
+// Print Limits
+// ============================================================================
+
+/// How much of a value `Display` prints. `None` means no limit.
+///
+/// These back the `*print-length*`, `*print-depth*` and `*print-total*`
+/// dynamic variables. The library default is unlimited; the REPL installs
+/// its own defaults so large results stay readable.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PrintLimits {
+    /// Elements printed per collection before the rest are elided
+    pub length: Option<usize>,
+    /// Collections nested deeper than this print as `(…)`
+    pub depth: Option<usize>,
+    /// Characters printed in total before the output is cut off
+    pub total: Option<usize>,
+}
+
+impl PrintLimits {
+    /// Print everything.
+    pub const UNLIMITED: PrintLimits = PrintLimits {
+        length: None,
+        depth: None,
+        total: None,
+    };
+}
+
+thread_local! {
+    static PRINT_LIMITS: Cell<PrintLimits> = const { Cell::new(PrintLimits::UNLIMITED) };
+}
+
+/// Limits used when displaying values on this thread.
+pub fn print_limits() -> PrintLimits {
+    PRINT_LIMITS.with(Cell::get)
+}
+
+/// Set the print limits for this thread, returning the previous ones.
+pub fn set_print_limits(limits: PrintLimits) -> PrintLimits {
+    PRINT_LIMITS.with(|cell| cell.replace(limits))
+}
+
+/// Writes values, eliding whatever falls outside the print limits.
+struct Printer<'a, W: fmt::Write> {
+    out: &'a mut W,
+    limits: PrintLimits,
+    depth: usize,
+    /// The refs whose values are being printed, to stop at a cycle
+    open_refs: Vec<*const RefValue>,
+}
+
+impl<'a, W: fmt::Write> Printer<'a, W> {
+    fn new(out: &'a mut W, limits: PrintLimits) -> Self {
+        Printer {
+            out,
+            limits,
+            depth: 0,
+            open_refs: Vec::new(),
+        }
+    }
+
+    fn value(&mut self, value: &Value) -> fmt::Result {
+        match value {
+            Value::Atom(AtomType::Symbol(s)) => write!(self.out, "{s}"),
+            Value::Atom(AtomType::Number(n)) => write!(self.out, "{n}"),
+            Value::Atom(AtomType::String(s)) => write!(self.out, "{s}"),
+            Value::Atom(AtomType::Bool(b)) => self.out.write_str(if *b { "t" } else { "nil" }),
+            Value::Atom(AtomType::Char(c)) => match CHAR_NAMES.iter().find(|(_, n)| n == c) {
+                Some((name, _)) => write!(self.out, "#\\{name}"),
+                None if c.is_control() || c.is_whitespace() => {
+                    write!(self.out, "#\\u{:04X}", *c as u32)
                 }
-                write!(f, ")")
+                None => write!(self.out, "#\\{c}"),
+            },
+            Value::Nil => self.out.write_str("nil"),
+            Value::Cons(_) => self.list(value),
+            Value::Lambda(lc) => write!(self.out, "<lambda {}>", lc.params),
+            Value::Macro(mc) => write!(self.out, "<macro {}>", mc.params),
+            Value::Vector(vec) => self.collection(
+                "<<",
+                ">>",
+                " ",
+                vec.elements.len(),
+                &vec.elements,
+                Self::value,
+            ),
+            Value::Map(map) => {
+                self.collection("{", "}", " ", map.entries.len(), &map.entries, Self::entry)
             }
-            Value::Lambda(_) => write!(f, "<lambda>"),
-            Value::Macro(_) => write!(f, "<macro>"),
-            Value::Vector(vec) => {
-                write!(f, "<<")?;
-                for (i, elem) in vec.elements.iter().enumerate() {
-                    if i > 0 {
-                        write!(f, " ")?;
-                    }
-                    write!(f, "{elem}")?;
-                }
-                write!(f, ">>")
+            Value::Set(set) => self.collection(
+                "#{",
+                "}",
+                " ",
+                set.elements.len(),
+                &set.elements,
+                Self::value,
+            ),
+            Value::PersistentVector(vec) => self.collection(
+                "#pvec[",
+                "]",
+                " ",
+                vec.elements.len(),
+                &vec.elements,
+                Self::value,
+            ),
+            Value::PersistentMap(map) => self.collection(
+                "#pmap{",
+                "}",
+                ", ",
+                map.entries.len(),
+                &map.entries,
+                Self::entry,
+            ),
+            Value::PersistentSet(set) => self.collection(
+                "#pset{",
+                "}",
+                " ",
+                set.elements.len(),
+                &set.elements,
+                Self::value,
+            ),
+            Value::Reduced(v) => {
+                self.out.write_str("#reduced(")?;
+                self.value(v)?;
+                self.out.write_str(")")
             }
-            Value::Map(map) => {
-                write!(f, "{{")?;
-                let mut first = true;
-                for (k, v) in &map.entries {
-                    if !first {
-                        write!(f, ", ")?;
-                    }
-                    first = false;
-                    write!(f, "{k} {v}")?;
-                }
-                write!(f, "}}")
+            Value::NativeFn(_) | Value::NativeClosure(_) => self.out.write_str("<native-fn>"),
+            Value::Ref(r) => self.reference(r),
+        }
+    }
+
+    fn entry(&mut self, (key, value): (&Value, &Value)) -> fmt::Result {
+        self.value(key)?;
+        self.out.write_str(" ")?;
+        self.value(value)
+    }
+
+    /// Print a ref as `#ref[value]`, or `#ref[…]` inside its own value.
+    fn reference(&mut self, r: &Arc<RefValue>) -> fmt::Result {
+        let id = Arc::as_ptr(r);
+        if self.open_refs.contains(&id) {
+            return self.out.write_str("#ref[…]");
+        }
+        self.open_refs.push(id);
+        let result = self.collection("#ref[", "]", " ", 1, [r.get()], |p, v| p.value(&v));
+        self.open_refs.pop();
+        result
+    }
+
+    fn too_deep(&self) -> bool {
+        self.limits.depth.is_some_and(|max| self.depth >= max)
+    }
+
+    fn at_length_limit(&self, printed: usize) -> bool {
+        self.limits.length.is_some_and(|max| printed >= max)
+    }
+
+    /// Print a list, following the cdr chain without recursing on it.
+    fn list(&mut self, list: &Value) -> fmt::Result {
+        if self.too_deep() {
+            return self.out.write_str("(…)");
+        }
+        self.out.write_str("(")?;
+        self.depth += 1;
+        let mut current = list;
+        let mut printed = 0;
+        while let Value::Cons(cell) = current {
+            if printed > 0 {
+                self.out.write_str(" ")?;
             }
-            Value::Set(set) => {
-                write!(f, "#{{")?;
-                let mut first = true;
-                for elem in &set.elements {
-                    if !first {
-                        write!(f, " ")?;
-                    }
-                    first = false;
-                    write!(f, "{elem}")?;
+            if self.at_length_limit(printed) {
+                let mut rest = 0;
+                while let Value::Cons(cell) = current {
+                    rest += 1;
+                    current = &cell.cdr;
                 }
-                write!(f, "}}")
+                write!(self.out, "… {rest} more")?;
+                break;
             }
-            Value::PersistentVector(vec) => {
-                write!(f, "#pvec[")?;
-                for (i, elem) in vec.elements.iter().enumerate() {
-                    if i > 0 {
-                        write!(f, " ")?;
-                    }
-                    write!(f, "{elem}")?;
+            self.value(&cell.car)?;
+            printed += 1;
+            match &cell.cdr {
+                Value::Cons(_) => current = &cell.cdr,
+                Value::Nil => break,
+                other => {
+                    self.out.write_str(" . ")?;
+                    self.value(other)?;
+                    break;
                 }
-                write!(f, "]")
             }
-            Value::PersistentMap(map) => {
-                write!(f, "#pmap{{")?;
-                let mut first = true;
-                for (k, v) in &map.entries {
-                    if !first {
-                        write!(f, ", ")?;
-                    }
-                    first = false;
-                    write!(f, "{k} {v}")?;
-                }
-                write!(f, "}}")
+        }
+        self.depth -= 1;
+        self.out.write_str(")")
+    }
+
+    fn collection<I: IntoIterator>(
+        &mut self,
+        open: &str,
+        close: &str,
+        separator: &str,
+        len: usize,
+        items: I,
+        mut item: impl FnMut(&mut Self, I::Item) -> fmt::Result,
+    ) -> fmt::Result {
+        if self.too_deep() {
+            return write!(self.out, "{open}…{close}");
+        }
+        self.out.write_str(open)?;
+        self.depth += 1;
+        for (printed, elem) in items.into_iter().enumerate() {
+            if printed > 0 {
+                self.out.write_str(separator)?;
             }
-            Value::PersistentSet(set) => {
-                write!(f, "#pset{{")?;
-                let mut first = true;
-                for elem in &set.elements {
-                    if !first {
-                        write!(f, " ")?;
-                    }
-                    first = false;
-                    write!(f, "{elem}")?;
-                }
-                write!(f, "}}")
+            if self.at_length_limit(printed) {
+                write!(self.out, "… {} more", len - printed)?;
+                break;
+            }
+            item(self, elem)?;
+        }
+        self.depth -= 1;
+        self.out.write_str(close)
+    }
+}
+
+/// Passes writes through until `remaining` characters have been written.
+struct Capped<'a, W: fmt::Write> {
+    out: &'a mut W,
+    remaining: usize,
+    truncated: bool,
+}
+
+impl<W: fmt::Write> fmt::Write for Capped<'_, W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let cut = s.char_indices().nth(self.remaining).map(|(i, _)| i);
+        match cut {
+            None => {
+                self.remaining -= s.chars().count();
+                self.out.write_str(s)
+            }
+            Some(i) => {
+                self.out.write_str(&s[..i])?;
+                self.remaining = 0;
+                self.truncated = true;
+                Err(fmt::Error)
             }
-            Value::Reduced(v) => write!(f, "#reduced({v})"),
-            Value::NativeFn(_) => write!(f, "<native-fn>"),
         }
     }
 }
@@ -502,20 +1034,35 @@ impl fmt::Display for Value {
 // ============================================================================
 
 pub fn cons(car: Value, cdr: Value) -> Value {
+    #[cfg(feature = "cons-freelist")]
+    if let Some(mut cell) = crate::freelist::take() {
+        let free = Arc::get_mut(&mut cell).expect("free cells have no other references");
+        free.car = car;
+        free.cdr = cdr;
+        return Value::Cons(cell);
+    }
     Value::Cons(Arc::new(ConsCell { car, cdr }))
 }
 
-pub fn car(value: &Value) -> Result<Value, String> {
+pub fn car(value: &Value) -> Result<Value, EvalError> {
     match value {
         Value::Cons(cell) => Ok(cell.car.clone()),
-        _ => Err(format!("car: expected cons cell, got {value}")),
+        _ => Err(cons_expected("car", value)),
     }
 }
 
-pub fn cdr(value: &Value) -> Result<Value, String> {
+pub fn cdr(value: &Value) -> Result<Value, EvalError> {
     match value {
         Value::Cons(cell) => Ok(cell.cdr.clone()),
-        _ => Err(format!("cdr: expected cons cell, got {value}")),
+        _ => Err(cons_expected("cdr", value)),
+    }
+}
+
+fn cons_expected(callee: &str, found: &Value) -> EvalError {
+    EvalError::TypeError {
+        callee: Some(callee.to_string()),
+        expected: "cons cell".to_string(),
+        found: found.clone(),
     }
 }
 
@@ -527,6 +1074,11 @@ pub fn eq(a: &Value, b: &Value) -> bool {
     match (a, b) {
         (Value::Atom(a1), Value::Atom(a2)) => a1 == a2,
         (Value::Nil, Value::Nil) => true,
+        (Value::Lambda(_), Value::Lambda(_))
+        | (Value::Macro(_), Value::Macro(_))
+        | (Value::NativeFn(_), Value::NativeFn(_))
+        | (Value::NativeClosure(_), Value::NativeClosure(_))
+        | (Value::Ref(_), Value::Ref(_)) => a == b,
         _ => false,
     }
 }
@@ -1,6 +1,13 @@
-use crate::language::StringType;
+use std::fmt;
+
+use crate::error::{ParseError, ParseErrorKind};
+use crate::language::{CHAR_NAMES, StringType};
 use crate::numeric::NumericType;
 
+/// Reported for a string with no closing quote, which `next_token` classifies
+/// as an unclosed delimiter rather than an invalid token
+const UNTERMINATED_STRING: &str = "Unterminated string";
+
 // ============================================================================
 // Lexer
 // ============================================================================
@@ -8,6 +15,8 @@ use crate::numeric::NumericType;
 pub struct Lexer {
     input: Vec<char>,
     position: usize,
+    /// Location of `input[position]`
+    span: Span,
 }
 
 impl Lexer {
@@ -15,6 +24,7 @@ impl Lexer {
         Lexer {
             input: input.chars().collect(),
             position: 0,
+            span: Span::START,
         }
     }
 
@@ -36,7 +46,16 @@ impl Lexer {
 
     fn advance(&mut self) {
         if self.position < self.input.len() {
+            let ch = self.input[self.position];
             self.position += 1;
+            self.span.offset += ch.len_utf8();
+            // \r\n, lone \r and \n each end one line
+            if ch == '\n' || (ch == '\r' && self.current_char() != '\n') {
+                self.span.line += 1;
+                self.span.col = 1;
+            } else {
+                self.span.col += 1;
+            }
         }
     }
 
@@ -44,31 +63,61 @@ impl Lexer {
         self.position >= self.input.len()
     }
 
-    fn skip_whitespace(&mut self) {
+    fn skip_whitespace(&mut self) -> Result<(), ParseError> {
         loop {
             // Skip whitespace
             while !self.is_eof() && self.current_char().is_whitespace() {
                 self.advance();
             }
 
-            // Skip comments (semicolon to end of line)
-            if self.current_char() == ';' {
+            // Skip comments (semicolon to end of line, or #| ... |#). A `#!`
+            // line at the very start is a comment too, so scripts can have a
+            // shebang line.
+            if self.current_char() == ';'
+                || (self.position == 0 && self.current_char() == '#' && self.peek_ahead(1) == '!')
+            {
                 self.skip_comment();
+            } else if self.current_char() == '#' && self.peek_ahead(1) == '|' {
+                self.skip_block_comment()?;
             } else {
-                break;
+                return Ok(());
             }
         }
     }
 
-    fn skip_comment(&mut self) {
-        // Skip from semicolon to end of line (or EOF)
-        while !self.is_eof() && self.current_char() != '\n' {
+    /// Skip a `#| ... |#` comment, which may contain nested block comments.
+    fn skip_block_comment(&mut self) -> Result<(), ParseError> {
+        let start = self.span;
+        let mut depth = 0;
+        loop {
+            if self.is_eof() {
+                return Err(ParseError::new(
+                    ParseErrorKind::UnclosedDelimiter,
+                    format!("Unterminated block comment starting at {start}"),
+                ));
+            }
+            match (self.current_char(), self.peek_ahead(1)) {
+                ('#', '|') => depth += 1,
+                ('|', '#') => depth -= 1,
+                _ => {
+                    self.advance();
+                    continue;
+                }
+            }
+            self.advance();
             self.advance();
+            if depth == 0 {
+                return Ok(());
+            }
         }
-        // Advance past the newline if present
-        if self.current_char() == '\n' {
+    }
+
+    fn skip_comment(&mut self) {
+        // Skip from semicolon to end of line (or EOF); \r\n and lone \r also end a line
+        while !self.is_eof() && !matches!(self.current_char(), '\n' | '\r') {
             self.advance();
         }
+        // The terminator itself is whitespace, skipped by the caller
     }
 
     fn expect_char(&mut self, expected: char) -> Result<(), String> {
@@ -92,10 +141,69 @@ impl Lexer {
     fn read_string_or_sigil(&mut self) -> Result<Token, String> {
         match self.current_char() {
             '"' => self.read_basic_string(),
+            '#' if self.peek_ahead(1) == '#' => self.read_symbolic_value(),
+            '#' if self.peek_ahead(1) == '\\' => self.read_char(),
             _ => Err("Not a string".to_string()),
         }
     }
 
+    /// Read a character literal: `#\a`, a name such as `#\space`, or a
+    /// unicode escape such as `#\u03BB`
+    fn read_char(&mut self) -> Result<Token, String> {
+        self.advance(); // skip '#'
+        self.advance(); // skip '\'
+        if self.is_eof() {
+            return Err("Expected a character after #\\".to_string());
+        }
+        // The first character is taken as is, so #\( and #\  are characters;
+        // a run of letters and digits after it is a name or unicode escape
+        let mut text = String::from(self.current_char());
+        self.advance();
+        if text.chars().all(char::is_alphanumeric) {
+            while !self.is_eof() && self.current_char().is_alphanumeric() {
+                text.push(self.current_char());
+                self.advance();
+            }
+        }
+
+        let mut chars = text.chars();
+        if let (Some(c), None) = (chars.next(), chars.next()) {
+            return Ok(Token::Char(c));
+        }
+        if let Some((_, c)) = CHAR_NAMES.iter().find(|(name, _)| *name == text) {
+            return Ok(Token::Char(*c));
+        }
+        if let Some(hex) = text.strip_prefix('u')
+            && hex.len() <= 6
+            && let Ok(code_point) = u32::from_str_radix(hex, 16)
+        {
+            return char::from_u32(code_point)
+                .map(Token::Char)
+                .ok_or_else(|| format!("Invalid unicode code point in #\\{text}"));
+        }
+        Err(format!("Unknown character name #\\{text}"))
+    }
+
+    /// Read a symbolic float value: `##Inf`, `##-Inf` or `##NaN`
+    fn read_symbolic_value(&mut self) -> Result<Token, String> {
+        self.advance(); // skip '#'
+        self.advance(); // skip '#'
+        let mut name = String::new();
+        while !self.is_eof()
+            && (self.current_char().is_alphanumeric() || self.current_char() == '-')
+        {
+            name.push(self.current_char());
+            self.advance();
+        }
+        let value = match name.as_str() {
+            "Inf" => f64::INFINITY,
+            "-Inf" => f64::NEG_INFINITY,
+            "NaN" => f64::NAN,
+            _ => return Err(format!("Unknown symbolic value ##{name}")),
+        };
+        Ok(Token::Number(NumericType::Float(value)))
+    }
+
     /// Read basic string with escape sequences
     fn read_basic_string(&mut self) -> Result<Token, String> {
         self.expect_char('"')?;
@@ -112,7 +220,7 @@ impl Lexer {
         }
 
         if self.is_eof() {
-            return Err("Unterminated string".to_string());
+            return Err(UNTERMINATED_STRING.to_string());
         }
 
         self.expect_char('"')?;
@@ -180,7 +288,10 @@ impl Lexer {
     /// Check if character is valid in symbol (excluding '/' for namespace separator)
     fn is_symbol_char(&self, c: char) -> bool {
         c.is_alphanumeric()
-            || matches!(c, '-' | '_' | '+' | '*' | '!' | '?' | '<' | '>' | '=' | '%')
+            || matches!(
+                c,
+                '-' | '_' | '+' | '*' | '!' | '?' | '<' | '>' | '=' | '%' | '&'
+            )
     }
 
     /// Check if character is valid in symbol (including '/')
@@ -192,68 +303,17 @@ impl Lexer {
     // Number and Symbol Parsing
     // ========================================================================
 
-    fn read_number_or_symbol(&mut self) -> Token {
+    /// Read a number: a token that starts with a digit, or with `-` and then
+    /// a digit or `.`. The token runs to the next delimiter, and all of it
+    /// must be one number; `1.2.3` and `2vec` are errors rather than
+    /// symbols.
+    fn read_number(&mut self) -> Result<Token, String> {
         let mut text = String::new();
-
-        // Collect the text
-        if self.current_char() == '-' {
+        while !self.is_eof() && !is_delimiter(self.current_char()) {
             text.push(self.current_char());
             self.advance();
         }
-
-        let mut has_dot = false;
-        let mut has_slash = false;
-
-        while !self.is_eof() {
-            let ch = self.current_char();
-            if ch.is_numeric() {
-                text.push(ch);
-                self.advance();
-            } else if ch == '.' && !has_dot && !has_slash {
-                has_dot = true;
-                text.push(ch);
-                self.advance();
-            } else if ch == '/' && !has_dot && !has_slash {
-                has_slash = true;
-                text.push(ch);
-                self.advance();
-            } else if (ch == 'e' || ch == 'E') && !has_slash {
-                text.push(ch);
-                self.advance();
-                if let Some(&sign) = self.input.get(self.position)
-                    && (sign == '+' || sign == '-')
-                {
-                    text.push(sign);
-                    self.advance();
-                }
-                has_dot = true; // Mark as float
-            } else {
-                break;
-            }
-        }
-
-        // Parse the number
-        if has_slash {
-            let parts: Vec<&str> = text.split('/').collect();
-            if parts.len() == 2
-                && let (Ok(numerator), Ok(denominator)) =
-                    (parts[0].parse::<i64>(), parts[1].parse::<i64>())
-                && let Ok(ratio) = NumericType::make_ratio(numerator, denominator)
-            {
-                return Token::Number(ratio);
-            }
-            Token::Symbol(text)
-        } else if has_dot || text.contains('e') || text.contains('E') {
-            if let Ok(f) = text.parse::<f64>() {
-                Token::Number(NumericType::Float(f))
-            } else {
-                Token::Symbol(text)
-            }
-        } else if let Ok(n) = text.parse::<i64>() {
-            Token::Number(NumericType::Int(n))
-        } else {
-            Token::Symbol(text)
-        }
+        parse_number(&text).map(Token::Number)
     }
 
     fn read_symbol(&mut self) -> Token {
@@ -261,11 +321,22 @@ impl Lexer {
 
         while !self.is_eof() {
             let ch = self.current_char();
-            if ch.is_whitespace() || matches!(ch, '(' | ')' | '\'' | '<' | '>' | '[' | ']' | ':') {
+            // `->` inside a name (`char->int`) is part of it; any other `>`
+            // may be closing a vector.
+            let arrow = ch == '>'
+                && symbol.ends_with('-')
+                && self.is_symbol_char(self.peek_ahead(1))
+                && self.peek_ahead(1) != '>';
+            if !arrow
+                && (ch.is_whitespace()
+                    || matches!(ch, '(' | ')' | '\'' | '<' | '>' | '[' | ']' | ':'))
+            {
                 break;
             }
-            // Allow '/' in symbols (for things like function names)
-            if self.is_symbol_char_with_slash(ch) {
+            // Allow '/' in symbols (for things like function names), and
+            // '#' after the first character (for auto-gensym names like
+            // `result#`)
+            if self.is_symbol_char_with_slash(ch) || (ch == '#' && !symbol.is_empty()) {
                 symbol.push(ch);
                 self.advance();
             } else {
@@ -276,13 +347,45 @@ impl Lexer {
         Token::Symbol(symbol)
     }
 
+    /// Read a keyword such as `:append`. Keywords are symbols whose name
+    /// keeps the leading colon; they evaluate to themselves.
+    fn read_keyword(&mut self) -> Result<Token, String> {
+        self.advance(); // skip ':'
+        match self.read_symbol() {
+            Token::Symbol(name) if !name.is_empty() => Ok(Token::Symbol(format!(":{name}"))),
+            _ => Err("Expected keyword name after ':'".to_string()),
+        }
+    }
+
+    /// The location just past the last character read.
+    pub fn location(&self) -> Span {
+        self.span
+    }
+
     // ========================================================================
     // Main Tokenization
     // ========================================================================
 
-    pub fn next_token(&mut self) -> Result<Token, String> {
-        self.skip_whitespace();
+    /// Read the next token and where it starts.
+    ///
+    /// Errors include the location of the offending token.
+    pub fn next_token(&mut self) -> Result<(Token, Span), ParseError> {
+        self.skip_whitespace()?;
+        let start = self.span;
+        match self.read_token() {
+            Ok(token) => Ok((token, start)),
+            Err(e) => {
+                let kind = if e == UNTERMINATED_STRING {
+                    ParseErrorKind::UnclosedDelimiter
+                } else {
+                    ParseErrorKind::InvalidToken
+                };
+                Err(ParseError::new(kind, format!("{e} at {start}")))
+            }
+        }
+    }
 
+    fn read_token(&mut self) -> Result<Token, String> {
         if self.is_eof() {
             return Ok(Token::Eof);
         }
@@ -298,6 +401,14 @@ impl Lexer {
                 self.advance();
                 Ok(Token::RParen)
             }
+            '{' => {
+                self.advance();
+                Ok(Token::MapOpen)
+            }
+            '}' => {
+                self.advance();
+                Ok(Token::MapClose)
+            }
             '\'' => {
                 self.advance();
                 Ok(Token::Quote)
@@ -316,6 +427,10 @@ impl Lexer {
                     Ok(Token::Unquote)
                 }
             }
+            '@' => {
+                self.advance();
+                Ok(Token::Deref)
+            }
             '<' => {
                 if self.peek_ahead(1) == '<' {
                     self.advance();
@@ -344,36 +459,143 @@ impl Lexer {
                     Ok(Token::Symbol(">".to_string()))
                 }
             }
+            '#' if self.peek_ahead(1) == '{' => {
+                self.advance();
+                self.advance();
+                Ok(Token::SetOpen)
+            }
+            '#' if self.peek_ahead(1) == '_' => {
+                self.advance();
+                self.advance();
+                Ok(Token::Discard)
+            }
+            // A `.` on its own separates the tail of a dotted list
+            '.' if self.peek_ahead(1) == '\0' || is_delimiter(self.peek_ahead(1)) => {
+                self.advance();
+                Ok(Token::Dot)
+            }
             '"' | '$' | '#' | '~' => self.read_string_or_sigil(),
-            ch if ch.is_numeric() => Ok(self.read_number_or_symbol()),
-            '-' => {
-                if self.peek_ahead(1).is_numeric() {
-                    Ok(self.read_number_or_symbol())
-                } else {
-                    Ok(self.read_symbol())
-                }
+            ':' => self.read_keyword(),
+            ch if ch.is_ascii_digit() => self.read_number(),
+            // `-` alone, or followed by anything else, is a symbol
+            '-' if self.peek_ahead(1).is_ascii_digit()
+                || (self.peek_ahead(1) == '.' && self.peek_ahead(2).is_ascii_digit()) =>
+            {
+                self.read_number()
             }
-            _ => Ok(self.read_symbol()),
+            ch => match self.read_symbol() {
+                Token::Symbol(name) if name.is_empty() => {
+                    Err(format!("Unexpected character '{ch}'"))
+                }
+                token => Ok(token),
+            },
         }
     }
 }
 
+/// True for the characters that end a number
+fn is_delimiter(c: char) -> bool {
+    c.is_whitespace()
+        || matches!(
+            c,
+            '(' | ')' | '[' | ']' | '{' | '}' | '<' | '>' | '\'' | '`' | ',' | '"' | ';' | ':'
+        )
+}
+
+/// The number `text` is: a decimal integer, ratio or float, or an integer
+/// in hexadecimal (`0x1F`), octal (`0o17`) or binary (`0b101`). Any of
+/// them may have a leading `-`.
+fn parse_number(text: &str) -> Result<NumericType, String> {
+    let (sign, unsigned) = match text.strip_prefix('-') {
+        Some(unsigned) => ("-", unsigned),
+        None => ("", text),
+    };
+    for (prefix, radix, name) in [
+        ("0x", 16, "hexadecimal"),
+        ("0o", 8, "octal"),
+        ("0b", 2, "binary"),
+    ] {
+        if let Some(digits) = unsigned.strip_prefix(prefix) {
+            return NumericType::from_str_radix(&format!("{sign}{digits}"), radix)
+                .ok_or_else(|| format!("Malformed {name} number {text}"));
+        }
+    }
+
+    // `-.5` is `-0.5`
+    let decimal = match unsigned.strip_prefix('.') {
+        Some(fraction) => format!("{sign}0.{fraction}"),
+        None => text.to_string(),
+    };
+    if let Some(number) = NumericType::parse(&decimal) {
+        return Ok(number);
+    }
+    if let Some((_, denom)) = text.split_once('/')
+        && !denom.is_empty()
+        && denom.bytes().all(|b| b == b'0')
+    {
+        return Err(format!("Ratio {text} has a zero denominator"));
+    }
+    Err(format!("Malformed number {text}"))
+}
+
 // ============================================================================
 // Token Types
 // ============================================================================
 
+/// A location in source text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Span {
+    /// 1-based line number
+    pub line: usize,
+    /// 1-based column, in characters
+    pub col: usize,
+    /// Byte offset from the start of the input
+    pub offset: usize,
+}
+
+impl Span {
+    /// The start of the input.
+    pub const START: Span = Span {
+        line: 1,
+        col: 1,
+        offset: 0,
+    };
+}
+
+impl Default for Span {
+    fn default() -> Self {
+        Span::START
+    }
+}
+
+impl fmt::Display for Span {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}, column {}", self.line, self.col)
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum Token {
     LParen,
     RParen,
     VectorOpen,  // <<
     VectorClose, // >>
+    MapOpen,     // {
+    MapClose,    // }
+    SetOpen,     // #{
     Quote,
     Quasiquote,
     Unquote,
     UnquoteSplicing,
+    /// `@`, which reads `@r` as `(deref r)`
+    Deref,
+    /// `#_`, which discards the form after it
+    Discard,
+    /// A `.` on its own, before the last form of a dotted list
+    Dot,
     Symbol(String),
     Number(NumericType),
     String(StringType),
+    Char(char),
     Eof,
 }
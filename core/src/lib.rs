@@ -6,22 +6,51 @@
 
 pub mod abstractions;
 pub mod environment;
+pub mod error;
+#[cfg(feature = "cons-freelist")]
+mod freelist;
 pub mod interner;
 pub mod language;
 pub mod lexer;
 pub mod numeric;
 pub mod parser;
+pub mod pretty;
+pub mod source;
 
 // Re-export commonly used items for convenience
 pub use abstractions::{
     Seq, assoc, conj, count, first, get, hash_map, hash_set, is_callable, is_reduced, next, nth,
     reduced, rest, seq, unreduced,
 };
-pub use environment::Environment;
+pub use environment::{DEFAULT_RECURSION_LIMIT, EnvFreeze, EnvSnapshot, Environment};
+pub use error::{Arity, EvalError, ParseError, ParseErrorKind};
 pub use interner::InternedSymbol;
 pub use language::{
-    AtomType, ConsCell, LambdaCell, MacroCell, MapValue, NativeFn, PersistentMap, PersistentSet,
-    PersistentVector, SetValue, StringType, SymbolType, Value, VectorValue, cons,
+    AtomType, ConsCell, DefaultedParam, LambdaCell, MacroCell, MapValue, NativeClosure,
+    NativeClosureFn, NativeFn, ParamSpec, PersistentMap, PersistentSet, PersistentVector, RefValue,
+    SetValue, StringType, SymbolType, Value, VectorValue, cons,
 };
 pub use numeric::NumericType;
-pub use parser::parse;
+pub use parser::{parse, parse_all, parse_all_with_spans, parse_iter, parse_with_spans};
+pub use pretty::pretty_print;
+pub use source::{binding_source, value_source};
+
+// Values and environments are shared between threads, e.g. by `par-map`, so
+// they and everything they are built from must stay `Send + Sync`
+const _: () = {
+    const fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<Value>();
+    assert_send_sync::<Environment>();
+    assert_send_sync::<EvalError>();
+    assert_send_sync::<InternedSymbol>();
+    assert_send_sync::<StringType>();
+    assert_send_sync::<VectorValue>();
+    assert_send_sync::<MapValue>();
+    assert_send_sync::<SetValue>();
+    assert_send_sync::<PersistentVector>();
+    assert_send_sync::<PersistentMap>();
+    assert_send_sync::<PersistentSet>();
+    assert_send_sync::<LambdaCell>();
+    assert_send_sync::<NativeClosure>();
+    assert_send_sync::<RefValue>();
+};
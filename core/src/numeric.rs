@@ -1,3 +1,4 @@
+use std::cell::Cell;
 use std::cmp::Ordering;
 use std::fmt;
 use std::hash::{Hash, Hasher};
@@ -5,7 +6,7 @@ use std::sync::Arc;
 
 use num_bigint::BigInt as BigInteger;
 use num_rational::Ratio as NumRatio;
-use num_traits::{One, ToPrimitive, Zero};
+use num_traits::{FromPrimitive, Signed, ToPrimitive, Zero};
 
 // ============================================================================
 // Numeric Type System
@@ -46,10 +47,16 @@ impl fmt::Display for NumericType {
             }
             NumericType::Float(x) => {
                 if x.is_nan() {
-                    write!(f, "NaN")
+                    write!(f, "##NaN")
                 } else if x.is_infinite() {
-                    let sign = if *x > 0.0 { "+Inf" } else { "-Inf" };
+                    let sign = if *x > 0.0 { "##Inf" } else { "##-Inf" };
                     write!(f, "{sign}")
+                } else if let Some(digits) = print_float_digits() {
+                    write!(f, "{x:.digits$}")
+                } else if x.fract() == 0.0 {
+                    // A decimal point keeps a whole float from reading back
+                    // as an integer
+                    write!(f, "{x:.1}")
                 } else {
                     write!(f, "{x}")
                 }
@@ -58,38 +65,30 @@ impl fmt::Display for NumericType {
     }
 }
 
+thread_local! {
+    static PRINT_FLOAT_DIGITS: Cell<Option<usize>> = const { Cell::new(None) };
+}
+
+/// Digits after the decimal point used when displaying floats on this thread.
+///
+/// `None` (the default) prints the shortest form that reads back as the same
+/// value. This backs the `*print-float-digits*` dynamic variable.
+pub fn print_float_digits() -> Option<usize> {
+    PRINT_FLOAT_DIGITS.with(Cell::get)
+}
+
+/// Set the float display precision for this thread, returning the previous setting.
+pub fn set_print_float_digits(digits: Option<usize>) -> Option<usize> {
+    PRINT_FLOAT_DIGITS.with(|cell| cell.replace(digits))
+}
+
 // ============================================================================
 // Equality and Comparison
 // ============================================================================
 
 impl PartialEq for NumericType {
     fn eq(&self, other: &Self) -> bool {
-        use NumericType::*;
-
-        match (self, other) {
-            // Same types
-            (Int(a), Int(b)) => a == b,
-            (BigInt(a), BigInt(b)) => a == b,
-            (Ratio(an, ad), Ratio(bn, bd)) => an == bn && ad == bd,
-            (BigRatio(a), BigRatio(b)) => a == b,
-            (Float(a), Float(b)) => a == b,
-
-            // Cross-type comparisons
-            (Int(a), BigInt(b)) => &BigInteger::from(*a) == b.as_ref(),
-            (BigInt(a), Int(b)) => a.as_ref() == &BigInteger::from(*b),
-
-            (Int(a), Ratio(bn, bd)) => a * bd == *bn,
-            (Ratio(an, ad), Int(b)) => *an == b * ad,
-
-            (Int(a), Float(b)) => (*a as f64) == *b,
-            (Float(a), Int(b)) => *a == (*b as f64),
-
-            (Ratio(an, ad), Float(b)) => (*an as f64) / (*ad as f64) == *b,
-            (Float(a), Ratio(bn, bd)) => *a == (*bn as f64) / (*bd as f64),
-
-            // Add more cross-type comparisons as needed
-            _ => false,
-        }
+        self.partial_cmp(other) == Some(Ordering::Equal)
     }
 }
 
@@ -124,33 +123,20 @@ impl Hash for NumericType {
 }
 
 impl PartialOrd for NumericType {
+    /// Numbers of every type compare by value. A float operand makes the
+    /// comparison a float one, where NaN is unordered; exact numbers compare
+    /// exactly.
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
         use NumericType::*;
 
         match (self, other) {
-            // Same types
             (Int(a), Int(b)) => a.partial_cmp(b),
-            (BigInt(a), BigInt(b)) => a.partial_cmp(b),
-            (Float(a), Float(b)) => a.partial_cmp(b),
-
-            // Ratios: a/b < c/d iff ad < bc (assuming positive denominators)
-            (Ratio(an, ad), Ratio(bn, bd)) => (an * bd).partial_cmp(&(bn * ad)),
-
-            // Cross-type comparisons
-            (Int(a), Float(b)) => (*a as f64).partial_cmp(b),
-            (Float(a), Int(b)) => a.partial_cmp(&(*b as f64)),
-
-            (Int(a), Ratio(bn, bd)) => (a * bd).partial_cmp(bn),
-            (Ratio(an, ad), Int(b)) => an.partial_cmp(&(b * ad)),
-
-            (Ratio(an, ad), Float(b)) => ((*an as f64) / (*ad as f64)).partial_cmp(b),
-            (Float(a), Ratio(bn, bd)) => a.partial_cmp(&((*bn as f64) / (*bd as f64))),
-
-            // BigInt comparisons
-            (Int(a), BigInt(b)) => BigInteger::from(*a).partial_cmp(b),
-            (BigInt(a), Int(b)) => a.as_ref().partial_cmp(&BigInteger::from(*b)),
-
-            _ => None,
+            (Float(_), _) | (_, Float(_)) => self.to_float().partial_cmp(&other.to_float()),
+            _ => match (self.small_fraction(), other.small_fraction()) {
+                // a/b < c/d iff ad < bc, as denominators are positive
+                (Some((an, ad)), Some((bn, bd))) => (an * bd).partial_cmp(&(bn * ad)),
+                _ => self.to_big_ratio()?.partial_cmp(&other.to_big_ratio()?),
+            },
         }
     }
 }
@@ -159,15 +145,15 @@ impl PartialOrd for NumericType {
 // Utility Functions
 // ============================================================================
 
-fn gcd(mut a: i64, mut b: i64) -> i64 {
-    a = a.abs();
-    b = b.abs();
+/// A numerator and denominator widened from 64 bits, so that adding or
+/// multiplying two of them cannot overflow
+type Fraction = (i128, i128);
+
+fn gcd(mut a: i128, mut b: i128) -> i128 {
     while b != 0 {
-        let temp = b;
-        b = a % b;
-        a = temp;
+        (a, b) = (b, a % b);
     }
-    a
+    a.abs()
 }
 
 impl NumericType {
@@ -177,21 +163,7 @@ impl NumericType {
             return Err("Division by zero".to_string());
         }
 
-        let g = gcd(num, denom);
-        let (mut num, mut denom) = (num / g, denom / g);
-
-        // Normalize: denominator always positive
-        if denom < 0 {
-            num = -num;
-            denom = -denom;
-        }
-
-        // Reduce to integer if possible
-        if denom == 1 {
-            Ok(NumericType::Int(num))
-        } else {
-            Ok(NumericType::Ratio(num, denom))
-        }
+        Ok(from_fraction(i128::from(num), i128::from(denom)))
     }
 
     /// Convert to float (may lose precision)
@@ -217,99 +189,195 @@ impl NumericType {
             NumericType::Float(x) => *x == 0.0,
         }
     }
-}
 
-// ============================================================================
-// Arithmetic Operations
-// ============================================================================
+    /// Check if number is a float NaN
+    pub fn is_nan(&self) -> bool {
+        matches!(self, NumericType::Float(x) if x.is_nan())
+    }
 
-impl NumericType {
-    /// Addition with automatic type promotion
-    pub fn add(&self, other: &NumericType) -> Result<NumericType, String> {
+    /// The integer part, rounding toward zero. Integers are returned as
+    /// they are; infinite and NaN floats have no integer part.
+    pub fn truncate(&self) -> Result<NumericType, String> {
         use NumericType::*;
 
-        match (self, other) {
-            // Int + Int with overflow check
-            (Int(a), Int(b)) => match a.checked_add(*b) {
-                Some(result) => Ok(Int(result)),
-                None => {
-                    // Promote to BigInt on overflow
-                    let big_a = BigInteger::from(*a);
-                    let big_b = BigInteger::from(*b);
-                    Ok(BigInt(Arc::new(big_a + big_b)))
+        match self {
+            Int(_) | BigInt(_) => Ok(self.clone()),
+            Ratio(num, denom) => Ok(Int(num / denom)),
+            BigRatio(r) => Ok(integer_from_big(r.to_integer())),
+            Float(x) if !x.is_finite() => Err(format!("cannot truncate {self} to an integer")),
+            Float(x) => {
+                let t = x.trunc();
+                if t >= i64::MIN as f64 && t < i64::MAX as f64 {
+                    Ok(Int(t as i64))
+                } else {
+                    // Finite floats always convert
+                    Ok(integer_from_big(BigInteger::from_f64(t).unwrap()))
                 }
-            },
+            }
+        }
+    }
 
-            // Int + BigInt
-            (Int(a), BigInt(b)) => Ok(BigInt(Arc::new(BigInteger::from(*a) + b.as_ref()))),
-            (BigInt(a), Int(b)) => Ok(BigInt(Arc::new(a.as_ref() + BigInteger::from(*b)))),
-
-            // BigInt + BigInt
-            (BigInt(a), BigInt(b)) => Ok(BigInt(Arc::new(a.as_ref() + b.as_ref()))),
-
-            // Ratio + Ratio: a/b + c/d = (ad + bc) / bd
-            (Ratio(an, ad), Ratio(bn, bd)) => {
-                let num = match an
-                    .checked_mul(*bd)
-                    .and_then(|x| bn.checked_mul(*ad).and_then(|y| x.checked_add(y)))
-                {
-                    Some(n) => n,
-                    None => {
-                        // Overflow: promote to BigRatio
-                        let a_big = NumRatio::new(BigInteger::from(*an), BigInteger::from(*ad));
-                        let b_big = NumRatio::new(BigInteger::from(*bn), BigInteger::from(*bd));
-                        return Ok(BigRatio(Arc::new(a_big + b_big)));
-                    }
-                };
-
-                let denom = match ad.checked_mul(*bd) {
-                    Some(d) => d,
-                    None => {
-                        // Overflow: promote to BigRatio
-                        let a_big = NumRatio::new(BigInteger::from(*an), BigInteger::from(*ad));
-                        let b_big = NumRatio::new(BigInteger::from(*bn), BigInteger::from(*bd));
-                        return Ok(BigRatio(Arc::new(a_big + b_big)));
-                    }
-                };
+    /// An integer written in the given radix (2 to 36), with lowercase
+    /// letters for digits above 9 and a leading `-` if negative.
+    pub fn to_str_radix(&self, radix: u32) -> Result<String, String> {
+        if !(2..=36).contains(&radix) {
+            return Err(format!("radix must be between 2 and 36, got {radix}"));
+        }
+        match self {
+            NumericType::Int(n) => Ok(BigInteger::from(*n).to_str_radix(radix)),
+            NumericType::BigInt(n) => Ok(n.to_str_radix(radix)),
+            _ => Err(format!(
+                "only integers can be written in radix {radix}, got {self}"
+            )),
+        }
+    }
 
-                Self::make_ratio(num, denom)
-            }
+    /// Parse an integer written in the given radix (2 to 36) with an
+    /// optional leading `-`, the inverse of `to_str_radix`. Letters for
+    /// digits above 9 may be either case.
+    ///
+    /// Returns None unless all of `text` is one integer.
+    pub fn from_str_radix(text: &str, radix: u32) -> Option<NumericType> {
+        let digits = text.strip_prefix('-').unwrap_or(text);
+        if !(2..=36).contains(&radix)
+            || digits.is_empty()
+            || !digits.chars().all(|c| c.is_digit(radix))
+        {
+            return None;
+        }
+        match i64::from_str_radix(text, radix) {
+            Ok(n) => Some(NumericType::Int(n)),
+            Err(_) => BigInteger::parse_bytes(text.as_bytes(), radix).map(integer_from_big),
+        }
+    }
 
-            // Int + Ratio: a + c/d = (ad + c) / d
-            (Int(a), Ratio(bn, bd)) => {
-                let num = match a.checked_mul(*bd).and_then(|x| x.checked_add(*bn)) {
-                    Some(n) => n,
-                    None => {
-                        let a_big = NumRatio::new(BigInteger::from(*a), BigInteger::one());
-                        let b_big = NumRatio::new(BigInteger::from(*bn), BigInteger::from(*bd));
-                        return Ok(BigRatio(Arc::new(a_big + b_big)));
-                    }
-                };
-                Self::make_ratio(num, *bd)
+    /// Parse a number written as the reader writes it: an integer (a
+    /// `BigInt` if it does not fit in an i64), a ratio such as `-3/4`, a
+    /// float such as `1.5` or `2e10`, or `##Inf`, `##-Inf` or `##NaN`.
+    ///
+    /// Returns None unless all of `text` is one number.
+    pub fn parse(text: &str) -> Option<NumericType> {
+        match text {
+            "##Inf" => return Some(NumericType::Float(f64::INFINITY)),
+            "##-Inf" => return Some(NumericType::Float(f64::NEG_INFINITY)),
+            "##NaN" => return Some(NumericType::Float(f64::NAN)),
+            _ => {}
+        }
+        // A leading digit also keeps out the words f64's parser accepts,
+        // such as "inf"
+        let unsigned = text.strip_prefix('-').unwrap_or(text);
+        if !unsigned.starts_with(|c: char| c.is_ascii_digit()) {
+            return None;
+        }
+
+        if let Some((numer, denom)) = text.split_once('/') {
+            return parse_ratio(numer, denom);
+        }
+        if text.contains(['.', 'e', 'E']) {
+            return text.parse::<f64>().ok().map(NumericType::Float);
+        }
+        parse_integer(text)
+    }
+}
+
+/// An arbitrary precision integer as an Int if it fits
+fn integer_from_big(n: BigInteger) -> NumericType {
+    n.to_i64()
+        .map_or_else(|| NumericType::BigInt(Arc::new(n)), NumericType::Int)
+}
+
+/// An integer of any size, or None if `text` has anything but an optional
+/// minus sign and digits
+fn parse_integer(text: &str) -> Option<NumericType> {
+    let unsigned = text.strip_prefix('-').unwrap_or(text);
+    if unsigned.is_empty() || !unsigned.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    match text.parse::<i64>() {
+        Ok(n) => Some(NumericType::Int(n)),
+        Err(_) => text
+            .parse::<BigInteger>()
+            .ok()
+            .map(|n| NumericType::BigInt(Arc::new(n))),
+    }
+}
+
+/// The smallest representation of an exact rational
+fn from_big_ratio(r: NumRatio<BigInteger>) -> NumericType {
+    if r.is_integer() {
+        return integer_from_big(r.to_integer());
+    }
+    match (r.numer().to_i64(), r.denom().to_i64()) {
+        (Some(num), Some(denom)) => NumericType::Ratio(num, denom),
+        _ => NumericType::BigRatio(Arc::new(r)),
+    }
+}
+
+/// The smallest representation of `num / denom`, where `denom` is not zero
+fn from_fraction(num: i128, denom: i128) -> NumericType {
+    let g = gcd(num, denom);
+    let (mut num, mut denom) = (num / g, denom / g);
+    if denom < 0 {
+        num = -num;
+        denom = -denom;
+    }
+    match (i64::try_from(num), i64::try_from(denom)) {
+        (Ok(num), Ok(1)) => NumericType::Int(num),
+        (Ok(num), Ok(denom)) => NumericType::Ratio(num, denom),
+        _ => from_big_ratio(NumRatio::new(
+            BigInteger::from(num),
+            BigInteger::from(denom),
+        )),
+    }
+}
+
+/// A ratio of two integers in lowest terms, or an integer if it divides
+/// exactly. None if either part is not an integer or the denominator is zero.
+fn parse_ratio(numer: &str, denom: &str) -> Option<NumericType> {
+    use NumericType::*;
+
+    match (parse_integer(numer)?, parse_integer(denom)?) {
+        (Int(n), Int(d)) => NumericType::make_ratio(n, d).ok(),
+        (n, d) => {
+            let big = |x: NumericType| match x {
+                Int(i) => BigInteger::from(i),
+                BigInt(b) => b.as_ref().clone(),
+                _ => unreachable!("parse_integer returns integers"),
+            };
+            let (n, d) = (big(n), big(d));
+            if d.is_zero() {
+                return None;
             }
-            (Ratio(an, ad), Int(b)) => {
-                let num = match b.checked_mul(*ad).and_then(|x| x.checked_add(*an)) {
-                    Some(n) => n,
-                    None => {
-                        let a_big = NumRatio::new(BigInteger::from(*an), BigInteger::from(*ad));
-                        let b_big = NumRatio::new(BigInteger::from(*b), BigInteger::one());
-                        return Ok(BigRatio(Arc::new(a_big + b_big)));
-                    }
-                };
-                Self::make_ratio(num, *ad)
+            let ratio = NumRatio::new(n, d);
+            if !ratio.is_integer() {
+                return Some(BigRatio(Arc::new(ratio)));
             }
+            Some(integer_from_big(ratio.to_integer()))
+        }
+    }
+}
 
-            // Float operations
-            (Float(a), Float(b)) => Ok(Float(a + b)),
-            (Int(a), Float(b)) => Ok(Float(*a as f64 + b)),
-            (Float(a), Int(b)) => Ok(Float(a + *b as f64)),
-            (Ratio(an, ad), Float(b)) => Ok(Float((*an as f64) / (*ad as f64) + b)),
-            (Float(a), Ratio(bn, bd)) => Ok(Float(a + (*bn as f64) / (*bd as f64))),
+// ============================================================================
+// Arithmetic Operations
+// ============================================================================
 
-            // BigRatio operations
-            (BigRatio(a), BigRatio(b)) => Ok(BigRatio(Arc::new(a.as_ref() + b.as_ref()))),
+impl NumericType {
+    /// Addition with automatic type promotion
+    pub fn add(&self, other: &NumericType) -> Result<NumericType, String> {
+        use NumericType::*;
 
-            _ => Err(format!("Unsupported addition: {self} + {other}")),
+        match (self, other) {
+            (Int(a), Int(b)) => Ok(a.checked_add(*b).map_or_else(
+                // Promote to BigInt on overflow
+                || BigInt(Arc::new(BigInteger::from(*a) + BigInteger::from(*b))),
+                Int,
+            )),
+            _ => Ok(self.combine(
+                other,
+                |(an, ad), (bn, bd)| (an * bd + bn * ad, ad * bd),
+                |a, b| a + b,
+                |a, b| a + b,
+            )),
         }
     }
 
@@ -318,285 +386,290 @@ impl NumericType {
         use NumericType::*;
 
         match (self, other) {
-            // Int - Int with overflow check
-            (Int(a), Int(b)) => match a.checked_sub(*b) {
-                Some(result) => Ok(Int(result)),
-                None => {
-                    let big_a = BigInteger::from(*a);
-                    let big_b = BigInteger::from(*b);
-                    Ok(BigInt(Arc::new(big_a - big_b)))
-                }
-            },
-
-            // Int - BigInt
-            (Int(a), BigInt(b)) => Ok(BigInt(Arc::new(BigInteger::from(*a) - b.as_ref()))),
-            (BigInt(a), Int(b)) => Ok(BigInt(Arc::new(a.as_ref() - BigInteger::from(*b)))),
-
-            // BigInt - BigInt
-            (BigInt(a), BigInt(b)) => Ok(BigInt(Arc::new(a.as_ref() - b.as_ref()))),
-
-            // Ratio - Ratio: a/b - c/d = (ad - bc) / bd
-            (Ratio(an, ad), Ratio(bn, bd)) => {
-                let num = match an
-                    .checked_mul(*bd)
-                    .and_then(|x| bn.checked_mul(*ad).and_then(|y| x.checked_sub(y)))
-                {
-                    Some(n) => n,
-                    None => {
-                        let a_big = NumRatio::new(BigInteger::from(*an), BigInteger::from(*ad));
-                        let b_big = NumRatio::new(BigInteger::from(*bn), BigInteger::from(*bd));
-                        return Ok(BigRatio(Arc::new(a_big - b_big)));
-                    }
-                };
-
-                let denom = match ad.checked_mul(*bd) {
-                    Some(d) => d,
-                    None => {
-                        let a_big = NumRatio::new(BigInteger::from(*an), BigInteger::from(*ad));
-                        let b_big = NumRatio::new(BigInteger::from(*bn), BigInteger::from(*bd));
-                        return Ok(BigRatio(Arc::new(a_big - b_big)));
-                    }
-                };
+            (Int(a), Int(b)) => Ok(a.checked_sub(*b).map_or_else(
+                || BigInt(Arc::new(BigInteger::from(*a) - BigInteger::from(*b))),
+                Int,
+            )),
+            _ => Ok(self.combine(
+                other,
+                |(an, ad), (bn, bd)| (an * bd - bn * ad, ad * bd),
+                |a, b| a - b,
+                |a, b| a - b,
+            )),
+        }
+    }
 
-                Self::make_ratio(num, denom)
-            }
+    /// Multiplication with automatic type promotion
+    pub fn mul(&self, other: &NumericType) -> Result<NumericType, String> {
+        use NumericType::*;
 
-            // Int - Ratio
-            (Int(a), Ratio(bn, bd)) => {
-                let num = match a.checked_mul(*bd).and_then(|x| x.checked_sub(*bn)) {
-                    Some(n) => n,
-                    None => {
-                        let a_big = NumRatio::new(BigInteger::from(*a), BigInteger::one());
-                        let b_big = NumRatio::new(BigInteger::from(*bn), BigInteger::from(*bd));
-                        return Ok(BigRatio(Arc::new(a_big - b_big)));
-                    }
-                };
-                Self::make_ratio(num, *bd)
-            }
-            (Ratio(an, ad), Int(b)) => {
-                let num = match b.checked_mul(*ad).and_then(|x| an.checked_sub(x)) {
-                    Some(n) => n,
-                    None => {
-                        let a_big = NumRatio::new(BigInteger::from(*an), BigInteger::from(*ad));
-                        let b_big = NumRatio::new(BigInteger::from(*b), BigInteger::one());
-                        return Ok(BigRatio(Arc::new(a_big - b_big)));
-                    }
-                };
-                Self::make_ratio(num, *ad)
-            }
+        match (self, other) {
+            (Int(a), Int(b)) => Ok(a.checked_mul(*b).map_or_else(
+                || BigInt(Arc::new(BigInteger::from(*a) * BigInteger::from(*b))),
+                Int,
+            )),
+            _ => Ok(self.combine(
+                other,
+                |(an, ad), (bn, bd)| (an * bn, ad * bd),
+                |a, b| a * b,
+                |a, b| a * b,
+            )),
+        }
+    }
 
-            // Float operations
-            (Float(a), Float(b)) => Ok(Float(a - b)),
-            (Int(a), Float(b)) => Ok(Float(*a as f64 - b)),
-            (Float(a), Int(b)) => Ok(Float(a - *b as f64)),
-            (Ratio(an, ad), Float(b)) => Ok(Float((*an as f64) / (*ad as f64) - b)),
-            (Float(a), Ratio(bn, bd)) => Ok(Float(a - (*bn as f64) / (*bd as f64))),
+    /// Division - returns exact ratio when possible
+    ///
+    /// A float operand makes it float division, which follows IEEE 754:
+    /// dividing by zero gives `##Inf`, `##-Inf` or `##NaN`. Only an exact
+    /// division by an exact zero is an error.
+    pub fn div(&self, other: &NumericType) -> Result<NumericType, String> {
+        if !matches!(self, NumericType::Float(_))
+            && !matches!(other, NumericType::Float(_))
+            && other.is_zero()
+        {
+            return Err("Division by zero".to_string());
+        }
+        Ok(self.combine(
+            other,
+            |(an, ad), (bn, bd)| (an * bd, ad * bn),
+            |a, b| a / b,
+            |a, b| a / b,
+        ))
+    }
 
-            // BigRatio operations
-            (BigRatio(a), BigRatio(b)) => Ok(BigRatio(Arc::new(a.as_ref() - b.as_ref()))),
+    /// An arithmetic operation on anything but two fixnums.
+    ///
+    /// A float operand makes the result a float. Otherwise the operation is
+    /// exact: `small` works on numerators and denominators widened to i128,
+    /// which cannot overflow for 64-bit parts, and `big` on bignum rationals.
+    /// Either way the result is reduced to lowest terms and stored in the
+    /// smallest type that holds it, so `1/3 + 2/3` is the integer 1 and a
+    /// ratio whose parts outgrow 64 bits becomes a BigRatio.
+    fn combine(
+        &self,
+        other: &NumericType,
+        small: fn(Fraction, Fraction) -> Fraction,
+        big: fn(&NumRatio<BigInteger>, &NumRatio<BigInteger>) -> NumRatio<BigInteger>,
+        float: fn(f64, f64) -> f64,
+    ) -> NumericType {
+        if let (Some(a), Some(b)) = (self.small_fraction(), other.small_fraction()) {
+            let (num, denom) = small(a, b);
+            return from_fraction(num, denom);
+        }
+        match exact_operands(self, other) {
+            Some((a, b)) => from_big_ratio(big(&a, &b)),
+            None => NumericType::Float(float(self.to_float(), other.to_float())),
+        }
+    }
 
-            _ => Err(format!("Unsupported subtraction: {self} - {other}")),
+    /// An Int or Ratio as a numerator and denominator
+    fn small_fraction(&self) -> Option<Fraction> {
+        match self {
+            NumericType::Int(n) => Some((i128::from(*n), 1)),
+            NumericType::Ratio(num, denom) => Some((i128::from(*num), i128::from(*denom))),
+            _ => None,
         }
     }
 
-    /// Multiplication with automatic type promotion
-    pub fn mul(&self, other: &NumericType) -> Result<NumericType, String> {
+    /// Negation
+    pub fn neg(&self) -> Result<NumericType, String> {
         use NumericType::*;
 
-        match (self, other) {
-            // Int * Int with overflow check
-            (Int(a), Int(b)) => match a.checked_mul(*b) {
+        match self {
+            Int(n) => match n.checked_neg() {
                 Some(result) => Ok(Int(result)),
-                None => {
-                    let big_a = BigInteger::from(*a);
-                    let big_b = BigInteger::from(*b);
-                    Ok(BigInt(Arc::new(big_a * big_b)))
-                }
+                None => Ok(BigInt(Arc::new(-BigInteger::from(*n)))),
             },
+            BigInt(n) => Ok(BigInt(Arc::new(-n.as_ref()))),
+            Ratio(num, denom) => Ok(Ratio(-num, *denom)),
+            BigRatio(r) => Ok(BigRatio(Arc::new(-r.as_ref()))),
+            Float(x) => Ok(Float(-x)),
+        }
+    }
+}
 
-            // Int * BigInt
-            (Int(a), BigInt(b)) => Ok(BigInt(Arc::new(BigInteger::from(*a) * b.as_ref()))),
-            (BigInt(a), Int(b)) => Ok(BigInt(Arc::new(a.as_ref() * BigInteger::from(*b)))),
-
-            // BigInt * BigInt
-            (BigInt(a), BigInt(b)) => Ok(BigInt(Arc::new(a.as_ref() * b.as_ref()))),
-
-            // Ratio * Ratio: a/b * c/d = ac / bd
-            (Ratio(an, ad), Ratio(bn, bd)) => {
-                let num = match an.checked_mul(*bn) {
-                    Some(n) => n,
-                    None => {
-                        let a_big = NumRatio::new(BigInteger::from(*an), BigInteger::from(*ad));
-                        let b_big = NumRatio::new(BigInteger::from(*bn), BigInteger::from(*bd));
-                        return Ok(BigRatio(Arc::new(a_big * b_big)));
-                    }
-                };
-
-                let denom = match ad.checked_mul(*bd) {
-                    Some(d) => d,
-                    None => {
-                        let a_big = NumRatio::new(BigInteger::from(*an), BigInteger::from(*ad));
-                        let b_big = NumRatio::new(BigInteger::from(*bn), BigInteger::from(*bd));
-                        return Ok(BigRatio(Arc::new(a_big * b_big)));
-                    }
-                };
+// ============================================================================
+// Integer Division, Powers and Ordering
+// ============================================================================
 
-                Self::make_ratio(num, denom)
-            }
+/// Both operands as exact rationals, or None if either is a float
+fn exact_operands(
+    a: &NumericType,
+    b: &NumericType,
+) -> Option<(NumRatio<BigInteger>, NumRatio<BigInteger>)> {
+    Some((a.to_big_ratio()?, b.to_big_ratio()?))
+}
 
-            // Int * Ratio
-            (Int(a), Ratio(bn, bd)) => {
-                let num = match a.checked_mul(*bn) {
-                    Some(n) => n,
-                    None => {
-                        let a_big = NumRatio::new(BigInteger::from(*a), BigInteger::one());
-                        let b_big = NumRatio::new(BigInteger::from(*bn), BigInteger::from(*bd));
-                        return Ok(BigRatio(Arc::new(a_big * b_big)));
-                    }
-                };
-                Self::make_ratio(num, *bd)
-            }
-            (Ratio(an, ad), Int(b)) => {
-                let num = match an.checked_mul(*b) {
-                    Some(n) => n,
-                    None => {
-                        let a_big = NumRatio::new(BigInteger::from(*an), BigInteger::from(*ad));
-                        let b_big = NumRatio::new(BigInteger::from(*b), BigInteger::one());
-                        return Ok(BigRatio(Arc::new(a_big * b_big)));
-                    }
-                };
-                Self::make_ratio(num, *ad)
-            }
+/// Remainder of a / b after truncating division
+fn exact_rem(a: &NumRatio<BigInteger>, b: &NumRatio<BigInteger>) -> NumRatio<BigInteger> {
+    a - b * (a / b).trunc()
+}
+
+impl NumericType {
+    /// The value as an exact rational, or None for floats
+    fn to_big_ratio(&self) -> Option<NumRatio<BigInteger>> {
+        use NumericType::*;
 
-            // Float operations
-            (Float(a), Float(b)) => Ok(Float(a * b)),
-            (Int(a), Float(b)) => Ok(Float(*a as f64 * b)),
-            (Float(a), Int(b)) => Ok(Float(a * *b as f64)),
-            (Ratio(an, ad), Float(b)) => Ok(Float((*an as f64) / (*ad as f64) * b)),
-            (Float(a), Ratio(bn, bd)) => Ok(Float(a * (*bn as f64) / (*bd as f64))),
+        match self {
+            Int(n) => Some(NumRatio::from_integer(BigInteger::from(*n))),
+            BigInt(n) => Some(NumRatio::from_integer(n.as_ref().clone())),
+            Ratio(num, denom) => Some(NumRatio::new_raw(
+                BigInteger::from(*num),
+                BigInteger::from(*denom),
+            )),
+            BigRatio(r) => Some(r.as_ref().clone()),
+            Float(_) => None,
+        }
+    }
 
-            // BigRatio operations
-            (BigRatio(a), BigRatio(b)) => Ok(BigRatio(Arc::new(a.as_ref() * b.as_ref()))),
+    /// Quotient rounded toward zero. Exact operands give an integer; a
+    /// float operand gives a float with no fractional part.
+    pub fn quot(&self, other: &NumericType) -> Result<NumericType, String> {
+        use NumericType::*;
 
-            _ => Err(format!("Unsupported multiplication: {self} * {other}")),
+        if other.is_zero() {
+            return Err("Division by zero".to_string());
+        }
+        match (self, other) {
+            (Int(a), Int(b)) => Ok(match a.checked_div(*b) {
+                Some(q) => Int(q),
+                // Only i64::MIN / -1 overflows
+                None => BigInt(Arc::new(-BigInteger::from(*a))),
+            }),
+            _ => match exact_operands(self, other) {
+                Some((a, b)) => Ok(integer_from_big((a / b).to_integer())),
+                None => Ok(Float((self.to_float() / other.to_float()).trunc())),
+            },
         }
     }
 
-    /// Division - returns exact ratio when possible
-    pub fn div(&self, other: &NumericType) -> Result<NumericType, String> {
+    /// Remainder of `quot`, which has the sign of the dividend
+    pub fn rem(&self, other: &NumericType) -> Result<NumericType, String> {
+        use NumericType::*;
+
         if other.is_zero() {
             return Err("Division by zero".to_string());
         }
+        match (self, other) {
+            (Int(a), Int(b)) => Ok(Int(a.checked_rem(*b).unwrap_or(0))),
+            _ => match exact_operands(self, other) {
+                Some((a, b)) => Ok(from_big_ratio(exact_rem(&a, &b))),
+                None => Ok(Float(self.to_float() % other.to_float())),
+            },
+        }
+    }
 
+    /// Remainder of division rounded toward negative infinity, which has
+    /// the sign of the divisor
+    pub fn modulo(&self, other: &NumericType) -> Result<NumericType, String> {
         use NumericType::*;
 
+        if other.is_zero() {
+            return Err("Division by zero".to_string());
+        }
         match (self, other) {
-            // Int / Int - return exact ratio if not evenly divisible
             (Int(a), Int(b)) => {
-                if a % b == 0 {
-                    Ok(Int(a / b))
+                let r = a.checked_rem(*b).unwrap_or(0);
+                Ok(Int(if r != 0 && (r < 0) != (*b < 0) {
+                    r + b
                 } else {
-                    Self::make_ratio(*a, *b)
-                }
+                    r
+                }))
             }
-
-            // Ratio / Ratio: (a/b) / (c/d) = (ad) / (bc)
-            (Ratio(an, ad), Ratio(bn, bd)) => {
-                let num = match an.checked_mul(*bd) {
-                    Some(n) => n,
-                    None => {
-                        let a_big = NumRatio::new(BigInteger::from(*an), BigInteger::from(*ad));
-                        let b_big = NumRatio::new(BigInteger::from(*bn), BigInteger::from(*bd));
-                        return Ok(BigRatio(Arc::new(a_big / b_big)));
-                    }
-                };
-
-                let denom = match ad.checked_mul(*bn) {
-                    Some(d) => d,
-                    None => {
-                        let a_big = NumRatio::new(BigInteger::from(*an), BigInteger::from(*ad));
-                        let b_big = NumRatio::new(BigInteger::from(*bn), BigInteger::from(*bd));
-                        return Ok(BigRatio(Arc::new(a_big / b_big)));
+            _ => match exact_operands(self, other) {
+                Some((a, b)) => {
+                    let r = exact_rem(&a, &b);
+                    if !r.is_zero() && r.is_negative() != b.is_negative() {
+                        Ok(from_big_ratio(r + b))
+                    } else {
+                        Ok(from_big_ratio(r))
                     }
-                };
-
-                Self::make_ratio(num, denom)
-            }
-
-            // Int / Ratio: a / (c/d) = ad / c
-            (Int(a), Ratio(bn, bd)) => {
-                let num = match a.checked_mul(*bd) {
-                    Some(n) => n,
-                    None => {
-                        let a_big = NumRatio::new(BigInteger::from(*a), BigInteger::one());
-                        let b_big = NumRatio::new(BigInteger::from(*bn), BigInteger::from(*bd));
-                        return Ok(BigRatio(Arc::new(a_big / b_big)));
-                    }
-                };
-                Self::make_ratio(num, *bn)
-            }
-
-            // Ratio / Int: (a/b) / c = a / (bc)
-            (Ratio(an, ad), Int(b)) => {
-                let denom = match ad.checked_mul(*b) {
-                    Some(d) => d,
-                    None => {
-                        let a_big = NumRatio::new(BigInteger::from(*an), BigInteger::from(*ad));
-                        let b_big = NumRatio::new(BigInteger::from(*b), BigInteger::one());
-                        return Ok(BigRatio(Arc::new(a_big / b_big)));
-                    }
-                };
-                Self::make_ratio(*an, denom)
-            }
-
-            // Float operations
-            (Float(a), Float(b)) => Ok(Float(a / b)),
-            (Int(a), Float(b)) => Ok(Float(*a as f64 / b)),
-            (Float(a), Int(b)) => Ok(Float(a / *b as f64)),
-            (Ratio(an, ad), Float(b)) => Ok(Float((*an as f64) / (*ad as f64) / b)),
-            (Float(a), Ratio(bn, bd)) => Ok(Float(a / ((*bn as f64) / (*bd as f64)))),
-
-            // BigInt operations
-            (BigInt(a), BigInt(b)) => {
-                let ratio = NumRatio::new(a.as_ref().clone(), b.as_ref().clone());
-                if ratio.is_integer() {
-                    Ok(BigInt(Arc::new(ratio.numer().clone())))
-                } else {
-                    Ok(BigRatio(Arc::new(ratio)))
                 }
-            }
+                None => {
+                    let (a, b) = (self.to_float(), other.to_float());
+                    let r = a % b;
+                    Ok(Float(if r != 0.0 && (r < 0.0) != (b < 0.0) {
+                        r + b
+                    } else {
+                        r
+                    }))
+                }
+            },
+        }
+    }
+
+    /// Absolute value
+    pub fn abs(&self) -> NumericType {
+        use NumericType::*;
 
-            (Int(a), BigInt(b)) => Ok(BigRatio(Arc::new(NumRatio::new(
-                BigInteger::from(*a),
-                b.as_ref().clone(),
-            )))),
+        match self {
+            Int(n) => match n.checked_abs() {
+                Some(result) => Int(result),
+                None => BigInt(Arc::new(BigInteger::from(*n).abs())),
+            },
+            Float(x) => Float(x.abs()),
+            _ => match self.to_big_ratio() {
+                Some(r) if r.is_negative() => from_big_ratio(-r),
+                _ => self.clone(),
+            },
+        }
+    }
 
-            (BigInt(a), Int(b)) => Ok(BigRatio(Arc::new(NumRatio::new(
-                a.as_ref().clone(),
-                BigInteger::from(*b),
-            )))),
+    /// Compare by value across representations. Exact operands compare
+    /// exactly; NaN is unordered.
+    fn compare(&self, other: &NumericType) -> Option<Ordering> {
+        match (self, other) {
+            (NumericType::Int(a), NumericType::Int(b)) => Some(a.cmp(b)),
+            _ => match exact_operands(self, other) {
+                Some((a, b)) => Some(a.cmp(&b)),
+                None => self.to_float().partial_cmp(&other.to_float()),
+            },
+        }
+    }
 
-            // BigRatio operations
-            (BigRatio(a), BigRatio(b)) => Ok(BigRatio(Arc::new(a.as_ref() / b.as_ref()))),
+    /// The smaller of two numbers, keeping its representation. NaN wins
+    /// over any other value.
+    pub fn min(&self, other: &NumericType) -> NumericType {
+        match self.compare(other) {
+            Some(Ordering::Greater) => other.clone(),
+            Some(_) => self.clone(),
+            None if self.is_nan() => self.clone(),
+            None => other.clone(),
+        }
+    }
 
-            _ => Err(format!("Unsupported division: {self} / {other}")),
+    /// The larger of two numbers, keeping its representation. NaN wins
+    /// over any other value.
+    pub fn max(&self, other: &NumericType) -> NumericType {
+        match self.compare(other) {
+            Some(Ordering::Less) => other.clone(),
+            Some(_) => self.clone(),
+            None if self.is_nan() => self.clone(),
+            None => other.clone(),
         }
     }
 
-    /// Negation
-    pub fn neg(&self) -> Result<NumericType, String> {
+    /// Raise to a power. An exact base with an integer exponent gives an
+    /// exact result; anything else is computed in floating point.
+    pub fn expt(&self, exponent: &NumericType) -> Result<NumericType, String> {
         use NumericType::*;
 
-        match self {
-            Int(n) => match n.checked_neg() {
-                Some(result) => Ok(Int(result)),
-                None => Ok(BigInt(Arc::new(-BigInteger::from(*n)))),
-            },
-            BigInt(n) => Ok(BigInt(Arc::new(-n.as_ref()))),
-            Ratio(num, denom) => Ok(Ratio(-num, *denom)),
-            BigRatio(r) => Ok(BigRatio(Arc::new(-r.as_ref()))),
-            Float(x) => Ok(Float(-x)),
+        let Int(e) = exponent else {
+            return Ok(Float(self.to_float().powf(exponent.to_float())));
+        };
+        let Some(base) = self.to_big_ratio() else {
+            return Ok(Float(self.to_float().powf(*e as f64)));
+        };
+        if let (Int(b), Ok(e)) = (self, u32::try_from(*e))
+            && let Some(result) = b.checked_pow(e)
+        {
+            return Ok(Int(result));
         }
+        let e = i32::try_from(*e).map_err(|_| format!("exponent {e} is too large"))?;
+        if e < 0 && base.is_zero() {
+            return Err("Division by zero".to_string());
+        }
+        Ok(from_big_ratio(base.pow(e)))
     }
 }
 
@@ -698,9 +771,9 @@ mod tests {
         // When b * ad overflows, we should promote to BigRatio
 
         // Use large values that will cause overflow when multiplied
-        let large_num = i64::MAX / 2;
-        let large_denom = 3i64;
-        let large_int = i64::MAX / 2;
+        let large_num = i64::MAX;
+        let large_denom = 2i64;
+        let large_int = i64::MIN;
 
         let ratio = NumericType::Ratio(large_num, large_denom);
         let int = NumericType::Int(large_int);
@@ -729,6 +802,83 @@ mod tests {
         assert_eq!(result, NumericType::Ratio(4, 3));
     }
 
+    #[test]
+    fn test_parse() {
+        assert_eq!(NumericType::parse("42"), Some(NumericType::Int(42)));
+        assert_eq!(NumericType::parse("-6/4"), Some(NumericType::Ratio(-3, 2)));
+        assert_eq!(NumericType::parse("2.5"), Some(NumericType::Float(2.5)));
+        assert!(matches!(
+            NumericType::parse("99999999999999999999"),
+            Some(NumericType::BigInt(_))
+        ));
+        assert_eq!(NumericType::parse("-"), None);
+        assert_eq!(NumericType::parse(".5"), None);
+        assert_eq!(NumericType::parse("1/0"), None);
+    }
+
+    #[test]
+    fn test_truncate() {
+        assert_eq!(
+            NumericType::Ratio(-7, 2).truncate().unwrap(),
+            NumericType::Int(-3)
+        );
+        assert_eq!(
+            NumericType::Float(-2.9).truncate().unwrap(),
+            NumericType::Int(-2)
+        );
+        assert!(NumericType::Float(f64::NAN).truncate().is_err());
+    }
+
+    #[test]
+    fn test_to_str_radix() {
+        assert_eq!(NumericType::Int(255).to_str_radix(16).unwrap(), "ff");
+        assert_eq!(NumericType::Int(-5).to_str_radix(2).unwrap(), "-101");
+        assert!(NumericType::Int(5).to_str_radix(37).is_err());
+        assert!(NumericType::Ratio(1, 2).to_str_radix(10).is_err());
+    }
+
+    #[test]
+    fn test_integer_division_stays_integer() {
+        let a = NumericType::Int(-7);
+        let b = NumericType::Int(2);
+
+        assert_eq!(a.quot(&b).unwrap(), NumericType::Int(-3));
+        assert_eq!(a.rem(&b).unwrap(), NumericType::Int(-1));
+        assert_eq!(a.modulo(&b).unwrap(), NumericType::Int(1));
+        assert!(a.modulo(&NumericType::Int(0)).is_err());
+
+        // i64::MIN / -1 overflows i64
+        let min = NumericType::Int(i64::MIN);
+        let minus_one = NumericType::Int(-1);
+        assert!(matches!(
+            min.quot(&minus_one).unwrap(),
+            NumericType::BigInt(_)
+        ));
+        assert_eq!(min.modulo(&minus_one).unwrap(), NumericType::Int(0));
+    }
+
+    #[test]
+    fn test_mixed_division_promotes() {
+        let ratio = NumericType::Ratio(7, 2);
+        assert_eq!(
+            ratio.modulo(&NumericType::Int(1)).unwrap(),
+            NumericType::Ratio(1, 2)
+        );
+        assert_eq!(
+            NumericType::Float(7.5).rem(&NumericType::Int(2)).unwrap(),
+            NumericType::Float(1.5)
+        );
+    }
+
+    #[test]
+    fn test_min_max_keep_representation() {
+        let one = NumericType::Int(1);
+        let half = NumericType::Float(0.5);
+        assert_eq!(one.min(&half), NumericType::Float(0.5));
+        assert_eq!(one.max(&half), NumericType::Int(1));
+        assert!(one.max(&NumericType::Float(f64::NAN)).is_nan());
+    }
+
     #[test]
     fn test_ratio_arithmetic_overflow_consistency() {
         // Ensure all overflow paths work consistently
@@ -740,8 +890,8 @@ mod tests {
         assert!(r1.add(&i1).is_ok());
 
         // Test Ratio - Int overflow
-        let r2 = NumericType::Ratio(large, 3);
-        let i2 = NumericType::Int(large);
+        let r2 = NumericType::Ratio(large, 2);
+        let i2 = NumericType::Int(-large);
         assert!(r2.sub(&i2).is_ok());
 
         // Both should produce BigRatio
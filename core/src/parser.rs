@@ -1,42 +1,285 @@
+use std::collections::HashMap;
 use std::sync::Arc;
 
+use rustc_hash::{FxHashMap, FxHashSet};
+
+use crate::abstractions::check_hash_key;
+use crate::error::{ParseError, ParseErrorKind};
 use crate::interner::InternedSymbol;
-use crate::language::{AtomType, SymbolType, Value, VectorValue, cons};
-use crate::lexer::{Lexer, Token};
+use crate::language::{
+    AtomType, ConsCell, MapValue, SetValue, SymbolType, Value, VectorValue, cons,
+};
+use crate::lexer::{Lexer, Span, Token};
+
+// ============================================================================
+// Source Spans
+// ============================================================================
+
+/// Where the lists of a parsed form start in the source.
+///
+/// Each list's first cons cell maps to the span of its opening parenthesis;
+/// the `(quote x)` style lists built for `'x` map to the quote character.
+/// Cells are looked up by identity, so clones of a parsed value find the
+/// same spans but structurally equal values built elsewhere do not.
+#[derive(Debug, Clone, Default)]
+pub struct SpanTable {
+    /// Keyed by the address of each list's first cell
+    spans: HashMap<usize, Span>,
+}
+
+impl SpanTable {
+    /// The span recorded for `value`, if it is a cons cell from the parse.
+    pub fn get(&self, value: &Value) -> Option<Span> {
+        match value {
+            Value::Cons(cell) => self.spans.get(&cell_address(cell)).copied(),
+            _ => None,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.spans.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.spans.is_empty()
+    }
+
+    fn insert(&mut self, value: &Value, span: Span) {
+        if let Value::Cons(cell) = value {
+            self.spans.insert(cell_address(cell), span);
+        }
+    }
+}
+
+fn cell_address(cell: &Arc<ConsCell>) -> usize {
+    Arc::as_ptr(cell) as usize
+}
+
+/// A parsed form together with its source locations.
+///
+/// The value keeps its cons cells alive, so the table's entries stay valid
+/// for as long as the `SpannedValue` exists.
+#[derive(Debug, Clone)]
+pub struct SpannedValue {
+    pub value: Value,
+    /// Where the form starts
+    pub span: Span,
+    /// Just past the form's last character
+    pub end: Span,
+    pub spans: SpanTable,
+}
 
 // ============================================================================
 // Parser
 // ============================================================================
 
+/// Reads forms from a lexer one at a time.
+///
+/// Tokens are read only as forms need them. As an iterator, a parser yields
+/// each top-level form in turn and stops after the first error.
 pub struct Parser<'a> {
-    lexer: &'a mut Lexer,
+    lexer: LexerHandle<'a>,
     current_token: Token,
+    current_span: Span,
+    /// A lexer error not yet reported, from `new` or from the token after a
+    /// top-level form
+    pending_error: Option<ParseError>,
+    /// Spans of the lists parsed so far, when parsing with spans
+    spans: Option<SpanTable>,
+    /// How many lists, vectors, maps and sets enclose the current token
+    depth: usize,
+    /// Just past the last form parsed
+    end: Span,
+    /// Set once iteration has returned an error
+    failed: bool,
+}
+
+/// The lexer a parser reads from, which it may own.
+enum LexerHandle<'a> {
+    Borrowed(&'a mut Lexer),
+    Owned(Box<Lexer>),
+}
+
+impl LexerHandle<'_> {
+    fn get(&mut self) -> &mut Lexer {
+        match self {
+            LexerHandle::Borrowed(lexer) => lexer,
+            LexerHandle::Owned(lexer) => lexer,
+        }
+    }
 }
 
 impl<'a> Parser<'a> {
     pub fn new(lexer: &'a mut Lexer) -> Self {
-        let current_token = lexer.next_token().unwrap_or(Token::Eof);
-        Parser {
+        Self::with_handle(LexerHandle::Borrowed(lexer))
+    }
+
+    /// A parser that owns a lexer over `input`.
+    pub fn from_source(input: &str) -> Parser<'static> {
+        Parser::with_handle(LexerHandle::Owned(Box::new(Lexer::new(input))))
+    }
+
+    fn with_handle(mut lexer: LexerHandle<'a>) -> Self {
+        let (current_token, current_span, pending_error) = match lexer.get().next_token() {
+            Ok((token, span)) => (token, span, None),
+            Err(e) => (Token::Eof, Span::START, Some(e)),
+        };
+        let mut parser = Parser {
             lexer,
             current_token,
+            current_span,
+            pending_error,
+            spans: None,
+            depth: 0,
+            end: Span::START,
+            failed: false,
+        };
+        if let Err(e) = parser.skip_discarded() {
+            parser.current_token = Token::Eof;
+            parser.pending_error = Some(e);
         }
+        parser
+    }
+
+    fn advance(&mut self) -> Result<(), ParseError> {
+        (self.current_token, self.current_span) = self.lexer.get().next_token()?;
+        self.skip_discarded()
     }
 
-    fn advance(&mut self) -> Result<(), String> {
-        self.current_token = self.lexer.next_token()?;
+    /// Read and drop the form after each `#_` at the current token.
+    ///
+    /// Discarded forms get no spans and do not move `end`.
+    fn skip_discarded(&mut self) -> Result<(), ParseError> {
+        while self.current_token == Token::Discard {
+            let end = self.end;
+            let spans = self.spans.take();
+            self.depth += 1;
+            let discarded = self
+                .advance()
+                .and_then(|()| self.parse_expression().map(drop));
+            self.depth -= 1;
+            self.spans = spans;
+            self.end = end;
+            discarded?;
+        }
         Ok(())
     }
 
-    pub fn parse_expression(&mut self) -> Result<Value, String> {
+    /// Consume the last token of a form.
+    ///
+    /// At top level, an invalid token after the form belongs to the next
+    /// form, so the error is kept for the next parse instead of failing this
+    /// one.
+    fn finish_form(&mut self) -> Result<(), ParseError> {
+        self.end = self.lexer.get().location();
+        if self.depth > 0 {
+            return self.advance();
+        }
+        if let Err(e) = self.advance() {
+            self.current_token = Token::Eof;
+            self.pending_error = Some(e);
+        }
+        Ok(())
+    }
+
+    /// Parse forms up to `close`, which is left as the current token. In a
+    /// list, a `.` also ends the forms and is left as the current token.
+    fn parse_elements(&mut self, close: &Token) -> Result<Vec<Value>, ParseError> {
+        self.depth += 1;
+        let mut elements = Vec::new();
+        let result = loop {
+            if self.current_token == *close
+                || self.current_token == Token::Eof
+                || (*close == Token::RParen && self.current_token == Token::Dot)
+            {
+                break Ok(elements);
+            }
+            match self.parse_expression() {
+                Ok(element) => elements.push(element),
+                Err(e) => break Err(e),
+            }
+        };
+        self.depth -= 1;
+        result
+    }
+
+    /// Parse the tail of a dotted list, from its `.` up to the closing
+    /// parenthesis, which is left as the current token. Exactly one form
+    /// must follow the `.`, and `elements` must hold at least one before it.
+    fn parse_dotted_tail(&mut self, elements: &[Value]) -> Result<Value, ParseError> {
+        let dot = self.current_span;
+        if elements.is_empty() {
+            return Err(invalid_dot(format!("Nothing before . at {dot}")));
+        }
+        self.depth += 1;
+        let tail = self.advance().and_then(|()| match self.current_token {
+            Token::RParen => Err(invalid_dot(format!("Nothing after . at {dot}"))),
+            Token::Dot => Err(invalid_dot(format!(
+                "More than one . in a list at {}",
+                self.current_span
+            ))),
+            _ => self.parse_expression(),
+        });
+        self.depth -= 1;
+        let tail = tail?;
+        match self.current_token {
+            Token::RParen | Token::Eof => Ok(tail),
+            Token::Dot => Err(invalid_dot(format!(
+                "More than one . in a list at {}",
+                self.current_span
+            ))),
+            _ => Err(invalid_dot(format!(
+                "More than one form after . at {}",
+                self.current_span
+            ))),
+        }
+    }
+
+    /// True once every form in the input has been parsed.
+    pub fn is_at_end(&self) -> bool {
+        self.pending_error.is_none() && self.current_token == Token::Eof
+    }
+
+    /// Parse the next expression, recording where its lists start.
+    pub fn parse_spanned_expression(&mut self) -> Result<SpannedValue, ParseError> {
+        let span = self.current_span;
+        self.spans = Some(SpanTable::default());
+        let value = self.parse_expression();
+        let spans = self.spans.take().unwrap_or_default();
+        Ok(SpannedValue {
+            value: value?,
+            span,
+            end: self.end,
+            spans,
+        })
+    }
+
+    fn record(&mut self, value: Value, span: Span) -> Value {
+        if let Some(ref mut spans) = self.spans {
+            spans.insert(&value, span);
+        }
+        value
+    }
+
+    pub fn parse_expression(&mut self) -> Result<Value, ParseError> {
+        if let Some(e) = self.pending_error.take() {
+            return Err(e);
+        }
+        let start = self.current_span;
         match &self.current_token.clone() {
             Token::Number(n) => {
                 let value = Value::Atom(AtomType::Number(n.clone()));
-                self.advance()?;
+                self.finish_form()?;
                 Ok(value)
             }
             Token::String(s) => {
                 let value = Value::Atom(AtomType::String(s.clone()));
-                self.advance()?;
+                self.finish_form()?;
+                Ok(value)
+            }
+            Token::Char(c) => {
+                let value = Value::Atom(AtomType::Char(*c));
+                self.finish_form()?;
                 Ok(value)
             }
             Token::Symbol(s) => {
@@ -47,97 +290,261 @@ impl<'a> Parser<'a> {
                 } else {
                     Value::Atom(AtomType::Symbol(SymbolType::Symbol(InternedSymbol::new(s))))
                 };
-                self.advance()?;
+                self.finish_form()?;
                 Ok(value)
             }
             Token::Quote => {
                 self.advance()?;
                 let quoted = self.parse_expression()?;
-                Ok(cons(
+                let form = cons(
                     Value::Atom(AtomType::Symbol(SymbolType::Symbol(InternedSymbol::new(
                         "quote",
                     )))),
                     cons(quoted, Value::Nil),
-                ))
+                );
+                Ok(self.record(form, start))
             }
             Token::Quasiquote => {
                 self.advance()?;
                 let quoted = self.parse_expression()?;
-                Ok(cons(
+                let form = cons(
                     Value::Atom(AtomType::Symbol(SymbolType::Symbol(InternedSymbol::new(
                         "quasiquote",
                     )))),
                     cons(quoted, Value::Nil),
-                ))
+                );
+                Ok(self.record(form, start))
             }
             Token::Unquote => {
                 self.advance()?;
                 let unquoted = self.parse_expression()?;
-                Ok(cons(
+                let form = cons(
                     Value::Atom(AtomType::Symbol(SymbolType::Symbol(InternedSymbol::new(
                         "unquote",
                     )))),
                     cons(unquoted, Value::Nil),
-                ))
+                );
+                Ok(self.record(form, start))
             }
             Token::UnquoteSplicing => {
                 self.advance()?;
                 let unquoted = self.parse_expression()?;
-                Ok(cons(
+                let form = cons(
                     Value::Atom(AtomType::Symbol(SymbolType::Symbol(InternedSymbol::new(
                         "unquote-splicing",
                     )))),
                     cons(unquoted, Value::Nil),
-                ))
+                );
+                Ok(self.record(form, start))
+            }
+            Token::Deref => {
+                self.advance()?;
+                let reference = self.parse_expression()?;
+                let form = cons(
+                    Value::Atom(AtomType::Symbol(SymbolType::Symbol(InternedSymbol::new(
+                        "deref",
+                    )))),
+                    cons(reference, Value::Nil),
+                );
+                Ok(self.record(form, start))
             }
             Token::LParen => {
                 self.advance()?;
-                let mut elements = Vec::new();
-
-                while !matches!(self.current_token, Token::RParen | Token::Eof) {
-                    elements.push(self.parse_expression()?);
-                }
+                let elements = self.parse_elements(&Token::RParen)?;
+                let tail = if self.current_token == Token::Dot {
+                    self.parse_dotted_tail(&elements)?
+                } else {
+                    Value::Nil
+                };
 
                 if matches!(self.current_token, Token::Eof) {
-                    return Err("Unclosed parenthesis".to_string());
+                    return Err(ParseError::new(
+                        ParseErrorKind::UnclosedDelimiter,
+                        format!("Unclosed parenthesis starting at {start}"),
+                    ));
                 }
 
-                self.advance()?; // consume )
+                self.finish_form()?; // consume )
 
                 let list = elements
                     .into_iter()
                     .rev()
-                    .fold(Value::Nil, |acc, val| cons(val, acc));
-                Ok(list)
+                    .fold(tail, |acc, val| cons(val, acc));
+                Ok(self.record(list, start))
             }
             Token::VectorOpen => {
                 self.advance()?;
-                let mut vec_elements = Vec::new();
-
-                while !matches!(self.current_token, Token::VectorClose | Token::Eof) {
-                    vec_elements.push(self.parse_expression()?);
-                }
+                let vec_elements = self.parse_elements(&Token::VectorClose)?;
 
                 if matches!(self.current_token, Token::Eof) {
-                    return Err("Unclosed vector literal".to_string());
+                    return Err(ParseError::new(
+                        ParseErrorKind::UnclosedDelimiter,
+                        format!("Unclosed vector literal starting at {start}"),
+                    ));
                 }
 
-                self.advance()?; // consume >>
+                self.finish_form()?; // consume >>
 
                 // Parser creates fast vectors by default
                 Ok(Value::Vector(Arc::new(VectorValue {
                     elements: vec_elements,
                 })))
             }
-            Token::RParen => Err("Unexpected )".to_string()),
-            Token::VectorClose => Err("Unexpected >>".to_string()),
-            Token::Eof => Err("Unexpected end of input".to_string()),
+            Token::MapOpen => {
+                self.advance()?;
+                let elements = self.parse_elements(&Token::MapClose)?;
+
+                if matches!(self.current_token, Token::Eof) {
+                    return Err(ParseError::new(
+                        ParseErrorKind::UnclosedDelimiter,
+                        format!("Unclosed map literal starting at {start}"),
+                    ));
+                }
+                if elements.len() % 2 != 0 {
+                    return Err(ParseError::new(
+                        ParseErrorKind::InvalidMapLiteral,
+                        format!(
+                            "Map literal starting at {start} has {} forms; expected key/value pairs",
+                            elements.len()
+                        ),
+                    ));
+                }
+
+                self.finish_form()?; // consume }
+
+                map_literal(elements, start)
+            }
+            Token::SetOpen => {
+                self.advance()?;
+                let elements = self.parse_elements(&Token::MapClose)?;
+
+                if matches!(self.current_token, Token::Eof) {
+                    return Err(ParseError::new(
+                        ParseErrorKind::UnclosedDelimiter,
+                        format!("Unclosed set literal starting at {start}"),
+                    ));
+                }
+
+                self.finish_form()?; // consume }
+
+                set_literal(elements, start)
+            }
+            Token::Discard => unreachable!("advancing skips #_ and its form"),
+            Token::Dot => Err(invalid_dot(format!("Unexpected . at {start}"))),
+            Token::RParen => Err(unexpected_close(format!("Unexpected ) at {start}"))),
+            Token::VectorClose => Err(unexpected_close(format!("Unexpected >> at {start}"))),
+            Token::MapClose => Err(unexpected_close(format!("Unexpected }} at {start}"))),
+            Token::Eof => Err(ParseError::new(
+                ParseErrorKind::UnexpectedEof,
+                "Unexpected end of input",
+            )),
+        }
+    }
+}
+
+impl Iterator for Parser<'_> {
+    type Item = Result<Value, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed || self.is_at_end() {
+            return None;
         }
+        let form = self.parse_expression();
+        self.failed = form.is_err();
+        Some(form)
     }
 }
 
-pub fn parse(input: &str) -> Result<Value, String> {
+fn unexpected_close(message: String) -> ParseError {
+    ParseError::new(ParseErrorKind::UnexpectedClose, message)
+}
+
+fn invalid_dot(message: String) -> ParseError {
+    ParseError::new(ParseErrorKind::InvalidDottedList, message)
+}
+
+/// Build the map for a `{k v ...}` literal from its forms.
+///
+/// Like vectors, map literals are data: keys and values are not evaluated.
+#[allow(clippy::mutable_key_type)]
+fn map_literal(elements: Vec<Value>, start: Span) -> Result<Value, ParseError> {
+    let invalid = |message| ParseError::new(ParseErrorKind::InvalidMapLiteral, message);
+    let mut entries = FxHashMap::default();
+    let mut elements = elements.into_iter();
+    while let (Some(key), Some(value)) = (elements.next(), elements.next()) {
+        check_hash_key(&key).map_err(|e| invalid(format!("{e} at {start}")))?;
+        if entries.contains_key(&key) {
+            return Err(invalid(format!(
+                "Duplicate key {key} in map literal starting at {start}"
+            )));
+        }
+        entries.insert(key, value);
+    }
+    Ok(Value::Map(Arc::new(MapValue { entries })))
+}
+
+/// Build the set for a `#{...}` literal from its forms.
+///
+/// Elements are not evaluated, and writing the same element twice is an error
+/// rather than being silently collapsed.
+#[allow(clippy::mutable_key_type)]
+fn set_literal(elements: Vec<Value>, start: Span) -> Result<Value, ParseError> {
+    let invalid = |message| ParseError::new(ParseErrorKind::InvalidSetLiteral, message);
+    let mut set = FxHashSet::default();
+    for element in elements {
+        check_hash_key(&element).map_err(|e| invalid(format!("{e} at {start}")))?;
+        if set.contains(&element) {
+            return Err(invalid(format!(
+                "Duplicate element {element} in set literal starting at {start}"
+            )));
+        }
+        set.insert(element);
+    }
+    Ok(Value::Set(Arc::new(SetValue { elements: set })))
+}
+
+pub fn parse(input: &str) -> Result<Value, ParseError> {
     let mut lexer = Lexer::new(input);
     let mut parser = Parser::new(&mut lexer);
     parser.parse_expression()
 }
+
+/// Parse every expression in `input`.
+///
+/// Where `parse` ignores whatever follows the first expression, here the rest
+/// of the input must be further expressions, so a stray closing delimiter is
+/// an error naming its location. Input holding only whitespace and comments
+/// parses to no expressions.
+pub fn parse_all(input: &str) -> Result<Vec<Value>, ParseError> {
+    parse_iter(input).collect()
+}
+
+/// Parse the expressions in `input` one at a time.
+///
+/// Each form is read only when the iterator is advanced, so a caller can
+/// evaluate a large input form by form without holding all of it parsed.
+/// Forms are read exactly as `parse_all` reads them; the iterator ends after
+/// the first error.
+pub fn parse_iter(input: &str) -> impl Iterator<Item = Result<Value, ParseError>> + use<> {
+    Parser::from_source(input)
+}
+
+/// Parse every expression in `input`, like `parse_all`, along with where
+/// each one starts and ends and the locations of its lists.
+pub fn parse_all_with_spans(input: &str) -> Result<Vec<SpannedValue>, ParseError> {
+    let mut lexer = Lexer::new(input);
+    let mut parser = Parser::new(&mut lexer);
+    let mut forms = Vec::new();
+    while !parser.is_at_end() {
+        forms.push(parser.parse_spanned_expression()?);
+    }
+    Ok(forms)
+}
+
+/// Parse the first expression in `input`, like `parse`, along with the
+/// locations of its lists.
+pub fn parse_with_spans(input: &str) -> Result<SpannedValue, ParseError> {
+    let mut lexer = Lexer::new(input);
+    let mut parser = Parser::new(&mut lexer);
+    parser.parse_spanned_expression()
+}
@@ -0,0 +1,191 @@
+//! Pretty-printing values across lines
+//!
+//! `pretty_print` writes a value the way `Display` does, but breaks
+//! collections that do not fit in the given width over several lines. The
+//! layout is fill style: elements go on the current line while they fit, and
+//! a collection that does not fit on its own line is broken in turn, with its
+//! elements indented to line up after the opening delimiter.
+//!
+//! ```text
+//! (define-record point (x 0) (y 0)
+//!  {:doc "A point in the plane"
+//!   :fields <<x y>>})
+//! ```
+//!
+//! The thread's `PrintLimits` apply as they do for `Display`.
+
+use crate::language::{PrintLimits, Value, print_limits, set_print_limits};
+
+/// Print `value` across lines so that, where possible, no line is wider than
+/// `width` characters.
+///
+/// Atoms are never split, so a long string or symbol can still overrun the
+/// width. Strings are quoted and escaped, as `Display` prints them.
+pub fn pretty_print(value: &Value, width: usize) -> String {
+    let limits = print_limits();
+    let mut printer = Pretty {
+        out: String::new(),
+        width,
+        limits,
+        depth: 0,
+        lines: 0,
+    };
+    printer.value(value);
+    let out = printer.out;
+    match limits.total {
+        Some(total) if out.chars().count() > total => {
+            let cut: String = out.chars().take(total).collect();
+            format!("{cut}… (truncated at {total} characters)")
+        }
+        _ => out,
+    }
+}
+
+/// One element of a collection being laid out.
+enum Item<'a> {
+    Value(&'a Value),
+    /// A map entry, kept together where it fits
+    Entry(&'a Value, &'a Value),
+    /// Fixed text such as the `.` of a dotted list
+    Text(String),
+}
+
+struct Pretty {
+    out: String,
+    width: usize,
+    limits: PrintLimits,
+    depth: usize,
+    /// Line breaks written so far
+    lines: usize,
+}
+
+impl Pretty {
+    /// Characters since the start of the current line.
+    fn column(&self) -> usize {
+        let line_start = self.out.rfind('\n').map_or(0, |i| i + 1);
+        self.out[line_start..].chars().count()
+    }
+
+    /// `value` on one line, with the depth limit reduced by how deep it is.
+    fn flat(&self, value: &Value) -> String {
+        let limits = PrintLimits {
+            depth: self.limits.depth.map(|max| max.saturating_sub(self.depth)),
+            total: None,
+            ..self.limits
+        };
+        let previous = set_print_limits(limits);
+        let text = value.to_string();
+        set_print_limits(previous);
+        text
+    }
+
+    fn flat_item(&self, item: &Item) -> String {
+        match item {
+            Item::Value(value) => self.flat(value),
+            Item::Entry(key, value) => format!("{} {}", self.flat(key), self.flat(value)),
+            Item::Text(text) => text.clone(),
+        }
+    }
+
+    fn value(&mut self, value: &Value) {
+        let flat = self.flat(value);
+        let too_deep = self.limits.depth.is_some_and(|max| self.depth >= max);
+        if too_deep || self.column() + flat.chars().count() <= self.width {
+            self.out.push_str(&flat);
+            return;
+        }
+        match value {
+            Value::Cons(_) => {
+                let mut items = Vec::new();
+                let mut current = value;
+                while let Value::Cons(cell) = current {
+                    items.push(Item::Value(&cell.car));
+                    current = &cell.cdr;
+                }
+                let len = items.len();
+                let mut items = self.limit(items, len);
+                if !matches!(current, Value::Nil) {
+                    items.push(Item::Text(".".to_string()));
+                    items.push(Item::Value(current));
+                }
+                self.collection("(", ")", items);
+            }
+            Value::Vector(vec) => self.values("<<", ">>", &vec.elements),
+            Value::PersistentVector(vec) => self.values("#pvec[", "]", &vec.elements),
+            Value::Set(set) => self.values("#{", "}", &set.elements),
+            Value::PersistentSet(set) => self.values("#pset{", "}", &set.elements),
+            Value::Map(map) => {
+                let items = map.entries.iter().map(|(k, v)| Item::Entry(k, v)).collect();
+                let items = self.limit(items, map.entries.len());
+                self.collection("{", "}", items);
+            }
+            Value::PersistentMap(map) => {
+                let items = map.entries.iter().map(|(k, v)| Item::Entry(k, v)).collect();
+                let items = self.limit(items, map.entries.len());
+                self.collection("#pmap{", "}", items);
+            }
+            // Atoms, functions and the rest are never split
+            _ => self.out.push_str(&flat),
+        }
+    }
+
+    fn values<'a>(
+        &mut self,
+        open: &str,
+        close: &str,
+        elements: impl IntoIterator<Item = &'a Value>,
+    ) {
+        let items: Vec<Item> = elements.into_iter().map(Item::Value).collect();
+        let len = items.len();
+        let items = self.limit(items, len);
+        self.collection(open, close, items);
+    }
+
+    /// Keep the items the length limit allows, noting how many were left out.
+    fn limit<'a>(&self, mut items: Vec<Item<'a>>, len: usize) -> Vec<Item<'a>> {
+        if let Some(max) = self.limits.length
+            && len > max
+        {
+            items.truncate(max);
+            items.push(Item::Text(format!("… {} more", len - max)));
+        }
+        items
+    }
+
+    /// Lay out `items` between `open` and `close`, filling each line.
+    fn collection(&mut self, open: &str, close: &str, items: Vec<Item>) {
+        let indent = self.column() + open.chars().count();
+        self.out.push_str(open);
+        self.depth += 1;
+        let mut broken = false;
+        for (i, item) in items.iter().enumerate() {
+            if i > 0 {
+                let len = self.flat_item(item).chars().count();
+                if broken || self.column() + 1 + len > self.width {
+                    self.out.push('\n');
+                    self.out.push_str(&" ".repeat(indent));
+                    self.lines += 1;
+                } else {
+                    self.out.push(' ');
+                }
+            }
+            let lines = self.lines;
+            self.item(item);
+            broken = self.lines > lines;
+        }
+        self.depth -= 1;
+        self.out.push_str(close);
+    }
+
+    fn item(&mut self, item: &Item) {
+        match item {
+            Item::Value(value) => self.value(value),
+            Item::Entry(key, value) => {
+                self.value(key);
+                self.out.push(' ');
+                self.value(value);
+            }
+            Item::Text(text) => self.out.push_str(text),
+        }
+    }
+}
@@ -0,0 +1,179 @@
+//! Writing values back out as source
+//!
+//! `value_source` gives an expression that evaluates to a value, and
+//! `binding_source` a top-level form that binds a name to one, so a session's
+//! definitions can be saved as ordinary Consair source and restored by
+//! evaluating it.
+//!
+//! Data is quoted where its printed form reads back as the same value, and
+//! rebuilt with `list`, `cons` and `vector` where it does not. Lambdas are
+//! written as `lambda` forms, inside a `let` that binds the variables they
+//! captured from enclosing scopes. Each restored closure gets its own copy of
+//! those variables, so closures that shared mutable state no longer do.
+//! Native functions have no source.
+
+use std::sync::Arc;
+
+use crate::interner::InternedSymbol;
+use crate::language::{
+    AtomType, LambdaCell, PrintLimits, StringType, SymbolType, Value, set_print_limits,
+};
+use crate::parser::parse;
+
+/// An expression that evaluates to `value`, or why there is none.
+pub fn value_source(value: &Value) -> Result<String, String> {
+    let previous = set_print_limits(PrintLimits::default());
+    let source = source(value, &mut Vec::new());
+    set_print_limits(previous);
+    source
+}
+
+/// A top-level form that binds `name` to `value`: `defmacro` for a macro,
+/// `define-constant` if `constant` is set, `label` for other functions and
+/// `def` for the rest.
+pub fn binding_source(name: &str, value: &Value, constant: bool) -> Result<String, String> {
+    let previous = set_print_limits(PrintLimits::default());
+    let source = match value {
+        Value::Macro(mc) if reads_back(&mc.body) => Ok(format!(
+            "(defmacro {name} {} {}{})",
+            mc.params,
+            docstring(mc.doc.as_deref()),
+            mc.body
+        )),
+        Value::Macro(_) => Err(format!("the body of macro {name} does not read back")),
+        _ => source(value, &mut Vec::new()).map(|source| match value {
+            _ if constant => format!("(define-constant {name} {source})"),
+            Value::Lambda(_) => format!("(label {name} {source})"),
+            _ => format!("(def {name} {source})"),
+        }),
+    };
+    set_print_limits(previous);
+    source.map_err(|e| format!("{name}: {e}"))
+}
+
+/// `value_source` with the print limits lifted. `open` holds the lambdas
+/// being written, to catch a closure that captures itself.
+fn source(value: &Value, open: &mut Vec<*const LambdaCell>) -> Result<String, String> {
+    match value {
+        Value::Atom(AtomType::Bool(true)) => Ok("t".to_string()),
+        Value::Atom(AtomType::Bool(false)) | Value::Nil => Ok("nil".to_string()),
+        Value::Lambda(lambda) => lambda_source(lambda, open),
+        Value::Macro(_) | Value::NativeFn(_) | Value::NativeClosure(_) => {
+            Err(format!("{value} has no source form"))
+        }
+        _ if reads_back(value) => Ok(match value {
+            Value::Atom(AtomType::Number(_) | AtomType::String(_)) => value.to_string(),
+            _ => format!("'{value}"),
+        }),
+        Value::Cons(_) => {
+            let mut elements = Vec::new();
+            let mut current = value;
+            while let Value::Cons(cell) = current {
+                elements.push(source(&cell.car, open)?);
+                current = &cell.cdr;
+            }
+            if matches!(current, Value::Nil) {
+                return Ok(format!("(list {})", elements.join(" ")));
+            }
+            let tail = source(current, open)?;
+            Ok(elements
+                .into_iter()
+                .rev()
+                .fold(tail, |tail, head| format!("(cons {head} {tail})")))
+        }
+        Value::Vector(vec) => {
+            let elements = vec
+                .elements
+                .iter()
+                .map(|element| source(element, open))
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(format!("(vector {})", elements.join(" ")))
+        }
+        _ => Err(format!("{value} does not read back as the same value")),
+    }
+}
+
+/// True if the printed form of `value` parses as an equal value.
+fn reads_back(value: &Value) -> bool {
+    parse(&value.to_string()).is_ok_and(|read| read == *value)
+}
+
+/// A `lambda` form for `lambda`, in a `let` of the variables its body uses
+/// that an enclosing scope, rather than the global one, binds.
+fn lambda_source(
+    lambda: &Arc<LambdaCell>,
+    open: &mut Vec<*const LambdaCell>,
+) -> Result<String, String> {
+    let form = format!(
+        "(lambda {} {}{})",
+        lambda.params,
+        docstring(lambda.doc.as_deref()),
+        lambda.body
+    );
+    if !reads_back(&lambda.body) {
+        return Err(format!("the body of {form} does not read back"));
+    }
+    let id = Arc::as_ptr(lambda);
+    if open.contains(&id) {
+        return Err(format!("{form} captures itself"));
+    }
+
+    let params: Vec<InternedSymbol> = lambda.params.names().collect();
+    let global = lambda.env.global();
+    let mut captured = Vec::new();
+    for name in symbols(&lambda.body) {
+        if params.contains(&name) {
+            continue;
+        }
+        let key = name.resolve();
+        if let Some(value) = lambda.env.lookup(&key)
+            && global.lookup(&key).as_ref() != Some(&value)
+        {
+            captured.push((key, value));
+        }
+    }
+    if captured.is_empty() {
+        return Ok(form);
+    }
+
+    open.push(id);
+    let bindings = captured
+        .iter()
+        .map(|(name, value)| source(value, open).map(|source| format!("({name} {source})")))
+        .collect::<Result<Vec<_>, _>>();
+    open.pop();
+    Ok(format!("(let ({}) {form})", bindings?.join(" ")))
+}
+
+/// A docstring as written before a body, with the space after it.
+fn docstring(doc: Option<&str>) -> String {
+    doc.map_or(String::new(), |doc| {
+        let doc = Value::Atom(AtomType::String(StringType::Basic(doc.to_string())));
+        format!("{doc} ")
+    })
+}
+
+/// The symbols in `body` that are not inside a quote, each once, in the
+/// order they first appear.
+fn symbols(body: &Value) -> Vec<InternedSymbol> {
+    let mut found = Vec::new();
+    let mut pending = vec![body];
+    while let Some(value) = pending.pop() {
+        match value {
+            Value::Atom(AtomType::Symbol(SymbolType::Symbol(name))) if !found.contains(name) => {
+                found.push(*name);
+            }
+            Value::Cons(cell) => {
+                if let Value::Atom(AtomType::Symbol(SymbolType::Symbol(head))) = &cell.car
+                    && head.resolve() == "quote"
+                {
+                    continue;
+                }
+                pending.push(&cell.cdr);
+                pending.push(&cell.car);
+            }
+            _ => {}
+        }
+    }
+    found
+}
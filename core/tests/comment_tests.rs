@@ -0,0 +1,109 @@
+use consair::error::codes;
+use consair::{ParseErrorKind, parse_all, parse_all_with_spans};
+
+fn printed(source: &str) -> Vec<String> {
+    parse_all(source)
+        .unwrap()
+        .iter()
+        .map(ToString::to_string)
+        .collect()
+}
+
+// ============================================================================
+// Block Comments
+// ============================================================================
+
+#[test]
+fn test_block_comment_between_forms() {
+    assert_eq!(printed("(a) #| skip\n (b) |# (c)"), ["(a)", "(c)"]);
+    assert_eq!(printed("#| leading |# 1"), ["1"]);
+    assert_eq!(printed("(a #| inside |# b)"), ["(a b)"]);
+    assert!(parse_all("#| only a comment |#").unwrap().is_empty());
+}
+
+#[test]
+fn test_block_comments_nest() {
+    let source = "#| outer #| inner |# still outer |# (kept)";
+    assert_eq!(printed(source), ["(kept)"]);
+    assert_eq!(printed("(a #|#||#|# b)"), ["(a b)"]);
+}
+
+#[test]
+fn test_block_comment_contents_are_not_read() {
+    assert_eq!(printed("#| \" ( ; #\\x <<|# 1"), ["1"]);
+}
+
+#[test]
+fn test_unterminated_block_comment() {
+    let err = parse_all("(a)\n  #| open #| nested |#").unwrap_err();
+    assert_eq!(err.kind, ParseErrorKind::UnclosedDelimiter);
+    assert_eq!(err.code(), codes::UNCLOSED_DELIM);
+    assert!(
+        err.message
+            .contains("Unterminated block comment starting at line 2, column 3"),
+        "{}",
+        err.message
+    );
+}
+
+// ============================================================================
+// Datum Comments
+// ============================================================================
+
+#[test]
+fn test_datum_comment_skips_one_form() {
+    assert_eq!(printed("#_(a (b c)) (d)"), ["(d)"]);
+    assert_eq!(printed("(a #_b c)"), ["(a c)"]);
+    assert_eq!(printed("(a #_(b <<1 2>> {:k 1}) c)"), ["(a c)"]);
+    assert_eq!(printed("(a b #_c)"), ["(a b)"]);
+    assert_eq!(printed("(#_a)"), ["nil"]);
+    assert_eq!(printed("'#_a b"), ["(quote b)"]);
+}
+
+#[test]
+fn test_datum_comments_stack() {
+    assert_eq!(printed("(#_ #_ a b c)"), ["(c)"]);
+    assert_eq!(printed("#_ ; comment\n a b"), ["b"]);
+}
+
+#[test]
+fn test_datum_comment_at_end_of_input() {
+    assert!(parse_all("#_(a b)").unwrap().is_empty());
+    assert_eq!(printed("(a) #_(b)"), ["(a)"]);
+
+    let err = parse_all("(a) #_").unwrap_err();
+    assert_eq!(err.kind, ParseErrorKind::UnexpectedEof);
+    let err = parse_all("(a #_)").unwrap_err();
+    assert_eq!(err.kind, ParseErrorKind::UnexpectedClose);
+}
+
+#[test]
+fn test_discarded_forms_do_not_extend_spans() {
+    let source = "(a) #_(b)\n(c #_d)";
+    let forms = parse_all_with_spans(source).unwrap();
+    let extents: Vec<&str> = forms
+        .iter()
+        .map(|form| &source[form.span.offset..form.end.offset])
+        .collect();
+    assert_eq!(extents, ["(a)", "(c #_d)"]);
+    assert_eq!(forms[1].spans.len(), 1);
+}
+
+// ============================================================================
+// Shebang Lines
+// ============================================================================
+
+#[test]
+fn test_leading_shebang_line_is_skipped() {
+    assert_eq!(printed("#!/usr/bin/env cons\n(a)\n(b)"), ["(a)", "(b)"]);
+    assert!(parse_all("#!/usr/bin/env cons").unwrap().is_empty());
+
+    let forms = parse_all_with_spans("#!/usr/bin/env cons\n(a)").unwrap();
+    assert_eq!((forms[0].span.line, forms[0].span.col), (2, 1));
+}
+
+#[test]
+fn test_shebang_only_at_the_start() {
+    assert!(parse_all(" #!/usr/bin/env cons\n(a)").is_err());
+    assert!(parse_all("(a)\n#!/usr/bin/env cons").is_err());
+}
@@ -0,0 +1,12 @@
+//! Helpers shared by the reader tests
+
+use consair::{Value, parse};
+
+/// Parse, print and parse again, checking the value survives the round trip
+pub fn round_trip(source: &str) -> Value {
+    let value = parse(source).unwrap();
+    let reparsed =
+        parse(&value.to_string()).unwrap_or_else(|e| panic!("{value} does not parse back: {e}"));
+    assert_eq!(reparsed, value, "round trip of {source}");
+    value
+}
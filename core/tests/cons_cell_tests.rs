@@ -0,0 +1,91 @@
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+
+use consair::language::{AtomType, LambdaCell, ParamSpec, cons};
+use consair::numeric::NumericType;
+use consair::{Environment, Value, parse};
+
+const LONG: i64 = 1_000_000;
+
+fn int(n: i64) -> Value {
+    Value::Atom(AtomType::Number(NumericType::Int(n)))
+}
+
+/// `(0 1 … n-1)` ending in `tail`
+fn list_onto(n: i64, tail: Value) -> Value {
+    (0..n).rev().fold(tail, |rest, i| cons(int(i), rest))
+}
+
+fn hash_of(value: &Value) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[test]
+fn test_long_lists_compare_without_overflow() {
+    let a = list_onto(LONG, Value::Nil);
+    let b = list_onto(LONG, Value::Nil);
+    assert_eq!(a, b);
+    assert_ne!(a, list_onto(LONG, int(0)));
+    assert_ne!(a, list_onto(LONG - 1, Value::Nil));
+    assert_eq!(hash_of(&a), hash_of(&b));
+}
+
+#[test]
+fn test_equality_matches_structure() {
+    let parsed = |source: &str| parse(source).unwrap();
+    assert_eq!(parsed("(1 (2 3) <<4>>)"), parsed("(1 (2 3) <<4>>)"));
+    assert_ne!(parsed("(1 (2 3))"), parsed("(1 (2 4))"));
+    assert_eq!(cons(int(1), int(2)), cons(int(1), int(2)));
+    assert_ne!(cons(int(1), int(2)), parsed("(1 2)"));
+    assert_eq!(hash_of(&parsed("(a b)")), hash_of(&parsed("(a b)")));
+}
+
+#[test]
+fn test_shared_tails_compare_by_identity() {
+    let tail = list_onto(LONG, Value::Nil);
+    let a = cons(int(-1), tail.clone());
+    let b = cons(int(-1), tail);
+    assert_eq!(a, b);
+}
+
+#[test]
+fn test_self_referencing_closure_terminates() {
+    // A closure stored in its own environment is the one cycle values can form
+    let env = Environment::new();
+    let params = ParamSpec::parse(&parse("(n)").unwrap(), "lambda").unwrap();
+    let lambda = Value::Lambda(Arc::new(LambdaCell {
+        params,
+        body: parse("(f n)").unwrap(),
+        env: env.clone(),
+        doc: None,
+    }));
+    env.define("f".to_string(), lambda.clone());
+
+    let looked_up = env.lookup("f").unwrap();
+    assert_eq!(looked_up, lambda);
+    assert_eq!(lambda.to_string(), "<lambda (n)>");
+    assert!(format!("{lambda:?}").contains("<environment>"));
+    assert_eq!(hash_of(&looked_up), hash_of(&lambda));
+}
+
+#[test]
+fn test_dropping_deep_lists() {
+    const DEEP: usize = 2_000_000;
+    let long = list_onto(DEEP as i64, Value::Nil);
+    drop(long);
+
+    // Nested through the car: ((((…))))
+    let nested = (0..DEEP).fold(Value::Nil, |inner, _| cons(inner, Value::Nil));
+    drop(nested);
+}
+
+#[test]
+fn test_dropping_a_list_keeps_shared_tails() {
+    let tail = list_onto(LONG, Value::Nil);
+    let head = cons(int(-1), tail.clone());
+    drop(head);
+    assert_eq!(tail, list_onto(LONG, Value::Nil));
+}
@@ -0,0 +1,58 @@
+//! Reuse of dropped cons cells, with the `cons-freelist` feature
+#![cfg(feature = "cons-freelist")]
+
+use std::sync::Arc;
+
+use consair::{Value, cons, parse};
+
+fn list(n: i64) -> Value {
+    (0..n).rev().fold(Value::Nil, |tail, i| {
+        cons(parse(&i.to_string()).unwrap(), tail)
+    })
+}
+
+/// The addresses of the cells of a list
+fn cells(list: &Value) -> Vec<*const consair::ConsCell> {
+    let mut cells = Vec::new();
+    let mut current = list;
+    while let Value::Cons(cell) = current {
+        cells.push(Arc::as_ptr(cell));
+        current = &cell.cdr;
+    }
+    cells
+}
+
+#[test]
+fn test_dropped_cells_are_reused() {
+    let first = list(100);
+    let old = cells(&first);
+    drop(first);
+
+    let second = list(100);
+    assert_eq!(second, parse(&format!("{}", list(100))).unwrap());
+    // Every cell but the head, which is freed as the list is dropped, is
+    // available again
+    let reused = cells(&second).iter().filter(|c| old.contains(c)).count();
+    assert!(reused >= 99, "{reused} cells reused");
+}
+
+#[test]
+fn test_shared_cells_are_kept() {
+    let whole = list(10);
+    let Value::Cons(cell) = &whole else { panic!() };
+    let tail = cell.cdr.clone();
+    drop(whole);
+
+    // Building new lists does not disturb the tail that outlived its head
+    let _other = list(50);
+    assert_eq!(tail.to_string(), "(1 2 3 4 5 6 7 8 9)");
+}
+
+#[test]
+fn test_cells_move_between_threads() {
+    let built = std::thread::spawn(|| list(1000)).join().unwrap();
+    assert_eq!(consair::count(&built), Some(1000));
+    // Dropped here, so this thread's freelist takes the cells
+    drop(built);
+    assert_eq!(list(3).to_string(), "(0 1 2)");
+}
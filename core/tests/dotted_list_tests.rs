@@ -0,0 +1,86 @@
+mod common;
+
+use common::round_trip;
+use consair::language::AtomType;
+use consair::{NumericType, ParseErrorKind, Value, cons, parse};
+
+fn int(n: i64) -> Value {
+    Value::Atom(AtomType::Number(NumericType::Int(n)))
+}
+
+#[test]
+fn test_dotted_pair() {
+    let value = round_trip("(1 . 2)");
+    assert_eq!(value, cons(int(1), int(2)));
+    assert_eq!(value.to_string(), "(1 . 2)");
+}
+
+#[test]
+fn test_improper_list() {
+    let value = round_trip("(1 2 . 3)");
+    assert_eq!(value, cons(int(1), cons(int(2), int(3))));
+    assert_eq!(value.to_string(), "(1 2 . 3)");
+}
+
+#[test]
+fn test_dot_before_a_list_makes_a_proper_list() {
+    assert_eq!(parse("(1 . (2 3))").unwrap(), parse("(1 2 3)").unwrap());
+    assert_eq!(parse("(1 . nil)").unwrap(), parse("(1)").unwrap());
+}
+
+#[test]
+fn test_quoted_and_nested_dotted_pairs() {
+    let value = round_trip("'(a . b)");
+    assert_eq!(value.to_string(), "(quote (a . b))");
+    let value = round_trip("((a . 1) (b . <<2>>) ((c . d) . e))");
+    assert_eq!(value.to_string(), "((a . 1) (b . <<2>>) ((c . d) . e))");
+    round_trip("(\"s\" . #\\c)");
+}
+
+#[test]
+fn test_displayed_improper_lists_read_back() {
+    let built = cons(int(1), cons(Value::Nil, int(3)));
+    assert_eq!(built.to_string(), "(1 nil . 3)");
+    assert_eq!(parse(&built.to_string()).unwrap(), built);
+}
+
+#[test]
+fn test_malformed_dotted_lists() {
+    for (source, message) in [
+        ("(. 1)", "Nothing before . at line 1, column 2"),
+        ("(1 .)", "Nothing after . at line 1, column 4"),
+        (
+            "(1 . 2 3)",
+            "More than one form after . at line 1, column 8",
+        ),
+        (
+            "(1 . 2 . 3)",
+            "More than one . in a list at line 1, column 8",
+        ),
+        ("(1 . . 2)", "More than one . in a list at line 1, column 6"),
+        (
+            "(f\n  (1 . 2 3))",
+            "More than one form after . at line 2, column 10",
+        ),
+        (".", "Unexpected . at line 1, column 1"),
+        ("<<1 . 2>>", "Unexpected . at line 1, column 5"),
+        ("{:a . 1}", "Unexpected . at line 1, column 5"),
+    ] {
+        let err = parse(source).unwrap_err();
+        assert_eq!(err.kind, ParseErrorKind::InvalidDottedList, "{source}");
+        assert_eq!(err.message, message, "{source}");
+        assert_eq!(err.code(), "P007_INVALID_DOTTED_LIST");
+    }
+}
+
+#[test]
+fn test_unfinished_dotted_lists_are_incomplete() {
+    assert_eq!(
+        parse("(1 . 2").unwrap_err().kind,
+        ParseErrorKind::UnclosedDelimiter
+    );
+    assert_eq!(
+        parse("(1 .").unwrap_err().kind,
+        ParseErrorKind::UnexpectedEof
+    );
+}
@@ -0,0 +1,166 @@
+use consair::{Environment, Value, parse};
+
+fn int(n: i64) -> Value {
+    parse(&n.to_string()).unwrap()
+}
+
+#[test]
+fn test_restore_rolls_back_definitions() {
+    let env = Environment::new();
+    env.define("kept".to_string(), int(1));
+    env.define("changed".to_string(), int(2));
+
+    let snapshot = env.snapshot();
+    env.define("added".to_string(), int(3));
+    env.define("changed".to_string(), int(20));
+    env.define("changed".to_string(), int(200));
+    env.set("kept", int(10));
+    env.define_constant("limit".to_string(), int(4));
+    env.restore(snapshot);
+
+    assert_eq!(env.lookup("kept"), Some(int(1)));
+    assert_eq!(env.lookup("changed"), Some(int(2)));
+    assert_eq!(env.lookup("added"), None);
+    assert_eq!(env.lookup("limit"), None);
+    assert!(!env.is_constant("limit"));
+}
+
+#[test]
+fn test_nested_snapshots() {
+    let env = Environment::new();
+    env.define("x".to_string(), int(1));
+
+    let outer = env.snapshot();
+    env.define("x".to_string(), int(2));
+    let inner = env.snapshot();
+    env.define("x".to_string(), int(3));
+    env.define("y".to_string(), int(3));
+    env.restore(inner);
+    assert_eq!(env.lookup("x"), Some(int(2)));
+    assert_eq!(env.lookup("y"), None);
+
+    env.restore(outer);
+    assert_eq!(env.lookup("x"), Some(int(1)));
+}
+
+#[test]
+fn test_snapshot_covers_one_scope() {
+    let global = Environment::new();
+    global.define("x".to_string(), int(1));
+    let child = global.extend(&[], &[]);
+
+    let snapshot = child.snapshot();
+    child.define("y".to_string(), int(2));
+    // `set` on a parent binding changes the parent, which the snapshot does not cover
+    child.set("x", int(10));
+    child.restore(snapshot);
+
+    assert_eq!(child.lookup("y"), None);
+    assert_eq!(global.lookup("x"), Some(int(10)));
+}
+
+#[test]
+fn test_dropped_snapshot_keeps_changes() {
+    let env = Environment::new();
+    env.define("x".to_string(), int(1));
+
+    let outer = env.snapshot();
+    let dropped = env.snapshot();
+    env.define("x".to_string(), int(2));
+    drop(dropped);
+    env.define("y".to_string(), int(3));
+    assert_eq!(env.lookup("x"), Some(int(2)));
+
+    // The outer snapshot still rolls back everything after it
+    env.restore(outer);
+    assert_eq!(env.lookup("x"), Some(int(1)));
+    assert_eq!(env.lookup("y"), None);
+
+    // With none outstanding, later snapshots start afresh
+    let snapshot = env.snapshot();
+    env.define("z".to_string(), int(4));
+    env.restore(snapshot);
+    assert_eq!(env.lookup("z"), None);
+    assert_eq!(env.lookup("x"), Some(int(1)));
+}
+
+#[test]
+fn test_scopes_run_from_inner_to_global() {
+    let global = Environment::new();
+    let child = global.extend(&[], &[]);
+    let grandchild = child.extend(&[], &[]);
+    grandchild.define("x".to_string(), int(1));
+
+    let scopes = grandchild.scopes();
+    assert_eq!(scopes.len(), 3);
+    assert_eq!(scopes[0].iter_local().count(), 1);
+    assert_eq!(scopes[2].depth(), 0);
+    assert_eq!(global.scopes().len(), 1);
+}
+
+#[test]
+fn test_freeze_covers_every_scope_until_dropped() {
+    let global = Environment::new();
+    let child = global.extend(&[], &[]);
+    assert!(!global.is_frozen());
+
+    let outer = child.freeze();
+    let inner = global.freeze();
+    assert!(global.is_frozen());
+    assert!(child.extend(&[], &[]).is_frozen());
+    // Other global environments are not affected
+    assert!(!Environment::new().is_frozen());
+
+    drop(inner);
+    assert!(global.is_frozen());
+    drop(outer);
+    assert!(!global.is_frozen());
+    assert!(!child.is_frozen());
+}
+
+#[test]
+#[should_panic(expected = "snapshot restored into a different environment")]
+fn test_restore_into_other_environment_panics() {
+    let a = Environment::new();
+    let b = Environment::new();
+    b.restore(a.snapshot());
+}
+
+#[test]
+fn test_names_lists_shadowed_names_once() {
+    let global = Environment::new();
+    global.define("x".to_string(), int(1));
+    global.define("y".to_string(), int(2));
+    let child = global.extend(&[], &[]);
+    child.define("x".to_string(), int(10));
+    child.define("z".to_string(), int(3));
+
+    assert_eq!(child.names(), ["x", "y", "z"]);
+    assert_eq!(global.names(), ["x", "y"]);
+}
+
+#[test]
+fn test_iter_local_covers_one_scope() {
+    let global = Environment::new();
+    global.define("b".to_string(), int(1));
+    let child = global.extend(&[], &[]);
+    child.define("a".to_string(), int(2));
+    child.define("c".to_string(), int(3));
+
+    let local: Vec<_> = child.iter_local().collect();
+    assert_eq!(
+        local,
+        [("a".to_string(), int(2)), ("c".to_string(), int(3))]
+    );
+}
+
+#[test]
+fn test_depth_counts_parent_scopes() {
+    let global = Environment::new();
+    let child = global.extend(&[], &[]);
+    let grandchild = child.extend(&[], &[]);
+
+    assert_eq!(global.depth(), 0);
+    assert_eq!(child.depth(), 1);
+    assert_eq!(grandchild.depth(), 2);
+}
@@ -0,0 +1,67 @@
+use std::collections::HashSet;
+
+use consair::error::codes;
+use consair::interner::InternedSymbol;
+use consair::{Arity, EvalError, Value, parse};
+
+#[test]
+fn test_codes_are_unique() {
+    let unique: HashSet<_> = codes::ALL.iter().collect();
+    assert_eq!(unique.len(), codes::ALL.len());
+
+    let numbers: HashSet<_> = codes::ALL.iter().map(|code| &code[..4]).collect();
+    assert_eq!(numbers.len(), codes::ALL.len(), "codes share a number");
+}
+
+#[test]
+fn test_eval_error_codes() {
+    let unbound = EvalError::UnboundSymbol(InternedSymbol::new("x"));
+    assert_eq!(unbound.code(), "E001_UNBOUND_SYMBOL");
+    assert_eq!(
+        unbound.to_string(),
+        "Unbound symbol: x [E001_UNBOUND_SYMBOL]"
+    );
+
+    let arity = EvalError::ArityMismatch {
+        callee: "f".to_string(),
+        expected: Arity::Exact(2),
+        got: 1,
+    };
+    assert_eq!(arity.code(), "E010_ARITY");
+
+    let type_error = EvalError::TypeError {
+        callee: Some("car".to_string()),
+        expected: "cons cell".to_string(),
+        found: Value::Nil,
+    };
+    assert_eq!(type_error.code(), "E020_TYPE");
+
+    let parse_error = EvalError::from(parse("(a").unwrap_err());
+    assert_eq!(parse_error.code(), "P001_UNCLOSED_DELIM");
+    assert!(parse_error.to_string().ends_with(" [P001_UNCLOSED_DELIM]"));
+}
+
+#[test]
+fn test_custom_errors_print_without_code() {
+    let err = EvalError::from("something went wrong");
+    assert_eq!(err.code(), "E000_CUSTOM");
+    assert_eq!(err.to_string(), "something went wrong");
+}
+
+#[test]
+fn test_message_omits_code() {
+    let type_error = EvalError::TypeError {
+        callee: Some("car".to_string()),
+        expected: "cons cell".to_string(),
+        found: Value::Nil,
+    };
+    assert_eq!(type_error.message(), "car: expected cons cell, got nil");
+    assert_eq!(
+        type_error.to_string(),
+        format!("{} [E020_TYPE]", type_error.message())
+    );
+
+    let parse_error = EvalError::from(parse("(a").unwrap_err());
+    assert!(!parse_error.message().contains("P001"));
+    assert_eq!(EvalError::from("plain").message(), "plain");
+}
@@ -0,0 +1,54 @@
+use consair::language::AtomType;
+use consair::{NumericType, Value, parse};
+
+fn float(x: f64) -> Value {
+    Value::Atom(AtomType::Number(NumericType::Float(x)))
+}
+
+/// The float in `value`, which must be one
+fn as_float(value: &Value) -> f64 {
+    match value {
+        Value::Atom(AtomType::Number(NumericType::Float(x))) => *x,
+        other => panic!("expected a float, got {other:?}"),
+    }
+}
+
+/// Print `x`, check the text, and check it reads back as the same float
+fn round_trip(x: f64, printed: &str) {
+    assert_eq!(float(x).to_string(), printed);
+    let read = as_float(&parse(printed).unwrap());
+    assert_eq!(read.to_bits(), x.to_bits(), "{printed} read back as {read}");
+}
+
+#[test]
+fn test_whole_floats_keep_a_decimal_point() {
+    round_trip(2.0, "2.0");
+    round_trip(-7.0, "-7.0");
+    round_trip(0.0, "0.0");
+    round_trip(-0.0, "-0.0");
+    round_trip(1e20, "100000000000000000000.0");
+    assert_eq!(parse("2.0").unwrap().to_string(), "2.0");
+    assert_eq!(parse("2").unwrap().to_string(), "2");
+}
+
+#[test]
+fn test_fractional_floats_print_in_full() {
+    round_trip(0.1, "0.1");
+    round_trip(2.5, "2.5");
+    round_trip(1.0 / 3.0, "0.3333333333333333");
+    round_trip(10.0 / 3.0, "3.3333333333333335");
+}
+
+#[test]
+fn test_special_values_read_and_print() {
+    round_trip(f64::INFINITY, "##Inf");
+    round_trip(f64::NEG_INFINITY, "##-Inf");
+    assert!(as_float(&parse("##NaN").unwrap()).is_nan());
+    assert_eq!(float(f64::NAN).to_string(), "##NaN");
+    assert_eq!(
+        parse("(1.0 ##Inf ##NaN)").unwrap().to_string(),
+        "(1.0 ##Inf ##NaN)"
+    );
+    let err = parse("##Infinity").unwrap_err();
+    assert!(err.to_string().contains("Unknown symbolic value"), "{err}");
+}
@@ -0,0 +1,55 @@
+mod common;
+
+use common::round_trip;
+use consair::{Value, parse};
+
+#[test]
+fn test_empty_map() {
+    let value = round_trip("{}");
+    assert!(matches!(&value, Value::Map(map) if map.entries.is_empty()));
+    assert_eq!(value.to_string(), "{}");
+}
+
+#[test]
+fn test_map_with_keyword_keys() {
+    let value = round_trip("{:a 1 :b \"two\"}");
+    let Value::Map(map) = &value else {
+        panic!("expected a map, got {value}");
+    };
+    assert_eq!(map.entries.len(), 2);
+    assert_eq!(map.entries[&parse(":a").unwrap()], parse("1").unwrap());
+    assert_eq!(round_trip("{:a 1}").to_string(), "{:a 1}");
+}
+
+#[test]
+fn test_nested_maps() {
+    let value = round_trip("{:outer {:inner {}} :list (1 {:x <<2>>})}");
+    assert_eq!(
+        value,
+        parse("{:list (1 {:x <<2>>}) :outer {:inner {}}}").unwrap()
+    );
+}
+
+#[test]
+fn test_maps_inside_lists() {
+    let value = round_trip("(f {:a 1} '{b 2} {})");
+    assert_eq!(value.to_string(), "(f {:a 1} (quote {b 2}) {})");
+}
+
+#[test]
+fn test_map_literal_errors() {
+    let code = |source: &str| parse(source).unwrap_err().code();
+    assert_eq!(code("(f\n  {:a 1 :b})"), "P005_INVALID_MAP_LITERAL");
+    assert_eq!(code("{:a 1 :a 2}"), "P005_INVALID_MAP_LITERAL");
+    assert_eq!(code("{:a 1"), "P001_UNCLOSED_DELIM");
+    assert_eq!(code("(a })"), "P002_UNEXPECTED_CLOSE");
+
+    assert_eq!(
+        parse("(f\n  {:a 1 :b})").unwrap_err().message,
+        "Map literal starting at line 2, column 3 has 3 forms; expected key/value pairs"
+    );
+    assert_eq!(
+        parse("{:a 1 :a 2}").unwrap_err().message,
+        "Duplicate key :a in map literal starting at line 1, column 1"
+    );
+}
@@ -0,0 +1,120 @@
+use consair::language::{AtomType, SymbolType};
+use consair::{NumericType, Value, parse};
+
+fn int(n: i64) -> Value {
+    Value::Atom(AtomType::Number(NumericType::Int(n)))
+}
+
+fn float(x: f64) -> Value {
+    Value::Atom(AtomType::Number(NumericType::Float(x)))
+}
+
+fn symbol(value: &Value) -> Option<String> {
+    match value {
+        Value::Atom(AtomType::Symbol(SymbolType::Symbol(name))) => Some(name.resolve()),
+        _ => None,
+    }
+}
+
+/// The elements of the list `src` reads as
+fn elements(src: &str) -> Vec<Value> {
+    let mut items = Vec::new();
+    let mut current = parse(src).unwrap();
+    while let Value::Cons(cell) = current {
+        items.push(cell.car.clone());
+        current = cell.cdr.clone();
+    }
+    items
+}
+
+#[test]
+fn test_decimal_literals() {
+    assert_eq!(parse("42").unwrap(), int(42));
+    assert_eq!(parse("-17").unwrap(), int(-17));
+    assert_eq!(parse("0").unwrap(), int(0));
+    assert_eq!(parse("3/4").unwrap().to_string(), "3/4");
+    assert_eq!(parse("-3/4").unwrap().to_string(), "-3/4");
+    assert_eq!(parse("2.5").unwrap(), float(2.5));
+    assert_eq!(parse("-.5").unwrap(), float(-0.5));
+    assert_eq!(
+        parse("123456789012345678901234567890").unwrap().to_string(),
+        "123456789012345678901234567890"
+    );
+}
+
+#[test]
+fn test_scientific_notation() {
+    assert_eq!(parse("1e10").unwrap(), float(1e10));
+    assert_eq!(parse("2.5e-3").unwrap(), float(2.5e-3));
+    assert_eq!(parse("1.5E+2").unwrap(), float(150.0));
+    assert_eq!(parse("-6.02e23").unwrap(), float(-6.02e23));
+}
+
+#[test]
+fn test_radix_literals() {
+    assert_eq!(parse("0x1F").unwrap(), int(31));
+    assert_eq!(parse("0xff").unwrap(), int(255));
+    assert_eq!(parse("-0x10").unwrap(), int(-16));
+    assert_eq!(parse("0o17").unwrap(), int(15));
+    assert_eq!(parse("0b101").unwrap(), int(5));
+    assert_eq!(parse("-0b1").unwrap(), int(-1));
+    assert_eq!(parse("-0x8000000000000000").unwrap(), int(i64::MIN));
+    assert_eq!(
+        parse("0x10000000000000000").unwrap().to_string(),
+        "18446744073709551616"
+    );
+}
+
+#[test]
+fn test_malformed_numbers_are_errors() {
+    for (src, message) in [
+        ("1.2.3", "Malformed number 1.2.3"),
+        ("2vec", "Malformed number 2vec"),
+        ("1e", "Malformed number 1e"),
+        ("-1-2", "Malformed number -1-2"),
+        ("0xGG", "Malformed hexadecimal number 0xGG"),
+        ("0x", "Malformed hexadecimal number 0x"),
+        ("0o8", "Malformed octal number 0o8"),
+        ("0b102", "Malformed binary number 0b102"),
+    ] {
+        let err = parse(src).unwrap_err().to_string();
+        assert!(err.contains(message), "{src}: {err}");
+        assert!(err.contains("line 1, column 1"), "{src}: {err}");
+    }
+    // The position is where the number starts
+    let err = parse("(+ 1\n   1.2.3)").unwrap_err().to_string();
+    assert!(err.contains("line 2, column 4"), "{err}");
+}
+
+#[test]
+fn test_minus_is_a_symbol_unless_a_number_follows() {
+    assert_eq!(symbol(&parse("-").unwrap()).as_deref(), Some("-"));
+    assert_eq!(symbol(&parse("-foo").unwrap()).as_deref(), Some("-foo"));
+    assert_eq!(symbol(&parse("-x1").unwrap()).as_deref(), Some("-x1"));
+
+    // (- 1) is a call of - on 1
+    let items = elements("(- 1)");
+    assert_eq!(symbol(&items[0]).as_deref(), Some("-"));
+    assert_eq!(items[1], int(1));
+    // (-1) is a list holding -1
+    assert_eq!(elements("(-1)"), vec![int(-1)]);
+    // (- -1 -2) is a call of - on two negative numbers
+    let items = elements("(- -1 -2)");
+    assert_eq!(symbol(&items[0]).as_deref(), Some("-"));
+    assert_eq!(items[1..], [int(-1), int(-2)]);
+}
+
+#[test]
+fn test_symbols_with_digits() {
+    for name in ["vec2", "x1", "a-1", "+1x", "*2*"] {
+        assert_eq!(symbol(&parse(name).unwrap()).as_deref(), Some(name));
+    }
+}
+
+#[test]
+fn test_numbers_end_at_delimiters() {
+    assert_eq!(elements("(1(2)3)").len(), 3);
+    assert_eq!(parse("<<1 2>>").unwrap().to_string(), "<<1 2>>");
+    assert_eq!(parse("{:a 0x10}").unwrap().to_string(), "{:a 16}");
+    assert_eq!(elements("(1;comment\n2)"), vec![int(1), int(2)]);
+}
@@ -0,0 +1,119 @@
+use consair::error::codes;
+use consair::{ParseErrorKind, parse, parse_all, parse_all_with_spans, parse_iter};
+
+fn printed(source: &str) -> Vec<String> {
+    parse_all(source)
+        .unwrap()
+        .iter()
+        .map(ToString::to_string)
+        .collect()
+}
+
+#[test]
+fn test_parse_all_returns_every_form() {
+    assert_eq!(
+        printed("(a b) 42 'c \"d\""),
+        ["(a b)", "42", "(quote c)", "\"d\""]
+    );
+    // parse still reads only the first form
+    assert_eq!(parse("(a b) 42").unwrap().to_string(), "(a b)");
+}
+
+#[test]
+fn test_parse_all_empty_input() {
+    assert!(parse_all("").unwrap().is_empty());
+    assert!(parse_all("  \n\t").unwrap().is_empty());
+    assert!(parse_all("; only a comment").unwrap().is_empty());
+}
+
+#[test]
+fn test_parse_all_ending_in_comment() {
+    assert_eq!(printed("(a)\n; trailing comment"), ["(a)"]);
+    assert_eq!(printed("(a) ; no newline after"), ["(a)"]);
+}
+
+#[test]
+fn test_parse_all_strings_with_unbalanced_parens() {
+    let source = r#"(f "((" "a \")" #\( ";") (g)"#;
+    let forms = parse_all(source).unwrap();
+    assert_eq!(forms.len(), 2);
+    assert_eq!(forms[1].to_string(), "(g)");
+}
+
+#[test]
+fn test_parse_all_trailing_garbage() {
+    let err = parse_all("(a b)\n  )").unwrap_err();
+    assert_eq!(err.kind, ParseErrorKind::UnexpectedClose);
+    assert!(err.message.contains("line 2, column 3"), "{}", err.message);
+
+    let err = parse_all("(a) (b").unwrap_err();
+    assert_eq!(err.code(), codes::UNCLOSED_DELIM);
+}
+
+#[test]
+fn test_parse_all_with_spans_records_form_extents() {
+    let source = "(a b) ; comment\n  'c\n\"s\"";
+    let forms = parse_all_with_spans(source).unwrap();
+    let extents: Vec<&str> = forms
+        .iter()
+        .map(|form| &source[form.span.offset..form.end.offset])
+        .collect();
+    assert_eq!(extents, ["(a b)", "'c", "\"s\""]);
+    assert_eq!((forms[1].span.line, forms[1].span.col), (2, 3));
+}
+
+#[test]
+fn test_invalid_token_belongs_to_the_form_it_starts() {
+    // The parser reads one token ahead; a bad token after a complete form
+    // must not fail that form
+    let mut lexer = consair::lexer::Lexer::new("(a) #bad");
+    let mut parser = consair::parser::Parser::new(&mut lexer);
+    assert_eq!(parser.parse_expression().unwrap().to_string(), "(a)");
+    assert!(!parser.is_at_end());
+    let err = parser.parse_expression().unwrap_err();
+    assert_eq!(err.kind, ParseErrorKind::InvalidToken);
+}
+
+#[test]
+fn test_parse_iter_yields_forms_lazily() {
+    let mut forms = parse_iter("(a) 'b <<1 2>> {:k 1} \"(\" ; done");
+    assert_eq!(forms.next().unwrap().unwrap().to_string(), "(a)");
+    assert_eq!(forms.next().unwrap().unwrap().to_string(), "(quote b)");
+    assert_eq!(forms.next().unwrap().unwrap().to_string(), "<<1 2>>");
+    assert_eq!(forms.next().unwrap().unwrap().to_string(), "{:k 1}");
+    assert_eq!(forms.next().unwrap().unwrap().to_string(), "\"(\"");
+    assert!(forms.next().is_none());
+}
+
+#[test]
+fn test_parse_iter_stops_after_error() {
+    let results: Vec<_> = parse_iter("(a) (b )) (c)").collect();
+    assert_eq!(results.len(), 3);
+    assert!(results[0].is_ok() && results[1].is_ok());
+    assert_eq!(
+        results[2].as_ref().unwrap_err().kind,
+        ParseErrorKind::UnexpectedClose
+    );
+}
+
+#[test]
+fn test_parse_iter_large_input() {
+    const COUNT: usize = 100_000;
+    let source: String = (0..COUNT)
+        .map(|i| format!("(item {i} '(x \"s\") <<{i}>>) ; form {i}\n"))
+        .collect();
+
+    // Each form is dropped before the next is read
+    let mut count = 0;
+    for (i, form) in parse_iter(&source).enumerate() {
+        let form = form.unwrap();
+        if i % 10_000 == 0 {
+            assert_eq!(
+                form.to_string(),
+                format!("(item {i} (quote (x \"s\")) <<{i}>>)")
+            );
+        }
+        count += 1;
+    }
+    assert_eq!(count, COUNT);
+}
@@ -0,0 +1,108 @@
+use consair::lexer::{Lexer, Span, Token};
+use consair::parser::Parser;
+use consair::{Value, parse, parse_with_spans};
+
+fn span(line: usize, col: usize, offset: usize) -> Span {
+    Span { line, col, offset }
+}
+
+#[test]
+fn test_tokens_carry_spans() {
+    let mut lexer = Lexer::new("(a\n  \"b\" ; c\n 12)");
+    let mut spans = Vec::new();
+    loop {
+        let (token, span) = lexer.next_token().unwrap();
+        if token == Token::Eof {
+            break;
+        }
+        spans.push(span);
+    }
+    assert_eq!(
+        spans,
+        vec![
+            span(1, 1, 0),
+            span(1, 2, 1),
+            span(2, 3, 5),
+            span(3, 2, 14),
+            span(3, 4, 16)
+        ]
+    );
+}
+
+#[test]
+fn test_parse_with_spans_maps_lists() {
+    let source = "(define\n  (f x)\n  '(g x))";
+    let parsed = parse_with_spans(source).unwrap();
+    assert_eq!(parsed.value, parse(source).unwrap());
+    assert_eq!(parsed.span, span(1, 1, 0));
+    assert_eq!(parsed.spans.get(&parsed.value), Some(span(1, 1, 0)));
+
+    let items: Vec<Value> = list_items(&parsed.value);
+    assert_eq!(parsed.spans.get(&items[1]), Some(span(2, 3, 10)));
+    // 'x is (quote x), located at the quote
+    assert_eq!(parsed.spans.get(&items[2]), Some(span(3, 3, 18)));
+    // Atoms and lists built elsewhere have no span
+    assert_eq!(parsed.spans.get(&items[0]), None);
+    assert_eq!(parsed.spans.get(&parse("(f x)").unwrap()), None);
+}
+
+#[test]
+fn test_parse_errors_report_locations() {
+    let source = "(a)\n\n  (b (c)\n";
+    let mut lexer = Lexer::new(source);
+    let mut parser = Parser::new(&mut lexer);
+    parser.parse_expression().unwrap();
+    assert_eq!(
+        parser.parse_expression().unwrap_err().message,
+        "Unclosed parenthesis starting at line 3, column 3"
+    );
+
+    assert_eq!(parse("(a))").map(|_| ()), Ok(()));
+    assert_eq!(
+        parse(")").unwrap_err().message,
+        "Unexpected ) at line 1, column 1"
+    );
+    assert_eq!(
+        parse("(a\n \"b)").unwrap_err().message,
+        "Unterminated string at line 2, column 2"
+    );
+}
+
+#[test]
+fn test_parse_error_codes() {
+    let code = |source: &str| parse(source).unwrap_err().code();
+    assert_eq!(code("(a (b)"), "P001_UNCLOSED_DELIM");
+    assert_eq!(code("<<1 2"), "P001_UNCLOSED_DELIM");
+    assert_eq!(code("\"abc"), "P001_UNCLOSED_DELIM");
+    assert_eq!(code(")"), "P002_UNEXPECTED_CLOSE");
+    assert_eq!(code(">>"), "P002_UNEXPECTED_CLOSE");
+    assert_eq!(code(""), "P003_UNEXPECTED_EOF");
+    assert_eq!(code("\"\\q\""), "P004_INVALID_TOKEN");
+
+    let err = parse(")").unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Unexpected ) at line 1, column 1 [P002_UNEXPECTED_CLOSE]"
+    );
+}
+
+#[test]
+fn test_parser_reads_successive_forms() {
+    let mut lexer = Lexer::new("1 (2)\n  three");
+    let mut parser = Parser::new(&mut lexer);
+    let mut starts = Vec::new();
+    while !parser.is_at_end() {
+        starts.push(parser.parse_spanned_expression().unwrap().span);
+    }
+    assert_eq!(starts, vec![span(1, 1, 0), span(1, 3, 2), span(2, 3, 8)]);
+}
+
+fn list_items(value: &Value) -> Vec<Value> {
+    let mut items = Vec::new();
+    let mut current = value;
+    while let Value::Cons(cell) = current {
+        items.push(cell.car.clone());
+        current = &cell.cdr;
+    }
+    items
+}
@@ -0,0 +1,83 @@
+use consair::language::{PrintLimits, set_print_limits};
+use consair::{cons, parse, pretty_print};
+
+fn pretty(source: &str, width: usize) -> String {
+    pretty_print(&parse(source).unwrap(), width)
+}
+
+const NESTED: &str = "(1 (2 3 (4 5 6 \"a\\nb\" (7 8 9 10 11 12 13 14 15 16 17 18 19 20)) \
+                      21 22 23 24 25 26 27) 28)";
+
+#[test]
+fn test_nested_structure_golden() {
+    let expected = "\
+(1
+ (2 3
+  (4 5 6 \"a\\nb\"
+   (7 8 9 10 11 12
+    13 14 15 16 17
+    18 19 20))
+  21 22 23 24 25 26
+  27)
+ 28)";
+    assert_eq!(pretty(NESTED, 20), expected);
+}
+
+#[test]
+fn test_width_changes_wrapping() {
+    let narrow = pretty(NESTED, 20);
+    let wide = pretty(NESTED, 60);
+    assert!(wide.lines().count() < narrow.lines().count());
+    assert!(wide.lines().all(|line| line.chars().count() <= 60));
+    assert!(narrow.lines().all(|line| line.chars().count() <= 20));
+
+    let one_line = parse(NESTED).unwrap().to_string();
+    assert_eq!(pretty(NESTED, 200), one_line);
+}
+
+#[test]
+fn test_short_forms_stay_on_one_line() {
+    assert_eq!(pretty("(a (b c) <<1 2>>)", 80), "(a (b c) <<1 2>>)");
+    assert_eq!(pretty("42", 1), "42");
+}
+
+#[test]
+fn test_collections_break_like_lists() {
+    assert_eq!(
+        pretty("<<(alpha beta) (gamma delta) (epsilon)>>", 16),
+        "<<(alpha beta)\n  (gamma delta)\n  (epsilon)>>"
+    );
+    assert_eq!(
+        pretty("{:name \"a long name\" :tags #{x}}", 20),
+        "{:name \"a long name\"\n :tags #{x}}"
+    );
+
+    let symbol = |name: &str| parse(name).unwrap();
+    let dotted = cons(
+        symbol("aaaa"),
+        cons(symbol("bbbb"), cons(symbol("cccc"), symbol("dddd"))),
+    );
+    assert_eq!(pretty_print(&dotted, 12), "(aaaa bbbb\n cccc . dddd)");
+}
+
+#[test]
+fn test_strings_are_quoted_and_escaped() {
+    assert_eq!(
+        pretty("(\"tab\\there\" \"quote\\\"d\")", 12),
+        "(\"tab\\there\"\n \"quote\\\"d\")"
+    );
+}
+
+#[test]
+fn test_print_limits_apply() {
+    let previous = set_print_limits(PrintLimits {
+        length: Some(3),
+        depth: Some(2),
+        total: None,
+    });
+    let result = pretty("(1 2 3 4 5 (a (b (c))))", 10);
+    let deep = pretty("((a (b)) (c (d)))", 8);
+    set_print_limits(previous);
+    assert_eq!(result, "(1 2 3\n … 3 more)");
+    assert_eq!(deep, "((a (…))\n (c (…)))");
+}
@@ -0,0 +1,66 @@
+//! Persistent vectors must behave exactly like the fast vectors they share
+//! an interface with.
+
+use std::sync::Arc;
+
+use consair::abstractions::{assoc, conj, count, nth};
+use consair::language::{AtomType, PersistentVector, VectorValue};
+use consair::{NumericType, Value};
+use proptest::prelude::*;
+
+#[derive(Debug, Clone)]
+enum Op {
+    Conj(i64),
+    Assoc(usize, i64),
+    Nth(usize),
+}
+
+fn int(n: i64) -> Value {
+    Value::Atom(AtomType::Number(NumericType::Int(n)))
+}
+
+fn op() -> impl Strategy<Value = Op> {
+    prop_oneof![
+        any::<i64>().prop_map(Op::Conj),
+        (0usize..64, any::<i64>()).prop_map(|(i, n)| Op::Assoc(i, n)),
+        (0usize..64).prop_map(Op::Nth),
+    ]
+}
+
+fn elements(value: &Value) -> Vec<Value> {
+    match value {
+        Value::Vector(vec) => vec.elements.clone(),
+        Value::PersistentVector(vec) => vec.elements.iter().cloned().collect(),
+        other => panic!("expected a vector, got {other}"),
+    }
+}
+
+proptest! {
+    #[test]
+    fn persistent_and_fast_vectors_agree(ops in prop::collection::vec(op(), 0..200)) {
+        let mut fast = Value::Vector(Arc::new(VectorValue { elements: Vec::new() }));
+        let mut persistent = Value::PersistentVector(Arc::new(PersistentVector {
+            elements: Default::default(),
+        }));
+        for op in ops {
+            match op {
+                Op::Conj(n) => {
+                    fast = conj(&fast, int(n)).unwrap();
+                    persistent = conj(&persistent, int(n)).unwrap();
+                }
+                Op::Assoc(i, n) => {
+                    let a = assoc(&fast, int(i as i64), int(n));
+                    let b = assoc(&persistent, int(i as i64), int(n));
+                    prop_assert_eq!(a.is_ok(), b.is_ok());
+                    if let (Ok(a), Ok(b)) = (a, b) {
+                        fast = a;
+                        persistent = b;
+                    }
+                }
+                Op::Nth(i) => prop_assert_eq!(nth(&fast, i, None), nth(&persistent, i, None)),
+            }
+            prop_assert_eq!(count(&fast), count(&persistent));
+        }
+        prop_assert_eq!(elements(&fast), elements(&persistent));
+    }
+}
@@ -0,0 +1,47 @@
+mod common;
+
+use common::round_trip;
+use consair::{Value, parse};
+
+#[test]
+fn test_empty_set() {
+    let value = round_trip("#{}");
+    assert!(matches!(&value, Value::Set(set) if set.elements.is_empty()));
+    assert_eq!(value.to_string(), "#{}");
+}
+
+#[test]
+fn test_set_elements() {
+    let value = round_trip("#{1 :two \"three\"}");
+    let Value::Set(set) = &value else {
+        panic!("expected a set, got {value}");
+    };
+    assert_eq!(set.elements.len(), 3);
+    assert!(set.elements.contains(&parse(":two").unwrap()));
+    assert_eq!(round_trip("#{1}").to_string(), "#{1}");
+}
+
+#[test]
+fn test_nested_collections_in_sets() {
+    let value = round_trip("#{#{1 2} {:a <<3>>} (4 5)}");
+    assert_eq!(value, parse("#{(4 5) {:a <<3>>} #{2 1}}").unwrap());
+}
+
+#[test]
+fn test_sets_inside_lists() {
+    let value = round_trip("(f #{1} '#{b} {:s #{}})");
+    assert_eq!(value.to_string(), "(f #{1} (quote #{b}) {:s #{}})");
+}
+
+#[test]
+fn test_set_literal_errors() {
+    let code = |source: &str| parse(source).unwrap_err().code();
+    assert_eq!(code("#{1 2 1}"), "P006_INVALID_SET_LITERAL");
+    assert_eq!(code("#{##NaN}"), "P006_INVALID_SET_LITERAL");
+    assert_eq!(code("#{1 2"), "P001_UNCLOSED_DELIM");
+
+    assert_eq!(
+        parse("(f\n  #{1 2 2})").unwrap_err().message,
+        "Duplicate element 2 in set literal starting at line 2, column 3"
+    );
+}
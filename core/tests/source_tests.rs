@@ -0,0 +1,121 @@
+use consair::language::{PrintLimits, set_print_limits};
+use consair::{
+    Environment, LambdaCell, ParamSpec, Value, binding_source, cons, parse, value_source,
+};
+use std::sync::Arc;
+
+fn source_of(text: &str) -> String {
+    value_source(&parse(text).unwrap()).unwrap()
+}
+
+fn lambda(params: &str, body: &str, env: &Environment) -> Value {
+    let params = ParamSpec::parse(&parse(params).unwrap(), "lambda").unwrap();
+    Value::Lambda(Arc::new(LambdaCell {
+        params,
+        body: parse(body).unwrap(),
+        env: env.clone(),
+        doc: None,
+    }))
+}
+
+#[test]
+fn test_data_is_quoted_where_it_reads_back() {
+    assert_eq!(source_of("42"), "42");
+    assert_eq!(source_of("\"a \\\"b\\\"\""), "\"a \\\"b\\\"\"");
+    assert_eq!(source_of("nil"), "nil");
+    assert_eq!(source_of("sym"), "'sym");
+    assert_eq!(source_of("(1 (2 \"s\") x)"), "'(1 (2 \"s\") x)");
+    assert_eq!(source_of("<<1 2>>"), "'<<1 2>>");
+}
+
+#[test]
+fn test_dotted_pairs_are_quoted_or_rebuilt() {
+    let pair = cons(parse("a").unwrap(), parse("2").unwrap());
+    assert_eq!(value_source(&pair).unwrap(), "'(a . 2)");
+    let list = cons(parse("1").unwrap(), pair);
+    assert_eq!(value_source(&list).unwrap(), "'(1 a . 2)");
+    // A tail that does not read back is rebuilt with cons
+    let env = Environment::new();
+    let pair = cons(parse("1").unwrap(), lambda("(x)", "x", &env));
+    assert_eq!(value_source(&pair).unwrap(), "(cons 1 (lambda (x) x))");
+}
+
+#[test]
+fn test_print_limits_do_not_apply() {
+    let previous = set_print_limits(PrintLimits {
+        length: Some(2),
+        depth: Some(1),
+        total: Some(5),
+    });
+    let source = source_of("(1 2 3 (4 (5)))");
+    set_print_limits(previous);
+    assert_eq!(source, "'(1 2 3 (4 (5)))");
+}
+
+#[test]
+fn test_lambdas_bind_what_they_capture() {
+    let global = Environment::new();
+    global.define("scale".to_string(), parse("10").unwrap());
+    assert_eq!(
+        value_source(&lambda("(x)", "(* x scale)", &global)).unwrap(),
+        "(lambda (x) (* x scale))"
+    );
+
+    let local = global.extend(&[], &[]);
+    local.define("step".to_string(), parse("3").unwrap());
+    local.define("scale".to_string(), parse("10").unwrap());
+    // scale has the same value globally, so only step is captured
+    assert_eq!(
+        value_source(&lambda(
+            "(x &opt (y 1))",
+            "(+ x y step scale 'step)",
+            &local
+        ))
+        .unwrap(),
+        "(let ((step 3)) (lambda (x &opt (y 1)) (+ x y step scale (quote step))))"
+    );
+}
+
+#[test]
+fn test_binding_forms() {
+    let env = Environment::new();
+    let square = lambda("(x)", "(* x x)", &env);
+    assert_eq!(
+        binding_source("square", &square, false).unwrap(),
+        "(label square (lambda (x) (* x x)))"
+    );
+    assert_eq!(
+        binding_source("xs", &parse("(1 2)").unwrap(), false).unwrap(),
+        "(def xs '(1 2))"
+    );
+    assert_eq!(
+        binding_source("limit", &parse("100").unwrap(), true).unwrap(),
+        "(define-constant limit 100)"
+    );
+}
+
+#[test]
+fn test_docstrings_are_written_before_the_body() {
+    let env = Environment::new();
+    let Value::Lambda(square) = lambda("(x)", "(* x x)", &env) else {
+        unreachable!()
+    };
+    let documented = Value::Lambda(Arc::new(LambdaCell {
+        doc: Some("Square \"x\".".to_string()),
+        ..(*square).clone()
+    }));
+    assert_eq!(
+        binding_source("square", &documented, false).unwrap(),
+        "(label square (lambda (x) \"Square \\\"x\\\".\" (* x x)))"
+    );
+}
+
+#[test]
+fn test_native_functions_have_no_source() {
+    let env = Environment::new();
+    env.register_native("twice", |args, _| Ok(args[0].clone()));
+    let native = env.lookup("twice").unwrap();
+    let err = binding_source("f", &native, false).unwrap_err();
+    assert!(err.starts_with("f: "), "{err}");
+    assert!(value_source(&parse("(1 2)").map(|l| cons(native, l)).unwrap()).is_err());
+}
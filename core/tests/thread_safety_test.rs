@@ -42,3 +42,25 @@ fn test_value_can_be_cloned_across_threads() {
     let result = handle1.join().unwrap();
     assert_eq!(result, "(quote (1 2 3))");
 }
+
+#[test]
+fn test_environment_is_shared_across_threads() {
+    let env = consair::Environment::new();
+    env.define("x".to_string(), parse("(1 2 3)").unwrap());
+    let _freeze = env.freeze();
+
+    let printed: Vec<String> = thread::scope(|scope| {
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let env = env.clone();
+                scope.spawn(move || {
+                    let local = env.extend(&[], &[]);
+                    assert!(local.is_frozen());
+                    local.lookup("x").unwrap().to_string()
+                })
+            })
+            .collect();
+        handles.into_iter().map(|h| h.join().unwrap()).collect()
+    });
+    assert_eq!(printed, vec!["(1 2 3)"; 4]);
+}